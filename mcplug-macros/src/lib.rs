@@ -0,0 +1,92 @@
+//! `mcp_client!` — a typed MCP client generated at compile time from a
+//! schema snapshot written by `mcplug snapshot`.
+//!
+//! ```ignore
+//! mcplug_macros::mcp_client!("firecrawl", snapshot = "firecrawl.json");
+//!
+//! let client = FirecrawlClient::new(mcplug::Runtime::from_config().await?);
+//! let page = client.scrape(ScrapeArgs { url: "https://example.com".into(), formats: None }).await?;
+//! ```
+//!
+//! The expansion is the same code `mcplug emit-rs` writes, so the calling
+//! crate needs `mcplug`, `serde` (with `derive`) and `serde_json` as
+//! dependencies.
+
+use mcplug::codegen::emit_rs::{emit_rust_types, to_snake_case};
+use mcplug::codegen::Snapshot;
+use proc_macro::TokenStream;
+use std::path::PathBuf;
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, Ident, LitStr, Token};
+
+/// Expand to the argument/result types and `<Server>Client` struct for a
+/// server's tools, read from a snapshot file.
+///
+/// The first argument is the server's name in the mcplug config, which the
+/// client passes to the runtime on every call. `snapshot` is resolved
+/// relative to the calling crate's `Cargo.toml`; the crate is rebuilt when
+/// the file changes.
+#[proc_macro]
+pub fn mcp_client(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as ClientInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+struct ClientInput {
+    server: LitStr,
+    snapshot: LitStr,
+}
+
+impl Parse for ClientInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let server: LitStr = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let key: Ident = input.parse()?;
+        if key != "snapshot" {
+            return Err(syn::Error::new(key.span(), "expected `snapshot = \"<path>\"`"));
+        }
+        input.parse::<Token![=]>()?;
+        let snapshot = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self { server, snapshot })
+    }
+}
+
+fn expand(input: &ClientInput) -> syn::Result<proc_macro2::TokenStream> {
+    let server = input.server.value();
+    if server.is_empty() {
+        return Err(syn::Error::new(input.server.span(), "server name is empty"));
+    }
+
+    let path = snapshot_path(&input.snapshot.value());
+    let snapshot = Snapshot::load(&path)
+        .map_err(|e| syn::Error::new(input.snapshot.span(), e.to_string()))?;
+
+    // The generated code lives in its own module so that several clients
+    // can be expanded in one scope; `include_str!` makes cargo track the
+    // snapshot file
+    let module = format!("__mcp_client_{}", to_snake_case(&server));
+    let source = format!(
+        "#[allow(clippy::all, dead_code)]\nmod {module} {{\n{code}\nconst _: &str = include_str!({path:?});\n}}\npub use {module}::*;\n",
+        code = emit_rust_types(&snapshot.tools, &server),
+        path = path.display().to_string(),
+    );
+    source
+        .parse()
+        .map_err(|e| syn::Error::new(input.snapshot.span(), format!("generated code does not parse: {e}")))
+}
+
+/// Resolve `path` against the calling crate's manifest directory.
+fn snapshot_path(path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    if path.is_absolute() {
+        return path;
+    }
+    match std::env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => PathBuf::from(dir).join(path),
+        None => path,
+    }
+}
@@ -0,0 +1,78 @@
+use mcplug::{McpTransport, MockTransport, Runtime};
+use serde_json::json;
+
+mcplug_macros::mcp_client!("weather", snapshot = "tests/fixtures/weather.json");
+
+// A second client in the same scope, under another config name
+mod staging {
+    mcplug_macros::mcp_client!("weather-staging", snapshot = "tests/fixtures/weather.json");
+}
+
+// Names with quotes and backslashes, emitted into string literals
+mod quoted {
+    mcplug_macros::mcp_client!("quoted", snapshot = "tests/fixtures/quoted.json");
+}
+
+fn runtime(name: &str, mock: MockTransport) -> Runtime {
+    let transport: Box<dyn McpTransport> = Box::new(mock);
+    Runtime::with_transports([(name.to_string(), transport)])
+}
+
+#[tokio::test]
+async fn expands_to_a_typed_client() {
+    let mock = MockTransport::new("weather")
+        .respond_json("forecast", json!({"tempC": 21.5, "summary": "Sunny"}))
+        .respond_text("list-alerts", "none");
+    let calls = mock.calls();
+    let client = WeatherClient::new(runtime("weather", mock));
+
+    let forecast = client
+        .forecast(ForecastArgs {
+            city: "Taipei".into(),
+            days: Some(3),
+        })
+        .await
+        .unwrap();
+    assert_eq!(forecast.temp_c, 21.5);
+    assert_eq!(forecast.summary, "Sunny");
+
+    let alerts = client
+        .list_alerts(ListAlertsArgs { region: None })
+        .await
+        .unwrap();
+    assert_eq!(alerts.text(), "none");
+    assert_eq!(
+        calls.all(),
+        vec![
+            ("forecast".to_string(), json!({"city": "Taipei", "days": 3})),
+            ("list-alerts".to_string(), json!({"region": null})),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn calls_the_named_server() {
+    let mock = MockTransport::new("weather").respond_text("list-alerts", "staging");
+    let client = staging::WeatherStagingClient::new(runtime("weather-staging", mock));
+    let alerts = client
+        .list_alerts(staging::ListAlertsArgs { region: None })
+        .await
+        .unwrap();
+    assert_eq!(alerts.text(), "staging");
+}
+
+#[tokio::test]
+async fn escapes_names_with_quotes_and_backslashes() {
+    let mock = MockTransport::new("quoted").respond_text("say \"hi\"\\now", "hello");
+    let calls = mock.calls();
+    let client = quoted::QuotedClient::new(runtime("quoted", mock));
+    let result = client
+        .say_hi_now(quoted::SayHiNowArgs { a_b_c: "x".into() })
+        .await
+        .unwrap();
+    assert_eq!(result.text(), "hello");
+    assert_eq!(
+        calls.all(),
+        vec![("say \"hi\"\\now".to_string(), json!({"a\"b\\c": "x"}))]
+    );
+}
@@ -26,6 +26,11 @@ pub fn parse_tool_ref(input: &str) -> Result<(String, String), McplugError> {
 /// - `true`/`false` become booleans
 /// - Valid numbers become JSON numbers
 /// - Everything else stays a string
+///
+/// A value of `@path` is replaced with the contents of that file, and `@-`
+/// with stdin, as a string. `@result:name[.field]` refers to a result saved
+/// with `--save-as` (see [`crate::results::resolve_reference`]). Quote the
+/// value (`key:'"@name"'`) to pass a literal leading `@`.
 pub fn parse_args(args: &[String]) -> Result<Value, McplugError> {
     if args.is_empty() {
         return Ok(json!({}));
@@ -50,13 +55,50 @@ pub fn parse_args(args: &[String]) -> Result<Value, McplugError> {
             )));
         }
 
-        let value = coerce_value(raw_value);
+        let value = match raw_value.strip_prefix('@') {
+            Some(reference) if reference.starts_with("result:") => {
+                crate::results::resolve_reference(&reference["result:".len()..])?
+            }
+            Some(path) if !path.is_empty() => read_file_value(key, path)?,
+            _ => coerce_value(raw_value),
+        };
         map.insert(key.to_string(), value);
     }
 
     Ok(Value::Object(map))
 }
 
+/// Parse a whole argument object given as JSON, as with `--args-json`.
+///
+/// `@path` reads the JSON from a file and `@-` from stdin. The value must be
+/// a JSON object.
+pub fn parse_args_json(input: &str) -> Result<Value, McplugError> {
+    let (source, text) = match input.strip_prefix('@') {
+        Some(path) => {
+            let text = if path == "-" {
+                std::io::read_to_string(std::io::stdin())
+            } else {
+                std::fs::read_to_string(path)
+            };
+            let text = text.map_err(|e| {
+                McplugError::ProtocolError(format!("Cannot read arguments from '{path}': {e}"))
+            })?;
+            (if path == "-" { "stdin" } else { path }, text)
+        }
+        None => ("--args-json", input.to_string()),
+    };
+
+    let value: Value = serde_json::from_str(&text).map_err(|e| {
+        McplugError::ProtocolError(format!("Invalid JSON arguments in {source}: {e}"))
+    })?;
+    if !value.is_object() {
+        return Err(McplugError::ProtocolError(format!(
+            "Invalid JSON arguments in {source}: expected an object"
+        )));
+    }
+    Ok(value)
+}
+
 /// Parse function-call syntax: `server.tool(args)` into (server, tool, args).
 ///
 /// Supports:
@@ -114,6 +156,33 @@ pub fn suggest_tool(input: &str, known_tools: &[&str]) -> Option<String> {
     }
 }
 
+/// Whether parsing these arguments reads stdin: `--args-json @-` (which
+/// `--stdin` stands for) or a `key:@-` value.
+pub fn reads_stdin(args: &[String], args_json: Option<&str>) -> bool {
+    args_json == Some("@-")
+        || args.iter().any(|arg| {
+            let value = match arg.find(':') {
+                Some(pos) => &arg[pos + 1..],
+                None => arg.split_once('=').map_or("", |(_, value)| value),
+            };
+            value == "@-"
+        })
+}
+
+/// Read an `@path` argument value from a file, or from stdin for `@-`.
+fn read_file_value(key: &str, path: &str) -> Result<Value, McplugError> {
+    let contents = if path == "-" {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(path)
+    };
+    contents.map(Value::String).map_err(|e| {
+        McplugError::ProtocolError(format!(
+            "Cannot read value for argument '{key}' from '{path}': {e}"
+        ))
+    })
+}
+
 /// Coerce a raw string value into a JSON value.
 fn coerce_value(raw: &str) -> Value {
     // Strip surrounding quotes
@@ -338,6 +407,79 @@ fn find_named_separator(input: &str) -> Option<usize> {
 mod tests {
     use super::*;
 
+    // --- @file value tests ---
+
+    #[test]
+    fn parse_args_reads_file_value() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("body.md");
+        std::fs::write(&path, "# Title\n\nline: with colon\n").unwrap();
+        let result = parse_args(&[format!("body:@{}", path.display())]).unwrap();
+        assert_eq!(result["body"], "# Title\n\nline: with colon\n");
+    }
+
+    #[test]
+    fn parse_args_file_value_is_not_coerced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("n.txt");
+        std::fs::write(&path, "42").unwrap();
+        let result = parse_args(&[format!("n=@{}", path.display())]).unwrap();
+        assert_eq!(result["n"], "42");
+    }
+
+    #[test]
+    fn parse_args_missing_file_errors() {
+        let err = parse_args(&["body:@/nonexistent/file.txt".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("Cannot read value for argument 'body'"));
+    }
+
+    #[test]
+    fn parse_args_quoted_at_is_literal() {
+        let result = parse_args(&["user:\"@hydai\"".to_string()]).unwrap();
+        assert_eq!(result["user"], "@hydai");
+    }
+
+    #[test]
+    fn parse_args_lone_at_is_literal() {
+        let result = parse_args(&["x:@".to_string()]).unwrap();
+        assert_eq!(result["x"], "@");
+    }
+
+    // --- parse_args_json tests ---
+
+    #[test]
+    fn args_json_inline_object() {
+        let v = parse_args_json(r#"{"nested": {"a": [1, 2]}, "flag": true}"#).unwrap();
+        assert_eq!(v, json!({"nested": {"a": [1, 2]}, "flag": true}));
+    }
+
+    #[test]
+    fn args_json_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("args.json");
+        std::fs::write(&path, r#"{"query": "rust"}"#).unwrap();
+        let v = parse_args_json(&format!("@{}", path.display())).unwrap();
+        assert_eq!(v, json!({"query": "rust"}));
+    }
+
+    #[test]
+    fn args_json_missing_file() {
+        let err = parse_args_json("@/nonexistent/args.json").unwrap_err();
+        assert!(err.to_string().contains("Cannot read arguments"));
+    }
+
+    #[test]
+    fn args_json_invalid() {
+        let err = parse_args_json("{not json").unwrap_err();
+        assert!(err.to_string().contains("Invalid JSON arguments"));
+    }
+
+    #[test]
+    fn args_json_rejects_non_object() {
+        let err = parse_args_json("[1, 2]").unwrap_err();
+        assert!(err.to_string().contains("expected an object"));
+    }
+
     // --- parse_tool_ref tests ---
 
     #[test]
@@ -537,6 +679,15 @@ mod tests {
         assert_eq!(result, json!({"key": null}));
     }
 
+    #[test]
+    fn reads_stdin_detects_stdin_sources() {
+        assert!(reads_stdin(&[], Some("@-")));
+        assert!(reads_stdin(&["a:1".into(), "text:@-".into()], None));
+        assert!(reads_stdin(&["text=@-".into()], None));
+        assert!(!reads_stdin(&["text:@notes.txt".into()], Some("@args.json")));
+        assert!(!reads_stdin(&["url:http://x?a=@-".into()], None));
+    }
+
     #[test]
     fn parse_args_json_object_inline() {
         let result = parse_args(&[r#"key:{"nested":"val"}"#.to_string()]).unwrap();
@@ -0,0 +1,287 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+use crate::config::env::resolve_secrets;
+use crate::config::{load_config, AuthConfig, McplugConfig};
+use crate::error::McplugError;
+use crate::oauth::{
+    cached_tokens, delete_token, discover_oauth_metadata, load_cached_token, revoke_token,
+    split_token_id, OAuthClient, TokenData,
+};
+
+/// Login state of a server, judged from its cached tokens alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenState {
+    Valid,
+    /// Expired, but a refresh token will renew it on next use.
+    Refreshable,
+    Expired,
+    LoggedOut,
+}
+
+impl TokenState {
+    pub fn of(token: Option<&TokenData>) -> Self {
+        match token {
+            None => TokenState::LoggedOut,
+            Some(token) if !token.is_expired() => TokenState::Valid,
+            Some(token) if token.refresh_token.is_some() => TokenState::Refreshable,
+            Some(_) => TokenState::Expired,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            TokenState::Valid => "valid",
+            TokenState::Refreshable => "expired (will refresh)",
+            TokenState::Expired => "expired",
+            TokenState::LoggedOut => "not logged in",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AuthRow {
+    server: String,
+    state: TokenState,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    scopes: Vec<String>,
+}
+
+impl AuthRow {
+    fn new(server: String, token: Option<&TokenData>) -> Self {
+        AuthRow {
+            server,
+            state: TokenState::of(token),
+            expires_at: token.and_then(|t| t.expires_at),
+            scopes: token
+                .and_then(|t| t.scope.as_deref())
+                .map(|s| s.split_whitespace().map(String::from).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The token ID that `name` refers to: `server:identity` as given, or a
+/// bare server name with the identity from its config or `--as`.
+fn resolve_token_id(config: &McplugConfig, name: &str) -> String {
+    if split_token_id(name).1.is_some() {
+        return name.to_string();
+    }
+    let auth = config.mcp_servers.get(name).and_then(|srv| srv.auth.as_ref());
+    OAuthClient::from_config(auth).token_id(name)
+}
+
+/// The server part of a token ID.
+fn server_of(token_id: &str) -> &str {
+    split_token_id(token_id).0
+}
+
+/// Run `mcplug auth status [server]`.
+///
+/// Without a server, reports every configured HTTP server that may use
+/// OAuth (not one with a configured bearer token) plus any other server
+/// or identity with cached tokens. Reads the token cache only; never
+/// starts a login.
+pub async fn run_auth_status(server: Option<&str>, json: bool) -> Result<(), McplugError> {
+    let config = load_config(None)?;
+    let names: BTreeSet<String> = match server {
+        Some(name) => {
+            let id = resolve_token_id(&config, name);
+            if !config.mcp_servers.contains_key(server_of(&id)) && load_cached_token(&id).is_none()
+            {
+                return Err(McplugError::ServerNotFound(name.to_string()));
+            }
+            BTreeSet::from([id])
+        }
+        None => config
+            .mcp_servers
+            .iter()
+            .filter(|(_, srv)| {
+                srv.base_url.is_some() && !matches!(srv.auth, Some(AuthConfig::Bearer { .. }))
+            })
+            .map(|(name, _)| resolve_token_id(&config, name))
+            .chain(cached_tokens()?.into_iter().map(|(name, _)| name))
+            .collect(),
+    };
+    let rows: Vec<AuthRow> = names
+        .into_iter()
+        .map(|name| {
+            let token = load_cached_token(&name);
+            AuthRow::new(name, token.as_ref())
+        })
+        .collect();
+    print_rows(&rows, json);
+    Ok(())
+}
+
+/// Run `mcplug auth list`: every server and identity with cached tokens.
+pub async fn run_auth_list(json: bool) -> Result<(), McplugError> {
+    let rows: Vec<AuthRow> = cached_tokens()?
+        .into_iter()
+        .map(|(name, token)| AuthRow::new(name, Some(&token)))
+        .collect();
+    if rows.is_empty() && !json {
+        eprintln!("No cached tokens.");
+    }
+    print_rows(&rows, json);
+    Ok(())
+}
+
+/// Run `mcplug auth logout <server>` or `mcplug auth logout --all`.
+///
+/// Revokes the cached tokens when the server's OAuth metadata advertises a
+/// revocation endpoint, then deletes them. A failed revocation is reported
+/// but never keeps the tokens on disk.
+pub async fn run_auth_logout(server: Option<&str>, all: bool) -> Result<(), McplugError> {
+    let config = load_config(None)?;
+    let targets: Vec<(String, TokenData)> = if all {
+        cached_tokens()?
+    } else {
+        let name = resolve_token_id(&config, server.unwrap_or_default());
+        match load_cached_token(&name) {
+            Some(token) => vec![(name, token)],
+            None if config.mcp_servers.contains_key(server_of(&name)) => {
+                println!("Not logged in to '{}'", name);
+                return Ok(());
+            }
+            None => return Err(McplugError::ServerNotFound(name)),
+        }
+    };
+    if targets.is_empty() {
+        println!("No cached tokens.");
+    }
+    for (name, token) in targets {
+        let revoked = match revoke_cached(&config, &name, &token).await {
+            Ok(revoked) => revoked,
+            Err(e) => {
+                eprintln!("Warning: could not revoke tokens for '{}': {}", name, e);
+                false
+            }
+        };
+        delete_token(&name)?;
+        if revoked {
+            println!("Logged out of '{}' (tokens revoked)", name);
+        } else {
+            println!("Logged out of '{}'", name);
+        }
+    }
+    Ok(())
+}
+
+/// Revoke a server's refresh and access tokens. Returns false when the
+/// server isn't configured over HTTP, the tokens were issued for another
+/// origin than its `baseUrl`, or it advertises no revocation endpoint.
+async fn revoke_cached(
+    config: &McplugConfig,
+    name: &str,
+    token: &TokenData,
+) -> Result<bool, McplugError> {
+    let Some(srv) = config.mcp_servers.get(server_of(name)) else {
+        return Ok(false);
+    };
+    let srv = resolve_secrets(srv)?;
+    let Some(base_url) = srv.base_url.as_deref().filter(|url| token.is_for(url)) else {
+        return Ok(false);
+    };
+    let metadata = discover_oauth_metadata(base_url).await?;
+    let Some(endpoint) = metadata.revocation_endpoint else {
+        return Ok(false);
+    };
+    let client = OAuthClient::from_config(srv.auth.as_ref());
+    // Revoking the refresh token first stops it minting new access tokens
+    if let Some(refresh) = token.refresh_token.as_deref() {
+        revoke_token(&endpoint, refresh, "refresh_token", &client.client_id).await?;
+    }
+    revoke_token(&endpoint, &token.access_token, "access_token", &client.client_id).await?;
+    Ok(true)
+}
+
+fn print_rows(rows: &[AuthRow], json: bool) {
+    if json {
+        println!("{}", serde_json::to_string_pretty(rows).unwrap_or_default());
+        return;
+    }
+    let width = rows.iter().map(|r| r.server.len()).max().unwrap_or(0);
+    for row in rows {
+        let mut line = format!("{:<width$}  {}", row.server, row.state.label());
+        if let Some(expires) = row.expires_at {
+            let verb = if row.state == TokenState::Valid {
+                "expires"
+            } else {
+                "expired"
+            };
+            line.push_str(&format!(
+                "  {} {}",
+                verb,
+                expires
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+            ));
+        }
+        if !row.scopes.is_empty() {
+            line.push_str(&format!("  scopes: {}", row.scopes.join(" ")));
+        }
+        println!("{}", line.trim_end());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(expires_in: Option<i64>, refresh: bool) -> TokenData {
+        TokenData {
+            access_token: "at".into(),
+            refresh_token: refresh.then(|| "rt".into()),
+            expires_at: expires_in.map(|s| chrono::Utc::now() + chrono::Duration::seconds(s)),
+            token_type: "Bearer".into(),
+            scope: Some("read  write".into()),
+            resource: None,
+            token_endpoint: None,
+        }
+    }
+
+    #[test]
+    fn token_state_classification() {
+        assert_eq!(TokenState::of(None), TokenState::LoggedOut);
+        assert_eq!(TokenState::of(Some(&token(None, false))), TokenState::Valid);
+        assert_eq!(TokenState::of(Some(&token(Some(3600), false))), TokenState::Valid);
+        assert_eq!(
+            TokenState::of(Some(&token(Some(-60), true))),
+            TokenState::Refreshable
+        );
+        assert_eq!(TokenState::of(Some(&token(Some(-60), false))), TokenState::Expired);
+    }
+
+    #[test]
+    fn token_ids_resolve_identities() {
+        let config: McplugConfig = serde_json::from_value(serde_json::json!({
+            "mcpServers": {
+                "gh": {
+                    "baseUrl": "https://example.com/mcp",
+                    "auth": { "type": "oauth", "identity": "work" }
+                },
+                "plain": { "baseUrl": "https://example.com/mcp" }
+            }
+        }))
+        .unwrap();
+        assert_eq!(resolve_token_id(&config, "gh"), "gh:work");
+        assert_eq!(resolve_token_id(&config, "gh:personal"), "gh:personal");
+        assert_eq!(server_of("gh:personal"), "gh");
+        assert_eq!(server_of("plain"), "plain");
+    }
+
+    #[test]
+    fn row_splits_scopes() {
+        let row = AuthRow::new("srv".into(), Some(&token(None, false)));
+        assert_eq!(row.scopes, vec!["read", "write"]);
+        let json = serde_json::to_value(&row).unwrap();
+        assert_eq!(json["state"], "valid");
+
+        let row = AuthRow::new("srv".into(), None);
+        assert_eq!(row.state, TokenState::LoggedOut);
+        assert!(row.scopes.is_empty() && row.expires_at.is_none());
+    }
+}
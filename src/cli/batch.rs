@@ -0,0 +1,339 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+use crate::args::parse_tool_ref;
+use crate::config::{load_config, McplugConfig};
+use crate::error::McplugError;
+use crate::transport::McpTransport;
+use crate::types::CallResult;
+
+use super::connection::{connect_to_server, verify_tool_pin};
+use super::fanout::{FanoutPolicy, FanoutSummary};
+
+/// Default timeout for each call in a batch.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Get the per-call timeout from the environment variable or use the default.
+fn get_timeout() -> Duration {
+    parse_timeout_secs(std::env::var("MCPLUG_CALL_TIMEOUT").ok())
+}
+
+fn parse_timeout_secs(val: Option<String>) -> Duration {
+    val.and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+}
+
+/// One line of a batch file.
+#[derive(Debug, Deserialize)]
+struct BatchEntry {
+    tool: String,
+    #[serde(default = "empty_args")]
+    args: serde_json::Value,
+}
+
+fn empty_args() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+/// A batch entry ready to run, tagged with its 1-based line number.
+#[derive(Debug)]
+struct BatchItem {
+    line: usize,
+    tool: String,
+    entry: Result<BatchEntry, String>,
+}
+
+/// Parse a JSONL batch file. Blank lines are skipped; malformed lines are
+/// kept as errors so they are reported in the output at their position.
+fn parse_batch(content: &str) -> Vec<BatchItem> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| {
+            let entry = serde_json::from_str::<BatchEntry>(l).map_err(|e| e.to_string());
+            let tool = entry
+                .as_ref()
+                .map(|e| e.tool.clone())
+                .unwrap_or_default();
+            BatchItem {
+                line: i + 1,
+                tool,
+                entry,
+            }
+        })
+        .collect()
+}
+
+/// Connections owned by one worker, reused across the entries it runs.
+pub(crate) struct ConnectionPool {
+    config: Arc<McplugConfig>,
+    connections: HashMap<String, Box<dyn McpTransport>>,
+    /// `(server, tool)` pairs whose schema pin has been checked.
+    verified: HashSet<(String, String)>,
+}
+
+impl ConnectionPool {
+    pub(crate) fn new(config: Arc<McplugConfig>) -> Self {
+        Self {
+            config,
+            connections: HashMap::new(),
+            verified: HashSet::new(),
+        }
+    }
+
+    /// Timeout for a call on `server`: its `timeoutMs`, else `default`.
+    pub(crate) fn call_timeout(&self, server: &str, default: Duration) -> Duration {
+        self.config.call_timeout(server, default)
+    }
+
+    /// Get the connection for `server`, connecting first if needed. The
+    /// `timeout` (or the server's `listTimeoutMs`/`timeoutMs`) bounds
+    /// initialization but not a first-run install.
+    pub(crate) async fn get(
+        &mut self,
+        server: &str,
+        timeout: Duration,
+    ) -> Result<&dyn McpTransport, McplugError> {
+        if !self.connections.contains_key(server) {
+            let timeout = self.config.list_timeout(server, timeout);
+            let mut transport = connect_to_server(server, &self.config, None, None)?;
+            transport.wait_ready().await?;
+            tokio::time::timeout(timeout, transport.initialize())
+                .await
+                .map_err(|_| McplugError::Timeout {
+                    server: server.to_string(),
+                    tool: None,
+                    duration: timeout,
+                })??;
+            self.connections.insert(server.to_string(), transport);
+        }
+        Ok(self.connections[server].as_ref())
+    }
+
+    /// Call `server`'s `tool`, connecting first if needed. A pinned tool's
+    /// schema is checked before its first call on each connection, and the
+    /// call is bounded by `timeout`.
+    pub(crate) async fn call_tool(
+        &mut self,
+        server: &str,
+        tool: &str,
+        args: serde_json::Value,
+        timeout: Duration,
+    ) -> Result<CallResult, McplugError> {
+        let key = (server.to_string(), tool.to_string());
+        let check = !self.verified.contains(&key);
+        let config = Arc::clone(&self.config);
+        let transport = self.get(server, timeout).await?;
+        if check {
+            verify_tool_pin(transport, server, tool, &config, timeout).await?;
+        }
+        let result = tokio::time::timeout(timeout, transport.call_tool(tool, args))
+            .await
+            .map_err(|_| McplugError::Timeout {
+                server: server.to_string(),
+                tool: Some(tool.to_string()),
+                duration: timeout,
+            })??;
+        self.verified.insert(key);
+        Ok(result)
+    }
+
+    pub(crate) async fn close(&mut self) {
+        self.verified.clear();
+        for (_, mut transport) in self.connections.drain() {
+            let _ = transport.close().await;
+        }
+    }
+}
+
+/// Run a single entry and build its output object.
+async fn run_item(
+    pool: &mut ConnectionPool,
+    item: &BatchItem,
+    timeout: Duration,
+) -> (serde_json::Value, bool) {
+    let entry = match item.entry {
+        Ok(ref entry) => entry,
+        Err(ref e) => {
+            let err = McplugError::ProtocolError(format!("Invalid batch line: {e}"));
+            return (error_output(item, &err), false);
+        }
+    };
+
+    let outcome = async {
+        let (server, tool) = parse_tool_ref(&entry.tool)?;
+        let timeout = pool.call_timeout(&server, timeout);
+        pool.call_tool(&server, &tool, entry.args.clone(), timeout).await
+    }
+    .await;
+
+    match outcome {
+        Ok(result) => {
+            let ok = !result.is_error;
+            let output = serde_json::json!({
+                "line": item.line,
+                "tool": item.tool,
+                "ok": ok,
+                "result": {
+                    "content": result.content,
+                    "isError": result.is_error,
+                },
+            });
+            (output, ok)
+        }
+        Err(e) => (error_output(item, &e), false),
+    }
+}
+
+fn skipped_output(item: &BatchItem) -> serde_json::Value {
+    serde_json::json!({
+        "line": item.line,
+        "tool": item.tool,
+        "ok": false,
+        "skipped": true,
+    })
+}
+
+fn error_output(item: &BatchItem, err: &McplugError) -> serde_json::Value {
+    serde_json::json!({
+        "line": item.line,
+        "tool": item.tool,
+        "ok": false,
+        "error": err.to_json()["error"],
+    })
+}
+
+/// Run the batch command.
+///
+/// Entries run on `parallel` workers, each with its own connections (a
+/// connection is never shared between concurrent calls). Results are printed
+/// as JSON lines in input order. With `fail_fast`, no new entries start after
+/// a failure and the rest are printed as skipped; `policy` decides the exit
+/// status.
+pub async fn run_batch(
+    file: &Path,
+    parallel: usize,
+    policy: FanoutPolicy,
+    fail_fast: bool,
+) -> Result<(), McplugError> {
+    let content = std::fs::read_to_string(file)?;
+    let items = parse_batch(&content);
+    let total = items.len();
+    let config = Arc::new(load_config(None)?);
+    let timeout = get_timeout();
+
+    let queue = Arc::new(Mutex::new(
+        items.into_iter().enumerate().collect::<VecDeque<_>>(),
+    ));
+    // Set on the first failure with --fail-fast; workers stop taking entries
+    let stop = Arc::new(AtomicBool::new(false));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    for _ in 0..parallel.max(1).min(total.max(1)) {
+        let queue = Arc::clone(&queue);
+        let stop = Arc::clone(&stop);
+        let tx = tx.clone();
+        let mut pool = ConnectionPool::new(Arc::clone(&config));
+        tokio::spawn(async move {
+            loop {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let next = queue.lock().ok().and_then(|mut q| q.pop_front());
+                let Some((index, item)) = next else { break };
+                let output = run_item(&mut pool, &item, timeout).await;
+                if fail_fast && !output.1 {
+                    stop.store(true, Ordering::Relaxed);
+                }
+                if tx.send((index, output)).is_err() {
+                    break;
+                }
+            }
+            pool.close().await;
+        });
+    }
+    drop(tx);
+
+    // Print in input order, buffering results that finish early
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    let mut summary = FanoutSummary::default();
+    while let Some((index, (output, ok))) = rx.recv().await {
+        summary.record(ok);
+        pending.insert(index, output);
+        while let Some(output) = pending.remove(&next) {
+            println!("{}", output);
+            next += 1;
+        }
+    }
+
+    // Entries never started because --fail-fast stopped the run
+    let skipped: Vec<_> = queue.lock().map(|mut q| q.drain(..).collect()).unwrap_or_default();
+    for (index, item) in skipped {
+        summary.record_skipped();
+        pending.insert(index, skipped_output(&item));
+    }
+    for (_, output) in pending {
+        println!("{}", output);
+    }
+
+    summary.check(policy, "batch calls")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_batch_lines() {
+        let content = r#"{"tool": "srv.echo", "args": {"input": "hi"}}
+
+{"tool": "srv.list"}
+not json
+"#;
+        let items = parse_batch(content);
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].line, 1);
+        assert_eq!(items[0].tool, "srv.echo");
+        assert_eq!(items[0].entry.as_ref().unwrap().args["input"], "hi");
+        assert_eq!(items[1].line, 3);
+        assert_eq!(
+            items[1].entry.as_ref().unwrap().args,
+            serde_json::json!({})
+        );
+        assert_eq!(items[2].line, 4);
+        assert!(items[2].entry.is_err());
+    }
+
+    #[test]
+    fn parse_batch_missing_tool_is_error() {
+        let items = parse_batch(r#"{"args": {}}"#);
+        assert!(items[0].entry.is_err());
+    }
+
+    #[test]
+    fn error_output_shape() {
+        let item = BatchItem {
+            line: 7,
+            tool: "srv.x".into(),
+            entry: Err("bad".into()),
+        };
+        let out = error_output(&item, &McplugError::ServerNotFound("srv".into()));
+        assert_eq!(out["line"], 7);
+        assert_eq!(out["ok"], false);
+        assert_eq!(out["error"]["code"], "not_found");
+    }
+
+    #[test]
+    fn timeout_default() {
+        assert_eq!(parse_timeout_secs(None), Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+    }
+}
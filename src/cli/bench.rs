@@ -0,0 +1,324 @@
+use std::io::IsTerminal;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+use tokio::task::JoinSet;
+
+use crate::args::{parse_args, parse_function_call, parse_tool_ref};
+use crate::config::{load_config, McplugConfig};
+use crate::error::McplugError;
+
+use super::connection::{connect_to_server, verify_tool_pin};
+
+/// Default timeout for each benchmarked call.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Get the per-call timeout from the environment variable or use the default.
+fn get_timeout() -> Duration {
+    parse_timeout_secs(std::env::var("MCPLUG_CALL_TIMEOUT").ok())
+}
+
+fn parse_timeout_secs(val: Option<String>) -> Duration {
+    val.and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+}
+
+/// What to benchmark and how.
+pub struct BenchOptions<'a> {
+    pub warmup: usize,
+    pub iterations: usize,
+    pub concurrency: usize,
+    pub http_url: Option<&'a str>,
+    pub stdio: Option<&'a str>,
+    pub json: bool,
+}
+
+/// Latency summary for the measured calls.
+#[derive(Debug, PartialEq)]
+struct BenchStats {
+    calls: usize,
+    errors: usize,
+    min: Duration,
+    mean: Duration,
+    p50: Duration,
+    p95: Duration,
+    p99: Duration,
+    max: Duration,
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[Duration], pct: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn summarize(mut latencies: Vec<Duration>, errors: usize) -> BenchStats {
+    latencies.sort();
+    let calls = latencies.len();
+    let total: Duration = latencies.iter().sum();
+    BenchStats {
+        calls,
+        errors,
+        min: latencies.first().copied().unwrap_or_default(),
+        mean: if calls > 0 {
+            total / calls as u32
+        } else {
+            Duration::ZERO
+        },
+        p50: percentile(&latencies, 50.0),
+        p95: percentile(&latencies, 95.0),
+        p99: percentile(&latencies, 99.0),
+        max: latencies.last().copied().unwrap_or_default(),
+    }
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// The call being benchmarked, shared by all workers.
+struct Target {
+    server: String,
+    tool: String,
+    args: serde_json::Value,
+    config: McplugConfig,
+    http_url: Option<String>,
+    stdio: Option<String>,
+    timeout: Duration,
+}
+
+/// Measurements from one worker.
+struct WorkerRun {
+    /// Latency of every measured call and whether it succeeded.
+    samples: Vec<(Duration, bool)>,
+    /// Calls per second over the worker's measured phase.
+    throughput: f64,
+}
+
+/// One worker's connection, warmup, and measured calls.
+async fn run_worker(
+    target: Arc<Target>,
+    warmup: usize,
+    iterations: usize,
+) -> Result<WorkerRun, McplugError> {
+    let timeout = target.timeout;
+    let mut transport = connect_to_server(
+        &target.server,
+        &target.config,
+        target.http_url.as_deref(),
+        target.stdio.as_deref(),
+    )?;
+    transport.wait_ready().await?;
+    tokio::time::timeout(timeout, transport.initialize())
+        .await
+        .map_err(|_| McplugError::Timeout {
+            server: target.server.clone(),
+            tool: None,
+            duration: timeout,
+        })??;
+    if target.http_url.is_none() && target.stdio.is_none() {
+        verify_tool_pin(transport.as_ref(), &target.server, &target.tool, &target.config, timeout)
+            .await?;
+    }
+
+    let call = || {
+        tokio::time::timeout(timeout, transport.call_tool(&target.tool, target.args.clone()))
+    };
+    for _ in 0..warmup {
+        let _ = call().await;
+    }
+
+    let measuring = Instant::now();
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let started = Instant::now();
+        let ok = matches!(call().await, Ok(Ok(ref result)) if !result.is_error);
+        samples.push((started.elapsed(), ok));
+    }
+    let throughput = iterations as f64 / measuring.elapsed().as_secs_f64().max(f64::EPSILON);
+    let _ = transport.close().await;
+    Ok(WorkerRun {
+        samples,
+        throughput,
+    })
+}
+
+/// Split `total` calls as evenly as possible across `workers`.
+fn split_evenly(total: usize, workers: usize) -> Vec<usize> {
+    (0..workers)
+        .map(|i| total / workers + usize::from(i < total % workers))
+        .collect()
+}
+
+/// Run the bench command.
+///
+/// Each of `concurrency` workers opens its own connection (stdio servers
+/// can't multiplex calls), runs its share of warmup calls, then its share of
+/// measured calls. Latencies of failed calls are included in the
+/// percentiles; the error rate is reported separately.
+pub async fn run_bench(
+    tool_ref: &str,
+    args: &[String],
+    opts: BenchOptions<'_>,
+) -> Result<(), McplugError> {
+    if opts.iterations == 0 {
+        return Err(McplugError::ProtocolError(
+            "--iterations must be at least 1".into(),
+        ));
+    }
+    let (server, tool, parsed_args) = if tool_ref.contains('(') {
+        parse_function_call(tool_ref)?
+    } else {
+        let (s, t) = parse_tool_ref(tool_ref)?;
+        (s, t, parse_args(args)?)
+    };
+    let config = load_config(None)?;
+    let timeout = if opts.http_url.is_none() && opts.stdio.is_none() {
+        config.call_timeout(&server, get_timeout())
+    } else {
+        get_timeout()
+    };
+    let target = Arc::new(Target {
+        server: server.clone(),
+        tool: tool.clone(),
+        args: parsed_args,
+        config,
+        http_url: opts.http_url.map(String::from),
+        stdio: opts.stdio.map(String::from),
+        timeout,
+    });
+    let workers = opts.concurrency.clamp(1, opts.iterations);
+
+    let warmups = split_evenly(opts.warmup, workers);
+    let measured = split_evenly(opts.iterations, workers);
+    let mut tasks = JoinSet::new();
+    for (w, m) in warmups.into_iter().zip(measured) {
+        tasks.spawn(run_worker(Arc::clone(&target), w, m));
+    }
+
+    let mut latencies = Vec::with_capacity(opts.iterations);
+    let mut errors = 0;
+    // Workers run side by side, so their rates add up
+    let mut throughput = 0.0;
+    while let Some(joined) = tasks.join_next().await {
+        let run = joined.map_err(|e| McplugError::TransportError(Box::new(e)))??;
+        throughput += run.throughput;
+        for (latency, ok) in run.samples {
+            latencies.push(latency);
+            if !ok {
+                errors += 1;
+            }
+        }
+    }
+    let stats = summarize(latencies, errors);
+    let error_rate = stats.errors as f64 / stats.calls as f64;
+
+    if opts.json {
+        let output = serde_json::json!({
+            "server": server,
+            "tool": tool,
+            "warmup": opts.warmup,
+            "iterations": stats.calls,
+            "concurrency": workers,
+            "errors": stats.errors,
+            "errorRate": error_rate,
+            "latencyMs": {
+                "min": ms(stats.min),
+                "mean": ms(stats.mean),
+                "p50": ms(stats.p50),
+                "p95": ms(stats.p95),
+                "p99": ms(stats.p99),
+                "max": ms(stats.max),
+            },
+            "throughputPerSec": throughput,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&output).unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    let is_tty = std::io::stdout().is_terminal();
+    let title = format!("{}.{}", server, tool);
+    println!(
+        "{} — {} calls ({} warmup), concurrency {}",
+        if is_tty {
+            title.bold().to_string()
+        } else {
+            title
+        },
+        stats.calls,
+        opts.warmup,
+        workers
+    );
+    println!(
+        "  latency   min {:.1} ms  mean {:.1} ms  max {:.1} ms",
+        ms(stats.min),
+        ms(stats.mean),
+        ms(stats.max)
+    );
+    println!(
+        "            p50 {:.1} ms  p95 {:.1} ms  p99 {:.1} ms",
+        ms(stats.p50),
+        ms(stats.p95),
+        ms(stats.p99)
+    );
+    println!(
+        "  errors    {} ({:.1}%)",
+        stats.errors,
+        error_rate * 100.0
+    );
+    println!(
+        "  throughput {:.1} calls/s",
+        throughput
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms_vec(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&v| Duration::from_millis(v)).collect()
+    }
+
+    #[test]
+    fn percentile_nearest_rank() {
+        let samples = ms_vec(&(1..=100).collect::<Vec<_>>());
+        assert_eq!(percentile(&samples, 50.0), Duration::from_millis(50));
+        assert_eq!(percentile(&samples, 95.0), Duration::from_millis(95));
+        assert_eq!(percentile(&samples, 99.0), Duration::from_millis(99));
+        assert_eq!(percentile(&samples, 100.0), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn percentile_small_and_empty() {
+        assert_eq!(percentile(&ms_vec(&[7]), 99.0), Duration::from_millis(7));
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn summarize_sorts_and_averages() {
+        let stats = summarize(ms_vec(&[30, 10, 20]), 1);
+        assert_eq!(stats.calls, 3);
+        assert_eq!(stats.errors, 1);
+        assert_eq!(stats.min, Duration::from_millis(10));
+        assert_eq!(stats.mean, Duration::from_millis(20));
+        assert_eq!(stats.p50, Duration::from_millis(20));
+        assert_eq!(stats.max, Duration::from_millis(30));
+    }
+
+    #[test]
+    fn split_evenly_distributes_remainder() {
+        assert_eq!(split_evenly(10, 3), vec![4, 3, 3]);
+        assert_eq!(split_evenly(0, 2), vec![0, 0]);
+        assert_eq!(split_evenly(5, 1), vec![5]);
+    }
+}
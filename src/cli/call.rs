@@ -1,16 +1,38 @@
 use std::io::IsTerminal;
-use std::time::Duration;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-use crate::args::{parse_args, parse_function_call, parse_tool_ref, suggest_tool};
-use crate::config::load_config;
+use crate::args::{
+    parse_args, parse_args_json, parse_function_call, parse_tool_ref, reads_stdin, suggest_tool,
+};
+use crate::config::{load_config, AuthConfig, McplugConfig};
+use crate::config::pins::{check_tool_pin, PinPolicy};
 use crate::error::McplugError;
+use crate::history::{self, HistoryEntry};
+use crate::redact::{install_key, redact_args, sensitive_args};
+use crate::jsonpath;
+use crate::oauth::{run_oauth_flow, OAuthClient};
+use crate::results::{save_result, validate_name};
+use crate::state::default_store;
+use crate::transport::McpTransport;
+use crate::types::ToolDefinition;
 
-use super::connection::connect_to_server;
-use super::output::{print_call_result, OutputMode};
+use super::connection::{apply_env_overrides, connect_recorded};
+use super::explain::{confirm, ExecutionPlan};
+use super::output::{self, print_call_result_with_images, OutputMode};
 
 /// Default timeout for call operations.
 const DEFAULT_TIMEOUT_SECS: u64 = 30;
 
+/// Default OAuth handshake timeout for `--auth`.
+const DEFAULT_OAUTH_TIMEOUT_MS: u64 = 60_000;
+
+/// Default size above which outgoing arguments trigger a warning (64 KiB).
+const DEFAULT_WARN_ARGS_BYTES: usize = 64 * 1024;
+
+/// Default size above which a tool result triggers a warning (1 MiB).
+const DEFAULT_WARN_RESULT_BYTES: usize = 1024 * 1024;
+
 /// Get the call timeout from the environment variable or use the default.
 fn get_timeout() -> Duration {
     parse_timeout_secs(std::env::var("MCPLUG_CALL_TIMEOUT").ok())
@@ -22,6 +44,40 @@ fn parse_timeout_secs(val: Option<String>) -> Duration {
         .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
 }
 
+/// Get the `--auth` handshake timeout from the environment or use the default.
+fn get_oauth_timeout() -> Duration {
+    std::env::var("MCPLUG_OAUTH_TIMEOUT_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(Duration::from_millis(DEFAULT_OAUTH_TIMEOUT_MS))
+}
+
+/// Get a size warning threshold from the environment or use the default.
+///
+/// A value of `0` disables the warning.
+fn get_size_limit(var: &str, default: usize) -> Option<usize> {
+    parse_size_limit(std::env::var(var).ok(), default)
+}
+
+fn parse_size_limit(val: Option<String>, default: usize) -> Option<usize> {
+    let limit = val
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(default);
+    (limit > 0).then_some(limit)
+}
+
+/// Serialized size of a JSON payload in bytes.
+fn payload_size(value: &serde_json::Value) -> usize {
+    serde_json::to_vec(value).map(|v| v.len()).unwrap_or(0)
+}
+
+/// Warning message when `bytes` exceeds `limit`.
+fn size_warning(what: &str, bytes: usize, limit: Option<usize>) -> Option<String> {
+    let limit = limit?;
+    (bytes > limit).then(|| format!("Warning: {what} are {bytes} bytes (threshold {limit} bytes)"))
+}
+
 /// Determine the output mode from CLI flags.
 fn resolve_output_mode(raw: bool, json: bool, output_format: Option<&str>) -> OutputMode {
     if json {
@@ -33,72 +89,340 @@ fn resolve_output_mode(raw: bool, json: bool, output_format: Option<&str>) -> Ou
     match output_format {
         Some("json") => OutputMode::Json,
         Some("raw") => OutputMode::Raw,
+        Some("markdown" | "md") => OutputMode::Markdown,
         _ => OutputMode::Pretty,
     }
 }
 
+/// Print a value selected by `--filter`: strings bare (like `jq -r`) unless
+/// JSON output was asked for, everything else as JSON.
+fn print_filtered(value: &serde_json::Value, mode: OutputMode) {
+    match value {
+        serde_json::Value::String(s) if mode != OutputMode::Json => println!("{}", s),
+        other => println!(
+            "{}",
+            serde_json::to_string_pretty(other).unwrap_or_default()
+        ),
+    }
+}
+
 /// Run the call command.
+///
+/// `args_json` supplies the whole argument object as JSON (or `@file`),
+/// replacing the `key:value` arguments.
+///
+/// With `filter`, only the part of the JSON text result selected by the
+/// path (jq-style `.a[0]` or JSONPath `$.a[0]`) is printed.
+///
+/// With `save_as`, the result is also stored under that name for
+/// `mcplug result show` and `@result:` references.
+///
+/// Every call that reaches the server is added to the history shown by
+/// `mcplug history query`.
+///
+/// With `record`, the exchange with the server is written to that file;
+/// with `replay`, responses come from such a file instead of a server.
+///
+/// With `explain`, the execution plan is printed to stderr and the call only
+/// proceeds after confirmation on stdin, so arguments can't also be read
+/// from stdin. A declined call is an error unless stdin is a terminal. With `auth`, a configured server
+/// that answers with an authentication challenge gets the OAuth login flow
+/// inline, and the connection is retried once with the new token.
+#[allow(clippy::too_many_arguments)]
 pub async fn run_call(
     tool_ref: &str,
     args: &[String],
+    args_json: Option<&str>,
     raw: bool,
     json: bool,
     output_format: Option<&str>,
     http_url: Option<&str>,
     stdio: Option<&str>,
+    explain: bool,
+    save_as: Option<&str>,
+    filter: Option<&str>,
+    save_images: Option<&Path>,
+    env: &[String],
+    record: Option<&Path>,
+    replay: Option<&Path>,
+    auth: bool,
 ) -> Result<(), McplugError> {
-    let config = load_config(None)?;
-    let timeout = get_timeout();
+    if let Some(name) = save_as {
+        validate_name(name)?;
+    }
+    if explain && !tool_ref.contains('(') && reads_stdin(args, args_json) {
+        return Err(McplugError::ProtocolError(
+            "--explain reads its confirmation from stdin, so arguments can't come from stdin \
+             too (--stdin, --args-json @-, or key:@-)"
+                .to_string(),
+        ));
+    }
+    let filter = filter.map(jsonpath::normalize);
+    if let Some(ref path) = filter {
+        jsonpath::validate(path)?;
+    }
+    let mut config = load_config(None)?;
     let mode = resolve_output_mode(raw, json, output_format);
     let is_tty = std::io::stdout().is_terminal();
 
     // Parse tool reference: support both "server.tool" and "server.tool(args)" syntax
     let (server_name, tool_name, parsed_args) = if tool_ref.contains('(') {
+        if args_json.is_some() {
+            return Err(McplugError::ProtocolError(
+                "--args-json cannot be combined with function-call syntax".to_string(),
+            ));
+        }
         let (s, t, a) = parse_function_call(tool_ref)?;
         (s, t, a)
     } else {
         let (s, t) = parse_tool_ref(tool_ref)?;
-        let a = parse_args(args)?;
+        let a = match args_json {
+            Some(input) => parse_args_json(input)?,
+            None => parse_args(args)?,
+        };
         (s, t, a)
     };
 
+    apply_env_overrides(&mut config, Some(&server_name), env)?;
+    // Ad-hoc servers don't get a configured server's timeouts
+    let (list_timeout, timeout) = if http_url.is_none() && stdio.is_none() {
+        (
+            config.list_timeout(&server_name, get_timeout()),
+            config.call_timeout(&server_name, get_timeout()),
+        )
+    } else {
+        (get_timeout(), get_timeout())
+    };
+
     // Connect and initialize
-    let mut transport = connect_to_server(&server_name, &config, http_url, stdio)?;
+    let target = Target {
+        server_name: &server_name,
+        tool_name: &tool_name,
+        http_url,
+        stdio,
+        record,
+        replay,
+        list_timeout,
+    };
+    let adhoc = http_url.is_some() || stdio.is_some() || replay.is_some();
+    let (mut transport, tools) = match connect_and_list(&target, &config).await {
+        Err(McplugError::AuthRequired(_)) if auth && !adhoc => {
+            login(&server_name, &config).await?;
+            connect_and_list(&target, &config).await?
+        }
+        other => other?,
+    };
 
-    let result = tokio::time::timeout(timeout, async {
-        transport.initialize().await?;
+    // Validate tool name exists and provide suggestions if not found
+    let tool_names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+    let Some(tool_def) = tools.iter().find(|t| t.name == tool_name) else {
+        let _ = transport.close().await;
+        return Err(McplugError::ToolNotFound {
+            server: server_name.clone(),
+            tool: tool_name.clone(),
+            suggestion: suggest_tool(&tool_name, &tool_names),
+        });
+    };
 
-        // Validate tool name exists and provide suggestions if not found
-        let tools = transport.list_tools().await?;
-        let tool_names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
-
-        if !tool_names.contains(&tool_name.as_str()) {
-            let suggestion = suggest_tool(&tool_name, &tool_names);
-            let mut msg = format!(
-                "Tool '{}' not found on {}.",
-                tool_name, server_name
-            );
-            if let Some(ref s) = suggestion {
-                msg.push_str(&format!(" Did you mean '{}'?", s));
+    // Enforce schema pins for configured servers
+    let pinned_config = config
+        .mcp_servers
+        .get(&server_name)
+        .filter(|_| http_url.is_none() && stdio.is_none());
+    if let Some(cfg) = pinned_config {
+        if let Err(e) = check_tool_pin(&server_name, cfg, tool_def, PinPolicy::from_env()) {
+            let _ = transport.close().await;
+            return Err(e);
+        }
+    }
+
+    if explain {
+        let plan = ExecutionPlan::build(
+            &server_name,
+            tool_def,
+            &config,
+            http_url,
+            stdio,
+            timeout,
+            mode,
+            &parsed_args,
+        );
+        eprint!("{}", plan.render());
+        let proceed = confirm(&mut std::io::stdin().lock(), "Proceed with this call? [y/N]: ")?;
+        if !proceed {
+            let _ = transport.close().await;
+            // A script that pipes in its answer must not take a declined
+            // call for a successful one
+            if !std::io::stdin().is_terminal() {
+                return Err(McplugError::ProtocolError(
+                    "Call not confirmed: --explain needs a 'y' answer, and stdin is not a terminal"
+                        .to_string(),
+                ));
             }
-            return Err(McplugError::ToolNotFound {
-                server: server_name.clone(),
-                tool: tool_name.clone(),
-            });
+            eprintln!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    let args_bytes = payload_size(&parsed_args);
+    let args_limit = get_size_limit("MCPLUG_WARN_ARGS_BYTES", DEFAULT_WARN_ARGS_BYTES);
+    if let Some(warning) = size_warning(
+        &format!("arguments for {}.{}", server_name, tool_name),
+        args_bytes,
+        args_limit,
+    ) {
+        eprintln!("{}", warning);
+    }
+
+    let configured = pinned_config
+        .map(|cfg| cfg.sensitive_args.as_slice())
+        .unwrap_or_default();
+    let sensitive = sensitive_args(&tool_def.input_schema, configured);
+    let logged_args = redact_args(&parsed_args, &sensitive, &install_key());
+
+    let started_at = chrono::Utc::now();
+    let started = Instant::now();
+    let outcome = tokio::select! {
+        outcome = tokio::time::timeout(timeout, transport.call_tool(&tool_name, parsed_args)) => {
+            outcome
+                .map_err(|_| McplugError::Timeout {
+                    server: server_name.clone(),
+                    tool: Some(tool_name.clone()),
+                    duration: timeout,
+                })
+                .and_then(|r| r)
+        }
+        _ = tokio::signal::ctrl_c() => Err(McplugError::Cancelled {
+            server: server_name.clone(),
+            tool: tool_name.clone(),
+        }),
+    };
+    if let Err(McplugError::Cancelled { .. }) = outcome {
+        // Ask the server to stop, then shut it down rather than leave it
+        // (and anything it spawned) running after we exit
+        if let Err(e) = transport.cancel_pending("Interrupted by user").await {
+            tracing::debug!(error = %e, "failed to send cancellation");
+        }
+        let _ = transport.close().await;
+    }
+    if replay.is_none() {
+        let error = match outcome {
+            Ok(ref result) if result.is_error => Some(result.text()),
+            Ok(_) => None,
+            Err(ref e) => Some(e.to_string()),
+        };
+        let entry = HistoryEntry::new(&server_name, &tool_name, started_at, started.elapsed(), error)
+            .with_args(logged_args);
+        if let Err(e) = default_store().and_then(|store| history::record(store.as_ref(), &entry)) {
+            tracing::warn!(error = %e, "failed to record call history");
+        }
+    }
+    let result = outcome?;
+
+    let result_bytes = result.raw_response.as_ref().map(payload_size).unwrap_or(0);
+    let result_limit = get_size_limit("MCPLUG_WARN_RESULT_BYTES", DEFAULT_WARN_RESULT_BYTES);
+    if let Some(warning) = size_warning(
+        &format!("results from {}.{}", server_name, tool_name),
+        result_bytes,
+        result_limit,
+    ) {
+        eprintln!("{}", warning);
+    }
+    tracing::info!(
+        server = %server_name,
+        tool = %tool_name,
+        args_bytes,
+        result_bytes,
+        "tool call payload sizes"
+    );
+
+    match filter {
+        // Error results have no JSON to filter; show them as usual
+        Some(ref path) if !result.is_error => {
+            let value = result.json().map_err(|_| {
+                McplugError::ProtocolError(format!(
+                    "--filter needs JSON, but {}.{} returned text that is not JSON",
+                    server_name, tool_name
+                ))
+            })?;
+            let selected = jsonpath::select(&value, path)?;
+            print_filtered(&selected, mode);
         }
+        _ => {
+            let saved = match save_images {
+                Some(dir) if !result.is_error => output::save_images(&result, dir)?,
+                _ => Vec::new(),
+            };
+            print_call_result_with_images(&result, mode, is_tty, &saved);
+        }
+    }
+
+    if let Some(name) = save_as {
+        save_result(name, &format!("{}.{}", server_name, tool_name), &result)?;
+    }
+
+    let _ = transport.close().await;
+    Ok(())
+}
 
-        transport.call_tool(&tool_name, parsed_args).await
+/// What `run_call` connects to, kept together so a login can retry it.
+struct Target<'a> {
+    server_name: &'a str,
+    tool_name: &'a str,
+    http_url: Option<&'a str>,
+    stdio: Option<&'a str>,
+    record: Option<&'a Path>,
+    replay: Option<&'a Path>,
+    list_timeout: Duration,
+}
+
+/// Connect to a server, initialize it, and list its tools.
+async fn connect_and_list(
+    target: &Target<'_>,
+    config: &McplugConfig,
+) -> Result<(Box<dyn McpTransport>, Vec<ToolDefinition>), McplugError> {
+    let mut transport = connect_recorded(
+        target.server_name,
+        config,
+        target.http_url,
+        target.stdio,
+        target.record,
+        target.replay,
+    )?;
+    transport.wait_ready().await?;
+
+    let tools = tokio::time::timeout(target.list_timeout, async {
+        transport.initialize().await?;
+        transport.list_tools().await
     })
     .await
     .map_err(|_| McplugError::Timeout {
-        server: server_name.clone(),
-        tool: Some(tool_name.clone()),
-        duration: timeout,
+        server: target.server_name.to_string(),
+        tool: Some(target.tool_name.to_string()),
+        duration: target.list_timeout,
     })??;
+    Ok((transport, tools))
+}
 
-    print_call_result(&result, mode, is_tty);
-
-    let _ = transport.close().await;
+/// Run the OAuth login flow for `call --auth` after the server asked for
+/// credentials.
+async fn login(server_name: &str, config: &McplugConfig) -> Result<(), McplugError> {
+    let auth_required = || McplugError::AuthRequired(server_name.to_string());
+    let srv = config
+        .mcp_servers
+        .get(server_name)
+        .ok_or_else(auth_required)?;
+    // A rejected static token can't be fixed by logging in
+    if matches!(srv.auth, Some(AuthConfig::Bearer { .. })) {
+        return Err(auth_required());
+    }
+    let base_url = srv.base_url.clone().ok_or_else(auth_required)?;
+    let client = OAuthClient::from_config(srv.auth.as_ref());
+    eprintln!(
+        "Server '{}' requires authentication; opening the browser to log in...",
+        server_name
+    );
+    run_oauth_flow(&base_url, &client.token_id(server_name), &client, get_oauth_timeout()).await?;
     Ok(())
 }
 
@@ -106,6 +430,18 @@ pub async fn run_call(
 mod tests {
     use super::*;
 
+    #[test]
+    fn resolve_output_format_markdown() {
+        assert_eq!(
+            resolve_output_mode(false, false, Some("markdown")),
+            OutputMode::Markdown
+        );
+        assert_eq!(
+            resolve_output_mode(false, false, Some("md")),
+            OutputMode::Markdown
+        );
+    }
+
     #[test]
     fn resolve_output_json_flag() {
         assert_eq!(resolve_output_mode(false, true, None), OutputMode::Json);
@@ -200,6 +536,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn size_limit_default_and_override() {
+        assert_eq!(parse_size_limit(None, 100), Some(100));
+        assert_eq!(parse_size_limit(Some("2048".into()), 100), Some(2048));
+        assert_eq!(parse_size_limit(Some("bad".into()), 100), Some(100));
+    }
+
+    #[test]
+    fn size_limit_zero_disables() {
+        assert_eq!(parse_size_limit(Some("0".into()), 100), None);
+    }
+
+    #[test]
+    fn size_warning_only_above_limit() {
+        assert!(size_warning("arguments", 100, Some(100)).is_none());
+        assert!(size_warning("arguments", 101, None).is_none());
+        let msg = size_warning("arguments", 101, Some(100)).unwrap();
+        assert_eq!(
+            msg,
+            "Warning: arguments are 101 bytes (threshold 100 bytes)"
+        );
+    }
+
+    #[test]
+    fn payload_size_counts_serialized_bytes() {
+        assert_eq!(payload_size(&serde_json::json!({"a": 1})), 7);
+    }
+
     #[test]
     fn timeout_very_large_value() {
         assert_eq!(
@@ -207,4 +571,22 @@ mod tests {
             Duration::from_secs(999999)
         );
     }
+
+    #[tokio::test]
+    async fn login_refuses_bearer_and_unknown_servers() {
+        let config: McplugConfig = serde_json::from_value(serde_json::json!({
+            "mcpServers": {
+                "static": {
+                    "baseUrl": "https://example.com/mcp",
+                    "auth": {"type": "bearer", "token": "t"}
+                },
+                "local": {"command": "server"}
+            }
+        }))
+        .unwrap();
+        for name in ["static", "local", "missing"] {
+            let err = login(name, &config).await.unwrap_err();
+            assert!(matches!(err, McplugError::AuthRequired(ref s) if s == name));
+        }
+    }
 }
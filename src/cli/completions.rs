@@ -0,0 +1,359 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::env::references_secrets;
+use crate::config::load_config;
+use crate::config::loader::load_merged_config;
+use crate::error::McplugError;
+use crate::state::default_store;
+
+use super::connection::connect_to_server;
+
+/// Timeout for fetching a server's tools while completing.
+const COMPLETE_TIMEOUT_SECS: u64 = 5;
+
+/// How long cached tool names are used before the server is queried again.
+const CACHE_TTL_SECS: i64 = 300;
+
+/// Shells `mcplug completions` can generate scripts for.
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish"];
+
+const BASH_SCRIPT: &str = r#"_mcplug() {
+    local IFS=$'\n'
+    COMPREPLY=($(mcplug __complete -- "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null))
+    if [[ ${#COMPREPLY[@]} -eq 1 && ${COMPREPLY[0]} == *. ]]; then
+        compopt -o nospace
+    fi
+}
+complete -o nosort -F _mcplug mcplug
+"#;
+
+const ZSH_SCRIPT: &str = r#"#compdef mcplug
+_mcplug() {
+    local -a candidates with_dot plain
+    candidates=("${(@f)$(mcplug __complete -- "${(@)words[2,CURRENT]}" 2>/dev/null)}")
+    with_dot=(${(M)candidates:#*.})
+    plain=(${candidates:#*.})
+    (( ${#with_dot} )) && compadd -S '' -- $with_dot
+    (( ${#plain} )) && compadd -- $plain
+}
+compdef _mcplug mcplug
+"#;
+
+const FISH_SCRIPT: &str = r#"function __mcplug_complete
+    set -l tokens (commandline -opc)
+    set -l current (commandline -ct)
+    mcplug __complete -- $tokens[2..-1] "$current" 2>/dev/null
+end
+complete -c mcplug -f -a '(__mcplug_complete)'
+"#;
+
+/// Return the completion script for a shell.
+pub fn completion_script(shell: &str) -> Result<&'static str, McplugError> {
+    match shell {
+        "bash" => Ok(BASH_SCRIPT),
+        "zsh" => Ok(ZSH_SCRIPT),
+        "fish" => Ok(FISH_SCRIPT),
+        other => Err(McplugError::ProtocolError(format!(
+            "Unsupported shell '{}'. Expected one of: {}",
+            other,
+            SHELLS.join(", ")
+        ))),
+    }
+}
+
+/// Run the completions command.
+pub fn run_completions(shell: &str) -> Result<(), McplugError> {
+    print!("{}", completion_script(shell)?);
+    Ok(())
+}
+
+/// What the word under the cursor should be completed with.
+#[derive(Debug, PartialEq)]
+enum Completion {
+    /// A fixed set of candidates (subcommands, flags, server names).
+    Values(Vec<String>),
+    /// A `server.tool` reference; tool names must be fetched for `server`.
+    ToolRef { server: String, prefix: String },
+}
+
+/// Work out completion candidates for `words` (everything after `mcplug`,
+/// ending with the possibly empty word under the cursor).
+fn complete_words(cmd: &clap::Command, words: &[String], servers: &[String]) -> Completion {
+    let (current, before) = match words.split_last() {
+        Some((current, before)) => (current.as_str(), before),
+        None => ("", &[][..]),
+    };
+
+    // Descend into subcommands and count positionals given so far
+    let mut cmd = cmd;
+    let mut positionals = 0;
+    let mut skip_value = false;
+    for word in before {
+        if skip_value {
+            skip_value = false;
+            continue;
+        }
+        if let Some(flag) = word.strip_prefix("--") {
+            if !flag.contains('=') {
+                skip_value = cmd
+                    .get_arguments()
+                    .find(|a| a.get_long() == Some(flag))
+                    .is_some_and(|a| a.get_action().takes_values());
+            }
+            continue;
+        }
+        if word.starts_with('-') {
+            continue;
+        }
+        if positionals == 0 {
+            if let Some(sub) = cmd.find_subcommand(word) {
+                cmd = sub;
+                continue;
+            }
+        }
+        positionals += 1;
+    }
+    if skip_value {
+        return Completion::Values(Vec::new());
+    }
+
+    let matching = |candidates: Vec<String>| {
+        let mut values: Vec<String> = candidates
+            .into_iter()
+            .filter(|c| c.starts_with(current))
+            .collect();
+        values.sort();
+        values.dedup();
+        Completion::Values(values)
+    };
+
+    if current.starts_with('-') {
+        return matching(
+            cmd.get_arguments()
+                .filter(|a| !a.is_hide_set())
+                .filter_map(|a| a.get_long().map(|l| format!("--{l}")))
+                .collect(),
+        );
+    }
+
+    if positionals == 0 && cmd.has_subcommands() {
+        return matching(
+            cmd.get_subcommands()
+                .filter(|s| !s.is_hide_set())
+                .map(|s| s.get_name().to_string())
+                .collect(),
+        );
+    }
+
+    let positional = cmd.get_positionals().nth(positionals);
+    match positional.map(|a| a.get_id().as_str()) {
+        Some("tool_ref") => match current.split_once('.') {
+            Some((server, prefix)) => Completion::ToolRef {
+                server: server.to_string(),
+                prefix: prefix.to_string(),
+            },
+            None => matching(servers.iter().map(|s| format!("{s}.")).collect()),
+        },
+        Some("server") => matching(servers.to_vec()),
+        _ => Completion::Values(Vec::new()),
+    }
+}
+
+/// Tool names cached for completion.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolNameCache {
+    tools: Vec<String>,
+    saved_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Key of the completion cache within a server's state namespace
+/// (`~/.mcplug/<server>/tool-names.json` by default).
+const TOOL_CACHE_KEY: &str = "tool-names";
+
+fn load_cached_tools(server: &str) -> Option<Vec<String>> {
+    let cache: ToolNameCache = default_store().ok()?.load(server, TOOL_CACHE_KEY)?;
+    let age = chrono::Utc::now() - cache.saved_at;
+    (age.num_seconds() < CACHE_TTL_SECS).then_some(cache.tools)
+}
+
+fn save_cached_tools(server: &str, tools: &[String]) -> Result<(), McplugError> {
+    let cache = ToolNameCache {
+        tools: tools.to_vec(),
+        saved_at: chrono::Utc::now(),
+    };
+    default_store()?.save(server, TOOL_CACHE_KEY, &cache)
+}
+
+/// Tool names for a server, from the cache or by querying the server.
+///
+/// A server whose config references a secret is not queried, so pressing
+/// Tab never runs a `${cmd:...}` command or reads the keychain.
+async fn tool_names(server: &str) -> Vec<String> {
+    if let Some(tools) = load_cached_tools(server) {
+        return tools;
+    }
+    let Ok(config) = load_config(None) else {
+        return Vec::new();
+    };
+    if config.mcp_servers.get(server).is_none_or(references_secrets) {
+        return Vec::new();
+    }
+    let Ok(mut transport) = connect_to_server(server, &config, None, None) else {
+        return Vec::new();
+    };
+    let fetched = tokio::time::timeout(Duration::from_secs(COMPLETE_TIMEOUT_SECS), async {
+        transport.initialize().await?;
+        transport.list_tools().await
+    })
+    .await;
+    let _ = transport.close().await;
+
+    match fetched {
+        Ok(Ok(tools)) => {
+            let names: Vec<String> = tools.into_iter().map(|t| t.name).collect();
+            let _ = save_cached_tools(server, &names);
+            names
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Run the hidden `__complete` helper used by the shell scripts.
+///
+/// Prints one candidate per line. Failures produce no candidates rather
+/// than errors, so a broken config never spams the terminal mid-completion.
+pub async fn run_complete(cmd: &clap::Command, words: &[String]) -> Result<(), McplugError> {
+    // Config values are not expanded; only names are needed
+    let config = load_merged_config(None).ok();
+    let servers: Vec<String> = config
+        .as_ref()
+        .map(|c| c.enabled_servers())
+        .unwrap_or_default();
+
+    let candidates = match complete_words(cmd, words, &servers) {
+        Completion::Values(values) => values,
+        Completion::ToolRef { server, prefix } => match config {
+            Some(ref config) if config.mcp_servers.contains_key(&server) => {
+                let mut tools: Vec<String> = tool_names(&server)
+                    .await
+                    .into_iter()
+                    .filter(|t| t.starts_with(&prefix))
+                    .map(|t| format!("{server}.{t}"))
+                    .collect();
+                tools.sort();
+                tools
+            }
+            _ => Vec::new(),
+        },
+    };
+
+    for candidate in candidates {
+        println!("{}", candidate);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, ArgAction, Command};
+
+    fn test_cmd() -> Command {
+        Command::new("mcplug")
+            .subcommand(
+                Command::new("call")
+                    .arg(Arg::new("tool_ref"))
+                    .arg(Arg::new("args").num_args(0..))
+                    .arg(Arg::new("raw").long("raw").action(ArgAction::SetTrue))
+                    .arg(Arg::new("output").long("output")),
+            )
+            .subcommand(Command::new("list").arg(Arg::new("server")))
+            .subcommand(Command::new("info").arg(Arg::new("server")))
+            .subcommand(Command::new("__complete").hide(true))
+    }
+
+    fn words(ws: &[&str]) -> Vec<String> {
+        ws.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn servers() -> Vec<String> {
+        vec!["firecrawl".into(), "filesystem".into(), "github".into()]
+    }
+
+    #[test]
+    fn completes_subcommands() {
+        let c = complete_words(&test_cmd(), &words(&[""]), &servers());
+        assert_eq!(c, Completion::Values(words(&["call", "info", "list"])));
+        let c = complete_words(&test_cmd(), &words(&["l"]), &servers());
+        assert_eq!(c, Completion::Values(words(&["list"])));
+    }
+
+    #[test]
+    fn completes_server_prefix_for_tool_ref() {
+        let c = complete_words(&test_cmd(), &words(&["call", "fi"]), &servers());
+        assert_eq!(
+            c,
+            Completion::Values(words(&["filesystem.", "firecrawl."]))
+        );
+    }
+
+    #[test]
+    fn completes_tool_after_dot() {
+        let c = complete_words(&test_cmd(), &words(&["call", "firecrawl.sc"]), &servers());
+        assert_eq!(
+            c,
+            Completion::ToolRef {
+                server: "firecrawl".into(),
+                prefix: "sc".into()
+            }
+        );
+    }
+
+    #[test]
+    fn completes_server_names() {
+        let c = complete_words(&test_cmd(), &words(&["info", "g"]), &servers());
+        assert_eq!(c, Completion::Values(words(&["github"])));
+    }
+
+    #[test]
+    fn completes_flags() {
+        let c = complete_words(&test_cmd(), &words(&["call", "--r"]), &servers());
+        assert_eq!(c, Completion::Values(words(&["--raw"])));
+    }
+
+    #[test]
+    fn flags_do_not_count_as_positionals() {
+        let c = complete_words(&test_cmd(), &words(&["call", "--raw", "gi"]), &servers());
+        assert_eq!(c, Completion::Values(words(&["github."])));
+        let c = complete_words(
+            &test_cmd(),
+            &words(&["call", "--output", "json", "gi"]),
+            &servers(),
+        );
+        assert_eq!(c, Completion::Values(words(&["github."])));
+    }
+
+    #[test]
+    fn no_completion_for_option_values() {
+        let c = complete_words(&test_cmd(), &words(&["call", "--output", ""]), &servers());
+        assert_eq!(c, Completion::Values(Vec::new()));
+    }
+
+    #[test]
+    fn no_completion_for_tool_arguments() {
+        let c = complete_words(&test_cmd(), &words(&["call", "github.search", "q"]), &servers());
+        assert_eq!(c, Completion::Values(Vec::new()));
+    }
+
+    #[test]
+    fn scripts_for_supported_shells() {
+        for shell in SHELLS {
+            let script = completion_script(shell).unwrap();
+            assert!(script.contains("mcplug __complete"), "{shell}");
+        }
+        assert!(completion_script("powershell").is_err());
+    }
+}
@@ -3,47 +3,83 @@
 use std::collections::HashMap;
 use std::io::{self, BufRead, Write};
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 use colored::Colorize;
 
-use crate::config::loader::{discover_config_files, load_config, strip_jsonc_comments};
+use crate::config::editors::{
+    export_editor_servers, export_key, export_path, parse_server_snippet,
+};
+use crate::config::loader::{
+    discover_config_files, load_config, load_config_layers, load_merged_config,
+    strip_jsonc_comments,
+};
 use crate::config::types::{Lifecycle, McplugConfig, ServerConfig};
+use crate::config::validate::{validate_files, Issue, Severity};
 use crate::error::McplugError;
+use crate::oauth::OAuthClient;
+use crate::types::{ServerInfo, ToolDefinition};
+
+use super::connection::connect_to_server;
+
+/// Timeout for the test connection made by `config add`.
+const PROBE_TIMEOUT_SECS: u64 = 30;
+
+/// Startup time above which keep-alive is suggested.
+const SLOW_STARTUP_MS: u64 = 2000;
+
+/// Tools listed after a successful test connection.
+pub(crate) const TOOL_PREVIEW_LIMIT: usize = 10;
+
+/// OAuth timeout when logging in from the wizard.
+const DEFAULT_OAUTH_TIMEOUT_MS: u64 = 60000;
 
 /// Holds a server config together with the file it was first defined in.
-struct AnnotatedEntry {
-    name: String,
-    config: ServerConfig,
-    source: PathBuf,
+pub(crate) struct AnnotatedEntry {
+    pub(crate) name: String,
+    pub(crate) config: ServerConfig,
+    pub(crate) source: PathBuf,
 }
 
-/// Load configs with source annotations.
-///
-/// Walks the discovered config files in precedence order and records, for each
-/// server name, the first file it appeared in.  The merged config from
-/// `load_config` is authoritative for the actual values (env expansion, editor
-/// imports, etc.), but we need the per-file walk to map server -> source.
-fn load_annotated(cli_config: Option<&str>) -> Result<Vec<AnnotatedEntry>, McplugError> {
+/// Map each server name to the first config file that defines it.
+fn server_sources(cli_config: Option<&str>) -> HashMap<String, PathBuf> {
     let config_files = discover_config_files(cli_config);
 
     // Track which server came from which file (first occurrence wins).
     let mut source_map: HashMap<String, PathBuf> = HashMap::new();
     for path in &config_files {
-        let content = match std::fs::read_to_string(path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-        let stripped = strip_jsonc_comments(&content);
-        let cfg: McplugConfig = match serde_json::from_str(&stripped) {
-            Ok(c) => c,
+        // Servers from an `extends` base are attributed to the base file
+        let layers = match load_config_layers(path) {
+            Ok(layers) => layers,
             Err(_) => continue,
         };
-        for name in cfg.mcp_servers.keys() {
-            source_map
-                .entry(name.clone())
-                .or_insert_with(|| path.clone());
+        for (layer_path, cfg) in layers {
+            for name in cfg.mcp_servers.keys() {
+                source_map
+                    .entry(name.clone())
+                    .or_insert_with(|| layer_path.clone());
+            }
         }
     }
+    source_map
+}
+
+/// The config file that defines `name`, or `None` for editor imports and
+/// unknown servers.
+pub(crate) fn find_server_source(name: &str, cli_config: Option<&str>) -> Option<PathBuf> {
+    server_sources(cli_config).remove(name)
+}
+
+/// Load configs with source annotations.
+///
+/// Walks the discovered config files in precedence order and records, for each
+/// server name, the first file it appeared in.  The merged config from
+/// `load_config` is authoritative for the actual values (env expansion, editor
+/// imports, etc.), but we need the per-file walk to map server -> source.
+pub(crate) fn load_annotated(
+    cli_config: Option<&str>,
+) -> Result<Vec<AnnotatedEntry>, McplugError> {
+    let source_map = server_sources(cli_config);
 
     // Get the fully-merged (env-expanded) config.
     let merged = load_config(cli_config)?;
@@ -89,7 +125,344 @@ pub async fn run_config_show() -> Result<(), McplugError> {
     Ok(())
 }
 
-fn print_entry(entry: &AnnotatedEntry, is_tty: bool) {
+/// Check every discovered config file and print the problems found, one per
+/// line as `file:line: severity: message`. Fails if any are errors.
+pub fn run_config_validate() -> Result<(), McplugError> {
+    let files = discover_config_files(None);
+    if files.is_empty() {
+        println!("No config files found.");
+        return Ok(());
+    }
+    let issues = validate_files(&files);
+    print_issues(&issues);
+
+    let errors: Vec<_> = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .collect();
+    let warnings = issues.len() - errors.len();
+    println!(
+        "Checked {} file(s): {} error(s), {} warning(s)",
+        files.len(),
+        errors.len(),
+        warnings
+    );
+    match errors.first() {
+        None => Ok(()),
+        Some(first) => {
+            let path = if errors.iter().all(|e| e.path == first.path) {
+                first.path.clone()
+            } else {
+                PathBuf::from("<config>")
+            };
+            Err(McplugError::ConfigError {
+                path,
+                detail: format!("{} error(s) found", errors.len()),
+            })
+        }
+    }
+}
+
+/// Convert the merged config into `editor`'s MCP config format.
+///
+/// Prints the editor's config document, or with `write` merges the servers
+/// into the editor's config file, replacing entries of the same name and
+/// keeping everything else in the file.
+pub fn run_config_export(editor: &str, write: bool) -> Result<(), McplugError> {
+    let config = load_merged_config(None)?;
+    let (servers, skipped) = export_editor_servers(editor, &config.mcp_servers);
+    for name in &skipped {
+        eprintln!("Skipping '{}': {} only supports stdio servers", name, editor);
+    }
+    let key = export_key(editor);
+
+    if !write {
+        let doc = serde_json::json!({ key: servers });
+        let json_str =
+            serde_json::to_string_pretty(&doc).map_err(|e| McplugError::ConfigError {
+                path: PathBuf::from("<export>"),
+                detail: format!("Failed to serialize config: {}", e),
+            })?;
+        println!("{}", json_str);
+        return Ok(());
+    }
+
+    let path = export_path(editor).ok_or_else(|| McplugError::ConfigError {
+        path: PathBuf::from(editor),
+        detail: format!("No {} config location on this platform", editor),
+    })?;
+    // Never replace an editor file we can't read back
+    if path.exists() {
+        let content = std::fs::read_to_string(&path)?;
+        if serde_json::from_str::<serde_json::Value>(&strip_jsonc_comments(&content)).is_err() {
+            return Err(McplugError::ConfigError {
+                path,
+                detail: "Existing file is not valid JSON; not overwriting it".into(),
+            });
+        }
+    }
+    let count = servers.len();
+    edit_servers_object(&path, key, |existing| {
+        existing.extend(servers);
+        Ok(())
+    })?;
+    println!("Exported {} server(s) to {}", count, path.display());
+    Ok(())
+}
+
+/// How `config import` treats a server whose name is already in the target file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Prompt for each conflict: overwrite, rename, or skip.
+    Ask,
+    /// Replace the existing entry (`--force`).
+    Overwrite,
+    /// Import under the next free `<name>-2`, `<name>-3`, ... (`--rename`).
+    Rename,
+}
+
+impl ConflictPolicy {
+    /// The policy for the `--force` and `--rename` flags.
+    pub fn from_flags(force: bool, rename: bool) -> Self {
+        if force {
+            ConflictPolicy::Overwrite
+        } else if rename {
+            ConflictPolicy::Rename
+        } else {
+            ConflictPolicy::Ask
+        }
+    }
+}
+
+/// Merge servers from a JSON snippet (a file, or stdin for `-`) into
+/// `~/.mcplug/mcplug.json`.
+///
+/// Entries are translated like editor imports, so snippets written for any
+/// editor work. Names already in the file are resolved by `policy`; asking
+/// needs stdin, so it can't be combined with reading the snippet from it.
+pub fn run_config_import(file: &str, policy: ConflictPolicy) -> Result<(), McplugError> {
+    let source = PathBuf::from(file);
+    let content = if file == "-" {
+        io::read_to_string(io::stdin())?
+    } else {
+        std::fs::read_to_string(&source).map_err(|e| McplugError::ConfigError {
+            path: source.clone(),
+            detail: format!("Cannot read file: {}", e),
+        })?
+    };
+    let snippet = parse_server_snippet(&content).map_err(|detail| McplugError::ConfigError {
+        path: source.clone(),
+        detail,
+    })?;
+    let servers = snippet.servers;
+    for name in &snippet.invalid {
+        eprintln!("Skipping '{}': neither 'command' nor 'baseUrl'", name);
+    }
+
+    let config_path = default_config_path()?;
+    let mut taken = existing_server_names(&config_path);
+    let has_conflicts = servers.iter().any(|(name, _)| taken.contains(name));
+    if has_conflicts && policy == ConflictPolicy::Ask && file == "-" {
+        return Err(McplugError::ConfigError {
+            path: config_path,
+            detail: "Some servers already exist; use --force or --rename when reading from stdin"
+                .into(),
+        });
+    }
+
+    let stdin = io::stdin();
+    let mut reader = stdin.lock();
+    let mut imported = Vec::new();
+    for (name, server) in servers {
+        let target = if taken.contains(&name) {
+            match resolve_conflict(&mut reader, &name, &taken, policy)? {
+                Some(target) => target,
+                None => {
+                    println!("Skipped '{}'", name);
+                    continue;
+                }
+            }
+        } else {
+            name.clone()
+        };
+        if target != name {
+            println!("Importing '{}' as '{}'", name, target);
+        }
+        let value = serde_json::to_value(&server).map_err(|e| McplugError::ConfigError {
+            path: config_path.clone(),
+            detail: format!("Failed to serialize server config: {}", e),
+        })?;
+        taken.push(target.clone());
+        imported.push((target, value));
+    }
+
+    let count = imported.len();
+    if count > 0 {
+        edit_config_file(&config_path, |existing| {
+            existing.extend(imported);
+            Ok(())
+        })?;
+    }
+    println!("Imported {} server(s) into {}", count, config_path.display());
+    Ok(())
+}
+
+/// Server names already defined in a config file; none if it's missing or
+/// unreadable.
+fn existing_server_names(path: &PathBuf) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| {
+            serde_json::from_str::<serde_json::Value>(&strip_jsonc_comments(&content)).ok()
+        })
+        .and_then(|doc| {
+            doc.get("mcpServers")
+                .and_then(|s| s.as_object())
+                .map(|servers| servers.keys().cloned().collect())
+        })
+        .unwrap_or_default()
+}
+
+/// The name to import a conflicting server under, or `None` to skip it.
+fn resolve_conflict(
+    reader: &mut impl BufRead,
+    name: &str,
+    taken: &[String],
+    policy: ConflictPolicy,
+) -> Result<Option<String>, McplugError> {
+    let suggested = free_name(name, taken);
+    match policy {
+        ConflictPolicy::Overwrite => Ok(Some(name.to_string())),
+        ConflictPolicy::Rename => Ok(Some(suggested)),
+        ConflictPolicy::Ask => {
+            let message = format!(
+                "Server '{}' already exists. [o]verwrite/[r]ename/[s]kip: ",
+                name
+            );
+            let choice = prompt_choice(
+                reader,
+                &message,
+                &["o", "overwrite", "r", "rename", "s", "skip"],
+            )?;
+            match choice.as_str() {
+                "o" | "overwrite" => Ok(Some(name.to_string())),
+                "s" | "skip" => Ok(None),
+                _ => loop {
+                    let answer = prompt(reader, &format!("New name [{}]: ", suggested))?;
+                    if answer.is_empty() {
+                        return Ok(Some(suggested));
+                    }
+                    if !taken.contains(&answer) {
+                        return Ok(Some(answer));
+                    }
+                    println!("'{}' is taken too.", answer);
+                },
+            }
+        }
+    }
+}
+
+/// `name-2`, `name-3`, ...: the first that isn't `taken`.
+fn free_name(name: &str, taken: &[String]) -> String {
+    (2..)
+        .map(|n| format!("{}-{}", name, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap()
+}
+
+/// Print validation issues one per line, colored on a TTY.
+fn print_issues(issues: &[Issue]) {
+    let is_tty = atty_stdout();
+    for issue in issues {
+        let line = issue.to_string();
+        match issue.severity {
+            Severity::Error if is_tty => println!("{}", line.red()),
+            Severity::Warning if is_tty => println!("{}", line.yellow()),
+            _ => println!("{}", line),
+        }
+    }
+}
+
+/// Open the highest-precedence config file (or `~/.mcplug/mcplug.json`,
+/// created if needed) in `$VISUAL`/`$EDITOR`, then validate it.
+///
+/// While the saved file has errors, offers to reopen it; declining leaves
+/// the file as saved and fails.
+pub fn run_config_edit() -> Result<(), McplugError> {
+    let path = match discover_config_files(None).into_iter().next() {
+        Some(path) => path,
+        None => {
+            let path = default_config_path()?;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, "{\n  \"mcpServers\": {}\n}\n")?;
+            path
+        }
+    };
+    let editor = editor_command();
+    let stdin = io::stdin();
+    let mut reader = stdin.lock();
+
+    loop {
+        open_in_editor(&editor, &path)?;
+        let issues = validate_files(std::slice::from_ref(&path));
+        print_issues(&issues);
+        let errors = issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+            .count();
+        if errors == 0 {
+            println!("{} is valid", path.display());
+            return Ok(());
+        }
+        if !prompt_yes_no(&mut reader, "Config has errors. Edit again? [Y/n]: ", true)? {
+            return Err(McplugError::ConfigError {
+                path,
+                detail: format!("{} error(s) found", errors),
+            });
+        }
+    }
+}
+
+/// The editor to run: `$VISUAL`, then `$EDITOR`, then a platform default.
+fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                "notepad".into()
+            } else {
+                "vi".into()
+            }
+        })
+}
+
+/// Run `editor` (a program optionally followed by arguments, such as
+/// `code --wait`) on `path` and wait for it to exit.
+fn open_in_editor(editor: &str, path: &PathBuf) -> Result<(), McplugError> {
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|e| McplugError::ConfigError {
+            path: path.clone(),
+            detail: format!("Cannot run editor '{}': {}", program, e),
+        })?;
+    if !status.success() {
+        return Err(McplugError::ConfigError {
+            path: path.clone(),
+            detail: format!("Editor '{}' exited with {}", program, status),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn print_entry(entry: &AnnotatedEntry, is_tty: bool) {
     let name = if is_tty {
         entry.name.bold().cyan().to_string()
     } else {
@@ -131,6 +504,24 @@ fn print_entry(entry: &AnnotatedEntry, is_tty: bool) {
         println!("  {}: {}", cmd_label, full_cmd);
     }
 
+    if entry.config.disabled {
+        let label = if is_tty {
+            "Status".dimmed().to_string()
+        } else {
+            "Status".to_string()
+        };
+        println!("  {}: disabled", label);
+    }
+
+    if !entry.config.tags.is_empty() {
+        let label = if is_tty {
+            "Tags".dimmed().to_string()
+        } else {
+            "Tags".to_string()
+        };
+        println!("  {}: {}", label, entry.config.tags.join(", "));
+    }
+
     // Description
     if let Some(ref desc) = entry.config.description {
         let label = if is_tty {
@@ -164,6 +555,10 @@ fn print_entry(entry: &AnnotatedEntry, is_tty: bool) {
 }
 
 /// Interactive wizard to add a new server definition.
+///
+/// The entry is test-connected before it is saved: discovered tools are shown
+/// for confirmation, servers needing auth are offered an OAuth login, and the
+/// measured startup time drives the suggested lifecycle.
 pub async fn run_config_add() -> Result<(), McplugError> {
     let stdin = io::stdin();
     let mut reader = stdin.lock();
@@ -190,6 +585,18 @@ pub async fn run_config_add() -> Result<(), McplugError> {
         env: HashMap::new(),
         headers: HashMap::new(),
         lifecycle: None,
+        tool_pins: HashMap::new(),
+        disabled: false,
+        install_timeout: None,
+        log_level: None,
+        sensitive_args: Vec::new(),
+        timeout_ms: None,
+        list_timeout_ms: None,
+        allow_http: None,
+        auth: None,
+        tags: Vec::new(),
+        transport: None,
+        retry: None,
     };
 
     match transport.as_str() {
@@ -228,15 +635,76 @@ pub async fn run_config_add() -> Result<(), McplugError> {
         server.description = Some(desc);
     }
 
+    // Validate the entry against the live server before saving it
+    println!("Testing connection to '{}'...", name);
+    let probe = probe_server(&name, &server).await;
+    let suggested = match probe {
+        Ok(ref result) => {
+            println!(
+                "Connected to {} v{} in {} ms.",
+                result.info.name,
+                result.info.version,
+                result.startup.as_millis()
+            );
+            println!("Discovered {} tool(s):", result.tools.len());
+            for line in tool_preview(&result.tools, TOOL_PREVIEW_LIMIT) {
+                println!("  {}", line);
+            }
+            if !prompt_yes_no(&mut reader, "Add this server? [Y/n]: ", true)? {
+                println!("Server '{}' not added.", name);
+                return Ok(());
+            }
+            Some(suggest_lifecycle(result.startup))
+        }
+        Err(ref e) if is_auth_error(e) => {
+            println!("Server '{}' requires authentication.", name);
+            if let Some(ref url) = server.base_url {
+                if prompt_yes_no(&mut reader, "Run OAuth login now? [Y/n]: ", true)? {
+                    let timeout = Duration::from_millis(DEFAULT_OAUTH_TIMEOUT_MS);
+                    let client = OAuthClient::from_config(server.auth.as_ref());
+                    match crate::oauth::flow::run_oauth_flow(url, &name, &client, timeout).await {
+                        Ok(_) => println!("Authentication successful for '{}'", name),
+                        Err(e) => println!(
+                            "Authentication failed: {}. Run 'mcplug auth {}' later.",
+                            e, name
+                        ),
+                    }
+                }
+            }
+            None
+        }
+        Err(ref e) => {
+            println!("Connection failed: {}", e);
+            if !prompt_yes_no(&mut reader, "Save this server anyway? [y/N]: ", false)? {
+                println!("Server '{}' not added.", name);
+                return Ok(());
+            }
+            None
+        }
+    };
+
+    let lifecycle_prompt = match suggested {
+        Some(Lifecycle::KeepAlive) => {
+            "Lifecycle [ephemeral/keep-alive] (suggested: keep-alive, slow startup): "
+        }
+        Some(Lifecycle::Ephemeral) => {
+            "Lifecycle [ephemeral/keep-alive] (suggested: ephemeral, fast startup): "
+        }
+        None => "Lifecycle [ephemeral/keep-alive] (default: ephemeral): ",
+    };
     let lifecycle = prompt_choice(
         &mut reader,
-        "Lifecycle [ephemeral/keep-alive] (default: ephemeral): ",
+        lifecycle_prompt,
         &["ephemeral", "keep-alive", ""],
     )?;
     server.lifecycle = match lifecycle.as_str() {
         "keep-alive" => Some(Lifecycle::KeepAlive),
         "ephemeral" => Some(Lifecycle::Ephemeral),
-        _ => None, // empty -> default
+        // empty -> suggestion, or the default when there is none
+        _ => match suggested {
+            Some(Lifecycle::KeepAlive) => Some(Lifecycle::KeepAlive),
+            _ => None,
+        },
     };
 
     // Write to ~/.mcplug/mcplug.json
@@ -247,6 +715,92 @@ pub async fn run_config_add() -> Result<(), McplugError> {
     Ok(())
 }
 
+/// Outcome of test-connecting to a server during `config add`.
+pub(crate) struct ProbeResult {
+    pub(crate) info: ServerInfo,
+    pub(crate) tools: Vec<ToolDefinition>,
+    /// Time from spawning/connecting until `initialize` completed.
+    pub(crate) startup: Duration,
+}
+
+/// Connect to a not-yet-saved server entry and list its tools.
+pub(crate) async fn probe_server(name: &str, server: &ServerConfig) -> Result<ProbeResult, McplugError> {
+    let config = McplugConfig {
+        mcp_servers: HashMap::from([(name.to_string(), server.clone())]),
+        imports: vec![],
+        state: None,
+        groups: HashMap::new(),
+        extends: Vec::new(),
+        defaults: None,
+    };
+    let timeout = Duration::from_secs(PROBE_TIMEOUT_SECS);
+    let started = Instant::now();
+    let mut transport = connect_to_server(name, &config, None, None)?;
+    transport.wait_ready().await?;
+
+    let result = tokio::time::timeout(timeout, async {
+        let info = transport.initialize().await?;
+        let startup = started.elapsed();
+        let tools = transport.list_tools().await?;
+        Ok(ProbeResult {
+            info,
+            tools,
+            startup,
+        })
+    })
+    .await
+    .unwrap_or_else(|_| {
+        Err(McplugError::Timeout {
+            server: name.to_string(),
+            tool: None,
+            duration: timeout,
+        })
+    });
+
+    let _ = transport.close().await;
+    result
+}
+
+/// Whether a connection error means the server wants credentials.
+fn is_auth_error(err: &McplugError) -> bool {
+    match err {
+        McplugError::AuthRequired(_) => true,
+        McplugError::ConnectionFailed { source, .. } => {
+            let msg = source.to_string();
+            msg.contains("HTTP 401") || msg.contains("HTTP 403")
+        }
+        _ => false,
+    }
+}
+
+/// Suggest keep-alive for servers that are slow to start.
+fn suggest_lifecycle(startup: Duration) -> Lifecycle {
+    if startup >= Duration::from_millis(SLOW_STARTUP_MS) {
+        Lifecycle::KeepAlive
+    } else {
+        Lifecycle::Ephemeral
+    }
+}
+
+/// One line per tool, truncated to `limit` entries.
+pub(crate) fn tool_preview(tools: &[ToolDefinition], limit: usize) -> Vec<String> {
+    let mut lines: Vec<String> = tools
+        .iter()
+        .take(limit)
+        .map(|t| {
+            if t.description.is_empty() {
+                t.name.clone()
+            } else {
+                format!("{} — {}", t.name, t.description)
+            }
+        })
+        .collect();
+    if tools.len() > limit {
+        lines.push(format!("... and {} more", tools.len() - limit));
+    }
+    lines
+}
+
 fn default_config_path() -> Result<PathBuf, McplugError> {
     let home = dirs::home_dir().ok_or_else(|| McplugError::ConfigError {
         path: PathBuf::from("~"),
@@ -260,6 +814,34 @@ fn write_server_to_config(
     path: &PathBuf,
     name: &str,
     server: &ServerConfig,
+) -> Result<(), McplugError> {
+    let server_value = serde_json::to_value(server).map_err(|e| McplugError::ConfigError {
+        path: path.clone(),
+        detail: format!("Failed to serialize server config: {}", e),
+    })?;
+
+    edit_config_file(path, |servers| {
+        servers.insert(name.to_string(), server_value);
+        Ok(())
+    })
+}
+
+/// Read or create a config file, let `edit` modify its `mcpServers` object,
+/// and write it back.
+///
+/// JSONC comments are not preserved.
+pub(crate) fn edit_config_file(
+    path: &PathBuf,
+    edit: impl FnOnce(&mut serde_json::Map<String, serde_json::Value>) -> Result<(), McplugError>,
+) -> Result<(), McplugError> {
+    edit_servers_object(path, "mcpServers", edit)
+}
+
+/// Like `edit_config_file`, for the server map under `key`.
+fn edit_servers_object(
+    path: &PathBuf,
+    key: &str,
+    edit: impl FnOnce(&mut serde_json::Map<String, serde_json::Value>) -> Result<(), McplugError>,
 ) -> Result<(), McplugError> {
     // Ensure parent directory exists.
     if let Some(parent) = path.parent() {
@@ -275,25 +857,23 @@ fn write_server_to_config(
         serde_json::json!({})
     };
 
-    // Ensure mcpServers object exists.
-    if doc.get("mcpServers").is_none() {
+    // Ensure the servers object exists.
+    if doc.get(key).is_none() {
         doc.as_object_mut()
             .unwrap()
-            .insert("mcpServers".into(), serde_json::json!({}));
+            .insert(key.into(), serde_json::json!({}));
     }
 
     let servers = doc
-        .get_mut("mcpServers")
+        .get_mut(key)
         .unwrap()
         .as_object_mut()
-        .unwrap();
-
-    let server_value = serde_json::to_value(server).map_err(|e| McplugError::ConfigError {
-        path: path.clone(),
-        detail: format!("Failed to serialize server config: {}", e),
-    })?;
+        .ok_or_else(|| McplugError::ConfigError {
+            path: path.clone(),
+            detail: format!("'{}' is not an object", key),
+        })?;
 
-    servers.insert(name.to_string(), server_value);
+    edit(servers)?;
 
     let json_str =
         serde_json::to_string_pretty(&doc).map_err(|e| McplugError::ConfigError {
@@ -336,6 +916,22 @@ fn prompt_choice(
     }
 }
 
+fn prompt_yes_no(
+    reader: &mut impl BufRead,
+    message: &str,
+    default: bool,
+) -> Result<bool, McplugError> {
+    loop {
+        let answer = prompt(reader, message)?.to_lowercase();
+        match answer.as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => println!("Please answer y or n."),
+        }
+    }
+}
+
 fn atty_stdout() -> bool {
     std::io::IsTerminal::is_terminal(&std::io::stdout())
 }
@@ -359,6 +955,18 @@ mod tests {
                 env: HashMap::new(),
                 headers: HashMap::new(),
                 lifecycle: Some(Lifecycle::Ephemeral),
+                tool_pins: HashMap::new(),
+                disabled: false,
+                install_timeout: None,
+                log_level: None,
+                sensitive_args: Vec::new(),
+                timeout_ms: None,
+                list_timeout_ms: None,
+                allow_http: None,
+                auth: None,
+                tags: Vec::new(),
+                transport: None,
+                retry: None,
             },
             source: PathBuf::from("/home/user/.mcplug/mcplug.json"),
         };
@@ -378,6 +986,18 @@ mod tests {
                 env: HashMap::new(),
                 headers: HashMap::new(),
                 lifecycle: Some(Lifecycle::KeepAlive),
+                tool_pins: HashMap::new(),
+                disabled: false,
+                install_timeout: None,
+                log_level: None,
+                sensitive_args: Vec::new(),
+                timeout_ms: None,
+                list_timeout_ms: None,
+                allow_http: None,
+                auth: None,
+                tags: Vec::new(),
+                transport: None,
+                retry: None,
             },
             source: PathBuf::from("./config/mcplug.json"),
         };
@@ -396,6 +1016,18 @@ mod tests {
                 env: HashMap::new(),
                 headers: HashMap::new(),
                 lifecycle: None,
+                tool_pins: HashMap::new(),
+                disabled: false,
+                install_timeout: None,
+                log_level: None,
+                sensitive_args: Vec::new(),
+                timeout_ms: None,
+                list_timeout_ms: None,
+                allow_http: None,
+                auth: None,
+                tags: Vec::new(),
+                transport: None,
+                retry: None,
             },
             source: PathBuf::from("<editor-import>"),
         };
@@ -417,6 +1049,18 @@ mod tests {
             env: HashMap::new(),
             headers: HashMap::new(),
             lifecycle: Some(Lifecycle::Ephemeral),
+            tool_pins: HashMap::new(),
+            disabled: false,
+            install_timeout: None,
+            log_level: None,
+            sensitive_args: Vec::new(),
+            timeout_ms: None,
+            list_timeout_ms: None,
+            allow_http: None,
+            auth: None,
+            tags: Vec::new(),
+            transport: None,
+            retry: None,
         };
 
         write_server_to_config(&config_path, "my-server", &server).unwrap();
@@ -460,6 +1104,18 @@ mod tests {
             env: HashMap::new(),
             headers: HashMap::new(),
             lifecycle: Some(Lifecycle::KeepAlive),
+            tool_pins: HashMap::new(),
+            disabled: false,
+            install_timeout: None,
+            log_level: None,
+            sensitive_args: Vec::new(),
+            timeout_ms: None,
+            list_timeout_ms: None,
+            allow_http: None,
+            auth: None,
+            tags: Vec::new(),
+            transport: None,
+            retry: None,
         };
 
         write_server_to_config(&config_path, "new-server", &new_server).unwrap();
@@ -495,6 +1151,18 @@ mod tests {
             env: HashMap::new(),
             headers: HashMap::new(),
             lifecycle: None,
+            tool_pins: HashMap::new(),
+            disabled: false,
+            install_timeout: None,
+            log_level: None,
+            sensitive_args: Vec::new(),
+            timeout_ms: None,
+            list_timeout_ms: None,
+            allow_http: None,
+            auth: None,
+            tags: Vec::new(),
+            transport: None,
+            retry: None,
         };
 
         write_server_to_config(&config_path, "srv", &server).unwrap();
@@ -536,6 +1204,169 @@ mod tests {
         assert_eq!(result, "stdio");
     }
 
+    #[test]
+    fn prompt_yes_no_default_and_answers() {
+        assert!(prompt_yes_no(&mut Cursor::new(b"\n"), "? ", true).unwrap());
+        assert!(!prompt_yes_no(&mut Cursor::new(b"\n"), "? ", false).unwrap());
+        assert!(prompt_yes_no(&mut Cursor::new(b"YES\n"), "? ", false).unwrap());
+        assert!(!prompt_yes_no(&mut Cursor::new(b"maybe\nn\n"), "? ", true).unwrap());
+    }
+
+    // --- wizard helper tests ---
+
+    #[test]
+    fn suggest_lifecycle_by_startup_time() {
+        assert!(matches!(
+            suggest_lifecycle(Duration::from_millis(150)),
+            Lifecycle::Ephemeral
+        ));
+        assert!(matches!(
+            suggest_lifecycle(Duration::from_millis(SLOW_STARTUP_MS)),
+            Lifecycle::KeepAlive
+        ));
+    }
+
+    #[test]
+    fn is_auth_error_detects_401() {
+        assert!(is_auth_error(&McplugError::AuthRequired("s".into())));
+        assert!(is_auth_error(&McplugError::ConnectionFailed {
+            server: "s".into(),
+            source: "HTTP 401 Unauthorized: ".into(),
+        }));
+        assert!(!is_auth_error(&McplugError::ConnectionFailed {
+            server: "s".into(),
+            source: "HTTP 500 Internal Server Error: ".into(),
+        }));
+        assert!(!is_auth_error(&McplugError::ServerNotFound("s".into())));
+    }
+
+    #[test]
+    fn tool_preview_truncates() {
+        let tools: Vec<ToolDefinition> = (0..4)
+            .map(|i| ToolDefinition {
+                name: format!("tool{i}"),
+                description: if i == 0 { String::new() } else { "Does things".into() },
+                input_schema: serde_json::json!({}),
+                annotations: None,
+                output_schema: None,
+            })
+            .collect();
+        let lines = tool_preview(&tools, 2);
+        assert_eq!(lines, vec!["tool0", "tool1 — Does things", "... and 2 more"]);
+        assert_eq!(tool_preview(&tools, 10).len(), 4);
+    }
+
+    // --- edit_config_file tests ---
+
+    #[test]
+    fn edit_config_file_preserves_other_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mcplug.json");
+        std::fs::write(
+            &path,
+            r#"{"mcpServers": {"a": {"command": "x"}, "b": {"command": "y"}}, "imports": ["cursor"]}"#,
+        )
+        .unwrap();
+        edit_config_file(&path, |servers| {
+            servers.remove("a");
+            Ok(())
+        })
+        .unwrap();
+        let doc: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(doc["mcpServers"].get("a").is_none());
+        assert_eq!(doc["mcpServers"]["b"]["command"], "y");
+        assert_eq!(doc["imports"][0], "cursor");
+    }
+
+    #[test]
+    fn edit_config_file_propagates_errors_without_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mcplug.json");
+        std::fs::write(&path, r#"{"mcpServers": {}}"#).unwrap();
+        let result = edit_config_file(&path, |_| {
+            Err(McplugError::ServerNotFound("missing".into()))
+        });
+        assert!(result.is_err());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            r#"{"mcpServers": {}}"#
+        );
+    }
+
+    #[test]
+    fn edit_servers_object_uses_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mcp.json");
+        std::fs::write(&path, r#"{"servers": {"keep": {"command": "a"}}, "inputs": []}"#).unwrap();
+        edit_servers_object(&path, "servers", |servers| {
+            servers.insert("new".into(), serde_json::json!({"command": "b"}));
+            Ok(())
+        })
+        .unwrap();
+        let doc: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(doc["servers"]["keep"]["command"], "a");
+        assert_eq!(doc["servers"]["new"]["command"], "b");
+        assert!(doc["inputs"].is_array());
+        assert!(doc.get("mcpServers").is_none());
+    }
+
+    // --- config edit tests ---
+
+    #[cfg(unix)]
+    #[test]
+    fn open_in_editor_passes_editor_args_and_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mcplug.json");
+        std::fs::write(&path, "{}").unwrap();
+        // `sh -c '<script>' <name> <path>`: $0 is the name, $1 the file
+        open_in_editor("sh -c echo>$1 editor", &path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "\n");
+        assert!(open_in_editor("false", &path).is_err());
+        assert!(open_in_editor("/nonexistent/editor", &path).is_err());
+    }
+
+    // --- config import tests ---
+
+    #[test]
+    fn free_name_skips_taken_suffixes() {
+        let taken = vec!["fs".to_string(), "fs-2".to_string()];
+        assert_eq!(free_name("fs", &taken), "fs-3");
+        assert_eq!(free_name("git", &taken), "git-2");
+    }
+
+    #[test]
+    fn resolve_conflict_by_policy_and_prompt() {
+        let taken = vec!["fs".to_string(), "fs-2".to_string()];
+        let mut none = Cursor::new(b"");
+        assert_eq!(
+            resolve_conflict(&mut none, "fs", &taken, ConflictPolicy::Overwrite).unwrap(),
+            Some("fs".into())
+        );
+        assert_eq!(
+            resolve_conflict(&mut none, "fs", &taken, ConflictPolicy::Rename).unwrap(),
+            Some("fs-3".into())
+        );
+
+        let ask = |input: &'static [u8]| {
+            resolve_conflict(&mut Cursor::new(input), "fs", &taken, ConflictPolicy::Ask).unwrap()
+        };
+        assert_eq!(ask(b"o\n"), Some("fs".into()));
+        assert_eq!(ask(b"skip\n"), None);
+        assert_eq!(ask(b"r\n\n"), Some("fs-3".into()));
+        assert_eq!(ask(b"r\nfs-2\nfiles\n"), Some("files".into()));
+    }
+
+    #[test]
+    fn existing_server_names_reads_jsonc() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mcplug.json");
+        assert!(existing_server_names(&path).is_empty());
+        std::fs::write(&path, "{\n  // mine\n  \"mcpServers\": {\"a\": {}}\n}").unwrap();
+        assert_eq!(existing_server_names(&path), vec!["a"]);
+    }
+
     // --- load_annotated tests ---
 
     #[test]
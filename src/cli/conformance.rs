@@ -0,0 +1,680 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config::load_config;
+use crate::error::McplugError;
+use crate::transport::McpTransport;
+use crate::transports::jsonrpc::PROTOCOL_VERSIONS;
+
+use super::connection::connect_to_server;
+
+/// Default timeout for each request.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Pages of `tools/list` followed before pagination is reported as endless.
+const MAX_PAGES: usize = 100;
+
+/// Names that no real server should know, so the error checks get errors.
+const UNKNOWN_METHOD: &str = "mcplug/conformance/unknown";
+const UNKNOWN_TOOL: &str = "mcplug-conformance-unknown-tool";
+const UNKNOWN_NOTIFICATION: &str = "notifications/mcplug/conformance";
+const INVALID_CURSOR: &str = "mcplug-conformance-invalid-cursor";
+
+/// JSON-RPC error codes the checks expect.
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Get the timeout from the environment variable or use the default.
+fn get_timeout() -> Duration {
+    std::env::var("MCPLUG_CALL_TIMEOUT")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+}
+
+/// The outcome of one check. `Warn` marks a missed SHOULD of the spec and
+/// doesn't fail the run; `Skip` marks a check that didn't apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+    Skip,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Pass => "PASS",
+            Status::Warn => "WARN",
+            Status::Fail => "FAIL",
+            Status::Skip => "SKIP",
+        }
+    }
+}
+
+/// One conformance check and what was observed.
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// The part of the protocol checked: `initialize`, `capabilities`,
+    /// `tools/list`, `errors`, or `notifications`.
+    pub section: &'static str,
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+}
+
+/// Run the conformance command.
+///
+/// Connects to the server, runs every check, and prints a pass/fail report
+/// (or JSON with `json`). Fails when any check fails.
+pub async fn run_conformance(
+    server: &str,
+    http_url: Option<&str>,
+    stdio: Option<&str>,
+    json: bool,
+) -> Result<(), McplugError> {
+    let config = load_config(None)?;
+    let mut transport = connect_to_server(server, &config, http_url, stdio)?;
+    let checks = check_conformance(transport.as_mut(), get_timeout()).await;
+    let _ = transport.close().await;
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let (passed, warned, failed, skipped) = (
+        count(Status::Pass),
+        count(Status::Warn),
+        count(Status::Fail),
+        count(Status::Skip),
+    );
+    if json {
+        let report = json!({
+            "server": server,
+            "passed": passed,
+            "warnings": warned,
+            "failed": failed,
+            "skipped": skipped,
+            "checks": checks,
+        });
+        println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default());
+    } else {
+        print!("{}", format_report(&checks, std::io::stdout().is_terminal()));
+        println!(
+            "\n{} passed, {} {}, {} failed, {} skipped",
+            passed,
+            warned,
+            plural(warned, "warning"),
+            failed,
+            skipped
+        );
+    }
+
+    if failed > 0 {
+        return Err(McplugError::ProtocolError(format!(
+            "{} of {} conformance checks failed",
+            failed,
+            checks.len()
+        )));
+    }
+    Ok(())
+}
+
+/// The checks grouped by section, one line each.
+fn format_report(checks: &[Check], is_tty: bool) -> String {
+    let mut out = String::new();
+    let mut section = "";
+    for check in checks {
+        if check.section != section {
+            if !section.is_empty() {
+                out.push('\n');
+            }
+            section = check.section;
+            out.push_str(&format!("{}\n", section));
+        }
+        let label = match (check.status, is_tty) {
+            (status, false) => status.label().to_string(),
+            (Status::Pass, true) => Status::Pass.label().green().to_string(),
+            (Status::Warn, true) => Status::Warn.label().yellow().to_string(),
+            (Status::Fail, true) => Status::Fail.label().red().to_string(),
+            (Status::Skip, true) => Status::Skip.label().dimmed().to_string(),
+        };
+        out.push_str(&format!("  {}  {}", label, check.name));
+        if !check.detail.is_empty() {
+            out.push_str(&format!(" - {}", check.detail));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Check how a connected, uninitialized transport's server follows the MCP
+/// protocol: the `initialize` handshake, its advertised capabilities,
+/// `tools/list` pagination, error responses, and how it takes
+/// notifications. If the handshake fails, that is the only check reported.
+pub async fn check_conformance(transport: &mut dyn McpTransport, timeout: Duration) -> Vec<Check> {
+    let mut checks = Checks::default();
+
+    let info = match timed(timeout, async {
+        transport.wait_ready().await?;
+        transport.initialize().await
+    })
+    .await
+    {
+        Ok(info) => info,
+        Err(e) => {
+            checks.fail("initialize", "handshake completes", e.to_string());
+            return checks.0;
+        }
+    };
+    checks.pass(
+        "initialize",
+        "handshake completes",
+        format!("{} {}", info.name, info.version),
+    );
+    match info.protocol_version.as_deref() {
+        Some(v) if PROTOCOL_VERSIONS.contains(&v) => {
+            checks.pass("initialize", "protocol version is supported", v.to_string())
+        }
+        Some(v) => checks.fail(
+            "initialize",
+            "protocol version is supported",
+            format!("{v} is not one of {}", PROTOCOL_VERSIONS.join(", ")),
+        ),
+        None => checks.fail(
+            "initialize",
+            "protocol version is supported",
+            "no protocolVersion in the result".into(),
+        ),
+    }
+    let missing: Vec<&str> = [("name", &info.name), ("version", &info.version)]
+        .into_iter()
+        .filter(|(_, value)| value.is_empty())
+        .map(|(field, _)| field)
+        .collect();
+    if missing.is_empty() {
+        checks.pass("initialize", "serverInfo names the server", String::new());
+    } else {
+        checks.fail(
+            "initialize",
+            "serverInfo names the server",
+            format!("missing {}", missing.join(" and ")),
+        );
+    }
+
+    let transport = &*transport;
+    check_capabilities(&mut checks, transport, &info.capabilities, timeout).await;
+    check_tools_list(&mut checks, transport, timeout).await;
+    check_errors(&mut checks, transport, timeout).await;
+    check_notifications(&mut checks, transport, timeout).await;
+    checks.0
+}
+
+/// The capabilities object, and a request to each optional capability the
+/// server advertises.
+async fn check_capabilities(
+    checks: &mut Checks,
+    transport: &dyn McpTransport,
+    capabilities: &Value,
+    timeout: Duration,
+) {
+    const SECTION: &str = "capabilities";
+    if !capabilities.is_object() {
+        checks.fail(SECTION, "capabilities is an object", format!("got {capabilities}"));
+        return;
+    }
+    checks.pass(SECTION, "capabilities is an object", String::new());
+    if capabilities.get("tools").is_some() {
+        checks.pass(SECTION, "tools is advertised", String::new());
+    } else {
+        checks.warn(
+            SECTION,
+            "tools is advertised",
+            "tools/list is checked anyway".into(),
+        );
+    }
+
+    match timed(timeout, transport.request("ping", None)).await {
+        Ok(_) => checks.pass(SECTION, "ping answers", String::new()),
+        Err(e) => checks.fail(SECTION, "ping answers", e.to_string()),
+    }
+
+    let optional = [
+        (
+            "logging",
+            "logging/setLevel answers",
+            "logging/setLevel",
+            Some(json!({"level": "info"})),
+            None,
+        ),
+        ("resources", "resources/list answers", "resources/list", None, Some("resources")),
+        ("prompts", "prompts/list answers", "prompts/list", None, Some("prompts")),
+    ];
+    for (capability, name, method, params, list) in optional {
+        if capabilities.get(capability).is_none() {
+            checks.skip(SECTION, name, format!("{capability} is not advertised"));
+            continue;
+        }
+        match timed(timeout, transport.request(method, params)).await {
+            Ok(result) => match list {
+                Some(key) if !result[key].is_array() => {
+                    checks.fail(SECTION, name, format!("result has no {key} array"))
+                }
+                _ => checks.pass(SECTION, name, String::new()),
+            },
+            Err(e) => checks.fail(SECTION, name, e.to_string()),
+        }
+    }
+}
+
+/// Follow `tools/list` through every page, and check the tools it lists.
+async fn check_tools_list(checks: &mut Checks, transport: &dyn McpTransport, timeout: Duration) {
+    const SECTION: &str = "tools/list";
+    let mut tools = Vec::new();
+    let mut cursors = HashSet::new();
+    let mut params = None;
+    let mut pages = 0;
+    let paginated = loop {
+        let result = match timed(timeout, transport.request("tools/list", params)).await {
+            Ok(result) => result,
+            Err(e) => break Err(format!("page {}: {e}", pages + 1)),
+        };
+        pages += 1;
+        match result.get("tools").and_then(Value::as_array) {
+            Some(page) => tools.extend(page.iter().cloned()),
+            None => break Err(format!("page {pages} has no tools array")),
+        }
+        let cursor = match result.get("nextCursor") {
+            None | Some(Value::Null) => break Ok(()),
+            Some(Value::String(cursor)) => cursor.clone(),
+            Some(other) => break Err(format!("nextCursor {other} is not a string")),
+        };
+        if !cursors.insert(cursor.clone()) {
+            break Err(format!("cursor {cursor:?} was returned twice"));
+        }
+        if pages == MAX_PAGES {
+            break Err(format!("still paginating after {MAX_PAGES} pages"));
+        }
+        params = Some(json!({ "cursor": cursor }));
+    };
+    match paginated {
+        Ok(()) => checks.pass(
+            SECTION,
+            "pagination completes",
+            format!(
+                "{} {} on {} {}",
+                tools.len(),
+                plural(tools.len(), "tool"),
+                pages,
+                plural(pages, "page")
+            ),
+        ),
+        Err(detail) => {
+            checks.fail(SECTION, "pagination completes", detail);
+            return;
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    let mut invalid = Vec::new();
+    for (i, tool) in tools.iter().enumerate() {
+        let name = tool.get("name").and_then(Value::as_str).unwrap_or_default();
+        if name.is_empty() {
+            invalid.push(format!("tool #{} has no name", i + 1));
+            continue;
+        }
+        if !seen.insert(name) {
+            duplicates.push(name);
+        }
+        if tool["inputSchema"]["type"] != "object" {
+            invalid.push(format!("{name} has no object inputSchema"));
+        }
+    }
+    if duplicates.is_empty() {
+        checks.pass(SECTION, "tool names are unique", String::new());
+    } else {
+        checks.fail(SECTION, "tool names are unique", format!("repeated: {}", duplicates.join(", ")));
+    }
+    if invalid.is_empty() {
+        checks.pass(SECTION, "tools have a name and inputSchema", String::new());
+    } else {
+        checks.fail(SECTION, "tools have a name and inputSchema", invalid.join("; "));
+    }
+
+    let invalid_cursor = json!({ "cursor": INVALID_CURSOR });
+    match timed(timeout, transport.request("tools/list", Some(invalid_cursor))).await {
+        Err(e) if rpc_error_code(&e) == Some(INVALID_PARAMS) => {
+            checks.pass(SECTION, "invalid cursor is rejected", String::new())
+        }
+        Err(e) => checks.warn(SECTION, "invalid cursor is rejected", expected_code(INVALID_PARAMS, &e)),
+        Ok(_) => checks.warn(
+            SECTION,
+            "invalid cursor is rejected",
+            format!("expected error {INVALID_PARAMS}, got a result"),
+        ),
+    }
+}
+
+/// Requests the server can't serve must get JSON-RPC errors, not results.
+async fn check_errors(checks: &mut Checks, transport: &dyn McpTransport, timeout: Duration) {
+    const SECTION: &str = "errors";
+    match timed(timeout, transport.request(UNKNOWN_METHOD, None)).await {
+        Err(e) if rpc_error_code(&e) == Some(METHOD_NOT_FOUND) => {
+            checks.pass(SECTION, "unknown method is rejected", String::new())
+        }
+        Err(e) => checks.fail(SECTION, "unknown method is rejected", expected_code(METHOD_NOT_FOUND, &e)),
+        Ok(_) => checks.fail(
+            SECTION,
+            "unknown method is rejected",
+            format!("expected error {METHOD_NOT_FOUND}, got a result"),
+        ),
+    }
+
+    // A tool error may also come back as a result with `isError` set
+    let params = json!({ "name": UNKNOWN_TOOL, "arguments": {} });
+    match timed(timeout, transport.request("tools/call", Some(params))).await {
+        Err(e) if rpc_error_code(&e).is_some() => {
+            checks.pass(SECTION, "unknown tool is rejected", String::new())
+        }
+        Ok(result) if result["isError"] == true => {
+            checks.pass(SECTION, "unknown tool is rejected", "as a tool error".into())
+        }
+        Err(e) => checks.fail(SECTION, "unknown tool is rejected", e.to_string()),
+        Ok(_) => checks.fail(
+            SECTION,
+            "unknown tool is rejected",
+            "expected an error, got a result".into(),
+        ),
+    }
+
+    match timed(timeout, transport.request("tools/call", Some(json!({})))).await {
+        Err(e) if rpc_error_code(&e) == Some(INVALID_PARAMS) => {
+            checks.pass(SECTION, "tools/call without a name is rejected", String::new())
+        }
+        Err(e) if rpc_error_code(&e).is_some() => checks.warn(
+            SECTION,
+            "tools/call without a name is rejected",
+            expected_code(INVALID_PARAMS, &e),
+        ),
+        Err(e) => checks.fail(SECTION, "tools/call without a name is rejected", e.to_string()),
+        Ok(_) => checks.fail(
+            SECTION,
+            "tools/call without a name is rejected",
+            "expected an error, got a result".into(),
+        ),
+    }
+}
+
+/// Notifications get no response, so each is followed by a `ping` to check
+/// the server took it without stalling or closing the connection.
+async fn check_notifications(checks: &mut Checks, transport: &dyn McpTransport, timeout: Duration) {
+    const SECTION: &str = "notifications";
+    let notifications = [
+        ("unknown notification is ignored", UNKNOWN_NOTIFICATION, None),
+        (
+            "cancelling an unknown request is ignored",
+            "notifications/cancelled",
+            Some(json!({ "requestId": UNKNOWN_TOOL, "reason": "conformance check" })),
+        ),
+    ];
+    for (name, method, params) in notifications {
+        let outcome = async {
+            timed(timeout, transport.notify(method, params)).await?;
+            timed(timeout, transport.request("ping", None)).await
+        };
+        match outcome.await {
+            Ok(_) => checks.pass(SECTION, name, String::new()),
+            Err(e) => checks.fail(SECTION, name, format!("ping afterwards: {e}")),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Checks(Vec<Check>);
+
+impl Checks {
+    fn add(&mut self, section: &'static str, name: &'static str, status: Status, detail: String) {
+        self.0.push(Check {
+            section,
+            name,
+            status,
+            detail,
+        });
+    }
+
+    fn pass(&mut self, section: &'static str, name: &'static str, detail: String) {
+        self.add(section, name, Status::Pass, detail);
+    }
+
+    fn warn(&mut self, section: &'static str, name: &'static str, detail: String) {
+        self.add(section, name, Status::Warn, detail);
+    }
+
+    fn fail(&mut self, section: &'static str, name: &'static str, detail: String) {
+        self.add(section, name, Status::Fail, detail);
+    }
+
+    fn skip(&mut self, section: &'static str, name: &'static str, detail: String) {
+        self.add(section, name, Status::Skip, detail);
+    }
+}
+
+/// Run `future`, giving up after `timeout`.
+async fn timed<T>(
+    timeout: Duration,
+    future: impl Future<Output = Result<T, McplugError>>,
+) -> Result<T, McplugError> {
+    tokio::time::timeout(timeout, future)
+        .await
+        .map_err(|_| McplugError::ProtocolError(format!("no response within {}s", timeout.as_secs())))?
+}
+
+/// The code of a JSON-RPC error response, as the transports report it.
+fn rpc_error_code(error: &McplugError) -> Option<i64> {
+    match error {
+        McplugError::ProtocolError(message) => message
+            .strip_prefix("JSON-RPC error ")?
+            .split(':')
+            .next()?
+            .parse()
+            .ok(),
+        _ => None,
+    }
+}
+
+fn expected_code(code: i64, error: &McplugError) -> String {
+    format!("expected error {code}, got: {error}")
+}
+
+fn plural(n: usize, word: &str) -> String {
+    if n == 1 {
+        word.to_string()
+    } else {
+        format!("{word}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CallResult, ServerInfo, ToolDefinition};
+    use async_trait::async_trait;
+
+    /// A result, or a JSON-RPC error code and message.
+    type Response = Result<Value, (i64, &'static str)>;
+
+    /// Answers requests from a function of the method and params, the way
+    /// the stdio and HTTP transports report results and errors.
+    struct Scripted {
+        info: ServerInfo,
+        respond: fn(&str, Option<&Value>) -> Response,
+    }
+
+    #[async_trait]
+    impl McpTransport for Scripted {
+        async fn initialize(&mut self) -> Result<ServerInfo, McplugError> {
+            Ok(self.info.clone())
+        }
+
+        async fn list_tools(&self) -> Result<Vec<ToolDefinition>, McplugError> {
+            Ok(Vec::new())
+        }
+
+        async fn call_tool(&self, _name: &str, _args: Value) -> Result<CallResult, McplugError> {
+            unreachable!("the checks call tools through request")
+        }
+
+        async fn request(&self, method: &str, params: Option<Value>) -> Result<Value, McplugError> {
+            (self.respond)(method, params.as_ref()).map_err(|(code, message)| {
+                McplugError::ProtocolError(format!("JSON-RPC error {code}: {message}"))
+            })
+        }
+
+        async fn close(&mut self) -> Result<(), McplugError> {
+            Ok(())
+        }
+    }
+
+    fn info(capabilities: Value) -> ServerInfo {
+        ServerInfo {
+            name: "srv".into(),
+            version: "1.0.0".into(),
+            capabilities,
+            protocol_version: Some("2025-03-26".into()),
+        }
+    }
+
+    fn tool(name: &str) -> Value {
+        json!({"name": name, "inputSchema": {"type": "object"}})
+    }
+
+    /// A well-behaved server with two pages of tools.
+    fn conforming(method: &str, params: Option<&Value>) -> Response {
+        let cursor = params.and_then(|p| p["cursor"].as_str());
+        match (method, cursor) {
+            ("ping", _) | ("logging/setLevel", _) => Ok(json!({})),
+            ("tools/list", None) => Ok(json!({"tools": [tool("a")], "nextCursor": "2"})),
+            ("tools/list", Some("2")) => Ok(json!({"tools": [tool("b")]})),
+            ("tools/list", Some(_)) => Err((-32602, "Invalid cursor")),
+            ("tools/call", _) if params.unwrap()["name"].is_null() => Err((-32602, "Missing name")),
+            ("tools/call", _) => Ok(json!({"content": [], "isError": true})),
+            _ => Err((-32601, "Method not found")),
+        }
+    }
+
+    fn status(checks: &[Check], name: &str) -> Status {
+        checks.iter().find(|c| c.name == name).unwrap().status
+    }
+
+    #[tokio::test]
+    async fn conforming_server_passes() {
+        let mut transport = Scripted {
+            info: info(json!({"tools": {}, "logging": {}})),
+            respond: conforming,
+        };
+        let checks = check_conformance(&mut transport, Duration::from_secs(5)).await;
+        let failing: Vec<_> = checks
+            .iter()
+            .filter(|c| !matches!(c.status, Status::Pass | Status::Skip))
+            .collect();
+        assert!(failing.is_empty(), "{failing:?}");
+        let pagination = checks.iter().find(|c| c.name == "pagination completes").unwrap();
+        assert_eq!(pagination.detail, "2 tools on 2 pages");
+        assert_eq!(status(&checks, "logging/setLevel answers"), Status::Pass);
+        assert_eq!(status(&checks, "resources/list answers"), Status::Skip);
+    }
+
+    #[tokio::test]
+    async fn reports_protocol_violations() {
+        let mut transport = Scripted {
+            info: ServerInfo {
+                version: String::new(),
+                protocol_version: Some("2023-01-01".into()),
+                ..info(json!({"prompts": {}}))
+            },
+            respond: |method, params| match method {
+                "ping" => Ok(json!({})),
+                // Loops back to its own cursor, and repeats a tool
+                "tools/list" if params.is_none() => {
+                    Ok(json!({"tools": [tool("a"), tool("a")], "nextCursor": "x"}))
+                }
+                "tools/list" => Ok(json!({"tools": [], "nextCursor": "x"})),
+                "prompts/list" => Ok(json!({})),
+                _ => Ok(json!({"content": []})),
+            },
+        };
+        let checks = check_conformance(&mut transport, Duration::from_secs(5)).await;
+        assert_eq!(status(&checks, "protocol version is supported"), Status::Fail);
+        let server_info = checks.iter().find(|c| c.name == "serverInfo names the server").unwrap();
+        assert_eq!(server_info.detail, "missing version");
+        assert_eq!(status(&checks, "tools is advertised"), Status::Warn);
+        assert_eq!(status(&checks, "prompts/list answers"), Status::Fail);
+        let pagination = checks.iter().find(|c| c.name == "pagination completes").unwrap();
+        assert_eq!(pagination.status, Status::Fail);
+        assert!(pagination.detail.contains("returned twice"), "{}", pagination.detail);
+        assert_eq!(status(&checks, "unknown method is rejected"), Status::Fail);
+        assert_eq!(status(&checks, "unknown tool is rejected"), Status::Fail);
+        assert_eq!(status(&checks, "unknown notification is ignored"), Status::Pass);
+    }
+
+    #[tokio::test]
+    async fn failed_handshake_is_the_only_check() {
+        struct Broken;
+
+        #[async_trait]
+        impl McpTransport for Broken {
+            async fn initialize(&mut self) -> Result<ServerInfo, McplugError> {
+                Err(McplugError::ProtocolError("bad handshake".into()))
+            }
+            async fn list_tools(&self) -> Result<Vec<ToolDefinition>, McplugError> {
+                unreachable!()
+            }
+            async fn call_tool(&self, _: &str, _: Value) -> Result<CallResult, McplugError> {
+                unreachable!()
+            }
+            async fn close(&mut self) -> Result<(), McplugError> {
+                Ok(())
+            }
+        }
+
+        let checks = check_conformance(&mut Broken, Duration::from_secs(5)).await;
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, Status::Fail);
+        assert!(checks[0].detail.contains("bad handshake"));
+    }
+
+    #[test]
+    fn rpc_error_code_from_transport_errors() {
+        let err = McplugError::ProtocolError("JSON-RPC error -32601: Method not found".into());
+        assert_eq!(rpc_error_code(&err), Some(-32601));
+        let err = McplugError::ProtocolError("Response missing both result and error".into());
+        assert_eq!(rpc_error_code(&err), None);
+        assert_eq!(rpc_error_code(&McplugError::ServerNotFound("x".into())), None);
+    }
+
+    #[test]
+    fn report_groups_checks_by_section() {
+        let check = |section, name, status, detail: &str| Check {
+            section,
+            name,
+            status,
+            detail: detail.into(),
+        };
+        let checks = [
+            check("initialize", "handshake completes", Status::Pass, "srv 1.0"),
+            check("errors", "unknown method is rejected", Status::Fail, "got a result"),
+        ];
+        assert_eq!(
+            format_report(&checks, false),
+            "initialize\n  PASS  handshake completes - srv 1.0\n\n\
+             errors\n  FAIL  unknown method is rejected - got a result\n"
+        );
+    }
+}
@@ -0,0 +1,376 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::config::env::resolve_secrets;
+use crate::config::{load_config, AuthConfig, McplugConfig};
+use crate::error::McplugError;
+use crate::oauth::load_cached_token;
+use crate::transport::McpTransport;
+use crate::transports::RecordingTransport;
+use crate::types::{ServerInfo, ToolDefinition};
+
+use super::connection::{connect_to_server, describe_transport, transport_credentials};
+
+/// Default timeout for each protocol step.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Tools included in full in the report; the rest are only counted.
+const TOOL_SAMPLE_SIZE: usize = 5;
+
+/// Configured values shorter than this are not redacted, so that short
+/// values like `1` don't blank out unrelated text.
+const MIN_SECRET_LEN: usize = 4;
+
+/// Get the timeout from the environment variable or use the default.
+fn get_timeout() -> Duration {
+    parse_timeout_secs(std::env::var("MCPLUG_CALL_TIMEOUT").ok())
+}
+
+fn parse_timeout_secs(val: Option<String>) -> Duration {
+    val.and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+}
+
+fn millis(since: Instant) -> Value {
+    json!(since.elapsed().as_millis() as u64)
+}
+
+/// Run the diagnose command.
+///
+/// Connects to the server, records the `initialize` and `tools/list`
+/// exchanges with timings, and writes a report (to `output`, or stdout)
+/// with the server's capabilities, a sample of its tools, an environment
+/// summary, and the server's stderr. Configured env and header values and
+/// cached tokens are redacted, and the home directory is shown as `~`.
+///
+/// The report is written even when the server fails, since that is when it
+/// is needed; the failure is then returned as the command's error.
+pub async fn run_diagnose(
+    server: &str,
+    output: Option<&Path>,
+    http_url: Option<&str>,
+    stdio: Option<&str>,
+) -> Result<(), McplugError> {
+    let config = load_config(None)?;
+    let timeout = if http_url.is_none() && stdio.is_none() {
+        config.list_timeout(server, get_timeout())
+    } else {
+        get_timeout()
+    };
+    let started = Instant::now();
+
+    let inner = match connect_to_server(server, &config, http_url, stdio) {
+        Ok(inner) => inner,
+        // A server that can't be spawned or reached is worth reporting
+        Err(e @ McplugError::ConnectionFailed { .. }) => {
+            return finish(
+                server,
+                &config,
+                http_url,
+                stdio,
+                output,
+                Report::failed(e, started),
+            );
+        }
+        Err(e) => return Err(e),
+    };
+    let mut transport = RecordingTransport::in_memory(inner, server);
+
+    let mut timing = serde_json::Map::new();
+    let outcome = probe(&mut transport, server, timeout, &mut timing).await;
+    let _ = transport.close().await;
+    // Let the stderr reader drain what the server wrote before exiting
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    timing.insert("totalMs".into(), millis(started));
+
+    let report = Report {
+        timing,
+        outcome,
+        exchanges: serde_json::to_value(transport.recording().exchanges).unwrap_or_default(),
+        stderr: transport.stderr_lines(),
+    };
+    finish(server, &config, http_url, stdio, output, report)
+}
+
+/// What was observed while probing the server.
+struct Report {
+    timing: serde_json::Map<String, Value>,
+    outcome: Result<(ServerInfo, Vec<ToolDefinition>), McplugError>,
+    exchanges: Value,
+    stderr: Vec<String>,
+}
+
+impl Report {
+    fn failed(error: McplugError, started: Instant) -> Self {
+        let mut timing = serde_json::Map::new();
+        timing.insert("totalMs".into(), millis(started));
+        Self {
+            timing,
+            outcome: Err(error),
+            exchanges: json!([]),
+            stderr: Vec::new(),
+        }
+    }
+}
+
+/// Wait for the server, initialize it, and list its tools, timing each step.
+async fn probe(
+    transport: &mut RecordingTransport,
+    server: &str,
+    timeout: Duration,
+    timing: &mut serde_json::Map<String, Value>,
+) -> Result<(ServerInfo, Vec<ToolDefinition>), McplugError> {
+    let timed_out = |_| McplugError::Timeout {
+        server: server.to_string(),
+        tool: None,
+        duration: timeout,
+    };
+
+    let step = Instant::now();
+    transport.wait_ready().await?;
+    timing.insert("readyMs".into(), millis(step));
+
+    let step = Instant::now();
+    let info = tokio::time::timeout(timeout, transport.initialize())
+        .await
+        .map_err(timed_out)??;
+    timing.insert("initializeMs".into(), millis(step));
+
+    let step = Instant::now();
+    let tools = tokio::time::timeout(timeout, transport.list_tools())
+        .await
+        .map_err(timed_out)??;
+    timing.insert("listToolsMs".into(), millis(step));
+    Ok((info, tools))
+}
+
+/// Assemble, sanitize, and write the report, then surface any failure.
+fn finish(
+    server: &str,
+    config: &McplugConfig,
+    http_url: Option<&str>,
+    stdio: Option<&str>,
+    output: Option<&Path>,
+    report: Report,
+) -> Result<(), McplugError> {
+    let transport: serde_json::Map<String, Value> = describe_transport(server, config, http_url, stdio)
+        .into_iter()
+        .map(|(label, value)| (label, json!(value)))
+        .collect();
+    let mut json = json!({
+        "mcplugVersion": env!("CARGO_PKG_VERSION"),
+        "generatedAt": chrono::Utc::now(),
+        "server": server,
+        "transport": transport,
+        "environment": environment(),
+        "timing": report.timing,
+        "exchanges": report.exchanges,
+        "stderr": report.stderr,
+    });
+    let error = match report.outcome {
+        Ok((info, tools)) => {
+            json["serverInfo"] = json!({
+                "name": info.name,
+                "version": info.version,
+                "protocolVersion": info.protocol_version,
+            });
+            json["capabilities"] = info.capabilities;
+            json["tools"] = json!({
+                "count": tools.len(),
+                "sample": tools.iter().take(TOOL_SAMPLE_SIZE).collect::<Vec<_>>(),
+            });
+            None
+        }
+        Err(e) => {
+            json["error"] = e.to_json()["error"].clone();
+            Some(e)
+        }
+    };
+
+    let home = dirs::home_dir().map(|h| h.to_string_lossy().into_owned());
+    let secrets = secrets(server, config, http_url, stdio);
+    sanitize(&mut json, &secrets, home.as_deref());
+    let text = serde_json::to_string_pretty(&json).unwrap_or_default();
+    match output {
+        Some(path) => {
+            std::fs::write(path, format!("{text}\n"))?;
+            eprintln!("Wrote diagnostics for {} to {}", server, path.display());
+        }
+        None => println!("{}", text),
+    }
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// OS and architecture, and which `MCPLUG_*` variables are set (names only).
+fn environment() -> Value {
+    let mut variables: Vec<String> = std::env::vars()
+        .map(|(name, _)| name)
+        .filter(|name| name.starts_with("MCPLUG_"))
+        .collect();
+    variables.sort();
+    json!({
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "mcplugVariables": variables,
+    })
+}
+
+/// Values that must not appear in the report: the server's configured env
+/// and header values (and the token part of `Bearer <token>` headers), its
+/// `auth` bearer token, credentials in its URL or command line, and its
+/// cached OAuth tokens.
+fn secrets(
+    server: &str,
+    config: &McplugConfig,
+    http_url: Option<&str>,
+    stdio: Option<&str>,
+) -> Vec<String> {
+    let mut secrets = transport_credentials(server, config, http_url, stdio);
+    // Secret references were resolved (and cached) when connecting
+    let resolved = config
+        .mcp_servers
+        .get(server)
+        .map(|cfg| resolve_secrets(cfg).unwrap_or_else(|_| cfg.clone()));
+    if let Some(ref cfg) = resolved {
+        for value in cfg.env.values().chain(cfg.headers.values()) {
+            secrets.push(value.clone());
+            if let Some((_, token)) = value.rsplit_once(' ') {
+                secrets.push(token.to_string());
+            }
+        }
+        if let Some(AuthConfig::Bearer { ref token }) = cfg.auth {
+            secrets.push(token.clone());
+        }
+    }
+    if let Some(token) = load_cached_token(server) {
+        secrets.push(token.access_token);
+        secrets.extend(token.refresh_token);
+    }
+    secrets.retain(|s| s.len() >= MIN_SECRET_LEN);
+    // Longest first, so a value is redacted whole before any part of it
+    secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    secrets.dedup();
+    secrets
+}
+
+/// Replace secrets with `<redacted>` and the home directory with `~` in
+/// every string of the report.
+fn sanitize(value: &mut Value, secrets: &[String], home: Option<&str>) {
+    match value {
+        Value::String(s) => {
+            for secret in secrets {
+                if s.contains(secret.as_str()) {
+                    *s = s.replace(secret.as_str(), "<redacted>");
+                }
+            }
+            if let Some(home) = home.filter(|h| h.len() > 1) {
+                if s.contains(home) {
+                    *s = s.replace(home, "~");
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                sanitize(item, secrets, home);
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                sanitize(item, secrets, home);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use std::collections::HashMap;
+
+    #[test]
+    fn sanitize_redacts_nested_strings_and_home() {
+        let mut report = json!({
+            "stderr": ["token sk-live-123 accepted", "loaded /home/me/.config/x"],
+            "nested": {"header": "Bearer sk-live-123"},
+            "count": 3
+        });
+        sanitize(&mut report, &["sk-live-123".into()], Some("/home/me"));
+        assert_eq!(report["stderr"][0], "token <redacted> accepted");
+        assert_eq!(report["stderr"][1], "loaded ~/.config/x");
+        assert_eq!(report["nested"]["header"], "Bearer <redacted>");
+        assert_eq!(report["count"], 3);
+    }
+
+    #[test]
+    fn secrets_from_env_and_headers() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "srv".to_string(),
+            ServerConfig {
+                description: None,
+                base_url: None,
+                command: Some("x".into()),
+                args: vec![],
+                env: HashMap::from([
+                    ("API_KEY".to_string(), "abcd1234".to_string()),
+                    ("DEBUG".to_string(), "1".to_string()),
+                ]),
+                headers: HashMap::from([(
+                    "Authorization".to_string(),
+                    "Bearer tok-5678".to_string(),
+                )]),
+                lifecycle: None,
+                tool_pins: HashMap::new(),
+                disabled: false,
+                install_timeout: None,
+                log_level: None,
+                sensitive_args: Vec::new(),
+                timeout_ms: None,
+                list_timeout_ms: None,
+                allow_http: None,
+                auth: Some(AuthConfig::Bearer {
+                    token: "auth-tok-90".into(),
+                }),
+                tags: Vec::new(),
+                transport: None,
+                retry: None,
+            },
+        );
+        let config = McplugConfig {
+            mcp_servers: servers,
+            imports: vec![],
+            state: None,
+            groups: HashMap::new(),
+            extends: Vec::new(),
+            defaults: None,
+        };
+        let secrets = secrets("srv", &config, None, None);
+        assert_eq!(secrets[0], "Bearer tok-5678");
+        assert!(secrets.contains(&"abcd1234".to_string()));
+        assert!(secrets.contains(&"tok-5678".to_string()));
+        assert!(secrets.contains(&"auth-tok-90".to_string()));
+        assert!(!secrets.contains(&"1".to_string()));
+    }
+
+    #[test]
+    fn environment_lists_variable_names_only() {
+        let env = environment();
+        assert_eq!(env["os"], std::env::consts::OS);
+        assert!(env["mcplugVariables"].is_array());
+    }
+
+    #[test]
+    fn parse_timeout_default() {
+        assert_eq!(parse_timeout_secs(None), Duration::from_secs(30));
+        assert_eq!(parse_timeout_secs(Some("5".into())), Duration::from_secs(5));
+    }
+}
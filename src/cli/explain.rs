@@ -0,0 +1,293 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use crate::config::{Lifecycle, McplugConfig};
+use crate::daemon::DaemonManager;
+use crate::error::McplugError;
+use crate::redact::{install_key, redact_args, sensitive_args};
+use crate::runtime::resolve_lifecycle;
+use crate::types::{ToolDefinition, ToolSchema};
+
+use super::connection::describe_transport;
+use super::output::OutputMode;
+
+/// Everything `mcplug call --explain` shows before a tool is executed.
+#[derive(Debug)]
+pub struct ExecutionPlan {
+    pub server: String,
+    pub tool: String,
+    /// Transport choice and connection details, as `(label, value)` pairs.
+    pub transport: Vec<(String, String)>,
+    pub lifecycle: Option<Lifecycle>,
+    pub daemon_running: bool,
+    pub timeout: Duration,
+    pub output_mode: OutputMode,
+    /// Call arguments, with sensitive values masked.
+    pub args: serde_json::Value,
+    /// Problems found when checking `args` against the tool's input schema.
+    pub issues: Vec<String>,
+}
+
+impl ExecutionPlan {
+    /// Assemble a plan from the resolved config and the tool definition.
+    #[allow(clippy::too_many_arguments)]
+    pub fn build(
+        server: &str,
+        tool: &ToolDefinition,
+        config: &McplugConfig,
+        http_url: Option<&str>,
+        stdio: Option<&str>,
+        timeout: Duration,
+        output_mode: OutputMode,
+        args: &serde_json::Value,
+    ) -> Self {
+        let lifecycle = config
+            .mcp_servers
+            .get(server)
+            .filter(|_| http_url.is_none() && stdio.is_none())
+            .and_then(|cfg| resolve_lifecycle(server, cfg));
+        let configured = config
+            .mcp_servers
+            .get(server)
+            .map(|cfg| cfg.sensitive_args.as_slice())
+            .unwrap_or_default();
+        let sensitive = sensitive_args(&tool.input_schema, configured);
+        Self {
+            server: server.to_string(),
+            tool: tool.name.clone(),
+            transport: describe_transport(server, config, http_url, stdio),
+            lifecycle,
+            daemon_running: DaemonManager::new().is_running(),
+            timeout,
+            output_mode,
+            args: redact_args(args, &sensitive, &install_key()),
+            issues: validate_args(&tool.schema(), args),
+        }
+    }
+
+    /// Render the plan as human-readable text.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "Execution plan for {}.{}", self.server, self.tool).unwrap();
+        for (label, value) in &self.transport {
+            writeln!(out, "  {}: {}", label, value).unwrap();
+        }
+
+        let lifecycle = match self.lifecycle {
+            Some(Lifecycle::KeepAlive) => "keep-alive",
+            Some(Lifecycle::Ephemeral) => "ephemeral",
+            None => "ephemeral (default)",
+        };
+        writeln!(out, "  Lifecycle: {}", lifecycle).unwrap();
+        let daemon = if self.daemon_running {
+            "running, not used (call connects directly)"
+        } else {
+            "not running, not used (call connects directly)"
+        };
+        writeln!(out, "  Daemon: {}", daemon).unwrap();
+        writeln!(out, "  Timeout: {}s", self.timeout.as_secs()).unwrap();
+        let mode = match self.output_mode {
+            OutputMode::Pretty => "pretty",
+            OutputMode::Raw => "raw",
+            OutputMode::Json => "json",
+            OutputMode::Markdown => "markdown",
+        };
+        writeln!(out, "  Output: {}", mode).unwrap();
+
+        let args = serde_json::to_string_pretty(&self.args).unwrap_or_default();
+        writeln!(out, "  Arguments:").unwrap();
+        for line in args.lines() {
+            writeln!(out, "    {}", line).unwrap();
+        }
+
+        if self.issues.is_empty() {
+            writeln!(out, "  Validation: ok").unwrap();
+        } else {
+            writeln!(out, "  Validation:").unwrap();
+            for issue in &self.issues {
+                writeln!(out, "    - {}", issue).unwrap();
+            }
+        }
+        out
+    }
+}
+
+/// Check call arguments against a tool's JSON Schema.
+///
+/// Reports missing required properties, unknown properties (unless the schema
+/// allows additional properties), and top-level type and enum mismatches.
+pub fn validate_args(schema: &ToolSchema, args: &serde_json::Value) -> Vec<String> {
+    let mut issues = Vec::new();
+    let obj = match args {
+        serde_json::Value::Object(obj) => obj,
+        serde_json::Value::Array(_) => {
+            issues.push("positional arguments cannot be validated against the schema".into());
+            return issues;
+        }
+        _ => {
+            issues.push("arguments are not a JSON object".into());
+            return issues;
+        }
+    };
+
+    for name in &schema.required {
+        if !obj.contains_key(name) {
+            issues.push(format!("missing required argument '{name}'"));
+        }
+    }
+
+    // A schema declaring no properties says nothing about which are allowed,
+    // unless it forbids additional ones
+    if schema.properties.is_empty() && schema.additional_properties {
+        return issues;
+    }
+    let mut keys: Vec<&String> = obj.keys().collect();
+    keys.sort();
+    for key in keys {
+        let value = &obj[key.as_str()];
+        match schema.property(key) {
+            Some(property) => {
+                if let Some(ty) = property.ty.as_ref().filter(|ty| !ty.matches(value)) {
+                    issues.push(format!("argument '{key}' should be of type {ty}"));
+                } else if !property.enum_values.is_empty()
+                    && !property.enum_values.contains(value)
+                {
+                    let allowed: Vec<String> =
+                        property.enum_values.iter().map(|v| v.to_string()).collect();
+                    issues.push(format!(
+                        "argument '{key}' should be one of {}",
+                        allowed.join(", ")
+                    ));
+                }
+            }
+            None if !schema.additional_properties => {
+                issues.push(format!("unknown argument '{key}'"));
+            }
+            None => {
+                issues.push(format!("argument '{key}' is not declared in the schema"));
+            }
+        }
+    }
+
+    issues
+}
+
+/// Ask a yes/no question on stderr. Anything other than `y`/`yes` declines.
+pub fn confirm(reader: &mut impl BufRead, message: &str) -> Result<bool, McplugError> {
+    eprint!("{}", message);
+    io::stderr().flush()?;
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let answer = line.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    fn schema() -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "a": {"type": "number"},
+                "name": {"type": "string"},
+            },
+            "required": ["a"],
+        })
+    }
+
+    fn check(schema: &serde_json::Value, args: &serde_json::Value) -> Vec<String> {
+        validate_args(&ToolSchema::parse(schema), args)
+    }
+
+    #[test]
+    fn validate_ok() {
+        assert!(check(&schema(), &json!({"a": 1, "name": "x"})).is_empty());
+    }
+
+    #[test]
+    fn validate_missing_required() {
+        let issues = check(&schema(), &json!({"name": "x"}));
+        assert_eq!(issues, vec!["missing required argument 'a'"]);
+    }
+
+    #[test]
+    fn validate_type_mismatch() {
+        let issues = check(&schema(), &json!({"a": "one"}));
+        assert_eq!(issues, vec!["argument 'a' should be of type number"]);
+    }
+
+    #[test]
+    fn validate_unknown_argument_strict() {
+        let mut s = schema();
+        s["additionalProperties"] = json!(false);
+        let issues = check(&s, &json!({"a": 1, "extra": true}));
+        assert_eq!(issues, vec!["unknown argument 'extra'"]);
+    }
+
+    #[test]
+    fn validate_undeclared_argument_lenient() {
+        let issues = check(&schema(), &json!({"a": 1, "extra": true}));
+        assert_eq!(issues, vec!["argument 'extra' is not declared in the schema"]);
+    }
+
+    #[test]
+    fn validate_positional_args() {
+        let issues = check(&schema(), &json!([1, 2]));
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].contains("positional"));
+    }
+
+    #[test]
+    fn validate_integer_rejects_float() {
+        let s = json!({"properties": {"n": {"type": "integer"}}});
+        assert!(check(&s, &json!({"n": 3})).is_empty());
+        assert_eq!(check(&s, &json!({"n": 3.5})).len(), 1);
+    }
+
+    #[test]
+    fn validate_enum_values() {
+        let s = json!({"properties": {"mode": {"type": "string", "enum": ["fast", "full"]}}});
+        assert!(check(&s, &json!({"mode": "fast"})).is_empty());
+        assert_eq!(
+            check(&s, &json!({"mode": "slow"})),
+            vec![r#"argument 'mode' should be one of "fast", "full""#]
+        );
+    }
+
+    #[test]
+    fn render_includes_validation_issues() {
+        let plan = ExecutionPlan {
+            server: "mock".into(),
+            tool: "add".into(),
+            transport: vec![("Transport".into(), "stdio".into())],
+            lifecycle: None,
+            daemon_running: false,
+            timeout: Duration::from_secs(30),
+            output_mode: OutputMode::Json,
+            args: json!({"a": 1}),
+            issues: vec!["missing required argument 'b'".into()],
+        };
+        let text = plan.render();
+        assert!(text.contains("Execution plan for mock.add"));
+        assert!(text.contains("Timeout: 30s"));
+        assert!(text.contains("Output: json"));
+        assert!(text.contains("- missing required argument 'b'"));
+    }
+
+    #[test]
+    fn confirm_accepts_yes() {
+        assert!(confirm(&mut Cursor::new(b"y\n"), "? ").unwrap());
+        assert!(confirm(&mut Cursor::new(b"YES\n"), "? ").unwrap());
+    }
+
+    #[test]
+    fn confirm_declines_by_default() {
+        assert!(!confirm(&mut Cursor::new(b"\n"), "? ").unwrap());
+        assert!(!confirm(&mut Cursor::new(b""), "? ").unwrap());
+    }
+}
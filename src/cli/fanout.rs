@@ -0,0 +1,123 @@
+use serde::Serialize;
+
+use crate::error::McplugError;
+
+/// How the exit status of a command that acts on several targets (servers,
+/// batch lines) depends on their outcomes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FanoutPolicy {
+    /// Always succeed; failures are only reported.
+    Report,
+    /// Fail unless every target succeeds.
+    RequireAll,
+    /// Fail only if no target succeeds.
+    RequireAny,
+}
+
+impl FanoutPolicy {
+    /// The policy selected by `--require-all`, `--require-any`, and
+    /// `--fail-fast` (which implies `--require-all`), or `default`.
+    pub fn from_flags(require_all: bool, require_any: bool, fail_fast: bool, default: Self) -> Self {
+        if require_all || fail_fast {
+            FanoutPolicy::RequireAll
+        } else if require_any {
+            FanoutPolicy::RequireAny
+        } else {
+            default
+        }
+    }
+}
+
+/// Outcome counts for a fan-out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FanoutSummary {
+    pub total: usize,
+    pub ok: usize,
+    pub failed: usize,
+    /// Targets not attempted because `--fail-fast` stopped the run.
+    pub skipped: usize,
+}
+
+impl FanoutSummary {
+    pub fn record(&mut self, ok: bool) {
+        self.total += 1;
+        if ok {
+            self.ok += 1;
+        } else {
+            self.failed += 1;
+        }
+    }
+
+    pub fn record_skipped(&mut self) {
+        self.total += 1;
+        self.skipped += 1;
+    }
+
+    /// Apply the policy, describing targets as `what` (e.g. "batch calls").
+    pub fn check(&self, policy: FanoutPolicy, what: &str) -> Result<(), McplugError> {
+        let failed = match policy {
+            FanoutPolicy::Report => false,
+            FanoutPolicy::RequireAll => self.ok < self.total,
+            FanoutPolicy::RequireAny => self.ok == 0 && self.total > 0,
+        };
+        if !failed {
+            return Ok(());
+        }
+        let mut message = format!("{} of {} {} failed", self.failed, self.total, what);
+        if self.skipped > 0 {
+            message.push_str(&format!(", {} skipped after --fail-fast", self.skipped));
+        }
+        Err(McplugError::ProtocolError(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(ok: usize, failed: usize, skipped: usize) -> FanoutSummary {
+        FanoutSummary {
+            total: ok + failed + skipped,
+            ok,
+            failed,
+            skipped,
+        }
+    }
+
+    #[test]
+    fn flags_select_policy() {
+        use FanoutPolicy::*;
+        assert_eq!(FanoutPolicy::from_flags(false, false, false, Report), Report);
+        assert_eq!(FanoutPolicy::from_flags(true, false, false, Report), RequireAll);
+        assert_eq!(FanoutPolicy::from_flags(false, true, false, RequireAll), RequireAny);
+        assert_eq!(FanoutPolicy::from_flags(false, false, true, Report), RequireAll);
+    }
+
+    #[test]
+    fn record_counts() {
+        let mut s = FanoutSummary::default();
+        s.record(true);
+        s.record(false);
+        s.record_skipped();
+        assert_eq!(s, summary(1, 1, 1));
+    }
+
+    #[test]
+    fn policies_on_partial_failure() {
+        let partial = summary(2, 1, 0);
+        assert!(partial.check(FanoutPolicy::Report, "x").is_ok());
+        assert!(partial.check(FanoutPolicy::RequireAny, "x").is_ok());
+        let err = partial.check(FanoutPolicy::RequireAll, "servers").unwrap_err();
+        assert!(err.to_string().contains("1 of 3 servers failed"));
+    }
+
+    #[test]
+    fn require_any_fails_when_nothing_succeeds() {
+        assert!(summary(0, 2, 0).check(FanoutPolicy::RequireAny, "x").is_err());
+        assert!(summary(0, 0, 0).check(FanoutPolicy::RequireAny, "x").is_ok());
+        let err = summary(0, 1, 2)
+            .check(FanoutPolicy::RequireAll, "batch calls")
+            .unwrap_err();
+        assert!(err.to_string().contains("2 skipped after --fail-fast"));
+    }
+}
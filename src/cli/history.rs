@@ -0,0 +1,137 @@
+use std::io::IsTerminal;
+
+use colored::Colorize;
+
+use crate::error::McplugError;
+use crate::history::{parse_age, query, tool_stats, HistoryFilter, NAMESPACE};
+use crate::state::default_store;
+
+/// Run `mcplug history query`.
+///
+/// Prints recorded calls oldest first, one per line, filtered by server,
+/// tool, age (`since` like `24h`), and failure.
+pub async fn run_history_query(
+    server: Option<&str>,
+    tool: Option<&str>,
+    since: Option<&str>,
+    failed: bool,
+    json: bool,
+) -> Result<(), McplugError> {
+    let filter = HistoryFilter {
+        server: server.map(String::from),
+        tool: tool.map(String::from),
+        since: since
+            .map(parse_age)
+            .transpose()?
+            .map(|age| chrono::Utc::now() - age),
+        failed,
+    };
+    let store = default_store()?;
+    let entries = query(store.as_ref(), &filter)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&entries).unwrap_or_default()
+        );
+        return Ok(());
+    }
+    if entries.is_empty() {
+        eprintln!("No matching calls.");
+    }
+    let is_tty = std::io::stdout().is_terminal();
+    for entry in entries {
+        let status = match (entry.ok, is_tty) {
+            (true, true) => "ok".green().to_string(),
+            (true, false) => "ok".to_string(),
+            (false, true) => "FAILED".red().to_string(),
+            (false, false) => "FAILED".to_string(),
+        };
+        let mut line = format!(
+            "{}  {}.{}  {}  {} ms",
+            entry
+                .started_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S"),
+            entry.server,
+            entry.tool,
+            status,
+            entry.duration_ms
+        );
+        if let Some(error) = entry.error {
+            // Keep one call per line
+            let first = error.lines().next().unwrap_or_default();
+            line.push_str(&format!("  {first}"));
+        }
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Run `mcplug history stats`.
+///
+/// Reports call counts, failures, and mean latency per tool from the
+/// history, and how many entries each namespace of the state store holds
+/// (saved results, per-server tokens and tool caches).
+pub async fn run_history_stats(json: bool) -> Result<(), McplugError> {
+    let store = default_store()?;
+    let entries = query(store.as_ref(), &HistoryFilter::default())?;
+    let tools = tool_stats(&entries);
+    let mut state = Vec::new();
+    for namespace in store.namespaces()? {
+        let count = store.keys(&namespace)?.len();
+        state.push((namespace, count));
+    }
+    if store.history().is_some() {
+        // Kept in the backend's own log rather than as namespace entries
+        state.push((NAMESPACE.to_string(), entries.len()));
+        state.sort();
+    }
+
+    if json {
+        let output = serde_json::json!({
+            "calls": entries.len(),
+            "since": entries.first().map(|e| e.started_at),
+            "tools": tools,
+            "state": state
+                .iter()
+                .map(|(ns, count)| (ns.clone(), serde_json::json!(count)))
+                .collect::<serde_json::Map<_, _>>(),
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&output).unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    match entries.first() {
+        Some(first) => println!(
+            "Calls: {} since {}",
+            entries.len(),
+            first
+                .started_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+        ),
+        None => println!("Calls: none recorded"),
+    }
+    let width = tools.keys().map(String::len).max().unwrap_or(0);
+    for (name, stats) in &tools {
+        println!(
+            "  {:<width$}  {} calls  {} failed  mean {:.1} ms",
+            name, stats.calls, stats.failed, stats.mean_ms
+        );
+    }
+    println!("State:");
+    let width = state.iter().map(|(ns, _)| ns.len()).max().unwrap_or(0);
+    for (namespace, count) in &state {
+        let label = if namespace == NAMESPACE {
+            "history entries"
+        } else {
+            "entries"
+        };
+        println!("  {:<width$}  {} {}", namespace, count, label);
+    }
+    Ok(())
+}
@@ -0,0 +1,255 @@
+use std::io::IsTerminal;
+use std::time::Duration;
+
+use colored::Colorize;
+
+use crate::config::{load_config, Lifecycle};
+use crate::error::McplugError;
+use crate::runtime::resolve_lifecycle;
+use crate::transport::McpTransport;
+use crate::types::ServerInfo;
+
+use super::connection::{connect_to_server, describe_transport};
+
+/// Default timeout for info operations.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Upper bound on pages fetched when counting paginated lists.
+const MAX_PAGES: usize = 100;
+
+/// Get the info timeout from the environment variable or use the default.
+fn get_timeout() -> Duration {
+    parse_timeout_secs(std::env::var("MCPLUG_LIST_TIMEOUT").ok())
+}
+
+fn parse_timeout_secs(val: Option<String>) -> Duration {
+    val.and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+}
+
+/// Details gathered about a single server.
+struct ServerDetails {
+    info: ServerInfo,
+    tool_count: usize,
+    /// `None` when the server does not advertise the capability.
+    resource_count: Option<usize>,
+    prompt_count: Option<usize>,
+}
+
+/// Names of the top-level capabilities a server advertises, sorted.
+fn capability_names(capabilities: &serde_json::Value) -> Vec<String> {
+    let mut names: Vec<String> = capabilities
+        .as_object()
+        .map(|obj| obj.keys().cloned().collect())
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+fn has_capability(capabilities: &serde_json::Value, name: &str) -> bool {
+    capabilities.get(name).is_some()
+}
+
+/// Count the items of a paginated MCP list method (e.g. `resources/list`).
+async fn count_paginated(
+    transport: &dyn McpTransport,
+    method: &str,
+    field: &str,
+) -> Result<usize, McplugError> {
+    let mut count = 0;
+    let mut cursor: Option<String> = None;
+    for _ in 0..MAX_PAGES {
+        let params = match cursor {
+            Some(ref c) => serde_json::json!({ "cursor": c }),
+            None => serde_json::json!({}),
+        };
+        let result = transport.request(method, Some(params)).await?;
+        count += result
+            .get(field)
+            .and_then(|v| v.as_array())
+            .map(|a| a.len())
+            .unwrap_or(0);
+        cursor = result
+            .get("nextCursor")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+        if cursor.is_none() {
+            break;
+        }
+    }
+    Ok(count)
+}
+
+async fn gather_details(transport: &mut dyn McpTransport) -> Result<ServerDetails, McplugError> {
+    let info = transport.initialize().await?;
+    let tool_count = transport.list_tools().await?.len();
+    let resource_count = if has_capability(&info.capabilities, "resources") {
+        Some(count_paginated(transport, "resources/list", "resources").await?)
+    } else {
+        None
+    };
+    let prompt_count = if has_capability(&info.capabilities, "prompts") {
+        Some(count_paginated(transport, "prompts/list", "prompts").await?)
+    } else {
+        None
+    };
+    Ok(ServerDetails {
+        info,
+        tool_count,
+        resource_count,
+        prompt_count,
+    })
+}
+
+fn lifecycle_str(lifecycle: Option<&Lifecycle>) -> &'static str {
+    match lifecycle {
+        Some(Lifecycle::KeepAlive) => "keep-alive",
+        Some(Lifecycle::Ephemeral) => "ephemeral",
+        None => "ephemeral (default)",
+    }
+}
+
+/// Run the info command.
+pub async fn run_info(
+    server: &str,
+    http_url: Option<&str>,
+    stdio: Option<&str>,
+    json: bool,
+) -> Result<(), McplugError> {
+    let config = load_config(None)?;
+    let timeout = if http_url.is_none() && stdio.is_none() {
+        config.list_timeout(server, get_timeout())
+    } else {
+        get_timeout()
+    };
+    let is_tty = std::io::stdout().is_terminal();
+
+    let mut transport = connect_to_server(server, &config, http_url, stdio)?;
+    transport.wait_ready().await?;
+    let details = tokio::time::timeout(timeout, gather_details(transport.as_mut()))
+        .await
+        .map_err(|_| McplugError::Timeout {
+            server: server.to_string(),
+            tool: None,
+            duration: timeout,
+        });
+    let _ = transport.close().await;
+    let details = details??;
+
+    let transport_lines = describe_transport(server, &config, http_url, stdio);
+    let lifecycle = if http_url.is_some() || stdio.is_some() {
+        None
+    } else {
+        config
+            .mcp_servers
+            .get(server)
+            .and_then(|cfg| resolve_lifecycle(server, cfg))
+    };
+
+    if json {
+        let transport_obj: serde_json::Map<String, serde_json::Value> = transport_lines
+            .iter()
+            .map(|(k, v)| (k.to_lowercase(), serde_json::json!(v)))
+            .collect();
+        let json_output = serde_json::json!({
+            "server": server,
+            "name": details.info.name,
+            "version": details.info.version,
+            "protocolVersion": details.info.protocol_version,
+            "capabilities": details.info.capabilities,
+            "transport": transport_obj,
+            "lifecycle": lifecycle_str(lifecycle.as_ref()),
+            "toolCount": details.tool_count,
+            "resourceCount": details.resource_count,
+            "promptCount": details.prompt_count,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json_output).unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    let label = |s: &str| {
+        if is_tty {
+            s.dimmed().to_string()
+        } else {
+            s.to_string()
+        }
+    };
+    let title = if is_tty {
+        server.bold().cyan().to_string()
+    } else {
+        server.to_string()
+    };
+    println!("{}", title);
+    println!(
+        "  {}: {} v{}",
+        label("Server"),
+        details.info.name,
+        details.info.version
+    );
+    println!(
+        "  {}: {}",
+        label("Protocol"),
+        details.info.protocol_version.as_deref().unwrap_or("unknown")
+    );
+    let caps = capability_names(&details.info.capabilities);
+    println!(
+        "  {}: {}",
+        label("Capabilities"),
+        if caps.is_empty() {
+            "none".to_string()
+        } else {
+            caps.join(", ")
+        }
+    );
+    for (k, v) in &transport_lines {
+        println!("  {}: {}", label(k), v);
+    }
+    println!("  {}: {}", label("Lifecycle"), lifecycle_str(lifecycle.as_ref()));
+    let count_str = |c: Option<usize>| c.map(|n| n.to_string()).unwrap_or_else(|| "n/a".into());
+    println!("  {}: {}", label("Tools"), details.tool_count);
+    println!("  {}: {}", label("Resources"), count_str(details.resource_count));
+    println!("  {}: {}", label("Prompts"), count_str(details.prompt_count));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn capability_names_sorted() {
+        let caps = json!({"tools": {}, "prompts": {}, "logging": {}});
+        assert_eq!(capability_names(&caps), vec!["logging", "prompts", "tools"]);
+    }
+
+    #[test]
+    fn capability_names_empty() {
+        assert!(capability_names(&json!({})).is_empty());
+        assert!(capability_names(&json!(null)).is_empty());
+    }
+
+    #[test]
+    fn has_capability_checks_key() {
+        let caps = json!({"resources": {"subscribe": true}});
+        assert!(has_capability(&caps, "resources"));
+        assert!(!has_capability(&caps, "prompts"));
+    }
+
+    #[test]
+    fn lifecycle_str_values() {
+        assert_eq!(lifecycle_str(Some(&Lifecycle::KeepAlive)), "keep-alive");
+        assert_eq!(lifecycle_str(Some(&Lifecycle::Ephemeral)), "ephemeral");
+        assert_eq!(lifecycle_str(None), "ephemeral (default)");
+    }
+
+    #[test]
+    fn timeout_default() {
+        assert_eq!(parse_timeout_secs(None), Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+    }
+}
@@ -1,12 +1,15 @@
+use std::path::Path;
 use std::time::Duration;
 
 use colored::Colorize;
 
 use crate::config::load_config;
 use crate::error::McplugError;
+use crate::runtime::{HealthReport, Runtime};
 use crate::types::ToolDefinition;
 
-use super::connection::connect_to_server;
+use super::connection::{apply_env_overrides, connect_recorded};
+use super::fanout::{FanoutPolicy, FanoutSummary};
 
 /// Default timeout for list operations.
 const DEFAULT_TIMEOUT_SECS: u64 = 30;
@@ -24,67 +27,124 @@ fn parse_timeout_secs(val: Option<String>) -> Duration {
 
 /// Format a tool definition as a function signature string.
 fn format_tool_signature(tool: &ToolDefinition, all_parameters: bool) -> String {
-    let schema = &tool.input_schema;
-    let properties = schema.get("properties").and_then(|p| p.as_object());
-    let required: Vec<&str> = schema
-        .get("required")
-        .and_then(|r| r.as_array())
-        .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect())
-        .unwrap_or_default();
+    let schema = tool.schema();
+    let show_optional = all_parameters || schema.required.len() < 5;
+    let params: Vec<String> = schema
+        .properties
+        .iter()
+        .filter(|p| p.required || show_optional)
+        .map(|p| {
+            let marker = if p.required { "" } else { "?" };
+            format!("{}{}: {}", p.name, marker, p.type_name())
+        })
+        .collect();
 
-    let mut params = Vec::new();
-
-    if let Some(props) = properties {
-        let required_count = required.len();
-
-        for (name, prop_schema) in props {
-            let is_required = required.contains(&name.as_str());
-            let show_optional = all_parameters || required_count < 5;
-
-            if !is_required && !show_optional {
-                continue;
-            }
-
-            let type_str = prop_schema
-                .get("type")
-                .and_then(|t| t.as_str())
-                .unwrap_or("any");
+    format!("{}({})", tool.name, params.join(", "))
+}
 
-            if is_required {
-                params.push(format!("{}: {}", name, type_str));
-            } else {
-                params.push(format!("{}?: {}", name, type_str));
-            }
-        }
+/// Format a tool's annotations as a bracketed hint suffix, e.g. `[Delete File; destructive]`.
+fn format_annotation_hints(tool: &ToolDefinition) -> Option<String> {
+    let ann = tool.annotations.as_ref()?;
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(ref title) = ann.title {
+        parts.push(title.clone());
+    }
+    let labels = ann.hint_labels();
+    if !labels.is_empty() {
+        parts.push(labels.join(", "));
+    }
+    if parts.is_empty() {
+        None
+    } else {
+        Some(format!("[{}]", parts.join("; ")))
     }
-
-    format!("{}({})", tool.name, params.join(", "))
 }
 
 /// Run the list command.
+///
+/// A group name lists the status of each of its servers, and `tags` limit
+/// the listing of all servers to those with any of the tags. When listing
+/// several servers, `policy` decides the exit status and `fail_fast` skips
+/// the servers after the first unreachable one.
+#[allow(clippy::too_many_arguments)]
 pub async fn run_list(
     server: Option<&str>,
     http_url: Option<&str>,
     stdio: Option<&str>,
     json: bool,
     all_parameters: bool,
+    record: Option<&Path>,
+    replay: Option<&Path>,
+    policy: FanoutPolicy,
+    fail_fast: bool,
+    env: &[String],
+    tags: &[String],
 ) -> Result<(), McplugError> {
-    let config = load_config(None)?;
+    let mut config = load_config(None)?;
+    // A single server gets the overrides; all servers or a group apply them
+    // to each stdio member
+    let target = server.filter(|name| !config.is_group(name));
+    apply_env_overrides(&mut config, target, env)?;
     let timeout = get_timeout();
     let is_tty = std::io::stdout().is_terminal();
 
     match server {
+        Some(name)
+            if config.is_group(name)
+                && http_url.is_none()
+                && stdio.is_none()
+                && record.is_none()
+                && replay.is_none() =>
+        {
+            // A group lists the status of each member, like `mcplug list`
+            let members = config.resolve_servers(name)?;
+            list_all_servers(&config, &members, json, timeout, is_tty, policy, fail_fast).await
+        }
         Some(name) => {
             // List tools for a specific server
-            list_server_tools(name, &config, http_url, stdio, json, all_parameters, timeout, is_tty).await
+            list_server_tools(
+                name,
+                &config,
+                http_url,
+                stdio,
+                json,
+                all_parameters,
+                timeout,
+                is_tty,
+                record,
+                replay,
+            )
+            .await
         }
         None if http_url.is_some() || stdio.is_some() => {
             // Ad-hoc server without a name
-            list_server_tools("adhoc", &config, http_url, stdio, json, all_parameters, timeout, is_tty).await
+            list_server_tools(
+                "adhoc",
+                &config,
+                http_url,
+                stdio,
+                json,
+                all_parameters,
+                timeout,
+                is_tty,
+                record,
+                replay,
+            )
+            .await
         }
+        None if record.is_some() || replay.is_some() => Err(McplugError::ProtocolError(
+            "--record and --replay need a single server: mcplug list <server>".into(),
+        )),
         None => {
-            // List all configured servers
-            list_all_servers(&config, json, timeout, is_tty).await
+            // List all configured servers, except disabled ones
+            let names = config.tagged_servers(tags);
+            if names.is_empty() && !tags.is_empty() {
+                return Err(McplugError::ProtocolError(format!(
+                    "No enabled servers are tagged {}",
+                    tags.join(" or ")
+                )));
+            }
+            list_all_servers(&config, &names, json, timeout, is_tty, policy, fail_fast).await
         }
     }
 }
@@ -100,8 +160,17 @@ async fn list_server_tools(
     all_parameters: bool,
     timeout: Duration,
     is_tty: bool,
+    record: Option<&Path>,
+    replay: Option<&Path>,
 ) -> Result<(), McplugError> {
-    let mut transport = connect_to_server(server_name, config, http_url, stdio)?;
+    let timeout = if http_url.is_none() && stdio.is_none() {
+        config.list_timeout(server_name, timeout)
+    } else {
+        timeout
+    };
+    let mut transport =
+        connect_recorded(server_name, config, http_url, stdio, record, replay)?;
+    transport.wait_ready().await?;
 
     let tools = tokio::time::timeout(timeout, async {
         transport.initialize().await?;
@@ -118,11 +187,16 @@ async fn list_server_tools(
         let json_output = serde_json::json!({
             "server": server_name,
             "tools": tools.iter().map(|t| {
-                serde_json::json!({
+                let mut obj = serde_json::json!({
                     "name": t.name,
                     "description": t.description,
                     "inputSchema": t.input_schema,
-                })
+                    "schemaHash": t.schema_hash(),
+                });
+                if let Some(ref ann) = t.annotations {
+                    obj["annotations"] = serde_json::json!(ann);
+                }
+                obj
             }).collect::<Vec<_>>(),
             "toolCount": tools.len(),
         });
@@ -133,10 +207,12 @@ async fn list_server_tools(
     } else {
         for tool in &tools {
             let sig = format_tool_signature(tool, all_parameters);
-            if is_tty {
-                println!("  {}", sig.bold());
-            } else {
-                println!("  {}", sig);
+            let hints = format_annotation_hints(tool);
+            match (is_tty, hints) {
+                (true, Some(h)) => println!("  {} {}", sig.bold(), h.yellow()),
+                (true, None) => println!("  {}", sig.bold()),
+                (false, Some(h)) => println!("  {} {}", sig, h),
+                (false, None) => println!("  {}", sig),
             }
             if !tool.description.is_empty() {
                 if is_tty {
@@ -152,88 +228,76 @@ async fn list_server_tools(
     Ok(())
 }
 
-/// List all configured servers with connection status.
+/// List servers with their connection status.
+///
+/// Every server is reported as ok, error (with an error code), or skipped
+/// when `fail_fast` stopped the run at an earlier failure. The exit status
+/// follows `policy`.
+#[allow(clippy::too_many_arguments)]
 async fn list_all_servers(
     config: &crate::config::McplugConfig,
+    server_names: &[String],
     json: bool,
     timeout: Duration,
     is_tty: bool,
+    policy: FanoutPolicy,
+    fail_fast: bool,
 ) -> Result<(), McplugError> {
-    if config.mcp_servers.is_empty() {
+    if server_names.is_empty() {
         if json {
-            println!("{}", serde_json::json!({"servers": [], "total": 0, "reachable": 0, "unreachable": 0}));
+            println!("{}", serde_json::json!({"servers": [], "total": 0, "reachable": 0, "unreachable": 0, "skipped": 0}));
         } else {
             eprintln!("No servers configured.");
         }
         return Ok(());
     }
 
-    let mut server_names: Vec<&String> = config.mcp_servers.keys().collect();
-    server_names.sort();
-
+    let runtime = Runtime::builder()
+        .config(config.clone())
+        .default_timeout(timeout)
+        .build()
+        .await?;
     let mut results = Vec::new();
-
-    for name in &server_names {
-        let status = match connect_to_server(name, config, None, None) {
-            Ok(mut transport) => {
-                match tokio::time::timeout(timeout, transport.initialize()).await {
-                    Ok(Ok(info)) => {
-                        let _ = transport.close().await;
-                        ServerStatus {
-                            name: name.to_string(),
-                            reachable: true,
-                            version: Some(info.version),
-                            tool_count: None,
-                            error: None,
-                        }
-                    }
-                    Ok(Err(e)) => ServerStatus {
-                        name: name.to_string(),
-                        reachable: false,
-                        version: None,
-                        tool_count: None,
-                        error: Some(e.to_string()),
-                    },
-                    Err(_) => ServerStatus {
-                        name: name.to_string(),
-                        reachable: false,
-                        version: None,
-                        tool_count: None,
-                        error: Some(format!("Timeout after {}s", timeout.as_secs())),
-                    },
-                }
-            }
-            Err(e) => ServerStatus {
+    let mut summary = FanoutSummary::default();
+    for name in server_names {
+        if fail_fast && summary.failed > 0 {
+            summary.record_skipped();
+            results.push(ServerStatus {
                 name: name.to_string(),
-                reachable: false,
-                version: None,
-                tool_count: None,
-                error: Some(e.to_string()),
-            },
-        };
-        results.push(status);
+                report: None,
+            });
+            continue;
+        }
+        let report = runtime.health_check(name).await;
+        let _ = runtime.close_server(name).await;
+        summary.record(report.is_healthy());
+        results.push(ServerStatus {
+            name: name.to_string(),
+            report: Some(report),
+        });
     }
 
     if json {
-        let reachable = results.iter().filter(|s| s.reachable).count();
-        let unreachable = results.iter().filter(|s| !s.reachable).count();
         let json_output = serde_json::json!({
             "servers": results.iter().map(|s| {
                 let mut obj = serde_json::json!({
                     "name": s.name,
-                    "status": if s.reachable { "ok" } else { "error" },
+                    "status": s.label(),
                 });
-                if let Some(ref v) = s.version {
-                    obj["version"] = serde_json::json!(v);
-                }
-                if let Some(ref e) = s.error {
-                    obj["error"] = serde_json::json!(e);
+                if let Some(ref report) = s.report {
+                    let health = report.to_json();
+                    for key in ["version", "latencyMs", "error", "code"] {
+                        if let Some(value) = health.get(key) {
+                            obj[key] = value.clone();
+                        }
+                    }
                 }
                 obj
             }).collect::<Vec<_>>(),
-            "total": results.len(),
-            "reachable": reachable,
-            "unreachable": unreachable,
+            "total": summary.total,
+            "reachable": summary.ok,
+            "unreachable": summary.failed,
+            "skipped": summary.skipped,
         });
         println!(
             "{}",
@@ -241,44 +305,39 @@ async fn list_all_servers(
         );
     } else {
         for status in &results {
-            if status.reachable {
-                let label = if is_tty {
-                    "ok".green().to_string()
-                } else {
-                    "ok".to_string()
-                };
-                let version_str = status
-                    .version
-                    .as_deref()
-                    .map(|v| format!(" (v{})", v))
-                    .unwrap_or_default();
-                println!("  {} [{}]{}", status.name, label, version_str);
-            } else {
-                let label = if is_tty {
-                    "error".red().to_string()
-                } else {
-                    "error".to_string()
-                };
-                let err_str = status
-                    .error
-                    .as_deref()
-                    .map(|e| format!(" - {}", e))
-                    .unwrap_or_default();
-                println!("  {} [{}]{}", status.name, label, err_str);
-            }
+            let outcome = status.report.as_ref().map(|report| &report.result);
+            let label = match (outcome, is_tty) {
+                (_, false) => status.label().to_string(),
+                (Some(Ok(_)), true) => "ok".green().to_string(),
+                (Some(Err(_)), true) => "error".red().to_string(),
+                (None, true) => "skipped".dimmed().to_string(),
+            };
+            let detail = match outcome {
+                Some(Ok(info)) => format!(" (v{})", info.version),
+                Some(Err(e)) => format!(" - {}", e),
+                None => String::new(),
+            };
+            println!("  {} [{}]{}", status.name, label, detail);
         }
     }
 
-    Ok(())
+    summary.check(policy, "servers")
 }
 
 struct ServerStatus {
     name: String,
-    reachable: bool,
-    version: Option<String>,
-    #[allow(dead_code)]
-    tool_count: Option<usize>,
-    error: Option<String>,
+    /// How the server's health check went; `None` if it was skipped.
+    report: Option<HealthReport>,
+}
+
+impl ServerStatus {
+    fn label(&self) -> &'static str {
+        match self.report {
+            Some(ref report) if report.is_healthy() => "ok",
+            Some(_) => "error",
+            None => "skipped",
+        }
+    }
 }
 
 use std::io::IsTerminal;
@@ -293,6 +352,8 @@ mod tests {
             name: name.to_string(),
             description: desc.to_string(),
             input_schema: schema,
+            annotations: None,
+            output_schema: None,
         }
     }
 
@@ -368,6 +429,37 @@ mod tests {
         assert!(sig.contains("optional_one"));
     }
 
+    #[test]
+    fn annotation_hints_absent() {
+        let tool = make_tool("ping", "Ping", serde_json::json!({}));
+        assert_eq!(format_annotation_hints(&tool), None);
+    }
+
+    #[test]
+    fn annotation_hints_title_and_flags() {
+        let mut tool = make_tool("rm", "Remove", serde_json::json!({}));
+        tool.annotations = Some(crate::types::ToolAnnotations {
+            title: Some("Remove File".into()),
+            destructive_hint: Some(true),
+            idempotent_hint: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(
+            format_annotation_hints(&tool).as_deref(),
+            Some("[Remove File; destructive, idempotent]")
+        );
+    }
+
+    #[test]
+    fn annotation_hints_false_flags_omitted() {
+        let mut tool = make_tool("get", "Get", serde_json::json!({}));
+        tool.annotations = Some(crate::types::ToolAnnotations {
+            read_only_hint: Some(false),
+            ..Default::default()
+        });
+        assert_eq!(format_annotation_hints(&tool), None);
+    }
+
     #[test]
     fn timeout_default() {
         assert_eq!(parse_timeout_secs(None), Duration::from_secs(DEFAULT_TIMEOUT_SECS));
@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use crate::config::load_config;
+use crate::error::McplugError;
+use crate::transports::log_level::set_log_level;
+use crate::types::LogLevel;
+
+use super::connection::connect_to_server;
+
+/// Default timeout for setting the level.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Get the timeout from the environment variable or use the default.
+fn get_timeout() -> Duration {
+    parse_timeout_secs(std::env::var("MCPLUG_CALL_TIMEOUT").ok())
+}
+
+fn parse_timeout_secs(val: Option<String>) -> Duration {
+    val.and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+}
+
+/// Run the log-level command.
+///
+/// Sends `logging/setLevel` to the server. The level lasts for the server's
+/// session: an HTTP session or a keep-alive server keeps it, while an
+/// ephemeral stdio server exits when the command does, so use `logLevel` in
+/// its config instead.
+pub async fn run_log_level(
+    server: &str,
+    level: &str,
+    http_url: Option<&str>,
+    stdio: Option<&str>,
+) -> Result<(), McplugError> {
+    let level: LogLevel = level.parse()?;
+    let config = load_config(None)?;
+    let timeout = if http_url.is_none() && stdio.is_none() {
+        config.call_timeout(server, get_timeout())
+    } else {
+        get_timeout()
+    };
+
+    let mut transport = connect_to_server(server, &config, http_url, stdio)?;
+    transport.wait_ready().await?;
+    let outcome = tokio::time::timeout(timeout, async {
+        let info = transport.initialize().await?;
+        set_log_level(transport.as_ref(), &info, level).await
+    })
+    .await
+    .map_err(|_| McplugError::Timeout {
+        server: server.to_string(),
+        tool: None,
+        duration: timeout,
+    });
+    let _ = transport.close().await;
+
+    if !outcome?? {
+        return Err(McplugError::ProtocolError(format!(
+            "Server '{server}' does not advertise the logging capability"
+        )));
+    }
+    println!("Set {} log level to {}", server, level);
+    Ok(())
+}
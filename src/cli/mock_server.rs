@@ -0,0 +1,579 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Map, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::error::McplugError;
+use crate::transports::jsonrpc::PROTOCOL_VERSIONS;
+
+/// A mock server's definition, read from a JSON or YAML spec file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MockSpec {
+    /// The name reported by `initialize`.
+    #[serde(default = "default_name")]
+    pub name: String,
+    #[serde(default = "default_version")]
+    pub version: String,
+    /// Delay before every response, unless a tool or response sets its own.
+    #[serde(default, deserialize_with = "number")]
+    pub latency_ms: u64,
+    #[serde(default)]
+    pub tools: Vec<MockTool>,
+}
+
+/// A tool of the mock server and how it answers calls.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MockTool {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "object_schema")]
+    pub input_schema: Value,
+    #[serde(default)]
+    pub output_schema: Option<Value>,
+    #[serde(default)]
+    pub annotations: Option<Value>,
+    /// Answers tried in order; the first whose `when` matches is used. A
+    /// call no answer matches gets its arguments back as JSON text.
+    #[serde(default)]
+    pub responses: Vec<MockResponse>,
+    #[serde(default, deserialize_with = "optional_number")]
+    pub latency_ms: Option<u64>,
+    /// Fraction of calls, from 0 to 1, that fail with a JSON-RPC error.
+    #[serde(default, deserialize_with = "number")]
+    pub error_rate: f64,
+}
+
+/// One canned answer to a tool call.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MockResponse {
+    /// Arguments the answer is for: each must equal the call's argument of
+    /// the same name. Unset matches every call.
+    #[serde(default)]
+    pub when: Option<Map<String, Value>>,
+    /// Text content, with `{{name}}` replaced by the argument `name`.
+    #[serde(default)]
+    pub text: Option<String>,
+    /// The result's `structuredContent`, also sent as JSON text unless
+    /// `text` or `content` is set.
+    #[serde(default)]
+    pub json: Option<Value>,
+    /// Content blocks, sent as given.
+    #[serde(default)]
+    pub content: Option<Vec<Value>>,
+    /// Mark the result `isError`, as a tool reports a failure.
+    #[serde(default)]
+    pub is_error: bool,
+    /// Fail the call with this JSON-RPC error message instead.
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default, deserialize_with = "optional_number")]
+    pub latency_ms: Option<u64>,
+}
+
+fn default_name() -> String {
+    "mock-server".to_string()
+}
+
+fn default_version() -> String {
+    "0.0.0".to_string()
+}
+
+fn object_schema() -> Value {
+    json!({"type": "object", "properties": {}})
+}
+
+/// A number, also given as a string, since YAML scalars are read as strings.
+fn number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr + serde::de::DeserializeOwned,
+{
+    match Value::deserialize(deserializer)? {
+        Value::String(s) => s
+            .parse()
+            .map_err(|_| serde::de::Error::custom(format!("expected a number, got \"{s}\""))),
+        value => T::deserialize(value).map_err(serde::de::Error::custom),
+    }
+}
+
+fn optional_number<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    number(deserializer).map(Some)
+}
+
+impl MockSpec {
+    /// Read a spec from a JSON file, or from YAML for `.yaml` and `.yml`
+    /// files.
+    pub fn load(path: &Path) -> Result<Self, McplugError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            McplugError::IoError(std::io::Error::new(
+                e.kind(),
+                format!("cannot read mock server spec {}: {e}", path.display()),
+            ))
+        })?;
+        let invalid = |detail: String| {
+            McplugError::IoError(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid mock server spec {}: {detail}", path.display()),
+            ))
+        };
+        let yaml = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("yaml" | "yml")
+        );
+        let value = if yaml {
+            crate::config::yaml::parse(&content)
+                .ok_or_else(|| invalid("not in the supported block-YAML subset".to_string()))?
+        } else {
+            serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?
+        };
+        serde_json::from_value(value).map_err(|e| invalid(e.to_string()))
+    }
+}
+
+impl MockResponse {
+    /// Whether every `when` argument equals the call's. A string also
+    /// matches a number or boolean written the same way, as YAML specs
+    /// give them as strings.
+    fn matches(&self, args: &Value) -> bool {
+        let Some(when) = &self.when else {
+            return true;
+        };
+        when.iter().all(|(name, expected)| match (expected, args.get(name)) {
+            (_, None) => false,
+            (Value::String(s), Some(Value::Number(n))) => *s == n.to_string(),
+            (Value::String(s), Some(Value::Bool(b))) => *s == b.to_string(),
+            (expected, Some(actual)) => expected == actual,
+        })
+    }
+
+    /// The `tools/call` result this answer gives for `args`.
+    fn result(&self, args: &Value) -> Value {
+        let content = match (&self.content, &self.text, &self.json) {
+            (Some(content), _, _) => Value::Array(content.clone()),
+            (None, Some(text), _) => json!([{"type": "text", "text": fill_template(text, args)}]),
+            (None, None, Some(value)) => json!([{"type": "text", "text": value.to_string()}]),
+            (None, None, None) => json!([]),
+        };
+        let mut result = json!({"content": content, "isError": self.is_error});
+        if let Some(value) = &self.json {
+            result["structuredContent"] = value.clone();
+        }
+        result
+    }
+}
+
+/// Replace each `{{name}}` in `text` with the argument `name`: strings as
+/// they are, other values as JSON. Unknown names are left in place.
+fn fill_template(text: &str, args: &Value) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + len].trim();
+        out.push_str(&rest[..start]);
+        match args.get(name) {
+            Some(Value::String(s)) => out.push_str(s),
+            Some(value) => out.push_str(&value.to_string()),
+            None => out.push_str(&rest[start..start + len + 4]),
+        }
+        rest = &rest[start + len + 4..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Answers MCP requests from a [`MockSpec`].
+pub struct MockServer {
+    spec: MockSpec,
+}
+
+impl MockServer {
+    pub fn new(spec: MockSpec) -> Self {
+        Self { spec }
+    }
+
+    /// Answer one JSON-RPC message, after the configured latency.
+    /// Notifications get no answer.
+    pub async fn handle(&self, message: &Value) -> Option<Value> {
+        let id = message.get("id")?.clone();
+        let method = message["method"].as_str().unwrap_or("");
+        let params = &message["params"];
+
+        let mut latency = self.spec.latency_ms;
+        let outcome = match method {
+            "initialize" => {
+                let requested = params["protocolVersion"].as_str().unwrap_or("");
+                let version = PROTOCOL_VERSIONS
+                    .iter()
+                    .find(|v| **v == requested)
+                    .unwrap_or(&PROTOCOL_VERSIONS[0]);
+                Ok(json!({
+                    "protocolVersion": version,
+                    "serverInfo": {"name": self.spec.name, "version": self.spec.version},
+                    "capabilities": {"tools": {}},
+                }))
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({"tools": self.spec.tools.iter().map(tool_json).collect::<Vec<_>>()})),
+            "tools/call" => {
+                let name = params["name"].as_str().unwrap_or("");
+                let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+                match self.spec.tools.iter().find(|t| t.name == name) {
+                    Some(tool) => {
+                        let response = tool.responses.iter().find(|r| r.matches(&args));
+                        latency = response
+                            .and_then(|r| r.latency_ms)
+                            .or(tool.latency_ms)
+                            .unwrap_or(latency);
+                        call(tool, response, &args)
+                    }
+                    None => Err((-32602, format!("Unknown tool: {name}"))),
+                }
+            }
+            _ => Err((-32601, format!("Method not found: {method}"))),
+        };
+
+        if latency > 0 {
+            tokio::time::sleep(Duration::from_millis(latency)).await;
+        }
+        Some(match outcome {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => {
+                json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+            }
+        })
+    }
+}
+
+fn tool_json(tool: &MockTool) -> Value {
+    let mut value = json!({
+        "name": tool.name,
+        "description": tool.description,
+        "inputSchema": tool.input_schema,
+    });
+    if let Some(schema) = &tool.output_schema {
+        value["outputSchema"] = schema.clone();
+    }
+    if let Some(annotations) = &tool.annotations {
+        value["annotations"] = annotations.clone();
+    }
+    value
+}
+
+/// The result of calling `tool` with `args`, answered by `response`, or an
+/// error code and message.
+fn call(tool: &MockTool, response: Option<&MockResponse>, args: &Value) -> Result<Value, (i64, String)> {
+    if tool.error_rate > 0.0 && rand::random::<f64>() < tool.error_rate {
+        return Err((-32603, format!("Injected failure calling {}", tool.name)));
+    }
+    match response {
+        Some(MockResponse {
+            error: Some(message),
+            ..
+        }) => Err((-32603, fill_template(message, args))),
+        Some(response) => Ok(response.result(args)),
+        None => Ok(json!({"content": [{"type": "text", "text": args.to_string()}], "isError": false})),
+    }
+}
+
+/// Serve newline-delimited JSON-RPC on stdin and stdout until stdin
+/// closes. Requests are answered concurrently, so a slow call doesn't
+/// hold up the others.
+pub async fn serve_stdio(server: Arc<MockServer>) -> Result<(), McplugError> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Value>();
+    let writer = tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        while let Some(message) = rx.recv().await {
+            let mut line = message.to_string();
+            line.push('\n');
+            stdout.write_all(line.as_bytes()).await?;
+            stdout.flush().await?;
+        }
+        Ok::<(), std::io::Error>(())
+    });
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(e) => {
+                let _ = tx.send(parse_error(&e));
+                continue;
+            }
+        };
+        let server = server.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            if let Some(response) = server.handle(&message).await {
+                let _ = tx.send(response);
+            }
+        });
+    }
+    drop(tx);
+    writer
+        .await
+        .map_err(|e| McplugError::IoError(std::io::Error::other(e)))??;
+    Ok(())
+}
+
+/// Serve JSON-RPC over HTTP POSTs to any path, one connection per request:
+/// answers are JSON, and notifications get `202 Accepted`.
+pub async fn serve_http(listener: TcpListener, server: Arc<MockServer>) -> Result<(), McplugError> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let server = server.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &server).await {
+                tracing::debug!(error = %e, "mock server connection failed");
+            }
+        });
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, server: &MockServer) -> std::io::Result<()> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let method = head.split_whitespace().next().unwrap_or("");
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while buf.len() < header_end + content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body = &buf[header_end..buf.len().min(header_end + content_length)];
+
+    let (status, body) = if method != "POST" {
+        ("405 Method Not Allowed", String::new())
+    } else {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(message) => match server.handle(&message).await {
+                Some(response) => ("200 OK", response.to_string()),
+                None => ("202 Accepted", String::new()),
+            },
+            Err(e) => ("400 Bad Request", parse_error(&e).to_string()),
+        }
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn parse_error(e: &serde_json::Error) -> Value {
+    json!({"jsonrpc": "2.0", "id": null, "error": {"code": -32700, "message": format!("Parse error: {e}")}})
+}
+
+/// Run the mock server described by the spec at `path`, on stdio or, with
+/// `http` set to an address such as `127.0.0.1:8080`, over HTTP.
+pub async fn run(path: &Path, http: Option<&str>) -> Result<(), McplugError> {
+    let spec = MockSpec::load(path)?;
+    let name = spec.name.clone();
+    let tools = spec.tools.len();
+    let server = Arc::new(MockServer::new(spec));
+    match http {
+        Some(addr) => {
+            let listener = TcpListener::bind(addr).await.map_err(|e| {
+                McplugError::IoError(std::io::Error::new(e.kind(), format!("cannot listen on {addr}: {e}")))
+            })?;
+            eprintln!(
+                "Mock MCP server {name} with {tools} tools listening on http://{}/mcp",
+                listener.local_addr()?
+            );
+            serve_http(listener, server).await
+        }
+        None => serve_stdio(server).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(value: Value) -> MockSpec {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn request(method: &str, params: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params})
+    }
+
+    #[tokio::test]
+    async fn answers_initialize_and_lists_tools() {
+        let server = MockServer::new(spec(json!({
+            "name": "weather",
+            "version": "2.0.0",
+            "tools": [{"name": "forecast", "description": "Get a forecast", "outputSchema": {"type": "object"}}]
+        })));
+        let init = server
+            .handle(&request("initialize", json!({"protocolVersion": "2024-11-05"})))
+            .await
+            .unwrap();
+        assert_eq!(init["result"]["serverInfo"]["name"], "weather");
+        assert_eq!(init["result"]["protocolVersion"], "2024-11-05");
+        let init = server
+            .handle(&request("initialize", json!({"protocolVersion": "1999-01-01"})))
+            .await
+            .unwrap();
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+
+        let list = server.handle(&request("tools/list", json!({}))).await.unwrap();
+        let tool = &list["result"]["tools"][0];
+        assert_eq!(tool["name"], "forecast");
+        assert_eq!(tool["inputSchema"], object_schema());
+        assert_eq!(tool["outputSchema"], json!({"type": "object"}));
+
+        assert!(server
+            .handle(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .await
+            .is_none());
+        let unknown = server.handle(&request("resources/list", json!({}))).await.unwrap();
+        assert_eq!(unknown["error"]["code"], -32601);
+    }
+
+    #[tokio::test]
+    async fn calls_pick_the_first_matching_response() {
+        let server = MockServer::new(spec(json!({
+            "tools": [{
+                "name": "lookup",
+                "responses": [
+                    {"when": {"id": "42"}, "json": {"name": "Ada"}},
+                    {"when": {"id": "0"}, "text": "no user {{id}}", "isError": true},
+                    {"when": {"id": "-1"}, "error": "database down"},
+                    {"text": "user {{id}} of {{missing}}"}
+                ]
+            }, {"name": "echo"}]
+        })));
+        let call = |tool: &str, args: Value| request("tools/call", json!({"name": tool, "arguments": args}));
+
+        let found = server.handle(&call("lookup", json!({"id": 42}))).await.unwrap();
+        assert_eq!(found["result"]["structuredContent"], json!({"name": "Ada"}));
+        assert_eq!(found["result"]["content"][0]["text"], "{\"name\":\"Ada\"}");
+
+        let missing = server.handle(&call("lookup", json!({"id": 0}))).await.unwrap();
+        assert_eq!(missing["result"]["isError"], true);
+        assert_eq!(missing["result"]["content"][0]["text"], "no user 0");
+
+        let failed = server.handle(&call("lookup", json!({"id": -1}))).await.unwrap();
+        assert_eq!(failed["error"]["message"], "database down");
+
+        let other = server.handle(&call("lookup", json!({"id": "7"}))).await.unwrap();
+        assert_eq!(other["result"]["content"][0]["text"], "user 7 of {{missing}}");
+
+        let echoed = server.handle(&call("echo", json!({"x": 1}))).await.unwrap();
+        assert_eq!(echoed["result"]["content"][0]["text"], "{\"x\":1}");
+
+        let unknown = server.handle(&call("nope", json!({}))).await.unwrap();
+        assert_eq!(unknown["error"]["message"], "Unknown tool: nope");
+    }
+
+    #[tokio::test]
+    async fn injects_latency_and_failures() {
+        let server = MockServer::new(spec(json!({
+            "latencyMs": 1,
+            "tools": [
+                {"name": "slow", "latencyMs": "30"},
+                {"name": "flaky", "errorRate": 1}
+            ]
+        })));
+        let call = |tool: &str| request("tools/call", json!({"name": tool, "arguments": {}}));
+
+        let start = std::time::Instant::now();
+        server.handle(&call("slow")).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(30));
+
+        let failed = server.handle(&call("flaky")).await.unwrap();
+        assert_eq!(failed["error"]["code"], -32603);
+        assert_eq!(failed["error"]["message"], "Injected failure calling flaky");
+    }
+
+    #[test]
+    fn loads_json_and_yaml_specs() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = dir.path().join("mock.yaml");
+        std::fs::write(
+            &yaml,
+            "name: weather\nlatencyMs: 5\ntools:\n  - name: forecast\n    errorRate: 0.5\n    responses:\n      - when:\n          city: Taipei\n        text: Sunny\n",
+        )
+        .unwrap();
+        let spec = MockSpec::load(&yaml).unwrap();
+        assert_eq!(spec.name, "weather");
+        assert_eq!(spec.latency_ms, 5);
+        assert_eq!(spec.tools[0].error_rate, 0.5);
+        assert!(spec.tools[0].responses[0].matches(&json!({"city": "Taipei"})));
+
+        let json_path = dir.path().join("mock.json");
+        std::fs::write(&json_path, r#"{"tools": [{"name": "a", "latency": 5}]}"#).unwrap();
+        let err = MockSpec::load(&json_path).unwrap_err().to_string();
+        assert!(err.contains("invalid mock server spec"), "{err}");
+        assert!(err.contains("latency"), "{err}");
+        assert!(MockSpec::load(&dir.path().join("missing.json")).is_err());
+    }
+
+    #[tokio::test]
+    async fn serves_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        let server = Arc::new(MockServer::new(spec(json!({
+            "tools": [{"name": "hello", "responses": [{"text": "hi {{who}}"}]}]
+        }))));
+        tokio::spawn(serve_http(listener, server));
+
+        let client = reqwest::Client::new();
+        let response: Value = client
+            .post(&url)
+            .json(&request("tools/call", json!({"name": "hello", "arguments": {"who": "there"}})))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["content"][0]["text"], "hi there");
+
+        let notification = client
+            .post(&url)
+            .json(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(notification.status(), reqwest::StatusCode::ACCEPTED);
+        assert_eq!(client.get(&url).send().await.unwrap().status(), reqwest::StatusCode::METHOD_NOT_ALLOWED);
+    }
+}
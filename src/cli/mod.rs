@@ -1,5 +1,24 @@
+pub mod batch;
+pub mod bench;
+pub mod auth_cmd;
 pub mod call;
+pub mod completions;
 pub mod config_cmd;
+pub mod conformance;
 pub mod connection;
+pub mod diagnose;
+pub mod explain;
+pub mod fanout;
+pub mod history;
+pub mod info;
 pub mod list;
+pub mod log_level;
+pub mod mock_server;
 pub mod output;
+pub mod pipe;
+pub mod prefetch;
+pub mod result_cmd;
+pub mod search;
+pub mod server_cmd;
+pub mod warm;
+pub mod watch;
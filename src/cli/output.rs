@@ -1,3 +1,7 @@
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 use colored::Colorize;
 
 use crate::error::McplugError;
@@ -8,21 +12,52 @@ pub enum OutputMode {
     Pretty,
     Raw,
     Json,
+    Markdown,
 }
 
 pub fn print_call_result(result: &CallResult, mode: OutputMode, is_tty: bool) {
+    print_call_result_with_images(result, mode, is_tty, &[]);
+}
+
+/// Print a result whose image blocks were written to files by
+/// [`save_images`]: saved images show their path instead of a placeholder,
+/// and JSON output lists them under `savedImages`.
+pub fn print_call_result_with_images(
+    result: &CallResult,
+    mode: OutputMode,
+    is_tty: bool,
+    saved: &[(usize, PathBuf)],
+) {
+    let saved_path = |index: usize| {
+        saved
+            .iter()
+            .find(|(i, _)| *i == index)
+            .map(|(_, path)| path.display().to_string())
+    };
     match mode {
         OutputMode::Json => {
-            let json = serde_json::json!({
+            let mut json = serde_json::json!({
                 "content": result.content,
                 "isError": result.is_error,
             });
+            if !saved.is_empty() {
+                json["savedImages"] = saved
+                    .iter()
+                    .map(|(_, path)| serde_json::json!(path.display().to_string()))
+                    .collect();
+            }
             println!("{}", serde_json::to_string_pretty(&json).unwrap_or_default());
         }
         OutputMode::Raw => {
             print!("{}", result.text());
+            for (_, path) in saved {
+                eprintln!("Saved image to {}", path.display());
+            }
         }
-        OutputMode::Pretty => {
+        OutputMode::Markdown if !result.is_error => {
+            println!("{}", result.markdown());
+        }
+        OutputMode::Pretty | OutputMode::Markdown => {
             if result.is_error {
                 let label = if is_tty {
                     "Error".red().bold().to_string()
@@ -31,13 +66,16 @@ pub fn print_call_result(result: &CallResult, mode: OutputMode, is_tty: bool) {
                 };
                 eprintln!("{}: {}", label, result.text());
             } else {
-                for block in &result.content {
+                for (index, block) in result.content.iter().enumerate() {
                     match block {
                         ContentBlock::Text { text } => {
                             println!("{}", text);
                         }
                         ContentBlock::Image { mime_type, .. } => {
-                            let msg = format!("[image: {}]", mime_type);
+                            let msg = match saved_path(index) {
+                                Some(path) => format!("[image: {} saved to {}]", mime_type, path),
+                                None => format!("[image: {}]", mime_type),
+                            };
                             if is_tty {
                                 println!("{}", msg.dimmed());
                             } else {
@@ -59,6 +97,49 @@ pub fn print_call_result(result: &CallResult, mode: OutputMode, is_tty: bool) {
     }
 }
 
+/// File extension for an image MIME type: `image/png` gives `png`,
+/// `image/svg+xml` gives `svg`.
+fn image_extension(mime_type: &str) -> String {
+    let subtype = mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .trim();
+    let subtype = subtype.split('+').next().unwrap_or_default();
+    match subtype {
+        "jpeg" => "jpg".to_string(),
+        s if !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') => {
+            s.to_ascii_lowercase()
+        }
+        _ => "bin".to_string(),
+    }
+}
+
+/// Decode a result's image blocks and write them to `dir` (created if
+/// missing) as `image-<n>.<ext>`, where `n` is the block's position in the
+/// content. Returns each block's index and path.
+pub fn save_images(result: &CallResult, dir: &Path) -> Result<Vec<(usize, PathBuf)>, McplugError> {
+    let mut saved = Vec::new();
+    for (index, block) in result.content.iter().enumerate() {
+        let ContentBlock::Image { data, mime_type } = block else {
+            continue;
+        };
+        let bytes = STANDARD.decode(data.trim()).map_err(|e| {
+            McplugError::ProtocolError(format!(
+                "Image in content block {index} has invalid base64 data: {e}"
+            ))
+        })?;
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("image-{}.{}", index, image_extension(mime_type)));
+        std::fs::write(&path, bytes)?;
+        saved.push((index, path));
+    }
+    Ok(saved)
+}
+
 pub fn print_error(err: &McplugError, json_mode: bool) {
     if json_mode {
         println!("{}", serde_json::to_string_pretty(&err.to_json()).unwrap_or_default());
@@ -71,6 +152,52 @@ pub fn print_error(err: &McplugError, json_mode: bool) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn image_extensions() {
+        assert_eq!(image_extension("image/png"), "png");
+        assert_eq!(image_extension("image/jpeg"), "jpg");
+        assert_eq!(image_extension("image/svg+xml"), "svg");
+        assert_eq!(image_extension("image/webp; q=1"), "webp");
+        assert_eq!(image_extension("image/../x"), "x");
+        assert_eq!(image_extension(""), "bin");
+    }
+
+    #[test]
+    fn save_images_decodes_by_block_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = CallResult {
+            content: vec![
+                ContentBlock::Text { text: "hi".into() },
+                ContentBlock::Image {
+                    data: STANDARD.encode(b"png-bytes"),
+                    mime_type: "image/png".into(),
+                },
+            ],
+            is_error: false,
+            raw_response: None,
+        };
+        let saved = save_images(&result, &dir.path().join("out")).unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].0, 1);
+        assert!(saved[0].1.ends_with("image-1.png"));
+        assert_eq!(std::fs::read(&saved[0].1).unwrap(), b"png-bytes");
+    }
+
+    #[test]
+    fn save_images_rejects_bad_base64() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = CallResult {
+            content: vec![ContentBlock::Image {
+                data: "not base64!".into(),
+                mime_type: "image/png".into(),
+            }],
+            is_error: false,
+            raw_response: None,
+        };
+        let err = save_images(&result, dir.path()).unwrap_err();
+        assert!(err.to_string().contains("content block 0"));
+    }
+
     #[test]
     fn output_mode_equality() {
         assert_eq!(OutputMode::Pretty, OutputMode::Pretty);
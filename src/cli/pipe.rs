@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::args::{parse_function_call, parse_tool_ref};
+use crate::config::load_config;
+use crate::error::McplugError;
+use crate::jsonpath;
+use crate::types::CallResult;
+
+use super::batch::ConnectionPool;
+use super::output::{print_call_result, OutputMode};
+
+/// Default timeout for each call in a pipeline.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Get the per-call timeout from the environment variable or use the default.
+fn get_timeout() -> Duration {
+    parse_timeout_secs(std::env::var("MCPLUG_CALL_TIMEOUT").ok())
+}
+
+fn parse_timeout_secs(val: Option<String>) -> Duration {
+    val.and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+}
+
+/// One step in a pipeline.
+#[derive(Debug, PartialEq)]
+enum Stage {
+    /// `server.tool(args)`: call a tool.
+    Call {
+        server: String,
+        tool: String,
+        args: Value,
+    },
+    /// `vars(name: expr, ...)`: bind variables from the previous result.
+    Vars(serde_json::Map<String, Value>),
+}
+
+/// Split a pipeline on `|`, ignoring pipes inside quotes, parentheses,
+/// braces, and brackets.
+fn split_stages(input: &str) -> Vec<String> {
+    let mut stages = Vec::new();
+    let mut current = String::new();
+    let mut in_quote: Option<char> = None;
+    let mut depth = 0i32;
+
+    for ch in input.chars() {
+        match in_quote {
+            Some(q) => {
+                if ch == q {
+                    in_quote = None;
+                }
+                current.push(ch);
+            }
+            None => match ch {
+                '"' | '\'' => {
+                    in_quote = Some(ch);
+                    current.push(ch);
+                }
+                '(' | '{' | '[' => {
+                    depth += 1;
+                    current.push(ch);
+                }
+                ')' | '}' | ']' => {
+                    depth -= 1;
+                    current.push(ch);
+                }
+                '|' if depth == 0 => {
+                    stages.push(current.trim().to_string());
+                    current.clear();
+                }
+                _ => current.push(ch),
+            },
+        }
+    }
+    stages.push(current.trim().to_string());
+    stages
+}
+
+/// Parse a pipeline into stages. Each stage is `server.tool(named: args)`,
+/// a bare `server.tool`, or `vars(name: expr, ...)`.
+fn parse_pipeline(input: &str) -> Result<Vec<Stage>, McplugError> {
+    split_stages(input)
+        .into_iter()
+        .enumerate()
+        .map(|(i, stage)| {
+            if stage.is_empty() {
+                return Err(McplugError::ProtocolError(format!(
+                    "Pipeline stage {} is empty",
+                    i + 1
+                )));
+            }
+            let named_args_error = || {
+                McplugError::ProtocolError(format!(
+                    "Pipeline stage {} ({stage}): arguments must be named (key: value)",
+                    i + 1
+                ))
+            };
+            if let Some(inner) = stage
+                .strip_prefix("vars(")
+                .and_then(|s| s.strip_suffix(')'))
+            {
+                // Reuse the function-call parser for the name: expr list
+                let (_, _, args) = parse_function_call(&format!("vars.vars({inner})"))?;
+                return match args {
+                    Value::Object(map) => Ok(Stage::Vars(map)),
+                    _ => Err(named_args_error()),
+                };
+            }
+            let (server, tool, args) = if stage.contains('(') {
+                parse_function_call(&stage)?
+            } else {
+                let (server, tool) = parse_tool_ref(&stage)?;
+                (server, tool, serde_json::json!({}))
+            };
+            if !args.is_object() {
+                return Err(named_args_error());
+            }
+            Ok(Stage::Call { server, tool, args })
+        })
+        .collect()
+}
+
+fn is_var_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Replace `$name` references in string values with bound values.
+///
+/// A value that is exactly `$name` takes the bound value as-is; a `$name`
+/// embedded in longer text is replaced by the value's text. A whole-value
+/// reference to an unbound name is an error; embedded unbound names are left
+/// alone so text like prices survives.
+fn substitute(value: &Value, bindings: &HashMap<String, Value>) -> Result<Value, McplugError> {
+    match value {
+        Value::String(s) => {
+            if let Some(name) = s.strip_prefix('$') {
+                if !name.is_empty() && name.chars().all(is_var_char) {
+                    return bindings.get(name).cloned().ok_or_else(|| {
+                        McplugError::ProtocolError(format!(
+                            "Unknown pipeline variable '${name}'"
+                        ))
+                    });
+                }
+            }
+            Ok(Value::String(interpolate(s, bindings)))
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(|v| substitute(v, bindings))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), substitute(v, bindings)?)))
+            .collect::<Result<serde_json::Map<_, _>, McplugError>>()
+            .map(Value::Object),
+        other => Ok(other.clone()),
+    }
+}
+
+/// Replace bound `$name` occurrences inside a string.
+fn interpolate(s: &str, bindings: &HashMap<String, Value>) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        let len = after.find(|c: char| !is_var_char(c)).unwrap_or(after.len());
+        match bindings.get(&after[..len]) {
+            Some(value) if len > 0 => {
+                match value {
+                    Value::String(text) => out.push_str(text),
+                    other => out.push_str(&other.to_string()),
+                }
+                rest = &after[len..];
+            }
+            _ => {
+                out.push('$');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Evaluate a `vars()` expression against the previous call's result.
+///
+/// JSONPath expressions (`$.id`, `$.items[0].url`) select from the result
+/// text parsed as JSON; anything else is substituted like a call argument.
+fn eval_var(
+    expr: &Value,
+    last: &CallResult,
+    bindings: &HashMap<String, Value>,
+) -> Result<Value, McplugError> {
+    match expr {
+        Value::String(path) if jsonpath::is_path(path) => {
+            let doc: Value = last.json().map_err(|_| {
+                McplugError::ProtocolError(format!(
+                    "Cannot apply '{path}': the previous result is not JSON"
+                ))
+            })?;
+            jsonpath::select(&doc, path)
+        }
+        other => substitute(other, bindings),
+    }
+}
+
+/// Run the pipe command.
+///
+/// Stages run in order. After each call the text output is bound to `$out`
+/// for the next stage's arguments, and `vars(name: expr)` stages bind more
+/// names from the latest result. A call returning an error result stops the
+/// pipeline. Only the final call's result is printed.
+pub async fn run_pipe(pipeline: &str, json: bool) -> Result<(), McplugError> {
+    let stages = parse_pipeline(pipeline)?;
+    match stages.first() {
+        Some(Stage::Vars(_)) => {
+            return Err(McplugError::ProtocolError(
+                "vars() needs a previous call to read from".into(),
+            ));
+        }
+        Some(Stage::Call { args, .. }) if uses_out(args) => {
+            return Err(McplugError::ProtocolError(
+                "The first pipeline stage has no previous output to bind to '$out'".into(),
+            ));
+        }
+        _ => {}
+    }
+
+    let config = Arc::new(load_config(None)?);
+    let timeout = get_timeout();
+    let mut pool = ConnectionPool::new(config);
+    let mut bindings = HashMap::new();
+    let outcome = run_stages(&mut pool, &stages, &mut bindings, timeout).await;
+    pool.close().await;
+
+    let result = outcome?;
+    let mode = if json { OutputMode::Json } else { OutputMode::Pretty };
+    print_call_result(&result, mode, std::io::stdout().is_terminal());
+    Ok(())
+}
+
+fn uses_out(args: &Value) -> bool {
+    match args {
+        Value::String(s) => s.contains("$out"),
+        Value::Array(items) => items.iter().any(uses_out),
+        Value::Object(map) => map.values().any(uses_out),
+        _ => false,
+    }
+}
+
+async fn run_stages(
+    pool: &mut ConnectionPool,
+    stages: &[Stage],
+    bindings: &mut HashMap<String, Value>,
+    timeout: Duration,
+) -> Result<CallResult, McplugError> {
+    let mut last: Option<CallResult> = None;
+    for (i, stage) in stages.iter().enumerate() {
+        let (server, tool, args) = match stage {
+            Stage::Call { server, tool, args } => (server, tool, args),
+            Stage::Vars(vars) => {
+                let Some(ref result) = last else { continue };
+                for (name, expr) in vars {
+                    let value = eval_var(expr, result, bindings).map_err(|e| {
+                        McplugError::ProtocolError(format!(
+                            "Pipeline stage {} (vars): {name}: {e}",
+                            i + 1
+                        ))
+                    })?;
+                    bindings.insert(name.clone(), value);
+                }
+                continue;
+            }
+        };
+
+        let args = substitute(args, bindings)?;
+        let timeout = pool.call_timeout(server, timeout);
+        let result = pool.call_tool(server, tool, args, timeout).await?;
+
+        if result.is_error {
+            return Err(McplugError::ProtocolError(format!(
+                "Pipeline stage {} ({}.{}) failed: {}",
+                i + 1,
+                server,
+                tool,
+                result.text()
+            )));
+        }
+        bindings.insert("out".to_string(), Value::String(result.text()));
+        last = Some(result);
+    }
+    last.ok_or_else(|| McplugError::ProtocolError("Pipeline has no calls".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bindings(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn split_respects_quotes_and_parens() {
+        let stages = split_stages(r#"a.fetch(url: "x|y") | b.summarize(text: $out)|c.done"#);
+        assert_eq!(
+            stages,
+            vec![
+                r#"a.fetch(url: "x|y")"#,
+                "b.summarize(text: $out)",
+                "c.done"
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_stages() {
+        let stages = parse_pipeline("a.fetch(url: \"https://x\") | b.summarize").unwrap();
+        assert_eq!(
+            stages,
+            vec![
+                Stage::Call {
+                    server: "a".into(),
+                    tool: "fetch".into(),
+                    args: json!({"url": "https://x"}),
+                },
+                Stage::Call {
+                    server: "b".into(),
+                    tool: "summarize".into(),
+                    args: json!({}),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_vars_stage() {
+        let stages = parse_pipeline("a.create() | vars(id: $.items[0].id, n: 2)").unwrap();
+        let Stage::Vars(ref vars) = stages[1] else {
+            panic!("expected vars stage");
+        };
+        assert_eq!(vars["id"], json!("$.items[0].id"));
+        assert_eq!(vars["n"], json!(2));
+        assert!(parse_pipeline("a.b() | vars(\"x\")").is_err());
+    }
+
+    #[test]
+    fn eval_var_jsonpath_and_substitution() {
+        let last = CallResult {
+            content: vec![crate::types::ContentBlock::Text {
+                text: r#"{"id": 7, "url": "https://x"}"#.into(),
+            }],
+            is_error: false,
+            raw_response: None,
+        };
+        let b = bindings(&[("out", json!("raw"))]);
+        assert_eq!(eval_var(&json!("$.id"), &last, &b).unwrap(), json!(7));
+        assert_eq!(eval_var(&json!("got $out"), &last, &b).unwrap(), json!("got raw"));
+
+        let not_json = CallResult {
+            content: vec![crate::types::ContentBlock::Text { text: "plain".into() }],
+            is_error: false,
+            raw_response: None,
+        };
+        let err = eval_var(&json!("$.id"), &not_json, &b).unwrap_err();
+        assert!(err.to_string().contains("not JSON"));
+    }
+
+    #[test]
+    fn parse_rejects_empty_stage() {
+        let err = parse_pipeline("a.fetch() | ").unwrap_err();
+        assert!(err.to_string().contains("stage 2 is empty"));
+    }
+
+    #[test]
+    fn parse_rejects_positional_args() {
+        let err = parse_pipeline("a.fetch(\"x\")").unwrap_err();
+        assert!(err.to_string().contains("must be named"));
+    }
+
+    #[test]
+    fn substitute_whole_value() {
+        let b = bindings(&[("out", json!("hello"))]);
+        let args = substitute(&json!({"text": "$out", "n": 3}), &b).unwrap();
+        assert_eq!(args, json!({"text": "hello", "n": 3}));
+    }
+
+    #[test]
+    fn substitute_embedded() {
+        let b = bindings(&[("out", json!("world"))]);
+        let args = substitute(&json!({"text": "hello $out, costs $5"}), &b).unwrap();
+        assert_eq!(args, json!({"text": "hello world, costs $5"}));
+    }
+
+    #[test]
+    fn substitute_unknown_whole_value_errors() {
+        let err = substitute(&json!({"text": "$nope"}), &HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("$nope"));
+    }
+
+    #[test]
+    fn detects_out_usage() {
+        assert!(uses_out(&json!({"a": ["x $out"]})));
+        assert!(!uses_out(&json!({"a": "plain"})));
+    }
+}
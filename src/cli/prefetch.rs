@@ -0,0 +1,234 @@
+use std::time::Instant;
+
+use crate::config::{load_config, ServerConfig};
+use crate::error::McplugError;
+use crate::transports::stdio::install_timeout;
+
+/// A command that warms the package or image cache for a server.
+#[derive(Debug, PartialEq)]
+struct PrefetchStep {
+    program: String,
+    args: Vec<String>,
+}
+
+impl PrefetchStep {
+    fn new(program: &str, args: &[&str]) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    fn display(&self) -> String {
+        std::iter::once(self.program.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// The first positional argument, skipping flags. `value_flags` take the
+/// following argument as their value; `--flag=value` is always one token.
+fn first_positional<'a>(args: &'a [String], value_flags: &[&str]) -> Option<&'a str> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if !arg.starts_with('-') {
+            return Some(arg);
+        }
+        if value_flags.contains(&arg.as_str()) {
+            iter.next();
+        }
+    }
+    None
+}
+
+/// Value of `flag` given as `flag value` or `flag=value`.
+fn flag_value<'a>(args: &'a [String], flags: &[&str]) -> Option<&'a str> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        flags.iter().find_map(|flag| {
+            if arg == flag {
+                args.get(i + 1).map(String::as_str)
+            } else {
+                arg.strip_prefix(flag)?.strip_prefix('=')
+            }
+        })
+    })
+}
+
+/// Flags of `docker run` that take a separate value.
+const DOCKER_VALUE_FLAGS: &[&str] = &[
+    "-e", "--env", "--env-file", "-v", "--volume", "--mount", "-p", "--publish", "--name",
+    "--network", "-w", "--workdir", "-u", "--user", "--entrypoint", "-l", "--label",
+    "--platform", "-m", "--memory",
+];
+
+/// Work out how to prefetch a server from its command, or `None` if it is
+/// not launched through a package runner or Docker.
+fn prefetch_step(cfg: &ServerConfig) -> Option<PrefetchStep> {
+    let command = cfg.command.as_deref()?;
+    let program = std::path::Path::new(command)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(command);
+    let args = &cfg.args;
+
+    match program {
+        "npx" => {
+            let package = flag_value(args, &["--package", "-p"])
+                .or_else(|| first_positional(args, &["--package", "-p"]))?;
+            // Runs a no-op inside the npx environment, which installs the package
+            Some(PrefetchStep::new(
+                command,
+                &["--yes", "--package", package, "--", "node", "-e", ""],
+            ))
+        }
+        "uvx" => {
+            let package = flag_value(args, &["--from"])
+                .or_else(|| first_positional(args, &["--from", "--with", "--python", "-p"]))?;
+            Some(PrefetchStep::new(
+                command,
+                &["--from", package, "python", "-c", "pass"],
+            ))
+        }
+        "docker" | "podman" => {
+            let run = args.iter().position(|a| a == "run")?;
+            let image = first_positional(&args[run + 1..], DOCKER_VALUE_FLAGS)?;
+            Some(PrefetchStep::new(command, &["pull", image]))
+        }
+        _ => None,
+    }
+}
+
+/// Run the prefetch command.
+///
+/// For each server (all enabled servers when none are named; group names
+/// expand to their servers), pre-installs
+/// its npm/uv package or pulls its Docker image without starting an MCP
+/// session. Servers not launched through npx, uvx, or Docker are skipped.
+pub async fn run_prefetch(servers: &[String]) -> Result<(), McplugError> {
+    let config = load_config(None)?;
+    let mut names: Vec<String> = if servers.is_empty() {
+        config.enabled_servers()
+    } else {
+        config.resolve_all(servers)?
+    };
+    names.sort();
+
+    let mut attempted = 0;
+    let mut failed = 0;
+    for name in names {
+        let cfg = config
+            .mcp_servers
+            .get(name.as_str())
+            .ok_or_else(|| McplugError::ServerNotFound(name.to_string()))?;
+        let Some(step) = prefetch_step(cfg) else {
+            println!("{}: nothing to prefetch", name);
+            continue;
+        };
+
+        attempted += 1;
+        println!("{}: {}", name, step.display());
+        let started = Instant::now();
+        let mut child = match tokio::process::Command::new(&step.program)
+            .args(&step.args)
+            .envs(&cfg.env)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!("{}: failed to run {}: {}", name, step.program, e);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let limit = install_timeout(cfg.install_timeout);
+        match tokio::time::timeout(limit, child.wait()).await {
+            Ok(Ok(status)) if status.success() => {
+                println!("{}: ready in {:.1}s", name, started.elapsed().as_secs_f64());
+            }
+            Ok(Ok(status)) => {
+                eprintln!("{}: prefetch failed ({})", name, status);
+                failed += 1;
+            }
+            Ok(Err(e)) => {
+                eprintln!("{}: prefetch failed: {}", name, e);
+                failed += 1;
+            }
+            Err(_) => {
+                let _ = child.kill().await;
+                eprintln!("{}: prefetch timed out after {}s", name, limit.as_secs());
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(McplugError::ProtocolError(format!(
+            "{failed} of {attempted} prefetches failed"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stdio(command: &str, args: &[&str]) -> ServerConfig {
+        serde_json::from_value(serde_json::json!({
+            "command": command,
+            "args": args,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn npx_package() {
+        let cfg = stdio("npx", &["-y", "@scope/server@1.2", "--port", "1"]);
+        let step = prefetch_step(&cfg).unwrap();
+        assert_eq!(
+            step.display(),
+            "npx --yes --package @scope/server@1.2 -- node -e "
+        );
+        let step = prefetch_step(&stdio("npx", &["-y", "--package=pkg", "pkg-bin"])).unwrap();
+        assert_eq!(step.args[2], "pkg");
+    }
+
+    #[test]
+    fn uvx_package() {
+        let step = prefetch_step(&stdio("uvx", &["mcp-server-fetch"])).unwrap();
+        assert_eq!(step.display(), "uvx --from mcp-server-fetch python -c pass");
+        let cfg = stdio("uvx", &["--from", "git+https://x/y", "srv"]);
+        let step = prefetch_step(&cfg).unwrap();
+        assert_eq!(step.args[1], "git+https://x/y");
+    }
+
+    #[test]
+    fn docker_image() {
+        let step = prefetch_step(&stdio(
+            "docker",
+            &["run", "-i", "--rm", "-e", "TOKEN", "ghcr.io/org/server:latest"],
+        ))
+        .unwrap();
+        assert_eq!(step.display(), "docker pull ghcr.io/org/server:latest");
+    }
+
+    #[test]
+    fn full_path_commands() {
+        let step = prefetch_step(&stdio("/usr/local/bin/npx", &["-y", "pkg"])).unwrap();
+        assert_eq!(step.program, "/usr/local/bin/npx");
+        assert_eq!(step.args[2], "pkg");
+    }
+
+    #[test]
+    fn nothing_to_prefetch() {
+        assert!(prefetch_step(&stdio("python", &["server.py"])).is_none());
+        assert!(prefetch_step(&stdio("docker", &["ps"])).is_none());
+        let http: ServerConfig =
+            serde_json::from_value(serde_json::json!({"baseUrl": "https://x"})).unwrap();
+        assert!(prefetch_step(&http).is_none());
+    }
+}
@@ -0,0 +1,36 @@
+use std::io::IsTerminal;
+
+use crate::error::McplugError;
+use crate::results::{list_results, load_result};
+
+use super::output::{print_call_result, OutputMode};
+
+/// Print a result saved with `call --save-as`.
+pub async fn run_result_show(name: &str, json: bool) -> Result<(), McplugError> {
+    let saved = load_result(name)?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&saved).unwrap_or_default()
+        );
+    } else {
+        print_call_result(
+            &saved.result,
+            OutputMode::Pretty,
+            std::io::stdout().is_terminal(),
+        );
+    }
+    Ok(())
+}
+
+/// List the names of saved results.
+pub async fn run_result_list() -> Result<(), McplugError> {
+    let names = list_results();
+    if names.is_empty() {
+        eprintln!("No saved results.");
+    }
+    for name in names {
+        println!("{}", name);
+    }
+    Ok(())
+}
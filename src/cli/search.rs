@@ -0,0 +1,284 @@
+use std::io::IsTerminal;
+use std::time::Duration;
+
+use colored::Colorize;
+
+use crate::error::McplugError;
+use crate::runtime::Runtime;
+use crate::types::ToolDefinition;
+
+/// Default timeout for connecting to and listing each server.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Minimum Jaro-Winkler similarity for a fuzzy tool-name match.
+const FUZZY_THRESHOLD: f64 = 0.85;
+
+/// Get the search timeout from the environment variable or use the default.
+fn get_timeout() -> Duration {
+    parse_timeout_secs(std::env::var("MCPLUG_LIST_TIMEOUT").ok())
+}
+
+fn parse_timeout_secs(val: Option<String>) -> Duration {
+    val.and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+}
+
+/// A tool that matched the search query.
+#[derive(Debug, Clone)]
+struct SearchHit {
+    server: String,
+    tool: String,
+    description: String,
+    score: f64,
+}
+
+/// Score how well a tool matches the query. Returns `None` for no match.
+///
+/// Exact and substring matches on the tool name rank highest, followed by
+/// matches in the description, then fuzzy matches on the name (to catch typos).
+fn score_tool(query: &str, tool: &ToolDefinition) -> Option<f64> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+    let name = tool.name.to_lowercase();
+    let description = tool.description.to_lowercase();
+
+    if name == query {
+        return Some(100.0);
+    }
+    if name.starts_with(&query) {
+        return Some(90.0);
+    }
+    if name.contains(&query) {
+        return Some(80.0);
+    }
+    if description.contains(&query) {
+        return Some(60.0);
+    }
+
+    // Multi-word queries: every term must appear somewhere in name or description
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.len() > 1
+        && terms
+            .iter()
+            .all(|t| name.contains(t) || description.contains(t))
+    {
+        return Some(50.0);
+    }
+
+    let similarity = strsim::jaro_winkler(&query, &name);
+    if similarity >= FUZZY_THRESHOLD {
+        return Some(similarity * 40.0);
+    }
+
+    None
+}
+
+/// Match tools from every server against the query and sort by relevance.
+///
+/// Ties are broken by server name, then tool name, so output is stable.
+fn rank_hits(query: &str, tools_by_server: &[(String, Vec<ToolDefinition>)]) -> Vec<SearchHit> {
+    let mut hits: Vec<SearchHit> = tools_by_server
+        .iter()
+        .flat_map(|(server, tools)| {
+            tools.iter().filter_map(move |tool| {
+                score_tool(query, tool).map(|score| SearchHit {
+                    server: server.clone(),
+                    tool: tool.name.clone(),
+                    description: tool.description.clone(),
+                    score,
+                })
+            })
+        })
+        .collect();
+
+    hits.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.server.cmp(&b.server))
+            .then_with(|| a.tool.cmp(&b.tool))
+    });
+    hits
+}
+
+/// Run the search command.
+///
+/// With `tags`, only servers carrying any of them are searched.
+pub async fn run_search(query: &str, json: bool, tags: &[String]) -> Result<(), McplugError> {
+    let runtime = Runtime::builder()
+        .default_timeout(get_timeout())
+        .build()
+        .await?;
+    let is_tty = std::io::stdout().is_terminal();
+
+    let names = runtime.config().tagged_servers(tags);
+    if names.is_empty() && !tags.is_empty() {
+        return Err(McplugError::ProtocolError(format!(
+            "No enabled servers are tagged {}",
+            tags.join(" or ")
+        )));
+    }
+
+    // Query all servers in parallel
+    let mut tools_by_server = Vec::new();
+    let mut failures = Vec::new();
+    for (name, result) in runtime.list_tools_by_server(&names).await {
+        match result {
+            Ok(tools) => tools_by_server.push((name, tools)),
+            Err(e) => failures.push((name, e.to_string())),
+        }
+    }
+    let _ = runtime.close().await;
+    failures.sort();
+
+    let hits = rank_hits(query, &tools_by_server);
+
+    if json {
+        let json_output = serde_json::json!({
+            "query": query,
+            "results": hits.iter().map(|h| {
+                serde_json::json!({
+                    "server": h.server,
+                    "tool": h.tool,
+                    "description": h.description,
+                    "score": h.score,
+                })
+            }).collect::<Vec<_>>(),
+            "errors": failures.iter().map(|(server, error)| {
+                serde_json::json!({ "server": server, "error": error })
+            }).collect::<Vec<_>>(),
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json_output).unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    for (server, error) in &failures {
+        eprintln!("Warning: skipped {}: {}", server, error);
+    }
+
+    if hits.is_empty() {
+        eprintln!("No tools matching '{}'.", query);
+        return Ok(());
+    }
+
+    for hit in &hits {
+        let tool_ref = format!("{}.{}", hit.server, hit.tool);
+        let tool_ref = if is_tty {
+            tool_ref.bold().to_string()
+        } else {
+            tool_ref
+        };
+        if hit.description.is_empty() {
+            println!("{}", tool_ref);
+        } else if is_tty {
+            println!("{} — {}", tool_ref, hit.description.dimmed());
+        } else {
+            println!("{} — {}", tool_ref, hit.description);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_tool(name: &str, desc: &str) -> ToolDefinition {
+        ToolDefinition {
+            name: name.to_string(),
+            description: desc.to_string(),
+            input_schema: serde_json::json!({}),
+            annotations: None,
+            output_schema: None,
+        }
+    }
+
+    #[test]
+    fn score_exact_name_highest() {
+        let exact = score_tool("screenshot", &make_tool("screenshot", "")).unwrap();
+        let prefix = score_tool("screenshot", &make_tool("screenshot_page", "")).unwrap();
+        let contains = score_tool("screenshot", &make_tool("take_screenshot", "")).unwrap();
+        assert!(exact > prefix);
+        assert!(prefix > contains);
+    }
+
+    #[test]
+    fn score_name_beats_description() {
+        let name = score_tool("scrape", &make_tool("scrape_url", "Fetch a page")).unwrap();
+        let desc = score_tool("scrape", &make_tool("fetch", "Scrape a page")).unwrap();
+        assert!(name > desc);
+    }
+
+    #[test]
+    fn score_is_case_insensitive() {
+        assert!(score_tool("SCREENSHOT", &make_tool("Screenshot", "")).is_some());
+        assert!(score_tool("page", &make_tool("capture", "Capture a PAGE")).is_some());
+    }
+
+    #[test]
+    fn score_multi_word_query() {
+        let tool = make_tool("capture", "Take a screenshot of the browser window");
+        assert!(score_tool("browser screenshot", &tool).is_some());
+        assert!(score_tool("browser database", &tool).is_none());
+    }
+
+    #[test]
+    fn score_fuzzy_typo() {
+        assert!(score_tool("screnshot", &make_tool("screenshot", "")).is_some());
+    }
+
+    #[test]
+    fn score_no_match() {
+        assert!(score_tool("database", &make_tool("echo", "Echo input")).is_none());
+    }
+
+    #[test]
+    fn score_empty_query() {
+        assert!(score_tool("  ", &make_tool("echo", "Echo input")).is_none());
+    }
+
+    #[test]
+    fn rank_hits_sorted_by_score_then_name() {
+        let servers = vec![
+            (
+                "zeta".to_string(),
+                vec![make_tool("screenshot", "Capture screen")],
+            ),
+            (
+                "alpha".to_string(),
+                vec![
+                    make_tool("screenshot", "Capture screen"),
+                    make_tool("capture", "Take a screenshot"),
+                    make_tool("echo", "Echo input"),
+                ],
+            ),
+        ];
+        let hits = rank_hits("screenshot", &servers);
+        let refs: Vec<String> = hits
+            .iter()
+            .map(|h| format!("{}.{}", h.server, h.tool))
+            .collect();
+        assert_eq!(
+            refs,
+            vec!["alpha.screenshot", "zeta.screenshot", "alpha.capture"]
+        );
+    }
+
+    #[test]
+    fn rank_hits_empty_when_nothing_matches() {
+        let servers = vec![("s".to_string(), vec![make_tool("echo", "Echo input")])];
+        assert!(rank_hits("screenshot", &servers).is_empty());
+    }
+
+    #[test]
+    fn timeout_default() {
+        assert_eq!(parse_timeout_secs(None), Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+    }
+}
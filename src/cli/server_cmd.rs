@@ -0,0 +1,154 @@
+use std::path::PathBuf;
+
+use crate::config::{load_config, McplugConfig};
+use crate::error::McplugError;
+
+use super::config_cmd::{
+    edit_config_file, find_server_source, load_annotated, print_entry, probe_server,
+    tool_preview, TOOL_PREVIEW_LIMIT,
+};
+
+/// Locate the config file defining `name`, failing for unknown servers and
+/// servers that only exist through editor imports.
+fn require_source(name: &str) -> Result<PathBuf, McplugError> {
+    match find_server_source(name, None) {
+        Some(path) => Ok(path),
+        None => {
+            let config = load_config(None)?;
+            if config.mcp_servers.contains_key(name) {
+                Err(McplugError::ConfigError {
+                    path: PathBuf::from("<editor-import>"),
+                    detail: format!(
+                        "Server '{name}' is imported from an editor config; edit it there"
+                    ),
+                })
+            } else {
+                Err(McplugError::ServerNotFound(name.to_string()))
+            }
+        }
+    }
+}
+
+/// Remove a server from the config file that defines it.
+pub async fn run_server_remove(name: &str) -> Result<(), McplugError> {
+    let path = require_source(name)?;
+    edit_config_file(&path, |servers| {
+        servers.remove(name);
+        Ok(())
+    })?;
+    println!("Server '{}' removed from {}", name, path.display());
+
+    // A lower-precedence file may still define the same name
+    if let Some(other) = find_server_source(name, None) {
+        println!(
+            "Note: '{}' is still defined in {}",
+            name,
+            other.display()
+        );
+    }
+    Ok(())
+}
+
+/// Rename a server within the config file that defines it.
+pub async fn run_server_rename(old: &str, new: &str) -> Result<(), McplugError> {
+    let path = require_source(old)?;
+    if load_config(None)?.mcp_servers.contains_key(new) {
+        return Err(McplugError::ConfigError {
+            path,
+            detail: format!("A server named '{new}' already exists"),
+        });
+    }
+    edit_config_file(&path, |servers| {
+        let entry = servers
+            .remove(old)
+            .ok_or_else(|| McplugError::ServerNotFound(old.to_string()))?;
+        servers.insert(new.to_string(), entry);
+        Ok(())
+    })?;
+    println!("Server '{}' renamed to '{}' in {}", old, new, path.display());
+    Ok(())
+}
+
+/// Set or clear the `disabled` flag on a server.
+pub async fn run_server_set_disabled(name: &str, disabled: bool) -> Result<(), McplugError> {
+    let path = require_source(name)?;
+    edit_config_file(&path, |servers| {
+        let entry = servers
+            .get_mut(name)
+            .and_then(|v| v.as_object_mut())
+            .ok_or_else(|| McplugError::ServerNotFound(name.to_string()))?;
+        if disabled {
+            entry.insert("disabled".into(), serde_json::Value::Bool(true));
+        } else {
+            entry.remove("disabled");
+        }
+        Ok(())
+    })?;
+    let state = if disabled { "disabled" } else { "enabled" };
+    println!("Server '{}' {} in {}", name, state, path.display());
+    Ok(())
+}
+
+/// Test-connect to a configured server and summarize what it offers.
+///
+/// A group name tests each of its servers in turn and fails if any of them
+/// fail.
+pub async fn run_server_test(name: &str) -> Result<(), McplugError> {
+    let config = load_config(None)?;
+    if !config.is_group(name) {
+        return test_server(&config, name).await;
+    }
+
+    let members = config.resolve_servers(name)?;
+    let mut failed = 0;
+    for member in &members {
+        println!("{}:", member);
+        if let Err(e) = test_server(&config, member).await {
+            eprintln!("{}: {}", member, e);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(McplugError::ProtocolError(format!(
+            "{failed} of {} servers in group '{name}' failed",
+            members.len()
+        )));
+    }
+    Ok(())
+}
+
+async fn test_server(config: &McplugConfig, name: &str) -> Result<(), McplugError> {
+    let server = config
+        .mcp_servers
+        .get(name)
+        .ok_or_else(|| McplugError::ServerNotFound(name.to_string()))?;
+    if server.disabled {
+        println!("Note: '{}' is disabled; testing anyway", name);
+    }
+    let mut server = server.clone();
+    server.disabled = false;
+
+    let result = probe_server(name, &server).await?;
+    println!(
+        "Connected to {} v{} in {} ms.",
+        result.info.name,
+        result.info.version,
+        result.startup.as_millis()
+    );
+    println!("Discovered {} tool(s):", result.tools.len());
+    for line in tool_preview(&result.tools, TOOL_PREVIEW_LIMIT) {
+        println!("  {}", line);
+    }
+    Ok(())
+}
+
+/// Show the merged config entry for one server, with its source file.
+pub async fn run_server_show(name: &str) -> Result<(), McplugError> {
+    let entries = load_annotated(None)?;
+    let entry = entries
+        .iter()
+        .find(|e| e.name == name)
+        .ok_or_else(|| McplugError::ServerNotFound(name.to_string()))?;
+    print_entry(entry, std::io::IsTerminal::is_terminal(&std::io::stdout()));
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+use futures_util::future::join_all;
+
+use crate::error::McplugError;
+use crate::runtime::Runtime;
+
+/// Run `mcplug warm [server|group...]`.
+///
+/// Connects to and initializes each server (all enabled servers when none
+/// are named; group names expand to their servers) concurrently, reporting
+/// how long each handshake took. This installs packages on first run and
+/// saves HTTP sessions for later invocations to resume.
+pub async fn run_warm(servers: &[String]) -> Result<(), McplugError> {
+    let runtime = Runtime::from_config().await?;
+    let mut names: Vec<String> = if servers.is_empty() {
+        runtime.server_names()
+    } else {
+        runtime.config().resolve_all(servers)?
+    };
+    names.sort();
+
+    let results = join_all(names.iter().map(|name| {
+        let runtime = &runtime;
+        async move {
+            let started = Instant::now();
+            let result = runtime.connect(name).await;
+            (name, result, started.elapsed())
+        }
+    }))
+    .await;
+
+    let mut failed = 0;
+    for (name, result, elapsed) in &results {
+        match result {
+            Ok(()) => println!("{}: ready in {}", name, format_elapsed(*elapsed)),
+            Err(e) => {
+                eprintln!("{}: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+    runtime.close().await?;
+
+    if failed > 0 {
+        return Err(McplugError::ProtocolError(format!(
+            "{} of {} servers failed to connect",
+            failed,
+            results.len()
+        )));
+    }
+    Ok(())
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    if elapsed < Duration::from_secs(1) {
+        format!("{}ms", elapsed.as_millis())
+    } else {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elapsed_formatting() {
+        assert_eq!(format_elapsed(Duration::from_millis(42)), "42ms");
+        assert_eq!(format_elapsed(Duration::from_millis(2340)), "2.3s");
+    }
+}
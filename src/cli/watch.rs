@@ -0,0 +1,299 @@
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+use colored::Colorize;
+
+use crate::args::{parse_args, parse_function_call, parse_tool_ref};
+use crate::config::{load_config, ConfigChanges, ConfigWatcher, McplugConfig};
+use crate::error::McplugError;
+use crate::transport::McpTransport;
+
+use super::connection::{connect_to_server, verify_tool_pin};
+
+/// Default timeout for each call while watching.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Get the per-call timeout from the environment variable or use the default.
+fn get_timeout() -> Duration {
+    parse_timeout_secs(std::env::var("MCPLUG_CALL_TIMEOUT").ok())
+}
+
+fn parse_timeout_secs(val: Option<String>) -> Duration {
+    val.and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+}
+
+/// ANSI sequence to clear the screen and move the cursor home.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// One line of a line diff between consecutive results.
+#[derive(Debug, PartialEq)]
+enum DiffLine<'a> {
+    Same(&'a str),
+    Added(&'a str),
+    Removed(&'a str),
+}
+
+/// Line diff of `old` against `new` using a longest-common-subsequence table.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            out.push(DiffLine::Same(a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(DiffLine::Removed(a[i]));
+            i += 1;
+        } else {
+            out.push(DiffLine::Added(b[j]));
+            j += 1;
+        }
+    }
+    out.extend(a[i..].iter().map(|l| DiffLine::Removed(l)));
+    out.extend(b[j..].iter().map(|l| DiffLine::Added(l)));
+    out
+}
+
+/// Render a diff with `+`/`-` markers, colored on a TTY.
+fn render_diff(diff: &[DiffLine], is_tty: bool) -> String {
+    diff.iter()
+        .map(|line| match *line {
+            DiffLine::Same(l) => format!("  {l}"),
+            DiffLine::Added(l) if is_tty => format!("+ {l}").green().to_string(),
+            DiffLine::Added(l) => format!("+ {l}"),
+            DiffLine::Removed(l) if is_tty => format!("- {l}").red().to_string(),
+            DiffLine::Removed(l) => format!("- {l}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parse the interval in seconds; fractions like `0.5` are allowed.
+fn parse_interval(secs: f64) -> Result<Duration, McplugError> {
+    if secs.is_finite() && secs > 0.0 {
+        Ok(Duration::from_secs_f64(secs))
+    } else {
+        Err(McplugError::ProtocolError(format!(
+            "Invalid interval '{secs}': must be a positive number of seconds"
+        )))
+    }
+}
+
+/// Run one call on the watched connection, connecting first if needed.
+/// A new connection checks the tool's schema pin in `pins`, the config of a
+/// configured (not ad-hoc) server.
+async fn watch_call(
+    transport: &mut Option<Box<dyn McpTransport>>,
+    connect: &dyn Fn() -> Result<Box<dyn McpTransport>, McplugError>,
+    pins: Option<&McplugConfig>,
+    server: &str,
+    tool: &str,
+    args: &serde_json::Value,
+    timeout: Duration,
+) -> Result<String, McplugError> {
+    let timeout_err = || McplugError::Timeout {
+        server: server.to_string(),
+        tool: Some(tool.to_string()),
+        duration: timeout,
+    };
+    let t = match transport {
+        Some(t) => t,
+        None => {
+            let mut t = connect()?;
+            t.wait_ready().await?;
+            tokio::time::timeout(timeout, t.initialize())
+                .await
+                .map_err(|_| timeout_err())??;
+            let t = transport.insert(t);
+            if let Some(config) = pins {
+                verify_tool_pin(t.as_ref(), server, tool, config, timeout).await?;
+            }
+            t
+        }
+    };
+    let result = tokio::time::timeout(timeout, t.call_tool(tool, args.clone()))
+        .await
+        .map_err(|_| timeout_err())??;
+    let text = result.text();
+    Ok(if result.is_error {
+        format!("Error: {text}")
+    } else {
+        text
+    })
+}
+
+/// Run the watch command.
+///
+/// Calls the tool every `interval`, redrawing the screen on a TTY (or
+/// printing a separator otherwise). With `diff`, lines that changed since the
+/// previous run are marked. Call failures are shown in place and the watch
+/// continues, reconnecting on the next tick. Edits to the config files are
+/// picked up before each run, reconnecting if the watched server changed.
+/// Stops after `count` runs, or on Ctrl-C.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_watch(
+    tool_ref: &str,
+    args: &[String],
+    interval_secs: f64,
+    diff: bool,
+    count: Option<usize>,
+    http_url: Option<&str>,
+    stdio: Option<&str>,
+) -> Result<(), McplugError> {
+    let interval = parse_interval(interval_secs)?;
+    let (server_name, tool_name, parsed_args) = if tool_ref.contains('(') {
+        parse_function_call(tool_ref)?
+    } else {
+        let (s, t) = parse_tool_ref(tool_ref)?;
+        (s, t, parse_args(args)?)
+    };
+    let mut config = load_config(None)?;
+    let configured = http_url.is_none() && stdio.is_none();
+    let timeout_for = |config: &McplugConfig| {
+        if configured {
+            config.call_timeout(&server_name, get_timeout())
+        } else {
+            get_timeout()
+        }
+    };
+    let mut timeout = timeout_for(&config);
+    let is_tty = std::io::stdout().is_terminal();
+    // Ad-hoc servers don't come from config, so there is nothing to reload
+    let mut watcher = configured.then(|| ConfigWatcher::new(None));
+
+    let mut transport: Option<Box<dyn McpTransport>> = None;
+    let mut previous: Option<String> = None;
+    let mut runs = 0;
+    loop {
+        if watcher.as_mut().is_some_and(ConfigWatcher::changed) {
+            match load_config(None) {
+                Ok(new) => {
+                    let changes = ConfigChanges::between(&config, &new);
+                    tracing::info!("Config reloaded: {changes}");
+                    if changes.affects(&server_name) {
+                        if let Some(mut t) = transport.take() {
+                            let _ = t.close().await;
+                        }
+                    }
+                    config = new;
+                    timeout = timeout_for(&config);
+                }
+                Err(e) => tracing::warn!("Keeping previous config: {e}"),
+            }
+        }
+        let connect = || connect_to_server(&server_name, &config, http_url, stdio);
+        let output = match watch_call(
+            &mut transport,
+            &connect,
+            configured.then_some(&config),
+            &server_name,
+            &tool_name,
+            &parsed_args,
+            timeout,
+        )
+        .await
+        {
+            Ok(text) => text,
+            Err(e) => {
+                // Drop the connection so the next tick starts fresh
+                if let Some(mut t) = transport.take() {
+                    let _ = t.close().await;
+                }
+                format!("Error: {e}")
+            }
+        };
+        runs += 1;
+
+        let header = format!(
+            "Every {}s: {}.{}    {}",
+            interval.as_secs_f64(),
+            server_name,
+            tool_name,
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        );
+        let body = match previous {
+            Some(ref prev) if diff => render_diff(&diff_lines(prev, &output), is_tty),
+            _ => output.clone(),
+        };
+        let mut stdout = std::io::stdout().lock();
+        if is_tty {
+            let _ = write!(stdout, "{}{}\n\n{}\n", CLEAR_SCREEN, header.bold(), body);
+        } else {
+            let _ = write!(stdout, "--- {}\n{}\n", header, body);
+        }
+        let _ = stdout.flush();
+        drop(stdout);
+        previous = Some(output);
+
+        if count.is_some_and(|c| runs >= c) {
+            break;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    if let Some(mut t) = transport {
+        let _ = t.close().await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_marks_changes() {
+        let diff = diff_lines("a\nb\nc", "a\nx\nc\nd");
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Same("a"),
+                DiffLine::Removed("b"),
+                DiffLine::Added("x"),
+                DiffLine::Same("c"),
+                DiffLine::Added("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_identical_is_all_same() {
+        let diff = diff_lines("a\nb", "a\nb");
+        assert!(diff.iter().all(|l| matches!(l, DiffLine::Same(_))));
+    }
+
+    #[test]
+    fn render_diff_plain_markers() {
+        let out = render_diff(&diff_lines("old", "new"), false);
+        assert_eq!(out, "- old\n+ new");
+    }
+
+    #[test]
+    fn interval_validation() {
+        assert_eq!(parse_interval(0.5).unwrap(), Duration::from_millis(500));
+        assert!(parse_interval(0.0).is_err());
+        assert!(parse_interval(-1.0).is_err());
+        assert!(parse_interval(f64::NAN).is_err());
+    }
+}
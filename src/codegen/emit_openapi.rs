@@ -0,0 +1,182 @@
+use crate::types::{ServerInfo, ToolDefinition};
+use serde_json::{json, Map, Value};
+
+/// Generate an OpenAPI 3.1 document describing a server's tools.
+///
+/// Each tool becomes `POST /tools/<name>`, with its input schema as the JSON
+/// request body and a `CallResult` response; a tool's `outputSchema`
+/// describes the response's `structuredContent`. OpenAPI 3.1 schemas are
+/// JSON Schema, so tool schemas are embedded unchanged. The document
+/// describes the catalog; serving these paths is left to a gateway.
+pub fn emit_openapi(tools: &[ToolDefinition], server_name: &str, info: &ServerInfo) -> String {
+    let mut paths = Map::new();
+    for tool in tools {
+        paths.insert(
+            format!("/tools/{}", encode_path_segment(&tool.name)),
+            json!({ "post": operation(tool, server_name) }),
+        );
+    }
+
+    let doc = json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": server_name,
+            "version": info.version,
+            "description": format!(
+                "Tools of the {server_name} MCP server ({}), generated by mcplug.",
+                info.name
+            ),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": {
+                "CallResult": {
+                    "type": "object",
+                    "properties": {
+                        "content": {
+                            "type": "array",
+                            "items": { "$ref": "#/components/schemas/ContentBlock" },
+                        },
+                        "isError": { "type": "boolean" },
+                        "structuredContent": {},
+                    },
+                    "required": ["content"],
+                },
+                "ContentBlock": {
+                    "type": "object",
+                    "properties": {
+                        "type": { "type": "string" },
+                        "text": { "type": "string" },
+                        "data": { "type": "string" },
+                        "mimeType": { "type": "string" },
+                    },
+                    "required": ["type"],
+                },
+            },
+        },
+    });
+    serde_json::to_string_pretty(&doc).unwrap_or_default()
+}
+
+fn operation(tool: &ToolDefinition, server_name: &str) -> Value {
+    let mut op = Map::new();
+    op.insert("operationId".into(), json!(tool.name));
+    op.insert("tags".into(), json!([server_name]));
+    if let Some(title) = tool.annotations.as_ref().and_then(|a| a.title.as_ref()) {
+        op.insert("summary".into(), json!(title));
+    }
+    if !tool.description.is_empty() {
+        op.insert("description".into(), json!(tool.description));
+    }
+    op.insert(
+        "requestBody".into(),
+        json!({
+            "required": true,
+            "content": { "application/json": { "schema": tool.input_schema } },
+        }),
+    );
+
+    let result = json!({ "$ref": "#/components/schemas/CallResult" });
+    let response_schema = match &tool.output_schema {
+        Some(output) => json!({
+            "allOf": [result, { "properties": { "structuredContent": output } }],
+        }),
+        None => result,
+    };
+    op.insert(
+        "responses".into(),
+        json!({
+            "200": {
+                "description": "The tool's result",
+                "content": { "application/json": { "schema": response_schema } },
+            },
+        }),
+    );
+    if let Some(annotations) = &tool.annotations {
+        op.insert("x-mcp-annotations".into(), json!(annotations));
+    }
+    Value::Object(op)
+}
+
+/// Percent-encode a tool name for use as one path segment.
+fn encode_path_segment(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ToolAnnotations;
+
+    fn info() -> ServerInfo {
+        ServerInfo {
+            name: "weather-server".into(),
+            version: "1.4.0".into(),
+            capabilities: json!({}),
+            protocol_version: None,
+        }
+    }
+
+    #[test]
+    fn tools_become_post_operations() {
+        let input = json!({
+            "type": "object",
+            "properties": {"city": {"type": "string"}},
+            "required": ["city"]
+        });
+        let output = json!({"type": "object", "properties": {"tempC": {"type": "number"}}});
+        let tools = vec![
+            ToolDefinition {
+                name: "forecast".into(),
+                description: "Get a forecast".into(),
+                input_schema: input.clone(),
+                annotations: Some(ToolAnnotations {
+                    title: Some("Forecast".into()),
+                    read_only_hint: Some(true),
+                    ..Default::default()
+                }),
+                output_schema: Some(output.clone()),
+            },
+            ToolDefinition {
+                name: "alerts/list all".into(),
+                description: String::new(),
+                input_schema: json!({}),
+                annotations: None,
+                output_schema: None,
+            },
+        ];
+        let doc: Value = serde_json::from_str(&emit_openapi(&tools, "weather", &info())).unwrap();
+
+        assert_eq!(doc["openapi"], "3.1.0");
+        assert_eq!(doc["info"]["title"], "weather");
+        assert_eq!(doc["info"]["version"], "1.4.0");
+
+        let op = &doc["paths"]["/tools/forecast"]["post"];
+        assert_eq!(op["operationId"], "forecast");
+        assert_eq!(op["summary"], "Forecast");
+        assert_eq!(op["description"], "Get a forecast");
+        assert_eq!(op["tags"], json!(["weather"]));
+        assert_eq!(op["requestBody"]["content"]["application/json"]["schema"], input);
+        let response = &op["responses"]["200"]["content"]["application/json"]["schema"];
+        assert_eq!(response["allOf"][0]["$ref"], "#/components/schemas/CallResult");
+        assert_eq!(response["allOf"][1]["properties"]["structuredContent"], output);
+        assert_eq!(op["x-mcp-annotations"]["readOnlyHint"], true);
+
+        let op = &doc["paths"]["/tools/alerts%2Flist%20all"]["post"];
+        assert_eq!(op["operationId"], "alerts/list all");
+        assert!(op.get("description").is_none());
+        assert_eq!(
+            op["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/CallResult"
+        );
+        assert!(doc["components"]["schemas"]["ContentBlock"].is_object());
+    }
+}
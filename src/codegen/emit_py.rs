@@ -0,0 +1,486 @@
+use crate::transports::jsonrpc::PROTOCOL_VERSIONS;
+use crate::types::{SchemaProperty, SchemaType, ToolDefinition, ToolSchema};
+use std::fmt::Write;
+
+use super::emit_rs::{sorted_tools, to_pascal_case, to_snake_case};
+
+/// Result types and transports shared by every generated client, using only
+/// the standard library. Calls go through a `Transport`; `StdioTransport`
+/// spawns a server and `HttpTransport` POSTs to one.
+const RUNTIME: &str = r#"class Transport(Protocol):
+    """Sends tool calls to an MCP server."""
+
+    async def call_tool(self, name: str, args: Mapping[str, Any]) -> CallResult: ...
+
+    async def close(self) -> None: ...
+
+
+def _unwrap(method: str, message: Any) -> Any:
+    if "error" in message:
+        raise RuntimeError(f"{method}: {message['error'].get('message')}")
+    return message.get("result")
+
+
+def text(result: CallResult) -> str:
+    """The text content of a result, joined."""
+    return "".join(block.get("text", "") for block in result["content"] if block.get("type") == "text")
+
+
+def _structured(tool: str, result: CallResult) -> Any:
+    if result.get("isError"):
+        raise RuntimeError(f"{tool}: {text(result)}")
+    if "structuredContent" in result:
+        return result["structuredContent"]
+    return json.loads(text(result))
+
+
+class StdioTransport:
+    """Spawns `command` and talks to it over stdin/stdout."""
+
+    def __init__(self, command: str, args: Sequence[str] = (), env: Mapping[str, str] | None = None) -> None:
+        self._argv = [command, *args]
+        self._env = {**os.environ, **(env or {})}
+        self._process: asyncio.subprocess.Process | None = None
+        self._reader: asyncio.Task[None] | None = None
+        self._ready: asyncio.Future[None] | None = None
+        self._pending: dict[int, asyncio.Future[Any]] = {}
+        self._next_id = 1
+
+    async def _start(self) -> None:
+        self._process = await asyncio.create_subprocess_exec(
+            *self._argv,
+            stdin=asyncio.subprocess.PIPE,
+            stdout=asyncio.subprocess.PIPE,
+            env=self._env,
+            limit=1 << 24,
+        )
+        self._reader = asyncio.create_task(self._read())
+        await self._request("initialize", INITIALIZE)
+        await self._send({"jsonrpc": "2.0", "method": "notifications/initialized", "params": {}})
+
+    async def _read(self) -> None:
+        assert self._process is not None and self._process.stdout is not None
+        async for line in self._process.stdout:
+            try:
+                message = json.loads(line)
+            except ValueError:
+                continue
+            future = self._pending.pop(message.get("id"), None) if isinstance(message, dict) else None
+            if future is not None and not future.done():
+                future.set_result(message)
+        error = ConnectionError(f"{self._argv[0]} exited")
+        for future in self._pending.values():
+            if not future.done():
+                future.set_exception(error)
+        self._pending.clear()
+
+    async def _send(self, message: Mapping[str, Any]) -> None:
+        assert self._process is not None and self._process.stdin is not None
+        self._process.stdin.write(json.dumps(message).encode() + b"\n")
+        await self._process.stdin.drain()
+
+    async def _request(self, method: str, params: Any) -> Any:
+        id = self._next_id
+        self._next_id += 1
+        future: asyncio.Future[Any] = asyncio.get_running_loop().create_future()
+        self._pending[id] = future
+        await self._send({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+        return _unwrap(method, await future)
+
+    async def call_tool(self, name: str, args: Mapping[str, Any]) -> CallResult:
+        if self._ready is None:
+            self._ready = asyncio.ensure_future(self._start())
+        await self._ready
+        return await self._request("tools/call", {"name": name, "arguments": args})
+
+    async def close(self) -> None:
+        if self._process is None:
+            return
+        if self._process.stdin is not None:
+            self._process.stdin.close()
+        try:
+            await asyncio.wait_for(self._process.wait(), 2)
+        except asyncio.TimeoutError:
+            self._process.kill()
+            await self._process.wait()
+        if self._reader is not None:
+            await self._reader
+
+
+class HttpTransport:
+    """Talks to an MCP server over streamable HTTP at `url`."""
+
+    def __init__(self, url: str, headers: Mapping[str, str] | None = None) -> None:
+        self._url = url
+        self._headers = dict(headers or {})
+        self._session: str | None = None
+        self._ready: asyncio.Future[None] | None = None
+        self._next_id = 1
+
+    def _post(self, message: Mapping[str, Any]) -> tuple[str, bytes]:
+        headers = {"Content-Type": "application/json", "Accept": "application/json, text/event-stream"}
+        if self._session is not None:
+            headers["Mcp-Session-Id"] = self._session
+        headers.update(self._headers)
+        request = urllib.request.Request(self._url, data=json.dumps(message).encode(), headers=headers, method="POST")
+        with urllib.request.urlopen(request) as response:
+            self._session = response.headers.get("Mcp-Session-Id", self._session)
+            return response.headers.get("Content-Type", ""), response.read()
+
+    async def _request(self, method: str, params: Any) -> Any:
+        id = self._next_id
+        self._next_id += 1
+        message = {"jsonrpc": "2.0", "id": id, "method": method, "params": params}
+        content_type, body = await asyncio.to_thread(self._post, message)
+        reply = body.decode()
+        # A streamed reply carries the response in its last data line
+        if content_type.startswith("text/event-stream"):
+            data = [line[5:] for line in reply.splitlines() if line.startswith("data:")]
+            reply = data[-1] if data else ""
+        return _unwrap(method, json.loads(reply))
+
+    async def _start(self) -> None:
+        await self._request("initialize", INITIALIZE)
+        await asyncio.to_thread(self._post, {"jsonrpc": "2.0", "method": "notifications/initialized", "params": {}})
+
+    async def call_tool(self, name: str, args: Mapping[str, Any]) -> CallResult:
+        if self._ready is None:
+            self._ready = asyncio.ensure_future(self._start())
+        await self._ready
+        return await self._request("tools/call", {"name": name, "arguments": args})
+
+    async def close(self) -> None:
+        pass
+"#;
+
+/// Generate a typed async Python client from MCP tool schemas: a
+/// `TypedDict` per tool's arguments (and result, when it declares an output
+/// schema), and a client class with one method per tool. Needs Python 3.11+.
+pub fn emit_python(tools: &[ToolDefinition], server_name: &str) -> String {
+    let tools = sorted_tools(tools);
+    let mut out = String::new();
+
+    // File header
+    writeln!(out, "# Auto-generated by mcplug. Do not edit.").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "from __future__ import annotations").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "import asyncio").unwrap();
+    writeln!(out, "import json").unwrap();
+    writeln!(out, "import os").unwrap();
+    writeln!(out, "import urllib.request").unwrap();
+    writeln!(
+        out,
+        "from typing import Any, Literal, Mapping, NotRequired, Protocol, Sequence, TypedDict, cast"
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "INITIALIZE = {}",
+        py_literal(&serde_json::json!({
+            "protocolVersion": PROTOCOL_VERSIONS[0],
+            "capabilities": {},
+            "clientInfo": {"name": "mcplug-py", "version": env!("CARGO_PKG_VERSION")},
+        }))
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "CallResult = TypedDict(\"CallResult\", {{\"content\": list[dict[str, Any]], \"isError\": NotRequired[bool], \"structuredContent\": NotRequired[Any]}})"
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    writeln!(out).unwrap();
+    out.push_str(RUNTIME);
+
+    // Generate TypedDicts for each tool's input and output schemas
+    for tool in &tools {
+        writeln!(out).unwrap();
+        writeln!(out).unwrap();
+        emit_typed_dict(&mut out, &format!("{}Args", class_name(&tool.name)), &tool.schema());
+        if let Some(schema) = typed_result(tool) {
+            writeln!(out).unwrap();
+            writeln!(out).unwrap();
+            emit_typed_dict(&mut out, &format!("{}Result", class_name(&tool.name)), &schema);
+        }
+    }
+
+    // Generate the client class
+    writeln!(out).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "class {}Client:", class_name(server_name)).unwrap();
+    writeln!(out, "    def __init__(self, transport: Transport) -> None:").unwrap();
+    writeln!(out, "        self._transport = transport").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    async def __aenter__(self) -> \"{}Client\":", class_name(server_name)).unwrap();
+    writeln!(out, "        return self").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    async def __aexit__(self, *exc: object) -> None:").unwrap();
+    writeln!(out, "        await self.close()").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    async def close(self) -> None:").unwrap();
+    writeln!(out, "        await self._transport.close()").unwrap();
+
+    for tool in &tools {
+        let class = class_name(&tool.name);
+        let method_name = py_identifier(&to_snake_case(&tool.name));
+        let tool_name = py_literal(&serde_json::Value::from(tool.name.as_str()));
+        writeln!(out).unwrap();
+        if typed_result(tool).is_some() {
+            writeln!(out, "    async def {method_name}(self, args: {class}Args) -> {class}Result:").unwrap();
+            emit_docstring(&mut out, &tool.description);
+            writeln!(
+                out,
+                "        result = await self._transport.call_tool({tool_name}, args)"
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "        return cast({class}Result, _structured({tool_name}, result))"
+            )
+            .unwrap();
+        } else {
+            writeln!(out, "    async def {method_name}(self, args: {class}Args) -> CallResult:").unwrap();
+            emit_docstring(&mut out, &tool.description);
+            writeln!(out, "        return await self._transport.call_tool({tool_name}, args)").unwrap();
+        }
+    }
+    out
+}
+
+/// The output schema of a tool whose results get their own type, as in
+/// `emit_rs`: one declaring an object with properties.
+fn typed_result(tool: &ToolDefinition) -> Option<ToolSchema> {
+    tool.result_schema().filter(|schema| !schema.properties.is_empty())
+}
+
+fn emit_docstring(out: &mut String, text: &str) {
+    if text.trim().is_empty() {
+        return;
+    }
+    let escaped = text.trim().replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\"");
+    let mut lines = escaped.lines();
+    let first = lines.next().unwrap_or_default();
+    let rest: Vec<&str> = lines.collect();
+    if rest.is_empty() {
+        writeln!(out, "        \"\"\"{first}\"\"\"").unwrap();
+    } else {
+        writeln!(out, "        \"\"\"{first}").unwrap();
+        for line in rest {
+            if line.is_empty() {
+                writeln!(out).unwrap();
+            } else {
+                writeln!(out, "        {line}").unwrap();
+            }
+        }
+        writeln!(out, "        \"\"\"").unwrap();
+    }
+}
+
+/// Emit a `TypedDict`, in class form when every key is an identifier and
+/// in functional form otherwise.
+fn emit_typed_dict(out: &mut String, name: &str, schema: &ToolSchema) {
+    let field_type = |prop: &SchemaProperty| {
+        let ty = property_type(prop);
+        if prop.required {
+            ty
+        } else {
+            format!("NotRequired[{ty}]")
+        }
+    };
+
+    if !schema.properties.iter().all(|p| is_identifier(&p.name)) {
+        let fields: Vec<String> = schema
+            .properties
+            .iter()
+            .map(|p| {
+                let key = py_literal(&serde_json::Value::from(p.name.as_str()));
+                format!("{key}: {}", field_type(p))
+            })
+            .collect();
+        writeln!(out, "{name} = TypedDict({name:?}, {{{}}})", fields.join(", ")).unwrap();
+        return;
+    }
+
+    writeln!(out, "class {name}(TypedDict):").unwrap();
+    if schema.properties.is_empty() {
+        writeln!(out, "    pass").unwrap();
+    }
+    for prop in &schema.properties {
+        if let Some(ref desc) = prop.description {
+            for line in desc.lines() {
+                writeln!(out, "    # {line}").unwrap();
+            }
+        }
+        writeln!(out, "    {}: {}", prop.name, field_type(prop)).unwrap();
+    }
+}
+
+/// A property's type: a `Literal` of its enum values if it lists them,
+/// else the Python type for its schema type.
+fn property_type(prop: &SchemaProperty) -> String {
+    if !prop.enum_values.is_empty() {
+        let values: Vec<String> = prop.enum_values.iter().map(py_literal).collect();
+        return format!("Literal[{}]", values.join(", "));
+    }
+    py_type(prop.ty.as_ref())
+}
+
+/// The Python type for values of a schema type; untyped values are `Any`.
+pub fn py_type(ty: Option<&SchemaType>) -> String {
+    match ty {
+        Some(SchemaType::String) => "str".to_string(),
+        Some(SchemaType::Number) => "float".to_string(),
+        Some(SchemaType::Integer) => "int".to_string(),
+        Some(SchemaType::Boolean) => "bool".to_string(),
+        Some(SchemaType::Array(items)) => format!("list[{}]", py_type(items.as_deref())),
+        Some(SchemaType::Object) => "dict[str, Any]".to_string(),
+        Some(SchemaType::Null) => "None".to_string(),
+        Some(SchemaType::Union(members)) => {
+            let types: Vec<String> = members.iter().map(|ty| py_type(Some(ty))).collect();
+            types.join(" | ")
+        }
+        Some(SchemaType::Other(_)) | None => "Any".to_string(),
+    }
+}
+
+/// A JSON value as a Python literal.
+fn py_literal(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "None".to_string(),
+        serde_json::Value::Bool(true) => "True".to_string(),
+        serde_json::Value::Bool(false) => "False".to_string(),
+        serde_json::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(py_literal).collect();
+            format!("[{}]", items.join(", "))
+        }
+        serde_json::Value::Object(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(k, v)| format!("{}: {}", serde_json::Value::from(k.as_str()), py_literal(v)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        // JSON strings and numbers are valid Python literals
+        other => other.to_string(),
+    }
+}
+
+const PY_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !PY_KEYWORDS.contains(&name)
+}
+
+/// Make a name usable as a Python identifier.
+fn py_identifier(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if PY_KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+    ident
+}
+
+fn class_name(name: &str) -> String {
+    py_identifier(&to_pascal_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(name: &str, description: &str, input_schema: serde_json::Value) -> ToolDefinition {
+        ToolDefinition {
+            name: name.to_string(),
+            description: description.to_string(),
+            input_schema,
+            annotations: None,
+            output_schema: None,
+        }
+    }
+
+    #[test]
+    fn emits_typed_dicts_and_client() {
+        let tools = vec![
+            tool(
+                "get-weather",
+                "Get weather for a location",
+                serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "location": {"type": "string", "description": "City name"},
+                        "units": {"type": "string", "enum": ["metric", "imperial"]},
+                        "days": {"type": "integer"},
+                        "tags": {"type": "array", "items": {"type": "string"}}
+                    },
+                    "required": ["location"]
+                }),
+            ),
+            tool(
+                "import",
+                "Line one\nline \"\"\"two\"\"\"",
+                serde_json::json!({"properties": {"max-age": {"type": "number"}}}),
+            ),
+            ToolDefinition {
+                output_schema: Some(serde_json::json!({
+                    "type": "object",
+                    "properties": {"tempC": {"type": "number"}},
+                    "required": ["tempC"]
+                })),
+                ..tool("forecast", "", serde_json::json!({}))
+            },
+        ];
+        let output = emit_python(&tools, "my-weather");
+
+        assert!(output.contains(
+            "class GetWeatherArgs(TypedDict):\n    days: NotRequired[int]\n    # City name\n    location: str\n    tags: NotRequired[list[str]]\n    units: NotRequired[Literal[\"metric\", \"imperial\"]]\n"
+        ));
+        assert!(output.contains(
+            "ImportArgs = TypedDict(\"ImportArgs\", {\"max-age\": NotRequired[float]})"
+        ));
+        assert!(output.contains("class ForecastArgs(TypedDict):\n    pass\n"));
+        assert!(output.contains("class ForecastResult(TypedDict):\n    tempC: float\n"));
+
+        assert!(output.contains("class MyWeatherClient:"));
+        assert!(output.contains(
+            "    async def get_weather(self, args: GetWeatherArgs) -> CallResult:\n        \"\"\"Get weather for a location\"\"\"\n        return await self._transport.call_tool(\"get-weather\", args)\n"
+        ));
+        assert!(output.contains(
+            "    async def import_(self, args: ImportArgs) -> CallResult:\n        \"\"\"Line one\n        line \\\"\\\"\\\"two\\\"\\\"\\\"\n        \"\"\"\n"
+        ));
+        assert!(output.contains(
+            "    async def forecast(self, args: ForecastArgs) -> ForecastResult:\n        result = await self._transport.call_tool(\"forecast\", args)\n        return cast(ForecastResult, _structured(\"forecast\", result))\n"
+        ));
+        assert!(output.contains(&format!("\"protocolVersion\": \"{}\"", PROTOCOL_VERSIONS[0])));
+    }
+
+    #[test]
+    fn python_types_and_names() {
+        assert_eq!(py_type(Some(&SchemaType::Array(None))), "list[Any]");
+        assert_eq!(py_type(Some(&SchemaType::Null)), "None");
+        assert_eq!(py_literal(&serde_json::json!([true, null, 1.5, "a"])), "[True, None, 1.5, \"a\"]");
+        assert_eq!(py_identifier("2fa"), "_2fa");
+        assert_eq!(py_identifier("class"), "class_");
+        assert!(is_identifier("tempC"));
+        assert!(!is_identifier("from"));
+        assert!(!is_identifier("max-age"));
+    }
+}
@@ -1,5 +1,9 @@
-use crate::types::ToolDefinition;
+use crate::error::McplugError;
+use crate::types::{SchemaType, ToolDefinition, ToolSchema};
 use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use super::Snapshot;
 
 /// Generate Rust type definitions and a typed client from MCP tool schemas.
 pub fn emit_rust_types(tools: &[ToolDefinition], server_name: &str) -> String {
@@ -11,16 +15,22 @@ pub fn emit_rust_types(tools: &[ToolDefinition], server_name: &str) -> String {
     writeln!(out, "use serde::{{Deserialize, Serialize}};").unwrap();
     writeln!(out).unwrap();
 
-    // Generate structs for each tool's input schema
-    for tool in tools {
-        let struct_name = format!("{}Args", to_pascal_case(&tool.name));
-        emit_struct(&mut out, &struct_name, &tool.input_schema, 0);
+    let tools = sorted_tools(tools);
+    let names = ToolNames::new(tools.iter().copied());
+
+    // Generate structs for each tool's input schema, and for its output
+    // schema when it declares one
+    for (tool, type_name) in tools.iter().zip(&names.types) {
+        emit_struct(&mut out, &format!("{type_name}Args"), &tool.schema(), 0);
         writeln!(out).unwrap();
+        if let Some(schema) = typed_result(tool) {
+            emit_struct(&mut out, &format!("{type_name}Result"), &schema, 0);
+            writeln!(out).unwrap();
+        }
     }
 
     // Generate the client struct
-    let client_name = format!("{}Client", to_pascal_case(server_name));
-    writeln!(out, "#[derive(Debug, Clone)]").unwrap();
+    let client_name = format!("{}Client", type_identifier(server_name));
     writeln!(out, "pub struct {client_name} {{").unwrap();
     writeln!(out, "    runtime: mcplug::Runtime,").unwrap();
     writeln!(out, "}}").unwrap();
@@ -34,18 +44,26 @@ pub fn emit_rust_types(tools: &[ToolDefinition], server_name: &str) -> String {
     writeln!(out, "        Self {{ runtime }}").unwrap();
     writeln!(out, "    }}").unwrap();
 
-    for tool in tools {
-        let method_name = to_snake_case(&tool.name);
-        let args_type = format!("{}Args", to_pascal_case(&tool.name));
+    for ((tool, type_name), method_name) in tools.iter().zip(&names.types).zip(&names.methods) {
+        let args_type = format!("{type_name}Args");
         writeln!(out).unwrap();
+        emit_tool_doc(&mut out, tool);
+        // Tools with an output schema return its struct, deserialized from
+        // the result's structured content. Names are emitted as escaped
+        // literals, since they may hold quotes or backslashes
+        let (result_type, call) = if typed_result(tool).is_some() {
+            (format!("{type_name}Result"), "call_tool_as")
+        } else {
+            ("mcplug::CallResult".to_string(), "call_tool")
+        };
         writeln!(
             out,
-            "    pub async fn {method_name}(&self, args: {args_type}) -> Result<mcplug::CallResult, mcplug::McplugError> {{"
+            "    pub async fn {method_name}(&self, args: {args_type}) -> Result<{result_type}, mcplug::McplugError> {{"
         )
         .unwrap();
         writeln!(
             out,
-            "        self.runtime.call_tool(\"{server}\", \"{tool}\", serde_json::to_value(args).unwrap()).await",
+            "        self.runtime.{call}({server:?}, {tool:?}, serde_json::to_value(args).unwrap()).await",
             server = server_name,
             tool = tool.name,
         )
@@ -57,75 +75,360 @@ pub fn emit_rust_types(tools: &[ToolDefinition], server_name: &str) -> String {
     out
 }
 
-fn emit_struct(out: &mut String, name: &str, schema: &serde_json::Value, depth: usize) {
+/// Generate one module per server for `emit-rs --all`: `<server>.rs` with
+/// the server's types and client as [`emit_rust_types`] writes them, and
+/// `mod.rs` declaring the modules and an `McpClients` struct holding every
+/// client. Returns `(file name, source)` pairs, with servers ordered by
+/// name and `mod.rs` last.
+pub fn emit_rust_modules(snapshots: &[Snapshot]) -> Vec<(String, String)> {
+    let mut snapshots: Vec<&Snapshot> = snapshots.iter().collect();
+    snapshots.sort_by(|a, b| a.server.cmp(&b.server));
+    let modules = unique_identifiers(
+        snapshots.iter().map(|s| field_identifier(&s.server)),
+        &[],
+        "_",
+    );
+
+    let mut files = Vec::new();
+    let mut root = String::new();
+    writeln!(root, "// Auto-generated by mcplug. Do not edit.").unwrap();
+    writeln!(root).unwrap();
+    for (snapshot, module) in snapshots.iter().zip(&modules) {
+        files.push((
+            format!("{module}.rs"),
+            emit_rust_types(&snapshot.tools, &snapshot.server),
+        ));
+        writeln!(root, "pub mod {module};").unwrap();
+    }
+
+    let clients: Vec<String> = snapshots
+        .iter()
+        .zip(&modules)
+        .map(|(snapshot, module)| {
+            format!("{module}::{}Client", type_identifier(&snapshot.server))
+        })
+        .collect();
+    writeln!(root).unwrap();
+    writeln!(root, "/// Typed clients for every server.").unwrap();
+    writeln!(root, "pub struct McpClients {{").unwrap();
+    for ((snapshot, module), client) in snapshots.iter().zip(&modules).zip(&clients) {
+        writeln!(root, "    /// The `{}` server.", snapshot.server).unwrap();
+        writeln!(root, "    pub {module}: {client},").unwrap();
+    }
+    writeln!(root, "}}").unwrap();
+    writeln!(root).unwrap();
+    writeln!(root, "impl McpClients {{").unwrap();
+    writeln!(
+        root,
+        "    /// Create every client, each with its own runtime from the mcplug config."
+    )
+    .unwrap();
+    writeln!(
+        root,
+        "    pub async fn from_config() -> Result<Self, mcplug::McplugError> {{"
+    )
+    .unwrap();
+    writeln!(root, "        Ok(Self {{").unwrap();
+    for (module, client) in modules.iter().zip(&clients) {
+        writeln!(
+            root,
+            "            {module}: {client}::new(mcplug::Runtime::from_config().await?),"
+        )
+        .unwrap();
+    }
+    writeln!(root, "        }})").unwrap();
+    writeln!(root, "    }}").unwrap();
+    writeln!(root, "}}").unwrap();
+    files.push(("mod.rs".to_string(), root));
+    files
+}
+
+/// Write the [`emit_rust_modules`] files into `dir`, creating it if needed,
+/// and format them with `rustfmt`. Returns the written paths.
+pub fn write_rust_modules(dir: &Path, snapshots: &[Snapshot]) -> Result<Vec<PathBuf>, McplugError> {
+    std::fs::create_dir_all(dir)?;
+    let mut paths = Vec::new();
+    for (name, source) in emit_rust_modules(snapshots) {
+        let path = dir.join(name);
+        std::fs::write(&path, source)?;
+        paths.push(path);
+    }
+    rustfmt(&paths)?;
+    Ok(paths)
+}
+
+/// Format `paths` in place with the `rustfmt` named by `$RUSTFMT`, else the
+/// one on `PATH`.
+pub fn rustfmt(paths: &[PathBuf]) -> Result<(), McplugError> {
+    let rustfmt = std::env::var_os("RUSTFMT").unwrap_or_else(|| "rustfmt".into());
+    let status = std::process::Command::new(&rustfmt)
+        .args(["--edition", "2021"])
+        .args(paths)
+        .stdin(std::process::Stdio::null())
+        .status()
+        .map_err(|e| {
+            McplugError::IoError(std::io::Error::new(
+                e.kind(),
+                format!("cannot run {}: {e}", rustfmt.to_string_lossy()),
+            ))
+        })?;
+    if !status.success() {
+        return Err(McplugError::IoError(std::io::Error::other(format!(
+            "rustfmt failed ({status})"
+        ))));
+    }
+    Ok(())
+}
+
+/// `tools` ordered by name, so generated code doesn't change with the order
+/// a server lists them in.
+pub fn sorted_tools(tools: &[ToolDefinition]) -> Vec<&ToolDefinition> {
+    let mut sorted: Vec<&ToolDefinition> = tools.iter().collect();
+    sorted.sort_by(|a, b| a.name.cmp(&b.name));
+    sorted
+}
+
+/// The Rust names generated for a list of tools, in the same order.
+pub struct ToolNames {
+    /// PascalCase bases of each tool's type names, as in `<base>Args`.
+    pub types: Vec<String>,
+    /// snake_case names of each tool's client method.
+    pub methods: Vec<String>,
+}
+
+impl ToolNames {
+    /// Name each tool, giving tools whose names normalize to the same
+    /// identifier numbered suffixes in list order. Methods never take the
+    /// client's `new`.
+    pub fn new<'a>(tools: impl IntoIterator<Item = &'a ToolDefinition>) -> Self {
+        let names: Vec<&str> = tools.into_iter().map(|t| t.name.as_str()).collect();
+        Self {
+            types: unique_identifiers(names.iter().map(|n| type_identifier(n)), &[], ""),
+            methods: unique_identifiers(names.iter().map(|n| field_identifier(n)), &["new"], "_"),
+        }
+    }
+}
+
+/// The parsed output schema of a tool whose results get their own struct:
+/// one declaring an object with properties. Results of other tools stay
+/// `CallResult`s.
+fn typed_result(tool: &ToolDefinition) -> Option<ToolSchema> {
+    tool.result_schema().filter(|schema| !schema.properties.is_empty())
+}
+
+/// Emit doc comments for a client method from the tool's description and annotations.
+fn emit_tool_doc(out: &mut String, tool: &ToolDefinition) {
+    let title = tool.annotations.as_ref().and_then(|a| a.title.as_deref());
+    if let Some(title) = title {
+        writeln!(out, "    /// {title}").unwrap();
+    }
+    if !tool.description.is_empty() {
+        if title.is_some() {
+            writeln!(out, "    ///").unwrap();
+        }
+        for line in tool.description.lines() {
+            writeln!(out, "    /// {line}").unwrap();
+        }
+    }
+    let labels = tool
+        .annotations
+        .as_ref()
+        .map(|a| a.hint_labels())
+        .unwrap_or_default();
+    if !labels.is_empty() {
+        if title.is_some() || !tool.description.is_empty() {
+            writeln!(out, "    ///").unwrap();
+        }
+        writeln!(out, "    /// Hints: {}", labels.join(", ")).unwrap();
+    }
+}
+
+fn emit_struct(out: &mut String, name: &str, schema: &ToolSchema, depth: usize) {
     let indent = "    ".repeat(depth);
+    // Enums for union-typed fields, emitted after the struct
+    let mut enums = String::new();
     writeln!(out, "{indent}#[derive(Debug, Clone, Serialize, Deserialize)]").unwrap();
     writeln!(out, "{indent}pub struct {name} {{").unwrap();
 
-    let required = schema
-        .get("required")
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| v.as_str())
-                .map(String::from)
-                .collect::<Vec<_>>()
-        })
-        .unwrap_or_default();
+    let field_names = unique_identifiers(
+        schema.properties.iter().map(|p| field_identifier(&p.name)),
+        &[],
+        "_",
+    );
+    for (prop, field_name) in schema.properties.iter().zip(field_names) {
+        let (rust_type, nullable) = match &prop.ty {
+            Some(ty @ SchemaType::Union(_)) => match ty.without_null() {
+                // `T | null` is an `Option<T>`
+                Some(inner) => (rust_type(Some(inner)), true),
+                None => {
+                    let enum_name = format!("{name}{}", to_pascal_case(&field_name));
+                    if emit_union_enum(&mut enums, &enum_name, ty, depth) {
+                        (enum_name, ty.is_nullable())
+                    } else {
+                        tracing::warn!(
+                            "{name}.{}: can't represent type `{ty}`; using serde_json::Value",
+                            prop.name
+                        );
+                        writeln!(out, "{indent}    /// Any of: {ty}").unwrap();
+                        ("serde_json::Value".to_string(), false)
+                    }
+                }
+            },
+            ty => (rust_type(ty.as_ref()), false),
+        };
+
+        // Add serde rename if the field name differs from the original
+        if field_name != prop.name {
+            writeln!(
+                out,
+                "{indent}    #[serde(rename = {:?})]",
+                prop.name
+            )
+            .unwrap();
+        }
 
-    if let Some(props) = schema.get("properties").and_then(|v| v.as_object()) {
-        for (prop_name, prop_schema) in props {
-            let field_name = sanitize_identifier(&to_snake_case(prop_name));
-            let rust_type = json_schema_to_rust_type(prop_schema);
-
-            // Add serde rename if the field name differs from the original
-            if field_name != *prop_name {
-                writeln!(
-                    out,
-                    "{indent}    #[serde(rename = \"{prop_name}\")]"
-                )
-                .unwrap();
-            }
+        // A nullable field that may also be absent is still one `Option`
+        if prop.required && !nullable {
+            writeln!(out, "{indent}    pub {field_name}: {rust_type},").unwrap();
+        } else {
+            writeln!(out, "{indent}    pub {field_name}: Option<{rust_type}>,").unwrap();
+        }
+    }
 
-            if required.contains(prop_name) {
-                writeln!(out, "{indent}    pub {field_name}: {rust_type},").unwrap();
-            } else {
-                writeln!(out, "{indent}    pub {field_name}: Option<{rust_type}>,").unwrap();
+    writeln!(out, "{indent}}}").unwrap();
+    if !enums.is_empty() {
+        writeln!(out).unwrap();
+        out.push_str(&enums);
+    }
+}
+
+/// Emit an untagged enum with a variant per non-null member of a union.
+/// Returns false, emitting nothing, if a member has no Rust type or two
+/// members would share a variant.
+fn emit_union_enum(out: &mut String, name: &str, ty: &SchemaType, depth: usize) -> bool {
+    let mut variants: Vec<(u8, String, String)> = Vec::new();
+    for member in ty.members() {
+        let (rank, variant) = match member {
+            SchemaType::Null => continue,
+            // Serde tries variants in order: booleans and integers before
+            // numbers, which would otherwise take them
+            SchemaType::Boolean => (0, "Boolean".to_string()),
+            SchemaType::Integer => (1, "Integer".to_string()),
+            SchemaType::Number => (2, "Number".to_string()),
+            SchemaType::String => (3, "String".to_string()),
+            SchemaType::Array(items) => {
+                let prefix = items.as_deref().map(variant_prefix).unwrap_or_default();
+                (4, format!("{prefix}Array"))
             }
+            SchemaType::Object => (5, "Object".to_string()),
+            SchemaType::Other(_) | SchemaType::Union(_) => return false,
+        };
+        if variants.iter().any(|(_, existing, _)| *existing == variant) {
+            return false;
         }
+        let inner = match member {
+            SchemaType::Object => "serde_json::Map<String, serde_json::Value>".to_string(),
+            member => rust_type(Some(member)),
+        };
+        variants.push((rank, variant, inner));
     }
+    variants.sort_by_key(|(rank, _, _)| *rank);
 
+    let indent = "    ".repeat(depth);
+    writeln!(out, "{indent}#[derive(Debug, Clone, Serialize, Deserialize)]").unwrap();
+    writeln!(out, "{indent}#[serde(untagged)]").unwrap();
+    writeln!(out, "{indent}pub enum {name} {{").unwrap();
+    for (_, variant, inner) in &variants {
+        writeln!(out, "{indent}    {variant}({inner}),").unwrap();
+    }
     writeln!(out, "{indent}}}").unwrap();
+    true
+}
+
+/// The name an array's item type gives its enum variant, as in
+/// `StringArray`.
+fn variant_prefix(items: &SchemaType) -> String {
+    match items {
+        SchemaType::Array(inner) => {
+            format!("{}Array", inner.as_deref().map(variant_prefix).unwrap_or_default())
+        }
+        SchemaType::Union(_) | SchemaType::Other(_) => String::new(),
+        ty => to_pascal_case(&ty.to_string()),
+    }
 }
 
 /// Convert a JSON Schema type description to a Rust type string.
 pub fn json_schema_to_rust_type(schema: &serde_json::Value) -> String {
-    match schema.get("type").and_then(|v| v.as_str()) {
-        Some("string") => "String".to_string(),
-        Some("number") => "f64".to_string(),
-        Some("integer") => "i64".to_string(),
-        Some("boolean") => "bool".to_string(),
-        Some("array") => {
-            let item_type = schema
-                .get("items")
-                .map(json_schema_to_rust_type)
-                .unwrap_or_else(|| "serde_json::Value".to_string());
-            format!("Vec<{item_type}>")
-        }
-        Some("object") => {
-            if schema.get("properties").is_some() {
-                // Nested object with known properties — would need a named struct.
-                // For simplicity in inline usage, use Value.
-                "serde_json::Value".to_string()
-            } else {
-                "serde_json::Value".to_string()
-            }
-        }
-        Some("null") => "()".to_string(),
-        _ => "serde_json::Value".to_string(),
+    rust_type(SchemaType::parse(schema).as_ref())
+}
+
+/// The Rust type for values of a schema type; untyped values stay JSON.
+pub fn rust_type(ty: Option<&SchemaType>) -> String {
+    match ty {
+        Some(SchemaType::String) => "String".to_string(),
+        Some(SchemaType::Number) => "f64".to_string(),
+        Some(SchemaType::Integer) => "i64".to_string(),
+        Some(SchemaType::Boolean) => "bool".to_string(),
+        Some(SchemaType::Array(items)) => format!("Vec<{}>", rust_type(items.as_deref())),
+        // Nested objects would need named structs; use Value for them
+        Some(SchemaType::Null) => "()".to_string(),
+        // Unions are `Option`s of their one other type, if they have one;
+        // emit_struct gives fields of other unions an enum
+        Some(ty @ SchemaType::Union(_)) => match ty.without_null() {
+            Some(inner) => format!("Option<{}>", rust_type(Some(inner))),
+            None => "serde_json::Value".to_string(),
+        },
+        Some(SchemaType::Object | SchemaType::Other(_)) | None => "serde_json::Value".to_string(),
     }
 }
 
+/// The Rust type name for a tool or server name, in PascalCase.
+pub fn type_identifier(name: &str) -> String {
+    match to_pascal_case(name) {
+        pascal if pascal.is_empty() => "Unnamed".to_string(),
+        // The one keyword in PascalCase; `Self_` would trip the naming lint
+        pascal if pascal == "Self" => "SelfTool".to_string(),
+        pascal => sanitize_identifier(&pascal),
+    }
+}
+
+/// The Rust field or function name for a property or tool name, in
+/// snake_case.
+pub fn field_identifier(name: &str) -> String {
+    sanitize_identifier(&to_snake_case(name))
+}
+
+/// Make `names` unique, keeping the first of each and numbering later
+/// repeats from 2, joined by `separator`. Numbered names skip every name
+/// given, and the `reserved` ones are never used.
+pub fn unique_identifiers(
+    names: impl IntoIterator<Item = String>,
+    reserved: &[&str],
+    separator: &str,
+) -> Vec<String> {
+    let names: Vec<String> = names.into_iter().collect();
+    let mut taken: std::collections::HashSet<String> =
+        reserved.iter().map(|name| name.to_string()).collect();
+    let given: std::collections::HashSet<&str> = names.iter().map(String::as_str).collect();
+    names
+        .iter()
+        .map(|name| {
+            let unique = if taken.contains(name) {
+                (2..)
+                    .map(|n| format!("{name}{separator}{n}"))
+                    .find(|candidate| {
+                        !taken.contains(candidate) && !given.contains(candidate.as_str())
+                    })
+                    .unwrap_or_default()
+            } else {
+                name.clone()
+            };
+            taken.insert(unique.clone());
+            unique
+        })
+        .collect()
+}
+
 /// Sanitize a string to be a valid Rust identifier.
 pub fn sanitize_identifier(name: &str) -> String {
     let mut result = String::with_capacity(name.len());
@@ -142,16 +445,18 @@ pub fn sanitize_identifier(name: &str) -> String {
             result = prefixed;
         }
     }
-    if result.is_empty() {
+    if result.is_empty() || result == "_" {
         return "_unnamed".to_string();
     }
-    // Avoid Rust keywords
+    // Avoid Rust keywords, including reserved ones. `self` and friends
+    // can't be raw identifiers, so all get a trailing underscore instead
     match result.as_str() {
-        "type" | "struct" | "enum" | "fn" | "let" | "mut" | "ref" | "self" | "super" | "crate"
-        | "use" | "mod" | "pub" | "return" | "match" | "if" | "else" | "loop" | "while"
-        | "for" | "in" | "as" | "impl" | "trait" | "where" | "async" | "await" | "move"
-        | "dyn" | "static" | "const" | "unsafe" | "extern" | "true" | "false" | "break"
-        | "continue" | "yield" => {
+        "type" | "struct" | "enum" | "fn" | "let" | "mut" | "ref" | "self" | "Self" | "super"
+        | "crate" | "use" | "mod" | "pub" | "return" | "match" | "if" | "else" | "loop"
+        | "while" | "for" | "in" | "as" | "impl" | "trait" | "where" | "async" | "await"
+        | "move" | "dyn" | "static" | "const" | "unsafe" | "extern" | "true" | "false"
+        | "break" | "continue" | "yield" | "abstract" | "become" | "box" | "do" | "final"
+        | "gen" | "macro" | "override" | "priv" | "try" | "typeof" | "unsized" | "virtual" => {
             result.push('_');
         }
         _ => {}
@@ -159,9 +464,10 @@ pub fn sanitize_identifier(name: &str) -> String {
     result
 }
 
-/// Convert a string to PascalCase.
+/// Convert a string to PascalCase, splitting words at any character that
+/// can't be in an identifier.
 pub fn to_pascal_case(name: &str) -> String {
-    name.split(['-', '_', '.', ' '])
+    name.split(|c: char| !c.is_ascii_alphanumeric())
         .filter(|s| !s.is_empty())
         .map(|word| {
             let mut chars = word.chars();
@@ -184,15 +490,7 @@ pub fn to_snake_case(name: &str) -> String {
     let mut prev_was_separator = false;
 
     for (i, ch) in name.chars().enumerate() {
-        if ch == '-' || ch == '.' || ch == ' ' {
-            if !result.is_empty() && !prev_was_separator {
-                result.push('_');
-            }
-            prev_was_separator = true;
-            prev_was_upper = false;
-            continue;
-        }
-        if ch == '_' {
+        if !ch.is_ascii_alphanumeric() {
             if !result.is_empty() && !prev_was_separator {
                 result.push('_');
             }
@@ -227,6 +525,7 @@ mod tests {
         assert_eq!(to_pascal_case("simple"), "Simple");
         assert_eq!(to_pascal_case("a.b.c"), "ABC");
         assert_eq!(to_pascal_case("already_Pascal"), "AlreadyPascal");
+        assert_eq!(to_pascal_case("repos/list all"), "ReposListAll");
     }
 
     #[test]
@@ -237,6 +536,7 @@ mod tests {
         assert_eq!(to_snake_case("kebab-case"), "kebab_case");
         assert_eq!(to_snake_case("with.dots"), "with_dots");
         assert_eq!(to_snake_case("already_snake"), "already_snake");
+        assert_eq!(to_snake_case("repos/list"), "repos_list");
     }
 
     #[test]
@@ -248,6 +548,33 @@ mod tests {
         assert_eq!(sanitize_identifier("type"), "type_");
         assert_eq!(sanitize_identifier(""), "_unnamed");
         assert_eq!(sanitize_identifier("hello!world"), "hello_world");
+        assert_eq!(sanitize_identifier("Self"), "Self_");
+        assert_eq!(sanitize_identifier("try"), "try_");
+        assert_eq!(sanitize_identifier("_"), "_unnamed");
+    }
+
+    #[test]
+    fn test_type_identifier() {
+        assert_eq!(type_identifier("get-user"), "GetUser");
+        assert_eq!(type_identifier("123start"), "_123start");
+        assert_eq!(type_identifier("self"), "SelfTool");
+        assert_eq!(type_identifier("@@"), "Unnamed");
+    }
+
+    #[test]
+    fn test_unique_identifiers() {
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            unique_identifiers(names(&["a", "b", "a", "a"]), &[], "_"),
+            ["a", "b", "a_2", "a_3"]
+        );
+        // Numbered names skip names given later, and reserved names
+        assert_eq!(
+            unique_identifiers(names(&["a", "a", "a_2"]), &[], "_"),
+            ["a", "a_3", "a_2"]
+        );
+        assert_eq!(unique_identifiers(names(&["new", "New"]), &["new"], "_"), ["new_2", "New"]);
+        assert_eq!(unique_identifiers(names(&["Get", "Get"]), &[], ""), ["Get", "Get2"]);
     }
 
     #[test]
@@ -311,6 +638,8 @@ mod tests {
                 },
                 "required": ["location"]
             }),
+            annotations: None,
+            output_schema: None,
         }];
 
         let output = emit_rust_types(&tools, "weather-api");
@@ -335,6 +664,8 @@ mod tests {
                     },
                     "required": ["path"]
                 }),
+                annotations: None,
+                output_schema: None,
             },
             ToolDefinition {
                 name: "read_file".to_string(),
@@ -347,6 +678,8 @@ mod tests {
                     },
                     "required": ["path"]
                 }),
+                annotations: None,
+                output_schema: None,
             },
         ];
 
@@ -398,6 +731,8 @@ mod tests {
                 },
                 "required": ["query"]
             }),
+            annotations: None,
+            output_schema: None,
         }];
 
         let output = emit_rust_types(&tools, "search-engine");
@@ -407,4 +742,225 @@ mod tests {
         assert!(output.contains("pub verbose: Option<bool>"));
         assert!(output.contains("pub score_threshold: Option<f64>"));
     }
+
+    #[test]
+    fn test_emit_rust_types_annotation_doc_comments() {
+        let tools = vec![ToolDefinition {
+            name: "delete_file".to_string(),
+            description: "Delete a file".to_string(),
+            input_schema: serde_json::json!({"type": "object", "properties": {}}),
+            annotations: Some(crate::types::ToolAnnotations {
+                title: Some("Delete File".into()),
+                destructive_hint: Some(true),
+                ..Default::default()
+            }),
+            output_schema: None,
+        }];
+
+        let output = emit_rust_types(&tools, "fs");
+        assert!(output.contains("    /// Delete File\n    ///\n    /// Delete a file\n"));
+        assert!(output.contains("    /// Hints: destructive\n    pub async fn delete_file"));
+    }
+
+    #[test]
+    fn test_emit_rust_types_escapes_names_in_literals() {
+        let tools = vec![ToolDefinition {
+            name: r#"say "hi"\now"#.to_string(),
+            description: String::new(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {r#"a"b\c"#: {"type": "string"}},
+                "required": [r#"a"b\c"#]
+            }),
+            annotations: None,
+            output_schema: None,
+        }];
+
+        let output = emit_rust_types(&tools, r#"my"srv"#);
+        assert!(output.contains(r#"#[serde(rename = "a\"b\\c")]"#), "{output}");
+        assert!(output.contains(r#"self.runtime.call_tool("my\"srv", "say \"hi\"\\now", "#));
+    }
+
+    #[test]
+    fn test_emit_rust_types_output_schema_result_struct() {
+        let tool = |name: &str, output_schema| ToolDefinition {
+            name: name.to_string(),
+            description: String::new(),
+            input_schema: serde_json::json!({"type": "object", "properties": {}}),
+            annotations: None,
+            output_schema,
+        };
+        let tools = vec![
+            tool(
+                "get-forecast",
+                Some(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "tempC": {"type": "number"},
+                        "summary": {"type": "string"}
+                    },
+                    "required": ["tempC"]
+                })),
+            ),
+            tool("ping", None),
+            tool("raw", Some(serde_json::json!({"type": "object"}))),
+        ];
+
+        let output = emit_rust_types(&tools, "weather");
+        assert!(output.contains("pub struct GetForecastResult {"));
+        assert!(output.contains("    #[serde(rename = \"tempC\")]\n    pub temp_c: f64,"));
+        assert!(output.contains("    pub summary: Option<String>,"));
+        assert!(output.contains(
+            "pub async fn get_forecast(&self, args: GetForecastArgs) -> Result<GetForecastResult, mcplug::McplugError> {\n        self.runtime.call_tool_as(\"weather\", \"get-forecast\""
+        ));
+        assert!(output.contains("pub async fn ping(&self, args: PingArgs) -> Result<mcplug::CallResult, mcplug::McplugError>"));
+        assert!(!output.contains("RawResult"));
+        assert!(output.contains("pub async fn raw(&self, args: RawArgs) -> Result<mcplug::CallResult"));
+    }
+
+    #[test]
+    fn test_emit_rust_types_union_schemas() {
+        let tools = vec![ToolDefinition {
+            name: "find".to_string(),
+            description: String::new(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "id": {"oneOf": [{"type": "string"}, {"type": "integer"}]},
+                    "limit": {"type": ["number", "integer", "null"]},
+                    "cursor": {"type": ["string", "null"]},
+                    "after": {"anyOf": [{"type": "string"}, {"type": "null"}]},
+                    "ids": {"type": "array", "items": {"type": ["string", "null"]}},
+                    "when": {"type": ["string", "date"]}
+                },
+                "required": ["id", "cursor", "when"]
+            }),
+            annotations: None,
+            output_schema: None,
+        }];
+
+        let output = emit_rust_types(&tools, "db");
+        assert!(output.contains("pub id: FindArgsId,"));
+        assert!(output.contains(
+            "#[serde(untagged)]\npub enum FindArgsId {\n    Integer(i64),\n    String(String),\n}"
+        ));
+        assert!(output.contains("pub limit: Option<FindArgsLimit>,"));
+        assert!(output.contains("pub enum FindArgsLimit {\n    Integer(i64),\n    Number(f64),\n}"));
+        // `T | null` is an Option, whether or not the field is required
+        assert!(output.contains("pub cursor: Option<String>,"));
+        assert!(output.contains("pub after: Option<String>,"));
+        assert!(output.contains("pub ids: Option<Vec<Option<String>>>,"));
+        // A union with a type Rust can't name falls back to JSON
+        assert!(output.contains("    /// Any of: string | date\n    pub when: serde_json::Value,"));
+        assert!(!output.contains("FindArgsWhen"));
+    }
+
+    #[test]
+    fn test_emit_rust_types_sorted_by_name() {
+        let tool = |name: &str| ToolDefinition {
+            name: name.to_string(),
+            description: String::new(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {"zeta": {"type": "string"}, "alpha": {"type": "integer"}}
+            }),
+            annotations: None,
+            output_schema: None,
+        };
+        let mut tools = vec![tool("search"), tool("get_user"), tool("getUser")];
+        let output = emit_rust_types(&tools, "svc");
+        tools.reverse();
+        assert_eq!(emit_rust_types(&tools, "svc"), output);
+
+        let order: Vec<usize> = ["struct GetUserArgs", "struct GetUser2Args", "struct SearchArgs"]
+            .iter()
+            .map(|s| output.find(s).unwrap())
+            .collect();
+        assert!(order.windows(2).all(|w| w[0] < w[1]), "{output}");
+        // `getUser` sorts before `get_user`, so it keeps the plain name
+        assert!(output.contains("call_tool(\"svc\", \"getUser\""));
+        assert!(output.contains("pub async fn get_user(&self, args: GetUserArgs)"));
+        assert!(output.find("pub alpha: Option<i64>").unwrap() < output.find("pub zeta: Option<String>").unwrap());
+    }
+
+    #[test]
+    fn test_emit_rust_modules() {
+        let snapshot = |server: &str| Snapshot {
+            server: server.to_string(),
+            server_info: crate::types::ServerInfo {
+                name: server.to_string(),
+                version: "1.0.0".into(),
+                capabilities: serde_json::json!({}),
+                protocol_version: None,
+            },
+            tools: vec![ToolDefinition {
+                name: "search".into(),
+                description: String::new(),
+                input_schema: serde_json::json!({"type": "object", "properties": {}}),
+                annotations: None,
+                output_schema: None,
+            }],
+        };
+        let files = emit_rust_modules(&[snapshot("linear"), snapshot("git_hub"), snapshot("git-hub")]);
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["git_hub.rs", "git_hub_2.rs", "linear.rs", "mod.rs"]);
+        assert!(files[0].1.contains("self.runtime.call_tool(\"git-hub\", \"search\""));
+        assert!(files[1].1.contains("self.runtime.call_tool(\"git_hub\", \"search\""));
+
+        let root = &files[3].1;
+        assert!(root.contains("pub mod git_hub;\npub mod git_hub_2;\npub mod linear;\n"));
+        assert!(root.contains("    pub git_hub: git_hub::GitHubClient,"));
+        assert!(root.contains("    pub git_hub_2: git_hub_2::GitHubClient,"));
+        assert!(root.contains(
+            "            linear: linear::LinearClient::new(mcplug::Runtime::from_config().await?),"
+        ));
+    }
+
+    #[test]
+    fn test_emit_rust_types_colliding_and_reserved_names() {
+        let tool = |name: &str, input_schema| ToolDefinition {
+            name: name.to_string(),
+            description: String::new(),
+            input_schema,
+            annotations: None,
+            output_schema: None,
+        };
+        let empty = serde_json::json!({"type": "object", "properties": {}});
+        let tools = vec![
+            tool(
+                "get-user",
+                serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "type": {"type": "string"},
+                        "fooBar": {"type": "string"},
+                        "foo_bar": {"type": "integer"},
+                        "123start": {"type": "boolean"}
+                    },
+                    "required": ["type", "fooBar", "foo_bar", "123start"]
+                }),
+            ),
+            tool("getUser", empty.clone()),
+            tool("new", empty.clone()),
+            tool("import", empty.clone()),
+            tool("123start", empty),
+        ];
+
+        let output = emit_rust_types(&tools, "self");
+        assert!(output.contains("pub struct GetUserArgs {"));
+        assert!(output.contains("pub struct GetUser2Args {"));
+        assert!(output.contains("pub async fn get_user(&self, args: GetUserArgs)"));
+        assert!(output.contains("pub async fn get_user_2(&self, args: GetUser2Args)"));
+        assert!(output.contains("self.runtime.call_tool(\"self\", \"getUser\""));
+        assert!(output.contains("pub async fn new_2(&self, args: NewArgs)"));
+        assert!(output.contains("pub async fn import(&self, args: ImportArgs)"));
+        assert!(output.contains("pub struct _123startArgs {"));
+        assert!(output.contains("pub async fn _123start(&self"));
+        assert!(output.contains("pub struct SelfToolClient {"));
+
+        assert!(output.contains("#[serde(rename = \"type\")]\n    pub type_: String,"));
+        assert!(output.contains("#[serde(rename = \"fooBar\")]\n    pub foo_bar: String,"));
+        assert!(output.contains("#[serde(rename = \"foo_bar\")]\n    pub foo_bar_2: i64,"));
+        assert!(output.contains("#[serde(rename = \"123start\")]\n    pub _123start: bool,"));
+    }
 }
@@ -0,0 +1,416 @@
+use crate::transports::jsonrpc::PROTOCOL_VERSIONS;
+use crate::types::{SchemaProperty, SchemaType, ToolDefinition, ToolSchema};
+use std::fmt::Write;
+
+use super::emit_rs::{sorted_tools, to_pascal_case};
+
+/// Result types and transports shared by every generated client. Calls go
+/// through a `Transport`; `stdioTransport` spawns a server (Node.js only)
+/// and `httpTransport` POSTs to one with `fetch`.
+const RUNTIME: &str = r#"export interface ContentBlock {
+  type: string;
+  text?: string;
+  data?: string;
+  mimeType?: string;
+  [key: string]: unknown;
+}
+
+export interface CallResult {
+  content: ContentBlock[];
+  isError?: boolean;
+  structuredContent?: unknown;
+}
+
+/** Sends tool calls to an MCP server. */
+export interface Transport {
+  callTool(name: string, args: unknown): Promise<CallResult>;
+  close(): Promise<void>;
+}
+
+function unwrap(method: string, message: any): any {
+  if (message.error) {
+    throw new Error(`${method}: ${message.error.message}`);
+  }
+  return message.result;
+}
+
+/** The result's structured content, or its text parsed as JSON. */
+function structured(tool: string, result: CallResult): unknown {
+  const text = result.content
+    .filter((block) => block.type === "text")
+    .map((block) => block.text)
+    .join("");
+  if (result.isError) {
+    throw new Error(`${tool}: ${text}`);
+  }
+  return result.structuredContent ?? JSON.parse(text);
+}
+
+/** Spawn `command` and talk to it over stdin/stdout. Node.js only. */
+export function stdioTransport(
+  command: string,
+  args: string[] = [],
+  env: Record<string, string> = {},
+): Transport {
+  let child: import("node:child_process").ChildProcess | undefined;
+  let ready: Promise<void> | undefined;
+  let nextId = 1;
+  const pending = new Map<number, { method: string; resolve(value: any): void; reject(error: Error): void }>();
+  const failAll = (error: Error) => {
+    for (const request of pending.values()) request.reject(error);
+    pending.clear();
+  };
+  const send = (method: string, params: unknown, notify = false): Promise<any> => {
+    const message = notify
+      ? { jsonrpc: "2.0", method, params }
+      : { jsonrpc: "2.0", id: nextId++, method, params };
+    child!.stdin!.write(JSON.stringify(message) + "\n");
+    if (notify) return Promise.resolve();
+    return new Promise((resolve, reject) => pending.set((message as { id: number }).id, { method, resolve, reject }));
+  };
+  const start = async () => {
+    const { spawn } = await import("node:child_process");
+    const { createInterface } = await import("node:readline");
+    child = spawn(command, args, { env: { ...process.env, ...env }, stdio: ["pipe", "pipe", "inherit"] });
+    child.on("error", failAll);
+    child.on("exit", () => failAll(new Error(`${command} exited`)));
+    createInterface({ input: child.stdout! }).on("line", (line) => {
+      let message: any;
+      try {
+        message = JSON.parse(line);
+      } catch {
+        return;
+      }
+      const request = pending.get(message.id);
+      if (request === undefined) return;
+      pending.delete(message.id);
+      try {
+        request.resolve(unwrap(request.method, message));
+      } catch (error) {
+        request.reject(error as Error);
+      }
+    });
+    await send("initialize", INITIALIZE);
+    await send("notifications/initialized", {}, true);
+  };
+  return {
+    async callTool(name, args) {
+      await (ready ??= start());
+      return send("tools/call", { name, arguments: args });
+    },
+    async close() {
+      child?.kill();
+    },
+  };
+}
+
+/** Talk to an MCP server over streamable HTTP at `url`. */
+export function httpTransport(url: string, headers: Record<string, string> = {}): Transport {
+  let ready: Promise<void> | undefined;
+  let session: string | undefined;
+  let nextId = 1;
+  const send = async (method: string, params: unknown, notify = false): Promise<any> => {
+    const message = notify
+      ? { jsonrpc: "2.0", method, params }
+      : { jsonrpc: "2.0", id: nextId++, method, params };
+    const response = await fetch(url, {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+        Accept: "application/json, text/event-stream",
+        ...(session === undefined ? {} : { "Mcp-Session-Id": session }),
+        ...headers,
+      },
+      body: JSON.stringify(message),
+    });
+    if (!response.ok) {
+      throw new Error(`${method}: HTTP ${response.status}`);
+    }
+    session = response.headers.get("Mcp-Session-Id") ?? session;
+    if (notify) return;
+    let body = await response.text();
+    // A streamed reply carries the response in its last data line
+    if (response.headers.get("Content-Type")?.startsWith("text/event-stream")) {
+      const data = body.split("\n").filter((line) => line.startsWith("data:"));
+      body = data.length > 0 ? data[data.length - 1].slice(5) : "";
+    }
+    return unwrap(method, JSON.parse(body));
+  };
+  const start = async () => {
+    await send("initialize", INITIALIZE);
+    await send("notifications/initialized", {}, true);
+  };
+  return {
+    async callTool(name, args) {
+      await (ready ??= start());
+      return send("tools/call", { name, arguments: args });
+    },
+    async close() {
+      if (session !== undefined) {
+        await fetch(url, { method: "DELETE", headers: { "Mcp-Session-Id": session, ...headers } }).catch(() => {});
+      }
+    },
+  };
+}
+"#;
+
+/// Generate a typed TypeScript client from MCP tool schemas: an interface
+/// per tool's arguments (and result, when it declares an output schema),
+/// and a client class with one method per tool.
+pub fn emit_typescript(tools: &[ToolDefinition], server_name: &str) -> String {
+    let tools = sorted_tools(tools);
+    let mut out = String::new();
+
+    // File header
+    writeln!(out, "// Auto-generated by mcplug. Do not edit.").unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "const INITIALIZE = {};",
+        serde_json::json!({
+            "protocolVersion": PROTOCOL_VERSIONS[0],
+            "capabilities": {},
+            "clientInfo": {"name": "mcplug-ts", "version": env!("CARGO_PKG_VERSION")},
+        })
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    out.push_str(RUNTIME);
+    writeln!(out).unwrap();
+
+    // Generate interfaces for each tool's input and output schemas
+    for tool in &tools {
+        let name = format!("{}Args", to_pascal_case(&tool.name));
+        emit_interface(&mut out, &name, &tool.schema());
+        writeln!(out).unwrap();
+        if let Some(schema) = typed_result(tool) {
+            let name = format!("{}Result", to_pascal_case(&tool.name));
+            emit_interface(&mut out, &name, &schema);
+            writeln!(out).unwrap();
+        }
+    }
+
+    // Generate the client class
+    let client_name = format!("{}Client", to_pascal_case(server_name));
+    writeln!(out, "export class {client_name} {{").unwrap();
+    writeln!(out, "  constructor(private readonly transport: Transport) {{}}").unwrap();
+
+    for tool in &tools {
+        let pascal = to_pascal_case(&tool.name);
+        let method_name = to_camel_case(&tool.name);
+        let tool_name = serde_json::Value::from(tool.name.as_str());
+        writeln!(out).unwrap();
+        emit_doc(&mut out, "  ", &tool.description);
+        if typed_result(tool).is_some() {
+            writeln!(
+                out,
+                "  async {method_name}(args: {pascal}Args): Promise<{pascal}Result> {{"
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "    return structured({tool_name}, await this.transport.callTool({tool_name}, args)) as {pascal}Result;"
+            )
+            .unwrap();
+        } else {
+            writeln!(out, "  {method_name}(args: {pascal}Args): Promise<CallResult> {{").unwrap();
+            writeln!(out, "    return this.transport.callTool({tool_name}, args);").unwrap();
+        }
+        writeln!(out, "  }}").unwrap();
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "  close(): Promise<void> {{").unwrap();
+    writeln!(out, "    return this.transport.close();").unwrap();
+    writeln!(out, "  }}").unwrap();
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+/// The output schema of a tool whose results get their own interface, as
+/// in `emit_rs`: one declaring an object with properties.
+fn typed_result(tool: &ToolDefinition) -> Option<ToolSchema> {
+    tool.result_schema().filter(|schema| !schema.properties.is_empty())
+}
+
+fn emit_doc(out: &mut String, indent: &str, text: &str) {
+    let lines: Vec<&str> = text.lines().collect();
+    match lines.as_slice() {
+        [] => {}
+        [line] => writeln!(out, "{indent}/** {} */", escape_doc(line)).unwrap(),
+        lines => {
+            writeln!(out, "{indent}/**").unwrap();
+            for line in lines {
+                writeln!(out, "{indent} * {}", escape_doc(line)).unwrap();
+            }
+            writeln!(out, "{indent} */").unwrap();
+        }
+    }
+}
+
+/// Keep a description from closing its comment early.
+fn escape_doc(line: &str) -> String {
+    line.replace("*/", "*\\/")
+}
+
+fn emit_interface(out: &mut String, name: &str, schema: &ToolSchema) {
+    writeln!(out, "export interface {name} {{").unwrap();
+    for prop in &schema.properties {
+        if let Some(ref desc) = prop.description {
+            emit_doc(out, "  ", desc);
+        }
+        let optional = if prop.required { "" } else { "?" };
+        writeln!(
+            out,
+            "  {}{optional}: {};",
+            property_key(&prop.name),
+            property_type(prop)
+        )
+        .unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+}
+
+/// A property's type: a union of its enum values if it lists them, else
+/// the TypeScript type for its schema type.
+fn property_type(prop: &SchemaProperty) -> String {
+    if !prop.enum_values.is_empty() {
+        return prop
+            .enum_values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+    ts_type(prop.ty.as_ref())
+}
+
+/// The TypeScript type for values of a schema type; untyped values are
+/// `unknown`.
+pub fn ts_type(ty: Option<&SchemaType>) -> String {
+    match ty {
+        Some(SchemaType::String) => "string".to_string(),
+        Some(SchemaType::Number | SchemaType::Integer) => "number".to_string(),
+        Some(SchemaType::Boolean) => "boolean".to_string(),
+        Some(SchemaType::Array(Some(items))) if matches!(**items, SchemaType::Union(_)) => {
+            format!("({})[]", ts_type(Some(items)))
+        }
+        Some(SchemaType::Array(items)) => format!("{}[]", ts_type(items.as_deref())),
+        Some(SchemaType::Object) => "Record<string, unknown>".to_string(),
+        Some(SchemaType::Null) => "null".to_string(),
+        Some(SchemaType::Union(members)) => {
+            let types: Vec<String> = members.iter().map(|ty| ts_type(Some(ty))).collect();
+            types.join(" | ")
+        }
+        Some(SchemaType::Other(_)) | None => "unknown".to_string(),
+    }
+}
+
+/// A property name as an interface key, quoted unless it is a plain
+/// identifier.
+fn property_key(name: &str) -> String {
+    let plain = name.chars().enumerate().all(|(i, c)| {
+        c.is_ascii_alphabetic() || c == '_' || c == '$' || (i > 0 && c.is_ascii_digit())
+    });
+    if plain && !name.is_empty() {
+        name.to_string()
+    } else {
+        serde_json::Value::from(name).to_string()
+    }
+}
+
+/// Convert a tool name to camelCase for a method name.
+pub fn to_camel_case(name: &str) -> String {
+    let pascal = to_pascal_case(name);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_digit() => format!("_{pascal}"),
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => "_unnamed".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(name: &str, description: &str, input_schema: serde_json::Value) -> ToolDefinition {
+        ToolDefinition {
+            name: name.to_string(),
+            description: description.to_string(),
+            input_schema,
+            annotations: None,
+            output_schema: None,
+        }
+    }
+
+    #[test]
+    fn emits_interfaces_and_client() {
+        let tools = vec![
+            tool(
+                "get-weather",
+                "Get weather for a location",
+                serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "location": {"type": "string", "description": "City name"},
+                        "units": {"type": "string", "enum": ["metric", "imperial"]},
+                        "days": {"type": "integer"},
+                        "tags": {"type": "array", "items": {"type": "string"}},
+                        "max-age": {"type": "number"},
+                        "extra": {}
+                    },
+                    "required": ["location"]
+                }),
+            ),
+            ToolDefinition {
+                output_schema: Some(serde_json::json!({
+                    "type": "object",
+                    "properties": {"tempC": {"type": "number"}},
+                    "required": ["tempC"]
+                })),
+                ..tool("forecast", "Line one\nline two", serde_json::json!({}))
+            },
+        ];
+        let output = emit_typescript(&tools, "my-weather");
+
+        assert!(output.contains("export interface GetWeatherArgs {"));
+        assert!(output.contains("  /** City name */\n  location: string;"));
+        assert!(output.contains("  units?: \"metric\" | \"imperial\";"));
+        assert!(output.contains("  days?: number;"));
+        assert!(output.contains("  tags?: string[];"));
+        assert!(output.contains("  \"max-age\"?: number;"));
+        assert!(output.contains("  extra?: unknown;"));
+        assert!(output.contains("export interface ForecastArgs {\n}"));
+        assert!(output.contains("export interface ForecastResult {\n  tempC: number;\n}"));
+
+        assert!(output.contains("export class MyWeatherClient {"));
+        assert!(output.contains(
+            "  /** Get weather for a location */\n  getWeather(args: GetWeatherArgs): Promise<CallResult> {\n    return this.transport.callTool(\"get-weather\", args);"
+        ));
+        assert!(output.contains("  /**\n   * Line one\n   * line two\n   */\n  async forecast(args: ForecastArgs): Promise<ForecastResult> {"));
+        assert!(output.contains("export function stdioTransport("));
+        assert!(output.contains("export function httpTransport("));
+        assert!(output.contains(&format!("\"protocolVersion\":\"{}\"", PROTOCOL_VERSIONS[0])));
+    }
+
+    #[test]
+    fn ts_types_and_names() {
+        assert_eq!(ts_type(Some(&SchemaType::Array(None))), "unknown[]");
+        assert_eq!(
+            ts_type(Some(&SchemaType::Array(Some(Box::new(SchemaType::Array(Some(
+                Box::new(SchemaType::Integer)
+            ))))))),
+            "number[][]"
+        );
+        assert_eq!(ts_type(Some(&SchemaType::Object)), "Record<string, unknown>");
+        let id = SchemaType::Union(vec![SchemaType::String, SchemaType::Integer]);
+        assert_eq!(ts_type(Some(&id)), "string | number");
+        assert_eq!(ts_type(Some(&SchemaType::Array(Some(Box::new(id))))), "(string | number)[]");
+        assert_eq!(to_camel_case("list_open-issues"), "listOpenIssues");
+        assert_eq!(to_camel_case("2fa"), "_2fa");
+        assert_eq!(property_key("$ref"), "$ref");
+        assert_eq!(property_key("a b"), "\"a b\"");
+        assert_eq!(escape_doc("see /* and */"), "see /* and *\\/");
+    }
+}
@@ -1,7 +1,86 @@
-use crate::types::ToolDefinition;
+use crate::config::ServerConfig;
+use crate::error::McplugError;
+use crate::types::{SchemaType, ToolDefinition};
 use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use super::emit_rs::{field_identifier, rust_type, to_pascal_case, unique_identifiers, ToolNames};
+
+/// Dependencies of a generated CLI project, pinned to the versions this
+/// release of mcplug is built with.
+pub(super) const PINNED_DEPENDENCIES: &[(&str, &str)] = &[
+    ("clap", r#"{ version = "=4.6.1", features = ["derive"] }"#),
+    ("serde", r#"{ version = "=1.0.228", features = ["derive"] }"#),
+    ("serde_json", r#""=1.0.149""#),
+    ("tokio", r#"{ version = "=1.52.3", features = ["macros", "rt-multi-thread"] }"#),
+];
+
+/// The entry point of every generated CLI: calls the tool its subcommand
+/// names and prints the result like `mcplug call`, with `--json` and
+/// `--raw`. Errors, timeouts, and results the tool marks `isError` exit 1.
+const MAIN: &str = r#"#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let (json, raw) = (cli.json, cli.raw);
+    match run(cli).await {
+        Ok(result) => {
+            print_result(&result, json, raw);
+            if result.is_error {
+                std::process::exit(1);
+            }
+        }
+        Err(e) => {
+            if json {
+                println!("{}", serde_json::to_string_pretty(&e.to_json()).unwrap_or_default());
+            } else {
+                eprintln!("Error: {e}");
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Call the tool the subcommand names. Calls to servers without a
+/// `timeoutMs` give up after `--timeout` seconds, else
+/// `$MCPLUG_CALL_TIMEOUT`, else 30.
+async fn run(cli: Cli) -> Result<mcplug::CallResult, mcplug::McplugError> {
+    let timeout = cli
+        .timeout
+        .or_else(|| std::env::var("MCPLUG_CALL_TIMEOUT").ok()?.parse().ok())
+        .unwrap_or(30);
+    let (tool, args) = cli.command.into_call().map_err(|e| {
+        mcplug::McplugError::ProtocolError(format!("Cannot parse arguments: {e}"))
+    })?;
+    let runtime = runtime_builder()?
+        .default_timeout(std::time::Duration::from_secs(timeout))
+        .build()
+        .await?;
+    let result = runtime.call_tool(SERVER, tool, args).await;
+    let _ = runtime.close().await;
+    result
+}
 
-use super::emit_rs::{json_schema_to_rust_type, sanitize_identifier, to_pascal_case, to_snake_case};
+/// Print a result as JSON, as its bare text, or as its content blocks,
+/// with an error result's message on stderr.
+fn print_result(result: &mcplug::CallResult, json: bool, raw: bool) {
+    if json {
+        let value = serde_json::json!({"content": result.content, "isError": result.is_error});
+        println!("{}", serde_json::to_string_pretty(&value).unwrap_or_default());
+    } else if raw {
+        print!("{}", result.text());
+    } else if result.is_error {
+        eprintln!("Error: {}", result.text());
+    } else {
+        for block in &result.content {
+            match block {
+                mcplug::ContentBlock::Text { text } => println!("{text}"),
+                mcplug::ContentBlock::Image { mime_type, .. } => println!("[image: {mime_type}]"),
+                mcplug::ContentBlock::Resource { uri, text } => println!("{uri}\n{text}"),
+            }
+        }
+    }
+}
+"#;
 
 /// Generate a standalone CLI Rust source file from MCP tool definitions.
 ///
@@ -12,7 +91,23 @@ pub fn generate_cli_source(
     include: Option<&[String]>,
     exclude: Option<&[String]>,
 ) -> String {
-    let filtered_tools = filter_tools(tools, include, exclude);
+    generate_cli_source_with_config(tools, server_name, include, exclude, None)
+}
+
+/// Generate a CLI like [`generate_cli_source`] that, given the server's
+/// config, carries it instead of reading the user's mcplug config when it
+/// runs. The config is embedded as given, so `${VAR}` references are
+/// expanded by the CLI at run time.
+pub fn generate_cli_source_with_config(
+    tools: &[ToolDefinition],
+    server_name: &str,
+    include: Option<&[String]>,
+    exclude: Option<&[String]>,
+    server_config: Option<&ServerConfig>,
+) -> String {
+    let mut filtered_tools = filter_tools(tools, include, exclude);
+    filtered_tools.sort_by(|a, b| a.name.cmp(&b.name));
+    let variants = ToolNames::new(filtered_tools.iter().copied()).types;
     let mut out = String::new();
 
     // File header
@@ -23,29 +118,79 @@ pub fn generate_cli_source(
     writeln!(out).unwrap();
 
     // Generate arg structs for each tool
-    for tool in &filtered_tools {
-        let struct_name = format!("{}Args", to_pascal_case(&tool.name));
+    for (tool, variant) in filtered_tools.iter().zip(&variants) {
+        let struct_name = format!("{variant}Args");
         emit_clap_args_struct(&mut out, &struct_name, tool);
         writeln!(out).unwrap();
     }
 
+    // Object-valued arguments are given as JSON on the command line
+    let takes_json = filtered_tools
+        .iter()
+        .any(|tool| {
+            tool.schema().properties.iter().any(|p| {
+                needs_json(p.ty.as_ref().map(|ty| ty.without_null().unwrap_or(ty)))
+            })
+        });
+    if takes_json {
+        writeln!(out, "fn parse_json(s: &str) -> Result<serde_json::Value, serde_json::Error> {{").unwrap();
+        writeln!(out, "    serde_json::from_str(s)").unwrap();
+        writeln!(out, "}}").unwrap();
+        writeln!(out).unwrap();
+    }
+
     // Generate the subcommand enum
     let app_name = to_pascal_case(server_name);
     writeln!(out, "#[derive(Debug, Subcommand)]").unwrap();
     writeln!(out, "pub enum Commands {{").unwrap();
-    for tool in &filtered_tools {
-        let variant = to_pascal_case(&tool.name);
+    for (tool, variant) in filtered_tools.iter().zip(&variants) {
         let args_type = format!("{variant}Args");
         // Add doc comment from tool description
-        if !tool.description.is_empty() {
-            writeln!(out, "    /// {}", tool.description).unwrap();
+        for line in tool.description.lines() {
+            writeln!(out, "    /// {line}").unwrap();
+        }
+        // A variant renamed to avoid a collision or keyword keeps the
+        // tool's name as its subcommand
+        if *variant != to_pascal_case(&tool.name) {
+            writeln!(out, "    #[command(name = {:?})]", tool.name).unwrap();
         }
         writeln!(out, "    {variant}({args_type}),").unwrap();
     }
     writeln!(out, "}}").unwrap();
     writeln!(out).unwrap();
 
-    // Generate the CLI parser struct
+    // Map each subcommand to its tool call
+    writeln!(out, "impl Commands {{").unwrap();
+    writeln!(out, "    /// The tool to call and its arguments.").unwrap();
+    writeln!(
+        out,
+        "    fn into_call(self) -> Result<(&'static str, serde_json::Value), serde_json::Error> {{"
+    )
+    .unwrap();
+    if filtered_tools.is_empty() {
+        writeln!(out, "        match self {{}}").unwrap();
+    } else {
+        writeln!(out, "        Ok(match self {{").unwrap();
+        for (tool, variant) in filtered_tools.iter().zip(&variants) {
+            writeln!(
+                out,
+                "            Commands::{variant}(args) => ({:?}, serde_json::to_value(args)?),",
+                tool.name
+            )
+            .unwrap();
+        }
+        writeln!(out, "        }})").unwrap();
+    }
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+
+    // Generate the CLI parser struct, with output flags shared by every
+    // subcommand
+    writeln!(out, "/// The server this CLI calls, by its name in the mcplug config.").unwrap();
+    writeln!(out, "const SERVER: &str = {server_name:?};").unwrap();
+    writeln!(out).unwrap();
+    emit_runtime_builder(&mut out, server_config);
     writeln!(out, "#[derive(Debug, Parser)]").unwrap();
     writeln!(
         out,
@@ -53,46 +198,171 @@ pub fn generate_cli_source(
     )
     .unwrap();
     writeln!(out, "pub struct Cli {{").unwrap();
+    writeln!(out, "    /// Print the result as JSON").unwrap();
+    writeln!(out, "    #[arg(long, global = true)]").unwrap();
+    writeln!(out, "    pub json: bool,").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    /// Print only the result's text, without formatting").unwrap();
+    writeln!(out, "    #[arg(long, global = true, conflicts_with = \"json\")]").unwrap();
+    writeln!(out, "    pub raw: bool,").unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "    /// Seconds to wait for the call, unless the server sets timeoutMs (default: $MCPLUG_CALL_TIMEOUT or 30)"
+    )
+    .unwrap();
+    writeln!(out, "    #[arg(long, global = true, value_name = \"SECS\")]").unwrap();
+    writeln!(out, "    pub timeout: Option<u64>,").unwrap();
+    writeln!(out).unwrap();
     writeln!(out, "    #[command(subcommand)]").unwrap();
     writeln!(out, "    pub command: Commands,").unwrap();
     writeln!(out, "}}").unwrap();
     writeln!(out).unwrap();
 
-    // Generate main function
-    writeln!(out, "#[tokio::main]").unwrap();
-    writeln!(out, "async fn main() -> Result<(), Box<dyn std::error::Error>> {{").unwrap();
-    writeln!(out, "    let cli = Cli::parse();").unwrap();
+    out.push_str(MAIN);
+    out
+}
+
+/// Emit `runtime_builder()`, which starts the CLI's runtime from the user's
+/// mcplug config, or from `server_config` embedded as `SERVER_CONFIG`.
+fn emit_runtime_builder(out: &mut String, server_config: Option<&ServerConfig>) {
+    let Some(server_config) = server_config else {
+        writeln!(out, "/// Runtime options for the servers in the user's mcplug config.").unwrap();
+        writeln!(out, "fn runtime_builder() -> Result<mcplug::RuntimeBuilder, mcplug::McplugError> {{").unwrap();
+        writeln!(out, "    Ok(mcplug::Runtime::builder())").unwrap();
+        writeln!(out, "}}").unwrap();
+        writeln!(out).unwrap();
+        return;
+    };
+
+    // Through a `Value`, so `env` and `headers` are sorted rather than in
+    // hash order
+    let json = serde_json::to_value(server_config)
+        .and_then(|value| serde_json::to_string_pretty(&value))
+        .unwrap_or_default();
+    // Enough `#`s that the JSON can't end the raw string
+    let mut hashes = "#".to_string();
+    while json.contains(&format!("\"{hashes}")) {
+        hashes.push('#');
+    }
+    writeln!(out, "/// The server's config, embedded by `mcplug generate-cli --embed-config`.").unwrap();
+    writeln!(out, "/// `${{VAR}}` references are expanded each time the CLI runs.").unwrap();
+    writeln!(out, "const SERVER_CONFIG: &str = r{hashes}\"{json}\"{hashes};").unwrap();
     writeln!(out).unwrap();
+    writeln!(out, "/// Runtime options for the embedded server config, without reading any").unwrap();
+    writeln!(out, "/// mcplug config file.").unwrap();
+    writeln!(out, "fn runtime_builder() -> Result<mcplug::RuntimeBuilder, mcplug::McplugError> {{").unwrap();
     writeln!(
         out,
-        "    let config = mcplug::load_config(None)?;"
+        "    let mut server: mcplug::ServerConfig = serde_json::from_str(SERVER_CONFIG).expect(\"the embedded config is valid\");"
     )
     .unwrap();
-    writeln!(out, "    let runtime = mcplug::Runtime::new(config).await?;").unwrap();
-    writeln!(out).unwrap();
-    writeln!(out, "    let result = match cli.command {{").unwrap();
-    for tool in &filtered_tools {
-        let variant = to_pascal_case(&tool.name);
-        let args_var = to_snake_case(&tool.name);
-        writeln!(out, "        Commands::{variant}({args_var}) => {{").unwrap();
-        writeln!(
-            out,
-            "            runtime.call_tool(\"{server}\", \"{tool}\", serde_json::to_value({args_var})?).await?",
-            server = server_name,
-            tool = tool.name,
-        )
-        .unwrap();
-        writeln!(out, "        }}").unwrap();
-    }
-    writeln!(out, "    }};").unwrap();
-    writeln!(out).unwrap();
-    writeln!(out, "    println!(\"{{}}\", result.text());").unwrap();
-    writeln!(out, "    Ok(())").unwrap();
+    writeln!(out, "    mcplug::config::env::expand_server_config(&mut server)?;").unwrap();
+    writeln!(out, "    let mut config = mcplug::McplugConfig::default();").unwrap();
+    writeln!(out, "    config.mcp_servers.insert(SERVER.to_string(), server);").unwrap();
+    writeln!(out, "    Ok(mcplug::Runtime::builder().config(config))").unwrap();
     writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+}
+
+/// The Cargo package (and binary) name for a server's generated CLI.
+pub fn package_name(server_name: &str) -> String {
+    let name: String = server_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    match name.trim_matches('-') {
+        "" => "mcp-cli".to_string(),
+        name if name.starts_with(|c: char| c.is_ascii_digit()) => format!("cli-{name}"),
+        name => name.to_string(),
+    }
+}
 
+/// Generate the `Cargo.toml` of a server's CLI project, with its
+/// dependencies pinned to exact versions.
+///
+/// `mcplug` is taken from the source this binary was built from while that
+/// is still on disk, so CLIs generated by an unreleased build compile
+/// against the API they were generated for; otherwise from crates.io.
+pub fn generate_cargo_toml(server_name: &str) -> String {
+    let mut out = String::new();
+    writeln!(out, "# Auto-generated CLI by mcplug. Do not edit.").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "[package]").unwrap();
+    writeln!(out, "name = \"{}\"", package_name(server_name)).unwrap();
+    writeln!(out, "version = \"0.1.0\"").unwrap();
+    writeln!(out, "edition = \"2021\"").unwrap();
+    writeln!(out, "publish = false").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "[dependencies]").unwrap();
+    writeln!(out, "mcplug = {}", mcplug_dependency(Path::new(env!("CARGO_MANIFEST_DIR")))).unwrap();
+    for (name, spec) in PINNED_DEPENDENCIES {
+        writeln!(out, "{name} = {spec}").unwrap();
+    }
+    writeln!(out).unwrap();
+    // Keep the project out of any workspace it happens to be created in
+    writeln!(out, "[workspace]").unwrap();
     out
 }
 
+/// The dependency spec for `mcplug`: this exact version, from the crate
+/// source in `source_dir` when it has a manifest.
+fn mcplug_dependency(source_dir: &Path) -> String {
+    let version = env!("CARGO_PKG_VERSION");
+    if !source_dir.join("Cargo.toml").is_file() {
+        return format!("\"={version}\"");
+    }
+    let path = source_dir
+        .to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    format!("{{ version = \"={version}\", path = \"{path}\" }}")
+}
+
+/// Write a Cargo project for a server's CLI into `dir`: `Cargo.toml` and
+/// `src/main.rs` holding `source`.
+pub fn write_project(dir: &Path, server_name: &str, source: &str) -> Result<(), McplugError> {
+    std::fs::create_dir_all(dir.join("src"))?;
+    std::fs::write(dir.join("Cargo.toml"), generate_cargo_toml(server_name))?;
+    std::fs::write(dir.join("src").join("main.rs"), source)?;
+    Ok(())
+}
+
+/// Build the project in `dir` with `cargo build --release` and copy the
+/// binary to `out`. Cargo's progress and errors go to stderr. Uses the
+/// `cargo` named by `$CARGO`, else the one on `PATH`.
+pub fn compile_project(dir: &Path, server_name: &str, out: &Path) -> Result<PathBuf, McplugError> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let status = std::process::Command::new(&cargo)
+        .args(["build", "--release", "--manifest-path"])
+        .arg(dir.join("Cargo.toml"))
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .status()
+        .map_err(|e| {
+            McplugError::IoError(std::io::Error::new(
+                e.kind(),
+                format!("cannot run {}: {e}", cargo.to_string_lossy()),
+            ))
+        })?;
+    if !status.success() {
+        return Err(McplugError::IoError(std::io::Error::other(format!(
+            "cargo build failed ({status}); the generated project is in {}",
+            dir.display()
+        ))));
+    }
+
+    let binary = dir
+        .join("target")
+        .join("release")
+        .join(format!("{}{}", package_name(server_name), std::env::consts::EXE_SUFFIX));
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::copy(&binary, out)?;
+    Ok(out.to_path_buf())
+}
+
 fn filter_tools<'a>(
     tools: &'a [ToolDefinition],
     include: Option<&[String]>,
@@ -116,43 +386,63 @@ fn filter_tools<'a>(
         .collect()
 }
 
-fn emit_clap_args_struct(out: &mut String, name: &str, tool: &ToolDefinition) {
-    let schema = &tool.input_schema;
+/// Whether clap can't parse a value of this type from its text, so the
+/// argument is given as JSON instead.
+fn needs_json(ty: Option<&SchemaType>) -> bool {
+    match ty {
+        Some(SchemaType::Array(items)) => needs_json(items.as_deref()),
+        Some(SchemaType::String | SchemaType::Number | SchemaType::Integer | SchemaType::Boolean) => {
+            false
+        }
+        _ => true,
+    }
+}
 
+fn emit_clap_args_struct(out: &mut String, name: &str, tool: &ToolDefinition) {
     writeln!(out, "#[derive(Debug, Clone, Serialize, Deserialize, clap::Args)]").unwrap();
     writeln!(out, "pub struct {name} {{").unwrap();
 
-    let required = schema
-        .get("required")
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| v.as_str())
-                .map(String::from)
-                .collect::<Vec<_>>()
-        })
-        .unwrap_or_default();
-
-    if let Some(props) = schema.get("properties").and_then(|v| v.as_object()) {
-        for (prop_name, prop_schema) in props {
-            let field_name = sanitize_identifier(&to_snake_case(prop_name));
-            let rust_type = json_schema_to_rust_type(prop_schema);
-
-            // Add description as doc comment
-            if let Some(desc) = prop_schema.get("description").and_then(|v| v.as_str()) {
-                writeln!(out, "    /// {desc}").unwrap();
+    let schema = tool.schema();
+    let field_names = unique_identifiers(
+        schema.properties.iter().map(|p| field_identifier(&p.name)),
+        &[],
+        "_",
+    );
+    for (prop, field_name) in schema.properties.iter().zip(field_names) {
+        // A flag can't be given as null, so `T | null` takes a `T`
+        let ty = prop.ty.as_ref().map(|ty| ty.without_null().unwrap_or(ty));
+        let json = needs_json(ty);
+        let rust_type = match ty {
+            // Null-typed and union-typed arguments can only be passed as JSON
+            Some(SchemaType::Null | SchemaType::Union(_)) => "serde_json::Value".to_string(),
+            ty => rust_type(ty),
+        };
+
+        // Add description as doc comment
+        if let Some(ref desc) = prop.description {
+            for line in desc.lines() {
+                writeln!(out, "    /// {line}").unwrap();
             }
+        }
 
-            // Add serde rename if field name differs
-            if field_name != *prop_name {
-                writeln!(out, "    #[serde(rename = \"{prop_name}\")]").unwrap();
-            }
+        // Add serde rename if field name differs, and keep the schema's
+        // name for the flag
+        let mut arg = if field_name != prop.name {
+            writeln!(out, "    #[serde(rename = {:?})]", prop.name).unwrap();
+            format!("long = {:?}", prop.name)
+        } else {
+            "long".to_string()
+        };
+        if json {
+            arg.push_str(", value_parser = parse_json");
+        }
+        writeln!(out, "    #[arg({arg})]").unwrap();
 
-            if required.contains(prop_name) {
-                writeln!(out, "    pub {field_name}: {rust_type},").unwrap();
-            } else {
-                writeln!(out, "    pub {field_name}: Option<{rust_type}>,").unwrap();
-            }
+        if prop.required {
+            writeln!(out, "    pub {field_name}: {rust_type},").unwrap();
+        } else {
+            writeln!(out, "    #[serde(skip_serializing_if = \"Option::is_none\")]").unwrap();
+            writeln!(out, "    pub {field_name}: Option<{rust_type}>,").unwrap();
         }
     }
 
@@ -177,6 +467,8 @@ mod tests {
                     },
                     "required": ["location"]
                 }),
+                annotations: None,
+                output_schema: None,
             },
             ToolDefinition {
                 name: "set-alarm".to_string(),
@@ -189,6 +481,8 @@ mod tests {
                     },
                     "required": ["time"]
                 }),
+                annotations: None,
+                output_schema: None,
             },
         ]
     }
@@ -210,6 +504,57 @@ mod tests {
         assert!(output.contains("Commands::SetAlarm"));
     }
 
+    #[test]
+    fn test_generate_cli_output_flags_and_errors() {
+        let output = generate_cli_source(&sample_tools(), "my-service", None, None);
+
+        assert!(output.contains("const SERVER: &str = \"my-service\";"));
+        assert!(output.contains(
+            "Commands::GetWeather(args) => (\"get-weather\", serde_json::to_value(args)?),"
+        ));
+        assert!(output.contains("    #[arg(long, global = true)]\n    pub json: bool,"));
+        assert!(output.contains("    #[arg(long, global = true, conflicts_with = \"json\")]\n    pub raw: bool,"));
+        assert!(output.contains("    pub timeout: Option<u64>,"));
+        assert!(output.contains(".default_timeout(std::time::Duration::from_secs(timeout))"));
+        // Failed calls and error results exit 1
+        assert!(output.contains("if result.is_error {\n                std::process::exit(1);"));
+        assert!(output.contains("eprintln!(\"Error: {e}\");\n            }\n            std::process::exit(1);"));
+    }
+
+    #[test]
+    fn test_generate_cli_embedded_config() {
+        let output = generate_cli_source(&sample_tools(), "svc", None, None);
+        assert!(output.contains("    Ok(mcplug::Runtime::builder())\n"));
+        assert!(!output.contains("SERVER_CONFIG"));
+
+        let server = ServerConfig {
+            base_url: Some("https://api.example.com/mcp".into()),
+            headers: [("Authorization".to_string(), "Bearer ${API_TOKEN}".to_string())].into(),
+            // A value that would end a raw string with a single `#`
+            description: Some("quote \"# inside".into()),
+            ..Default::default()
+        };
+        let output = generate_cli_source_with_config(&sample_tools(), "svc", None, None, Some(&server));
+        assert!(output.contains("const SERVER_CONFIG: &str = r##\"{"));
+        assert!(output.contains("\"Authorization\": \"Bearer ${API_TOKEN}\""));
+        assert!(output.contains("mcplug::config::env::expand_server_config(&mut server)?;"));
+        assert!(output.contains("Ok(mcplug::Runtime::builder().config(config))"));
+
+        let start = output.find("r##\"").unwrap() + 4;
+        let end = output.find("\"##;").unwrap();
+        let embedded: ServerConfig = serde_json::from_str(&output[start..end]).unwrap();
+        assert_eq!(embedded, server);
+    }
+
+    #[test]
+    fn test_generate_cli_sorted_by_name() {
+        let mut tools = sample_tools();
+        let output = generate_cli_source(&tools, "svc", None, None);
+        tools.reverse();
+        assert_eq!(generate_cli_source(&tools, "svc", None, None), output);
+        assert!(output.find("    GetWeather(GetWeatherArgs),").unwrap() < output.find("    SetAlarm(SetAlarmArgs),").unwrap());
+    }
+
     #[test]
     fn test_generate_cli_source_include_filter() {
         let tools = sample_tools();
@@ -245,6 +590,7 @@ mod tests {
     fn test_generate_cli_source_empty_tools() {
         let output = generate_cli_source(&[], "empty", None, None);
         assert!(output.contains("pub enum Commands"));
+        assert!(output.contains("        match self {}\n"));
         assert!(output.contains("async fn main()"));
     }
 
@@ -320,9 +666,125 @@ mod tests {
                 },
                 "required": ["tags"]
             }),
+            annotations: None,
+            output_schema: None,
         }];
         let output = generate_cli_source(&tools, "svc", None, None);
         assert!(output.contains("pub tags: Vec<String>"));
         assert!(output.contains("pub ids: Option<Vec<i64>>"));
     }
+
+    #[test]
+    fn test_generate_cli_json_and_renamed_args() {
+        let tools = vec![ToolDefinition {
+            name: "query".to_string(),
+            description: "Run a query\nacross tables".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "filter": {"type": "object"},
+                    "rows": {"type": "array", "items": {"type": "object"}},
+                    "type": {"type": "string"},
+                    "maxRows": {"type": "integer"},
+                    "cursor": {"type": ["string", "null"]},
+                    "id": {"oneOf": [{"type": "string"}, {"type": "integer"}]}
+                },
+                "required": ["filter"]
+            }),
+            annotations: None,
+            output_schema: None,
+        }];
+        let output = generate_cli_source(&tools, "db", None, None);
+
+        assert!(output.contains("    /// Run a query\n    /// across tables\n"));
+        assert!(output.contains("fn parse_json("));
+        assert!(output.contains(
+            "    #[arg(long, value_parser = parse_json)]\n    pub filter: serde_json::Value,"
+        ));
+        assert!(output.contains("#[arg(long, value_parser = parse_json)]\n    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub rows: Option<Vec<serde_json::Value>>,"));
+        assert!(output.contains("#[serde(rename = \"type\")]\n    #[arg(long = \"type\")]"));
+        assert!(output.contains("#[arg(long = \"maxRows\")]"));
+        assert!(output.contains("#[arg(long)]\n    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub cursor: Option<String>,"));
+        assert!(output.contains("#[arg(long, value_parser = parse_json)]\n    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub id: Option<serde_json::Value>,"));
+        assert!(output.contains("const SERVER: &str = \"db\";"));
+        assert!(!generate_cli_source(&sample_tools(), "svc", None, None).contains("parse_json"));
+    }
+
+    #[test]
+    fn test_generate_cli_colliding_names() {
+        let tool = |name: &str, properties| ToolDefinition {
+            name: name.to_string(),
+            description: String::new(),
+            input_schema: serde_json::json!({"type": "object", "properties": properties}),
+            annotations: None,
+            output_schema: None,
+        };
+        let tools = vec![
+            tool("get-user", serde_json::json!({"fooBar": {"type": "string"}, "foo_bar": {"type": "string"}})),
+            tool("get_user", serde_json::json!({})),
+        ];
+        let output = generate_cli_source(&tools, "svc", None, None);
+
+        assert!(output.contains(
+            "    GetUser(GetUserArgs),\n    #[command(name = \"get_user\")]\n    GetUser2(GetUser2Args),"
+        ));
+        assert!(output.contains("Commands::GetUser2(args) => (\"get_user\", serde_json::to_value(args)?),"));
+        assert!(output.contains("#[arg(long = \"fooBar\")]\n    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub foo_bar: Option<String>,"));
+        assert!(output.contains("#[arg(long = \"foo_bar\")]\n    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub foo_bar_2: Option<String>,"));
+    }
+
+    #[test]
+    fn test_generate_cargo_toml_pins_dependencies() {
+        let manifest = generate_cargo_toml("my.server");
+        assert!(manifest.contains("name = \"my-server\""));
+        assert!(manifest.contains(&format!("mcplug = {{ version = \"={}\", path = ", env!("CARGO_PKG_VERSION"))));
+        assert!(manifest.contains("clap = { version = \"=4.6.1\""));
+        assert!(manifest.contains("[workspace]"));
+        assert_eq!(package_name("9lives"), "cli-9lives");
+        assert_eq!(package_name("@@"), "mcp-cli");
+    }
+
+    #[test]
+    fn test_mcplug_dependency_prefers_local_source() {
+        let version = env!("CARGO_PKG_VERSION");
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(mcplug_dependency(dir.path()), format!("\"={version}\""));
+
+        let source = dir.path().join("mc\"plug");
+        std::fs::create_dir(&source).unwrap();
+        std::fs::write(source.join("Cargo.toml"), "").unwrap();
+        assert_eq!(
+            mcplug_dependency(&source),
+            format!(
+                "{{ version = \"={version}\", path = \"{}/mc\\\"plug\" }}",
+                dir.path().display()
+            )
+        );
+    }
+
+    /// Builds a generated CLI against this checkout, as `generate-cli
+    /// --compile` does. Slow (a release build of mcplug), so run it with
+    /// `cargo test -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_compile_project_against_local_crate() {
+        let tools = vec![ToolDefinition {
+            name: "echo".into(),
+            description: "Echo the input".into(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {"input": {"type": "string"}},
+                "required": ["input"]
+            }),
+            annotations: None,
+            output_schema: None,
+        }];
+        let dir = tempfile::tempdir().unwrap();
+        write_project(dir.path(), "mock", &generate_cli_source(&tools, "mock", None, None)).unwrap();
+        let out = dir.path().join("bin").join("mock-cli");
+        let binary = compile_project(dir.path(), "mock", &out).unwrap();
+        let help = std::process::Command::new(&binary).arg("--help").output().unwrap();
+        assert!(help.status.success());
+        assert!(String::from_utf8_lossy(&help.stdout).contains("echo"));
+    }
 }
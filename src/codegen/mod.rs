@@ -1,5 +1,15 @@
+pub mod emit_openapi;
+pub mod emit_py;
 pub mod emit_rs;
+pub mod emit_ts;
 pub mod generate_cli;
+pub mod scaffold_server;
+pub mod snapshot;
 
+pub use emit_openapi::emit_openapi;
+pub use emit_py::emit_python;
 pub use emit_rs::emit_rust_types;
+pub use emit_ts::emit_typescript;
 pub use generate_cli::generate_cli_source;
+pub use scaffold_server::ServerScaffold;
+pub use snapshot::Snapshot;
@@ -0,0 +1,642 @@
+use crate::error::McplugError;
+use crate::transports::jsonrpc::PROTOCOL_VERSIONS;
+use serde_json::{json, Map, Value};
+use std::fmt::Write;
+use std::path::Path;
+
+use super::emit_rs::{field_identifier, to_snake_case, unique_identifiers};
+use super::generate_cli::{package_name, PINNED_DEPENDENCIES};
+
+/// Names the scaffold's own functions use, which tool functions must avoid.
+const RESERVED_FUNCTIONS: &[&str] = &[
+    "main", "handle", "call_tool", "run_command", "request", "base_url", "encode",
+];
+
+/// The JSON-RPC loop shared by every scaffold: reads requests from stdin
+/// and answers `initialize`, `ping`, `tools/list`, and `tools/call`.
+const SERVER_LOOP: &str = r#"fn main() {
+    let tools: Value = serde_json::from_str(TOOLS).expect("TOOLS is valid JSON");
+    let stdout = std::io::stdout();
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        let Ok(request) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        // Notifications get no reply
+        let Some(id) = request.get("id").cloned() else {
+            continue;
+        };
+        let reply = match handle(&request, &tools) {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": code, "message": message},
+            }),
+        };
+        let mut out = stdout.lock();
+        writeln!(out, "{reply}").and_then(|()| out.flush()).expect("write to stdout");
+    }
+}
+
+/// The result of one request, or a JSON-RPC error code and message.
+fn handle(request: &Value, tools: &Value) -> Result<Value, (i64, String)> {
+    let params = &request["params"];
+    match request["method"].as_str().unwrap_or_default() {
+        "initialize" => {
+            let requested = params["protocolVersion"].as_str().unwrap_or_default();
+            let version = PROTOCOL_VERSIONS
+                .iter()
+                .find(|version| **version == requested)
+                .unwrap_or(&PROTOCOL_VERSIONS[0]);
+            Ok(json!({
+                "protocolVersion": version,
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION")},
+            }))
+        }
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({"tools": tools})),
+        "tools/call" => {
+            let name = params["name"].as_str().unwrap_or_default();
+            let no_args = json!({});
+            let args = params.get("arguments").unwrap_or(&no_args);
+            match call_tool(name, args) {
+                Some(Ok(text)) => Ok(json!({"content": [{"type": "text", "text": text}]})),
+                Some(Err(message)) => Ok(json!({
+                    "content": [{"type": "text", "text": message}],
+                    "isError": true,
+                })),
+                None => Err((-32602, format!("Unknown tool: {name}"))),
+            }
+        }
+        method => Err((-32601, format!("Method not found: {method}"))),
+    }
+}
+"#;
+
+/// Runs a wrapped command for the scaffold of `--from-command`.
+const COMMAND_RUNTIME: &str = r#"/// Run `command` with the call's `args` appended, writing its `stdin` to
+/// the command. The command's stdout is the result; a failed run is a tool
+/// error carrying its stderr.
+fn run_command(command: &[&str], args: &Value) -> ToolResult {
+    let extra: Vec<&str> = match args.get("args") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| item.as_str().ok_or("args must be strings"))
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err("args must be an array of strings".to_string()),
+    };
+    let mut child = std::process::Command::new(command[0])
+        .args(&command[1..])
+        .args(&extra)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("cannot run {}: {e}", command[0]))?;
+    let input = args["stdin"].as_str().unwrap_or_default().to_string();
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    // Write from another thread, so a command that fills stdout first
+    // can't deadlock
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    let _ = writer.join();
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(format!(
+            "{} failed ({}): {}",
+            command[0],
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+"#;
+
+/// Sends HTTP requests for the scaffold of `--from-openapi`.
+const HTTP_RUNTIME: &str = r#"/// Where an operation's parameter goes in its request.
+#[derive(Clone, Copy)]
+#[allow(dead_code)] // Not every API uses every location
+enum In {
+    Path,
+    Query,
+    Header,
+}
+
+/// The API's base URL: `$API_BASE_URL`, else the OpenAPI document's first
+/// server.
+fn base_url() -> String {
+    std::env::var("API_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string())
+}
+
+/// Send `method path`, filling in its parameters from the call's arguments
+/// and sending the argument named `body`, if any, as JSON. The response
+/// body is the result; a status other than 2xx is a tool error.
+fn request(method: &str, path: &str, params: &[(&str, In)], body: Option<&str>, args: &Value) -> ToolResult {
+    let mut url = format!("{}{path}", base_url().trim_end_matches('/'));
+    let mut query = Vec::new();
+    let mut headers = Vec::new();
+    for &(name, location) in params {
+        let Some(value) = args.get(name).filter(|value| !value.is_null()) else {
+            if matches!(location, In::Path) {
+                return Err(format!("missing argument: {name}"));
+            }
+            continue;
+        };
+        let value = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        match location {
+            In::Path => url = url.replace(&format!("{{{name}}}"), &encode(&value)),
+            In::Query => query.push((name, value)),
+            In::Header => headers.push((name, value)),
+        }
+    }
+    let method = reqwest::Method::from_bytes(method.as_bytes()).map_err(|e| e.to_string())?;
+    let mut request = reqwest::blocking::Client::new().request(method, url).query(&query);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    if let Some(body) = body.and_then(|body| args.get(body)) {
+        request = request.json(body);
+    }
+    let response = request.send().map_err(|e| e.to_string())?;
+    let status = response.status();
+    let text = response.text().map_err(|e| e.to_string())?;
+    if status.is_success() {
+        Ok(text)
+    } else {
+        Err(format!("{status}: {text}"))
+    }
+}
+
+/// Percent-encode a path parameter.
+fn encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+"#;
+
+/// A generated Rust MCP server that exposes a command or an HTTP API as
+/// tools: the inverse of the client codegen. The server speaks JSON-RPC
+/// over stdio and depends only on `serde_json`, plus `reqwest` for APIs.
+#[derive(Debug, Clone)]
+pub struct ServerScaffold {
+    /// The server's name, reported in `initialize` and used for its package.
+    pub name: String,
+    tools: Vec<ScaffoldTool>,
+    backend: Backend,
+}
+
+#[derive(Debug, Clone)]
+struct ScaffoldTool {
+    name: String,
+    description: String,
+    input_schema: Value,
+    /// The Rust function implementing the tool.
+    function: String,
+    /// Doc comment lines for the function.
+    doc: Vec<String>,
+    /// The function's body, one expression.
+    body: String,
+}
+
+#[derive(Debug, Clone)]
+enum Backend {
+    Command,
+    Http { base_url: String },
+}
+
+impl ServerScaffold {
+    /// A server with one tool running `command`, split at whitespace, with
+    /// the call's `args` appended and its `stdin` written to the command.
+    /// The tool is named after the program.
+    pub fn from_command(command: &str) -> Result<Self, McplugError> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        let Some(program) = words.first() else {
+            return Err(invalid_input("--from-command needs a command to run"));
+        };
+        let program_name = Path::new(program)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| program.to_string());
+        let name = to_snake_case(&program_name);
+        let tool = ScaffoldTool {
+            description: format!("Run `{command}` with extra arguments and return its output."),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "args": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": format!("Arguments to append to `{command}`"),
+                    },
+                    "stdin": {
+                        "type": "string",
+                        "description": "Text to write to the command's standard input",
+                    },
+                },
+            }),
+            function: unique_identifiers([field_identifier(&name)], RESERVED_FUNCTIONS, "_")
+                .remove(0),
+            doc: vec![format!("Runs `{command}`.")],
+            body: format!("run_command(&{words:?}, args)"),
+            name,
+        };
+        Ok(Self {
+            name: format!("{program_name}-mcp"),
+            tools: vec![tool],
+            backend: Backend::Command,
+        })
+    }
+
+    /// A server with a tool per operation of an OpenAPI 3 document (JSON).
+    /// A tool takes the operation's path, query, and header parameters as
+    /// arguments, and its JSON request body as `body`, and is named by the
+    /// `operationId` or else by method and path. Local `$ref`s are inlined.
+    pub fn from_openapi(doc: &Value) -> Result<Self, McplugError> {
+        let Some(paths) = doc.get("paths").and_then(Value::as_object) else {
+            return Err(invalid_input("OpenAPI document has no paths"));
+        };
+
+        let mut operations = Vec::new();
+        for (path, item) in paths {
+            let item = resolve(doc, item, 0);
+            let shared = item.get("parameters").and_then(Value::as_array).cloned().unwrap_or_default();
+            for method in ["get", "put", "post", "delete", "patch", "head", "options"] {
+                if let Some(op) = item.get(method) {
+                    operations.push(openapi_tool(doc, path, method, op, &shared));
+                }
+            }
+        }
+        if operations.is_empty() {
+            return Err(invalid_input("OpenAPI document has no operations"));
+        }
+
+        let names = unique_identifiers(operations.iter().map(|t| t.name.clone()), &[], "_");
+        let functions = unique_identifiers(
+            names.iter().map(|name| field_identifier(name)),
+            RESERVED_FUNCTIONS,
+            "_",
+        );
+        for ((tool, name), function) in operations.iter_mut().zip(names).zip(functions) {
+            tool.name = name;
+            tool.function = function;
+        }
+
+        let title = doc["info"]["title"].as_str().unwrap_or("api");
+        let base_url = doc["servers"][0]["url"].as_str().unwrap_or("http://localhost");
+        Ok(Self {
+            name: format!("{}-mcp", package_name(&title.to_lowercase())),
+            tools: operations,
+            backend: Backend::Http {
+                base_url: base_url.to_string(),
+            },
+        })
+    }
+
+    /// The names of the server's tools.
+    pub fn tool_names(&self) -> Vec<&str> {
+        self.tools.iter().map(|tool| tool.name.as_str()).collect()
+    }
+
+    /// The server's `src/main.rs`.
+    pub fn main_rs(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "// MCP server scaffold generated by mcplug. Edit freely: each tool is a").unwrap();
+        writeln!(out, "// function below, and TOOLS is what `tools/list` returns.").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "use serde_json::{{json, Value}};").unwrap();
+        writeln!(out, "use std::io::{{BufRead, Write}};").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "const SERVER_NAME: &str = {:?};", self.name).unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "/// Protocol versions the server speaks, newest first.").unwrap();
+        writeln!(out, "const PROTOCOL_VERSIONS: &[&str] = &{PROTOCOL_VERSIONS:?};").unwrap();
+        if let Backend::Http { base_url } = &self.backend {
+            writeln!(out).unwrap();
+            writeln!(out, "/// Used unless `$API_BASE_URL` is set.").unwrap();
+            writeln!(out, "const DEFAULT_BASE_URL: &str = {base_url:?};").unwrap();
+        }
+        writeln!(out).unwrap();
+
+        let tools: Vec<Value> = self
+            .tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "inputSchema": tool.input_schema,
+                })
+            })
+            .collect();
+        let tools = serde_json::to_string_pretty(&tools).unwrap_or_default();
+        writeln!(out, "/// The tools listed by `tools/list`.").unwrap();
+        writeln!(out, "const TOOLS: &str = {};", raw_string(&tools)).unwrap();
+        writeln!(out).unwrap();
+
+        writeln!(out, "/// A tool's text output, or its error message.").unwrap();
+        writeln!(out, "type ToolResult = Result<String, String>;").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "/// Run the tool called `name`, or `None` if there is no such tool.").unwrap();
+        writeln!(out, "fn call_tool(name: &str, args: &Value) -> Option<ToolResult> {{").unwrap();
+        writeln!(out, "    Some(match name {{").unwrap();
+        for tool in &self.tools {
+            writeln!(out, "        {:?} => {}(args),", tool.name, tool.function).unwrap();
+        }
+        writeln!(out, "        _ => return None,").unwrap();
+        writeln!(out, "    }})").unwrap();
+        writeln!(out, "}}").unwrap();
+
+        for tool in &self.tools {
+            writeln!(out).unwrap();
+            for line in &tool.doc {
+                match line.as_str() {
+                    "" => writeln!(out, "///").unwrap(),
+                    line => writeln!(out, "/// {line}").unwrap(),
+                }
+            }
+            writeln!(out, "fn {}(args: &Value) -> ToolResult {{", tool.function).unwrap();
+            writeln!(out, "    {}", tool.body).unwrap();
+            writeln!(out, "}}").unwrap();
+        }
+
+        writeln!(out).unwrap();
+        out.push_str(SERVER_LOOP);
+        writeln!(out).unwrap();
+        out.push_str(match self.backend {
+            Backend::Command => COMMAND_RUNTIME,
+            Backend::Http { .. } => HTTP_RUNTIME,
+        });
+        out
+    }
+
+    /// The server's `Cargo.toml`, with dependencies pinned to the versions
+    /// this release of mcplug is built with.
+    pub fn cargo_toml(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "[package]").unwrap();
+        writeln!(out, "name = \"{}\"", package_name(&self.name)).unwrap();
+        writeln!(out, "version = \"0.1.0\"").unwrap();
+        writeln!(out, "edition = \"2021\"").unwrap();
+        writeln!(out, "publish = false").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "[dependencies]").unwrap();
+        for (name, spec) in PINNED_DEPENDENCIES {
+            if *name == "serde_json" {
+                writeln!(out, "{name} = {spec}").unwrap();
+            }
+        }
+        if let Backend::Http { .. } = self.backend {
+            writeln!(
+                out,
+                r#"reqwest = {{ version = "=0.13.3", features = ["blocking", "json", "query"] }}"#
+            )
+            .unwrap();
+        }
+        writeln!(out).unwrap();
+        // Keep the project out of any workspace it happens to be created in
+        writeln!(out, "[workspace]").unwrap();
+        out
+    }
+
+    /// Write the server's Cargo project into `dir`, refusing to replace an
+    /// existing project there.
+    pub fn write(&self, dir: &Path) -> Result<(), McplugError> {
+        let manifest = dir.join("Cargo.toml");
+        if manifest.exists() {
+            return Err(McplugError::IoError(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} already exists; choose another --out", manifest.display()),
+            )));
+        }
+        std::fs::create_dir_all(dir.join("src"))?;
+        std::fs::write(&manifest, self.cargo_toml())?;
+        std::fs::write(dir.join("src").join("main.rs"), self.main_rs())?;
+        Ok(())
+    }
+}
+
+/// A tool for one OpenAPI operation; its function is named later.
+fn openapi_tool(doc: &Value, path: &str, method: &str, op: &Value, shared: &[Value]) -> ScaffoldTool {
+    let op = resolve(doc, op, 0);
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    let mut params = Vec::new();
+
+    // Operation parameters override path-level ones of the same name
+    let own = op.get("parameters").and_then(Value::as_array).cloned().unwrap_or_default();
+    let own: Vec<Value> = own.iter().map(|p| resolve(doc, p, 0)).collect();
+    let inherited = shared
+        .iter()
+        .map(|p| resolve(doc, p, 0))
+        .filter(|p| !own.iter().any(|o| o["name"] == p["name"] && o["in"] == p["in"]));
+    for param in inherited.chain(own.iter().cloned()) {
+        let (Some(name), Some(location)) = (param["name"].as_str(), param["in"].as_str()) else {
+            continue;
+        };
+        let location = match location {
+            "path" => "Path",
+            "query" => "Query",
+            "header" => "Header",
+            _ => continue,
+        };
+        let mut schema = param.get("schema").cloned().unwrap_or_else(|| json!({}));
+        if let (Some(description), Some(schema)) = (param.get("description"), schema.as_object_mut()) {
+            schema.insert("description".into(), description.clone());
+        }
+        properties.insert(name.to_string(), schema);
+        if location == "Path" || param["required"] == true {
+            required.push(name.to_string());
+        }
+        params.push(format!("({name:?}, In::{location})"));
+    }
+
+    let body_schema = op
+        .get("requestBody")
+        .map(|body| resolve(doc, body, 0))
+        .and_then(|body| {
+            let schema = body["content"]["application/json"].get("schema")?.clone();
+            Some((schema, body["required"] == true))
+        });
+    let body = body_schema.map(|(schema, is_required)| {
+        let key = if properties.contains_key("body") { "requestBody" } else { "body" };
+        properties.insert(key.to_string(), schema);
+        if is_required {
+            required.push(key.to_string());
+        }
+        key
+    });
+
+    let summary = op["summary"].as_str().unwrap_or_default();
+    let details = op["description"].as_str().unwrap_or_default();
+    let description = match (summary, details) {
+        ("", "") => format!("{} {path}", method.to_uppercase()),
+        (summary, "") => summary.to_string(),
+        ("", details) => details.to_string(),
+        (summary, details) => format!("{summary}\n\n{details}"),
+    };
+    let mut doc_lines = vec![format!("{} {path}", method.to_uppercase())];
+    if !summary.is_empty() {
+        doc_lines.push(String::new());
+        doc_lines.extend(summary.lines().map(str::to_string));
+    }
+
+    let name = op["operationId"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| to_snake_case(&format!("{method} {path}")).trim_matches('_').to_string());
+    ScaffoldTool {
+        name,
+        description,
+        input_schema: json!({"type": "object", "properties": properties, "required": required}),
+        function: String::new(),
+        doc: doc_lines,
+        body: format!(
+            "request({:?}, {path:?}, &[{}], {body:?}, args)",
+            method.to_uppercase(),
+            params.join(", ")
+        ),
+    }
+}
+
+/// `value` with local `$ref`s (`#/...`) replaced by what they point to.
+/// Refs nested deeper than a few levels, as in recursive schemas, are
+/// left as they are.
+fn resolve(doc: &Value, value: &Value, depth: usize) -> Value {
+    const MAX_DEPTH: usize = 8;
+    match value {
+        Value::Object(map) => {
+            if let Some(target) = map
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|r| r.strip_prefix('#'))
+                .and_then(|pointer| doc.pointer(pointer))
+            {
+                if depth < MAX_DEPTH {
+                    return resolve(doc, target, depth + 1);
+                }
+                return value.clone();
+            }
+            Value::Object(map.iter().map(|(k, v)| (k.clone(), resolve(doc, v, depth))).collect())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|v| resolve(doc, v, depth)).collect()),
+        other => other.clone(),
+    }
+}
+
+/// `text` as a Rust raw string literal, with enough `#`s to hold it.
+fn raw_string(text: &str) -> String {
+    let mut hashes = String::from("#");
+    while text.contains(&format!("\"{hashes}")) {
+        hashes.push('#');
+    }
+    format!("r{hashes}\"{text}\"{hashes}")
+}
+
+fn invalid_input(message: &str) -> McplugError {
+    McplugError::IoError(std::io::Error::new(std::io::ErrorKind::InvalidInput, message.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_scaffold_wraps_the_command() {
+        let scaffold = ServerScaffold::from_command("/usr/bin/my-tool --json").unwrap();
+        assert_eq!(scaffold.name, "my-tool-mcp");
+        assert_eq!(scaffold.tool_names(), ["my_tool"]);
+
+        let main = scaffold.main_rs();
+        assert!(main.contains("const SERVER_NAME: &str = \"my-tool-mcp\";"));
+        assert!(main.contains("\"my_tool\" => my_tool(args),"));
+        assert!(main.contains("fn my_tool(args: &Value) -> ToolResult {\n    run_command(&[\"/usr/bin/my-tool\", \"--json\"], args)\n}"));
+        assert!(main.contains("fn run_command("));
+        assert!(!main.contains("fn request("));
+        assert!(main.contains("\"inputSchema\""));
+
+        let manifest = scaffold.cargo_toml();
+        assert!(manifest.contains("name = \"my-tool-mcp\""));
+        assert!(manifest.contains("serde_json = \"=1.0.149\""));
+        assert!(!manifest.contains("reqwest"));
+        assert!(ServerScaffold::from_command("  ").is_err());
+        // A tool function never shadows the scaffold's own
+        let main = ServerScaffold::from_command("request").unwrap().main_rs();
+        assert!(main.contains("\"request\" => request_2(args),"));
+    }
+
+    #[test]
+    fn openapi_scaffold_has_a_tool_per_operation() {
+        let doc = json!({
+            "openapi": "3.0.3",
+            "info": {"title": "Pet Store", "version": "1.0.0"},
+            "servers": [{"url": "https://pets.example.com/v1"}],
+            "paths": {
+                "/pets/{petId}": {
+                    "parameters": [{"$ref": "#/components/parameters/PetId"}],
+                    "get": {"operationId": "showPetById", "summary": "Info for a pet"},
+                    "delete": {}
+                },
+                "/pets": {
+                    "post": {
+                        "operationId": "createPet",
+                        "parameters": [{"name": "X-Trace", "in": "header", "schema": {"type": "string"}}],
+                        "requestBody": {
+                            "required": true,
+                            "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Pet"}}}
+                        }
+                    }
+                }
+            },
+            "components": {
+                "parameters": {
+                    "PetId": {"name": "petId", "in": "path", "required": true, "description": "The pet", "schema": {"type": "string"}}
+                },
+                "schemas": {
+                    "Pet": {"type": "object", "properties": {"name": {"type": "string"}}}
+                }
+            }
+        });
+        let scaffold = ServerScaffold::from_openapi(&doc).unwrap();
+        assert_eq!(scaffold.name, "pet-store-mcp");
+        assert_eq!(scaffold.tool_names(), ["createPet", "showPetById", "delete_pets_pet_id"]);
+
+        let tools: Vec<Value> = scaffold
+            .tools
+            .iter()
+            .map(|t| json!({"name": t.name, "description": t.description, "schema": t.input_schema}))
+            .collect();
+        assert_eq!(tools[0]["schema"]["properties"]["body"]["properties"]["name"]["type"], "string");
+        assert_eq!(tools[0]["schema"]["required"], json!(["body"]));
+        assert_eq!(tools[1]["description"], "Info for a pet");
+        assert_eq!(tools[1]["schema"]["properties"]["petId"]["description"], "The pet");
+        assert_eq!(tools[1]["schema"]["required"], json!(["petId"]));
+        assert_eq!(tools[2]["description"], "DELETE /pets/{petId}");
+
+        let main = scaffold.main_rs();
+        assert!(main.contains("const DEFAULT_BASE_URL: &str = \"https://pets.example.com/v1\";"));
+        assert!(main.contains("/// GET /pets/{petId}\n///\n/// Info for a pet\nfn show_pet_by_id(args: &Value) -> ToolResult {\n    request(\"GET\", \"/pets/{petId}\", &[(\"petId\", In::Path)], None, args)\n}"));
+        assert!(main.contains("request(\"POST\", \"/pets\", &[(\"X-Trace\", In::Header)], Some(\"body\"), args)"));
+        assert!(scaffold.cargo_toml().contains("reqwest"));
+        assert!(ServerScaffold::from_openapi(&json!({"paths": {}})).is_err());
+    }
+
+    #[test]
+    fn raw_string_outgrows_its_contents() {
+        assert_eq!(raw_string("{}"), "r#\"{}\"#");
+        assert_eq!(raw_string("\"#"), "r##\"\"#\"##");
+    }
+}
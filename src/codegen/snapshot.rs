@@ -0,0 +1,96 @@
+use crate::error::McplugError;
+use crate::runtime::Runtime;
+use crate::types::{ServerInfo, ToolDefinition};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A server's tool catalog saved to a file, so code can be generated from
+/// it without connecting to the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    /// The server's name in the config it was captured from.
+    pub server: String,
+    /// What the server reported about itself when captured.
+    pub server_info: ServerInfo,
+    pub tools: Vec<ToolDefinition>,
+}
+
+impl Snapshot {
+    /// Capture `server`'s info and tools through `runtime`.
+    pub async fn capture(runtime: &Runtime, server: &str) -> Result<Self, McplugError> {
+        let tools = runtime.list_tools(server).await?;
+        let server_info = runtime.server_info(server).await?;
+        Ok(Self {
+            server: server.to_string(),
+            server_info,
+            tools,
+        })
+    }
+
+    /// Read a snapshot written by [`Snapshot::save`].
+    pub fn load(path: &Path) -> Result<Self, McplugError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            McplugError::IoError(std::io::Error::new(
+                e.kind(),
+                format!("cannot read snapshot {}: {e}", path.display()),
+            ))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            McplugError::IoError(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid snapshot {}: {e}", path.display()),
+            ))
+        })
+    }
+
+    /// The snapshot as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Write the snapshot to `path` as JSON.
+    pub fn save(&self, path: &Path) -> Result<(), McplugError> {
+        std::fs::write(path, self.to_json() + "\n")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_round_trips_through_a_file() {
+        let snapshot = Snapshot {
+            server: "weather".into(),
+            server_info: ServerInfo {
+                name: "weather-server".into(),
+                version: "1.4.0".into(),
+                capabilities: serde_json::json!({"tools": {}}),
+                protocol_version: Some("2025-03-26".into()),
+            },
+            tools: vec![ToolDefinition {
+                name: "forecast".into(),
+                description: "Get a forecast".into(),
+                input_schema: serde_json::json!({"type": "object"}),
+                annotations: None,
+                output_schema: Some(serde_json::json!({"type": "object"})),
+            }],
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("weather.json");
+        snapshot.save(&path).unwrap();
+
+        let loaded = Snapshot::load(&path).unwrap();
+        assert_eq!(loaded.server, "weather");
+        assert_eq!(loaded.server_info.version, "1.4.0");
+        assert_eq!(loaded.tools.len(), 1);
+        assert_eq!(loaded.tools[0].output_schema, snapshot.tools[0].output_schema);
+
+        std::fs::write(&path, "{\"tools\": []}").unwrap();
+        let err = Snapshot::load(&path).unwrap_err().to_string();
+        assert!(err.contains("invalid snapshot"), "{err}");
+        assert!(Snapshot::load(&dir.path().join("missing.json")).is_err());
+    }
+}
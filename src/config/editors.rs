@@ -1,12 +1,19 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
 
 use super::loader::strip_jsonc_comments;
-use super::types::ServerConfig;
+use super::types::{AuthConfig, ServerConfig};
+use super::yaml;
+
+/// Editors `config export` can produce a config file for.
+pub const EXPORT_EDITORS: &[&str] = &["cursor", "claude-desktop", "vscode"];
 
 /// Import MCP server configs from editor configuration files.
 ///
-/// Supported editors: cursor, claude-desktop, claude-code, vscode, windsurf, codex, opencode.
+/// Supported editors: cursor, claude-desktop, claude-code, vscode, windsurf, codex, opencode,
+/// zed, cline, roo, continue, goose, gemini-cli.
 /// Returns a map of server name -> ServerConfig for all successfully parsed entries.
 /// Silently skips editors whose config files don't exist or can't be parsed.
 pub fn import_editor_configs(imports: &[String]) -> HashMap<String, ServerConfig> {
@@ -19,20 +26,9 @@ pub fn import_editor_configs(imports: &[String]) -> HashMap<String, ServerConfig
                 continue;
             }
             if let Ok(content) = std::fs::read_to_string(&path) {
-                let stripped = strip_jsonc_comments(&content);
-                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&stripped) {
-                    if let Some(mcp_servers) = parsed.get("mcpServers") {
-                        if let Ok(editor_servers) =
-                            serde_json::from_value::<HashMap<String, ServerConfig>>(
-                                mcp_servers.clone(),
-                            )
-                        {
-                            for (name, config) in editor_servers {
-                                // Don't override: earlier sources win
-                                servers.entry(name).or_insert(config);
-                            }
-                        }
-                    }
+                for (name, config) in parse_editor_config(editor, &path, &content) {
+                    // Don't override: earlier sources win
+                    servers.entry(name).or_insert(config);
                 }
             }
         }
@@ -41,6 +37,194 @@ pub fn import_editor_configs(imports: &[String]) -> HashMap<String, ServerConfig
     servers
 }
 
+/// Parse one editor config file into servers.
+///
+/// Most editors keep a `mcpServers` map; Zed uses `context_servers` and
+/// Goose `extensions` (YAML), and Continue lists servers with a `name` in
+/// its YAML files. Entries mcplug can't use are skipped.
+fn parse_editor_config(editor: &str, path: &Path, content: &str) -> Vec<(String, ServerConfig)> {
+    let is_yaml = path
+        .extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml");
+    let parsed = if is_yaml {
+        yaml::parse(content)
+    } else {
+        serde_json::from_str::<Value>(&strip_jsonc_comments(content)).ok()
+    };
+    let key = match editor {
+        "zed" => "context_servers",
+        "goose" => "extensions",
+        // VS Code's own spelling, as written by `config export`
+        "vscode" if parsed.as_ref().is_some_and(|p| p.get("mcpServers").is_none()) => {
+            "servers"
+        }
+        _ => "mcpServers",
+    };
+    let entries: Vec<(String, &Value)> = match parsed.as_ref().and_then(|p| p.get(key)) {
+        Some(Value::Object(map)) => map.iter().map(|(name, v)| (name.clone(), v)).collect(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| Some((v.get("name")?.as_str()?.to_string(), v)))
+            .collect(),
+        _ => Vec::new(),
+    };
+    entries
+        .into_iter()
+        .filter_map(|(name, entry)| Some((name, editor_server(entry)?)))
+        .collect()
+}
+
+/// Servers parsed from a pasted snippet by `parse_server_snippet`.
+#[derive(Debug)]
+pub struct ServerSnippet {
+    /// Usable servers, by name.
+    pub servers: Vec<(String, ServerConfig)>,
+    /// Names of entries with neither a command nor a URL.
+    pub invalid: Vec<String>,
+}
+
+/// Parse a pasted server snippet, such as the JSON in a server's README:
+/// `{"mcpServers": {...}}`, VS Code's `{"servers": {...}}`, or a bare map
+/// of server names to entries, in any editor's key spelling.
+///
+pub fn parse_server_snippet(content: &str) -> Result<ServerSnippet, String> {
+    let doc: Value = serde_json::from_str(&strip_jsonc_comments(content))
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+    let map = ["mcpServers", "servers"]
+        .iter()
+        .find_map(|key| doc.get(key))
+        .unwrap_or(&doc)
+        .as_object()
+        .ok_or("Expected an object of servers")?;
+    let mut servers = Vec::new();
+    let mut invalid = Vec::new();
+    for (name, entry) in map {
+        match editor_server(entry) {
+            Some(config) => servers.push((name.clone(), config)),
+            None => invalid.push(name.clone()),
+        }
+    }
+    Ok(ServerSnippet { servers, invalid })
+}
+
+/// Convert one editor server entry into a `ServerConfig`, accepting the
+/// key spellings editors use: `url`/`httpUrl`/`serverUrl`/`uri` for the
+/// base URL, Goose's `cmd`, `envs` and `enabled`, and Zed's nested
+/// `command: {path, args, env}`. Editors' own `type` values are dropped;
+/// the transport follows from `command` or the URL.
+fn editor_server(entry: &Value) -> Option<ServerConfig> {
+    let mut obj = entry.as_object()?.clone();
+    obj.remove("type");
+    if let Some(Value::Object(command)) = obj.get("command").cloned() {
+        obj.remove("command");
+        for (from, to) in [("path", "command"), ("args", "args"), ("env", "env")] {
+            if let Some(value) = command.get(from) {
+                obj.insert(to.to_string(), value.clone());
+            }
+        }
+    }
+    for (alias, key) in [
+        ("cmd", "command"),
+        ("envs", "env"),
+        ("url", "baseUrl"),
+        ("httpUrl", "baseUrl"),
+        ("serverUrl", "baseUrl"),
+        ("uri", "baseUrl"),
+    ] {
+        if !obj.contains_key(key) {
+            if let Some(value) = obj.remove(alias) {
+                obj.insert(key.to_string(), value);
+            }
+        }
+    }
+    if obj.get("enabled") == Some(&Value::Bool(false)) {
+        obj.insert("disabled".to_string(), Value::Bool(true));
+    }
+    let config: ServerConfig = serde_json::from_value(Value::Object(obj)).ok()?;
+    (config.command.is_some() || config.base_url.is_some()).then_some(config)
+}
+
+/// The key holding the server map in `editor`'s config file.
+pub fn export_key(editor: &str) -> &'static str {
+    match editor {
+        "vscode" => "servers",
+        _ => "mcpServers",
+    }
+}
+
+/// The config file `config export --write` updates for `editor`.
+pub fn export_path(editor: &str) -> Option<PathBuf> {
+    editor_config_paths(editor).into_iter().next()
+}
+
+/// Convert servers into `editor`'s server map, the inverse of import.
+///
+/// Disabled servers are left out. Returns the map and the names of servers
+/// the editor can't run (HTTP servers for Claude Desktop, which only
+/// launches stdio servers). Values are copied as written, so `${VAR}`
+/// references are not expanded into the editor's file.
+pub fn export_editor_servers(
+    editor: &str,
+    servers: &HashMap<String, ServerConfig>,
+) -> (serde_json::Map<String, Value>, Vec<String>) {
+    let mut names: Vec<&String> = servers.keys().collect();
+    names.sort();
+    let mut exported = serde_json::Map::new();
+    let mut skipped = Vec::new();
+    for name in names {
+        let config = &servers[name];
+        if config.disabled {
+            continue;
+        }
+        match export_server(editor, config) {
+            Some(entry) => {
+                exported.insert(name.clone(), entry);
+            }
+            None => skipped.push(name.clone()),
+        }
+    }
+    (exported, skipped)
+}
+
+/// One server in an editor's format: `command`/`args`/`env` for stdio and
+/// `url`/`headers` for HTTP, with VS Code's `type` field. A bearer `auth`
+/// token becomes an `Authorization` header.
+fn export_server(editor: &str, config: &ServerConfig) -> Option<Value> {
+    let mut entry = serde_json::Map::new();
+    if let Some(ref command) = config.command {
+        if editor == "vscode" {
+            entry.insert("type".into(), "stdio".into());
+        }
+        entry.insert("command".into(), command.clone().into());
+        if !config.args.is_empty() {
+            entry.insert("args".into(), serde_json::json!(config.args));
+        }
+        if !config.env.is_empty() {
+            entry.insert("env".into(), serde_json::json!(config.env));
+        }
+    } else if let Some(ref url) = config.base_url {
+        if editor == "claude-desktop" {
+            return None;
+        }
+        if editor == "vscode" {
+            entry.insert("type".into(), "http".into());
+        }
+        entry.insert("url".into(), url.clone().into());
+        let mut headers = config.headers.clone();
+        if let Some(AuthConfig::Bearer { ref token }) = config.auth {
+            headers
+                .entry("Authorization".into())
+                .or_insert_with(|| format!("Bearer {token}"));
+        }
+        if !headers.is_empty() {
+            entry.insert("headers".into(), serde_json::json!(headers));
+        }
+    } else {
+        return None;
+    }
+    Some(Value::Object(entry))
+}
+
 /// Return the config file paths for a given editor name.
 fn editor_config_paths(editor: &str) -> Vec<PathBuf> {
     let home = match dirs::home_dir() {
@@ -80,10 +264,64 @@ fn editor_config_paths(editor: &str) -> Vec<PathBuf> {
         "windsurf" => vec![home.join(".windsurf").join("mcp.json")],
         "codex" => vec![home.join(".codex").join("mcp.json")],
         "opencode" => vec![home.join(".opencode").join("mcp.json")],
+        "zed" => {
+            if cfg!(target_os = "windows") {
+                dirs::config_dir()
+                    .map(|c| vec![c.join("Zed").join("settings.json")])
+                    .unwrap_or_default()
+            } else {
+                vec![home.join(".config").join("zed").join("settings.json")]
+            }
+        }
+        "cline" => vscode_global_storage("saoudrizwan.claude-dev", "cline_mcp_settings.json"),
+        "roo" => vscode_global_storage("rooveterinaryinc.roo-cline", "mcp_settings.json"),
+        "continue" => {
+            let dir = home.join(".continue");
+            let mut paths = vec![dir.join("config.yaml")];
+            // One block per file, in name order
+            if let Ok(entries) = std::fs::read_dir(dir.join("mcpServers")) {
+                let mut blocks: Vec<PathBuf> = entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| {
+                        p.extension()
+                            .is_some_and(|ext| ext == "yaml" || ext == "yml" || ext == "json")
+                    })
+                    .collect();
+                blocks.sort();
+                paths.extend(blocks);
+            }
+            paths
+        }
+        "goose" => {
+            if cfg!(target_os = "windows") {
+                dirs::config_dir()
+                    .map(|c| vec![c.join("Block").join("goose").join("config").join("config.yaml")])
+                    .unwrap_or_default()
+            } else {
+                vec![home.join(".config").join("goose").join("config.yaml")]
+            }
+        }
+        "gemini-cli" => vec![home.join(".gemini").join("settings.json")],
         _ => vec![],
     }
 }
 
+/// A settings file kept by a VS Code extension (Cline, Roo Code) in the
+/// editor's global storage.
+fn vscode_global_storage(extension: &str, file: &str) -> Vec<PathBuf> {
+    dirs::config_dir()
+        .map(|c| {
+            vec![c
+                .join("Code")
+                .join("User")
+                .join("globalStorage")
+                .join(extension)
+                .join("settings")
+                .join(file)]
+        })
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -137,6 +375,189 @@ mod tests {
         assert!(!paths.is_empty() || cfg!(not(target_os = "macos")));
     }
 
+    #[test]
+    fn editor_config_paths_new_editors() {
+        if !cfg!(target_os = "windows") {
+            assert!(editor_config_paths("zed")[0].ends_with(".config/zed/settings.json"));
+            assert!(editor_config_paths("goose")[0].ends_with(".config/goose/config.yaml"));
+        }
+        assert!(editor_config_paths("gemini-cli")[0].ends_with(".gemini/settings.json"));
+        assert!(editor_config_paths("continue")[0].ends_with(".continue/config.yaml"));
+        assert!(editor_config_paths("cline")[0]
+            .ends_with("saoudrizwan.claude-dev/settings/cline_mcp_settings.json"));
+        assert!(editor_config_paths("roo")[0]
+            .ends_with("rooveterinaryinc.roo-cline/settings/mcp_settings.json"));
+    }
+
+    fn names(servers: &[(String, ServerConfig)]) -> Vec<&str> {
+        servers.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    #[test]
+    fn parse_zed_context_servers() {
+        let settings = r#"{
+            // Zed settings are JSONC
+            "theme": "One Dark",
+            "context_servers": {
+                "nested": {"command": {"path": "node", "args": ["srv.js"], "env": {"K": "v"}}, "settings": {}},
+                "flat": {"command": "uvx", "args": ["mcp-server-git"]},
+                "remote": {"url": "https://example.com/mcp"}
+            }
+        }"#;
+        let servers = parse_editor_config("zed", Path::new("settings.json"), settings);
+        assert_eq!(names(&servers), vec!["flat", "nested", "remote"]);
+        let nested = &servers[1].1;
+        assert_eq!(nested.command.as_deref(), Some("node"));
+        assert_eq!(nested.args, vec!["srv.js"]);
+        assert_eq!(nested.env["K"], "v");
+        assert_eq!(servers[2].1.base_url.as_deref(), Some("https://example.com/mcp"));
+    }
+
+    #[test]
+    fn parse_cline_and_gemini_mcp_servers() {
+        let cline = r#"{"mcpServers": {
+            "fs": {"command": "npx", "args": ["-y", "fs"], "disabled": true, "autoApprove": []},
+            "broken": {"args": ["no command"]}
+        }}"#;
+        let servers = parse_editor_config("cline", Path::new("cline_mcp_settings.json"), cline);
+        assert_eq!(names(&servers), vec!["fs"]);
+        assert!(servers[0].1.disabled);
+
+        let gemini = r#"{"mcpServers": {"api": {"httpUrl": "https://api.example.com/mcp", "headers": {"X-Key": "k"}}}}"#;
+        let servers = parse_editor_config("gemini-cli", Path::new("settings.json"), gemini);
+        assert_eq!(servers[0].1.base_url.as_deref(), Some("https://api.example.com/mcp"));
+        assert_eq!(servers[0].1.headers["X-Key"], "k");
+    }
+
+    #[test]
+    fn parse_goose_extensions_yaml() {
+        let config = r#"
+GOOSE_PROVIDER: openai
+extensions:
+  developer:
+    type: builtin
+    enabled: true
+  github:
+    cmd: npx
+    args:
+    - -y
+    - '@modelcontextprotocol/server-github'
+    envs:
+      GITHUB_TOKEN: ghp_x
+    enabled: false
+    type: stdio
+  remote:
+    type: sse
+    uri: https://example.com/sse
+    enabled: true
+"#;
+        let servers = parse_editor_config("goose", Path::new("config.yaml"), config);
+        assert_eq!(names(&servers), vec!["github", "remote"]);
+        let github = &servers[0].1;
+        assert_eq!(github.command.as_deref(), Some("npx"));
+        assert_eq!(github.args, vec!["-y", "@modelcontextprotocol/server-github"]);
+        assert_eq!(github.env["GITHUB_TOKEN"], "ghp_x");
+        assert!(github.disabled);
+        assert_eq!(servers[1].1.base_url.as_deref(), Some("https://example.com/sse"));
+    }
+
+    #[test]
+    fn parse_continue_yaml_list() {
+        let config = "name: my-assistant
+mcpServers:
+  - name: sqlite
+    command: uvx
+    args:
+      - mcp-server-sqlite
+  - command: unnamed
+";
+        let servers = parse_editor_config("continue", Path::new("config.yaml"), config);
+        assert_eq!(names(&servers), vec!["sqlite"]);
+        assert_eq!(servers[0].1.args, vec!["mcp-server-sqlite"]);
+    }
+
+    fn server(value: Value) -> ServerConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn export_formats_per_editor() {
+        let servers = HashMap::from([
+            (
+                "fs".to_string(),
+                server(serde_json::json!({"command": "npx", "args": ["-y", "fs"], "env": {"ROOT": "${HOME}"}})),
+            ),
+            (
+                "api".to_string(),
+                server(serde_json::json!({
+                    "baseUrl": "https://api.example.com/mcp",
+                    "auth": {"type": "bearer", "token": "${API_TOKEN}"}
+                })),
+            ),
+            (
+                "off".to_string(),
+                server(serde_json::json!({"command": "x", "disabled": true})),
+            ),
+        ]);
+
+        let (cursor, skipped) = export_editor_servers("cursor", &servers);
+        assert!(skipped.is_empty());
+        assert_eq!(
+            Value::Object(cursor),
+            serde_json::json!({
+                "api": {
+                    "url": "https://api.example.com/mcp",
+                    "headers": {"Authorization": "Bearer ${API_TOKEN}"}
+                },
+                "fs": {"command": "npx", "args": ["-y", "fs"], "env": {"ROOT": "${HOME}"}}
+            })
+        );
+
+        let (vscode, _) = export_editor_servers("vscode", &servers);
+        assert_eq!(vscode["fs"]["type"], "stdio");
+        assert_eq!(vscode["api"]["type"], "http");
+
+        let (desktop, skipped) = export_editor_servers("claude-desktop", &servers);
+        assert_eq!(desktop.keys().collect::<Vec<_>>(), vec!["fs"]);
+        assert_eq!(skipped, vec!["api"]);
+    }
+
+    #[test]
+    fn export_round_trips_through_import() {
+        let servers = HashMap::from([(
+            "api".to_string(),
+            server(serde_json::json!({"baseUrl": "https://api.example.com/mcp", "headers": {"X-Key": "k"}})),
+        )]);
+        for editor in EXPORT_EDITORS.iter().filter(|e| **e != "claude-desktop") {
+            let (map, _) = export_editor_servers(editor, &servers);
+            let doc = serde_json::json!({ export_key(editor): map }).to_string();
+            let imported = parse_editor_config(editor, Path::new("mcp.json"), &doc);
+            assert_eq!(imported, vec![("api".to_string(), servers["api"].clone())]);
+        }
+    }
+
+    #[test]
+    fn parse_server_snippet_shapes() {
+        let wrapped = r#"{"mcpServers": {"fs": {"command": "npx", "args": ["fs"]}, "bad": {"args": []}}}"#;
+        let snippet = parse_server_snippet(wrapped).unwrap();
+        assert_eq!(names(&snippet.servers), vec!["fs"]);
+        assert_eq!(snippet.invalid, vec!["bad"]);
+
+        let vscode = r#"{"servers": {"api": {"type": "http", "url": "https://example.com/mcp"}}}"#;
+        let servers = parse_server_snippet(vscode).unwrap().servers;
+        assert_eq!(servers[0].1.base_url.as_deref(), Some("https://example.com/mcp"));
+
+        let bare = r#"{
+            // copied from a README
+            "git": {"command": "uvx", "args": ["mcp-server-git"]}
+        }"#;
+        let servers = parse_server_snippet(bare).unwrap().servers;
+        assert_eq!(names(&servers), vec!["git"]);
+
+        assert!(parse_server_snippet("[1, 2]").is_err());
+        assert!(parse_server_snippet("{not json").is_err());
+    }
+
     #[test]
     fn editor_config_paths_unknown() {
         let paths = editor_config_paths("unknown-editor");
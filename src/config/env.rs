@@ -1,8 +1,10 @@
 use std::collections::HashMap;
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
 
 use crate::error::McplugError;
 
-use super::types::ServerConfig;
+use super::types::{AuthConfig, ServerConfig};
 
 /// Expand environment variable references in a string.
 ///
@@ -10,6 +12,12 @@ use super::types::ServerConfig;
 /// - `${VAR}` - replaced with env var value; error if unset
 /// - `${VAR:-fallback}` - replaced with env var value, or fallback if unset
 /// - `$env:VAR` - same as `${VAR}`
+/// - `${keychain:service/account}` - password from the OS keychain
+/// - `${cmd:command}` - trimmed stdout of a shell command (e.g. `op read ...`)
+/// - `${file:path}` - trimmed contents of a file (e.g. a mounted secret)
+///
+/// Braces inside a reference nest, so `${cmd:awk '{print $1}' f}` ends at
+/// its last `}`; write `\{` and `\}` for braces that don't pair up.
 pub fn expand_env_vars(input: &str) -> Result<String, McplugError> {
     let mut result = String::with_capacity(input.len());
     let mut chars = input.chars().peekable();
@@ -23,20 +31,20 @@ pub fn expand_env_vars(input: &str) -> Result<String, McplugError> {
         // Check for ${VAR} or ${VAR:-fallback}
         if chars.peek() == Some(&'{') {
             chars.next(); // consume '{'
-            let mut var_expr = String::new();
-            let mut found_close = false;
-            for c in chars.by_ref() {
-                if c == '}' {
-                    found_close = true;
-                    break;
-                }
-                var_expr.push(c);
-            }
-            if !found_close {
+            let rest: String = chars.clone().collect();
+            let Some((var_expr, len)) = reference_body(&rest) else {
                 return Err(env_error(&format!(
                     "Unclosed variable reference: ${{{}",
-                    var_expr
+                    rest
                 )));
+            };
+            for _ in rest[..len].chars() {
+                chars.next();
+            }
+
+            if let Some(secret) = resolve_secret(&var_expr) {
+                result.push_str(&secret?);
+                continue;
             }
 
             // Check for :-fallback syntax
@@ -105,31 +113,260 @@ pub fn expand_env_vars(input: &str) -> Result<String, McplugError> {
 }
 
 /// Expand environment variables in all string fields of a ServerConfig.
+///
+/// Values that reference a secret (`${keychain:...}`, `${cmd:...}`, or
+/// `${file:...}`) are left as written, so loading a config never runs a
+/// command or reads the keychain; `resolve_secrets` expands them once the
+/// server is connected.
 pub fn expand_server_config(config: &mut ServerConfig) -> Result<(), McplugError> {
+    map_strings(config, |value| {
+        if has_secret_ref(value) {
+            Ok(value.to_string())
+        } else {
+            expand_env_vars(value)
+        }
+    })
+}
+
+/// A copy of a server's config with the values left unexpanded by
+/// `expand_server_config` (those referencing secrets) expanded.
+pub fn resolve_secrets(config: &ServerConfig) -> Result<ServerConfig, McplugError> {
+    let mut resolved = config.clone();
+    map_strings(&mut resolved, |value| {
+        if has_secret_ref(value) {
+            expand_env_vars(value)
+        } else {
+            Ok(value.to_string())
+        }
+    })?;
+    Ok(resolved)
+}
+
+/// Whether any of a server's values references a secret.
+pub fn references_secrets(config: &ServerConfig) -> bool {
+    let mut found = false;
+    let _ = map_strings(&mut config.clone(), |value| {
+        found |= has_secret_ref(value);
+        Ok(value.to_string())
+    });
+    found
+}
+
+/// Replace every expandable string field of a ServerConfig with `f` of it.
+fn map_strings(
+    config: &mut ServerConfig,
+    mut f: impl FnMut(&str) -> Result<String, McplugError>,
+) -> Result<(), McplugError> {
     if let Some(ref mut url) = config.base_url {
-        *url = expand_env_vars(url)?;
+        *url = f(url)?;
     }
     if let Some(ref mut cmd) = config.command {
-        *cmd = expand_env_vars(cmd)?;
+        *cmd = f(cmd)?;
     }
     for arg in &mut config.args {
-        *arg = expand_env_vars(arg)?;
-    }
-    let expanded_env: HashMap<String, String> = config
-        .env
-        .iter()
-        .map(|(k, v)| Ok((k.clone(), expand_env_vars(v)?)))
-        .collect::<Result<_, McplugError>>()?;
-    config.env = expanded_env;
-    let expanded_headers: HashMap<String, String> = config
-        .headers
-        .iter()
-        .map(|(k, v)| Ok((k.clone(), expand_env_vars(v)?)))
-        .collect::<Result<_, McplugError>>()?;
-    config.headers = expanded_headers;
+        *arg = f(arg)?;
+    }
+    for value in config.env.values_mut().chain(config.headers.values_mut()) {
+        *value = f(value)?;
+    }
+    match config.auth {
+        Some(AuthConfig::Bearer { ref mut token }) => *token = f(token)?,
+        Some(AuthConfig::OAuth {
+            ref mut client_id,
+            ref mut scopes,
+            ..
+        }) => {
+            if let Some(id) = client_id {
+                *id = f(id)?;
+            }
+            for scope in scopes {
+                *scope = f(scope)?;
+            }
+        }
+        None => {}
+    }
     Ok(())
 }
 
+/// Whether `input` has a `${scheme:...}` secret reference.
+fn has_secret_ref(input: &str) -> bool {
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        rest = &rest[start + 2..];
+        let Some((expr, len)) = reference_body(rest) else {
+            return false;
+        };
+        if secret_scheme(&expr).is_some() {
+            return true;
+        }
+        rest = &rest[len..];
+    }
+    false
+}
+
+/// The body of a `${...}` reference, given the text after `${`, with `\{`
+/// and `\}` unescaped, and the length of text it spans up to and including
+/// the closing `}`. Braces nest; escaped braces don't count. `None` if the
+/// reference is never closed.
+fn reference_body(rest: &str) -> Option<(String, usize)> {
+    let mut body = String::new();
+    let mut depth = 0usize;
+    let mut chars = rest.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if matches!(chars.peek(), Some((_, '{' | '}'))) => {
+                if let Some((_, brace)) = chars.next() {
+                    body.push(brace);
+                }
+                continue;
+            }
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some((body, i + 1)),
+            '}' => depth -= 1,
+            _ => {}
+        }
+        body.push(c);
+    }
+    None
+}
+
+/// The scheme and spec of a `${scheme:spec}` secret reference's `expr`.
+fn secret_scheme(expr: &str) -> Option<(&str, &str)> {
+    let (scheme, spec) = expr.split_once(':')?;
+    // `${cmd:-fallback}` is the fallback syntax for a variable named `cmd`
+    if !matches!(scheme, "keychain" | "cmd" | "file") || spec.starts_with('-') {
+        return None;
+    }
+    Some((scheme, spec))
+}
+
+/// Resolve a `${scheme:...}` secret reference, or `None` if `expr` has no
+/// secret scheme. Keychain and command results are cached for the life of
+/// the process, so each is read once however often it is referenced; files
+/// are cheap to read and are read each time.
+fn resolve_secret(expr: &str) -> Option<Result<String, McplugError>> {
+    let (scheme, spec) = secret_scheme(expr)?;
+    if scheme == "file" {
+        return Some(read_secret_file(spec));
+    }
+
+    static CACHE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(value) = cache.lock().unwrap().get(expr) {
+        return Some(Ok(value.clone()));
+    }
+    let value = match scheme {
+        "keychain" => read_keychain(spec),
+        // An unpaired `}` in a quoted string ends the reference early; don't
+        // run what is left of the command
+        _ if !cfg!(windows) && has_open_quote(spec) => Err(env_error(&format!(
+            "Command '{spec}' has an unclosed quote. If it contains a `}}` without a \
+             matching `{{`, write it as `\\}}`"
+        ))),
+        _ => run_secret_command(spec),
+    };
+    if let Ok(ref value) = value {
+        cache.lock().unwrap().insert(expr.to_string(), value.clone());
+    }
+    Some(value)
+}
+
+/// Read a secret file such as a Docker or Kubernetes secret mount, trimming
+/// surrounding whitespace. `~/` is the home directory.
+fn read_secret_file(path: &str) -> Result<String, McplugError> {
+    let resolved = match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => std::path::PathBuf::from(path),
+    };
+    std::fs::read_to_string(&resolved)
+        .map(|content| content.trim().to_string())
+        .map_err(|e| env_error(&format!("Cannot read secret file '{path}': {e}")))
+}
+
+/// Read `service/account` from the OS keychain: the macOS login keychain
+/// via `security`, or the Secret Service via `secret-tool` elsewhere.
+fn read_keychain(spec: &str) -> Result<String, McplugError> {
+    let (service, account) = spec
+        .split_once('/')
+        .filter(|(service, account)| !service.is_empty() && !account.is_empty())
+        .ok_or_else(|| {
+            env_error(&format!(
+                "Invalid keychain reference '{spec}': expected ${{keychain:service/account}}"
+            ))
+        })?;
+    let mut command = keychain_command(service, account)?;
+    run_for_secret(&mut command, &format!("keychain entry '{spec}'"))
+}
+
+fn keychain_command(service: &str, account: &str) -> Result<Command, McplugError> {
+    if cfg!(target_os = "macos") {
+        let mut command = Command::new("security");
+        command.args(["find-generic-password", "-s", service, "-a", account, "-w"]);
+        Ok(command)
+    } else if cfg!(unix) {
+        let mut command = Command::new("secret-tool");
+        command.args(["lookup", "service", service, "account", account]);
+        Ok(command)
+    } else {
+        Err(env_error(
+            "${keychain:...} is not supported on this platform; use ${cmd:...} instead",
+        ))
+    }
+}
+
+/// Whether `command` ends inside a shell quote.
+fn has_open_quote(command: &str) -> bool {
+    let mut quote = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            // Backslash escapes the next character, except in single quotes
+            (None | Some('"'), '\\') => {
+                chars.next();
+            }
+            _ => {}
+        }
+    }
+    quote.is_some()
+}
+
+/// Run `command` through the shell and use its output as the value.
+fn run_secret_command(command: &str) -> Result<String, McplugError> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    run_for_secret(&mut shell, &format!("command '{command}'"))
+}
+
+/// Run a secret lookup, returning its stdout without the trailing newline.
+/// Errors mention stderr but never stdout, which may hold the secret.
+fn run_for_secret(command: &mut Command, what: &str) -> Result<String, McplugError> {
+    let output = command
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| env_error(&format!("Cannot read {what}: {e}")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut detail = format!("Cannot read {what} ({})", output.status);
+        if !stderr.trim().is_empty() {
+            detail.push_str(": ");
+            detail.push_str(stderr.trim());
+        }
+        return Err(env_error(&detail));
+    }
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|_| env_error(&format!("Cannot read {what}: output is not UTF-8")))?;
+    Ok(stdout.trim_end_matches(['\r', '\n']).to_string())
+}
+
 fn env_error(detail: &str) -> McplugError {
     McplugError::ConfigError {
         path: std::path::PathBuf::from("<env>"),
@@ -229,6 +466,18 @@ mod tests {
                 "Bearer $env:MCPLUG_TEST_SC_TOK".into(),
             )]),
             lifecycle: None,
+            tool_pins: HashMap::new(),
+            disabled: false,
+            install_timeout: None,
+            log_level: None,
+            sensitive_args: Vec::new(),
+            timeout_ms: None,
+            list_timeout_ms: None,
+            allow_http: None,
+            auth: None,
+            tags: Vec::new(),
+            transport: None,
+            retry: None,
         };
         expand_server_config(&mut cfg).unwrap();
 
@@ -244,6 +493,144 @@ mod tests {
         std::env::remove_var("MCPLUG_TEST_SC_TOK");
     }
 
+    #[test]
+    fn expand_server_config_expands_auth() {
+        std::env::set_var("MCPLUG_TEST_AUTH_TOK", "tok789");
+        let mut cfg: ServerConfig = serde_json::from_value(serde_json::json!({
+            "auth": {"type": "bearer", "token": "${MCPLUG_TEST_AUTH_TOK}"}
+        }))
+        .unwrap();
+        expand_server_config(&mut cfg).unwrap();
+        assert_eq!(
+            cfg.auth,
+            Some(AuthConfig::Bearer {
+                token: "tok789".into()
+            })
+        );
+        std::env::remove_var("MCPLUG_TEST_AUTH_TOK");
+
+        let mut cfg: ServerConfig = serde_json::from_value(serde_json::json!({
+            "auth": {"type": "oauth", "clientId": "${MCPLUG_TEST_AUTH_UNSET_XYZ}"}
+        }))
+        .unwrap();
+        assert!(expand_server_config(&mut cfg).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn secret_references_wait_for_resolve_secrets() {
+        std::env::set_var("MCPLUG_TEST_LAZY_URL", "https://example.com");
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("ran");
+        let mut cfg: ServerConfig = serde_json::from_value(serde_json::json!({
+            "baseUrl": "${MCPLUG_TEST_LAZY_URL}/mcp",
+            "headers": {
+                "Authorization": format!("Bearer ${{cmd:touch {} && printf tok}}", marker.display()),
+                "X-Fallback": "${cmd:-plain}"
+            }
+        }))
+        .unwrap();
+        assert!(references_secrets(&cfg));
+        expand_server_config(&mut cfg).unwrap();
+        assert_eq!(cfg.base_url.as_deref(), Some("https://example.com/mcp"));
+        assert_eq!(cfg.headers["X-Fallback"], "plain");
+        assert!(cfg.headers["Authorization"].starts_with("Bearer ${cmd:"));
+        assert!(!marker.exists());
+
+        let resolved = resolve_secrets(&cfg).unwrap();
+        assert_eq!(resolved.headers["Authorization"], "Bearer tok");
+        assert_eq!(resolved.base_url, cfg.base_url);
+        assert!(!references_secrets(&resolved));
+        std::env::remove_var("MCPLUG_TEST_LAZY_URL");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn expand_cmd_reference() {
+        let result = expand_env_vars("Bearer ${cmd:printf 'tok\\n'}").unwrap();
+        assert_eq!(result, "Bearer tok");
+        // \163 is 's': the secret appears on stdout but not in the command
+        let err = expand_env_vars("${cmd:printf '\\163ecret'; echo oops >&2; exit 3}").unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("(exit status: 3): oops"), "got: {msg}");
+        assert!(!msg.contains("secret"), "got: {msg}");
+        // A variable named `cmd` with a fallback is not a command
+        std::env::remove_var("cmd");
+        assert_eq!(expand_env_vars("${cmd:-x}").unwrap(), "x");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cmd_reference_braces_nest_and_escape() {
+        let result = expand_env_vars("${cmd:echo a b | awk '{print $2}'}!").unwrap();
+        assert_eq!(result, "b!");
+        let result = expand_env_vars(r"${cmd:printf '%s' '\}'}").unwrap();
+        assert_eq!(result, "}");
+        assert!(has_secret_ref("x ${cmd:awk '{print $1}' f} y"));
+        assert_eq!(expand_env_vars(r"${MCPLUG_TEST_UNSET_BRACE:-a\{b}").unwrap(), "a{b");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cmd_reference_cut_short_is_not_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("ran");
+        // The unpaired `}` ends the reference inside the quoted string
+        let input = format!("${{cmd:touch {} ; echo '}}'}}", marker.display());
+        let err = expand_env_vars(&input).unwrap_err();
+        assert!(err.to_string().contains("unclosed quote"), "got: {err}");
+        assert!(!marker.exists());
+        // Unbalanced the other way, the reference is never closed
+        let err = expand_env_vars("${cmd:echo '{'}").unwrap_err();
+        assert!(err.to_string().contains("Unclosed"), "got: {err}");
+    }
+
+    #[test]
+    fn open_quotes() {
+        assert!(!has_open_quote(r#"op read "op://a/b" 'x'"#));
+        assert!(!has_open_quote(r#"echo "it's" \'"#));
+        assert!(has_open_quote("echo '"));
+        assert!(has_open_quote(r#"echo "a\""#));
+    }
+
+    #[test]
+    fn expand_file_reference() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token");
+        std::fs::write(&path, "  s3cret\n").unwrap();
+        let input = format!("Bearer ${{file:{}}}", path.display());
+        assert_eq!(expand_env_vars(&input).unwrap(), "Bearer s3cret");
+
+        // Not cached: a rotated secret is picked up on the next load
+        std::fs::write(&path, "rotated").unwrap();
+        assert_eq!(expand_env_vars(&input).unwrap(), "Bearer rotated");
+
+        let missing = format!("${{file:{}}}", dir.path().join("missing").display());
+        let err = expand_env_vars(&missing).unwrap_err();
+        assert!(err.to_string().contains("Cannot read secret file"), "got: {err}");
+    }
+
+    #[test]
+    fn keychain_reference_needs_service_and_account() {
+        let err = expand_env_vars("${keychain:only-service}").unwrap_err();
+        assert!(err.to_string().contains("service/account"));
+        assert!(expand_env_vars("${keychain:/acct}").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keychain_command_for_platform() {
+        let command = keychain_command("svc", "me@example.com").unwrap();
+        let args: Vec<_> = command.get_args().collect();
+        if cfg!(target_os = "macos") {
+            assert_eq!(command.get_program(), "security");
+            assert!(args.contains(&std::ffi::OsStr::new("-w")));
+        } else {
+            assert_eq!(command.get_program(), "secret-tool");
+            assert_eq!(args, ["lookup", "service", "svc", "account", "me@example.com"]);
+        }
+    }
+
     #[test]
     fn expand_unclosed_brace_errors() {
         let err = expand_env_vars("prefix-${UNCLOSED_VAR").unwrap_err();
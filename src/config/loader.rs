@@ -5,7 +5,7 @@ use crate::error::McplugError;
 
 use super::editors::import_editor_configs;
 use super::env::expand_server_config;
-use super::types::{McplugConfig, ServerConfig};
+use super::types::{McplugConfig, ServerConfig, ServerDefaults};
 
 /// Strip JSONC comments (// line comments and /* */ block comments) from input.
 pub fn strip_jsonc_comments(input: &str) -> String {
@@ -81,7 +81,9 @@ pub fn strip_jsonc_comments(input: &str) -> String {
 /// Precedence:
 /// 1. `--config` CLI flag
 /// 2. `MCPLUG_CONFIG` env var
-/// 3. `./config/mcplug.json` (project-level)
+/// 3. Project-level: the nearest directory at or above the working
+///    directory with `.mcplug/mcplug.json`, `mcplug.json`, or
+///    `config/mcplug.json`, see `project_config_files`
 /// 4. `~/.mcplug/mcplug.json` or `~/.mcplug/mcplug.jsonc` (home-level)
 /// 5. Fallback: `~/.mcporter/mcporter.json[c]`, `./config/mcporter.json`
 pub fn discover_config_files(cli_config: Option<&str>) -> Vec<PathBuf> {
@@ -103,10 +105,13 @@ pub fn discover_config_files(cli_config: Option<&str>) -> Vec<PathBuf> {
         }
     }
 
-    // 3. ./config/mcplug.json (project-level)
-    let project_config = PathBuf::from("./config/mcplug.json");
-    if project_config.exists() && !files.contains(&project_config) {
-        files.push(project_config);
+    // 3. Project-level, found by walking up from the working directory
+    if let Ok(cwd) = std::env::current_dir() {
+        for project_config in project_config_files(&cwd, dirs::home_dir().as_deref()) {
+            if !files.contains(&project_config) {
+                files.push(project_config);
+            }
+        }
     }
 
     // 4. ~/.mcplug/mcplug.json or ~/.mcplug/mcplug.jsonc
@@ -138,6 +143,32 @@ pub fn discover_config_files(cli_config: Option<&str>) -> Vec<PathBuf> {
     files
 }
 
+/// Project config file names, in precedence order, relative to a project root.
+const PROJECT_CONFIG_FILES: &[&str] = &[".mcplug/mcplug.json", "mcplug.json", "config/mcplug.json"];
+
+/// Find the project config by walking up from `start`, like git finds its
+/// repository: the first directory holding any of `PROJECT_CONFIG_FILES`
+/// is the project root, and all of its config files are returned.
+///
+/// The walk stops below `home`, whose `.mcplug/mcplug.json` is the
+/// home-level config rather than a project's.
+pub fn project_config_files(start: &Path, home: Option<&Path>) -> Vec<PathBuf> {
+    for dir in start.ancestors() {
+        if Some(dir) == home {
+            break;
+        }
+        let found: Vec<PathBuf> = PROJECT_CONFIG_FILES
+            .iter()
+            .map(|name| dir.join(name))
+            .filter(|path| path.is_file())
+            .collect();
+        if !found.is_empty() {
+            return found;
+        }
+    }
+    Vec::new()
+}
+
 /// Load a single config file, stripping JSONC comments before parsing.
 fn load_config_file(path: &Path) -> Result<McplugConfig, McplugError> {
     let content = std::fs::read_to_string(path).map_err(|e| McplugError::ConfigError {
@@ -152,6 +183,65 @@ fn load_config_file(path: &Path) -> Result<McplugConfig, McplugError> {
     })
 }
 
+/// Resolve an `extends` entry: `~/` is the home directory, and relative
+/// paths are relative to the directory of the file that extends them.
+pub fn resolve_extends_path(from: &Path, base: &str) -> PathBuf {
+    if let (Some(rest), Some(home)) = (base.strip_prefix("~/"), dirs::home_dir()) {
+        return home.join(rest);
+    }
+    let base = Path::new(base);
+    if base.is_absolute() {
+        return base.to_path_buf();
+    }
+    from.parent().unwrap_or(Path::new(".")).join(base)
+}
+
+/// Load a config file together with the files it `extends`, recursively,
+/// in precedence order: the file first, then its bases with later entries
+/// taking precedence over earlier ones (each followed by its own bases).
+///
+/// A base reached twice is loaded once; a base that extends one of the
+/// files extending it is a config error.
+pub fn load_config_layers(path: &Path) -> Result<Vec<(PathBuf, McplugConfig)>, McplugError> {
+    let mut layers = Vec::new();
+    collect_layers(path, &mut Vec::new(), &mut Vec::new(), &mut layers)?;
+    Ok(layers)
+}
+
+fn collect_layers(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    seen: &mut Vec<PathBuf>,
+    layers: &mut Vec<(PathBuf, McplugConfig)>,
+) -> Result<(), McplugError> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if let Some(start) = stack.iter().position(|p| *p == canonical) {
+        let chain: Vec<String> = stack[start..]
+            .iter()
+            .chain(std::iter::once(&canonical))
+            .map(|p| p.display().to_string())
+            .collect();
+        return Err(McplugError::ConfigError {
+            path: path.to_path_buf(),
+            detail: format!("Circular extends: {}", chain.join(" -> ")),
+        });
+    }
+    if seen.contains(&canonical) {
+        return Ok(());
+    }
+    seen.push(canonical.clone());
+
+    let mut cfg = load_config_file(path)?;
+    let extends = std::mem::take(&mut cfg.extends);
+    layers.push((path.to_path_buf(), cfg));
+    stack.push(canonical);
+    for base in extends.iter().rev() {
+        collect_layers(&resolve_extends_path(path, base), stack, seen, layers)?;
+    }
+    stack.pop();
+    Ok(())
+}
+
 /// Merge server configs from `source` into `target`.
 /// Servers already present in `target` are NOT overridden (earlier sources win).
 fn merge_servers(
@@ -165,25 +255,55 @@ fn merge_servers(
 
 /// Load, merge, and expand all configuration.
 ///
-/// - Discovers config files in precedence order
+/// - Discovers config files in precedence order, each followed by the
+///   files it `extends`
 /// - Merges mcpServers (earlier sources win for same name)
 /// - Collects imports from all configs
 /// - Imports editor configs (lowest precedence)
 /// - Expands environment variables in all server configs
 pub fn load_config(cli_config: Option<&str>) -> Result<McplugConfig, McplugError> {
+    let mut config = load_merged_config(cli_config)?;
+    for server in config.mcp_servers.values_mut() {
+        expand_server_config(server)?;
+    }
+    Ok(config)
+}
+
+/// Load and merge all configuration like `load_config`, but leave `${VAR}`
+/// and secret references unexpanded.
+pub fn load_merged_config(cli_config: Option<&str>) -> Result<McplugConfig, McplugError> {
     let config_files = discover_config_files(cli_config);
 
     let mut merged_servers: HashMap<String, ServerConfig> = HashMap::new();
     let mut all_imports: Vec<String> = Vec::new();
+    let mut state = None;
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    let mut defaults: Option<ServerDefaults> = None;
 
+    let mut layers = Vec::new();
     for path in &config_files {
-        let cfg = load_config_file(path)?;
+        layers.extend(load_config_layers(path)?);
+    }
+
+    for (_, cfg) in layers {
         merge_servers(&mut merged_servers, cfg.mcp_servers);
         for import in cfg.imports {
             if !all_imports.contains(&import) {
                 all_imports.push(import);
             }
         }
+        // Like servers, the highest-precedence file that sets it wins
+        state = state.or(cfg.state);
+        for (name, members) in cfg.groups {
+            groups.entry(name).or_insert(members);
+        }
+        // Field by field, so a base file can supply what a project leaves out
+        if let Some(lower) = cfg.defaults {
+            match defaults {
+                Some(ref mut defaults) => defaults.merge(lower),
+                None => defaults = Some(lower),
+            }
+        }
     }
 
     // Import editor configs (lowest precedence — merged after everything else)
@@ -192,14 +312,19 @@ pub fn load_config(cli_config: Option<&str>) -> Result<McplugConfig, McplugError
         merge_servers(&mut merged_servers, editor_servers);
     }
 
-    // Expand environment variables in all server configs
-    for config in merged_servers.values_mut() {
-        expand_server_config(config)?;
+    if let Some(ref defaults) = defaults {
+        for config in merged_servers.values_mut() {
+            defaults.apply(config);
+        }
     }
 
     Ok(McplugConfig {
         mcp_servers: merged_servers,
         imports: all_imports,
+        state,
+        groups,
+        extends: Vec::new(),
+        defaults,
     })
 }
 
@@ -288,6 +413,18 @@ mod tests {
                 env: HashMap::new(),
                 headers: HashMap::new(),
                 lifecycle: None,
+                tool_pins: HashMap::new(),
+                disabled: false,
+                install_timeout: None,
+                log_level: None,
+                sensitive_args: Vec::new(),
+                timeout_ms: None,
+                list_timeout_ms: None,
+                allow_http: None,
+                auth: None,
+                tags: Vec::new(),
+                transport: None,
+                retry: None,
             },
         );
 
@@ -302,6 +439,18 @@ mod tests {
                 env: HashMap::new(),
                 headers: HashMap::new(),
                 lifecycle: None,
+                tool_pins: HashMap::new(),
+                disabled: false,
+                install_timeout: None,
+                log_level: None,
+                sensitive_args: Vec::new(),
+                timeout_ms: None,
+                list_timeout_ms: None,
+                allow_http: None,
+                auth: None,
+                tags: Vec::new(),
+                transport: None,
+                retry: None,
             },
         );
         source.insert(
@@ -314,6 +463,18 @@ mod tests {
                 env: HashMap::new(),
                 headers: HashMap::new(),
                 lifecycle: None,
+                tool_pins: HashMap::new(),
+                disabled: false,
+                install_timeout: None,
+                log_level: None,
+                sensitive_args: Vec::new(),
+                timeout_ms: None,
+                list_timeout_ms: None,
+                allow_http: None,
+                auth: None,
+                tags: Vec::new(),
+                transport: None,
+                retry: None,
             },
         );
 
@@ -342,6 +503,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn project_config_found_in_ancestor() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("repo");
+        let nested = project.join("src").join("deep");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir_all(project.join(".mcplug")).unwrap();
+        std::fs::write(project.join(".mcplug").join("mcplug.json"), "{}").unwrap();
+        std::fs::write(project.join("mcplug.json"), "{}").unwrap();
+        // A config further up belongs to an enclosing project and is not used
+        std::fs::write(root.path().join("mcplug.json"), "{}").unwrap();
+
+        assert_eq!(
+            project_config_files(&nested, None),
+            vec![
+                project.join(".mcplug").join("mcplug.json"),
+                project.join("mcplug.json"),
+            ]
+        );
+        assert_eq!(
+            project_config_files(root.path(), None),
+            vec![root.path().join("mcplug.json")]
+        );
+    }
+
+    #[test]
+    fn project_config_walk_stops_at_home() {
+        let home = tempfile::tempdir().unwrap();
+        let nested = home.path().join("code").join("app");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir_all(home.path().join(".mcplug")).unwrap();
+        std::fs::write(home.path().join(".mcplug").join("mcplug.json"), "{}").unwrap();
+
+        assert!(project_config_files(&nested, Some(home.path())).is_empty());
+        std::fs::create_dir_all(nested.join("config")).unwrap();
+        std::fs::write(nested.join("config").join("mcplug.json"), "{}").unwrap();
+        assert_eq!(
+            project_config_files(&nested, Some(home.path())),
+            vec![nested.join("config").join("mcplug.json")]
+        );
+    }
+
     #[test]
     fn load_config_file_parses_jsonc() {
         let dir = tempfile::tempdir().unwrap();
@@ -450,6 +653,85 @@ mod tests {
         assert!(all_imports.contains(&"claude-code".to_string()));
     }
 
+    #[test]
+    fn extends_layers_base_under_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("shared");
+        std::fs::create_dir(&shared).unwrap();
+        std::fs::write(
+            shared.join("base.json"),
+            r#"{"mcpServers": {"a": {"command": "base-a"}, "b": {"command": "base-b"}},
+                "groups": {"g": ["a"]}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            shared.join("team.json"),
+            r#"{"extends": ["base.json"], "mcpServers": {"b": {"command": "team-b"}}}"#,
+        )
+        .unwrap();
+        let project = dir.path().join("mcplug.json");
+        std::fs::write(
+            &project,
+            r#"{"extends": ["shared/base.json", "shared/team.json"],
+                "mcpServers": {"a": {"command": "project-a"}}}"#,
+        )
+        .unwrap();
+
+        let layers = load_config_layers(&project).unwrap();
+        let names: Vec<String> = layers
+            .iter()
+            .map(|(p, _)| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        // Later entries win, and base.json is loaded once
+        assert_eq!(names, vec!["mcplug.json", "team.json", "base.json"]);
+        assert!(layers.iter().all(|(_, cfg)| cfg.extends.is_empty()));
+
+        let mut merged: HashMap<String, ServerConfig> = HashMap::new();
+        for (_, cfg) in layers {
+            merge_servers(&mut merged, cfg.mcp_servers);
+        }
+        assert_eq!(merged["a"].command.as_deref(), Some("project-a"));
+        assert_eq!(merged["b"].command.as_deref(), Some("team-b"));
+    }
+
+    #[test]
+    fn extends_cycle_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.json");
+        let b = dir.path().join("b.json");
+        std::fs::write(&a, r#"{"extends": ["b.json"]}"#).unwrap();
+        std::fs::write(&b, r#"{"extends": ["./a.json"]}"#).unwrap();
+        let err = load_config_layers(&a).unwrap_err().to_string();
+        assert!(err.contains("Circular extends"), "got: {err}");
+        assert!(err.contains("a.json -> "), "got: {err}");
+    }
+
+    #[test]
+    fn extends_missing_base_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.json");
+        std::fs::write(&a, r#"{"extends": ["missing.json"]}"#).unwrap();
+        let err = load_config_layers(&a).unwrap_err().to_string();
+        assert!(err.contains("missing.json"), "got: {err}");
+        assert!(err.contains("Cannot read file"), "got: {err}");
+    }
+
+    #[test]
+    fn extends_path_resolution() {
+        let from = Path::new("/work/project/mcplug.json");
+        assert_eq!(
+            resolve_extends_path(from, "../shared/mcplug.json"),
+            PathBuf::from("/work/project/../shared/mcplug.json")
+        );
+        assert_eq!(
+            resolve_extends_path(from, "/etc/mcplug.json"),
+            PathBuf::from("/etc/mcplug.json")
+        );
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(resolve_extends_path(from, "~/base.json"), home.join("base.json"));
+        }
+    }
+
     #[test]
     fn load_config_empty_servers_object() {
         let dir = tempfile::tempdir().unwrap();
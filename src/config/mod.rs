@@ -1,7 +1,12 @@
 pub mod editors;
 pub mod env;
 pub mod loader;
+pub mod pins;
 pub mod types;
+pub mod validate;
+pub mod watch;
+pub(crate) mod yaml;
 
 pub use loader::load_config;
-pub use types::{AnnotatedServerConfig, Lifecycle, McplugConfig, ServerConfig};
+pub use types::{AnnotatedServerConfig, AuthConfig, Lifecycle, McplugConfig, ServerConfig};
+pub use watch::{ConfigChanges, ConfigWatcher};
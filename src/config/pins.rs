@@ -0,0 +1,139 @@
+use crate::config::ServerConfig;
+use crate::error::McplugError;
+use crate::types::ToolDefinition;
+
+/// What to do when a pinned tool's input schema no longer matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinPolicy {
+    /// Refuse to call the tool (default).
+    Refuse,
+    /// Log a warning and call the tool anyway.
+    Warn,
+}
+
+impl PinPolicy {
+    /// Read the policy from `MCPLUG_PIN_POLICY` (`refuse` or `warn`).
+    pub fn from_env() -> Self {
+        Self::parse(std::env::var("MCPLUG_PIN_POLICY").ok().as_deref())
+    }
+
+    fn parse(val: Option<&str>) -> Self {
+        match val {
+            Some(v) if v.eq_ignore_ascii_case("warn") => PinPolicy::Warn,
+            _ => PinPolicy::Refuse,
+        }
+    }
+}
+
+/// Check a tool's input schema against the hash pinned in the server config.
+///
+/// Tools without a pin always pass. On mismatch, returns
+/// `SchemaPinMismatch` under `PinPolicy::Refuse` and logs a warning under
+/// `PinPolicy::Warn`.
+pub fn check_tool_pin(
+    server: &str,
+    cfg: &ServerConfig,
+    tool: &ToolDefinition,
+    policy: PinPolicy,
+) -> Result<(), McplugError> {
+    let Some(expected) = cfg.tool_pins.get(&tool.name) else {
+        return Ok(());
+    };
+    let actual = tool.schema_hash();
+    if expected.eq_ignore_ascii_case(&actual) {
+        return Ok(());
+    }
+
+    let err = McplugError::SchemaPinMismatch {
+        server: server.to_string(),
+        tool: tool.name.clone(),
+        expected: expected.clone(),
+        actual,
+    };
+    match policy {
+        PinPolicy::Refuse => Err(err),
+        PinPolicy::Warn => {
+            tracing::warn!("{err}");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn tool() -> ToolDefinition {
+        ToolDefinition {
+            name: "scrape".into(),
+            description: "Scrape a page".into(),
+            input_schema: serde_json::json!({"type": "object"}),
+            annotations: None,
+            output_schema: None,
+        }
+    }
+
+    fn config_with_pin(pin: Option<&str>) -> ServerConfig {
+        let mut tool_pins = HashMap::new();
+        if let Some(p) = pin {
+            tool_pins.insert("scrape".to_string(), p.to_string());
+        }
+        ServerConfig {
+            description: None,
+            base_url: None,
+            command: Some("echo".into()),
+            args: vec![],
+            env: HashMap::new(),
+            headers: HashMap::new(),
+            lifecycle: None,
+            tool_pins,
+            disabled: false,
+            install_timeout: None,
+            log_level: None,
+            sensitive_args: Vec::new(),
+            timeout_ms: None,
+            list_timeout_ms: None,
+            allow_http: None,
+            auth: None,
+            tags: Vec::new(),
+            transport: None,
+            retry: None,
+        }
+    }
+
+    #[test]
+    fn unpinned_tool_passes() {
+        let cfg = config_with_pin(None);
+        assert!(check_tool_pin("s", &cfg, &tool(), PinPolicy::Refuse).is_ok());
+    }
+
+    #[test]
+    fn matching_pin_passes() {
+        let cfg = config_with_pin(Some(&tool().schema_hash()));
+        assert!(check_tool_pin("s", &cfg, &tool(), PinPolicy::Refuse).is_ok());
+    }
+
+    #[test]
+    fn mismatched_pin_refused() {
+        let cfg = config_with_pin(Some("sha256:0000"));
+        let err = check_tool_pin("s", &cfg, &tool(), PinPolicy::Refuse).unwrap_err();
+        assert!(matches!(err, McplugError::SchemaPinMismatch { .. }));
+        assert!(err.to_string().contains(&tool().schema_hash()));
+    }
+
+    #[test]
+    fn mismatched_pin_warns() {
+        let cfg = config_with_pin(Some("sha256:0000"));
+        assert!(check_tool_pin("s", &cfg, &tool(), PinPolicy::Warn).is_ok());
+    }
+
+    #[test]
+    fn policy_parse() {
+        assert_eq!(PinPolicy::parse(None), PinPolicy::Refuse);
+        assert_eq!(PinPolicy::parse(Some("warn")), PinPolicy::Warn);
+        assert_eq!(PinPolicy::parse(Some("WARN")), PinPolicy::Warn);
+        assert_eq!(PinPolicy::parse(Some("refuse")), PinPolicy::Refuse);
+        assert_eq!(PinPolicy::parse(Some("bogus")), PinPolicy::Refuse);
+    }
+}
@@ -1,17 +1,116 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::McplugError;
+use crate::retry::RetryPolicy;
+use crate::state::StateConfig;
+use crate::types::LogLevel;
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct McplugConfig {
     #[serde(default, rename = "mcpServers")]
     pub mcp_servers: HashMap<String, ServerConfig>,
     #[serde(default)]
     pub imports: Vec<String>,
+    /// Where tokens, caches, and saved results are stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<StateConfig>,
+    /// Named sets of servers, usable where a server name is accepted.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub groups: HashMap<String, Vec<String>>,
+    /// Base config files layered under this one, relative to this file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extends: Vec<String>,
+    /// Settings applied to every server that doesn't set them itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<ServerDefaults>,
+}
+
+impl McplugConfig {
+    /// Resolve a server or group name to server names.
+    ///
+    /// A server name resolves to itself; a server shadows a group of the
+    /// same name. A group resolves to its members in the order listed, and
+    /// every member must be a configured server.
+    pub fn resolve_servers(&self, name: &str) -> Result<Vec<String>, McplugError> {
+        if self.mcp_servers.contains_key(name) {
+            return Ok(vec![name.to_string()]);
+        }
+        let members = self
+            .groups
+            .get(name)
+            .ok_or_else(|| McplugError::ServerNotFound(name.to_string()))?;
+        if let Some(missing) = members.iter().find(|m| !self.mcp_servers.contains_key(*m)) {
+            return Err(McplugError::ServerNotFound(format!(
+                "{missing} (member of group '{name}')"
+            )));
+        }
+        Ok(members.clone())
+    }
+
+    /// Names of the servers that aren't `disabled`, sorted. Listing and
+    /// discovery skip disabled servers; naming one directly is an error.
+    pub fn enabled_servers(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .mcp_servers
+            .iter()
+            .filter(|(_, cfg)| !cfg.disabled)
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Names of the enabled servers carrying any of `tags`, sorted. With no
+    /// tags, all enabled servers.
+    pub fn tagged_servers(&self, tags: &[String]) -> Vec<String> {
+        let mut names = self.enabled_servers();
+        if !tags.is_empty() {
+            names.retain(|name| self.mcp_servers[name].tags.iter().any(|t| tags.contains(t)));
+        }
+        names
+    }
+
+    /// Whether `name` is a group rather than a server.
+    pub fn is_group(&self, name: &str) -> bool {
+        !self.mcp_servers.contains_key(name) && self.groups.contains_key(name)
+    }
+
+    /// Resolve several server or group names, dropping duplicates.
+    pub fn resolve_all(&self, names: &[String]) -> Result<Vec<String>, McplugError> {
+        let mut servers: Vec<String> = Vec::new();
+        for name in names {
+            for server in self.resolve_servers(name)? {
+                if !servers.contains(&server) {
+                    servers.push(server);
+                }
+            }
+        }
+        Ok(servers)
+    }
+
+    /// Timeout for calling a tool on `server`: its `timeoutMs`, else `default`.
+    pub fn call_timeout(&self, server: &str, default: Duration) -> Duration {
+        self.mcp_servers
+            .get(server)
+            .and_then(ServerConfig::call_timeout)
+            .unwrap_or(default)
+    }
+
+    /// Timeout for connecting to `server` and listing its tools: its
+    /// `listTimeoutMs` or `timeoutMs`, else `default`.
+    pub fn list_timeout(&self, server: &str, default: Duration) -> Duration {
+        self.mcp_servers
+            .get(server)
+            .and_then(ServerConfig::list_timeout)
+            .unwrap_or(default)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ServerConfig {
     #[serde(default)]
     pub description: Option<String>,
@@ -27,15 +126,157 @@ pub struct ServerConfig {
     pub headers: HashMap<String, String>,
     #[serde(default)]
     pub lifecycle: Option<Lifecycle>,
+    /// Expected input schema hash per tool (`sha256:<hex>`), see `ToolDefinition::schema_hash`.
+    #[serde(default, rename = "toolPins", skip_serializing_if = "HashMap::is_empty")]
+    pub tool_pins: HashMap<String, String>,
+    /// Keep the entry in config but refuse to connect to it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+    /// Seconds a stdio server may take to produce its first output (e.g. an
+    /// `npx -y` install) before protocol timeouts start.
+    #[serde(default, rename = "installTimeout", skip_serializing_if = "Option::is_none")]
+    pub install_timeout: Option<u64>,
+    /// Level sent with `logging/setLevel` after connecting, for servers that
+    /// advertise the logging capability.
+    #[serde(default, rename = "logLevel", skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<LogLevel>,
+    /// Argument paths (`token`, `auth.password`) to treat as sensitive in
+    /// addition to those the tool schemas mark with `x-sensitive`.
+    #[serde(default, rename = "sensitiveArgs", skip_serializing_if = "Vec::is_empty")]
+    pub sensitive_args: Vec<String>,
+    /// Milliseconds allowed for a tool call, overriding `MCPLUG_CALL_TIMEOUT`.
+    #[serde(default, rename = "timeoutMs", skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Milliseconds allowed for connecting and listing tools, overriding
+    /// `MCPLUG_LIST_TIMEOUT`. Defaults to `timeoutMs` when only that is set.
+    #[serde(default, rename = "listTimeoutMs", skip_serializing_if = "Option::is_none")]
+    pub list_timeout_ms: Option<u64>,
+    /// Permit a cleartext `http://` `baseUrl` (e.g. a localhost or LAN server).
+    /// Unset means `defaults.allowHttp`, else `false`.
+    #[serde(default, rename = "allowHttp", skip_serializing_if = "Option::is_none")]
+    pub allow_http: Option<bool>,
+    /// How to authenticate to an HTTP server, see `AuthConfig`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<AuthConfig>,
+    /// Labels for organizing servers, usable with `list --tag` and `search --tag`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// How to connect: `stdio`, `http`, or `sse` (inferred from `command` or
+    /// `baseUrl` when unset), or the name of a transport registered with
+    /// `RuntimeBuilder::transport`.
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub transport: Option<String>,
+    /// How `Runtime` retries failed calls to this server, see `RetryPolicy`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+}
+
+/// Transport `type`s mcplug provides itself.
+pub const BUILTIN_TRANSPORTS: &[&str] = &["stdio", "http", "sse"];
+
+impl ServerConfig {
+    /// The `type` when it names a transport registered with the Runtime
+    /// rather than a built-in one.
+    pub fn custom_transport(&self) -> Option<&str> {
+        self.transport
+            .as_deref()
+            .filter(|kind| !BUILTIN_TRANSPORTS.contains(kind))
+    }
+
+    /// The configured `timeoutMs`.
+    pub fn call_timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
+
+    /// The configured `listTimeoutMs`, falling back to `timeoutMs`.
+    pub fn list_timeout(&self) -> Option<Duration> {
+        self.list_timeout_ms
+            .or(self.timeout_ms)
+            .map(Duration::from_millis)
+    }
+}
+
+/// The `defaults` section: values for servers that leave them unset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServerDefaults {
+    #[serde(default, rename = "timeoutMs", skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    #[serde(default, rename = "listTimeoutMs", skip_serializing_if = "Option::is_none")]
+    pub list_timeout_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lifecycle: Option<Lifecycle>,
+    #[serde(default, rename = "allowHttp", skip_serializing_if = "Option::is_none")]
+    pub allow_http: Option<bool>,
+    /// Headers sent to every HTTP server; a server's own header of the same
+    /// name wins.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+}
+
+impl ServerDefaults {
+    /// Fill in what this leaves unset from `lower`, the defaults of a
+    /// lower-precedence file.
+    pub fn merge(&mut self, lower: ServerDefaults) {
+        self.timeout_ms = self.timeout_ms.or(lower.timeout_ms);
+        self.list_timeout_ms = self.list_timeout_ms.or(lower.list_timeout_ms);
+        self.lifecycle = self.lifecycle.take().or(lower.lifecycle);
+        self.allow_http = self.allow_http.or(lower.allow_http);
+        for (name, value) in lower.headers {
+            self.headers.entry(name).or_insert(value);
+        }
+    }
+
+    /// Set each field `server` leaves unset. Headers only go to HTTP servers.
+    pub fn apply(&self, server: &mut ServerConfig) {
+        server.timeout_ms = server.timeout_ms.or(self.timeout_ms);
+        server.list_timeout_ms = server.list_timeout_ms.or(self.list_timeout_ms);
+        if server.lifecycle.is_none() {
+            server.lifecycle = self.lifecycle.clone();
+        }
+        server.allow_http = server.allow_http.or(self.allow_http);
+        if server.base_url.is_some() {
+            for (name, value) in &self.headers {
+                server
+                    .headers
+                    .entry(name.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum Lifecycle {
     KeepAlive,
     Ephemeral,
 }
 
+/// Declarative authentication for an HTTP server (`"auth"` in config).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AuthConfig {
+    /// A static token sent as `Authorization: Bearer <token>`.
+    Bearer { token: String },
+    /// OAuth client settings used by `mcplug auth`; the cached access token
+    /// is sent as the bearer token.
+    #[serde(rename_all = "camelCase")]
+    OAuth {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        client_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        scopes: Vec<String>,
+        /// Fixed localhost port for the login callback, for clients whose
+        /// redirect URI is pre-registered. A free port is picked otherwise.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        redirect_port: Option<u16>,
+        /// Named token set to log in as and send, e.g. `work` for
+        /// `github:work`. `--as` overrides it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        identity: Option<String>,
+    },
+}
+
 #[derive(Debug, Clone)]
 pub struct AnnotatedServerConfig {
     pub config: ServerConfig,
@@ -61,6 +302,31 @@ mod tests {
         assert_eq!(cfg.headers.get("Authorization").unwrap(), "Bearer tok");
     }
 
+    #[test]
+    fn deserialize_auth_section() {
+        let cfg: ServerConfig =
+            serde_json::from_str(r#"{"auth": {"type": "bearer", "token": "tok"}}"#).unwrap();
+        assert_eq!(cfg.auth, Some(AuthConfig::Bearer { token: "tok".into() }));
+
+        let json = r#"{"auth": {"type": "oauth", "clientId": "app", "scopes": ["read"], "redirectPort": 8765}}"#;
+        let cfg: ServerConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            cfg.auth,
+            Some(AuthConfig::OAuth {
+                client_id: Some("app".into()),
+                scopes: vec!["read".into()],
+                redirect_port: Some(8765),
+                identity: None,
+            })
+        );
+        assert_eq!(serde_json::to_value(&cfg).unwrap()["auth"]["clientId"], "app");
+        assert_eq!(serde_json::to_value(&cfg).unwrap()["auth"]["redirectPort"], 8765);
+
+        let cfg: ServerConfig = serde_json::from_str(r#"{"auth": {"type": "oauth"}}"#).unwrap();
+        assert!(matches!(cfg.auth, Some(AuthConfig::OAuth { client_id: None, .. })));
+        assert!(serde_json::from_str::<ServerConfig>(r#"{"auth": {"type": "basic"}}"#).is_err());
+    }
+
     #[test]
     fn deserialize_stdio_server() {
         let json = r#"{
@@ -86,6 +352,170 @@ mod tests {
         assert!(cfg.env.is_empty());
         assert!(cfg.headers.is_empty());
         assert!(cfg.lifecycle.is_none());
+        assert!(cfg.tool_pins.is_empty());
+        assert!(!cfg.disabled);
+        assert!(cfg.install_timeout.is_none());
+    }
+
+    #[test]
+    fn deserialize_install_timeout() {
+        let json = r#"{"command": "npx", "args": ["-y", "big-server"], "installTimeout": 600}"#;
+        let cfg: ServerConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.install_timeout, Some(600));
+        let out = serde_json::to_value(&cfg).unwrap();
+        assert_eq!(out["installTimeout"], 600);
+    }
+
+    #[test]
+    fn per_server_timeouts() {
+        let cfg: McplugConfig = serde_json::from_str(
+            r#"{"mcpServers": {
+                "crawler": {"command": "x", "timeoutMs": 120000},
+                "split": {"command": "x", "timeoutMs": 5000, "listTimeoutMs": 2000},
+                "plain": {"command": "x"}
+            }}"#,
+        )
+        .unwrap();
+        let default = Duration::from_secs(30);
+        assert_eq!(cfg.call_timeout("crawler", default), Duration::from_secs(120));
+        // listTimeoutMs falls back to timeoutMs
+        assert_eq!(cfg.list_timeout("crawler", default), Duration::from_secs(120));
+        assert_eq!(cfg.call_timeout("split", default), Duration::from_secs(5));
+        assert_eq!(cfg.list_timeout("split", default), Duration::from_secs(2));
+        assert_eq!(cfg.call_timeout("plain", default), default);
+        assert_eq!(cfg.list_timeout("missing", default), default);
+        let out = serde_json::to_value(&cfg.mcp_servers["plain"]).unwrap();
+        assert!(out.get("timeoutMs").is_none());
+    }
+
+    #[test]
+    fn defaults_fill_unset_server_fields() {
+        let cfg: McplugConfig = serde_json::from_str(
+            r#"{
+                "defaults": {"timeoutMs": 60000, "lifecycle": "keep-alive", "allowHttp": true,
+                             "headers": {"X-Team": "core", "X-Trace": "on"}},
+                "mcpServers": {
+                    "web": {"baseUrl": "http://localhost:3000", "headers": {"X-Trace": "off"}},
+                    "strict": {"baseUrl": "http://lan:3000", "allowHttp": false, "timeoutMs": 5000},
+                    "local": {"command": "x", "lifecycle": "ephemeral"}
+                }
+            }"#,
+        )
+        .unwrap();
+        let defaults = cfg.defaults.unwrap();
+        let mut servers = cfg.mcp_servers;
+        for server in servers.values_mut() {
+            defaults.apply(server);
+        }
+
+        let web = &servers["web"];
+        assert_eq!(web.timeout_ms, Some(60000));
+        assert_eq!(web.lifecycle, Some(Lifecycle::KeepAlive));
+        assert_eq!(web.allow_http, Some(true));
+        assert_eq!(web.headers["X-Team"], "core");
+        assert_eq!(web.headers["X-Trace"], "off");
+
+        let strict = &servers["strict"];
+        assert_eq!(strict.allow_http, Some(false));
+        assert_eq!(strict.timeout_ms, Some(5000));
+
+        let local = &servers["local"];
+        assert_eq!(local.lifecycle, Some(Lifecycle::Ephemeral));
+        assert!(local.headers.is_empty());
+    }
+
+    #[test]
+    fn defaults_merge_fills_from_lower_precedence() {
+        let mut project: ServerDefaults =
+            serde_json::from_str(r#"{"timeoutMs": 1000, "headers": {"A": "project"}}"#).unwrap();
+        let base: ServerDefaults = serde_json::from_str(
+            r#"{"timeoutMs": 9000, "allowHttp": true, "headers": {"A": "base", "B": "base"}}"#,
+        )
+        .unwrap();
+        project.merge(base);
+        assert_eq!(project.timeout_ms, Some(1000));
+        assert_eq!(project.allow_http, Some(true));
+        assert_eq!(project.headers["A"], "project");
+        assert_eq!(project.headers["B"], "base");
+    }
+
+    #[test]
+    fn disabled_serialized_only_when_set() {
+        let mut cfg: ServerConfig = serde_json::from_str(r#"{"command": "echo"}"#).unwrap();
+        let out = serde_json::to_value(&cfg).unwrap();
+        assert!(out.get("disabled").is_none());
+        cfg.disabled = true;
+        let out = serde_json::to_value(&cfg).unwrap();
+        assert_eq!(out["disabled"], true);
+    }
+
+    #[test]
+    fn deserialize_tool_pins() {
+        let json = r#"{
+            "command": "npx",
+            "toolPins": {"scrape": "sha256:abc123"}
+        }"#;
+        let cfg: ServerConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.tool_pins.get("scrape").unwrap(), "sha256:abc123");
+    }
+
+    fn grouped_config() -> McplugConfig {
+        serde_json::from_str(
+            r#"{
+                "mcpServers": {"a": {"command": "x"}, "b": {"command": "x"}, "c": {"command": "x"}},
+                "groups": {"research": ["b", "a"], "all": ["a", "b", "c"], "c": ["a"], "broken": ["a", "zzz"]}
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn resolve_servers_and_groups() {
+        let cfg = grouped_config();
+        assert_eq!(cfg.resolve_servers("a").unwrap(), vec!["a"]);
+        assert_eq!(cfg.resolve_servers("research").unwrap(), vec!["b", "a"]);
+        // A server shadows a group of the same name
+        assert_eq!(cfg.resolve_servers("c").unwrap(), vec!["c"]);
+        assert!(cfg.is_group("research"));
+        assert!(!cfg.is_group("c"));
+        assert!(matches!(
+            cfg.resolve_servers("nope"),
+            Err(McplugError::ServerNotFound(_))
+        ));
+        let err = cfg.resolve_servers("broken").unwrap_err();
+        assert!(err.to_string().contains("member of group 'broken'"));
+    }
+
+    #[test]
+    fn enabled_servers_skip_disabled() {
+        let mut cfg = grouped_config();
+        cfg.mcp_servers.get_mut("b").unwrap().disabled = true;
+        assert_eq!(cfg.enabled_servers(), vec!["a", "c"]);
+    }
+
+    #[test]
+    fn tagged_servers_match_any_tag() {
+        let mut cfg = grouped_config();
+        cfg.mcp_servers.get_mut("a").unwrap().tags = vec!["search".into()];
+        cfg.mcp_servers.get_mut("b").unwrap().tags = vec!["internal".into(), "search".into()];
+        cfg.mcp_servers.get_mut("c").unwrap().tags = vec!["internal".into()];
+        assert_eq!(cfg.tagged_servers(&["search".into()]), vec!["a", "b"]);
+        assert_eq!(
+            cfg.tagged_servers(&["search".into(), "internal".into()]),
+            vec!["a", "b", "c"]
+        );
+        assert_eq!(cfg.tagged_servers(&[]), vec!["a", "b", "c"]);
+        assert!(cfg.tagged_servers(&["nope".into()]).is_empty());
+        // Disabled servers stay hidden
+        cfg.mcp_servers.get_mut("b").unwrap().disabled = true;
+        assert_eq!(cfg.tagged_servers(&["search".into()]), vec!["a"]);
+    }
+
+    #[test]
+    fn resolve_all_dedups() {
+        let cfg = grouped_config();
+        let names = vec!["research".to_string(), "all".to_string()];
+        assert_eq!(cfg.resolve_all(&names).unwrap(), vec!["b", "a", "c"]);
     }
 
     #[test]
@@ -116,11 +546,27 @@ mod tests {
                 env: HashMap::new(),
                 headers: HashMap::new(),
                 lifecycle: Some(Lifecycle::Ephemeral),
+                tool_pins: HashMap::new(),
+                disabled: false,
+                install_timeout: None,
+                log_level: None,
+                sensitive_args: Vec::new(),
+                timeout_ms: None,
+                list_timeout_ms: None,
+                allow_http: None,
+                auth: None,
+                tags: Vec::new(),
+                transport: None,
+                retry: None,
             },
         );
         let cfg = McplugConfig {
             mcp_servers: servers,
             imports: vec!["cursor".into()],
+            state: None,
+            groups: HashMap::new(),
+            extends: Vec::new(),
+            defaults: None,
         };
         let json = serde_json::to_string(&cfg).unwrap();
         let parsed: McplugConfig = serde_json::from_str(&json).unwrap();
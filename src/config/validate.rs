@@ -0,0 +1,568 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::de::{Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::env::expand_server_config;
+use super::loader::{resolve_extends_path, strip_jsonc_comments};
+use super::types::McplugConfig;
+
+/// Keys understood at the top level of a config file.
+pub const CONFIG_KEYS: &[&str] = &["mcpServers", "imports", "state", "groups", "extends", "defaults"];
+
+/// Keys understood in the `defaults` section. Keep in sync with `ServerDefaults`.
+pub const DEFAULTS_KEYS: &[&str] = &["timeoutMs", "listTimeoutMs", "lifecycle", "allowHttp", "headers"];
+
+/// Keys understood in a server entry. Keep in sync with `ServerConfig`.
+pub const SERVER_KEYS: &[&str] = &[
+    "description",
+    "baseUrl",
+    "command",
+    "args",
+    "env",
+    "headers",
+    "lifecycle",
+    "toolPins",
+    "disabled",
+    "installTimeout",
+    "logLevel",
+    "sensitiveArgs",
+    "timeoutMs",
+    "listTimeoutMs",
+    "allowHttp",
+    "auth",
+    "tags",
+    "type",
+    "retry",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The entry can't be loaded or used as written.
+    Error,
+    /// The entry loads, but probably not as intended.
+    Warning,
+}
+
+/// A problem found in a config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub path: PathBuf,
+    /// 1-based line, when the problem can be located.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}: {}", self.path.display(), line, severity, self.message),
+            None => write!(f, "{}: {}: {}", self.path.display(), severity, self.message),
+        }
+    }
+}
+
+/// Check config files, given in precedence order (highest first), and the
+/// files they `extend`.
+///
+/// Each file is checked for JSON errors, unknown keys, servers with neither
+/// `command` nor `baseUrl`, env references that don't resolve, and server
+/// names defined twice. Missing and circular `extends` entries are errors.
+/// Across files, a server shadowed by a higher-precedence file is reported
+/// as a warning.
+pub fn validate_files(paths: &[PathBuf]) -> Vec<Issue> {
+    let mut checker = Checker::default();
+    for path in paths {
+        checker.check(path);
+    }
+    checker.issues
+}
+
+#[derive(Default)]
+struct Checker {
+    issues: Vec<Issue>,
+    /// First (highest-precedence) file defining each server.
+    defined_in: HashMap<String, PathBuf>,
+    /// Canonical paths already checked.
+    seen: Vec<PathBuf>,
+    /// Canonical paths of the files currently being extended.
+    stack: Vec<PathBuf>,
+}
+
+impl Checker {
+    fn check(&mut self, path: &Path) {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if self.seen.contains(&canonical) {
+            return;
+        }
+        self.seen.push(canonical.clone());
+
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                self.issues.push(issue(Severity::Error, path, None, format!("Cannot read file: {e}")));
+                return;
+            }
+        };
+        let Some((names, extends)) = validate_file(path, &content, &mut self.issues) else {
+            return;
+        };
+        let stripped = strip_jsonc_comments(&content);
+        for name in names {
+            match self.defined_in.get(&name) {
+                // Duplicates within the file were reported above
+                Some(first) if first == path => {}
+                Some(first) => self.issues.push(issue(
+                    Severity::Warning,
+                    path,
+                    server_line(&stripped, &name),
+                    format!(
+                        "Server '{name}' is also defined in {}, which takes precedence",
+                        first.display()
+                    ),
+                )),
+                None => {
+                    self.defined_in.insert(name, path.to_path_buf());
+                }
+            }
+        }
+
+        let line = key_line(&stripped, "extends", 0);
+        self.stack.push(canonical);
+        // Later entries take precedence, as in the loader
+        for base in extends.iter().rev() {
+            let base_path = resolve_extends_path(path, base);
+            let base_canonical = base_path.canonicalize().unwrap_or_else(|_| base_path.clone());
+            if !base_path.exists() {
+                self.issues.push(issue(
+                    Severity::Error,
+                    path,
+                    line,
+                    format!("Extended config '{base}' not found at {}", base_path.display()),
+                ));
+            } else if self.stack.contains(&base_canonical) {
+                self.issues.push(issue(
+                    Severity::Error,
+                    path,
+                    line,
+                    format!("Circular extends: '{base}' extends this file"),
+                ));
+            } else {
+                self.check(&base_path);
+            }
+        }
+        self.stack.pop();
+    }
+}
+
+/// Check one file's content, returning its server names and `extends`
+/// entries if it parses.
+fn validate_file(
+    path: &Path,
+    content: &str,
+    issues: &mut Vec<Issue>,
+) -> Option<(Vec<String>, Vec<String>)> {
+    let stripped = strip_jsonc_comments(content);
+    let value: Value = match serde_json::from_str(&stripped) {
+        Ok(value) => value,
+        Err(e) => {
+            issues.push(issue(Severity::Error, path, Some(e.line()), format!("Invalid JSON: {e}")));
+            return None;
+        }
+    };
+    let config: McplugConfig = match serde_json::from_str(&stripped) {
+        Ok(config) => config,
+        Err(e) => {
+            issues.push(issue(Severity::Error, path, Some(e.line()), format!("Invalid config: {e}")));
+            return None;
+        }
+    };
+
+    if let Some(object) = value.as_object() {
+        for key in object.keys().filter(|key| !CONFIG_KEYS.contains(&key.as_str())) {
+            issues.push(issue(
+                Severity::Warning,
+                path,
+                key_line(&stripped, key, 0),
+                format!("Unknown key '{key}'"),
+            ));
+        }
+    }
+
+    if let Some(defaults) = value.get("defaults").and_then(Value::as_object) {
+        let start = key_offset(&stripped, "defaults", 0, 0);
+        for key in defaults.keys().filter(|key| !DEFAULTS_KEYS.contains(&key.as_str())) {
+            issues.push(issue(
+                Severity::Warning,
+                path,
+                start.and_then(|offset| key_line(&stripped, key, offset)),
+                format!("defaults: unknown key '{key}'"),
+            ));
+        }
+    }
+
+    // serde_json keeps only the last of duplicate keys, so list them separately
+    let names = serde_json::from_str::<ServerNames>(&stripped)
+        .map(|names| names.servers.0)
+        .unwrap_or_default();
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for name in &names {
+        let count = seen.entry(name).or_insert(0);
+        *count += 1;
+        if *count == 2 {
+            issues.push(issue(
+                Severity::Error,
+                path,
+                nth_server_line(&stripped, name, 1),
+                format!("Server '{name}' is defined more than once; only the last definition is used"),
+            ));
+        }
+    }
+
+    let servers = value.get("mcpServers").and_then(Value::as_object);
+    let mut sorted: Vec<_> = config.mcp_servers.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, server) in sorted {
+        let start = server_offset(&stripped, name, 0);
+        let line = start.map(|offset| line_at(&stripped, offset));
+        if let Some(entry) = servers.and_then(|s| s.get(name)).and_then(Value::as_object) {
+            for key in entry.keys().filter(|key| !SERVER_KEYS.contains(&key.as_str())) {
+                let key_line = start.and_then(|offset| key_line(&stripped, key, offset));
+                issues.push(issue(
+                    Severity::Warning,
+                    path,
+                    key_line.or(line),
+                    format!("Server '{name}': unknown key '{key}'"),
+                ));
+            }
+        }
+        match (server.transport.as_deref(), &server.command, &server.base_url) {
+            (Some("stdio"), None, _) => issues.push(issue(
+                Severity::Error,
+                path,
+                line,
+                format!("Server '{name}' has type 'stdio' but no 'command'"),
+            )),
+            (Some(kind @ ("http" | "sse")), _, None) => issues.push(issue(
+                Severity::Error,
+                path,
+                line,
+                format!("Server '{name}' has type '{kind}' but no 'baseUrl'"),
+            )),
+            (None, None, None) => issues.push(issue(
+                Severity::Error,
+                path,
+                line,
+                format!("Server '{name}' has neither 'command' nor 'baseUrl'"),
+            )),
+            (None, Some(_), Some(_)) => issues.push(issue(
+                Severity::Warning,
+                path,
+                line,
+                format!("Server '{name}' has both 'command' and 'baseUrl'; 'baseUrl' is used"),
+            )),
+            _ => {}
+        }
+        if let Err(e) = expand_server_config(&mut server.clone()) {
+            let detail = match e {
+                crate::error::McplugError::ConfigError { detail, .. } => detail,
+                other => other.to_string(),
+            };
+            issues.push(issue(Severity::Error, path, line, format!("Server '{name}': {detail}")));
+        }
+    }
+
+    let groups = key_offset(&stripped, "groups", 0, 0);
+    for group in config.groups.keys() {
+        if config.mcp_servers.contains_key(group) {
+            issues.push(issue(
+                Severity::Warning,
+                path,
+                groups.and_then(|offset| key_line(&stripped, group, offset)),
+                format!("Group '{group}' is shadowed by the server of the same name"),
+            ));
+        }
+    }
+
+    Some((names, config.extends))
+}
+
+fn issue(severity: Severity, path: &Path, line: Option<usize>, message: String) -> Issue {
+    Issue {
+        severity,
+        path: path.to_path_buf(),
+        line,
+        message,
+    }
+}
+
+/// The `mcpServers` keys of a file, in order and including duplicates.
+#[derive(Deserialize)]
+struct ServerNames {
+    #[serde(default, rename = "mcpServers")]
+    servers: KeyList,
+}
+
+#[derive(Default)]
+struct KeyList(Vec<String>);
+
+impl<'de> Deserialize<'de> for KeyList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeysVisitor;
+
+        impl<'de> Visitor<'de> for KeysVisitor {
+            type Value = KeyList;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<KeyList, A::Error> {
+                let mut keys = Vec::new();
+                while let Some((key, IgnoredAny)) = map.next_entry::<String, IgnoredAny>()? {
+                    keys.push(key);
+                }
+                Ok(KeyList(keys))
+            }
+        }
+
+        deserializer.deserialize_map(KeysVisitor)
+    }
+}
+
+/// Byte offset of the `n`th (0-based) `"key":` at or after `from`.
+fn key_offset(text: &str, key: &str, from: usize, n: usize) -> Option<usize> {
+    let quoted = format!("\"{key}\"");
+    let mut start = from;
+    let mut found = 0;
+    while let Some(pos) = text.get(start..)?.find(&quoted) {
+        let at = start + pos;
+        let after = at + quoted.len();
+        if text[after..].trim_start().starts_with(':') {
+            if found == n {
+                return Some(at);
+            }
+            found += 1;
+        }
+        start = after;
+    }
+    None
+}
+
+fn key_line(text: &str, key: &str, from: usize) -> Option<usize> {
+    key_offset(text, key, from, 0).map(|offset| line_at(text, offset))
+}
+
+/// Offset of the `n`th definition of server `name` within `mcpServers`.
+fn server_offset(text: &str, name: &str, n: usize) -> Option<usize> {
+    let servers = key_offset(text, "mcpServers", 0, 0)?;
+    key_offset(text, name, servers, n)
+}
+
+fn server_line(text: &str, name: &str) -> Option<usize> {
+    nth_server_line(text, name, 0)
+}
+
+fn nth_server_line(text: &str, name: &str, n: usize) -> Option<usize> {
+    server_offset(text, name, n).map(|offset| line_at(text, offset))
+}
+
+fn line_at(text: &str, offset: usize) -> usize {
+    text[..offset].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(content: &str) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        validate_file(Path::new("mcplug.json"), content, &mut issues);
+        issues
+    }
+
+    #[test]
+    fn valid_config_has_no_issues() {
+        let issues = check(
+            r#"{
+  // comment
+  "mcpServers": {
+    "web": {"baseUrl": "https://example.com/mcp"},
+    "local": {"command": "npx", "args": ["-y", "server"]}
+  },
+  "groups": {"all": ["web", "local"]}
+}"#,
+        );
+        assert!(issues.is_empty(), "{issues:?}");
+    }
+
+    #[test]
+    fn json_errors_report_line() {
+        let issues = check("{\n  \"mcpServers\": {\n    \"a\": {,}\n  }\n}");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].line, Some(3));
+        assert!(issues[0].message.starts_with("Invalid JSON"));
+
+        let issues = check("{\n  \"mcpServers\": {\n    \"a\": {\"args\": \"x\"}\n  }\n}");
+        assert_eq!(issues[0].line, Some(3));
+        assert!(issues[0].message.starts_with("Invalid config"));
+    }
+
+    #[test]
+    fn unknown_keys_are_warnings_with_lines() {
+        let issues = check(
+            r#"{
+  "servers": {},
+  "mcpServers": {
+    "a": {
+      "command": "x",
+      "baseURL": "https://example.com"
+    }
+  }
+}"#,
+        );
+        let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "mcplug.json:2: warning: Unknown key 'servers'",
+                "mcplug.json:6: warning: Server 'a': unknown key 'baseURL'",
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_defaults_key_is_a_warning() {
+        let issues = check(
+            "{\n  \"defaults\": {\n    \"timeoutMs\": 1000,\n    \"timeout\": 1000\n  }\n}",
+        );
+        let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        assert_eq!(messages, vec!["mcplug.json:4: warning: defaults: unknown key 'timeout'"]);
+    }
+
+    #[test]
+    fn server_without_transport_is_an_error() {
+        let issues = check("{\"mcpServers\": {\n\"a\": {\"description\": \"x\"}}}");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].line, Some(2));
+        assert!(issues[0].message.contains("neither 'command' nor 'baseUrl'"));
+    }
+
+    #[test]
+    fn transport_type_decides_required_fields() {
+        let issues = check(
+            r#"{"mcpServers": {"a": {"type": "grpc"}, "b": {"type": "http", "command": "x"}}}"#,
+        );
+        let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(messages, vec!["Server 'b' has type 'http' but no 'baseUrl'"]);
+    }
+
+    #[test]
+    fn unresolved_env_reference_is_an_error() {
+        let issues = check(
+            r#"{"mcpServers": {"a": {"command": "x", "env": {"K": "${MCPLUG_VALIDATE_UNSET_XYZ}"}}}}"#,
+        );
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("MCPLUG_VALIDATE_UNSET_XYZ"));
+        assert!(issues[0].message.contains("not set"));
+    }
+
+    #[test]
+    fn duplicate_server_in_file_is_an_error() {
+        let issues = check(
+            "{\"mcpServers\": {\n\"a\": {\"command\": \"x\"},\n\"a\": {\"command\": \"y\"}\n}}",
+        );
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, Some(3));
+        assert!(issues[0].message.contains("defined more than once"));
+    }
+
+    #[test]
+    fn shadowed_servers_across_files_are_warnings() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project.json");
+        let home = dir.path().join("home.json");
+        std::fs::write(&project, r#"{"mcpServers": {"a": {"command": "x"}}}"#).unwrap();
+        std::fs::write(
+            &home,
+            "{\"mcpServers\": {\n\"b\": {\"command\": \"y\"},\n\"a\": {\"command\": \"z\"}}}",
+        )
+        .unwrap();
+        let issues = validate_files(&[project.clone(), home.clone()]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert_eq!(issues[0].path, home);
+        assert_eq!(issues[0].line, Some(3));
+        assert!(issues[0].message.contains(&project.display().to_string()));
+    }
+
+    #[test]
+    fn extends_are_followed_and_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("mcplug.json");
+        let base = dir.path().join("base.json");
+        std::fs::write(
+            &project,
+            "{\"extends\": [\"base.json\", \"gone.json\"],\n\"mcpServers\": {\"a\": {\"command\": \"x\"}}}",
+        )
+        .unwrap();
+        std::fs::write(
+            &base,
+            r#"{"extends": ["mcplug.json"], "mcpServers": {"a": {"command": "y"}, "b": {}}}"#,
+        )
+        .unwrap();
+
+        let issues = validate_files(std::slice::from_ref(&project));
+        let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(issues.len(), 4, "{messages:?}");
+        assert!(messages[0].starts_with("Extended config 'gone.json' not found"));
+        assert_eq!(issues[0].line, Some(1));
+        // base.json is checked too, with project taking precedence
+        assert!(messages.contains(&"Server 'b' has neither 'command' nor 'baseUrl'"));
+        assert!(messages.iter().any(|m| m.starts_with("Server 'a' is also defined in")));
+        assert!(messages.contains(&"Circular extends: 'mcplug.json' extends this file"));
+    }
+
+    #[test]
+    fn server_keys_match_server_config() {
+        let entry = serde_json::json!({
+            "description": "d",
+            "baseUrl": "https://example.com",
+            "command": "c",
+            "args": ["a"],
+            "env": {"K": "v"},
+            "headers": {"H": "v"},
+            "lifecycle": "keep-alive",
+            "toolPins": {"t": "sha256:x"},
+            "disabled": true,
+            "installTimeout": 1,
+            "logLevel": "debug",
+            "sensitiveArgs": ["token"],
+            "timeoutMs": 1,
+            "listTimeoutMs": 1,
+            "allowHttp": true,
+            "auth": {"type": "bearer", "token": "t"},
+            "tags": ["search"],
+            "type": "stdio",
+            "retry": {"maxAttempts": 2}
+        });
+        let cfg: super::super::types::ServerConfig =
+            serde_json::from_value(entry.clone()).unwrap();
+        let round_trip = serde_json::to_value(cfg).unwrap();
+        let mut keys: Vec<&str> = round_trip.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        let mut expected = SERVER_KEYS.to_vec();
+        expected.sort();
+        assert_eq!(keys, expected);
+    }
+}
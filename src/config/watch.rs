@@ -0,0 +1,190 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use super::loader::{discover_config_files, load_config_layers};
+use super::types::McplugConfig;
+
+/// What a file looked like when last checked: modification time and size,
+/// or `None` if it couldn't be read.
+type Stamp = Option<(SystemTime, u64)>;
+
+/// Notices edits to the discovered config files (and the files they
+/// `extends`) by comparing their modification times and sizes on each
+/// `changed` call, so long-lived modes can reload between operations.
+///
+/// Discovery is repeated on every check, so a config file that appears or
+/// disappears counts as a change too.
+pub struct ConfigWatcher {
+    cli_config: Option<String>,
+    stamps: Vec<(PathBuf, Stamp)>,
+}
+
+impl ConfigWatcher {
+    /// Start watching the files `load_config(cli_config)` would read.
+    pub fn new(cli_config: Option<&str>) -> Self {
+        let cli_config = cli_config.map(str::to_string);
+        let stamps = snapshot(cli_config.as_deref());
+        Self { cli_config, stamps }
+    }
+
+    /// The files currently watched.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.stamps.iter().map(|(path, _)| path.clone()).collect()
+    }
+
+    /// Whether any watched file changed since the last check.
+    pub fn changed(&mut self) -> bool {
+        let stamps = snapshot(self.cli_config.as_deref());
+        if stamps == self.stamps {
+            return false;
+        }
+        self.stamps = stamps;
+        true
+    }
+}
+
+fn snapshot(cli_config: Option<&str>) -> Vec<(PathBuf, Stamp)> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for path in discover_config_files(cli_config) {
+        let layers = match load_config_layers(&path) {
+            Ok(layers) => layers.into_iter().map(|(p, _)| p).collect(),
+            // Still watch a broken file, so fixing it is noticed
+            Err(_) => vec![path],
+        };
+        for layer in layers {
+            if !files.contains(&layer) {
+                files.push(layer);
+            }
+        }
+    }
+    files
+        .into_iter()
+        .map(|path| {
+            let stamp = std::fs::metadata(&path)
+                .ok()
+                .and_then(|m| Some((m.modified().ok()?, m.len())));
+            (path, stamp)
+        })
+        .collect()
+}
+
+/// Servers added, removed, or edited between two configs, each sorted.
+#[derive(Debug, Default, PartialEq)]
+pub struct ConfigChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl ConfigChanges {
+    /// Compare the servers of `old` and `new`. Disabling a server counts as
+    /// removing it, and enabling one as adding it.
+    pub fn between(old: &McplugConfig, new: &McplugConfig) -> Self {
+        let old_names = old.enabled_servers();
+        let new_names = new.enabled_servers();
+        let mut changes = Self::default();
+        for name in &new_names {
+            if !old_names.contains(name) {
+                changes.added.push(name.clone());
+            } else if old.mcp_servers.get(name) != new.mcp_servers.get(name) {
+                changes.changed.push(name.clone());
+            }
+        }
+        changes.removed = old_names
+            .into_iter()
+            .filter(|name| !new_names.contains(name))
+            .collect();
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Whether `server` was removed or edited, so an open connection to it
+    /// is stale.
+    pub fn affects(&self, server: &str) -> bool {
+        self.removed.iter().chain(&self.changed).any(|s| s == server)
+    }
+}
+
+impl std::fmt::Display for ConfigChanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        for (label, names) in [
+            ("added", &self.added),
+            ("removed", &self.removed),
+            ("changed", &self.changed),
+        ] {
+            if !names.is_empty() {
+                parts.push(format!("{label} {}", names.join(", ")));
+            }
+        }
+        if parts.is_empty() {
+            write!(f, "no server changes")
+        } else {
+            write!(f, "{}", parts.join("; "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> McplugConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn changes_between_configs() {
+        let old = config(
+            r#"{"mcpServers": {
+                "a": {"command": "a"},
+                "b": {"command": "b"},
+                "c": {"command": "c"},
+                "d": {"command": "d", "disabled": true}
+            }}"#,
+        );
+        let new = config(
+            r#"{"mcpServers": {
+                "a": {"command": "a"},
+                "b": {"command": "b", "args": ["--new"]},
+                "d": {"command": "d"},
+                "e": {"command": "e"}
+            }}"#,
+        );
+        let changes = ConfigChanges::between(&old, &new);
+        assert_eq!(changes.added, vec!["d", "e"]);
+        assert_eq!(changes.removed, vec!["c"]);
+        assert_eq!(changes.changed, vec!["b"]);
+        assert!(changes.affects("b") && changes.affects("c"));
+        assert!(!changes.affects("a") && !changes.affects("e"));
+        assert_eq!(changes.to_string(), "added d, e; removed c; changed b");
+    }
+
+    #[test]
+    fn identical_configs_have_no_changes() {
+        let cfg = config(r#"{"mcpServers": {"a": {"command": "a"}}}"#);
+        let changes = ConfigChanges::between(&cfg, &cfg.clone());
+        assert!(changes.is_empty());
+        assert_eq!(changes.to_string(), "no server changes");
+    }
+
+    #[test]
+    fn watcher_notices_edits_and_bases() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("mcplug.json");
+        let base = dir.path().join("base.json");
+        std::fs::write(&base, r#"{"mcpServers": {}}"#).unwrap();
+        std::fs::write(&main, r#"{"extends": ["base.json"]}"#).unwrap();
+
+        let mut watcher = ConfigWatcher::new(main.to_str());
+        assert!(watcher.files().contains(&base));
+        assert!(!watcher.changed());
+
+        std::fs::write(&base, r#"{"mcpServers": {"x": {"command": "x"}}}"#).unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+    }
+}
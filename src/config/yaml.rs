@@ -0,0 +1,242 @@
+use serde_json::{Map, Value};
+
+/// Parse the block-style YAML subset that editor configs use (Goose,
+/// Continue) into JSON: nested mappings and sequences by indentation,
+/// `- key: value` sequence items, plain and quoted scalars, `[a, b]` flow
+/// sequences, and `#` comments. `true`/`false` become booleans and
+/// `null`/`~` null; every other scalar stays a string.
+///
+/// Returns `None` for input outside the subset rather than guessing.
+pub fn parse(input: &str) -> Option<Value> {
+    let mut lines: Vec<(usize, String)> = input
+        .lines()
+        .map(strip_comment)
+        .filter(|line| !line.trim().is_empty() && line.trim() != "---")
+        .map(|line| {
+            let indent = line.len() - line.trim_start().len();
+            (indent, line.trim().to_string())
+        })
+        .collect();
+    if lines.is_empty() {
+        return Some(Value::Object(Map::new()));
+    }
+    let mut pos = 0;
+    let indent = lines[0].0;
+    let value = parse_block(&mut lines, &mut pos, indent)?;
+    (pos == lines.len()).then_some(value)
+}
+
+fn parse_block(lines: &mut [(usize, String)], pos: &mut usize, indent: usize) -> Option<Value> {
+    if is_item(&lines[*pos].1) {
+        parse_sequence(lines, pos, indent)
+    } else {
+        parse_mapping(lines, pos, indent)
+    }
+}
+
+fn is_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+fn parse_sequence(lines: &mut [(usize, String)], pos: &mut usize, indent: usize) -> Option<Value> {
+    let mut items = Vec::new();
+    while *pos < lines.len() && lines[*pos].0 == indent && is_item(&lines[*pos].1) {
+        let rest = lines[*pos].1[1..].trim_start().to_string();
+        if rest.is_empty() {
+            *pos += 1;
+            let child = lines.get(*pos).map(|l| l.0).filter(|i| *i > indent)?;
+            items.push(parse_block(lines, pos, child)?);
+        } else if split_key(&rest).is_some() {
+            // `- key: value` starts a mapping indented past the dash
+            let child = indent + (lines[*pos].1.len() - rest.len());
+            lines[*pos] = (child, rest);
+            items.push(parse_mapping(lines, pos, child)?);
+        } else {
+            items.push(scalar(&rest));
+            *pos += 1;
+        }
+    }
+    Some(Value::Array(items))
+}
+
+fn parse_mapping(lines: &mut [(usize, String)], pos: &mut usize, indent: usize) -> Option<Value> {
+    let mut map = Map::new();
+    while *pos < lines.len() && lines[*pos].0 == indent {
+        let (key, rest) = split_key(&lines[*pos].1)?;
+        *pos += 1;
+        let value = if !rest.is_empty() {
+            scalar(&rest)
+        } else {
+            match lines.get(*pos) {
+                Some((i, _)) if *i > indent => parse_block(lines, pos, *i)?,
+                // Sequences may sit at the same indent as their key
+                Some((i, text)) if *i == indent && is_item(text) => {
+                    parse_sequence(lines, pos, indent)?
+                }
+                _ => Value::Null,
+            }
+        };
+        map.insert(key, value);
+    }
+    if *pos < lines.len() && lines[*pos].0 > indent {
+        return None;
+    }
+    Some(Value::Object(map))
+}
+
+/// Split `key: value` (or `key:`) into the unquoted key and the value text.
+fn split_key(text: &str) -> Option<(String, String)> {
+    let (key, rest) = if text.starts_with('"') || text.starts_with('\'') {
+        let end = closing_quote(text)?;
+        let rest = text[end + 1..].trim_start().strip_prefix(':')?;
+        (unquote(&text[..=end]), rest)
+    } else {
+        let at = text
+            .find(": ")
+            .or_else(|| text.ends_with(':').then(|| text.len() - 1))?;
+        (text[..at].trim().to_string(), &text[at + 1..])
+    };
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((key, rest.trim().to_string()))
+}
+
+fn scalar(text: &str) -> Value {
+    match text {
+        "true" | "True" => Value::Bool(true),
+        "false" | "False" => Value::Bool(false),
+        "null" | "~" => Value::Null,
+        "{}" => Value::Object(Map::new()),
+        _ if text.starts_with('[') && text.ends_with(']') => {
+            let inner = text[1..text.len() - 1].trim();
+            if inner.is_empty() {
+                return Value::Array(Vec::new());
+            }
+            Value::Array(inner.split(',').map(|item| scalar(item.trim())).collect())
+        }
+        _ if text.starts_with('"') || text.starts_with('\'') => Value::String(unquote(text)),
+        _ => Value::String(text.to_string()),
+    }
+}
+
+/// Index of the quote closing the one `text` starts with.
+fn closing_quote(text: &str) -> Option<usize> {
+    let quote = text.chars().next()?;
+    let bytes = text.as_bytes();
+    let mut i = 1;
+    while i < bytes.len() {
+        match bytes[i] as char {
+            '\\' if quote == '"' => i += 1,
+            '\'' if quote == '\'' && bytes.get(i + 1) == Some(&b'\'') => i += 1,
+            c if c == quote => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn unquote(text: &str) -> String {
+    let Some(end) = closing_quote(text) else {
+        return text.to_string();
+    };
+    let inner = &text[1..end];
+    if text.starts_with('\'') {
+        return inner.replace("''", "'");
+    }
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Drop a trailing `# comment` that isn't inside quotes.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut prev = ' ';
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' && prev.is_whitespace() => return &line[..i],
+            None => {}
+        }
+        prev = c;
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn nested_mappings_and_sequences() {
+        let doc = r#"
+# Goose config
+extensions:
+  github:
+    cmd: npx
+    args:
+    - -y
+    - '@modelcontextprotocol/server-github'
+    enabled: true
+    envs:
+      GITHUB_TOKEN: "ghp_x#1"  # inline comment
+  empty: {}
+"#;
+        assert_eq!(
+            parse(doc).unwrap(),
+            json!({"extensions": {
+                "github": {
+                    "cmd": "npx",
+                    "args": ["-y", "@modelcontextprotocol/server-github"],
+                    "enabled": true,
+                    "envs": {"GITHUB_TOKEN": "ghp_x#1"}
+                },
+                "empty": {}
+            }})
+        );
+    }
+
+    #[test]
+    fn sequence_of_mappings() {
+        let doc = "mcpServers:\n  - name: fs\n    command: npx\n    args: [\"-y\", server]\n  - name: web\n    url: https://example.com/mcp\n";
+        assert_eq!(
+            parse(doc).unwrap(),
+            json!({"mcpServers": [
+                {"name": "fs", "command": "npx", "args": ["-y", "server"]},
+                {"name": "web", "url": "https://example.com/mcp"}
+            ]})
+        );
+    }
+
+    #[test]
+    fn scalars_stay_strings_except_bools_and_null() {
+        let doc = "port: 8080\nquoted: 'it''s'\nnothing: ~\nurl: http://x:1/a";
+        assert_eq!(
+            parse(doc).unwrap(),
+            json!({"port": "8080", "quoted": "it's", "nothing": null, "url": "http://x:1/a"})
+        );
+    }
+
+    #[test]
+    fn rejects_bad_indentation() {
+        assert!(parse("a:\n    b: 1\n  c: 2").is_none());
+        assert_eq!(parse("").unwrap(), json!({}));
+    }
+}
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
 use crate::error::McplugError;
+use crate::stats::RuntimeStats;
 
 /// Status information for the daemon.
 #[derive(Debug, serde::Serialize)]
@@ -9,6 +10,8 @@ pub struct DaemonStatus {
     pub pid: Option<u32>,
     pub uptime_secs: Option<u64>,
     pub managed_servers: Vec<String>,
+    /// Calls the daemon has made for its servers (`Runtime::stats`).
+    pub call_stats: Option<RuntimeStats>,
 }
 
 /// Manages the mcplug background daemon process.
@@ -123,6 +126,7 @@ impl DaemonManager {
             pid,
             uptime_secs: None, // Would need start time tracking
             managed_servers: vec![],
+            call_stats: None, // Reported by the daemon once it serves status over the socket
         })
     }
 }
@@ -255,12 +259,14 @@ mod tests {
             pid: Some(1234),
             uptime_secs: Some(600),
             managed_servers: vec!["server-a".to_string(), "server-b".to_string()],
+            call_stats: Some(RuntimeStats::default()),
         };
         let json = serde_json::to_value(&status).unwrap();
         assert_eq!(json["running"], true);
         assert_eq!(json["pid"], 1234);
         assert_eq!(json["uptime_secs"], 600);
         assert_eq!(json["managed_servers"].as_array().unwrap().len(), 2);
+        assert!(json["call_stats"]["servers"].as_object().unwrap().is_empty());
     }
 
     #[test]
@@ -6,8 +6,13 @@ pub enum McplugError {
     #[error("Server '{0}' not found. Available: (none loaded)")]
     ServerNotFound(String),
 
-    #[error("Tool '{tool}' not found on {server}.")]
-    ToolNotFound { server: String, tool: String },
+    #[error("Tool '{tool}' not found on {server}.{}", format_suggestion(.suggestion.as_deref()))]
+    ToolNotFound {
+        server: String,
+        tool: String,
+        /// A tool of the server with a similar name, if there is one.
+        suggestion: Option<String>,
+    },
 
     #[error("Cannot connect to {server}: {source}")]
     ConnectionFailed {
@@ -22,6 +27,28 @@ pub enum McplugError {
         duration: Duration,
     },
 
+    #[error("Call to {server}.{tool} was cancelled")]
+    Cancelled { server: String, tool: String },
+
+    #[error(
+        "Input schema of {server}.{tool} changed: pinned {expected}, server reports {actual}. \
+         Update toolPins to accept the new schema."
+    )]
+    SchemaPinMismatch {
+        server: String,
+        tool: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("Result of {server}.{tool} does not match {expected}: {detail}")]
+    ResultMismatch {
+        server: String,
+        tool: String,
+        expected: String,
+        detail: String,
+    },
+
     #[error("Server '{0}' requires authentication. Run: mcplug auth {0}")]
     AuthRequired(String),
 
@@ -41,11 +68,22 @@ pub enum McplugError {
     IoError(#[from] std::io::Error),
 }
 
+fn format_suggestion(suggestion: Option<&str>) -> String {
+    suggestion
+        .map(|s| format!(" Did you mean '{s}'?"))
+        .unwrap_or_default()
+}
+
 fn format_timeout(server: &str, tool: Option<&str>, duration: &Duration) -> String {
-    let secs = duration.as_secs();
+    // Per-server timeouts are set in milliseconds and need not be whole seconds
+    let after = if duration.subsec_millis() == 0 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format!("{}ms", duration.as_millis())
+    };
     match tool {
-        Some(t) => format!("Timeout after {secs}s calling {server}.{t}"),
-        None => format!("Timeout after {secs}s calling {server}"),
+        Some(t) => format!("Timeout after {after} calling {server}.{t}"),
+        None => format!("Timeout after {after} calling {server}"),
     }
 }
 
@@ -57,6 +95,9 @@ impl McplugError {
             McplugError::ToolNotFound { .. } => "not_found",
             McplugError::ConnectionFailed { .. } => "connection_refused",
             McplugError::Timeout { .. } => "timeout",
+            McplugError::Cancelled { .. } => "cancelled",
+            McplugError::SchemaPinMismatch { .. } => "pin_mismatch",
+            McplugError::ResultMismatch { .. } => "result_mismatch",
             McplugError::AuthRequired(_) => "auth_required",
             McplugError::ConfigError { .. } => "config_error",
             McplugError::TransportError(_) => "transport_error",
@@ -72,6 +113,9 @@ impl McplugError {
             McplugError::ToolNotFound { server, .. } => Some(server),
             McplugError::ConnectionFailed { server, .. } => Some(server),
             McplugError::Timeout { server, .. } => Some(server),
+            McplugError::Cancelled { server, .. } => Some(server),
+            McplugError::SchemaPinMismatch { server, .. } => Some(server),
+            McplugError::ResultMismatch { server, .. } => Some(server),
             McplugError::AuthRequired(s) => Some(s),
             _ => None,
         }
@@ -81,6 +125,9 @@ impl McplugError {
         match self {
             McplugError::ToolNotFound { tool, .. } => Some(tool),
             McplugError::Timeout { tool, .. } => tool.as_deref(),
+            McplugError::Cancelled { tool, .. } => Some(tool),
+            McplugError::SchemaPinMismatch { tool, .. } => Some(tool),
+            McplugError::ResultMismatch { tool, .. } => Some(tool),
             _ => None,
         }
     }
@@ -94,6 +141,13 @@ impl McplugError {
         if let Some(tool) = self.tool_name() {
             obj.insert("tool".into(), serde_json::Value::String(tool.to_string()));
         }
+        if let McplugError::ToolNotFound {
+            suggestion: Some(suggestion),
+            ..
+        } = self
+        {
+            obj.insert("suggestion".into(), serde_json::Value::String(suggestion.clone()));
+        }
         obj.insert("message".into(), serde_json::Value::String(self.to_string()));
         obj.insert("code".into(), serde_json::Value::String(self.code().to_string()));
         serde_json::json!({ "error": obj })
@@ -118,10 +172,25 @@ mod tests {
         let err = McplugError::ToolNotFound {
             server: "firecrawl".into(),
             tool: "scrap".into(),
+            suggestion: None,
         };
         assert_eq!(err.to_string(), "Tool 'scrap' not found on firecrawl.");
     }
 
+    #[test]
+    fn display_tool_not_found_with_suggestion() {
+        let err = McplugError::ToolNotFound {
+            server: "firecrawl".into(),
+            tool: "scrap".into(),
+            suggestion: Some("scrape".into()),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Tool 'scrap' not found on firecrawl. Did you mean 'scrape'?"
+        );
+        assert_eq!(err.to_json()["error"]["suggestion"], "scrape");
+    }
+
     #[test]
     fn display_connection_failed() {
         let err = McplugError::ConnectionFailed {
@@ -157,6 +226,51 @@ mod tests {
         assert_eq!(err.to_string(), "Timeout after 30s calling firecrawl");
     }
 
+    #[test]
+    fn timeout_display_sub_second() {
+        let err = McplugError::Timeout {
+            server: "crawler".into(),
+            tool: Some("crawl".into()),
+            duration: Duration::from_millis(1500),
+        };
+        assert_eq!(err.to_string(), "Timeout after 1500ms calling crawler.crawl");
+    }
+
+    #[test]
+    fn display_schema_pin_mismatch() {
+        let err = McplugError::SchemaPinMismatch {
+            server: "firecrawl".into(),
+            tool: "scrape".into(),
+            expected: "sha256:aaa".into(),
+            actual: "sha256:bbb".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Input schema of firecrawl.scrape changed: pinned sha256:aaa, server reports \
+             sha256:bbb. Update toolPins to accept the new schema."
+        );
+        assert_eq!(err.code(), "pin_mismatch");
+        assert_eq!(err.server_name(), Some("firecrawl"));
+        assert_eq!(err.tool_name(), Some("scrape"));
+    }
+
+    #[test]
+    fn display_result_mismatch() {
+        let err = McplugError::ResultMismatch {
+            server: "weather".into(),
+            tool: "forecast".into(),
+            expected: "app::Forecast".into(),
+            detail: "missing field `days`".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Result of weather.forecast does not match app::Forecast: missing field `days`"
+        );
+        assert_eq!(err.code(), "result_mismatch");
+        assert_eq!(err.server_name(), Some("weather"));
+        assert_eq!(err.tool_name(), Some("forecast"));
+    }
+
     #[test]
     fn display_auth_required() {
         let err = McplugError::AuthRequired("github".into());
@@ -184,6 +298,17 @@ mod tests {
         assert_eq!(err.to_string(), "Transport error: pipe broken");
     }
 
+    #[test]
+    fn display_cancelled() {
+        let err = McplugError::Cancelled {
+            server: "db".into(),
+            tool: "query".into(),
+        };
+        assert_eq!(err.to_string(), "Call to db.query was cancelled");
+        assert_eq!(err.code(), "cancelled");
+        assert_eq!(err.to_json()["error"]["tool"], "query");
+    }
+
     #[test]
     fn display_protocol_error() {
         let err = McplugError::ProtocolError("unexpected message type".into());
@@ -201,7 +326,8 @@ mod tests {
         assert_eq!(
             McplugError::ToolNotFound {
                 server: "s".into(),
-                tool: "t".into()
+                tool: "t".into(),
+                suggestion: None,
             }
             .code(),
             "not_found"
@@ -247,6 +373,7 @@ mod tests {
         let err = McplugError::ToolNotFound {
             server: "myserver".into(),
             tool: "mytool".into(),
+            suggestion: None,
         };
         let json = err.to_json();
         let error_obj = json.get("error").expect("should have error key");
@@ -265,7 +392,8 @@ mod tests {
         assert_eq!(
             McplugError::ToolNotFound {
                 server: "s2".into(),
-                tool: "t".into()
+                tool: "t".into(),
+                suggestion: None,
             }
             .server_name(),
             Some("s2")
@@ -302,7 +430,8 @@ mod tests {
         assert_eq!(
             McplugError::ToolNotFound {
                 server: "s".into(),
-                tool: "mytool".into()
+                tool: "mytool".into(),
+                suggestion: None,
             }
             .tool_name(),
             Some("mytool")
@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::McplugError;
+use crate::state::StateStore;
+
+/// State namespace holding call history (`~/.mcplug/history` by default) in
+/// stores without a [`HistoryLog`].
+pub const NAMESPACE: &str = "history";
+
+/// Oldest entries beyond this many are pruned when a call is recorded in the
+/// `history` namespace. A [`HistoryLog`] keeps every call.
+const MAX_ENTRIES: usize = 1000;
+
+/// One tool call made with `mcplug call`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub server: String,
+    pub tool: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// Whether the call returned a result that is not an error.
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Call arguments, with sensitive values replaced by hash placeholders.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<serde_json::Value>,
+}
+
+impl HistoryEntry {
+    pub fn new(
+        server: &str,
+        tool: &str,
+        started_at: DateTime<Utc>,
+        duration: Duration,
+        error: Option<String>,
+    ) -> Self {
+        Self {
+            server: server.to_string(),
+            tool: tool.to_string(),
+            started_at,
+            duration_ms: duration.as_millis() as u64,
+            ok: error.is_none(),
+            error,
+            args: None,
+        }
+    }
+
+    /// Attach the call's arguments, which must already be redacted.
+    pub fn with_args(mut self, args: serde_json::Value) -> Self {
+        self.args = Some(args);
+        self
+    }
+}
+
+/// Keys sort in call order: timestamp first, then a random suffix so calls
+/// started in the same microsecond don't collide.
+fn entry_key(entry: &HistoryEntry) -> String {
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    format!(
+        "{}-{}",
+        entry.started_at.format("%Y%m%dT%H%M%S%.6fZ"),
+        &id[..8]
+    )
+}
+
+/// Call history kept by a state backend that can filter it itself, such as
+/// the SQLite store.
+pub trait HistoryLog: Send + Sync {
+    fn append(&self, entry: &HistoryEntry) -> Result<(), McplugError>;
+
+    /// Matching entries, oldest first.
+    fn query(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>, McplugError>;
+}
+
+/// Append a call to the history. Without a [`HistoryLog`], the entry goes in
+/// the `history` namespace, pruning the oldest entries past the limit.
+pub fn record(store: &dyn StateStore, entry: &HistoryEntry) -> Result<(), McplugError> {
+    if let Some(log) = store.history() {
+        return log.append(entry);
+    }
+    store.save(NAMESPACE, &entry_key(entry), entry)?;
+    let keys = store.keys(NAMESPACE)?;
+    if keys.len() > MAX_ENTRIES {
+        for key in &keys[..keys.len() - MAX_ENTRIES] {
+            store.delete(NAMESPACE, key)?;
+        }
+    }
+    Ok(())
+}
+
+/// Which history entries to return.
+#[derive(Debug, Default)]
+pub struct HistoryFilter {
+    pub server: Option<String>,
+    pub tool: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    /// Only calls that failed.
+    pub failed: bool,
+}
+
+impl HistoryFilter {
+    fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.server.as_ref().is_none_or(|s| *s == entry.server)
+            && self.tool.as_ref().is_none_or(|t| *t == entry.tool)
+            && self.since.is_none_or(|since| entry.started_at >= since)
+            && !(self.failed && entry.ok)
+    }
+}
+
+/// Matching history entries, oldest first. Unreadable entries are skipped.
+pub fn query(
+    store: &dyn StateStore,
+    filter: &HistoryFilter,
+) -> Result<Vec<HistoryEntry>, McplugError> {
+    if let Some(log) = store.history() {
+        return log.query(filter);
+    }
+    let mut entries: Vec<HistoryEntry> = store
+        .keys(NAMESPACE)?
+        .iter()
+        .filter_map(|key| store.load::<HistoryEntry>(NAMESPACE, key))
+        .filter(|entry| filter.matches(entry))
+        .collect();
+    entries.sort_by_key(|e| e.started_at);
+    Ok(entries)
+}
+
+/// Parse a relative age like `30s`, `15m`, `24h`, or `7d`.
+pub fn parse_age(input: &str) -> Result<chrono::Duration, McplugError> {
+    let invalid = || {
+        McplugError::ProtocolError(format!(
+            "Invalid age '{input}': use a number with s, m, h, or d (e.g. 24h)"
+        ))
+    };
+    let split = input.len().checked_sub(1).ok_or_else(invalid)?;
+    let (amount, unit) = input.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(invalid()),
+    };
+    amount
+        .checked_mul(seconds)
+        .and_then(chrono::Duration::try_seconds)
+        .ok_or_else(invalid)
+}
+
+/// Call counts and latency for one `server.tool`.
+#[derive(Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolStats {
+    pub calls: usize,
+    pub failed: usize,
+    pub mean_ms: f64,
+}
+
+/// Per-tool statistics keyed by `server.tool`.
+pub fn tool_stats(entries: &[HistoryEntry]) -> BTreeMap<String, ToolStats> {
+    let mut totals: BTreeMap<String, (ToolStats, u64)> = BTreeMap::new();
+    for entry in entries {
+        let (stats, total_ms) = totals
+            .entry(format!("{}.{}", entry.server, entry.tool))
+            .or_default();
+        stats.calls += 1;
+        stats.failed += usize::from(!entry.ok);
+        *total_ms += entry.duration_ms;
+    }
+    totals
+        .into_iter()
+        .map(|(name, (mut stats, total_ms))| {
+            stats.mean_ms = total_ms as f64 / stats.calls as f64;
+            (name, stats)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::FileStore;
+
+    fn entry(server: &str, tool: &str, minutes_ago: i64, ok: bool) -> HistoryEntry {
+        HistoryEntry::new(
+            server,
+            tool,
+            Utc::now() - chrono::Duration::minutes(minutes_ago),
+            Duration::from_millis(10),
+            (!ok).then(|| "boom".to_string()),
+        )
+    }
+
+    #[test]
+    fn record_and_query_with_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        check_record_and_query(&FileStore::new(dir.path()));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn record_and_query_in_sqlite() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = crate::state::SqliteStore::open(dir.path()).unwrap();
+        check_record_and_query(&store);
+        // The history has its own table, not entries in the namespace
+        assert!(store.keys(NAMESPACE).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_history_is_not_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = crate::state::SqliteStore::open(dir.path()).unwrap();
+        let args = serde_json::json!({"q": "x", "n": 1});
+        for _ in 0..MAX_ENTRIES + 5 {
+            record(&store, &entry("a", "x", 1, true).with_args(args.clone())).unwrap();
+        }
+        let all = query(&store, &HistoryFilter::default()).unwrap();
+        assert_eq!(all.len(), MAX_ENTRIES + 5);
+        assert_eq!(all[0].args.as_ref(), Some(&args));
+    }
+
+    fn check_record_and_query(store: &dyn StateStore) {
+        record(store, &entry("a", "x", 120, true)).unwrap();
+        record(store, &entry("a", "y", 5, false)).unwrap();
+        record(store, &entry("b", "x", 1, true)).unwrap();
+
+        let all = query(store, &HistoryFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert!(all.windows(2).all(|w| w[0].started_at <= w[1].started_at));
+
+        let filter = HistoryFilter {
+            server: Some("a".into()),
+            ..Default::default()
+        };
+        assert_eq!(query(store, &filter).unwrap().len(), 2);
+
+        let filter = HistoryFilter {
+            since: Some(Utc::now() - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert_eq!(query(store, &filter).unwrap().len(), 2);
+
+        let filter = HistoryFilter {
+            tool: Some("y".into()),
+            failed: true,
+            ..Default::default()
+        };
+        let failed = query(store, &filter).unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].error.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn age_parsing() {
+        assert_eq!(parse_age("30s").unwrap(), chrono::Duration::seconds(30));
+        assert_eq!(parse_age("24h").unwrap(), chrono::Duration::hours(24));
+        assert_eq!(parse_age("7d").unwrap(), chrono::Duration::days(7));
+        assert!(parse_age("").is_err());
+        assert!(parse_age("h").is_err());
+        assert!(parse_age("5w").is_err());
+        assert!(parse_age("1.5h").is_err());
+    }
+
+    #[test]
+    fn stats_per_tool() {
+        let stats = tool_stats(&[
+            entry("a", "x", 1, true),
+            entry("a", "x", 1, false),
+            entry("b", "y", 1, true),
+        ]);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(
+            stats["a.x"],
+            ToolStats {
+                calls: 2,
+                failed: 1,
+                mean_ms: 10.0
+            }
+        );
+    }
+}
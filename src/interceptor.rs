@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::error::McplugError;
+use crate::types::CallResult;
+
+/// A tool call passing through `Runtime::call_tool`.
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    pub server: String,
+    pub tool: String,
+    pub args: serde_json::Value,
+}
+
+/// Hooks run around every `Runtime::call_tool`, for logging, metrics,
+/// argument rewriting, or policy checks.
+///
+/// Interceptors run in the order they were registered. Every method has a
+/// no-op default, so implementors only write the hooks they need.
+#[async_trait]
+pub trait CallInterceptor: Send + Sync {
+    /// Runs before the call is sent. Changes to `call` (typically its
+    /// `args`) are what the server receives. Returning an error refuses the
+    /// call: no later `before` hooks run, the server is not contacted, and
+    /// the error goes to every `on_error` hook and then to the caller.
+    async fn before(&self, _call: &mut ToolCall) -> Result<(), McplugError> {
+        Ok(())
+    }
+
+    /// Runs after the server answered. Results with `isError` set are still
+    /// results and come here, not to `on_error`.
+    async fn after(&self, _call: &ToolCall, _result: &CallResult, _elapsed: Duration) {}
+
+    /// Runs when the call failed or was refused by a `before` hook.
+    async fn on_error(&self, _call: &ToolCall, _error: &McplugError, _elapsed: Duration) {}
+}
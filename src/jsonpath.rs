@@ -0,0 +1,199 @@
+use serde_json::Value;
+
+use crate::error::McplugError;
+
+/// One step of a parsed path.
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+}
+
+/// Whether a string looks like a JSONPath expression (`$`, `$.a`, `$[0]`).
+pub fn is_path(expr: &str) -> bool {
+    expr == "$" || expr.starts_with("$.") || expr.starts_with("$[")
+}
+
+/// Convert a jq-style path (`.items[0].url`, `.`) to JSONPath; `$` paths
+/// are returned unchanged.
+pub fn normalize(expr: &str) -> String {
+    let expr = expr.trim();
+    match expr {
+        "." => "$".to_string(),
+        _ if expr.starts_with(".[") => format!("${}", &expr[1..]),
+        _ if expr.starts_with('.') => format!("${expr}"),
+        _ => expr.to_string(),
+    }
+}
+
+/// Check that a path parses, without selecting anything.
+pub fn validate(path: &str) -> Result<(), McplugError> {
+    parse_path(path).map(|_| ())
+}
+
+fn path_error(path: &str, detail: &str) -> McplugError {
+    McplugError::ProtocolError(format!("Invalid JSONPath '{path}': {detail}"))
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>, McplugError> {
+    let mut rest = path
+        .strip_prefix('$')
+        .ok_or_else(|| path_error(path, "must start with '$'"))?;
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let key = &after[..end];
+            if key.is_empty() {
+                return Err(path_error(path, "empty key after '.'"));
+            }
+            segments.push(if key == "*" {
+                Segment::Wildcard
+            } else {
+                Segment::Key(key.to_string())
+            });
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after
+                .find(']')
+                .ok_or_else(|| path_error(path, "missing ']'"))?;
+            let inner = after[..end].trim();
+            let quoted = inner.len() >= 2
+                && ((inner.starts_with('\'') && inner.ends_with('\''))
+                    || (inner.starts_with('"') && inner.ends_with('"')));
+            segments.push(if inner == "*" {
+                Segment::Wildcard
+            } else if quoted {
+                Segment::Key(inner[1..inner.len() - 1].to_string())
+            } else {
+                Segment::Index(
+                    inner
+                        .parse()
+                        .map_err(|_| path_error(path, &format!("bad index '{inner}'")))?,
+                )
+            });
+            rest = &after[end + 1..];
+        } else {
+            return Err(path_error(path, "expected '.' or '['"));
+        }
+    }
+    Ok(segments)
+}
+
+/// Select a value from `value` with a JSONPath subset.
+///
+/// Supports `$`, `.key`, `['key']`, `[n]` (negative counts from the end),
+/// and `*` / `[*]`. Once a wildcard is used the result is an array of every
+/// match; otherwise it is the single selected value. A path that selects
+/// nothing is an error.
+pub fn select(value: &Value, path: &str) -> Result<Value, McplugError> {
+    let segments = parse_path(path)?;
+    let mut current = vec![value];
+    let mut multiple = false;
+
+    for segment in &segments {
+        multiple |= *segment == Segment::Wildcard;
+        let mut next = Vec::new();
+        for v in current {
+            match segment {
+                Segment::Key(key) => next.extend(v.get(key.as_str())),
+                Segment::Index(i) => {
+                    if let Value::Array(items) = v {
+                        let len = items.len() as i64;
+                        let idx = if *i < 0 { len + i } else { *i };
+                        if (0..len).contains(&idx) {
+                            next.push(&items[idx as usize]);
+                        }
+                    }
+                }
+                Segment::Wildcard => match v {
+                    Value::Array(items) => next.extend(items.iter()),
+                    Value::Object(map) => next.extend(map.values()),
+                    _ => {}
+                },
+            }
+        }
+        current = next;
+    }
+
+    if multiple {
+        return Ok(Value::Array(current.into_iter().cloned().collect()));
+    }
+    current
+        .first()
+        .map(|v| (*v).clone())
+        .ok_or_else(|| McplugError::ProtocolError(format!("JSONPath '{path}' matched nothing")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn doc() -> Value {
+        json!({
+            "id": 42,
+            "links": [{"href": "https://a"}, {"href": "https://b"}],
+            "meta": {"odd key": true}
+        })
+    }
+
+    #[test]
+    fn detects_paths() {
+        assert!(is_path("$"));
+        assert!(is_path("$.id"));
+        assert!(is_path("$[0]"));
+        assert!(!is_path("$out"));
+        assert!(!is_path("plain"));
+    }
+
+    #[test]
+    fn select_root_and_keys() {
+        assert_eq!(select(&doc(), "$").unwrap(), doc());
+        assert_eq!(select(&doc(), "$.id").unwrap(), json!(42));
+        assert_eq!(select(&doc(), "$.meta['odd key']").unwrap(), json!(true));
+    }
+
+    #[test]
+    fn select_indexes() {
+        assert_eq!(select(&doc(), "$.links[0].href").unwrap(), json!("https://a"));
+        assert_eq!(select(&doc(), "$.links[-1].href").unwrap(), json!("https://b"));
+    }
+
+    #[test]
+    fn select_wildcard() {
+        assert_eq!(
+            select(&doc(), "$.links[*].href").unwrap(),
+            json!(["https://a", "https://b"])
+        );
+        assert_eq!(select(&doc(), "$.missing[*]").unwrap(), json!([]));
+    }
+
+    #[test]
+    fn select_missing_errors() {
+        let err = select(&doc(), "$.nope").unwrap_err();
+        assert!(err.to_string().contains("matched nothing"));
+        assert!(select(&doc(), "$.links[9]").is_err());
+    }
+
+    #[test]
+    fn normalize_jq_style() {
+        assert_eq!(normalize(".items[0].url"), "$.items[0].url");
+        assert_eq!(normalize("."), "$");
+        assert_eq!(normalize(".[1]"), "$[1]");
+        assert_eq!(normalize("$.id"), "$.id");
+        assert_eq!(select(&doc(), &normalize(".links[1].href")).unwrap(), json!("https://b"));
+        assert!(validate(&normalize(".links[0]")).is_ok());
+        assert!(validate(&normalize("links")).is_err());
+    }
+
+    #[test]
+    fn invalid_paths() {
+        assert!(select(&doc(), "id").is_err());
+        assert!(select(&doc(), "$.").is_err());
+        assert!(select(&doc(), "$[0").is_err());
+        assert!(select(&doc(), "$[x]").is_err());
+    }
+}
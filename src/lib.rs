@@ -4,20 +4,37 @@ pub mod codegen;
 pub mod config;
 pub mod daemon;
 pub mod error;
+pub mod history;
+pub mod interceptor;
+pub mod jsonpath;
+pub mod logging;
 pub mod oauth;
+pub mod redact;
+pub mod results;
+pub mod retry;
 pub mod runtime;
 pub mod server_proxy;
+pub mod stats;
+pub mod state;
 pub mod transport;
 pub mod transports;
 pub mod types;
 
 pub use config::{load_config, McplugConfig, ServerConfig};
 pub use error::McplugError;
-pub use runtime::Runtime;
+pub use interceptor::{CallInterceptor, ToolCall};
+pub use retry::{RetryOn, RetryPolicy};
+pub use runtime::{HealthProbe, HealthReport, Runtime, RuntimeBuilder};
 pub use server_proxy::ServerProxy;
-pub use transport::McpTransport;
-pub use transports::{HttpSseTransport, StdioTransport};
-pub use types::{CallResult, ContentBlock, ServerInfo, ToolDefinition};
+pub use stats::{LatencyHistogram, RuntimeStats, ServerStats};
+pub use transport::{McpTransport, TransportFactory};
+pub use transports::{HttpSseTransport, RecordingTransport, ReplayTransport, StdioTransport};
+#[cfg(feature = "test-util")]
+pub use transports::{MockCalls, MockTransport};
+pub use types::{
+    CallResult, ContentBlock, Notification, SchemaProperty, SchemaType, ServerInfo,
+    ToolAnnotations, ToolDefinition, ToolSchema,
+};
 
 /// One-shot convenience function: connect, call, disconnect.
 pub async fn call_once(
@@ -0,0 +1,199 @@
+use std::fmt;
+
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::EnvFilter;
+
+/// How log lines on stderr are formatted (`MCPLUG_LOG_FORMAT`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// Human-readable lines (the default).
+    #[default]
+    Text,
+    /// One JSON object per line, for CI systems and agents.
+    Json,
+}
+
+impl LogFormat {
+    /// Read `MCPLUG_LOG_FORMAT`; anything other than `json` means text.
+    pub fn from_env() -> Self {
+        Self::parse(std::env::var("MCPLUG_LOG_FORMAT").ok().as_deref())
+    }
+
+    fn parse(value: Option<&str>) -> Self {
+        match value.map(str::trim) {
+            Some(v) if v.eq_ignore_ascii_case("json") => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+}
+
+/// The log filter for the global `-q`/`-v` flags: `-q` logs only errors,
+/// each `-v` raises the level from the default `warn` (info, debug, trace).
+/// Without either flag, `MCPLUG_LOG_LEVEL` applies.
+pub fn log_filter(quiet: bool, verbose: u8) -> EnvFilter {
+    let level = match (quiet, verbose) {
+        (true, _) => "error",
+        (false, 0) => {
+            return EnvFilter::try_from_env("MCPLUG_LOG_LEVEL")
+                .unwrap_or_else(|_| EnvFilter::new("warn"))
+        }
+        (false, 1) => "info",
+        (false, 2) => "debug",
+        (false, _) => "trace",
+    };
+    EnvFilter::new(level)
+}
+
+/// Install the global tracing subscriber, writing to stderr.
+pub fn init(quiet: bool, verbose: u8) {
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(log_filter(quiet, verbose))
+        .with_writer(std::io::stderr);
+    match LogFormat::from_env() {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder.event_format(JsonLines).init(),
+    }
+}
+
+/// Formats each event as a JSON object: `timestamp`, `level`, `target`,
+/// `message`, the event's other `fields`, and the names of enclosing `spans`.
+#[derive(Debug, Default)]
+pub struct JsonLines;
+
+impl<S, N> FormatEvent<S, N> for JsonLines
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+        let mut fields = FieldMap::default();
+        event.record(&mut fields);
+        let mut fields = fields.0;
+
+        let mut line = Map::new();
+        line.insert(
+            "timestamp".into(),
+            Value::String(
+                chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            ),
+        );
+        line.insert("level".into(), Value::String(meta.level().to_string()));
+        line.insert("target".into(), Value::String(meta.target().to_string()));
+        if let Some(message) = fields.remove("message") {
+            line.insert("message".into(), message);
+        }
+        if !fields.is_empty() {
+            line.insert("fields".into(), Value::Object(fields));
+        }
+        if let Some(scope) = ctx.event_scope() {
+            let spans: Vec<Value> = scope
+                .from_root()
+                .map(|span| Value::String(span.name().to_string()))
+                .collect();
+            line.insert("spans".into(), Value::Array(spans));
+        }
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+/// Collects an event's fields as JSON values.
+#[derive(Default)]
+struct FieldMap(Map<String, Value>);
+
+impl Visit for FieldMap {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), Value::String(value.into()));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), Value::Bool(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().into(), Value::String(format!("{value:?}")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn format_from_env_value() {
+        assert_eq!(LogFormat::parse(Some("json")), LogFormat::Json);
+        assert_eq!(LogFormat::parse(Some(" JSON ")), LogFormat::Json);
+        assert_eq!(LogFormat::parse(Some("text")), LogFormat::Text);
+        assert_eq!(LogFormat::parse(None), LogFormat::Text);
+    }
+
+    #[test]
+    fn flags_set_filter_level() {
+        assert_eq!(log_filter(true, 2).to_string(), "error");
+        assert_eq!(log_filter(false, 1).to_string(), "info");
+        assert_eq!(log_filter(false, 2).to_string(), "debug");
+        assert_eq!(log_filter(false, 5).to_string(), "trace");
+    }
+
+    #[test]
+    fn json_lines_include_message_fields_and_spans() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .event_format(JsonLines)
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("call");
+            let _guard = span.enter();
+            tracing::warn!(server = "mock", bytes = 42u64, "payload too large");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["message"], "payload too large");
+        assert_eq!(line["fields"]["server"], "mock");
+        assert_eq!(line["fields"]["bytes"], 42);
+        assert_eq!(line["spans"], serde_json::json!(["call"]));
+        assert!(line["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+}
@@ -1,12 +1,87 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use mcplug::cli::config_cmd::ConflictPolicy;
+use mcplug::cli::fanout::FanoutPolicy;
+use mcplug::codegen::Snapshot;
 
 #[derive(Parser)]
 #[command(name = "mcplug", version, about = "A toolkit for discovering, calling, and composing MCP servers")]
 struct Cli {
+    /// Log only errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Log more: -v for info, -vv for debug, -vvv for trace
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Permit cleartext http:// URLs for every configured server
+    #[arg(long, global = true)]
+    allow_http: bool,
+
+    /// Use a named OAuth token set, e.g. `--as work` for `github:work`
+    #[arg(long = "as", global = true, value_name = "IDENTITY")]
+    identity: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+/// Exit status policy for commands that act on several targets.
+#[derive(Args)]
+struct FanoutArgs {
+    /// Stop at the first failure and fail (implies --require-all)
+    #[arg(long)]
+    fail_fast: bool,
+
+    /// Fail unless every target succeeds
+    #[arg(long, conflicts_with = "require_any")]
+    require_all: bool,
+
+    /// Fail only if no target succeeds
+    #[arg(long)]
+    require_any: bool,
+}
+
+impl FanoutArgs {
+    fn policy(&self, default: FanoutPolicy) -> FanoutPolicy {
+        FanoutPolicy::from_flags(self.require_all, self.require_any, self.fail_fast, default)
+    }
+}
+
+/// Where code generation gets a server's tools: the live server, or a file
+/// saved by `mcplug snapshot`.
+#[derive(Args)]
+struct CatalogArgs {
+    /// Server name (default with --from-snapshot: the snapshot's server)
+    #[arg(required_unless_present = "from_snapshot")]
+    server: Option<String>,
+
+    /// Read the tools from a snapshot file instead of connecting to the server
+    #[arg(long, value_name = "FILE")]
+    from_snapshot: Option<std::path::PathBuf>,
+}
+
+impl CatalogArgs {
+    async fn load(self) -> Result<Snapshot, mcplug::McplugError> {
+        match (self.from_snapshot, self.server) {
+            (Some(path), server) => {
+                let mut snapshot = Snapshot::load(&path)?;
+                if let Some(server) = server {
+                    snapshot.server = server;
+                }
+                Ok(snapshot)
+            }
+            (None, Some(server)) => {
+                let runtime = mcplug::Runtime::from_config().await?;
+                let snapshot = Snapshot::capture(&runtime, &server).await;
+                runtime.close().await?;
+                snapshot
+            }
+            (None, None) => unreachable!("clap requires a server without --from-snapshot"),
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// List configured MCP servers and their tools
@@ -29,6 +104,26 @@ enum Commands {
         /// Show all parameters including optional ones
         #[arg(long)]
         all_parameters: bool,
+
+        /// Set an environment variable for the spawned stdio server (repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE", conflicts_with_all = ["http_url", "stdio"])]
+        env: Vec<String>,
+
+        /// Write the exchange with the server to a recording file
+        #[arg(long, value_name = "FILE")]
+        record: Option<std::path::PathBuf>,
+
+        /// Serve responses from a recording file instead of the server
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["http_url", "stdio"])]
+        replay: Option<std::path::PathBuf>,
+
+        /// Only list servers with this tag (repeatable: any of them)
+        #[arg(long = "tag", value_name = "TAG", conflicts_with_all = ["server", "http_url", "stdio", "record", "replay"])]
+        tags: Vec<String>,
+
+        /// When listing several servers (the default: report only)
+        #[command(flatten)]
+        fanout: FanoutArgs,
     },
 
     /// Call an MCP tool
@@ -39,6 +134,14 @@ enum Commands {
         /// Tool arguments
         args: Vec<String>,
 
+        /// Entire argument object as JSON, or @file.json to read it from a file
+        #[arg(long, conflicts_with = "args")]
+        args_json: Option<String>,
+
+        /// Read the argument object as JSON from stdin
+        #[arg(long, conflicts_with_all = ["args", "args_json"])]
+        stdin: bool,
+
         /// Raw output (no formatting)
         #[arg(long)]
         raw: bool,
@@ -47,7 +150,7 @@ enum Commands {
         #[arg(long)]
         json: bool,
 
-        /// Output format
+        /// Output format: pretty, raw, json, or markdown
         #[arg(long)]
         output: Option<String>,
 
@@ -58,16 +161,246 @@ enum Commands {
         /// Ad-hoc stdio server
         #[arg(long)]
         stdio: Option<String>,
+
+        /// Print the execution plan and ask for confirmation on stdin before calling
+        #[arg(long)]
+        explain: bool,
+
+        /// Save the result under a name for `mcplug result show` and `@result:` references
+        #[arg(long)]
+        save_as: Option<String>,
+
+        /// Print only this path of the JSON text result (e.g. '.items[0].url' or '$.items[0].url')
+        #[arg(long, value_name = "PATH")]
+        filter: Option<String>,
+
+        /// Decode image results and write them to files in this directory
+        #[arg(long, value_name = "DIR")]
+        save_images: Option<std::path::PathBuf>,
+
+        /// Set an environment variable for the spawned stdio server (repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE", conflicts_with_all = ["http_url", "stdio"])]
+        env: Vec<String>,
+
+        /// Write the exchange with the server to a recording file
+        #[arg(long, value_name = "FILE")]
+        record: Option<std::path::PathBuf>,
+
+        /// Serve responses from a recording file instead of the server
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["http_url", "stdio"])]
+        replay: Option<std::path::PathBuf>,
+
+        /// If the server asks for credentials, run the OAuth login and retry
+        #[arg(long, conflicts_with_all = ["http_url", "stdio", "replay"])]
+        auth: bool,
+    },
+
+    /// Run tool calls from a JSONL file and print one result per line
+    Batch {
+        /// JSONL file with one {"tool": "server.tool", "args": {...}} per line
+        file: std::path::PathBuf,
+
+        /// Number of calls to run concurrently
+        #[arg(long, default_value_t = 1)]
+        parallel: usize,
+
+        /// Exit status policy (the default: --require-all)
+        #[command(flatten)]
+        fanout: FanoutArgs,
+    },
+
+    /// Run a pipeline of calls, binding each call's text output to $out and
+    /// vars(name: $.path) captures to $name
+    Pipe {
+        /// Pipeline, e.g. 'a.fetch(url: "https://x") | b.summarize(text: $out)'
+        pipeline: String,
+
+        /// Output the final result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Re-run a tool call on an interval, redrawing the output
+    Watch {
+        /// Tool reference (server.tool or server.tool(args))
+        tool_ref: String,
+
+        /// Arguments in key:value or key=value format
+        args: Vec<String>,
+
+        /// Seconds between calls (fractions allowed)
+        #[arg(short = 'n', long, default_value_t = 2.0)]
+        interval: f64,
+
+        /// Mark lines that changed since the previous run
+        #[arg(long)]
+        diff: bool,
+
+        /// Stop after this many runs
+        #[arg(long)]
+        count: Option<usize>,
+
+        /// Ad-hoc HTTP server URL (bypasses config)
+        #[arg(long)]
+        http_url: Option<String>,
+
+        /// Ad-hoc stdio command (bypasses config)
+        #[arg(long)]
+        stdio: Option<String>,
+    },
+
+    /// Measure call latency (p50/p95/p99) and error rate for a tool
+    Bench {
+        /// Tool reference (server.tool or server.tool(args))
+        tool_ref: String,
+
+        /// Arguments in key:value or key=value format
+        args: Vec<String>,
+
+        /// Unmeasured calls to make first
+        #[arg(long, default_value_t = 5)]
+        warmup: usize,
+
+        /// Measured calls
+        #[arg(short = 'n', long, default_value_t = 100)]
+        iterations: usize,
+
+        /// Number of connections calling concurrently
+        #[arg(short = 'c', long, default_value_t = 1)]
+        concurrency: usize,
+
+        /// Output results as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Ad-hoc HTTP server URL (bypasses config)
+        #[arg(long)]
+        http_url: Option<String>,
+
+        /// Ad-hoc stdio command (bypasses config)
+        #[arg(long)]
+        stdio: Option<String>,
+    },
+
+    /// Pre-install npm/uv packages or pull Docker images for servers
+    Prefetch {
+        /// Servers to prefetch (default: all enabled servers)
+        servers: Vec<String>,
+    },
+
+    /// Connect to and initialize servers ahead of use, reporting handshake times
+    Warm {
+        /// Servers to warm (default: all enabled servers)
+        servers: Vec<String>,
+    },
+
+    /// Write a sanitized connection report for filing issues against a server
+    Diagnose {
+        /// Server name
+        server: String,
+
+        /// Write the report to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+
+        /// Ad-hoc HTTP endpoint
+        #[arg(long)]
+        http_url: Option<String>,
+
+        /// Ad-hoc stdio server
+        #[arg(long)]
+        stdio: Option<String>,
+    },
+
+    /// Check a server against the MCP protocol and report what passes and fails
+    Conformance {
+        /// Server name
+        server: String,
+
+        /// Ad-hoc HTTP endpoint
+        #[arg(long)]
+        http_url: Option<String>,
+
+        /// Ad-hoc stdio server
+        #[arg(long)]
+        stdio: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Set a server's log level with `logging/setLevel`
+    #[command(name = "log-level")]
+    LogLevel {
+        /// Server name
+        server: String,
+
+        /// debug, info, notice, warning, error, critical, alert, or emergency
+        level: String,
+
+        /// Ad-hoc HTTP endpoint
+        #[arg(long)]
+        http_url: Option<String>,
+
+        /// Ad-hoc stdio server
+        #[arg(long)]
+        stdio: Option<String>,
+    },
+
+    /// Show server details, capabilities, and tool/resource/prompt counts
+    Info {
+        /// Server name
+        server: String,
+
+        /// Query an ad-hoc HTTP endpoint
+        #[arg(long)]
+        http_url: Option<String>,
+
+        /// Query an ad-hoc stdio server
+        #[arg(long)]
+        stdio: Option<String>,
+
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Search tools across all configured servers
+    Search {
+        /// Text to match against tool names and descriptions
+        query: String,
+
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+
+        /// Only search servers with this tag (repeatable: any of them)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
     },
 
     /// Complete OAuth login for a protected MCP server
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Auth {
+        #[command(subcommand)]
+        action: Option<AuthAction>,
+
         /// Server name or URL
-        server: String,
+        #[arg(required = true)]
+        server: Option<String>,
 
         /// OAuth timeout in milliseconds
         #[arg(long, env = "MCPLUG_OAUTH_TIMEOUT_MS")]
         oauth_timeout: Option<u64>,
+
+        /// Sign in with a device code instead of a browser callback (for SSH sessions and containers)
+        #[arg(long)]
+        device: bool,
+
+        /// Print the sign-in URL and read the redirect URL or code from stdin instead of opening a browser
+        #[arg(long, conflicts_with = "device")]
+        no_browser: bool,
     },
 
     /// Manage persistent background servers
@@ -79,13 +412,17 @@ enum Commands {
     /// Generate a standalone CLI binary for a specific MCP server
     #[command(name = "generate-cli")]
     GenerateCli {
-        /// Server name
-        server: String,
+        #[command(flatten)]
+        catalog: CatalogArgs,
 
-        /// Compile the generated source
+        /// Build the generated CLI into a binary with `cargo build --release`
         #[arg(long)]
         compile: bool,
 
+        /// Where to put the compiled binary (default: ./<server>)
+        #[arg(long, requires = "compile")]
+        out: Option<std::path::PathBuf>,
+
         /// Include only specified tools
         #[arg(long, value_delimiter = ',')]
         include_tools: Option<Vec<String>>,
@@ -93,11 +430,97 @@ enum Commands {
         /// Exclude specified tools
         #[arg(long, value_delimiter = ',')]
         exclude_tools: Option<Vec<String>>,
+
+        /// Embed the server's config, so the CLI runs without an mcplug config
+        #[arg(long)]
+        embed_config: bool,
     },
 
     /// Emit Rust type definitions and client wrappers for an MCP server
-    #[command(name = "emit-rs")]
+    #[command(
+        name = "emit-rs",
+        mut_arg("server", |arg| arg.required_unless_present_any(["from_snapshot", "all"]))
+    )]
     EmitRs {
+        #[command(flatten)]
+        catalog: CatalogArgs,
+
+        /// Emit a module per configured server, and an McpClients struct, into the --output directory
+        #[arg(long, conflicts_with_all = ["server", "from_snapshot"], requires = "output")]
+        all: bool,
+
+        /// Output file path (with --all, a directory)
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Emit a typed TypeScript client for an MCP server
+    #[command(name = "emit-ts")]
+    EmitTs {
+        #[command(flatten)]
+        catalog: CatalogArgs,
+
+        /// Output file path
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Emit a typed async Python client for an MCP server
+    #[command(name = "emit-py")]
+    EmitPy {
+        #[command(flatten)]
+        catalog: CatalogArgs,
+
+        /// Output file path
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Emit an OpenAPI 3.1 document describing an MCP server's tools
+    #[command(name = "emit-openapi")]
+    EmitOpenapi {
+        #[command(flatten)]
+        catalog: CatalogArgs,
+
+        /// Output file path
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Generate a Rust MCP server that exposes a command or an HTTP API as tools
+    #[command(name = "scaffold-server")]
+    ScaffoldServer {
+        /// Wrap this command, e.g. "mytool --json", as a tool
+        #[arg(long, value_name = "COMMAND", required_unless_present = "from_openapi")]
+        from_command: Option<String>,
+
+        /// Expose each operation of this OpenAPI 3 document (JSON) as a tool
+        #[arg(long, value_name = "FILE", conflicts_with = "from_command")]
+        from_openapi: Option<std::path::PathBuf>,
+
+        /// Server name (default: the command's program or the API's title, plus "-mcp")
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Directory to write the Cargo project to (default: ./<name>)
+        #[arg(long)]
+        out: Option<std::path::PathBuf>,
+    },
+
+    /// Run a mock MCP server whose tools and answers come from a spec file
+    #[command(name = "mock-server")]
+    MockServer {
+        /// JSON or YAML file defining the tools, responses, latency, and errors
+        spec: std::path::PathBuf,
+
+        /// Serve over HTTP on this address (e.g. 127.0.0.1:8080) instead of stdio
+        #[arg(long, value_name = "ADDR")]
+        http: Option<String>,
+    },
+
+    /// Save a server's info and tools to a JSON file, for code generation
+    /// with --from-snapshot
+    Snapshot {
         /// Server name
         server: String,
 
@@ -106,11 +529,44 @@ enum Commands {
         output: Option<String>,
     },
 
+    /// Query the history of tool calls and state statistics
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+
+    /// Inspect results saved with `call --save-as`
+    Result {
+        #[command(subcommand)]
+        action: ResultAction,
+    },
+
+    /// Add, remove, rename, enable/disable, test, or show a single server
+    Server {
+        #[command(subcommand)]
+        action: ServerAction,
+    },
+
     /// Manage server configuration
     Config {
         #[command(subcommand)]
         action: ConfigAction,
     },
+
+    /// Print a shell completion script
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_parser = clap::builder::PossibleValuesParser::new(mcplug::cli::completions::SHELLS))]
+        shell: String,
+    },
+
+    /// Print completion candidates for the shell scripts
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// Words after `mcplug`, ending with the word being completed
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        words: Vec<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -138,25 +594,173 @@ enum DaemonAction {
     Status,
 }
 
+#[derive(Subcommand)]
+enum AuthAction {
+    /// Show login state, token expiry, and scopes without starting a login
+    Status {
+        /// Only this server
+        server: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List servers with cached tokens
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Revoke (when supported) and delete cached tokens
+    Logout {
+        /// Server to log out of
+        #[arg(required_unless_present = "all")]
+        server: Option<String>,
+
+        /// Log out of every server with cached tokens
+        #[arg(long, conflicts_with = "server")]
+        all: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryAction {
+    /// List recorded calls, oldest first
+    Query {
+        /// Only calls to this server
+        #[arg(long)]
+        server: Option<String>,
+
+        /// Only calls to this tool
+        #[arg(long)]
+        tool: Option<String>,
+
+        /// Only calls within this age (e.g. 30m, 24h, 7d)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only calls that failed
+        #[arg(long)]
+        failed: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show per-tool call statistics and state entry counts
+    Stats {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ResultAction {
+    /// Print a saved result
+    Show {
+        /// Result name
+        name: String,
+
+        /// Output the saved result with metadata as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List saved result names
+    List,
+}
+
+#[derive(Subcommand)]
+enum ServerAction {
+    /// Add a new server interactively, testing the connection before saving
+    Add,
+    /// Remove a server from the config file that defines it
+    Remove {
+        /// Server name
+        name: String,
+    },
+    /// Rename a server
+    Rename {
+        /// Current server name
+        old: String,
+        /// New server name
+        new: String,
+    },
+    /// Re-enable a disabled server
+    Enable {
+        /// Server name
+        name: String,
+    },
+    /// Disable a server without removing it from config
+    Disable {
+        /// Server name
+        name: String,
+    },
+    /// Connect to a server and list its tools
+    Test {
+        /// Server name
+        name: String,
+    },
+    /// Show the merged config entry for a server
+    Show {
+        /// Server name
+        name: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum ConfigAction {
     /// Add a new server definition interactively
     Add,
     /// Display merged config with source annotations
     Show,
+    /// Check every discovered config file for mistakes
+    Validate,
+    /// Remove a server from the config file that defines it
+    Remove {
+        /// Server name
+        name: String,
+    },
+    /// Open the highest-precedence config file in $EDITOR and validate it
+    Edit,
+    /// Merge servers from a JSON snippet into ~/.mcplug/mcplug.json
+    Import {
+        /// File with `mcpServers` JSON, or `-` for stdin
+        file: String,
+        /// Overwrite servers that already exist
+        #[arg(long, conflicts_with = "rename")]
+        force: bool,
+        /// Import conflicting servers under a new name (`name-2`)
+        #[arg(long)]
+        rename: bool,
+    },
+    /// Convert the merged config to an editor's MCP config format
+    Export {
+        /// Editor format to produce
+        #[arg(long, value_parser = clap::builder::PossibleValuesParser::new(mcplug::config::editors::EXPORT_EDITORS))]
+        to: String,
+        /// Merge the servers into the editor's config file instead of printing
+        #[arg(long)]
+        write: bool,
+    },
 }
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_env("MCPLUG_LOG_LEVEL")
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
-        )
-        .with_writer(std::io::stderr)
-        .init();
-
     let cli = Cli::parse();
+    mcplug::logging::init(cli.quiet, cli.verbose);
+    if cli.allow_http {
+        // Read by the transport layer wherever a connection is made
+        std::env::set_var("MCPLUG_ALLOW_HTTP", "1");
+    }
+    if let Some(identity) = &cli.identity {
+        if let Err(e) = mcplug::oauth::validate_identity(identity) {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        // Read by OAuthClient::from_config, overriding any configured identity
+        std::env::set_var("MCPLUG_AUTH_IDENTITY", identity);
+    }
 
     let result = run(cli).await;
     if let Err(e) = result {
@@ -173,6 +777,11 @@ async fn run(cli: Cli) -> Result<(), mcplug::McplugError> {
             stdio,
             json,
             all_parameters,
+            env,
+            record,
+            replay,
+            tags,
+            fanout,
         } => {
             mcplug::cli::list::run_list(
                 server.as_deref(),
@@ -180,50 +789,253 @@ async fn run(cli: Cli) -> Result<(), mcplug::McplugError> {
                 stdio.as_deref(),
                 json,
                 all_parameters,
+                record.as_deref(),
+                replay.as_deref(),
+                fanout.policy(FanoutPolicy::Report),
+                fanout.fail_fast,
+                &env,
+                &tags,
             )
             .await
         }
         Commands::Call {
             tool_ref,
             args,
+            args_json,
+            stdin,
             raw,
             json,
             output,
             http_url,
             stdio,
+            explain,
+            save_as,
+            filter,
+            save_images,
+            env,
+            record,
+            replay,
+            auth,
         } => {
+            // --stdin is shorthand for --args-json @-
+            let args_json = if stdin { Some("@-".to_string()) } else { args_json };
             mcplug::cli::call::run_call(
                 &tool_ref,
                 &args,
+                args_json.as_deref(),
                 raw,
                 json,
                 output.as_deref(),
                 http_url.as_deref(),
                 stdio.as_deref(),
+                explain,
+                save_as.as_deref(),
+                filter.as_deref(),
+                save_images.as_deref(),
+                &env,
+                record.as_deref(),
+                replay.as_deref(),
+                auth,
+            )
+            .await
+        }
+        Commands::Batch {
+            file,
+            parallel,
+            fanout,
+        } => {
+            mcplug::cli::batch::run_batch(
+                &file,
+                parallel,
+                fanout.policy(FanoutPolicy::RequireAll),
+                fanout.fail_fast,
+            )
+            .await
+        }
+        Commands::Watch {
+            tool_ref,
+            args,
+            interval,
+            diff,
+            count,
+            http_url,
+            stdio,
+        } => {
+            mcplug::cli::watch::run_watch(
+                &tool_ref,
+                &args,
+                interval,
+                diff,
+                count,
+                http_url.as_deref(),
+                stdio.as_deref(),
+            )
+            .await
+        }
+        Commands::Bench {
+            tool_ref,
+            args,
+            warmup,
+            iterations,
+            concurrency,
+            json,
+            http_url,
+            stdio,
+        } => {
+            let opts = mcplug::cli::bench::BenchOptions {
+                warmup,
+                iterations,
+                concurrency,
+                http_url: http_url.as_deref(),
+                stdio: stdio.as_deref(),
+                json,
+            };
+            mcplug::cli::bench::run_bench(&tool_ref, &args, opts).await
+        }
+        Commands::Prefetch { servers } => mcplug::cli::prefetch::run_prefetch(&servers).await,
+        Commands::Warm { servers } => mcplug::cli::warm::run_warm(&servers).await,
+        Commands::Diagnose {
+            server,
+            output,
+            http_url,
+            stdio,
+        } => {
+            mcplug::cli::diagnose::run_diagnose(
+                &server,
+                output.as_deref(),
+                http_url.as_deref(),
+                stdio.as_deref(),
+            )
+            .await
+        }
+        Commands::Conformance {
+            server,
+            http_url,
+            stdio,
+            json,
+        } => {
+            mcplug::cli::conformance::run_conformance(
+                &server,
+                http_url.as_deref(),
+                stdio.as_deref(),
+                json,
             )
             .await
         }
+        Commands::LogLevel {
+            server,
+            level,
+            http_url,
+            stdio,
+        } => {
+            mcplug::cli::log_level::run_log_level(
+                &server,
+                &level,
+                http_url.as_deref(),
+                stdio.as_deref(),
+            )
+            .await
+        }
+        Commands::Pipe { pipeline, json } => mcplug::cli::pipe::run_pipe(&pipeline, json).await,
+        Commands::Info {
+            server,
+            http_url,
+            stdio,
+            json,
+        } => {
+            mcplug::cli::info::run_info(&server, http_url.as_deref(), stdio.as_deref(), json)
+                .await
+        }
+        Commands::Search { query, json, tags } => {
+            mcplug::cli::search::run_search(&query, json, &tags).await
+        }
+        Commands::Auth {
+            action: Some(action),
+            ..
+        } => match action {
+            AuthAction::Status { server, json } => {
+                mcplug::cli::auth_cmd::run_auth_status(server.as_deref(), json).await
+            }
+            AuthAction::List { json } => mcplug::cli::auth_cmd::run_auth_list(json).await,
+            AuthAction::Logout { server, all } => {
+                mcplug::cli::auth_cmd::run_auth_logout(server.as_deref(), all).await
+            }
+        },
         Commands::Auth {
+            action: None,
             server,
             oauth_timeout,
+            device,
+            no_browser,
         } => {
+            let mut server = server.unwrap_or_default();
             let timeout = std::time::Duration::from_millis(oauth_timeout.unwrap_or(60000));
             // Determine base_url from config or treat server as URL
             let config = mcplug::load_config(None)?;
-            let base_url = if server.starts_with("http://") || server.starts_with("https://") {
-                server.clone()
-            } else {
-                let srv = config.mcp_servers.get(&server).ok_or_else(|| {
-                    mcplug::McplugError::ServerNotFound(server.clone())
-                })?;
-                srv.base_url.clone().ok_or_else(|| {
-                    mcplug::McplugError::ConfigError {
-                        path: std::path::PathBuf::from("<config>"),
-                        detail: format!("Server '{}' has no baseUrl for OAuth", server),
+            let is_url = server.starts_with("http://") || server.starts_with("https://");
+            // `server:identity` picks the token set like `--as identity`
+            let mut identity = None;
+            if !is_url {
+                if let (name, Some(id)) = mcplug::oauth::split_token_id(&server) {
+                    identity = Some(id.to_string());
+                    server = name.to_string();
+                }
+            }
+            let (base_url, mut client) =
+                if is_url {
+                    (server.clone(), mcplug::oauth::OAuthClient::from_config(None))
+                } else {
+                    let srv = config.mcp_servers.get(&server).ok_or_else(|| {
+                        mcplug::McplugError::ServerNotFound(server.clone())
+                    })?;
+                    let srv = &mcplug::config::env::resolve_secrets(srv)?;
+                    if let Some(mcplug::config::AuthConfig::Bearer { .. }) = srv.auth {
+                        return Err(mcplug::McplugError::ConfigError {
+                            path: std::path::PathBuf::from("<config>"),
+                            detail: format!(
+                                "Server '{}' authenticates with a configured bearer token; there is nothing to log in to",
+                                server
+                            ),
+                        });
+                    }
+                    let base_url = srv.base_url.clone().ok_or_else(|| {
+                        mcplug::McplugError::ConfigError {
+                            path: std::path::PathBuf::from("<config>"),
+                            detail: format!("Server '{}' has no baseUrl for OAuth", server),
+                        }
+                    })?;
+                    (base_url, mcplug::oauth::OAuthClient::from_config(srv.auth.as_ref()))
+                };
+            if identity.is_some() {
+                client.identity = identity;
+            }
+            let server = client.token_id(&server);
+            let token = if device {
+                // The device code is valid for minutes; only an explicit timeout cuts it short
+                let timeout = oauth_timeout.map(std::time::Duration::from_millis);
+                mcplug::oauth::flow::run_device_flow(&base_url, &server, &client, timeout, |code| {
+                    match code.verification_uri_complete {
+                        Some(ref uri) => println!("To sign in, visit {}", uri),
+                        None => println!("To sign in, visit {}", code.verification_uri),
                     }
-                })?
+                    println!("and enter the code: {}", code.user_code);
+                    println!("Waiting for approval...");
+                })
+                .await?
+            } else if no_browser {
+                mcplug::oauth::flow::run_manual_flow(&base_url, &server, &client, |url| {
+                    println!("Open this URL in a browser to sign in:\n\n  {}\n", url);
+                    println!("After approving, the browser is sent to a localhost page that may fail to load.");
+                    print!("Paste that page's full URL (or just the code): ");
+                    std::io::Write::flush(&mut std::io::stdout())?;
+                    let mut line = String::new();
+                    std::io::stdin().read_line(&mut line)?;
+                    Ok(line)
+                })
+                .await?
+            } else {
+                mcplug::oauth::flow::run_oauth_flow(&base_url, &server, &client, timeout).await?
             };
-            let token = mcplug::oauth::flow::run_oauth_flow(&base_url, &server, timeout).await?;
             println!("Authentication successful for '{}'", server);
             println!("Token expires: {:?}", token.expires_at);
             Ok(())
@@ -245,6 +1057,11 @@ async fn run(cli: Cli) -> Result<(), mcplug::McplugError> {
                     if status.running {
                         println!("Daemon running (PID: {})", status.pid.unwrap_or(0));
                         println!("Managed servers: {:?}", status.managed_servers);
+                        if let Some(stats) = status.call_stats {
+                            for (server, server_stats) in &stats.servers {
+                                println!("  {}: {}", server, server_stats.summary());
+                            }
+                        }
                     } else {
                         println!("Daemon is not running");
                     }
@@ -253,34 +1070,76 @@ async fn run(cli: Cli) -> Result<(), mcplug::McplugError> {
             }
         }
         Commands::GenerateCli {
-            server,
+            catalog,
             compile,
+            out,
             include_tools,
             exclude_tools,
+            embed_config,
         } => {
-            let runtime = mcplug::Runtime::from_config().await?;
-            let tools = runtime.list_tools(&server).await?;
-            let source = mcplug::codegen::generate_cli::generate_cli_source(
+            let Snapshot { server, tools, .. } = catalog.load().await?;
+            // Unexpanded, so the CLI reads secrets from its environment
+            let server_config = if embed_config {
+                let config = mcplug::config::loader::load_merged_config(None)?;
+                let server_config = config
+                    .mcp_servers
+                    .get(&server)
+                    .cloned()
+                    .ok_or_else(|| mcplug::McplugError::ServerNotFound(server.clone()))?;
+                Some(server_config)
+            } else {
+                None
+            };
+            let source = mcplug::codegen::generate_cli::generate_cli_source_with_config(
                 &tools,
                 &server,
                 include_tools.as_deref(),
                 exclude_tools.as_deref(),
+                server_config.as_ref(),
             );
             if compile {
-                let dir = std::env::temp_dir().join(format!("mcplug-gen-{}", server));
-                std::fs::create_dir_all(&dir).map_err(mcplug::McplugError::IoError)?;
-                let main_path = dir.join("main.rs");
-                std::fs::write(&main_path, &source).map_err(mcplug::McplugError::IoError)?;
-                println!("Generated source at: {}", main_path.display());
-                eprintln!("Compilation requires a Cargo project setup — source written to {}", main_path.display());
+                use mcplug::codegen::generate_cli::{compile_project, package_name, write_project};
+                let dir = std::env::temp_dir().join(format!("mcplug-gen-{}", package_name(&server)));
+                write_project(&dir, &server, &source)?;
+                eprintln!("Building {} in {}", package_name(&server), dir.display());
+                let out = out.unwrap_or_else(|| {
+                    std::path::PathBuf::from(format!(
+                        "{}{}",
+                        package_name(&server),
+                        std::env::consts::EXE_SUFFIX
+                    ))
+                });
+                let binary = compile_project(&dir, &server, &out)?;
+                println!("Compiled CLI to {}", binary.display());
             } else {
                 println!("{source}");
             }
             Ok(())
         }
-        Commands::EmitRs { server, output } => {
+        Commands::EmitRs {
+            all: true, output, ..
+        } => {
+            let dir = output.expect("clap requires --output with --all");
             let runtime = mcplug::Runtime::from_config().await?;
-            let tools = runtime.list_tools(&server).await?;
+            let mut snapshots = Vec::new();
+            let mut captured = Ok(());
+            for server in runtime.server_names() {
+                match Snapshot::capture(&runtime, &server).await {
+                    Ok(snapshot) => snapshots.push(snapshot),
+                    Err(e) => {
+                        captured = Err(e);
+                        break;
+                    }
+                }
+            }
+            runtime.close().await?;
+            captured?;
+            mcplug::codegen::emit_rs::write_rust_modules(std::path::Path::new(&dir), &snapshots)?;
+            println!("Wrote Rust modules for {} servers to {dir}", snapshots.len());
+            Ok(())
+        }
+        Commands::EmitRs { catalog, output, .. } => {
+            let Snapshot { server, tools, .. } = catalog.load().await?;
             let code = mcplug::codegen::emit_rs::emit_rust_types(&tools, &server);
             if let Some(path) = output {
                 std::fs::write(&path, &code).map_err(mcplug::McplugError::IoError)?;
@@ -290,9 +1149,157 @@ async fn run(cli: Cli) -> Result<(), mcplug::McplugError> {
             }
             Ok(())
         }
+        Commands::EmitTs { catalog, output } => {
+            let Snapshot { server, tools, .. } = catalog.load().await?;
+            let code = mcplug::codegen::emit_ts::emit_typescript(&tools, &server);
+            if let Some(path) = output {
+                std::fs::write(&path, &code).map_err(mcplug::McplugError::IoError)?;
+                println!("Wrote TypeScript client to {path}");
+            } else {
+                println!("{code}");
+            }
+            Ok(())
+        }
+        Commands::EmitPy { catalog, output } => {
+            let Snapshot { server, tools, .. } = catalog.load().await?;
+            let code = mcplug::codegen::emit_py::emit_python(&tools, &server);
+            if let Some(path) = output {
+                std::fs::write(&path, &code).map_err(mcplug::McplugError::IoError)?;
+                println!("Wrote Python client to {path}");
+            } else {
+                println!("{code}");
+            }
+            Ok(())
+        }
+        Commands::EmitOpenapi { catalog, output } => {
+            let snapshot = catalog.load().await?;
+            let code = mcplug::codegen::emit_openapi::emit_openapi(
+                &snapshot.tools,
+                &snapshot.server,
+                &snapshot.server_info,
+            );
+            if let Some(path) = output {
+                std::fs::write(&path, &code).map_err(mcplug::McplugError::IoError)?;
+                println!("Wrote OpenAPI document to {path}");
+            } else {
+                println!("{code}");
+            }
+            Ok(())
+        }
+        Commands::MockServer { spec, http } => {
+            mcplug::cli::mock_server::run(&spec, http.as_deref()).await
+        }
+        Commands::ScaffoldServer {
+            from_command,
+            from_openapi,
+            name,
+            out,
+        } => {
+            use mcplug::codegen::ServerScaffold;
+            let mut scaffold = match (from_command, from_openapi) {
+                (_, Some(path)) => {
+                    let content = std::fs::read_to_string(&path)?;
+                    let doc: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+                        mcplug::McplugError::IoError(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!("{} is not a JSON OpenAPI document: {e}", path.display()),
+                        ))
+                    })?;
+                    ServerScaffold::from_openapi(&doc)?
+                }
+                (Some(command), None) => ServerScaffold::from_command(&command)?,
+                (None, None) => unreachable!("clap requires --from-command or --from-openapi"),
+            };
+            if let Some(name) = name {
+                scaffold.name = name;
+            }
+            let out = out.unwrap_or_else(|| {
+                mcplug::codegen::generate_cli::package_name(&scaffold.name).into()
+            });
+            scaffold.write(&out)?;
+            println!(
+                "Wrote MCP server {} with {} tools to {}",
+                scaffold.name,
+                scaffold.tool_names().len(),
+                out.display()
+            );
+            println!("Build it with: cargo build --release --manifest-path {}", out.join("Cargo.toml").display());
+            Ok(())
+        }
+        Commands::Snapshot { server, output } => {
+            let runtime = mcplug::Runtime::from_config().await?;
+            let snapshot = Snapshot::capture(&runtime, &server).await;
+            runtime.close().await?;
+            let snapshot = snapshot?;
+            if let Some(path) = output {
+                snapshot.save(std::path::Path::new(&path))?;
+                println!("Wrote snapshot of {} tools to {path}", snapshot.tools.len());
+            } else {
+                println!("{}", snapshot.to_json());
+            }
+            Ok(())
+        }
+        Commands::History { action } => match action {
+            HistoryAction::Query {
+                server,
+                tool,
+                since,
+                failed,
+                json,
+            } => {
+                mcplug::cli::history::run_history_query(
+                    server.as_deref(),
+                    tool.as_deref(),
+                    since.as_deref(),
+                    failed,
+                    json,
+                )
+                .await
+            }
+            HistoryAction::Stats { json } => mcplug::cli::history::run_history_stats(json).await,
+        },
+        Commands::Result { action } => match action {
+            ResultAction::Show { name, json } => {
+                mcplug::cli::result_cmd::run_result_show(&name, json).await
+            }
+            ResultAction::List => mcplug::cli::result_cmd::run_result_list().await,
+        },
+        Commands::Server { action } => match action {
+            ServerAction::Add => mcplug::cli::config_cmd::run_config_add().await,
+            ServerAction::Remove { name } => mcplug::cli::server_cmd::run_server_remove(&name).await,
+            ServerAction::Rename { old, new } => {
+                mcplug::cli::server_cmd::run_server_rename(&old, &new).await
+            }
+            ServerAction::Enable { name } => {
+                mcplug::cli::server_cmd::run_server_set_disabled(&name, false).await
+            }
+            ServerAction::Disable { name } => {
+                mcplug::cli::server_cmd::run_server_set_disabled(&name, true).await
+            }
+            ServerAction::Test { name } => mcplug::cli::server_cmd::run_server_test(&name).await,
+            ServerAction::Show { name } => mcplug::cli::server_cmd::run_server_show(&name).await,
+        },
         Commands::Config { action } => match action {
             ConfigAction::Add => mcplug::cli::config_cmd::run_config_add().await,
             ConfigAction::Show => mcplug::cli::config_cmd::run_config_show().await,
+            ConfigAction::Validate => mcplug::cli::config_cmd::run_config_validate(),
+            ConfigAction::Remove { name } => mcplug::cli::server_cmd::run_server_remove(&name).await,
+            ConfigAction::Edit => mcplug::cli::config_cmd::run_config_edit(),
+            ConfigAction::Import {
+                file,
+                force,
+                rename,
+            } => mcplug::cli::config_cmd::run_config_import(
+                &file,
+                ConflictPolicy::from_flags(force, rename),
+            ),
+            ConfigAction::Export { to, write } => {
+                mcplug::cli::config_cmd::run_config_export(&to, write)
+            }
         },
+        Commands::Completions { shell } => mcplug::cli::completions::run_completions(&shell),
+        Commands::Complete { words } => {
+            mcplug::cli::completions::run_complete(&Cli::command(), &words).await
+        }
     }
 }
@@ -2,31 +2,110 @@ use std::path::PathBuf;
 
 use crate::error::McplugError;
 use crate::oauth::token::TokenData;
+use crate::state::{default_store, FileStore};
+
+/// Key of a server's tokens within its state namespace.
+pub(crate) const TOKENS_KEY: &str = "tokens";
+
+/// Token IDs name a token set: `server` for the server's default tokens,
+/// or `server:identity` for a named set such as `github:work`. Every
+/// function here taking a `server_name` accepts either form.
+pub fn token_id(server_name: &str, identity: Option<&str>) -> String {
+    match identity {
+        Some(identity) => format!("{server_name}:{identity}"),
+        None => server_name.to_string(),
+    }
+}
+
+/// Identities become part of a file name, so they are limited to ASCII
+/// letters, digits, `-`, `_`, and `.`.
+pub fn validate_identity(identity: &str) -> Result<(), McplugError> {
+    let valid = !identity.is_empty()
+        && !identity.starts_with('.')
+        && identity
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(McplugError::OAuthError(format!(
+            "Invalid identity '{identity}': use letters, digits, '-', '_', or '.'"
+        )))
+    }
+}
+
+/// Split a token ID into its server and identity. URLs used as server
+/// names keep their colons: a suffix only counts as an identity when it
+/// is valid and the rest contains no `/`.
+pub fn split_token_id(token_id: &str) -> (&str, Option<&str>) {
+    match token_id.rsplit_once(':') {
+        Some((server, identity))
+            if !server.contains('/') && validate_identity(identity).is_ok() =>
+        {
+            (server, Some(identity))
+        }
+        _ => (token_id, None),
+    }
+}
+
+/// Namespace and key of a token set: the server's namespace, with key
+/// `tokens` or `tokens.<identity>`.
+pub(crate) fn token_location(token_id: &str) -> (&str, String) {
+    match split_token_id(token_id) {
+        (server, Some(identity)) => (server, format!("{TOKENS_KEY}.{identity}")),
+        (server, None) => (server, TOKENS_KEY.to_string()),
+    }
+}
 
+/// Where the default file store keeps a server's tokens:
+/// `~/.mcplug/<server>/tokens.json`, or `tokens.<identity>.json`.
 pub fn cache_path(server_name: &str) -> PathBuf {
-    let base = dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".mcplug")
-        .join(server_name);
-    base.join("tokens.json")
+    let (namespace, key) = token_location(server_name);
+    FileStore::home().entry_path(namespace, &key)
+}
+
+/// Lock file held while a server's tokens are refreshed:
+/// `~/.mcplug/<server>/tokens.lock`, or `tokens.<identity>.lock`.
+pub fn refresh_lock_path(server_name: &str) -> PathBuf {
+    cache_path(server_name).with_extension("lock")
 }
 
 pub fn load_cached_token(server_name: &str) -> Option<TokenData> {
-    let path = cache_path(server_name);
-    let data = std::fs::read_to_string(&path).ok()?;
-    serde_json::from_str(&data).ok()
+    let (namespace, key) = token_location(server_name);
+    default_store().ok()?.load(namespace, &key)
 }
 
 pub fn save_token(server_name: &str, token: &TokenData) -> Result<(), McplugError> {
-    let path = cache_path(server_name);
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
+    let (namespace, key) = token_location(server_name);
+    default_store()?.save(namespace, &key, token)
+}
+
+/// Remove a server's cached tokens. Removing missing tokens is not an error.
+pub fn delete_token(server_name: &str) -> Result<(), McplugError> {
+    let (namespace, key) = token_location(server_name);
+    default_store()?.delete(namespace, &key)
+}
+
+/// Every cached token set by token ID, sorted by server name.
+pub fn cached_tokens() -> Result<Vec<(String, TokenData)>, McplugError> {
+    let store = default_store()?;
+    let mut tokens = Vec::new();
+    for server in store.namespaces()? {
+        for key in store.keys(&server)? {
+            let identity = match key.strip_prefix(TOKENS_KEY) {
+                Some("") => None,
+                Some(rest) => match rest.strip_prefix('.') {
+                    Some(identity) => Some(identity),
+                    None => continue,
+                },
+                None => continue,
+            };
+            if let Some(token) = store.load(&server, &key) {
+                tokens.push((token_id(&server, identity), token));
+            }
+        }
     }
-    let data = serde_json::to_string_pretty(token).map_err(|e| {
-        McplugError::OAuthError(format!("Failed to serialize token: {e}"))
-    })?;
-    std::fs::write(&path, data)?;
-    Ok(())
+    Ok(tokens)
 }
 
 #[cfg(test)]
@@ -42,6 +121,32 @@ mod tests {
         assert!(path_str.ends_with("tokens.json"));
     }
 
+    #[test]
+    fn token_ids_name_identities() {
+        assert_eq!(token_id("github", None), "github");
+        assert_eq!(token_id("github", Some("work")), "github:work");
+        assert_eq!(split_token_id("github:work"), ("github", Some("work")));
+        assert_eq!(split_token_id("github"), ("github", None));
+        assert_eq!(
+            split_token_id("https://example.com:8080"),
+            ("https://example.com:8080", None)
+        );
+        assert_eq!(token_location("github"), ("github", "tokens".to_string()));
+        assert_eq!(token_location("github:work"), ("github", "tokens.work".to_string()));
+        assert!(cache_path("github:work").ends_with("github/tokens.work.json"));
+        assert!(refresh_lock_path("github:work").ends_with("github/tokens.work.lock"));
+    }
+
+    #[test]
+    fn identity_validation() {
+        assert!(validate_identity("work").is_ok());
+        assert!(validate_identity("team-a_1.x").is_ok());
+        assert!(validate_identity("").is_err());
+        assert!(validate_identity(".hidden").is_err());
+        assert!(validate_identity("a/b").is_err());
+        assert!(validate_identity("a:b").is_err());
+    }
+
     #[test]
     fn cache_path_different_servers() {
         let a = cache_path("server-a");
@@ -62,6 +167,9 @@ mod tests {
             refresh_token: Some("test-refresh".into()),
             expires_at: None,
             token_type: "Bearer".into(),
+            scope: None,
+            resource: None,
+            token_endpoint: None,
         };
 
         save_token(server, &token).unwrap();
@@ -84,6 +192,9 @@ mod tests {
             refresh_token: Some("persist-refresh".into()),
             expires_at: None,
             token_type: "Bearer".into(),
+            scope: None,
+            resource: None,
+            token_endpoint: None,
         };
 
         // Save from one "instance"
@@ -5,8 +5,24 @@ use tokio::net::TcpListener;
 
 use crate::error::McplugError;
 
-pub async fn listen_for_callback(port: u16, timeout: Duration) -> Result<String, McplugError> {
-    let listener = TcpListener::bind(format!("127.0.0.1:{port}")).await?;
+/// Wait for the authorization server to redirect the browser to
+/// `http://localhost:<port>/callback` and return the authorization code.
+///
+/// The redirect must carry `expected_state`. A state mismatch, an `error=`
+/// response, or a missing code shows an error page in the browser and fails
+/// with an OAuth error.
+pub async fn listen_for_callback(
+    port: u16,
+    expected_state: &str,
+    timeout: Duration,
+) -> Result<String, McplugError> {
+    let listener = TcpListener::bind(format!("127.0.0.1:{port}"))
+        .await
+        .map_err(|e| {
+            McplugError::OAuthError(format!(
+                "Cannot listen for the OAuth callback on port {port}: {e}"
+            ))
+        })?;
 
     let accept_future = async {
         let (mut stream, _) = listener.accept().await?;
@@ -15,25 +31,27 @@ pub async fn listen_for_callback(port: u16, timeout: Duration) -> Result<String,
         let n = stream.read(&mut buf).await?;
         let request = String::from_utf8_lossy(&buf[..n]);
 
-        // Parse the GET request line to extract the code parameter
-        let code = parse_code_from_request(&request).ok_or_else(|| {
-            McplugError::OAuthError(
-                "No authorization code found in callback request".to_string(),
-            )
-        })?;
-
-        // Send a simple HTML response
-        let body = "<!DOCTYPE html><html><body><h1>Authentication successful!</h1>\
-                     <p>You can close this window and return to the terminal.</p></body></html>";
+        let outcome = parse_callback(&request, expected_state);
+        let (status, body) = match outcome {
+            Ok(_) => (
+                "200 OK",
+                html_page(
+                    "Authentication successful!",
+                    "You can close this window and return to the terminal.",
+                ),
+            ),
+            Err(ref reason) => ("400 Bad Request", html_page("Authentication failed", reason)),
+        };
         let response = format!(
-            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            "HTTP/1.1 {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
             body.len(),
             body
         );
         stream.write_all(response.as_bytes()).await?;
         stream.shutdown().await?;
 
-        Ok::<String, McplugError>(code)
+        outcome.map_err(McplugError::OAuthError)
     };
 
     tokio::time::timeout(timeout, accept_future)
@@ -46,21 +64,64 @@ pub async fn listen_for_callback(port: u16, timeout: Duration) -> Result<String,
         })?
 }
 
-fn parse_code_from_request(request: &str) -> Option<String> {
-    // Extract the request path from "GET /callback?code=... HTTP/1.1"
-    let first_line = request.lines().next()?;
-    let path = first_line.split_whitespace().nth(1)?;
-    let query = path.split('?').nth(1)?;
-
-    for param in query.split('&') {
-        if let Some(value) = param.strip_prefix("code=") {
-            let decoded = urldecode(value);
-            if !decoded.is_empty() {
-                return Some(decoded);
-            }
-        }
+/// The authorization code from a callback request, or why it was rejected.
+fn parse_callback(request: &str, expected_state: &str) -> Result<String, String> {
+    // The target of "GET /callback?code=... HTTP/1.1"
+    let path = request
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or_default();
+    let query = path.split_once('?').map(|(_, q)| q).unwrap_or_default();
+    parse_callback_query(query, expected_state)
+}
+
+/// The authorization code from the query string of a callback redirect, or
+/// why it was rejected.
+pub(crate) fn parse_callback_query(query: &str, expected_state: &str) -> Result<String, String> {
+    let params: Vec<(String, String)> = query
+        .split('&')
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (urldecode(key), urldecode(value)))
+        .collect();
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    if let Some(error) = param("error") {
+        return Err(match param("error_description") {
+            Some(description) => format!("Authorization failed: {error} ({description})"),
+            None => format!("Authorization failed: {error}"),
+        });
     }
-    None
+    if param("state") != Some(expected_state) {
+        return Err(
+            "OAuth callback state does not match this login; the redirect was not started by mcplug"
+                .to_string(),
+        );
+    }
+    param("code")
+        .filter(|code| !code.is_empty())
+        .map(String::from)
+        .ok_or_else(|| "No authorization code found in callback request".to_string())
+}
+
+fn html_page(title: &str, message: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html><body><h1>{}</h1><p>{}</p></body></html>",
+        title,
+        html_escape(message)
+    )
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
 }
 
 fn urldecode(s: &str) -> String {
@@ -96,19 +157,19 @@ mod tests {
     #[test]
     fn parse_code_from_valid_request() {
         let request = "GET /callback?code=abc123&state=xyz HTTP/1.1\r\nHost: localhost\r\n";
-        assert_eq!(parse_code_from_request(request), Some("abc123".into()));
+        assert_eq!(parse_callback(request, "xyz"), Ok("abc123".into()));
     }
 
     #[test]
     fn parse_code_missing() {
         let request = "GET /callback?state=xyz HTTP/1.1\r\nHost: localhost\r\n";
-        assert_eq!(parse_code_from_request(request), None);
+        assert!(parse_callback(request, "xyz").unwrap_err().contains("No authorization code"));
     }
 
     #[test]
     fn parse_code_urlencoded() {
-        let request = "GET /callback?code=abc%20123 HTTP/1.1\r\nHost: localhost\r\n";
-        assert_eq!(parse_code_from_request(request), Some("abc 123".into()));
+        let request = "GET /callback?code=abc%20123&state=a%2Bb HTTP/1.1\r\nHost: localhost\r\n";
+        assert_eq!(parse_callback(request, "a+b"), Ok("abc 123".into()));
     }
 
     #[test]
@@ -119,17 +180,66 @@ mod tests {
     }
 
     #[test]
-    fn parse_code_with_error_param() {
-        // When an error parameter is present but no code, should return None
+    fn parse_error_response() {
         let request =
             "GET /callback?error=access_denied&state=xyz HTTP/1.1\r\nHost: localhost\r\n";
-        assert_eq!(parse_code_from_request(request), None);
+        assert_eq!(
+            parse_callback(request, "xyz"),
+            Err("Authorization failed: access_denied".into())
+        );
+        let request = "GET /callback?error=access_denied&error_description=User+said+no HTTP/1.1\r\n";
+        assert_eq!(
+            parse_callback(request, "xyz"),
+            Err("Authorization failed: access_denied (User said no)".into())
+        );
+    }
+
+    #[test]
+    fn parse_rejects_state_mismatch() {
+        let request = "GET /callback?code=abc&state=forged HTTP/1.1\r\n";
+        assert!(parse_callback(request, "xyz").unwrap_err().contains("state does not match"));
+        let request = "GET /callback?code=abc HTTP/1.1\r\n";
+        assert!(parse_callback(request, "xyz").is_err());
     }
 
     #[test]
     fn parse_code_empty_code_value() {
-        // code= with empty value should return None
+        // code= with empty value is no code
         let request = "GET /callback?code=&state=xyz HTTP/1.1\r\nHost: localhost\r\n";
-        assert_eq!(parse_code_from_request(request), None);
+        assert!(parse_callback(request, "xyz").is_err());
+    }
+
+    #[test]
+    fn error_page_escapes_message() {
+        let page = html_page("Authentication failed", "<script>x</script>");
+        assert!(page.contains("&lt;script&gt;"));
+        assert!(!page.contains("<script>"));
+    }
+
+    #[tokio::test]
+    async fn callback_serves_error_page_on_state_mismatch() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let server = tokio::spawn(async move {
+            listen_for_callback(port, "expected", Duration::from_secs(5)).await
+        });
+
+        let mut stream = loop {
+            match tokio::net::TcpStream::connect(("127.0.0.1", port)).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        };
+        stream
+            .write_all(b"GET /callback?code=abc&state=forged HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 400"));
+        assert!(response.contains("Authentication failed"));
+        assert!(matches!(server.await.unwrap(), Err(McplugError::OAuthError(_))));
     }
 }
@@ -0,0 +1,262 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::error::McplugError;
+use crate::oauth::token::{TokenData, TokenResponse};
+
+/// Grant type for polling the token endpoint (RFC 8628 §3.4).
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Seconds between polls when the server doesn't say.
+const DEFAULT_INTERVAL_SECS: u64 = 5;
+
+/// Seconds added to the interval on each `slow_down` response.
+const SLOW_DOWN_SECS: u64 = 5;
+
+/// Device authorization response: the code to show the user and the one to
+/// poll with.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    /// The verification URI with the user code filled in, if offered.
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    /// Seconds until the device code expires.
+    pub expires_in: u64,
+    /// Minimum seconds between token polls.
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+}
+
+fn default_interval() -> u64 {
+    DEFAULT_INTERVAL_SECS
+}
+
+/// Error body of a token poll that hasn't produced a token.
+#[derive(Debug, Deserialize)]
+struct PollError {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// Ask the authorization server for a device and user code.
+pub async fn request_device_code(
+    endpoint: &str,
+    client_id: &str,
+    scopes: &[String],
+) -> Result<DeviceAuthorization, McplugError> {
+    let scope = scopes.join(" ");
+    let mut form = vec![("client_id", client_id)];
+    if !scopes.is_empty() {
+        form.push(("scope", scope.as_str()));
+    }
+    let resp = reqwest::Client::new()
+        .post(endpoint)
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| {
+            McplugError::OAuthError(format!("Device authorization request failed: {e}"))
+        })?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(McplugError::OAuthError(format!(
+            "Device authorization failed with status {status}: {body}"
+        )));
+    }
+
+    resp.json().await.map_err(|e| {
+        McplugError::OAuthError(format!("Failed to parse device authorization response: {e}"))
+    })
+}
+
+/// Poll the token endpoint until the user approves the device code.
+///
+/// Waits `interval` seconds between polls, slowing down when the server
+/// asks to, and gives up after `timeout` or when the code is denied or
+/// expires.
+pub async fn poll_device_token(
+    token_endpoint: &str,
+    device: &DeviceAuthorization,
+    client_id: &str,
+    timeout: Duration,
+) -> Result<TokenData, McplugError> {
+    let client = reqwest::Client::new();
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut interval = Duration::from_secs(device.interval);
+
+    loop {
+        tokio::time::sleep(interval).await;
+        if tokio::time::Instant::now() >= deadline {
+            return Err(McplugError::OAuthError(
+                "Timed out waiting for device authorization".into(),
+            ));
+        }
+
+        let resp = client
+            .post(token_endpoint)
+            .form(&[
+                ("grant_type", DEVICE_CODE_GRANT),
+                ("device_code", device.device_code.as_str()),
+                ("client_id", client_id),
+            ])
+            .send()
+            .await
+            .map_err(|e| McplugError::OAuthError(format!("Token poll request failed: {e}")))?;
+
+        if resp.status().is_success() {
+            let token: TokenResponse = resp.json().await.map_err(|e| {
+                McplugError::OAuthError(format!("Failed to parse token response: {e}"))
+            })?;
+            return Ok(token.into_token_data());
+        }
+
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        let Ok(poll) = serde_json::from_str::<PollError>(&body) else {
+            return Err(McplugError::OAuthError(format!(
+                "Token poll failed with status {status}: {body}"
+            )));
+        };
+        match poll.error.as_str() {
+            "authorization_pending" => {}
+            "slow_down" => interval += Duration::from_secs(SLOW_DOWN_SECS),
+            "access_denied" => {
+                return Err(McplugError::OAuthError("Device authorization was denied".into()))
+            }
+            "expired_token" => {
+                return Err(McplugError::OAuthError(
+                    "Device code expired before it was approved".into(),
+                ))
+            }
+            other => {
+                let detail = poll.error_description.unwrap_or_default();
+                return Err(McplugError::OAuthError(format!(
+                    "Device authorization failed: {other} {detail}"
+                )));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn device(interval: u64) -> DeviceAuthorization {
+        DeviceAuthorization {
+            device_code: "dev-123".into(),
+            user_code: "ABCD-EFGH".into(),
+            verification_uri: "https://auth.example.com/device".into(),
+            verification_uri_complete: None,
+            expires_in: 600,
+            interval,
+        }
+    }
+
+    #[test]
+    fn device_authorization_defaults_interval() {
+        let auth: DeviceAuthorization = serde_json::from_str(
+            r#"{"device_code": "d", "user_code": "U", "verification_uri": "https://a/device", "expires_in": 900}"#,
+        )
+        .unwrap();
+        assert_eq!(auth.interval, DEFAULT_INTERVAL_SECS);
+        assert!(auth.verification_uri_complete.is_none());
+    }
+
+    #[tokio::test]
+    async fn request_device_code_sends_client_and_scopes() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/device"))
+            .and(body_string_contains("client_id=mcplug"))
+            .and(body_string_contains("scope=repo+read"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "device_code": "dev-123",
+                "user_code": "ABCD-EFGH",
+                "verification_uri": "https://auth.example.com/device",
+                "expires_in": 600,
+                "interval": 1
+            })))
+            .mount(&server)
+            .await;
+
+        let auth = request_device_code(
+            &format!("{}/device", server.uri()),
+            "mcplug",
+            &["repo".into(), "read".into()],
+        )
+        .await
+        .unwrap();
+        assert_eq!(auth.user_code, "ABCD-EFGH");
+        assert_eq!(auth.interval, 1);
+    }
+
+    #[tokio::test]
+    async fn poll_waits_while_pending() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(
+                ResponseTemplate::new(400)
+                    .set_body_json(serde_json::json!({"error": "authorization_pending"})),
+            )
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("device_code=dev-123"))
+            .and(body_string_contains("grant-type%3Adevice_code"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "tok",
+                "token_type": "Bearer",
+                "expires_in": 3600
+            })))
+            .mount(&server)
+            .await;
+
+        let token = poll_device_token(
+            &format!("{}/token", server.uri()),
+            &device(0),
+            "mcplug",
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+        assert_eq!(token.access_token, "tok");
+        assert!(token.expires_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn poll_stops_when_denied() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(
+                ResponseTemplate::new(400)
+                    .set_body_json(serde_json::json!({"error": "access_denied"})),
+            )
+            .mount(&server)
+            .await;
+
+        let err = poll_device_token(
+            &format!("{}/token", server.uri()),
+            &device(0),
+            "mcplug",
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("denied"), "{err}");
+    }
+}
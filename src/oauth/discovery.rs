@@ -8,6 +8,12 @@ pub struct OAuthMetadata {
     pub token_endpoint: String,
     #[serde(default)]
     pub registration_endpoint: Option<String>,
+    /// Endpoint for the device authorization grant (RFC 8628), if supported.
+    #[serde(default)]
+    pub device_authorization_endpoint: Option<String>,
+    /// Token revocation endpoint (RFC 7009), if supported.
+    #[serde(default)]
+    pub revocation_endpoint: Option<String>,
     #[serde(default)]
     pub scopes_supported: Vec<String>,
 }
@@ -109,6 +115,7 @@ mod tests {
             "https://auth.example.com/token"
         );
         assert!(metadata.registration_endpoint.is_none());
+        assert!(metadata.device_authorization_endpoint.is_none());
         assert!(metadata.scopes_supported.is_empty());
     }
 }
@@ -1,38 +1,127 @@
+use std::future::Future;
+use std::path::Path;
 use std::time::Duration;
 
+use crate::config::AuthConfig;
 use crate::error::McplugError;
-use crate::oauth::cache::{load_cached_token, save_token};
-use crate::oauth::callback::listen_for_callback;
+use crate::oauth::cache::{
+    load_cached_token, refresh_lock_path, save_token, token_id, token_location,
+};
+use crate::oauth::callback::{listen_for_callback, parse_callback_query};
+use crate::oauth::device::{poll_device_token, request_device_code, DeviceAuthorization};
 use crate::oauth::discovery::discover_oauth_metadata;
-use crate::oauth::pkce::generate_pkce;
+use crate::oauth::pkce::{generate_pkce, generate_state};
 use crate::oauth::token::{exchange_code, refresh_token, TokenData};
+use crate::state::{default_store, FileLock, StateStore};
+
+/// Client ID used when a server's `auth` section doesn't set one.
+pub const DEFAULT_CLIENT_ID: &str = "mcplug";
+
+/// OAuth client settings for a server, from its `auth` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthClient {
+    pub client_id: String,
+    pub scopes: Vec<String>,
+    /// Fixed callback port; a free one is picked when unset.
+    pub redirect_port: Option<u16>,
+    /// Named token set to use instead of the server's default one.
+    pub identity: Option<String>,
+}
+
+impl Default for OAuthClient {
+    fn default() -> Self {
+        Self {
+            client_id: DEFAULT_CLIENT_ID.to_string(),
+            scopes: Vec::new(),
+            redirect_port: None,
+            identity: None,
+        }
+    }
+}
+
+impl OAuthClient {
+    /// The settings of an `oauth` auth section, or the defaults, with the
+    /// identity from `--as` (`MCPLUG_AUTH_IDENTITY`) taking precedence.
+    pub fn from_config(auth: Option<&AuthConfig>) -> Self {
+        let mut client = match auth {
+            Some(AuthConfig::OAuth {
+                client_id,
+                scopes,
+                redirect_port,
+                identity,
+            }) => Self {
+                client_id: client_id
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_CLIENT_ID.to_string()),
+                scopes: scopes.clone(),
+                redirect_port: *redirect_port,
+                identity: identity.clone(),
+            },
+            _ => Self::default(),
+        };
+        if let Some(identity) = identity_override(std::env::var("MCPLUG_AUTH_IDENTITY").ok()) {
+            client.identity = Some(identity);
+        }
+        client
+    }
+
+    /// The token ID of this client's token set for `server_name`.
+    pub fn token_id(&self, server_name: &str) -> String {
+        token_id(server_name, self.identity.as_deref())
+    }
+
+    /// Authorization URL for the PKCE flow, requesting the configured scopes.
+    fn authorization_url(
+        &self,
+        endpoint: &str,
+        redirect_uri: &str,
+        code_challenge: &str,
+        state: &str,
+    ) -> String {
+        let mut url = format!(
+            "{}?response_type=code&client_id={}&redirect_uri={}&code_challenge={}&code_challenge_method=S256&state={}",
+            endpoint,
+            urlencoded(&self.client_id),
+            urlencoded(redirect_uri),
+            code_challenge,
+            urlencoded(state),
+        );
+        if !self.scopes.is_empty() {
+            url.push_str("&scope=");
+            url.push_str(&urlencoded(&self.scopes.join(" ")));
+        }
+        url
+    }
+}
+
+fn identity_override(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.is_empty())
+}
 
 /// Run the full OAuth browser flow for a given server.
 pub async fn run_oauth_flow(
     base_url: &str,
     server_name: &str,
+    client: &OAuthClient,
     timeout: Duration,
 ) -> Result<TokenData, McplugError> {
     // 1. Discover OAuth metadata
     let metadata = discover_oauth_metadata(base_url).await?;
 
-    // 2. Generate PKCE challenge
+    // 2. Generate PKCE challenge and CSRF state
     let pkce = generate_pkce();
+    let state = generate_state();
 
-    // 3. Find a free port and build redirect URI
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
-    let port = listener.local_addr()?.port();
-    drop(listener); // Release the port so the callback server can bind to it
+    // 3. Pick the callback port and build redirect URI
+    let port = callback_port(client).await?;
     let redirect_uri = format!("http://localhost:{port}/callback");
 
     // 4. Build authorization URL and open browser
-    let client_id = "mcplug";
-    let auth_url = format!(
-        "{}?response_type=code&client_id={}&redirect_uri={}&code_challenge={}&code_challenge_method=S256",
-        metadata.authorization_endpoint,
-        client_id,
-        urlencoded(&redirect_uri),
-        pkce.code_challenge,
+    let auth_url = client.authorization_url(
+        &metadata.authorization_endpoint,
+        &redirect_uri,
+        &pkce.code_challenge,
+        &state,
     );
 
     if webbrowser::open(&auth_url).is_err() {
@@ -40,7 +129,7 @@ pub async fn run_oauth_flow(
     }
 
     // 5. Listen for callback
-    let code = listen_for_callback(port, timeout).await?;
+    let code = listen_for_callback(port, &state, timeout).await?;
 
     // 6. Exchange code for tokens
     let token = exchange_code(
@@ -48,9 +137,11 @@ pub async fn run_oauth_flow(
         &code,
         &pkce.code_verifier,
         &redirect_uri,
-        client_id,
+        &client.client_id,
     )
-    .await?;
+    .await?
+    .or_requested_scopes(&client.scopes)
+    .issued_for(base_url, &metadata.token_endpoint);
 
     // 7. Cache tokens
     save_token(server_name, &token)?;
@@ -58,25 +149,142 @@ pub async fn run_oauth_flow(
     Ok(token)
 }
 
+/// Run the OAuth authorization code flow without a browser or callback
+/// listener, for remote machines.
+///
+/// `prompt` is given the authorization URL to open on any machine and
+/// returns what the user pasted back: the URL the browser was redirected to
+/// (whose `state` is checked) or just the authorization code.
+pub async fn run_manual_flow(
+    base_url: &str,
+    server_name: &str,
+    client: &OAuthClient,
+    prompt: impl FnOnce(&str) -> Result<String, McplugError>,
+) -> Result<TokenData, McplugError> {
+    let metadata = discover_oauth_metadata(base_url).await?;
+    let pkce = generate_pkce();
+    let state = generate_state();
+    // Nothing listens here; the user copies the redirect from the address bar
+    let port = callback_port(client).await?;
+    let redirect_uri = format!("http://localhost:{port}/callback");
+    let auth_url = client.authorization_url(
+        &metadata.authorization_endpoint,
+        &redirect_uri,
+        &pkce.code_challenge,
+        &state,
+    );
+
+    let code = code_from_pasted(&prompt(&auth_url)?, &state)?;
+    let token = exchange_code(
+        &metadata.token_endpoint,
+        &code,
+        &pkce.code_verifier,
+        &redirect_uri,
+        &client.client_id,
+    )
+    .await?
+    .or_requested_scopes(&client.scopes)
+    .issued_for(base_url, &metadata.token_endpoint);
+    save_token(server_name, &token)?;
+    Ok(token)
+}
+
+/// The configured callback port, or a free one.
+async fn callback_port(client: &OAuthClient) -> Result<u16, McplugError> {
+    if let Some(port) = client.redirect_port {
+        return Ok(port);
+    }
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    drop(listener); // Release the port so the callback server can bind to it
+    Ok(port)
+}
+
+/// The authorization code from a pasted redirect URL or bare code.
+fn code_from_pasted(pasted: &str, expected_state: &str) -> Result<String, McplugError> {
+    let pasted = pasted.trim();
+    let pasted = pasted.split_once('#').map_or(pasted, |(url, _)| url);
+    match pasted.split_once('?') {
+        Some((_, query)) => {
+            parse_callback_query(query, expected_state).map_err(McplugError::OAuthError)
+        }
+        None if pasted.is_empty() => Err(McplugError::OAuthError(
+            "No redirect URL or authorization code was entered".to_string(),
+        )),
+        None => Ok(pasted.to_string()),
+    }
+}
+
+/// Run the OAuth device authorization grant (RFC 8628) for a server.
+///
+/// For machines that can't open a browser or take a localhost callback:
+/// `show_code` is given the user code and verification URI to display, and
+/// the token endpoint is polled until the user approves on another device.
+/// Polling stops when the device code expires, or after `timeout` if given.
+pub async fn run_device_flow(
+    base_url: &str,
+    server_name: &str,
+    client: &OAuthClient,
+    timeout: Option<Duration>,
+    show_code: impl FnOnce(&DeviceAuthorization),
+) -> Result<TokenData, McplugError> {
+    let metadata = discover_oauth_metadata(base_url).await?;
+    let endpoint = metadata.device_authorization_endpoint.ok_or_else(|| {
+        McplugError::OAuthError(format!(
+            "Server '{server_name}' does not support the device authorization grant"
+        ))
+    })?;
+
+    let device = request_device_code(&endpoint, &client.client_id, &client.scopes).await?;
+    show_code(&device);
+
+    let expires = Duration::from_secs(device.expires_in);
+    let timeout = timeout.map_or(expires, |t| t.min(expires));
+    let token = poll_device_token(&metadata.token_endpoint, &device, &client.client_id, timeout)
+        .await?
+        .or_requested_scopes(&client.scopes)
+        .issued_for(base_url, &metadata.token_endpoint);
+
+    save_token(server_name, &token)?;
+    Ok(token)
+}
+
+/// How long to wait for another process that is refreshing the same
+/// server's tokens.
+const REFRESH_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// Get a valid token for a server, using cache and refresh if possible.
+///
+/// Refreshes hold a per-server lock file, so when several invocations find
+/// the same expired token only one of them uses the refresh token; the
+/// others wait and reuse the token it saved. Many servers rotate refresh
+/// tokens, so a second refresh with the old one would fail or revoke the
+/// new one.
+///
+/// A token issued for another origin than `base_url`'s is neither sent nor
+/// refreshed, so renaming a server's URL in the config can't leak it.
 pub async fn get_valid_token(
     server_name: &str,
     base_url: &str,
+    client: &OAuthClient,
 ) -> Result<TokenData, McplugError> {
     // Load cached token
     if let Some(token) = load_cached_token(server_name) {
+        if !token.is_for(base_url) {
+            tracing::debug!("Cached token for '{server_name}' was issued for another server");
+            return Err(McplugError::AuthRequired(server_name.to_string()));
+        }
         if !token.is_expired() {
             return Ok(token);
         }
 
         // Try to refresh if we have a refresh token
-        if let Some(ref refresh_tok) = token.refresh_token {
-            let metadata = discover_oauth_metadata(base_url).await?;
-            match refresh_token(&metadata.token_endpoint, refresh_tok, "mcplug").await {
-                Ok(new_token) => {
-                    save_token(server_name, &new_token)?;
-                    return Ok(new_token);
-                }
+        if token.refresh_token.is_some() {
+            let refreshed =
+                refresh_ahead(server_name, base_url, client, chrono::Duration::zero()).await;
+            match refreshed {
+                Ok(Some(token)) => return Ok(token),
+                Ok(None) => {}
                 Err(e) => {
                     tracing::debug!("Token refresh failed: {e}");
                 }
@@ -87,6 +295,84 @@ pub async fn get_valid_token(
     Err(McplugError::AuthRequired(server_name.to_string()))
 }
 
+/// Refresh a server's cached token if it expires within `ahead`, and save
+/// the new one. Returns the current token, or `None` when there is no
+/// token or no refresh token.
+///
+/// The refresh token is only sent to the token endpoint that issued it,
+/// and only while the token belongs to `base_url`'s origin.
+pub async fn refresh_ahead(
+    server_name: &str,
+    base_url: &str,
+    client: &OAuthClient,
+    ahead: chrono::Duration,
+) -> Result<Option<TokenData>, McplugError> {
+    let store = default_store()?;
+    refresh_shared(
+        store.as_ref(),
+        server_name,
+        &refresh_lock_path(server_name),
+        base_url,
+        ahead,
+        |endpoint, refresh_tok| async move {
+            refresh_token(&endpoint, &refresh_tok, &client.client_id).await
+        },
+    )
+    .await
+}
+
+/// Refresh a server's cached token under its refresh lock, if it expires
+/// within `ahead`.
+///
+/// The cache is re-read once the lock is held: if another process already
+/// refreshed the token, that token is returned without calling `refresh`,
+/// which is given the token endpoint and refresh token otherwise. Returns
+/// `None` when there is no token to refresh, and `AuthRequired` when the
+/// token isn't for `base_url` or doesn't record its token endpoint.
+async fn refresh_shared<F, Fut>(
+    store: &dyn StateStore,
+    server_name: &str,
+    lock_path: &Path,
+    base_url: &str,
+    ahead: chrono::Duration,
+    refresh: F,
+) -> Result<Option<TokenData>, McplugError>
+where
+    F: FnOnce(String, String) -> Fut,
+    Fut: Future<Output = Result<TokenData, McplugError>>,
+{
+    let _lock = FileLock::acquire(lock_path, REFRESH_LOCK_TIMEOUT).await?;
+    let (namespace, key) = token_location(server_name);
+    let Some(token) = store.load::<TokenData>(namespace, &key) else {
+        return Ok(None);
+    };
+    if !token.is_for(base_url) {
+        return Err(McplugError::AuthRequired(server_name.to_string()));
+    }
+    if !token.expires_within(ahead) {
+        return Ok(Some(token));
+    }
+    let Some(refresh_tok) = token.refresh_token else {
+        return Ok(None);
+    };
+    let Some(endpoint) = token.token_endpoint.clone() else {
+        return Err(McplugError::AuthRequired(server_name.to_string()));
+    };
+    let mut new_token = refresh(endpoint, refresh_tok.clone()).await?;
+    // Servers that don't rotate refresh tokens omit them from the response
+    if new_token.refresh_token.is_none() {
+        new_token.refresh_token = Some(refresh_tok);
+    }
+    // An omitted scope is unchanged from the original grant
+    if new_token.scope.is_none() {
+        new_token.scope = token.scope;
+    }
+    new_token.resource = token.resource;
+    new_token.token_endpoint = token.token_endpoint;
+    store.save(namespace, &key, &new_token)?;
+    Ok(Some(new_token))
+}
+
 fn urlencoded(s: &str) -> String {
     let mut result = String::with_capacity(s.len() * 2);
     for b in s.bytes() {
@@ -106,6 +392,7 @@ fn urlencoded(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::oauth::cache::TOKENS_KEY;
 
     #[test]
     fn urlencoded_basic_characters_pass_through() {
@@ -136,10 +423,224 @@ mod tests {
     #[tokio::test]
     async fn get_valid_token_returns_auth_required_when_no_cache() {
         // With no cached token, get_valid_token should return AuthRequired
-        let result = get_valid_token("nonexistent-server-xyz-test", "https://example.com").await;
+        let result = get_valid_token(
+            "nonexistent-server-xyz-test",
+            "https://example.com",
+            &OAuthClient::default(),
+        )
+        .await;
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(matches!(err, McplugError::AuthRequired(_)));
         assert_eq!(err.code(), "auth_required");
     }
+
+    #[test]
+    fn oauth_client_from_config() {
+        assert_eq!(OAuthClient::from_config(None), OAuthClient::default());
+        let bearer = AuthConfig::Bearer { token: "t".into() };
+        assert_eq!(OAuthClient::from_config(Some(&bearer)).client_id, "mcplug");
+        let oauth = AuthConfig::OAuth {
+            client_id: Some("my-app".into()),
+            scopes: vec!["repo".into(), "read:user".into()],
+            redirect_port: Some(8765),
+            identity: Some("work".into()),
+        };
+        let client = OAuthClient::from_config(Some(&oauth));
+        assert_eq!(client.token_id("github"), "github:work");
+        assert_eq!(client.client_id, "my-app");
+        assert_eq!(client.scopes, vec!["repo", "read:user"]);
+        assert_eq!(client.redirect_port, Some(8765));
+    }
+
+    #[test]
+    fn identity_override_ignores_empty() {
+        assert_eq!(identity_override(None), None);
+        assert_eq!(identity_override(Some(String::new())), None);
+        assert_eq!(identity_override(Some("work".into())), Some("work".into()));
+        assert_eq!(OAuthClient::default().token_id("github"), "github");
+    }
+
+    #[test]
+    fn authorization_url_includes_scopes() {
+        let url = OAuthClient::default().authorization_url("https://a/authorize", "http://localhost:1/callback", "ch", "st");
+        assert!(url.starts_with("https://a/authorize?response_type=code&client_id=mcplug&"));
+        assert!(url.ends_with("&state=st"));
+        assert!(!url.contains("scope="));
+
+        let client = OAuthClient {
+            client_id: "my app".into(),
+            scopes: vec!["repo".into(), "read:user".into()],
+            redirect_port: None,
+            identity: None,
+        };
+        let url = client.authorization_url("https://a/authorize", "http://localhost:1/callback", "ch", "st");
+        assert!(url.contains("client_id=my%20app&"));
+        assert!(url.ends_with("&scope=repo%20read%3Auser"));
+    }
+
+    #[test]
+    fn code_from_pasted_url_or_code() {
+        let url = "http://localhost:8765/callback?code=abc%2F1&state=st";
+        assert_eq!(code_from_pasted(url, "st").unwrap(), "abc/1");
+        assert_eq!(code_from_pasted(&format!("  {url}#frag\n"), "st").unwrap(), "abc/1");
+        assert!(code_from_pasted(url, "other").is_err());
+        assert_eq!(code_from_pasted(" raw-code \n", "st").unwrap(), "raw-code");
+        assert!(code_from_pasted("\n", "st").is_err());
+        let denied = "http://localhost:1/callback?error=access_denied&state=st";
+        assert!(code_from_pasted(denied, "st")
+            .unwrap_err()
+            .to_string()
+            .contains("access_denied"));
+    }
+
+    #[tokio::test]
+    async fn manual_flow_exchanges_pasted_redirect() {
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/.well-known/oauth-authorization-server"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "authorization_endpoint": format!("{}/authorize", server.uri()),
+                "token_endpoint": format!("{}/token", server.uri()),
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("code=pasted-code"))
+            .and(body_string_contains("redirect_uri=http%3A%2F%2Flocalhost%3A8765%2Fcallback"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "manual-access",
+                "token_type": "Bearer",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let server_name = "test-manual-oauth-flow";
+        let client = OAuthClient {
+            redirect_port: Some(8765),
+            ..OAuthClient::default()
+        };
+        let token = run_manual_flow(&server.uri(), server_name, &client, |url| {
+            let state = url.split("&state=").nth(1).unwrap().split('&').next().unwrap();
+            Ok(format!("http://localhost:8765/callback?code=pasted-code&state={state}"))
+        })
+        .await;
+
+        let cache = crate::oauth::cache::cache_path(server_name);
+        let _ = std::fs::remove_file(&cache);
+        let _ = std::fs::remove_dir(cache.parent().unwrap());
+        assert_eq!(token.unwrap().access_token, "manual-access");
+    }
+
+    const BASE_URL: &str = "https://srv.example/mcp";
+    const TOKEN_ENDPOINT: &str = "https://auth.example/token";
+
+    fn expired_token(access: &str, refresh: Option<&str>) -> TokenData {
+        TokenData {
+            access_token: access.into(),
+            refresh_token: refresh.map(String::from),
+            expires_at: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+            token_type: "Bearer".into(),
+            scope: None,
+            resource: None,
+            token_endpoint: None,
+        }
+        .issued_for(BASE_URL, TOKEN_ENDPOINT)
+    }
+
+    #[tokio::test]
+    async fn concurrent_refreshes_use_the_refresh_token_once() {
+        use crate::state::FileStore;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(FileStore::new(dir.path()));
+        (store.as_ref() as &dyn StateStore)
+            .save("srv", TOKENS_KEY, &expired_token("old", Some("r1")))
+            .unwrap();
+        let lock_path = dir.path().join("srv").join("tokens.lock");
+        let refreshes = Arc::new(AtomicUsize::new(0));
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for _ in 0..4 {
+            let (store, lock_path, refreshes) =
+                (store.clone(), lock_path.clone(), refreshes.clone());
+            tasks.spawn(async move {
+                refresh_shared(store.as_ref(), "srv", &lock_path, BASE_URL, chrono::Duration::zero(), |endpoint, refresh_tok| async move {
+                    assert_eq!(endpoint, TOKEN_ENDPOINT);
+                    assert_eq!(refresh_tok, "r1");
+                    refreshes.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    Ok(TokenData {
+                        access_token: "new".into(),
+                        refresh_token: None,
+                        expires_at: None,
+                        token_type: "Bearer".into(),
+                        scope: None,
+                        resource: None,
+                        token_endpoint: None,
+                    })
+                })
+                .await
+                .unwrap()
+                .unwrap()
+            });
+        }
+        while let Some(token) = tasks.join_next().await {
+            assert_eq!(token.unwrap().access_token, "new");
+        }
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        assert!(!lock_path.exists());
+        // The old refresh token is kept when the response has none
+        let saved: TokenData = (store.as_ref() as &dyn StateStore)
+            .load("srv", TOKENS_KEY)
+            .unwrap();
+        assert_eq!(saved.refresh_token.as_deref(), Some("r1"));
+        assert!(saved.is_for(BASE_URL));
+    }
+
+    #[tokio::test]
+    async fn refresh_without_refresh_token_is_none() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = crate::state::FileStore::new(dir.path());
+        let lock_path = dir.path().join("tokens.lock");
+        let none = refresh_shared(&store, "srv", &lock_path, BASE_URL, chrono::Duration::zero(), |_, _| async {
+            unreachable!("nothing to refresh")
+        })
+        .await
+        .unwrap();
+        assert!(none.is_none());
+
+        (&store as &dyn StateStore)
+            .save("srv", TOKENS_KEY, &expired_token("old", None))
+            .unwrap();
+        let none = refresh_shared(&store, "srv", &lock_path, BASE_URL, chrono::Duration::zero(), |_, _| async {
+            unreachable!("nothing to refresh")
+        })
+        .await
+        .unwrap();
+        assert!(none.is_none());
+    }
+
+    #[tokio::test]
+    async fn token_for_another_origin_is_not_refreshed() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = crate::state::FileStore::new(dir.path());
+        let lock_path = dir.path().join("tokens.lock");
+        (&store as &dyn StateStore)
+            .save("srv", TOKENS_KEY, &expired_token("old", Some("r1")))
+            .unwrap();
+        let err = refresh_shared(&store, "srv", &lock_path, "https://evil.example/mcp", chrono::Duration::zero(), |_, _| async {
+            unreachable!("the refresh token must not leave its origin")
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(err, McplugError::AuthRequired(_)));
+    }
 }
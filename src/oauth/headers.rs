@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use crate::config::{AuthConfig, ServerConfig};
+use crate::oauth::flow::OAuthClient;
+
+/// The HTTP headers to send to a server: its configured `headers`, plus an
+/// `Authorization` header for a `bearer` auth section.
+///
+/// An explicit `Authorization` entry in `headers` always wins.
+pub fn auth_headers(cfg: &ServerConfig) -> HashMap<String, String> {
+    let mut headers = cfg.headers.clone();
+    if has_authorization(cfg) {
+        return headers;
+    }
+    if let Some(AuthConfig::Bearer { token }) = &cfg.auth {
+        headers.insert("Authorization".to_string(), format!("Bearer {token}"));
+    }
+    headers
+}
+
+/// The OAuth client whose cached `mcplug auth` token a server's requests
+/// should carry: that of any server without a `bearer` auth section or an
+/// explicit `Authorization` header.
+pub fn oauth_client(cfg: &ServerConfig) -> Option<OAuthClient> {
+    if has_authorization(cfg) || matches!(cfg.auth, Some(AuthConfig::Bearer { .. })) {
+        return None;
+    }
+    Some(OAuthClient::from_config(cfg.auth.as_ref()))
+}
+
+fn has_authorization(cfg: &ServerConfig) -> bool {
+    cfg.headers
+        .keys()
+        .any(|name| name.eq_ignore_ascii_case("authorization"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(auth: Option<AuthConfig>) -> ServerConfig {
+        let mut cfg: ServerConfig =
+            serde_json::from_value(serde_json::json!({"baseUrl": "https://example.com/mcp"}))
+                .unwrap();
+        cfg.auth = auth;
+        cfg
+    }
+
+    #[test]
+    fn bearer_auth_sets_authorization() {
+        let cfg = config(Some(AuthConfig::Bearer { token: "tok".into() }));
+        assert_eq!(auth_headers(&cfg)["Authorization"], "Bearer tok");
+        assert!(oauth_client(&cfg).is_none());
+    }
+
+    #[test]
+    fn oauth_auth_uses_cached_token_client() {
+        let cfg = config(Some(AuthConfig::OAuth {
+            client_id: Some("my-app".into()),
+            scopes: vec![],
+            redirect_port: None,
+            identity: None,
+        }));
+        assert!(auth_headers(&cfg).is_empty());
+        assert_eq!(oauth_client(&cfg).unwrap().client_id, "my-app");
+    }
+
+    #[test]
+    fn explicit_authorization_header_wins() {
+        let mut cfg = config(Some(AuthConfig::Bearer { token: "tok".into() }));
+        cfg.headers
+            .insert("authorization".to_string(), "Basic abc".to_string());
+        let headers = auth_headers(&cfg);
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers["authorization"], "Basic abc");
+
+        cfg.auth = None;
+        assert!(oauth_client(&cfg).is_none());
+    }
+
+    #[test]
+    fn no_auth_keeps_configured_headers_and_uses_cached_token() {
+        let mut cfg = config(None);
+        cfg.headers.insert("X-Api-Key".to_string(), "k".to_string());
+        assert_eq!(auth_headers(&cfg), cfg.headers);
+        assert_eq!(oauth_client(&cfg), Some(OAuthClient::default()));
+    }
+}
@@ -1,13 +1,24 @@
 pub mod cache;
 pub mod callback;
+pub mod device;
 pub mod discovery;
 pub mod flow;
+pub mod headers;
 pub mod pkce;
+pub mod refresh;
 pub mod token;
 
-pub use cache::{cache_path, load_cached_token, save_token};
+pub use cache::{
+    cache_path, cached_tokens, delete_token, load_cached_token, refresh_lock_path, save_token,
+    split_token_id, token_id, validate_identity,
+};
 pub use callback::listen_for_callback;
+pub use device::{poll_device_token, request_device_code, DeviceAuthorization};
 pub use discovery::{discover_oauth_metadata, OAuthMetadata};
-pub use flow::{get_valid_token, run_oauth_flow};
-pub use pkce::{generate_pkce, PkceChallenge};
-pub use token::{exchange_code, refresh_token, TokenData};
+pub use flow::{
+    get_valid_token, refresh_ahead, run_device_flow, run_manual_flow, run_oauth_flow, OAuthClient,
+};
+pub use headers::{auth_headers, oauth_client};
+pub use pkce::{generate_pkce, generate_state, PkceChallenge};
+pub use refresh::spawn_token_refresher;
+pub use token::{exchange_code, refresh_token, revoke_token, TokenData};
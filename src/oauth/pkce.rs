@@ -24,6 +24,14 @@ pub fn generate_pkce() -> PkceChallenge {
     }
 }
 
+/// Random `state` for an authorization request, echoed back on the callback
+/// so a redirect that mcplug didn't start can be rejected (CSRF).
+pub fn generate_state() -> String {
+    let mut buf = [0u8; 16];
+    rand::rng().fill(&mut buf);
+    URL_SAFE_NO_PAD.encode(buf)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,6 +54,14 @@ mod tests {
         assert_eq!(pkce.code_challenge, expected);
     }
 
+    #[test]
+    fn state_is_random_and_url_safe() {
+        let a = generate_state();
+        assert_eq!(a.len(), 22);
+        assert_ne!(a, generate_state());
+        assert!(a.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_'));
+    }
+
     #[test]
     fn pkce_generates_unique_values() {
         let a = generate_pkce();
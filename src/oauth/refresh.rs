@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::error::McplugError;
+use crate::oauth::cache::load_cached_token;
+use crate::oauth::flow::{refresh_ahead, OAuthClient};
+
+/// How long before expiry a long-lived connection's token is refreshed.
+const REFRESH_AHEAD: Duration = Duration::from_secs(5 * 60);
+
+/// Wait before retrying a failed background refresh.
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Keep a server's cached token fresh in the background, for connections
+/// that stay open long enough to outlive it (keep-alive servers).
+///
+/// The token is refreshed `REFRESH_AHEAD` before it expires, or halfway
+/// through its remaining lifetime if that is shorter, and saved to the
+/// cache the transport reads on each request. The task ends when the
+/// server has no cached token, no refresh token, or no expiry, or when the
+/// token was issued for another origin than `base_url`'s; abort the
+/// handle to stop it earlier.
+pub fn spawn_token_refresher(
+    server_name: String,
+    base_url: String,
+    client: OAuthClient,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let Some(token) = load_cached_token(&server_name) else {
+                return;
+            };
+            let (Some(expires_at), Some(_)) = (token.expires_at, token.refresh_token) else {
+                return;
+            };
+            let (wait, ahead) = refresh_schedule(expires_at - chrono::Utc::now());
+            tokio::time::sleep(wait).await;
+
+            match refresh_ahead(&server_name, &base_url, &client, ahead).await {
+                Ok(Some(_)) => tracing::debug!(server = %server_name, "refreshed OAuth token"),
+                Ok(None) | Err(McplugError::AuthRequired(_)) => return,
+                Err(e) => {
+                    tracing::warn!(server = %server_name, error = %e, "background token refresh failed");
+                    tokio::time::sleep(RETRY_DELAY).await;
+                }
+            }
+        }
+    })
+}
+
+/// How long to sleep before refreshing a token with `remaining` lifetime,
+/// and how close to expiry it will then be.
+fn refresh_schedule(remaining: chrono::Duration) -> (Duration, chrono::Duration) {
+    let remaining = remaining.to_std().unwrap_or(Duration::ZERO);
+    let ahead = REFRESH_AHEAD.min(remaining / 2);
+    let ahead_chrono = chrono::Duration::from_std(ahead).unwrap_or(chrono::Duration::zero());
+    (remaining - ahead, ahead_chrono)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedule_refreshes_ahead_of_expiry() {
+        let (wait, ahead) = refresh_schedule(chrono::Duration::hours(1));
+        assert_eq!(wait, Duration::from_secs(55 * 60));
+        assert_eq!(ahead, chrono::Duration::minutes(5));
+    }
+
+    #[test]
+    fn schedule_halves_short_lifetimes() {
+        let (wait, ahead) = refresh_schedule(chrono::Duration::seconds(60));
+        assert_eq!(wait, Duration::from_secs(30));
+        assert_eq!(ahead, chrono::Duration::seconds(30));
+    }
+
+    #[test]
+    fn schedule_refreshes_expired_tokens_now() {
+        let (wait, ahead) = refresh_schedule(chrono::Duration::seconds(-10));
+        assert_eq!(wait, Duration::ZERO);
+        assert_eq!(ahead, chrono::Duration::zero());
+    }
+
+    #[tokio::test]
+    async fn refresher_ends_without_cached_token() {
+        let handle = spawn_token_refresher(
+            "test-refresher-no-token-xyz".into(),
+            "https://example.com/mcp".into(),
+            OAuthClient::default(),
+        );
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
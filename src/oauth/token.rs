@@ -8,12 +8,51 @@ pub struct TokenData {
     pub refresh_token: Option<String>,
     pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
     pub token_type: String,
+    /// Space-separated scopes granted, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Origin of the server the token was issued for, such as
+    /// `https://example.com`. The token is only sent there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource: Option<String>,
+    /// Token endpoint that issued the token, and the only one its refresh
+    /// token is sent to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_endpoint: Option<String>,
 }
 
 impl TokenData {
+    /// Record the server at `base_url` as the one the token was issued for,
+    /// by the authorization server's `token_endpoint`.
+    pub fn issued_for(mut self, base_url: &str, token_endpoint: &str) -> Self {
+        self.resource = origin(base_url);
+        self.token_endpoint = Some(token_endpoint.to_string());
+        self
+    }
+
+    /// Whether the token was issued for a server with the same origin as
+    /// `base_url`. Tokens cached before the origin was recorded match none.
+    pub fn is_for(&self, base_url: &str) -> bool {
+        self.resource.is_some() && self.resource == origin(base_url)
+    }
+
+    /// Record the requested `scopes` when the server didn't say which it
+    /// granted, which per RFC 6749 means it granted them as requested.
+    pub fn or_requested_scopes(mut self, scopes: &[String]) -> Self {
+        if self.scope.is_none() && !scopes.is_empty() {
+            self.scope = Some(scopes.join(" "));
+        }
+        self
+    }
+
     pub fn is_expired(&self) -> bool {
+        self.expires_within(chrono::Duration::zero())
+    }
+
+    /// Whether the token has expired or will within `margin`.
+    pub fn expires_within(&self, margin: chrono::Duration) -> bool {
         match self.expires_at {
-            Some(expires) => chrono::Utc::now() >= expires,
+            Some(expires) => chrono::Utc::now() + margin >= expires,
             None => false,
         }
     }
@@ -21,15 +60,17 @@ impl TokenData {
 
 /// Raw token response from the OAuth server.
 #[derive(Debug, Deserialize)]
-struct TokenResponse {
+pub(crate) struct TokenResponse {
     access_token: String,
     refresh_token: Option<String>,
     expires_in: Option<i64>,
     token_type: String,
+    #[serde(default)]
+    scope: Option<String>,
 }
 
 impl TokenResponse {
-    fn into_token_data(self) -> TokenData {
+    pub(crate) fn into_token_data(self) -> TokenData {
         let expires_at = self
             .expires_in
             .map(|secs| chrono::Utc::now() + chrono::Duration::seconds(secs));
@@ -38,10 +79,19 @@ impl TokenResponse {
             refresh_token: self.refresh_token,
             expires_at,
             token_type: self.token_type,
+            scope: self.scope,
+            resource: None,
+            token_endpoint: None,
         }
     }
 }
 
+/// The scheme, host, and port of `url`.
+fn origin(url: &str) -> Option<String> {
+    let origin = reqwest::Url::parse(url).ok()?.origin();
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
 pub async fn exchange_code(
     token_endpoint: &str,
     code: &str,
@@ -110,9 +160,41 @@ pub async fn refresh_token(
     Ok(token_resp.into_token_data())
 }
 
+/// Revoke a token at the server's revocation endpoint (RFC 7009).
+/// `token_type_hint` is `access_token` or `refresh_token`.
+pub async fn revoke_token(
+    revocation_endpoint: &str,
+    token: &str,
+    token_type_hint: &str,
+    client_id: &str,
+) -> Result<(), McplugError> {
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(revocation_endpoint)
+        .form(&[
+            ("token", token),
+            ("token_type_hint", token_type_hint),
+            ("client_id", client_id),
+        ])
+        .send()
+        .await
+        .map_err(|e| McplugError::OAuthError(format!("Token revocation request failed: {e}")))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(McplugError::OAuthError(format!(
+            "Token revocation failed with status {status}: {body}"
+        )));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[test]
     fn token_data_serialization_roundtrip() {
@@ -121,6 +203,9 @@ mod tests {
             refresh_token: Some("refresh456".into()),
             expires_at: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
             token_type: "Bearer".into(),
+            scope: None,
+            resource: None,
+            token_endpoint: None,
         };
 
         let json = serde_json::to_string(&token).unwrap();
@@ -139,6 +224,9 @@ mod tests {
             refresh_token: None,
             expires_at: None,
             token_type: "Bearer".into(),
+            scope: None,
+            resource: None,
+            token_endpoint: None,
         };
 
         let json = serde_json::to_string(&token).unwrap();
@@ -156,6 +244,9 @@ mod tests {
             refresh_token: None,
             expires_at: None,
             token_type: "Bearer".into(),
+            scope: None,
+            resource: None,
+            token_endpoint: None,
         };
         assert!(!token.is_expired());
     }
@@ -167,6 +258,9 @@ mod tests {
             refresh_token: None,
             expires_at: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
             token_type: "Bearer".into(),
+            scope: None,
+            resource: None,
+            token_endpoint: None,
         };
         assert!(!token.is_expired());
     }
@@ -178,7 +272,66 @@ mod tests {
             refresh_token: None,
             expires_at: Some(chrono::Utc::now() - chrono::Duration::hours(1)),
             token_type: "Bearer".into(),
+            scope: None,
+            resource: None,
+            token_endpoint: None,
         };
         assert!(token.is_expired());
     }
+
+    #[test]
+    fn token_is_only_for_its_origin() {
+        let token = TokenData {
+            access_token: "a".into(),
+            refresh_token: None,
+            expires_at: None,
+            token_type: "Bearer".into(),
+            scope: None,
+            resource: None,
+            token_endpoint: None,
+        };
+        assert!(!token.is_for("https://example.com/mcp"));
+
+        let token = token.issued_for("https://example.com/mcp", "https://auth.example.com/token");
+        assert_eq!(token.resource.as_deref(), Some("https://example.com"));
+        assert_eq!(token.token_endpoint.as_deref(), Some("https://auth.example.com/token"));
+        assert!(token.is_for("https://example.com/other"));
+        assert!(token.is_for("https://EXAMPLE.com:443/mcp"));
+        assert!(!token.is_for("https://evil.example/mcp"));
+        assert!(!token.is_for("http://example.com/mcp"));
+        assert!(!token.is_for("https://example.com:8443/mcp"));
+        assert!(!token.is_for("not a url"));
+    }
+
+    #[tokio::test]
+    async fn revoke_token_posts_token_and_hint() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/revoke"))
+            .and(body_string_contains("token=rt-1"))
+            .and(body_string_contains("token_type_hint=refresh_token"))
+            .and(body_string_contains("client_id=mcplug"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let endpoint = format!("{}/revoke", server.uri());
+        revoke_token(&endpoint, "rt-1", "refresh_token", "mcplug")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn revoke_token_reports_error_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("unsupported_token_type"))
+            .mount(&server)
+            .await;
+        let endpoint = format!("{}/revoke", server.uri());
+        let err = revoke_token(&endpoint, "at", "access_token", "mcplug")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("unsupported_token_type"));
+    }
 }
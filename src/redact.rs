@@ -0,0 +1,268 @@
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::error::McplugError;
+use crate::state::{default_store, StateStore};
+
+/// Schema keyword marking a property as sensitive (`"x-sensitive": true`).
+pub const SENSITIVE_KEYWORD: &str = "x-sensitive";
+
+/// Where the install's placeholder key is kept in the state store.
+const KEY_NAMESPACE: &str = "redact";
+const KEY_NAME: &str = "key";
+
+/// SHA-256 block size, for HMAC.
+const BLOCK_SIZE: usize = 64;
+
+/// Dotted paths (`token`, `auth.password`) of the arguments that must not be
+/// shown or stored: properties the tool's input schema marks with
+/// `x-sensitive`, plus the server's configured `sensitiveArgs`.
+pub fn sensitive_args(schema: &Value, configured: &[String]) -> BTreeSet<String> {
+    let mut paths: BTreeSet<String> = configured.iter().cloned().collect();
+    collect_sensitive(schema, "", &mut paths);
+    paths
+}
+
+fn collect_sensitive(schema: &Value, prefix: &str, paths: &mut BTreeSet<String>) {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return;
+    };
+    for (name, property) in properties {
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{prefix}.{name}")
+        };
+        if property.get(SENSITIVE_KEYWORD).and_then(Value::as_bool) == Some(true) {
+            paths.insert(path);
+        } else {
+            collect_sensitive(property, &path, paths);
+            // Paths run through arrays to the properties of their items
+            if let Some(items) = property.get("items") {
+                collect_sensitive(items, &path, paths);
+            }
+        }
+    }
+}
+
+/// This install's secret key for placeholders, kept in the state store and
+/// created on first use. If the store can't be used, a one-off key is
+/// returned: placeholders then still hide their values, but don't match
+/// those of other runs.
+pub fn install_key() -> [u8; 32] {
+    match default_store().and_then(|store| load_or_create_key(store.as_ref())) {
+        Ok(key) => key,
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to load the redaction key, using a one-off key");
+            rand::random()
+        }
+    }
+}
+
+fn load_or_create_key(store: &dyn StateStore) -> Result<[u8; 32], McplugError> {
+    if let Some(key) = store
+        .load::<String>(KEY_NAMESPACE, KEY_NAME)
+        .as_deref()
+        .and_then(decode_key)
+    {
+        return Ok(key);
+    }
+    let key: [u8; 32] = rand::random();
+    store.save(KEY_NAMESPACE, KEY_NAME, &hex(&key))?;
+    Ok(key)
+}
+
+fn decode_key(hex: &str) -> Option<[u8; 32]> {
+    let mut key = [0u8; 32];
+    if hex.len() != key.len() * 2 {
+        return None;
+    }
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(key)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// HMAC-SHA256 (RFC 2104) of `message` under `key`.
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.map(|b| b ^ byte);
+    let inner = Sha256::new()
+        .chain_update(pad(0x36))
+        .chain_update(message)
+        .finalize();
+    Sha256::new()
+        .chain_update(pad(0x5c))
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+/// Stand-in for a sensitive value: a short HMAC of its JSON under the
+/// install's key, so repeated uses of the same value can be matched up
+/// without the placeholder being open to guessing short values.
+pub fn placeholder(value: &Value, key: &[u8]) -> String {
+    let mac = hmac_sha256(key, value.to_string().as_bytes());
+    format!("<redacted hmac:{}>", hex(&mac[..6]))
+}
+
+/// A copy of `args` with every sensitive path replaced by its placeholder.
+/// A path runs through arrays, masking the field in every element.
+pub fn redact_args(args: &Value, sensitive: &BTreeSet<String>, key: &[u8]) -> Value {
+    let mut redacted = args.clone();
+    for path in sensitive {
+        redact_path(&mut redacted, path, key);
+    }
+    redacted
+}
+
+fn redact_path(value: &mut Value, path: &str, key: &[u8]) {
+    if let Value::Array(items) = value {
+        for item in items {
+            redact_path(item, path, key);
+        }
+        return;
+    }
+    let (head, rest) = match path.split_once('.') {
+        Some((head, rest)) => (head, Some(rest)),
+        None => (path, None),
+    };
+    let Some(child) = value.as_object_mut().and_then(|obj| obj.get_mut(head)) else {
+        return;
+    };
+    match rest {
+        Some(rest) => redact_path(child, rest, key),
+        None => *child = Value::String(placeholder(child, key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const KEY: &[u8] = b"test key";
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {"type": "string"},
+                "token": {"type": "string", "x-sensitive": true},
+                "auth": {
+                    "type": "object",
+                    "properties": {
+                        "user": {"type": "string"},
+                        "password": {"type": "string", "x-sensitive": true}
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn collects_schema_and_config_paths() {
+        let paths = sensitive_args(&schema(), &["apiKey".to_string()]);
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        assert_eq!(paths, vec!["apiKey", "auth.password", "token"]);
+    }
+
+    #[test]
+    fn redacts_only_sensitive_values() {
+        let sensitive = sensitive_args(&schema(), &[]);
+        let args = json!({
+            "query": "weather",
+            "token": "s3cret",
+            "auth": {"user": "me", "password": "hunter2"}
+        });
+        let redacted = redact_args(&args, &sensitive, KEY);
+        assert_eq!(redacted["query"], "weather");
+        assert_eq!(redacted["auth"]["user"], "me");
+        assert_eq!(redacted["token"], placeholder(&json!("s3cret"), KEY));
+        assert!(!redacted.to_string().contains("hunter2"));
+        // The original is untouched
+        assert_eq!(args["token"], "s3cret");
+    }
+
+    #[test]
+    fn placeholder_is_stable_per_value_and_key() {
+        let a = placeholder(&json!("s3cret"), KEY);
+        assert!(a.starts_with("<redacted hmac:"));
+        assert_eq!(a, placeholder(&json!("s3cret"), KEY));
+        assert_ne!(a, placeholder(&json!("other"), KEY));
+        assert_ne!(a, placeholder(&json!("s3cret"), b"another key"));
+    }
+
+    #[test]
+    fn hmac_matches_rfc_4231() {
+        // Test case 2
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            hex(&mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Test case 6: a key longer than the block is hashed first
+        let mac = hmac_sha256(
+            &[0xaa; 131],
+            b"Test Using Larger Than Block-Size Key - Hash Key First",
+        );
+        assert_eq!(
+            hex(&mac),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn key_is_created_once_per_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = crate::state::FileStore::new(dir.path());
+        let key = load_or_create_key(&store).unwrap();
+        assert_eq!(load_or_create_key(&store).unwrap(), key);
+        assert_eq!(decode_key(&hex(&key)), Some(key));
+        assert_eq!(decode_key("not hex"), None);
+    }
+
+    #[test]
+    fn redacts_fields_inside_arrays() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "accounts": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "user": {"type": "string"},
+                            "pin": {"type": "string", "x-sensitive": true}
+                        }
+                    }
+                }
+            }
+        });
+        let sensitive = sensitive_args(&schema, &[]);
+        assert!(sensitive.contains("accounts.pin"));
+        let args = json!({"accounts": [{"user": "a", "pin": "1234"}, {"user": "b", "pin": "9876"}]});
+        let redacted = redact_args(&args, &sensitive, KEY);
+        assert_eq!(redacted["accounts"][0]["user"], "a");
+        assert_eq!(redacted["accounts"][1]["pin"], placeholder(&json!("9876"), KEY));
+        assert!(!redacted.to_string().contains("1234"));
+    }
+
+    #[test]
+    fn missing_and_non_object_paths_are_ignored() {
+        let sensitive = sensitive_args(&json!({}), &["a.b".to_string(), "c".to_string()]);
+        assert_eq!(redact_args(&json!({"a": 1}), &sensitive, KEY), json!({"a": 1}));
+        assert_eq!(redact_args(&json!([1, 2]), &sensitive, KEY), json!([1, 2]));
+    }
+}
@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::McplugError;
+use crate::state::{default_state_dir, default_store};
+use crate::types::CallResult;
+
+/// A tool call result saved under a name with `mcplug call --save-as`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedResult {
+    pub name: String,
+    /// The `server.tool` that produced the result.
+    pub tool: String,
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+    pub result: CallResult,
+}
+
+/// State namespace holding saved results (`~/.mcplug/results` by default).
+const NAMESPACE: &str = "results";
+
+/// Directory holding saved results in the default file store: `~/.mcplug/results`.
+pub fn results_dir() -> PathBuf {
+    default_state_dir().join(NAMESPACE)
+}
+
+/// Result names become file names, so keep them to a safe character set.
+pub fn validate_name(name: &str) -> Result<(), McplugError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(McplugError::ProtocolError(format!(
+            "Invalid result name '{name}': use letters, digits, '-' and '_'"
+        )))
+    }
+}
+
+pub fn save_result(name: &str, tool: &str, result: &CallResult) -> Result<(), McplugError> {
+    validate_name(name)?;
+    let saved = SavedResult {
+        name: name.to_string(),
+        tool: tool.to_string(),
+        saved_at: chrono::Utc::now(),
+        result: result.clone(),
+    };
+    default_store()?.save(NAMESPACE, name, &saved)
+}
+
+pub fn load_result(name: &str) -> Result<SavedResult, McplugError> {
+    validate_name(name)?;
+    let value = default_store()?
+        .get(NAMESPACE, name)?
+        .ok_or_else(|| McplugError::ProtocolError(format!("No saved result named '{name}'")))?;
+    serde_json::from_value(value).map_err(|e| {
+        McplugError::ProtocolError(format!("Saved result '{name}' is corrupt: {e}"))
+    })
+}
+
+/// Names of all saved results, sorted.
+pub fn list_results() -> Vec<String> {
+    default_store()
+        .and_then(|store| store.keys(NAMESPACE))
+        .unwrap_or_default()
+}
+
+/// Resolve a `name[.field]` reference to a saved result.
+///
+/// Fields: `text` (default) is the text content, `json` parses the text as
+/// JSON, `content` is the raw content block array.
+pub fn resolve_reference(reference: &str) -> Result<serde_json::Value, McplugError> {
+    let (name, field) = reference.split_once('.').unwrap_or((reference, "text"));
+    let saved = load_result(name)?;
+    match field {
+        "text" => Ok(serde_json::Value::String(saved.result.text())),
+        "json" => saved.result.json(),
+        "content" => serde_json::to_value(&saved.result.content).map_err(|e| {
+            McplugError::ProtocolError(format!("Failed to serialize content: {e}"))
+        }),
+        other => Err(McplugError::ProtocolError(format!(
+            "Unknown result field '{other}' in '@result:{reference}': expected text, json, or content"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_name_rules() {
+        assert!(validate_name("lastcrawl").is_ok());
+        assert!(validate_name("run_2-b").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../etc").is_err());
+        assert!(validate_name("a.b").is_err());
+    }
+
+    #[test]
+    fn results_dir_structure() {
+        let dir = results_dir();
+        assert!(dir.ends_with(".mcplug/results"));
+    }
+
+    #[test]
+    fn resolve_unknown_field_errors() {
+        let err = resolve_reference("nonexistent-test-result-xyz.bogus").unwrap_err();
+        // Loading fails before the field is inspected
+        assert!(err.to_string().contains("No saved result"));
+    }
+}
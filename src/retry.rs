@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::McplugError;
+
+/// Failure classes a `RetryPolicy` can retry, named by the error codes in
+/// `--json` output (`McplugError::code`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryOn {
+    /// The call or the connection attempt timed out.
+    Timeout,
+    /// The server couldn't be spawned or reached.
+    ConnectionRefused,
+    /// The connection broke during the call.
+    TransportError,
+    /// Reading from or writing to the server failed.
+    IoError,
+}
+
+impl RetryOn {
+    /// The class `error` belongs to, if it is one worth retrying.
+    pub fn of(error: &McplugError) -> Option<Self> {
+        match error {
+            McplugError::Timeout { .. } => Some(RetryOn::Timeout),
+            McplugError::ConnectionFailed { .. } => Some(RetryOn::ConnectionRefused),
+            McplugError::TransportError(_) => Some(RetryOn::TransportError),
+            McplugError::IoError(_) => Some(RetryOn::IoError),
+            _ => None,
+        }
+    }
+}
+
+/// How `Runtime::call_tool` retries calls that fail.
+///
+/// Set per server with the `retry` config key, for every server with
+/// `RuntimeBuilder::retry_policy`, or for one call with
+/// `Runtime::call_tool_with_retry`. Without one, calls are attempted once.
+///
+/// ```json
+/// "retry": {"maxAttempts": 4, "backoffMs": 500, "retryOn": ["timeout"]}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first; 1 never retries.
+    #[serde(rename = "maxAttempts")]
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubling before each later one.
+    #[serde(rename = "backoffMs", with = "millis")]
+    pub backoff: Duration,
+    /// Which failures to retry. Failures of other kinds, and results the
+    /// server marks `isError`, are returned at once.
+    #[serde(rename = "retryOn")]
+    pub retry_on: Vec<RetryOn>,
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, 200ms apart and then 400ms, on connection failures.
+    ///
+    /// Timeouts are not retried by default: a call that timed out may still
+    /// have run on the server, and retrying a tool that isn't idempotent
+    /// would run it twice. Add `RetryOn::Timeout` for tools safe to repeat.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Duration::from_millis(200),
+            retry_on: vec![RetryOn::ConnectionRefused, RetryOn::TransportError],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that attempts calls once.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Whether to try again after `attempt` (1-based) failed with `error`.
+    pub fn should_retry(&self, attempt: u32, error: &McplugError) -> bool {
+        attempt < self.max_attempts
+            && RetryOn::of(error).is_some_and(|class| self.retry_on.contains(&class))
+    }
+
+    /// How long to wait after `attempt` (1-based) failed.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+    }
+}
+
+mod millis {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeout() -> McplugError {
+        McplugError::Timeout {
+            server: "s".into(),
+            tool: None,
+            duration: Duration::from_secs(1),
+        }
+    }
+
+    fn refused() -> McplugError {
+        McplugError::ConnectionFailed {
+            server: "s".into(),
+            source: "connection refused".into(),
+        }
+    }
+
+    #[test]
+    fn retries_listed_classes_until_attempts_run_out() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry(1, &refused()));
+        assert!(policy.should_retry(2, &refused()));
+        assert!(!policy.should_retry(3, &refused()));
+        assert!(!policy.should_retry(1, &McplugError::AuthRequired("s".into())));
+        assert!(!RetryPolicy::none().should_retry(1, &refused()));
+
+        let policy = RetryPolicy {
+            retry_on: vec![RetryOn::Timeout],
+            ..RetryPolicy::default()
+        };
+        assert!(policy.should_retry(1, &timeout()));
+        assert!(!policy.should_retry(1, &refused()));
+    }
+
+    #[test]
+    fn default_does_not_retry_timeouts() {
+        let policy = RetryPolicy::default();
+        assert_eq!(
+            policy.retry_on,
+            vec![RetryOn::ConnectionRefused, RetryOn::TransportError]
+        );
+        assert!(!policy.should_retry(1, &timeout()));
+    }
+
+    #[test]
+    fn backoff_doubles() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1), Duration::from_millis(200));
+        assert_eq!(policy.delay(2), Duration::from_millis(400));
+        assert_eq!(policy.delay(3), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn config_form_fills_defaults() {
+        let policy: RetryPolicy =
+            serde_json::from_str(r#"{"maxAttempts": 5, "retryOn": ["timeout", "io_error"]}"#).unwrap();
+        assert_eq!(policy.max_attempts, 5);
+        assert_eq!(policy.backoff, Duration::from_millis(200));
+        assert_eq!(policy.retry_on, vec![RetryOn::Timeout, RetryOn::IoError]);
+        assert_eq!(serde_json::to_value(&policy).unwrap()["backoffMs"], 200);
+        assert!(serde_json::from_str::<RetryPolicy>(r#"{"retryOn": ["not_found"]}"#).is_err());
+    }
+}
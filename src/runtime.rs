@@ -1,126 +1,869 @@
 use std::collections::HashMap;
 use std::env;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use tokio::sync::Mutex;
+use futures_util::future::join_all;
+use futures_util::StreamExt;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tokio::task::JoinHandle;
 
+use crate::args::suggest_tool;
 use crate::config::types::{Lifecycle, McplugConfig, ServerConfig};
+use crate::config::env::resolve_secrets;
 use crate::config::load_config;
+use crate::config::watch::ConfigChanges;
+use crate::config::pins::{check_tool_pin, PinPolicy};
 use crate::error::McplugError;
-use crate::transport::McpTransport;
-use crate::transports::{HttpSseTransport, StdioTransport};
-use crate::types::{CallResult, ServerInfo, ToolDefinition};
+use crate::interceptor::{CallInterceptor, ToolCall};
+use crate::oauth::{auth_headers, oauth_client, spawn_token_refresher};
+use crate::retry::RetryPolicy;
+use crate::stats::RuntimeStats;
+use crate::transport::{McpTransport, TransportFactory};
+use crate::transports::http_sse::allow_http;
+use crate::transports::session::session_path;
+use crate::transports::stdio::install_timeout;
+use crate::transports::{HttpSseTransport, LogLevelTransport, StdioTransport};
+use crate::types::{CallResult, Notification, ServerInfo, ToolDefinition};
+
+/// How long a connection's tool list is reused before it is fetched again.
+const DEFAULT_TOOL_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Config `type` of the servers created by `Runtime::with_transports`.
+const PREBUILT_TRANSPORT: &str = "prebuilt";
+
+/// An open connection, what the server reported when it initialized, and
+/// the tool list it last returned.
+struct Connection {
+    transport: Box<dyn McpTransport>,
+    info: ServerInfo,
+    tools: Option<(Instant, Vec<ToolDefinition>)>,
+}
+
+/// A server's connection, empty until first use. Each has its own lock, so
+/// a slow call to one server doesn't hold up calls to another.
+type Slot = Arc<Mutex<Option<Connection>>>;
 
 /// Manages connections to MCP servers based on the merged configuration.
 pub struct Runtime {
     config: McplugConfig,
-    connections: Mutex<HashMap<String, Box<dyn McpTransport>>>,
+    /// The map lock is only held to look up or remove a slot, never across
+    /// an await.
+    connections: std::sync::Mutex<HashMap<String, Slot>>,
+    /// Background OAuth token refreshes for connected keep-alive servers.
+    refreshers: std::sync::Mutex<HashMap<String, JoinHandle<()>>>,
+    /// Highest-precedence config file, used again by `reload_config`.
+    config_path: Option<PathBuf>,
+    /// Permit cleartext `http://` for every server, like `--allow-http`.
+    allow_http: bool,
+    /// Call and list timeout for servers without `timeoutMs`.
+    default_timeout: Option<Duration>,
+    /// How long `list_tools` results are reused; zero disables caching.
+    tool_cache_ttl: Duration,
+    /// Hooks run around every `call_tool`, in order.
+    interceptors: Vec<Arc<dyn CallInterceptor>>,
+    /// Transports for custom config `type`s, by type name.
+    transports: HashMap<String, Arc<dyn TransportFactory>>,
+    /// Retries for servers without a `retry` policy of their own.
+    retry_policy: Option<RetryPolicy>,
+    /// Counts and latencies of the calls made so far.
+    stats: std::sync::Mutex<RuntimeStats>,
+}
+
+/// How `Runtime::health_check` exercised a server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthProbe {
+    /// A new connection was opened and initialized.
+    Initialize,
+    /// The open connection was sent a `ping`.
+    Ping,
+}
+
+impl HealthProbe {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HealthProbe::Initialize => "initialize",
+            HealthProbe::Ping => "ping",
+        }
+    }
+}
+
+/// The outcome of `Runtime::health_check`.
+#[derive(Debug)]
+pub struct HealthReport {
+    pub server: String,
+    pub probe: HealthProbe,
+    /// How long the check took, including any connecting.
+    pub elapsed: Duration,
+    /// What the server reported when it initialized, or why the check
+    /// failed.
+    pub result: Result<ServerInfo, McplugError>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// The report as JSON: `server`, `healthy`, `probe`, `latencyMs`, and
+    /// the server's `version` or the error's `error` and `code`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::json!({
+            "server": self.server,
+            "healthy": self.is_healthy(),
+            "probe": self.probe.as_str(),
+            "latencyMs": self.elapsed.as_millis() as u64,
+        });
+        match &self.result {
+            Ok(info) => json["version"] = serde_json::json!(info.version),
+            Err(e) => {
+                json["error"] = serde_json::json!(e.to_string());
+                json["code"] = serde_json::json!(e.code());
+            }
+        }
+        json
+    }
+}
+
+/// Builds a `Runtime` with options that `from_config` and `with_config`
+/// leave at their defaults.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), mcplug::McplugError> {
+/// let runtime = mcplug::Runtime::builder()
+///     .config_path("./mcplug.json")
+///     .allow_http(true)
+///     .default_timeout(std::time::Duration::from_secs(10))
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct RuntimeBuilder {
+    config: Option<McplugConfig>,
+    config_path: Option<PathBuf>,
+    allow_http: bool,
+    default_timeout: Option<Duration>,
+    tool_cache_ttl: Option<Duration>,
+    interceptors: Vec<Arc<dyn CallInterceptor>>,
+    transports: HashMap<String, Arc<dyn TransportFactory>>,
+    retry_policy: Option<RetryPolicy>,
+}
+
+impl RuntimeBuilder {
+    /// Use this config instead of loading one from disk.
+    pub fn config(mut self, config: McplugConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Load this config file ahead of the discovered ones, like `--config`.
+    pub fn config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = Some(path.into());
+        self
+    }
+
+    /// Permit cleartext `http://` URLs for every server, not only those
+    /// with `allowHttp`.
+    pub fn allow_http(mut self, allow: bool) -> Self {
+        self.allow_http = allow;
+        self
+    }
+
+    /// Bound calls, connects, and tool listings of servers that don't set
+    /// `timeoutMs`. Unbounded by default.
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// Reuse each connection's tool list for `ttl` (60 seconds by default)
+    /// before listing again. `Duration::ZERO` lists on every use.
+    pub fn tool_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.tool_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Run `interceptor` around every tool call, after any registered
+    /// before it.
+    pub fn interceptor(mut self, interceptor: impl CallInterceptor + 'static) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Connect servers whose config `type` is `kind` through `factory`.
+    /// Registering a built-in type (`stdio`, `http`, `sse`) replaces it.
+    pub fn transport(
+        mut self,
+        kind: impl Into<String>,
+        factory: impl TransportFactory + 'static,
+    ) -> Self {
+        self.transports.insert(kind.into(), Arc::new(factory));
+        self
+    }
+
+    /// Retry failed calls to servers without a `retry` policy of their own
+    /// this way. Calls are attempted once by default.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Create the Runtime, loading and merging the config unless one was
+    /// given.
+    pub async fn build(self) -> Result<Runtime, McplugError> {
+        let config = match self.config {
+            Some(config) => config,
+            None => load_config_from(self.config_path.as_deref())?,
+        };
+        Ok(Runtime {
+            config,
+            connections: Default::default(),
+            refreshers: Default::default(),
+            config_path: self.config_path,
+            allow_http: self.allow_http,
+            default_timeout: self.default_timeout,
+            tool_cache_ttl: self.tool_cache_ttl.unwrap_or(DEFAULT_TOOL_CACHE_TTL),
+            interceptors: self.interceptors,
+            transports: self.transports,
+            retry_policy: self.retry_policy,
+            stats: Default::default(),
+        })
+    }
 }
 
 impl Runtime {
+    /// Start building a Runtime with non-default options.
+    pub fn builder() -> RuntimeBuilder {
+        RuntimeBuilder::default()
+    }
+
     /// Create a Runtime by loading and merging all config sources.
     pub async fn from_config() -> Result<Self, McplugError> {
-        let config = load_config(None)?;
-        Ok(Self {
-            config,
-            connections: Mutex::new(HashMap::new()),
-        })
+        Self::builder().build().await
     }
 
     /// Create a Runtime from an existing config.
     pub fn with_config(config: McplugConfig) -> Self {
         Self {
             config,
-            connections: Mutex::new(HashMap::new()),
+            connections: Default::default(),
+            refreshers: Default::default(),
+            config_path: None,
+            allow_http: false,
+            default_timeout: None,
+            tool_cache_ttl: DEFAULT_TOOL_CACHE_TTL,
+            interceptors: Vec::new(),
+            transports: HashMap::new(),
+            retry_policy: None,
+            stats: Default::default(),
         }
     }
 
+    /// Create a Runtime whose servers are the given transports, keyed by
+    /// server name, instead of configured ones. Nothing is spawned or
+    /// dialed, which suits tests of code built on mcplug.
+    ///
+    /// Each transport is initialized on first use like a configured one.
+    /// Once closed (by `close`, `close_server`, or `reconnect`) it can't be
+    /// reopened, and connecting to its server again fails.
+    pub fn with_transports(
+        transports: impl IntoIterator<Item = (String, Box<dyn McpTransport>)>,
+    ) -> Self {
+        let transports: HashMap<String, Box<dyn McpTransport>> = transports.into_iter().collect();
+        let mut config = McplugConfig::default();
+        for server in transports.keys() {
+            let cfg = ServerConfig {
+                transport: Some(PREBUILT_TRANSPORT.to_string()),
+                ..Default::default()
+            };
+            config.mcp_servers.insert(server.clone(), cfg);
+        }
+        let mut runtime = Self::with_config(config);
+        runtime.register_transport(
+            PREBUILT_TRANSPORT,
+            Arc::new(Prebuilt(std::sync::Mutex::new(transports))),
+        );
+        runtime
+    }
+
+    /// Run `interceptor` around every tool call, after those already
+    /// registered.
+    pub fn add_interceptor(&mut self, interceptor: Arc<dyn CallInterceptor>) {
+        self.interceptors.push(interceptor);
+    }
+
+    /// Connect servers whose config `type` is `kind` through `factory`,
+    /// replacing any factory already registered for it. Existing
+    /// connections are kept.
+    pub fn register_transport(
+        &mut self,
+        kind: impl Into<String>,
+        factory: Arc<dyn TransportFactory>,
+    ) {
+        self.transports.insert(kind.into(), factory);
+    }
+
     /// Call a tool on a given server, lazily connecting if needed.
+    ///
+    /// If the server config pins the tool's schema hash, the (cached) tool
+    /// list is checked first and the call is refused when the schema has
+    /// changed. Failed calls are retried per the server's `retry` policy,
+    /// else the builder's. Registered interceptors run around the call,
+    /// including its retries.
     pub async fn call_tool(
         &self,
         server: &str,
         tool: &str,
         args: serde_json::Value,
     ) -> Result<CallResult, McplugError> {
-        let mut conns = self.connections.lock().await;
-        if !conns.contains_key(server) {
-            let mut transport = self.create_transport(server)?;
-            transport.initialize().await?;
-            conns.insert(server.to_string(), transport);
+        self.call(server, tool, args, None).await
+    }
+
+    /// Call a tool like `call_tool`, retrying per `policy` instead of the
+    /// configured policy. `RetryPolicy::none()` makes a single attempt.
+    pub async fn call_tool_with_retry(
+        &self,
+        server: &str,
+        tool: &str,
+        args: serde_json::Value,
+        policy: &RetryPolicy,
+    ) -> Result<CallResult, McplugError> {
+        self.call(server, tool, args, Some(policy)).await
+    }
+
+    /// Run a batch of calls concurrently, at most `concurrency` at a time,
+    /// returning each call's outcome in the order given. Each call goes
+    /// through `call_tool`, so one failing doesn't stop the others.
+    ///
+    /// A server has one connection, so calls to the same server still run
+    /// one after another; the concurrency is across servers.
+    pub async fn call_many(
+        &self,
+        calls: impl IntoIterator<Item = ToolCall>,
+        concurrency: usize,
+    ) -> Vec<Result<CallResult, McplugError>> {
+        futures_util::stream::iter(calls)
+            .map(|call| async move { self.call_tool(&call.server, &call.tool, call.args).await })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    async fn call(
+        &self,
+        server: &str,
+        tool: &str,
+        args: serde_json::Value,
+        retry: Option<&RetryPolicy>,
+    ) -> Result<CallResult, McplugError> {
+        let started = Instant::now();
+        let outcome = self.intercept(server, tool, args, retry).await;
+        self.stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .servers
+            .entry(server.to_string())
+            .or_default()
+            .record(started.elapsed(), &outcome);
+        outcome
+    }
+
+    /// Per-server call counts, error counts, and latency histograms for the
+    /// `call_tool` calls made so far. Each call counts once, under the
+    /// server it was made to, however many times it was retried.
+    pub fn stats(&self) -> RuntimeStats {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Run a call through the interceptors.
+    async fn intercept(
+        &self,
+        server: &str,
+        tool: &str,
+        args: serde_json::Value,
+        retry: Option<&RetryPolicy>,
+    ) -> Result<CallResult, McplugError> {
+        if self.interceptors.is_empty() {
+            return self.dispatch_retrying(server, tool, args, retry).await;
+        }
+        let mut call = ToolCall {
+            server: server.to_string(),
+            tool: tool.to_string(),
+            args,
+        };
+        let started = Instant::now();
+        let mut outcome = Ok(());
+        for interceptor in &self.interceptors {
+            outcome = interceptor.before(&mut call).await;
+            if outcome.is_err() {
+                break;
+            }
+        }
+        let outcome = match outcome {
+            Ok(()) => {
+                self.dispatch_retrying(&call.server, &call.tool, call.args.clone(), retry)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        let elapsed = started.elapsed();
+        for interceptor in &self.interceptors {
+            match &outcome {
+                Ok(result) => interceptor.after(&call, result, elapsed).await,
+                Err(e) => interceptor.on_error(&call, e, elapsed).await,
+            }
+        }
+        outcome
+    }
+
+    /// Dispatch a call, retrying failures per `retry`, else the server's
+    /// policy, else the Runtime's. Connections that failed are closed
+    /// before retrying, so the retry reconnects; timed-out ones are kept.
+    async fn dispatch_retrying(
+        &self,
+        server: &str,
+        tool: &str,
+        args: serde_json::Value,
+        retry: Option<&RetryPolicy>,
+    ) -> Result<CallResult, McplugError> {
+        let Some(policy) = self.retry_policy_for(server, retry) else {
+            return self.dispatch(server, tool, args).await;
+        };
+        let mut attempt = 1;
+        loop {
+            match self.dispatch(server, tool, args.clone()).await {
+                Err(e) if policy.should_retry(attempt, &e) => {
+                    let delay = policy.delay(attempt);
+                    tracing::debug!(server = %server, tool = %tool, attempt, error = %e, ?delay, "retrying call");
+                    if !matches!(e, McplugError::Timeout { .. }) {
+                        let _ = self.close_server(server).await;
+                    }
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                outcome => return outcome,
+            }
         }
-        conns
+    }
+
+    fn retry_policy_for<'a>(
+        &'a self,
+        server: &str,
+        retry: Option<&'a RetryPolicy>,
+    ) -> Option<&'a RetryPolicy> {
+        retry
+            .or_else(|| self.config.mcp_servers.get(server)?.retry.as_ref())
+            .or(self.retry_policy.as_ref())
+    }
+
+    /// Send a tool call to its server, checking any schema pin first.
+    async fn dispatch(
+        &self,
+        server: &str,
+        tool: &str,
+        args: serde_json::Value,
+    ) -> Result<CallResult, McplugError> {
+        let mut conn = self.lock_connection(server).await?;
+        let conn = self.ensure_connected(server, &mut conn).await?;
+
+        // Pinned tools must still match their expected input schema
+        if let Some(cfg) = self
+            .config
+            .mcp_servers
             .get(server)
-            .unwrap()
-            .call_tool(tool, args)
-            .await
+            .filter(|cfg| cfg.tool_pins.contains_key(tool))
+        {
+            let tools = self.cached_tools(server, conn).await?;
+            let def = tools
+                .iter()
+                .find(|t| t.name == tool)
+                .ok_or_else(|| tool_not_found(server, tool, &tools))?;
+            check_tool_pin(server, cfg, def, PinPolicy::from_env())?;
+        }
+
+        let call = conn.transport.call_tool(tool, args);
+        bounded(server, Some(tool), self.call_timeout(server), call).await
+    }
+
+    /// Connect to and initialize a server ahead of its first call, so the
+    /// call doesn't pay for the handshake. Does nothing if connected.
+    pub async fn connect(&self, server: &str) -> Result<(), McplugError> {
+        let mut conn = self.lock_connection(server).await?;
+        self.ensure_connected(server, &mut conn).await?;
+        Ok(())
+    }
+
+    /// Connect to every enabled server concurrently; see `connect_servers`.
+    pub async fn connect_all(&self) -> Vec<(String, Result<(), McplugError>)> {
+        self.connect_servers(&self.server_names()).await
+    }
+
+    /// Connect to the given servers concurrently, returning each one's
+    /// outcome in the order given. One server failing doesn't stop the
+    /// others.
+    pub async fn connect_servers(
+        &self,
+        servers: &[String],
+    ) -> Vec<(String, Result<(), McplugError>)> {
+        join_all(servers.iter().map(|server| async move {
+            (server.clone(), self.connect(server).await)
+        }))
+        .await
+    }
+
+    /// Call a tool and deserialize its result as `T`.
+    ///
+    /// Uses the response's `structuredContent`, or its text content parsed
+    /// as JSON when there is none. Fails with `ResultMismatch` when the
+    /// result doesn't have the shape of `T`, and with a protocol error when
+    /// the tool reports an error.
+    pub async fn call_tool_as<T: serde::de::DeserializeOwned>(
+        &self,
+        server: &str,
+        tool: &str,
+        args: serde_json::Value,
+    ) -> Result<T, McplugError> {
+        let result = self.call_tool(server, tool, args).await?;
+        typed_result(server, tool, &result)
     }
 
     /// List tools available on a given server, lazily connecting if needed.
+    ///
+    /// The list is cached per connection for the builder's `tool_cache_ttl`;
+    /// `refresh_tools` fetches it again right away.
     pub async fn list_tools(&self, server: &str) -> Result<Vec<ToolDefinition>, McplugError> {
-        let mut conns = self.connections.lock().await;
-        if !conns.contains_key(server) {
-            let mut transport = self.create_transport(server)?;
-            transport.initialize().await?;
-            conns.insert(server.to_string(), transport);
+        let mut conn = self.lock_connection(server).await?;
+        let conn = self.ensure_connected(server, &mut conn).await?;
+        self.cached_tools(server, conn).await
+    }
+
+    /// List a server's tools from the server, replacing the cached list,
+    /// e.g. after it announced that its tools changed.
+    pub async fn refresh_tools(&self, server: &str) -> Result<Vec<ToolDefinition>, McplugError> {
+        let mut conn = self.lock_connection(server).await?;
+        let conn = self.ensure_connected(server, &mut conn).await?;
+        conn.tools = None;
+        self.cached_tools(server, conn).await
+    }
+
+    /// List the tools of several servers concurrently, returning each
+    /// one's tools or error in the order given.
+    pub async fn list_tools_by_server(
+        &self,
+        servers: &[String],
+    ) -> Vec<(String, Result<Vec<ToolDefinition>, McplugError>)> {
+        join_all(servers.iter().map(|server| async move {
+            (server.clone(), self.list_tools(server).await)
+        }))
+        .await
+    }
+
+    /// Every tool of every enabled server, gathered concurrently, as
+    /// `(server, tool)` pairs ordered by server name. Servers that fail are
+    /// skipped with a warning; use `list_tools_by_server` to handle them.
+    pub async fn list_all_tools(&self) -> Vec<(String, ToolDefinition)> {
+        let mut servers = self.server_names();
+        servers.sort();
+        let mut all = Vec::new();
+        for (server, result) in self.list_tools_by_server(&servers).await {
+            match result {
+                Ok(tools) => all.extend(tools.into_iter().map(|tool| (server.clone(), tool))),
+                Err(e) => tracing::warn!(server = %server, error = %e, "skipped server"),
+            }
         }
-        conns.get(server).unwrap().list_tools().await
+        all
     }
 
-    /// Return server info by initializing (or reusing) a connection.
+    /// What the server reported when it initialized, connecting first if
+    /// needed. An open connection's info is reused without a round trip.
     pub async fn server_info(&self, server: &str) -> Result<ServerInfo, McplugError> {
-        let mut conns = self.connections.lock().await;
-        if !conns.contains_key(server) {
-            let mut transport = self.create_transport(server)?;
-            let info = transport.initialize().await?;
-            conns.insert(server.to_string(), transport);
-            return Ok(info);
+        let mut conn = self.lock_connection(server).await?;
+        Ok(self.ensure_connected(server, &mut conn).await?.info.clone())
+    }
+
+    /// The notifications `server` sends from now on, such as log messages
+    /// and `notifications/tools/list_changed`, connecting first if needed.
+    ///
+    /// Notifications are delivered as the connection reads them from the
+    /// server, which for a stdio server is while a request to it is in
+    /// flight. HTTP servers don't report notifications, so their stream is
+    /// empty. The stream ends when the connection closes; a subscriber that
+    /// falls far behind skips the notifications it missed.
+    pub async fn subscribe(
+        &self,
+        server: &str,
+    ) -> Result<impl futures_util::Stream<Item = Notification> + Send + 'static, McplugError> {
+        let mut conn = self.lock_connection(server).await?;
+        let receiver = self.ensure_connected(server, &mut conn).await?.transport.subscribe();
+        let server = server.to_string();
+        Ok(futures_util::stream::unfold(receiver, move |mut receiver| {
+            let server = server.clone();
+            async move {
+                loop {
+                    match receiver.as_mut()?.recv().await {
+                        Ok(notification) => return Some((notification, receiver)),
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                            tracing::warn!(server = %server, missed, "subscriber missed notifications");
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Check that a server responds, timing the check.
+    ///
+    /// A connected server is sent a `ping` on its open connection; a
+    /// connection that fails it is closed, so the next use reconnects.
+    /// Otherwise the server is connected and initialized, and the new
+    /// connection is kept for later calls. Failures are reported in the
+    /// `HealthReport` rather than returned.
+    pub async fn health_check(&self, server: &str) -> HealthReport {
+        let started = Instant::now();
+        let (probe, result) = match self.lock_connection(server).await {
+            Ok(mut conn) => match conn.as_ref() {
+                Some(open) => {
+                    let ping = open.transport.request("ping", None);
+                    let result = match bounded(server, None, self.list_timeout(server), ping).await {
+                        // A server that answers, even to refuse the ping, is alive
+                        Ok(_) | Err(McplugError::ProtocolError(_)) => Ok(open.info.clone()),
+                        Err(e) => Err(e),
+                    };
+                    if result.is_err() {
+                        self.stop_refreshers(|name| name == server);
+                        if let Some(mut broken) = conn.take() {
+                            let _ = broken.transport.close().await;
+                        }
+                    }
+                    (HealthProbe::Ping, result)
+                }
+                None => {
+                    let result = self.ensure_connected(server, &mut conn).await;
+                    let result = result.map(|open| open.info.clone());
+                    (HealthProbe::Initialize, result)
+                }
+            },
+            Err(e) => (HealthProbe::Initialize, Err(e)),
+        };
+        HealthReport {
+            server: server.to_string(),
+            probe,
+            elapsed: started.elapsed(),
+            result,
         }
-        // Already connected — re-list isn't ideal, but we don't cache ServerInfo.
-        // For now, return a placeholder. A real impl might cache this.
-        Ok(ServerInfo {
-            name: server.to_string(),
-            version: "unknown".to_string(),
-            capabilities: serde_json::json!({}),
-        })
     }
 
-    /// Close all active connections.
+    /// Close all active connections, waiting for calls in flight on each.
     pub async fn close(&self) -> Result<(), McplugError> {
-        let mut conns = self.connections.lock().await;
-        for (_name, transport) in conns.iter_mut() {
-            // close() requires &mut self on the trait, but we have Box<dyn McpTransport>.
-            // We need to use the DerefMut on Box.
-            transport.close().await?;
+        let slots: Vec<Slot> = self.lock_connections().drain().map(|(_, slot)| slot).collect();
+        self.stop_refreshers(|_| true);
+        for slot in slots {
+            if let Some(mut conn) = slot.lock().await.take() {
+                conn.transport.close().await?;
+            }
         }
-        conns.clear();
         Ok(())
     }
 
+    /// Close one server's connection, leaving the others open. It
+    /// reconnects on next use. Waits for calls in flight on the server;
+    /// closing a server that isn't connected does nothing.
+    pub async fn close_server(&self, server: &str) -> Result<(), McplugError> {
+        let slot = self.lock_connections().get(server).cloned();
+        self.stop_refreshers(|name| name == server);
+        let Some(slot) = slot else {
+            return Ok(());
+        };
+        // The slot stays in the map, so callers already waiting on it
+        // reconnect into it rather than into a detached connection
+        let conn = slot.lock().await.take();
+        match conn {
+            Some(mut conn) => conn.transport.close().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Replace a server's connection with a fresh one, e.g. after it got
+    /// into a bad state. The old connection is closed even if it fails to
+    /// close cleanly.
+    pub async fn reconnect(&self, server: &str) -> Result<ServerInfo, McplugError> {
+        let mut conn = self.lock_connection(server).await?;
+        if let Some(mut old) = conn.take() {
+            self.stop_refreshers(|name| name == server);
+            if let Err(e) = old.transport.close().await {
+                tracing::debug!(server = %server, error = %e, "closing old connection failed");
+            }
+        }
+        Ok(self.ensure_connected(server, &mut conn).await?.info.clone())
+    }
+
+    /// Reload the merged configuration from disk and apply it, see
+    /// `apply_config`.
+    pub async fn reload_config(&mut self) -> Result<ConfigChanges, McplugError> {
+        let config = load_config_from(self.config_path.as_deref())?;
+        Ok(self.apply_config(config).await)
+    }
+
+    /// Replace the configuration without restarting: connections to servers
+    /// that were removed, disabled, or edited are closed (edited ones
+    /// reconnect with their new settings on next use), and the rest stay
+    /// open.
+    pub async fn apply_config(&mut self, config: McplugConfig) -> ConfigChanges {
+        let changes = ConfigChanges::between(&self.config, &config);
+        let stale: Vec<Slot> = {
+            let mut conns = self.lock_connections();
+            let names: Vec<String> = conns
+                .keys()
+                .filter(|name| changes.affects(name))
+                .cloned()
+                .collect();
+            names.iter().filter_map(|name| conns.remove(name)).collect()
+        };
+        for slot in stale {
+            if let Some(mut conn) = slot.lock().await.take() {
+                let _ = conn.transport.close().await;
+            }
+        }
+        self.stop_refreshers(|name| changes.affects(name));
+        self.config = config;
+        changes
+    }
+
     /// Return a reference to the loaded configuration.
     pub fn config(&self) -> &McplugConfig {
         &self.config
     }
 
-    /// Return the list of configured server names.
+    /// Return the names of the configured servers that aren't disabled.
     pub fn server_names(&self) -> Vec<String> {
-        self.config.mcp_servers.keys().cloned().collect()
+        self.config.enabled_servers()
     }
 
-    /// Resolve the effective lifecycle for a server, considering env overrides.
-    #[allow(dead_code)]
-    fn effective_lifecycle(&self, server: &str, cfg: &ServerConfig) -> Option<Lifecycle> {
-        // MCPLUG_KEEPALIVE=server_name forces keep-alive
-        if let Ok(val) = env::var("MCPLUG_KEEPALIVE") {
-            if val == server || val == "*" {
-                return Some(Lifecycle::KeepAlive);
-            }
+    fn lock_connections(&self) -> std::sync::MutexGuard<'_, HashMap<String, Slot>> {
+        self.connections.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Lock a configured server's connection slot, waiting only for other
+    /// users of the same server.
+    async fn lock_connection(
+        &self,
+        server: &str,
+    ) -> Result<OwnedMutexGuard<Option<Connection>>, McplugError> {
+        if !self.config.mcp_servers.contains_key(server) {
+            return Err(McplugError::ServerNotFound(server.to_string()));
         }
-        // MCPLUG_DISABLE_KEEPALIVE=server_name forces ephemeral
-        if let Ok(val) = env::var("MCPLUG_DISABLE_KEEPALIVE") {
-            if val == server || val == "*" {
-                return Some(Lifecycle::Ephemeral);
+        let slot = self
+            .lock_connections()
+            .entry(server.to_string())
+            .or_default()
+            .clone();
+        Ok(slot.lock_owned().await)
+    }
+
+    /// Connect and initialize `server` into its locked slot unless it is
+    /// already connected, returning the connection.
+    async fn ensure_connected<'c>(
+        &self,
+        server: &str,
+        conn: &'c mut Option<Connection>,
+    ) -> Result<&'c mut Connection, McplugError> {
+        if conn.is_none() {
+            *conn = Some(self.open(server).await?);
+            self.start_refresher(server);
+        }
+        Ok(conn.as_mut().unwrap())
+    }
+
+    /// Create, start, and initialize a new connection to `server`.
+    async fn open(&self, server: &str) -> Result<Connection, McplugError> {
+        let mut transport = self.create_transport(server)?;
+        transport.wait_ready().await?;
+        let info = bounded(server, None, self.list_timeout(server), transport.initialize()).await?;
+        Ok(Connection {
+            transport,
+            info,
+            tools: None,
+        })
+    }
+
+    /// The connection's tool list, reused while younger than the cache TTL.
+    async fn cached_tools(
+        &self,
+        server: &str,
+        conn: &mut Connection,
+    ) -> Result<Vec<ToolDefinition>, McplugError> {
+        if let Some((fetched, tools)) = &conn.tools {
+            if fetched.elapsed() < self.tool_cache_ttl {
+                return Ok(tools.clone());
             }
         }
-        cfg.lifecycle.clone()
+        let tools =
+            bounded(server, None, self.list_timeout(server), conn.transport.list_tools()).await?;
+        conn.tools = Some((Instant::now(), tools.clone()));
+        Ok(tools)
+    }
+
+    /// The server's `timeoutMs`, else the builder's default timeout.
+    fn call_timeout(&self, server: &str) -> Option<Duration> {
+        self.config
+            .mcp_servers
+            .get(server)
+            .and_then(ServerConfig::call_timeout)
+            .or(self.default_timeout)
+    }
+
+    /// The server's `listTimeoutMs` (or `timeoutMs`), else the builder's
+    /// default timeout.
+    fn list_timeout(&self, server: &str) -> Option<Duration> {
+        self.config
+            .mcp_servers
+            .get(server)
+            .and_then(ServerConfig::list_timeout)
+            .or(self.default_timeout)
+    }
+
+    /// Keep the OAuth token of a newly connected keep-alive HTTP server
+    /// fresh in the background, so the open connection never sends an
+    /// expired token.
+    fn start_refresher(&self, server: &str) {
+        let Some(cfg) = self.config.mcp_servers.get(server) else {
+            return;
+        };
+        let (Some(base_url), Some(client)) = (cfg.base_url.as_ref(), oauth_client(cfg)) else {
+            return;
+        };
+        if !matches!(self.effective_lifecycle(server, cfg), Some(Lifecycle::KeepAlive)) {
+            return;
+        }
+        let handle = spawn_token_refresher(client.token_id(server), base_url.clone(), client);
+        if let Some(old) = self.lock_refreshers().insert(server.to_string(), handle) {
+            old.abort();
+        }
+    }
+
+    /// Stop the background refreshes of servers matching `filter`.
+    fn stop_refreshers(&self, filter: impl Fn(&str) -> bool) {
+        self.lock_refreshers().retain(|name, handle| {
+            let stop = filter(name);
+            if stop {
+                handle.abort();
+            }
+            !stop
+        });
+    }
+
+    fn lock_refreshers(&self) -> std::sync::MutexGuard<'_, HashMap<String, JoinHandle<()>>> {
+        self.refreshers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Resolve the effective lifecycle for a server, considering env overrides.
+    fn effective_lifecycle(&self, server: &str, cfg: &ServerConfig) -> Option<Lifecycle> {
+        resolve_lifecycle(server, cfg)
     }
 
     /// Create a transport for the given server name based on its config.
@@ -134,14 +877,45 @@ impl Runtime {
             .get(server)
             .ok_or_else(|| McplugError::ServerNotFound(server.to_string()))?;
 
-        if let Some(ref base_url) = cfg.base_url {
+        if cfg.disabled {
+            return Err(McplugError::ConfigError {
+                path: std::path::PathBuf::from("<runtime>"),
+                detail: format!("Server '{}' is disabled", server),
+            });
+        }
+        // Secrets are only looked up for the server being connected
+        let cfg = &resolve_secrets(cfg)?;
+
+        let kind = cfg.transport.as_deref();
+        let transport: Box<dyn McpTransport> = if let Some(factory) =
+            kind.and_then(|kind| self.transports.get(kind))
+        {
+            factory.create(server, cfg)?
+        } else if let Some(kind) = cfg.custom_transport() {
+            return Err(McplugError::ConfigError {
+                path: std::path::PathBuf::from("<runtime>"),
+                detail: format!(
+                    "Server '{}' has type '{}', but no transport of that type is registered",
+                    server, kind
+                ),
+            });
+        } else if let Some(base_url) = cfg.base_url.as_ref().filter(|_| kind != Some("stdio")) {
+            let oauth = oauth_client(cfg);
+            // Each identity keeps its own session
+            let token_id = oauth
+                .as_ref()
+                .map_or_else(|| server.to_string(), |client| client.token_id(server));
             let transport = HttpSseTransport::new(
                 base_url,
-                &cfg.headers,
+                &auth_headers(cfg),
                 server,
-                false,
-            )?;
-            Ok(Box::new(transport))
+                self.allow_http || allow_http(cfg.allow_http.unwrap_or(false)),
+            )?
+            .with_session_file(session_path(&token_id));
+            match oauth {
+                Some(client) => Box::new(transport.with_oauth(client)),
+                None => Box::new(transport),
+            }
         } else if let Some(ref command) = cfg.command {
             let transport = StdioTransport::new(
                 command,
@@ -149,18 +923,125 @@ impl Runtime {
                 &cfg.env,
                 None,
                 server,
-            )?;
-            Ok(Box::new(transport))
+            )?
+            .with_install_timeout(install_timeout(cfg.install_timeout));
+            Box::new(transport)
         } else {
-            Err(McplugError::ConfigError {
+            return Err(McplugError::ConfigError {
                 path: std::path::PathBuf::from("<runtime>"),
                 detail: format!(
                     "Server '{}' has neither 'baseUrl' nor 'command' configured",
                     server
                 ),
+            });
+        };
+        Ok(match cfg.log_level {
+            Some(level) => Box::new(LogLevelTransport::new(transport, server, level)),
+            None => transport,
+        })
+    }
+}
+
+/// Hands out the transports given to `Runtime::with_transports`, each once.
+struct Prebuilt(std::sync::Mutex<HashMap<String, Box<dyn McpTransport>>>);
+
+impl TransportFactory for Prebuilt {
+    fn create(
+        &self,
+        server: &str,
+        _config: &ServerConfig,
+    ) -> Result<Box<dyn McpTransport>, McplugError> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(server)
+            .ok_or_else(|| McplugError::ConnectionFailed {
+                server: server.to_string(),
+                source: "the transport given to Runtime::with_transports was closed".into(),
             })
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        self.stop_refreshers(|_| true);
+    }
+}
+
+/// Load the merged config, with `path` (if any) taking precedence.
+fn load_config_from(path: Option<&Path>) -> Result<McplugConfig, McplugError> {
+    let path = path.map(|p| p.to_string_lossy());
+    load_config(path.as_deref())
+}
+
+/// Deserialize a successful call result as `T`.
+fn typed_result<T: serde::de::DeserializeOwned>(
+    server: &str,
+    tool: &str,
+    result: &CallResult,
+) -> Result<T, McplugError> {
+    if result.is_error {
+        return Err(McplugError::ProtocolError(format!(
+            "{}.{} failed: {}",
+            server,
+            tool,
+            result.text()
+        )));
+    }
+    result.structured().map_err(|e| McplugError::ResultMismatch {
+        server: server.to_string(),
+        tool: tool.to_string(),
+        expected: std::any::type_name::<T>().to_string(),
+        detail: e.to_string(),
+    })
+}
+
+/// Await `operation`, failing with a timeout error after `limit` if one is
+/// set. Servers without configured timeouts are not bounded.
+async fn bounded<T>(
+    server: &str,
+    tool: Option<&str>,
+    limit: Option<Duration>,
+    operation: impl Future<Output = Result<T, McplugError>>,
+) -> Result<T, McplugError> {
+    let Some(limit) = limit else {
+        return operation.await;
+    };
+    tokio::time::timeout(limit, operation)
+        .await
+        .map_err(|_| McplugError::Timeout {
+            server: server.to_string(),
+            tool: tool.map(String::from),
+            duration: limit,
+        })?
+}
+
+/// `ToolNotFound` for `tool`, suggesting the closest of `tools` if one is
+/// near enough.
+pub(crate) fn tool_not_found(server: &str, tool: &str, tools: &[ToolDefinition]) -> McplugError {
+    let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+    McplugError::ToolNotFound {
+        server: server.to_string(),
+        tool: tool.to_string(),
+        suggestion: suggest_tool(tool, &names),
+    }
+}
+
+/// Resolve the effective lifecycle for a server, considering env overrides.
+pub(crate) fn resolve_lifecycle(server: &str, cfg: &ServerConfig) -> Option<Lifecycle> {
+    // MCPLUG_KEEPALIVE=server_name forces keep-alive
+    if let Ok(val) = env::var("MCPLUG_KEEPALIVE") {
+        if val == server || val == "*" {
+            return Some(Lifecycle::KeepAlive);
+        }
+    }
+    // MCPLUG_DISABLE_KEEPALIVE=server_name forces ephemeral
+    if let Ok(val) = env::var("MCPLUG_DISABLE_KEEPALIVE") {
+        if val == server || val == "*" {
+            return Some(Lifecycle::Ephemeral);
         }
     }
+    cfg.lifecycle.clone()
 }
 
 #[cfg(test)]
@@ -185,6 +1066,18 @@ mod tests {
                 env: HashMap::new(),
                 headers: HashMap::new(),
                 lifecycle: None,
+                tool_pins: HashMap::new(),
+                disabled: false,
+                install_timeout: None,
+                log_level: None,
+                sensitive_args: Vec::new(),
+                timeout_ms: None,
+                list_timeout_ms: None,
+                allow_http: None,
+                auth: None,
+                tags: Vec::new(),
+                transport: None,
+                retry: None,
             },
         );
         servers.insert(
@@ -197,11 +1090,27 @@ mod tests {
                 env: HashMap::new(),
                 headers: HashMap::new(),
                 lifecycle: Some(Lifecycle::KeepAlive),
+                tool_pins: HashMap::new(),
+                disabled: false,
+                install_timeout: None,
+                log_level: None,
+                sensitive_args: Vec::new(),
+                timeout_ms: None,
+                list_timeout_ms: None,
+                allow_http: None,
+                auth: None,
+                tags: Vec::new(),
+                transport: None,
+                retry: None,
             },
         );
         McplugConfig {
             mcp_servers: servers,
             imports: vec![],
+            state: None,
+            groups: HashMap::new(),
+            extends: Vec::new(),
+            defaults: None,
         }
     }
 
@@ -252,11 +1161,27 @@ mod tests {
                 env: HashMap::new(),
                 headers: HashMap::new(),
                 lifecycle: None,
+                tool_pins: HashMap::new(),
+                disabled: false,
+                install_timeout: None,
+                log_level: None,
+                sensitive_args: Vec::new(),
+                timeout_ms: None,
+                list_timeout_ms: None,
+                allow_http: None,
+                auth: None,
+                tags: Vec::new(),
+                transport: None,
+                retry: None,
             },
         );
         let config = McplugConfig {
             mcp_servers: servers,
             imports: vec![],
+            state: None,
+            groups: HashMap::new(),
+            extends: Vec::new(),
+            defaults: None,
         };
         let runtime = Runtime::with_config(config);
         let err = runtime.create_transport("broken").unwrap_err();
@@ -359,6 +1284,10 @@ mod tests {
         let config = McplugConfig {
             mcp_servers: HashMap::new(),
             imports: vec![],
+            state: None,
+            groups: HashMap::new(),
+            extends: Vec::new(),
+            defaults: None,
         };
         let runtime = Runtime::with_config(config);
         // Closing a runtime with no active connections should succeed
@@ -380,15 +1309,134 @@ mod tests {
                 env: HashMap::new(),
                 headers: HashMap::new(),
                 lifecycle: None,
+                tool_pins: HashMap::new(),
+                disabled: false,
+                install_timeout: None,
+                log_level: None,
+                sensitive_args: Vec::new(),
+                timeout_ms: None,
+                list_timeout_ms: None,
+                allow_http: None,
+                auth: None,
+                tags: Vec::new(),
+                transport: None,
+                retry: None,
             },
         );
         let config = McplugConfig {
             mcp_servers: servers,
             imports: vec![],
+            state: None,
+            groups: HashMap::new(),
+            extends: Vec::new(),
+            defaults: None,
         };
         let runtime = Runtime::with_config(config);
         let transport = runtime.create_transport("both");
         // Should succeed — create_transport checks base_url first
         assert!(transport.is_ok());
     }
+
+    #[tokio::test]
+    async fn refreshers_only_for_keep_alive_http_servers() {
+        let runtime = Runtime::with_config(make_stdio_config());
+        {
+            let _lock = LIFECYCLE_ENV_LOCK.lock().unwrap();
+            runtime.start_refresher("echo");
+            runtime.start_refresher("http-server");
+        }
+        let refreshers: Vec<String> = runtime.lock_refreshers().keys().cloned().collect();
+        assert_eq!(refreshers, vec!["http-server"]);
+        runtime.close().await.unwrap();
+        assert!(runtime.lock_refreshers().is_empty());
+    }
+
+    fn cleartext_config() -> McplugConfig {
+        serde_json::from_value(serde_json::json!({
+            "mcpServers": {
+                "lan": {"baseUrl": "http://192.168.1.10/mcp"},
+                "slow": {"command": "cat", "timeoutMs": 500, "listTimeoutMs": 2000}
+            }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn builder_applies_default_timeout() {
+        let runtime = Runtime::builder()
+            .config(cleartext_config())
+            .default_timeout(Duration::from_secs(5))
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(runtime.call_timeout("lan"), Some(Duration::from_secs(5)));
+        assert_eq!(runtime.list_timeout("lan"), Some(Duration::from_secs(5)));
+        assert_eq!(runtime.call_timeout("slow"), Some(Duration::from_millis(500)));
+        assert_eq!(runtime.list_timeout("slow"), Some(Duration::from_millis(2000)));
+
+        let runtime = Runtime::with_config(cleartext_config());
+        assert_eq!(runtime.call_timeout("lan"), None);
+    }
+
+    #[tokio::test]
+    async fn builder_allow_http_permits_cleartext() {
+        let runtime = Runtime::builder()
+            .config(cleartext_config())
+            .allow_http(true)
+            .build()
+            .await
+            .unwrap();
+        assert!(runtime.create_transport("lan").is_ok());
+    }
+
+    #[tokio::test]
+    async fn custom_transport_types_use_registered_factories() {
+        let config: McplugConfig = serde_json::from_value(serde_json::json!({
+            "mcpServers": {
+                "inproc": {"type": "inproc", "args": ["marker"]},
+                "grpc": {"type": "grpc", "baseUrl": "https://example.com"}
+            }
+        }))
+        .unwrap();
+        let runtime = Runtime::builder()
+            .config(config)
+            .transport(
+                "inproc",
+                |server: &str, cfg: &ServerConfig| -> Result<Box<dyn McpTransport>, McplugError> {
+                    Err(McplugError::ProtocolError(format!("{} {}", server, cfg.args[0])))
+                },
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let err = runtime.create_transport("inproc").unwrap_err();
+        assert_eq!(err.to_string(), "Protocol error: inproc marker");
+        let err = runtime.create_transport("grpc").unwrap_err();
+        assert!(err.to_string().contains("type 'grpc'"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn retry_policy_precedence() {
+        let config: McplugConfig = serde_json::from_value(serde_json::json!({
+            "mcpServers": {
+                "flaky": {"command": "x", "retry": {"maxAttempts": 5}},
+                "steady": {"command": "x"}
+            }
+        }))
+        .unwrap();
+        let runtime = Runtime::with_config(config.clone());
+        assert!(runtime.retry_policy_for("steady", None).is_none());
+
+        let runtime = Runtime::builder()
+            .config(config)
+            .retry_policy(RetryPolicy::default())
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(runtime.retry_policy_for("flaky", None).unwrap().max_attempts, 5);
+        assert_eq!(runtime.retry_policy_for("steady", None).unwrap().max_attempts, 3);
+        let once = RetryPolicy::none();
+        assert_eq!(runtime.retry_policy_for("flaky", Some(&once)).unwrap().max_attempts, 1);
+    }
 }
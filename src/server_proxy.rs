@@ -1,6 +1,6 @@
 use crate::error::McplugError;
-use crate::runtime::Runtime;
-use crate::types::CallResult;
+use crate::runtime::{tool_not_found, Runtime};
+use crate::types::{CallResult, ToolDefinition};
 
 /// A typed proxy for a specific MCP server, wrapping a Runtime.
 pub struct ServerProxy<'a> {
@@ -24,6 +24,41 @@ impl<'a> ServerProxy<'a> {
         self.runtime.call_tool(&self.server, tool, args).await
     }
 
+    /// Call a tool and deserialize its result as `T`; see
+    /// [`Runtime::call_tool_as`].
+    pub async fn call_as<T: serde::de::DeserializeOwned>(
+        &self,
+        tool: &str,
+        args: serde_json::Value,
+    ) -> Result<T, McplugError> {
+        self.runtime.call_tool_as(&self.server, tool, args).await
+    }
+
+    /// Call a tool after checking that the server has it. A missing tool
+    /// fails with `ToolNotFound`, suggesting a similarly named tool if
+    /// there is one, without sending the call.
+    pub async fn call_checked(
+        &self,
+        tool: &str,
+        args: serde_json::Value,
+    ) -> Result<CallResult, McplugError> {
+        let tools = self.list_tools().await?;
+        if !tools.iter().any(|t| t.name == tool) {
+            return Err(tool_not_found(&self.server, tool, &tools));
+        }
+        self.call(tool, args).await
+    }
+
+    /// The server's tools; see [`Runtime::list_tools`].
+    pub async fn list_tools(&self) -> Result<Vec<ToolDefinition>, McplugError> {
+        self.runtime.list_tools(&self.server).await
+    }
+
+    /// Whether the server has a tool called `name`.
+    pub async fn has_tool(&self, name: &str) -> Result<bool, McplugError> {
+        Ok(self.list_tools().await?.iter().any(|t| t.name == name))
+    }
+
     pub fn server_name(&self) -> &str {
         &self.server
     }
@@ -39,6 +74,10 @@ mod tests {
         Runtime::with_config(McplugConfig {
             mcp_servers: HashMap::new(),
             imports: vec![],
+            state: None,
+            groups: HashMap::new(),
+            extends: Vec::new(),
+            defaults: None,
         })
     }
 
@@ -79,6 +118,15 @@ mod tests {
         assert!(matches!(err, McplugError::ServerNotFound(_)));
     }
 
+    #[tokio::test]
+    async fn server_proxy_checked_call_nonexistent_server_errors() {
+        let runtime = empty_runtime();
+        let proxy = ServerProxy::new(&runtime, "nonexistent");
+        let err = proxy.call_checked("tool", serde_json::json!({})).await.unwrap_err();
+        assert!(matches!(err, McplugError::ServerNotFound(_)));
+        assert!(proxy.has_tool("tool").await.is_err());
+    }
+
     #[test]
     fn server_proxy_empty_server_name() {
         let runtime = empty_runtime();
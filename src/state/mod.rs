@@ -0,0 +1,442 @@
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::loader::load_merged_config;
+use crate::error::McplugError;
+use crate::history::HistoryLog;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+/// Which storage backend holds mcplug's local state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StateBackend {
+    /// One JSON file per entry under the state directory.
+    #[default]
+    File,
+    /// A SQLite database (`state.db`) under the state directory. Requires
+    /// mcplug built with the `sqlite` feature.
+    Sqlite,
+}
+
+/// The `state` section of the config file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StateConfig {
+    #[serde(default)]
+    pub backend: StateBackend,
+    /// Where the backend keeps its data. Defaults to `~/.mcplug`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+/// Storage for local state: OAuth tokens, caches, and saved results.
+///
+/// Entries are JSON values addressed by a namespace (a server name, or a
+/// fixed name like `results`) and a key within it.
+pub trait StateStore: Send + Sync {
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<serde_json::Value>, McplugError>;
+
+    fn put(&self, namespace: &str, key: &str, value: &serde_json::Value)
+        -> Result<(), McplugError>;
+
+    /// Remove an entry. Removing a missing entry is not an error.
+    fn delete(&self, namespace: &str, key: &str) -> Result<(), McplugError>;
+
+    /// Keys in a namespace, sorted.
+    fn keys(&self, namespace: &str) -> Result<Vec<String>, McplugError>;
+
+    /// Namespaces holding at least one entry, sorted.
+    fn namespaces(&self) -> Result<Vec<String>, McplugError>;
+
+    /// A dedicated call history log, for backends that can query one.
+    /// Without it, history is kept as entries in the `history` namespace.
+    fn history(&self) -> Option<&dyn HistoryLog> {
+        None
+    }
+}
+
+impl dyn StateStore + '_ {
+    /// Get an entry and deserialize it. Entries that no longer match `T` are
+    /// treated as missing.
+    pub fn load<T: DeserializeOwned>(&self, namespace: &str, key: &str) -> Option<T> {
+        let value = self.get(namespace, key).ok()??;
+        serde_json::from_value(value).ok()
+    }
+
+    pub fn save<T: Serialize>(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: &T,
+    ) -> Result<(), McplugError> {
+        let value = serde_json::to_value(value).map_err(|e| {
+            McplugError::ProtocolError(format!("Failed to serialize {namespace}/{key}: {e}"))
+        })?;
+        self.put(namespace, key, &value)
+    }
+}
+
+/// Stores each entry as `<root>/<namespace>/<key>.json`.
+///
+/// Writes go to a temporary file that is renamed into place, so a reader
+/// never sees a half-written entry and concurrent writers don't interleave.
+pub struct FileStore {
+    root: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The store at `~/.mcplug`.
+    pub fn home() -> Self {
+        Self::new(default_state_dir())
+    }
+
+    /// Path of the file holding an entry.
+    pub fn entry_path(&self, namespace: &str, key: &str) -> PathBuf {
+        self.root.join(namespace).join(format!("{key}.json"))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl StateStore for FileStore {
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<serde_json::Value>, McplugError> {
+        let path = self.entry_path(namespace, key);
+        let data = match std::fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|e| McplugError::ConfigError {
+                path,
+                detail: format!("Corrupt state entry: {e}"),
+            })
+    }
+
+    fn put(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> Result<(), McplugError> {
+        let path = self.entry_path(namespace, key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_string_pretty(value).map_err(|e| {
+            McplugError::ProtocolError(format!("Failed to serialize {namespace}/{key}: {e}"))
+        })?;
+        // Unique per write, so concurrent writers in one process don't share it
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+        let tmp = path.with_extension(format!(
+            "json.tmp-{}-{}",
+            std::process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&tmp, data)?;
+        std::fs::rename(&tmp, &path).inspect_err(|_| {
+            let _ = std::fs::remove_file(&tmp);
+        })?;
+        Ok(())
+    }
+
+    fn delete(&self, namespace: &str, key: &str) -> Result<(), McplugError> {
+        match std::fs::remove_file(self.entry_path(namespace, key)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn keys(&self, namespace: &str) -> Result<Vec<String>, McplugError> {
+        let entries = match std::fs::read_dir(self.root.join(namespace)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut keys: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().into_string().ok()?;
+                name.strip_suffix(".json").map(String::from)
+            })
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn namespaces(&self) -> Result<Vec<String>, McplugError> {
+        let entries = match std::fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        // The state directory also holds config and daemon files; only
+        // directories with entries count
+        let mut namespaces: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().into_string().ok())
+            .filter(|ns| self.keys(ns).is_ok_and(|keys| !keys.is_empty()))
+            .collect();
+        namespaces.sort();
+        Ok(namespaces)
+    }
+}
+
+/// Lock files older than this are assumed to belong to a process that died
+/// while holding them.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(60);
+
+/// An exclusive lock shared between processes: a file created with
+/// `create_new` and removed when the guard is dropped.
+#[derive(Debug)]
+pub struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Wait up to `timeout` to create the lock file at `path`.
+    pub async fn acquire(path: &Path, timeout: Duration) -> Result<Self, McplugError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)
+            {
+                Ok(mut file) => {
+                    let _ = write!(file, "{}", std::process::id());
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                    });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e.into()),
+            }
+            let stale = std::fs::metadata(path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > STALE_LOCK_AGE);
+            if stale {
+                let _ = std::fs::remove_file(path);
+                continue;
+            }
+            if Instant::now() >= deadline {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("Timed out waiting for lock {}", path.display()),
+                )
+                .into());
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// `~/.mcplug`, the default home of all state.
+pub fn default_state_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".mcplug")
+}
+
+/// Open the store described by a `state` config section.
+pub fn open_store(config: &StateConfig) -> Result<Box<dyn StateStore>, McplugError> {
+    let root = config.path.clone().unwrap_or_else(default_state_dir);
+    match config.backend {
+        StateBackend::File => Ok(Box::new(FileStore::new(root))),
+        #[cfg(feature = "sqlite")]
+        StateBackend::Sqlite => Ok(Box::new(SqliteStore::open(&root)?)),
+        #[cfg(not(feature = "sqlite"))]
+        StateBackend::Sqlite => Err(McplugError::ConfigError {
+            path: root,
+            detail: "The sqlite state backend needs mcplug built with the `sqlite` feature"
+                .to_string(),
+        }),
+    }
+}
+
+/// Open the store selected by the loaded config, or the file store at
+/// `~/.mcplug` when the config has no `state` section or can't be loaded.
+pub fn default_store() -> Result<Box<dyn StateStore>, McplugError> {
+    let config = load_merged_config(None).ok().and_then(|c| c.state);
+    open_store(&config.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn file_lock_is_exclusive_until_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("srv").join("tokens.lock");
+        let lock = FileLock::acquire(&path, Duration::from_secs(1)).await.unwrap();
+        assert!(path.exists());
+        let err = FileLock::acquire(&path, Duration::from_millis(100))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Timed out waiting for lock"));
+        drop(lock);
+        assert!(!path.exists());
+        FileLock::acquire(&path, Duration::from_millis(100)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn file_lock_breaks_stale_locks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tokens.lock");
+        let file = std::fs::File::create(&path).unwrap();
+        file.set_modified(std::time::SystemTime::now() - 2 * STALE_LOCK_AGE)
+            .unwrap();
+        FileLock::acquire(&path, Duration::from_millis(100)).await.unwrap();
+    }
+
+    #[test]
+    fn file_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Box<dyn StateStore> = Box::new(FileStore::new(dir.path()));
+        assert_eq!(store.get("srv", "tokens").unwrap(), None);
+
+        store.put("srv", "tokens", &json!({"a": 1})).unwrap();
+        assert_eq!(store.get("srv", "tokens").unwrap(), Some(json!({"a": 1})));
+        assert!(dir.path().join("srv").join("tokens.json").exists());
+
+        store.delete("srv", "tokens").unwrap();
+        store.delete("srv", "tokens").unwrap();
+        assert_eq!(store.get("srv", "tokens").unwrap(), None);
+    }
+
+    #[test]
+    fn file_store_keys_sorted_without_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path());
+        assert!(store.keys("results").unwrap().is_empty());
+        store.put("results", "b", &json!(1)).unwrap();
+        store.put("results", "a", &json!(2)).unwrap();
+        std::fs::write(dir.path().join("results").join("c.json.tmp-1"), "x").unwrap();
+        assert_eq!(store.keys("results").unwrap(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn concurrent_puts_to_one_key_leave_a_whole_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = std::sync::Arc::new(FileStore::new(dir.path()));
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    let value = json!({"writer": i, "data": "x".repeat(1024 * 1024)});
+                    for _ in 0..10 {
+                        store.put("srv", "tokens", &value).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        let value = store.get("srv", "tokens").unwrap().unwrap();
+        assert_eq!(value["data"].as_str().unwrap().len(), 1024 * 1024);
+        assert_eq!(store.keys("srv").unwrap(), vec!["tokens"]);
+        let files = std::fs::read_dir(dir.path().join("srv")).unwrap().count();
+        assert_eq!(files, 1, "temporary files were left behind");
+    }
+
+    #[test]
+    fn file_store_namespaces_skip_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path());
+        assert!(store.namespaces().unwrap().is_empty());
+        store.put("srv", "tokens", &json!({})).unwrap();
+        store.put("results", "a", &json!(1)).unwrap();
+        std::fs::write(dir.path().join("mcplug.json"), "{}").unwrap();
+        std::fs::create_dir(dir.path().join("empty")).unwrap();
+        assert_eq!(store.namespaces().unwrap(), vec!["results", "srv"]);
+    }
+
+    #[test]
+    fn corrupt_entry_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path());
+        std::fs::create_dir_all(dir.path().join("srv")).unwrap();
+        std::fs::write(dir.path().join("srv").join("bad.json"), "{").unwrap();
+        assert!(store.get("srv", "bad").is_err());
+        let store: &dyn StateStore = &store;
+        assert!(store.load::<serde_json::Value>("srv", "bad").is_none());
+    }
+
+    #[test]
+    fn typed_load_and_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Box<dyn StateStore> = Box::new(FileStore::new(dir.path()));
+        store.save("ns", "k", &vec!["x".to_string()]).unwrap();
+        assert_eq!(store.load::<Vec<String>>("ns", "k").unwrap(), vec!["x"]);
+        assert!(store.load::<u32>("ns", "k").is_none());
+    }
+
+    #[test]
+    fn state_config_parsing() {
+        let cfg: StateConfig = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(cfg.backend, StateBackend::File);
+        let cfg: StateConfig =
+            serde_json::from_value(json!({"backend": "file", "path": "/tmp/s"})).unwrap();
+        assert_eq!(cfg.path, Some(PathBuf::from("/tmp/s")));
+        let cfg: StateConfig = serde_json::from_value(json!({"backend": "sqlite"})).unwrap();
+        assert_eq!(cfg.backend, StateBackend::Sqlite);
+        assert!(serde_json::from_value::<StateConfig>(json!({"backend": "redis"})).is_err());
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    #[test]
+    fn sqlite_backend_needs_the_feature() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StateConfig {
+            backend: StateBackend::Sqlite,
+            path: Some(dir.path().to_path_buf()),
+        };
+        let err = open_store(&config).err().unwrap();
+        assert!(err.to_string().contains("`sqlite` feature"), "{err}");
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_backend_selected_by_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StateConfig {
+            backend: StateBackend::Sqlite,
+            path: Some(dir.path().to_path_buf()),
+        };
+        let store = open_store(&config).unwrap();
+        store.put("srv", "k", &json!(1)).unwrap();
+        assert!(dir.path().join(sqlite::DATABASE_FILE).exists());
+        assert!(!dir.path().join("srv").exists());
+    }
+}
@@ -0,0 +1,528 @@
+//! The SQLite state backend, linked against the system `libsqlite3`.
+//!
+//! Entries live in one `entries` table keyed by namespace and key, and call
+//! history in a `history` table with one column per field. The database runs in WAL mode with a busy timeout, so several processes can
+//! read while one writes, and writers queue instead of failing.
+
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+
+use crate::error::McplugError;
+use crate::history::{HistoryEntry, HistoryFilter, HistoryLog};
+
+use super::StateStore;
+
+/// File name of the database inside the state directory.
+pub const DATABASE_FILE: &str = "state.db";
+
+/// How long a writer waits for another to finish before giving up.
+const BUSY_TIMEOUT_MS: c_int = 5000;
+
+const SQLITE_OK: c_int = 0;
+const SQLITE_ROW: c_int = 100;
+const SQLITE_DONE: c_int = 101;
+const SQLITE_NULL: c_int = 5;
+const SQLITE_OPEN_READWRITE: c_int = 0x2;
+const SQLITE_OPEN_CREATE: c_int = 0x4;
+const SQLITE_OPEN_FULLMUTEX: c_int = 0x10000;
+/// `SQLITE_TRANSIENT`: SQLite copies bound text before the call returns.
+const SQLITE_TRANSIENT: isize = -1;
+
+#[repr(C)]
+struct RawDb {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+struct RawStmt {
+    _private: [u8; 0],
+}
+
+#[link(name = "sqlite3")]
+extern "C" {
+    fn sqlite3_open_v2(
+        filename: *const c_char,
+        db: *mut *mut RawDb,
+        flags: c_int,
+        vfs: *const c_char,
+    ) -> c_int;
+    fn sqlite3_close_v2(db: *mut RawDb) -> c_int;
+    fn sqlite3_errmsg(db: *mut RawDb) -> *const c_char;
+    fn sqlite3_busy_timeout(db: *mut RawDb, ms: c_int) -> c_int;
+    fn sqlite3_prepare_v2(
+        db: *mut RawDb,
+        sql: *const c_char,
+        len: c_int,
+        stmt: *mut *mut RawStmt,
+        tail: *mut *const c_char,
+    ) -> c_int;
+    fn sqlite3_bind_text(
+        stmt: *mut RawStmt,
+        index: c_int,
+        text: *const c_char,
+        len: c_int,
+        destructor: isize,
+    ) -> c_int;
+    fn sqlite3_bind_int64(stmt: *mut RawStmt, index: c_int, value: i64) -> c_int;
+    fn sqlite3_bind_null(stmt: *mut RawStmt, index: c_int) -> c_int;
+    fn sqlite3_step(stmt: *mut RawStmt) -> c_int;
+    fn sqlite3_column_type(stmt: *mut RawStmt, col: c_int) -> c_int;
+    fn sqlite3_column_text(stmt: *mut RawStmt, col: c_int) -> *const u8;
+    fn sqlite3_column_bytes(stmt: *mut RawStmt, col: c_int) -> c_int;
+    fn sqlite3_column_int64(stmt: *mut RawStmt, col: c_int) -> i64;
+    fn sqlite3_finalize(stmt: *mut RawStmt) -> c_int;
+}
+
+/// A value bound to a `?` parameter.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Param<'a> {
+    Text(&'a str),
+    Int(i64),
+    Null,
+}
+
+/// One row of a query result.
+pub(crate) struct Row<'s> {
+    stmt: &'s Statement,
+}
+
+impl Row<'_> {
+    pub(crate) fn text(&self, col: c_int) -> Option<String> {
+        // SAFETY: the statement is positioned on a row by a successful step
+        unsafe {
+            if sqlite3_column_type(self.stmt.raw, col) == SQLITE_NULL {
+                return None;
+            }
+            let ptr = sqlite3_column_text(self.stmt.raw, col);
+            let len = sqlite3_column_bytes(self.stmt.raw, col);
+            if ptr.is_null() {
+                return Some(String::new());
+            }
+            let bytes = std::slice::from_raw_parts(ptr, len as usize);
+            Some(String::from_utf8_lossy(bytes).into_owned())
+        }
+    }
+
+    pub(crate) fn int(&self, col: c_int) -> i64 {
+        // SAFETY: as for `text`
+        unsafe { sqlite3_column_int64(self.stmt.raw, col) }
+    }
+}
+
+struct Statement {
+    raw: *mut RawStmt,
+}
+
+impl Drop for Statement {
+    fn drop(&mut self) {
+        // SAFETY: `raw` came from sqlite3_prepare_v2 and is finalized once
+        unsafe {
+            sqlite3_finalize(self.raw);
+        }
+    }
+}
+
+/// An open database connection.
+pub(crate) struct Connection {
+    db: *mut RawDb,
+    path: PathBuf,
+}
+
+// SAFETY: the connection is opened with SQLITE_OPEN_FULLMUTEX (serialized
+// mode), so SQLite itself serializes use from several threads
+unsafe impl Send for Connection {}
+
+impl Connection {
+    /// Open (creating if needed) the database at `path`, in WAL mode.
+    pub(crate) fn open(path: &Path) -> Result<Self, McplugError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let filename = CString::new(path.to_string_lossy().as_bytes()).map_err(|_| {
+            sqlite_error(path, "the database path contains a NUL byte".to_string())
+        })?;
+        let mut db = std::ptr::null_mut();
+        let flags = SQLITE_OPEN_READWRITE | SQLITE_OPEN_CREATE | SQLITE_OPEN_FULLMUTEX;
+        // SAFETY: `filename` is NUL-terminated and `db` is a valid out pointer
+        let rc = unsafe { sqlite3_open_v2(filename.as_ptr(), &mut db, flags, std::ptr::null()) };
+        // SQLite allocates a handle even when opening fails; close it via Drop
+        let conn = Self {
+            db,
+            path: path.to_path_buf(),
+        };
+        if rc != SQLITE_OK {
+            return Err(conn.error());
+        }
+        // SAFETY: `db` is an open connection
+        unsafe {
+            sqlite3_busy_timeout(conn.db, BUSY_TIMEOUT_MS);
+        }
+        conn.query("PRAGMA journal_mode = WAL", &[], |_| Ok(()))?;
+        // In WAL mode this still never corrupts the database, and spares a
+        // sync on every commit
+        conn.execute("PRAGMA synchronous = NORMAL", &[])?;
+        Ok(conn)
+    }
+
+    /// Run a statement that returns no rows.
+    pub(crate) fn execute(&self, sql: &str, params: &[Param]) -> Result<(), McplugError> {
+        self.query(sql, params, |_| Ok(())).map(|_| ())
+    }
+
+    /// Run a query, mapping each row with `f`.
+    pub(crate) fn query<T>(
+        &self,
+        sql: &str,
+        params: &[Param],
+        mut f: impl FnMut(&Row) -> Result<T, McplugError>,
+    ) -> Result<Vec<T>, McplugError> {
+        let stmt = self.prepare(sql)?;
+        for (i, param) in params.iter().enumerate() {
+            let index = i as c_int + 1;
+            // SAFETY: `stmt` is a prepared statement; text is copied
+            // (SQLITE_TRANSIENT) before the call returns
+            let rc = unsafe {
+                match *param {
+                    Param::Text(text) => sqlite3_bind_text(
+                        stmt.raw,
+                        index,
+                        text.as_ptr().cast(),
+                        text.len() as c_int,
+                        SQLITE_TRANSIENT,
+                    ),
+                    Param::Int(value) => sqlite3_bind_int64(stmt.raw, index, value),
+                    Param::Null => sqlite3_bind_null(stmt.raw, index),
+                }
+            };
+            if rc != SQLITE_OK {
+                return Err(self.error());
+            }
+        }
+        let mut rows = Vec::new();
+        loop {
+            // SAFETY: `stmt` is a prepared statement with all parameters bound
+            match unsafe { sqlite3_step(stmt.raw) } {
+                SQLITE_ROW => rows.push(f(&Row { stmt: &stmt })?),
+                SQLITE_DONE => return Ok(rows),
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    fn prepare(&self, sql: &str) -> Result<Statement, McplugError> {
+        let mut raw = std::ptr::null_mut();
+        // SAFETY: `sql` is valid for `len` bytes and `raw` is a valid out pointer
+        let rc = unsafe {
+            sqlite3_prepare_v2(
+                self.db,
+                sql.as_ptr().cast(),
+                sql.len() as c_int,
+                &mut raw,
+                std::ptr::null_mut(),
+            )
+        };
+        if rc != SQLITE_OK {
+            return Err(self.error());
+        }
+        Ok(Statement { raw })
+    }
+
+    /// The connection's latest error as a `McplugError`.
+    fn error(&self) -> McplugError {
+        // SAFETY: sqlite3_errmsg returns a NUL-terminated string owned by SQLite
+        let message = unsafe {
+            let ptr = sqlite3_errmsg(self.db);
+            if ptr.is_null() {
+                "out of memory".to_string()
+            } else {
+                CStr::from_ptr(ptr).to_string_lossy().into_owned()
+            }
+        };
+        sqlite_error(&self.path, message)
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // SAFETY: `db` came from sqlite3_open_v2 and is closed once
+        unsafe {
+            sqlite3_close_v2(self.db);
+        }
+    }
+}
+
+fn sqlite_error(path: &Path, message: String) -> McplugError {
+    McplugError::IoError(std::io::Error::other(format!(
+        "SQLite error in {}: {message}",
+        path.display()
+    )))
+}
+
+/// Stores entries in the `entries` table of `<root>/state.db`.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// Open the database in the state directory `root`, creating it and its
+    /// tables if needed.
+    pub fn open(root: &Path) -> Result<Self, McplugError> {
+        let conn = Connection::open(&root.join(DATABASE_FILE))?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS entries (
+                namespace TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+                PRIMARY KEY (namespace, key)
+            )",
+            &[],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS history (
+                id INTEGER PRIMARY KEY,
+                server TEXT NOT NULL,
+                tool TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                ok INTEGER NOT NULL,
+                error TEXT,
+                args TEXT
+            )",
+            &[],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS history_started_at ON history (started_at)",
+            &[],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS history_server_tool ON history (server, tool)",
+            &[],
+        )?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Run `f` with the store's connection.
+    pub(crate) fn with_conn<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> Result<T, McplugError>,
+    ) -> Result<T, McplugError> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        f(&conn)
+    }
+}
+
+impl StateStore for SqliteStore {
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<serde_json::Value>, McplugError> {
+        let values = self.with_conn(|conn| {
+            conn.query(
+                "SELECT value FROM entries WHERE namespace = ?1 AND key = ?2",
+                &[Param::Text(namespace), Param::Text(key)],
+                |row| Ok(row.text(0).unwrap_or_default()),
+            )
+        })?;
+        let Some(data) = values.into_iter().next() else {
+            return Ok(None);
+        };
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|e| McplugError::ProtocolError(format!("Corrupt state entry {namespace}/{key}: {e}")))
+    }
+
+    fn put(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> Result<(), McplugError> {
+        let data = value.to_string();
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO entries (namespace, key, value) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (namespace, key) DO UPDATE SET
+                    value = excluded.value,
+                    updated_at = excluded.updated_at",
+                &[Param::Text(namespace), Param::Text(key), Param::Text(&data)],
+            )
+        })
+    }
+
+    fn delete(&self, namespace: &str, key: &str) -> Result<(), McplugError> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "DELETE FROM entries WHERE namespace = ?1 AND key = ?2",
+                &[Param::Text(namespace), Param::Text(key)],
+            )
+        })
+    }
+
+    fn keys(&self, namespace: &str) -> Result<Vec<String>, McplugError> {
+        self.with_conn(|conn| {
+            conn.query(
+                "SELECT key FROM entries WHERE namespace = ?1 ORDER BY key",
+                &[Param::Text(namespace)],
+                |row| Ok(row.text(0).unwrap_or_default()),
+            )
+        })
+    }
+
+    fn namespaces(&self) -> Result<Vec<String>, McplugError> {
+        self.with_conn(|conn| {
+            conn.query(
+                "SELECT DISTINCT namespace FROM entries ORDER BY namespace",
+                &[],
+                |row| Ok(row.text(0).unwrap_or_default()),
+            )
+        })
+    }
+
+    fn history(&self) -> Option<&dyn HistoryLog> {
+        Some(self)
+    }
+}
+
+/// `started_at` as stored: fixed width, so text order is time order.
+fn timestamp(time: &DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string()
+}
+
+impl HistoryLog for SqliteStore {
+    fn append(&self, entry: &HistoryEntry) -> Result<(), McplugError> {
+        let started_at = timestamp(&entry.started_at);
+        let args = entry.args.as_ref().map(|args| args.to_string());
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO history (server, tool, started_at, duration_ms, ok, error, args)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                &[
+                    Param::Text(&entry.server),
+                    Param::Text(&entry.tool),
+                    Param::Text(&started_at),
+                    Param::Int(entry.duration_ms as i64),
+                    Param::Int(i64::from(entry.ok)),
+                    entry.error.as_deref().map_or(Param::Null, Param::Text),
+                    args.as_deref().map_or(Param::Null, Param::Text),
+                ],
+            )
+        })
+    }
+
+    fn query(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>, McplugError> {
+        let since = filter.since.as_ref().map(timestamp);
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+        if let Some(server) = &filter.server {
+            conditions.push("server = ?");
+            params.push(Param::Text(server));
+        }
+        if let Some(tool) = &filter.tool {
+            conditions.push("tool = ?");
+            params.push(Param::Text(tool));
+        }
+        if let Some(since) = &since {
+            conditions.push("started_at >= ?");
+            params.push(Param::Text(since));
+        }
+        if filter.failed {
+            conditions.push("ok = 0");
+        }
+        let mut sql = "SELECT server, tool, started_at, duration_ms, ok, error, args FROM history"
+            .to_string();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY started_at, id");
+        let rows = self.with_conn(|conn| {
+            conn.query(&sql, &params, |row| {
+                Ok((
+                    row.text(0).unwrap_or_default(),
+                    row.text(1).unwrap_or_default(),
+                    row.text(2).unwrap_or_default(),
+                    row.int(3),
+                    row.int(4),
+                    row.text(5),
+                    row.text(6),
+                ))
+            })
+        })?;
+        // Rows that no longer parse are skipped, as with the file backend
+        Ok(rows
+            .into_iter()
+            .filter_map(|(server, tool, started_at, duration_ms, ok, error, args)| {
+                Some(HistoryEntry {
+                    server,
+                    tool,
+                    started_at: DateTime::parse_from_rfc3339(&started_at)
+                        .ok()?
+                        .with_timezone(&Utc),
+                    duration_ms: u64::try_from(duration_ms).ok()?,
+                    ok: ok != 0,
+                    error,
+                    args: match args {
+                        Some(args) => Some(serde_json::from_str(&args).ok()?),
+                        None => None,
+                    },
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sqlite_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Box<dyn StateStore> = Box::new(SqliteStore::open(dir.path()).unwrap());
+        assert_eq!(store.get("srv", "tokens").unwrap(), None);
+
+        store.put("srv", "tokens", &json!({"a": 1})).unwrap();
+        store.put("srv", "tokens", &json!({"a": 2})).unwrap();
+        store.put("results", "b", &json!("it's \"quoted\"")).unwrap();
+        store.put("results", "a", &json!(null)).unwrap();
+        assert_eq!(store.get("srv", "tokens").unwrap(), Some(json!({"a": 2})));
+        assert_eq!(store.get("results", "b").unwrap(), Some(json!("it's \"quoted\"")));
+        assert_eq!(store.keys("results").unwrap(), vec!["a", "b"]);
+        assert_eq!(store.namespaces().unwrap(), vec!["results", "srv"]);
+        assert!(dir.path().join(DATABASE_FILE).exists());
+
+        store.delete("srv", "tokens").unwrap();
+        store.delete("srv", "tokens").unwrap();
+        assert_eq!(store.get("srv", "tokens").unwrap(), None);
+        assert_eq!(store.namespaces().unwrap(), vec!["results"]);
+    }
+
+    #[test]
+    fn concurrent_writers_on_separate_connections() {
+        let dir = tempfile::tempdir().unwrap();
+        SqliteStore::open(dir.path()).unwrap();
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let root = dir.path().to_path_buf();
+                std::thread::spawn(move || {
+                    // Each thread stands in for another mcplug process
+                    let store = SqliteStore::open(&root).unwrap();
+                    for n in 0..25 {
+                        store.put("srv", "shared", &json!({"writer": i, "n": n})).unwrap();
+                        store.put("srv", &format!("w{i}-{n}"), &json!(n)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        let store = SqliteStore::open(dir.path()).unwrap();
+        assert_eq!(store.keys("srv").unwrap().len(), 8 * 25 + 1);
+        assert_eq!(store.get("srv", "shared").unwrap().unwrap()["n"], 24);
+    }
+}
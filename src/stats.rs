@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::error::McplugError;
+use crate::types::CallResult;
+
+/// Upper bounds, in milliseconds, of the latency histogram's buckets. Calls
+/// slower than the last bound are counted in one more bucket after them.
+pub const LATENCY_BUCKETS_MS: [u64; 8] = [10, 50, 100, 250, 500, 1000, 5000, 30000];
+
+/// Call counts and latencies per server, as returned by `Runtime::stats`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RuntimeStats {
+    pub servers: BTreeMap<String, ServerStats>,
+}
+
+impl RuntimeStats {
+    /// Stats for `server`, if it has been called.
+    pub fn server(&self, server: &str) -> Option<&ServerStats> {
+        self.servers.get(server)
+    }
+
+    /// Stats of every server combined.
+    pub fn total(&self) -> ServerStats {
+        let mut total = ServerStats::default();
+        for stats in self.servers.values() {
+            total.calls += stats.calls;
+            total.errors += stats.errors;
+            total.tool_errors += stats.tool_errors;
+            total.latency.merge(&stats.latency);
+        }
+        total
+    }
+}
+
+/// The calls made to one server through `Runtime::call_tool`, counting each
+/// call once however many times it was retried.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStats {
+    pub calls: u64,
+    /// Calls that failed, including those refused by an interceptor.
+    pub errors: u64,
+    /// Calls the server answered with a result marked `isError`.
+    pub tool_errors: u64,
+    pub latency: LatencyHistogram,
+}
+
+impl ServerStats {
+    pub(crate) fn record(&mut self, elapsed: Duration, outcome: &Result<CallResult, McplugError>) {
+        self.calls += 1;
+        match outcome {
+            Ok(result) if result.is_error => self.tool_errors += 1,
+            Ok(_) => {}
+            Err(_) => self.errors += 1,
+        }
+        self.latency.record(elapsed);
+    }
+
+    /// One line for status output, e.g. `12 calls, 1 error, p50 ≤50ms, p95 ≤250ms, max 310ms`.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![plural(self.calls, "call")];
+        if self.errors > 0 {
+            parts.push(plural(self.errors, "error"));
+        }
+        if self.tool_errors > 0 {
+            parts.push(plural(self.tool_errors, "tool error"));
+        }
+        if let (Some(p50), Some(p95)) = (self.latency.percentile(0.5), self.latency.percentile(0.95)) {
+            parts.push(format!("p50 ≤{}ms", p50.as_millis()));
+            parts.push(format!("p95 ≤{}ms", p95.as_millis()));
+            parts.push(format!("max {}ms", self.latency.max_ms));
+        }
+        parts.join(", ")
+    }
+}
+
+fn plural(count: u64, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// Call latencies, bucketed by `LATENCY_BUCKETS_MS`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyHistogram {
+    /// Calls per bucket, with one more entry than `LATENCY_BUCKETS_MS` for
+    /// calls slower than every bound.
+    pub counts: Vec<u64>,
+    pub total_ms: u64,
+    pub max_ms: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            counts: vec![0; LATENCY_BUCKETS_MS.len() + 1],
+            total_ms: 0,
+            max_ms: 0,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub(crate) fn record(&mut self, elapsed: Duration) {
+        let ms = elapsed.as_millis() as u64;
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.counts[bucket] += 1;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    fn merge(&mut self, other: &LatencyHistogram) {
+        for (count, more) in self.counts.iter_mut().zip(&other.counts) {
+            *count += more;
+        }
+        self.total_ms += other.total_ms;
+        self.max_ms = self.max_ms.max(other.max_ms);
+    }
+
+    /// How many calls were recorded.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The average latency, if any calls were recorded.
+    pub fn mean(&self) -> Option<Duration> {
+        let count = self.count();
+        (count > 0).then(|| Duration::from_millis(self.total_ms / count))
+    }
+
+    /// An upper bound on the `q` quantile (0.0 to 1.0) of latencies: the
+    /// bound of the bucket it falls in, or the slowest call for the last
+    /// bucket. `None` if no calls were recorded.
+    pub fn percentile(&self, q: f64) -> Option<Duration> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, n) in self.counts.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                let ms = LATENCY_BUCKETS_MS
+                    .get(bucket)
+                    .map_or(self.max_ms, |bound| (*bound).min(self.max_ms));
+                return Some(Duration::from_millis(ms));
+            }
+        }
+        Some(Duration::from_millis(self.max_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok(is_error: bool) -> Result<CallResult, McplugError> {
+        Ok(CallResult {
+            content: vec![],
+            is_error,
+            raw_response: None,
+        })
+    }
+
+    #[test]
+    fn records_calls_and_outcomes() {
+        let mut stats = ServerStats::default();
+        stats.record(Duration::from_millis(5), &ok(false));
+        stats.record(Duration::from_millis(40), &ok(true));
+        stats.record(Duration::from_millis(700), &Err(McplugError::ProtocolError("x".into())));
+        assert_eq!((stats.calls, stats.errors, stats.tool_errors), (3, 1, 1));
+        assert_eq!(stats.latency.counts[0], 1);
+        assert_eq!(stats.latency.counts[1], 1);
+        assert_eq!(stats.latency.counts[5], 1);
+        assert_eq!(stats.latency.max_ms, 700);
+        assert_eq!(stats.latency.mean(), Some(Duration::from_millis(248)));
+        assert_eq!(
+            stats.summary(),
+            "3 calls, 1 error, 1 tool error, p50 ≤50ms, p95 ≤700ms, max 700ms"
+        );
+    }
+
+    #[test]
+    fn percentiles_use_bucket_bounds() {
+        let mut latency = LatencyHistogram::default();
+        assert_eq!(latency.percentile(0.5), None);
+        for ms in [1, 2, 3, 80, 60_000] {
+            latency.record(Duration::from_millis(ms));
+        }
+        assert_eq!(latency.percentile(0.5), Some(Duration::from_millis(10)));
+        assert_eq!(latency.percentile(0.8), Some(Duration::from_millis(100)));
+        assert_eq!(latency.percentile(1.0), Some(Duration::from_millis(60_000)));
+    }
+
+    #[test]
+    fn total_combines_servers() {
+        let mut stats = RuntimeStats::default();
+        for (server, ms) in [("a", 5), ("b", 300), ("a", 20)] {
+            stats
+                .servers
+                .entry(server.to_string())
+                .or_default()
+                .record(Duration::from_millis(ms), &ok(false));
+        }
+        assert_eq!(stats.server("a").unwrap().calls, 2);
+        let total = stats.total();
+        assert_eq!(total.calls, 3);
+        assert_eq!(total.latency.max_ms, 300);
+        assert_eq!(total.latency.count(), 3);
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["servers"]["b"]["toolErrors"], 0);
+        assert_eq!(json["servers"]["b"]["latency"]["maxMs"], 300);
+    }
+}
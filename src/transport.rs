@@ -1,7 +1,8 @@
 use async_trait::async_trait;
 
+use crate::config::ServerConfig;
 use crate::error::McplugError;
-use crate::types::{CallResult, ServerInfo, ToolDefinition};
+use crate::types::{CallResult, Notification, ServerInfo, ToolDefinition};
 
 impl std::fmt::Debug for dyn McpTransport {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -11,6 +12,13 @@ impl std::fmt::Debug for dyn McpTransport {
 
 #[async_trait]
 pub trait McpTransport: Send + Sync {
+    /// Wait for the server to finish starting up before protocol timeouts
+    /// apply. Stdio servers launched with `npx -y` may spend minutes
+    /// installing on first run; other transports are ready immediately.
+    async fn wait_ready(&mut self) -> Result<(), McplugError> {
+        Ok(())
+    }
+
     /// Perform the MCP initialization handshake and return server info.
     async fn initialize(&mut self) -> Result<ServerInfo, McplugError>;
 
@@ -21,6 +29,74 @@ pub trait McpTransport: Send + Sync {
     async fn call_tool(&self, name: &str, args: serde_json::Value)
         -> Result<CallResult, McplugError>;
 
+    /// Send an arbitrary JSON-RPC request and return its `result` value.
+    /// Transports that can't send arbitrary requests return an error.
+    async fn request(
+        &self,
+        method: &str,
+        _params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, McplugError> {
+        Err(McplugError::ProtocolError(format!(
+            "request not supported by this transport: {method}"
+        )))
+    }
+
+    /// Send an arbitrary JSON-RPC notification, which gets no response.
+    /// Transports that can't send notifications do nothing.
+    async fn notify(
+        &self,
+        _method: &str,
+        _params: Option<serde_json::Value>,
+    ) -> Result<(), McplugError> {
+        Ok(())
+    }
+
+    /// The most recent lines the server wrote to stderr, oldest first. Only
+    /// stdio servers have a stderr to report.
+    fn stderr_lines(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Receive the notifications the server sends from now on, as the
+    /// transport reads them. `None` if the transport doesn't report
+    /// notifications.
+    fn subscribe(&self) -> Option<tokio::sync::broadcast::Receiver<Notification>> {
+        None
+    }
+
+    /// Tell the server to stop work on requests whose callers gave up
+    /// waiting (their futures were dropped), by sending
+    /// `notifications/cancelled` for each. Transports that can't cancel do
+    /// nothing.
+    async fn cancel_pending(&self, _reason: &str) -> Result<(), McplugError> {
+        Ok(())
+    }
+
     /// Close the transport connection and clean up resources.
     async fn close(&mut self) -> Result<(), McplugError>;
 }
+
+/// Creates transports for servers whose config `type` names it, letting
+/// programs embedding mcplug add transports of their own (gRPC,
+/// in-process, ...). Register one with `RuntimeBuilder::transport`.
+///
+/// Closures with the same signature as `create` implement this trait.
+pub trait TransportFactory: Send + Sync {
+    /// Create an unconnected transport for `server`. The Runtime calls
+    /// `wait_ready` and `initialize` on it before use.
+    fn create(&self, server: &str, config: &ServerConfig)
+        -> Result<Box<dyn McpTransport>, McplugError>;
+}
+
+impl<F> TransportFactory for F
+where
+    F: Fn(&str, &ServerConfig) -> Result<Box<dyn McpTransport>, McplugError> + Send + Sync,
+{
+    fn create(
+        &self,
+        server: &str,
+        config: &ServerConfig,
+    ) -> Result<Box<dyn McpTransport>, McplugError> {
+        self(server, config)
+    }
+}
@@ -1,27 +1,71 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde_json::json;
 
 use crate::error::McplugError;
+use crate::oauth::{get_valid_token, OAuthClient};
 use crate::transport::McpTransport;
-use crate::types::{CallResult, ServerInfo, ToolDefinition};
+use crate::types::{CallResult, Notification, ServerInfo, ToolDefinition};
 
-use super::jsonrpc::{JsonRpcNotification, JsonRpcResponse, RequestBuilder};
+use super::jsonrpc::{
+    cancelled_params, initialize_params, is_protocol_version_error, next_protocol_version,
+    InFlight, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, RequestBuilder,
+    PROTOCOL_VERSIONS,
+};
+use super::session::{clear_session, load_session, save_session, SessionState};
+use super::sse::SseParser;
+
+/// How many times a dropped SSE stream is resumed before its request fails.
+const MAX_RESUME_ATTEMPTS: u32 = 3;
+
+/// How long to wait before resuming a dropped stream, unless the server set
+/// a `retry` delay.
+const DEFAULT_RESUME_DELAY: Duration = Duration::from_millis(500);
+
+/// How many notifications a slow subscriber may fall behind by before it
+/// misses some.
+const NOTIFICATION_BUFFER: usize = 64;
+
+/// Resolve whether cleartext `http://` is permitted: the server's
+/// `allowHttp` if set, otherwise `MCPLUG_ALLOW_HTTP` (set by `--allow-http`).
+pub fn allow_http(configured: bool) -> bool {
+    configured || env_allows_http(std::env::var("MCPLUG_ALLOW_HTTP").ok().as_deref())
+}
+
+fn env_allows_http(value: Option<&str>) -> bool {
+    matches!(
+        value.map(|v| v.trim().to_ascii_lowercase()).as_deref(),
+        Some("1" | "true" | "yes")
+    )
+}
 
 /// MCP HTTP Streamable transport.
 ///
-/// Sends JSON-RPC requests as HTTP POST to a base URL and parses
-/// the JSON-RPC response from the response body.
+/// Sends JSON-RPC requests as HTTP POST to a base URL and parses the
+/// JSON-RPC response from the response body, which is either JSON or an SSE
+/// stream. A stream that drops before the response arrives is resumed with
+/// a GET carrying `Last-Event-ID`.
 pub struct HttpSseTransport {
     client: reqwest::Client,
     base_url: String,
     server_name: String,
     session_id: Mutex<Option<String>>,
+    /// Where the session is persisted for resumption, if enabled.
+    session_file: Option<PathBuf>,
+    /// OAuth client whose cached token is sent as the bearer token, if enabled.
+    oauth: Option<OAuthClient>,
     // RequestBuilder doesn't derive Debug, so we implement Debug manually below
     request_builder: RequestBuilder,
+    /// Requests posted whose responses haven't arrived.
+    in_flight: InFlight,
+    /// ID of the latest SSE event received on the session.
+    last_event_id: Mutex<Option<String>>,
+    notifications: tokio::sync::broadcast::Sender<Notification>,
 }
 
 impl std::fmt::Debug for HttpSseTransport {
@@ -58,7 +102,8 @@ impl HttpSseTransport {
                     server: server_name.to_string(),
                     source: format!(
                         "Cleartext HTTP is not allowed for '{base_url}'. \
-                         Use https:// or pass --allow-http to permit insecure connections."
+                         Use https://, set \"allowHttp\": true for the server, \
+                         or pass --allow-http to permit insecure connections."
                     )
                     .into(),
                 });
@@ -79,7 +124,7 @@ impl HttpSseTransport {
         );
         header_map.insert(
             reqwest::header::ACCEPT,
-            HeaderValue::from_static("application/json"),
+            HeaderValue::from_static("application/json, text/event-stream"),
         );
 
         for (key, value) in headers {
@@ -110,18 +155,159 @@ impl HttpSseTransport {
             base_url: base_url.to_string(),
             server_name: server_name.to_string(),
             session_id: Mutex::new(None),
+            session_file: None,
+            oauth: None,
             request_builder: RequestBuilder::new(),
+            in_flight: InFlight::default(),
+            last_event_id: Mutex::new(None),
+            notifications: tokio::sync::broadcast::channel(NOTIFICATION_BUFFER).0,
         })
     }
 
-    /// Send a JSON-RPC request and return the parsed response.
-    async fn send_request(
+    /// Send the server's cached `mcplug auth` token with every request,
+    /// refreshing it once it expires. Without a usable cached token,
+    /// requests go out without one.
+    pub fn with_oauth(mut self, client: OAuthClient) -> Self {
+        self.oauth = Some(client);
+        self
+    }
+
+    /// Attach the bearer token from the OAuth cache, if enabled and available.
+    async fn authorize(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let Some(ref client) = self.oauth else {
+            return req;
+        };
+        match get_valid_token(&client.token_id(&self.server_name), &self.base_url, client).await {
+            Ok(token) => req.bearer_auth(token.access_token),
+            Err(_) => req,
+        }
+    }
+
+    /// Persist the `Mcp-Session-Id` to `path` and try to resume it on the next
+    /// `initialize` instead of starting a new session.
+    pub fn with_session_file(mut self, path: PathBuf) -> Self {
+        self.session_file = Some(path);
+        self
+    }
+
+    fn current_session_id(&self) -> Option<String> {
+        self.session_id.lock().ok().and_then(|guard| guard.clone())
+    }
+
+    fn set_session_id(&self, sid: Option<String>) {
+        if let Ok(mut guard) = self.session_id.lock() {
+            *guard = sid;
+        }
+    }
+
+    /// Try to resume a persisted session by pinging the server with its ID.
+    ///
+    /// Returns the server info saved with the session on success. A stale or
+    /// rejected session is discarded so the caller can re-initialize.
+    async fn try_resume(&self) -> Option<ServerInfo> {
+        let path = self.session_file.as_ref()?;
+        let state = load_session(path)?;
+        if state.base_url != self.base_url {
+            tracing::debug!(
+                server = %self.server_name,
+                saved_url = %state.base_url,
+                "persisted session is for another URL, re-initializing"
+            );
+            clear_session(path);
+            return None;
+        }
+        self.set_session_id(Some(state.session_id.clone()));
+        self.set_last_event_id(state.last_event_id.clone());
+
+        match self.send_request("ping", None).await {
+            Ok(_) => {
+                tracing::debug!(
+                    server = %self.server_name,
+                    session_id = %state.session_id,
+                    "resumed persisted session"
+                );
+                Some(state.server_info)
+            }
+            Err(e) => {
+                tracing::debug!(
+                    server = %self.server_name,
+                    error = %e,
+                    "persisted session rejected, re-initializing"
+                );
+                self.set_session_id(None);
+                clear_session(path);
+                None
+            }
+        }
+    }
+
+    /// Save the current session so a later process can resume it.
+    fn persist_session(&self, server_info: &ServerInfo) {
+        let (Some(path), Some(session_id)) = (&self.session_file, self.current_session_id())
+        else {
+            return;
+        };
+        let state = SessionState {
+            session_id,
+            base_url: self.base_url.clone(),
+            last_event_id: self.current_last_event_id(),
+            server_info: server_info.clone(),
+            saved_at: chrono::Utc::now(),
+        };
+        if let Err(e) = save_session(path, &state) {
+            tracing::warn!(server = %self.server_name, error = %e, "failed to persist session");
+        }
+    }
+
+    fn current_last_event_id(&self) -> Option<String> {
+        self.last_event_id.lock().ok().and_then(|guard| guard.clone())
+    }
+
+    fn set_last_event_id(&self, id: Option<String>) {
+        if let Ok(mut guard) = self.last_event_id.lock() {
+            *guard = id;
+        }
+    }
+
+    /// Save the latest SSE event ID with the persisted session, if it is
+    /// still the current one.
+    fn persist_last_event_id(&self) {
+        let Some(path) = &self.session_file else {
+            return;
+        };
+        let Some(mut state) = load_session(path) else {
+            return;
+        };
+        let last_event_id = self.current_last_event_id();
+        if Some(&state.session_id) != self.current_session_id().as_ref()
+            || state.last_event_id == last_event_id
+        {
+            return;
+        }
+        state.last_event_id = last_event_id;
+        if let Err(e) = save_session(path, &state) {
+            tracing::warn!(server = %self.server_name, error = %e, "failed to persist session");
+        }
+    }
+
+    /// Send a JSON-RPC request and return the parsed response envelope.
+    ///
+    /// A non-2xx response whose body is itself a JSON-RPC error is returned as
+    /// that error response, since some servers signal RPC errors via HTTP 400.
+    async fn send_raw(
         &self,
         method: &str,
         params: Option<serde_json::Value>,
-    ) -> Result<serde_json::Value, McplugError> {
+    ) -> Result<JsonRpcResponse, McplugError> {
         let req = self.request_builder.next_request(method, params);
+        self.in_flight.start(req.id, method);
+        let resp = self.post_request(&req).await;
+        self.in_flight.finish(req.id);
+        resp
+    }
 
+    /// POST request `req` and read its response.
+    async fn post_request(&self, req: &JsonRpcRequest) -> Result<JsonRpcResponse, McplugError> {
         let mut http_req = self.client.post(&self.base_url);
 
         // Attach session ID if we have one
@@ -131,7 +317,7 @@ impl HttpSseTransport {
             }
         }
 
-        let response = http_req.json(&req).send().await.map_err(|e| {
+        let response = self.authorize(http_req).await.json(req).send().await.map_err(|e| {
             McplugError::ConnectionFailed {
                 server: self.server_name.clone(),
                 source: Box::new(e),
@@ -140,8 +326,16 @@ impl HttpSseTransport {
 
         // Check HTTP status
         let status = response.status();
+        if let Some(err) = self.auth_challenge(&response) {
+            return Err(err);
+        }
         if !status.is_success() {
             let body = response.text().await.unwrap_or_default();
+            if let Ok(rpc_response) = serde_json::from_str::<JsonRpcResponse>(&body) {
+                if rpc_response.error.is_some() {
+                    return Ok(rpc_response);
+                }
+            }
             return Err(McplugError::ConnectionFailed {
                 server: self.server_name.clone(),
                 source: format!("HTTP {status}: {body}").into(),
@@ -157,15 +351,181 @@ impl HttpSseTransport {
             }
         }
 
-        let rpc_response: JsonRpcResponse =
-            response.json().await.map_err(|e| {
-                McplugError::ProtocolError(format!(
-                    "Failed to parse JSON-RPC response from {}: {e}",
-                    self.server_name
-                ))
-            })?;
+        if is_event_stream(&response) {
+            return self.read_event_stream(response, req.id).await;
+        }
+        response.json().await.map_err(|e| {
+            McplugError::ProtocolError(format!(
+                "Failed to parse JSON-RPC response from {}: {e}",
+                self.server_name
+            ))
+        })
+    }
+
+    /// Read SSE `response` until the response to request `id` arrives,
+    /// resuming the stream from the last event ID if it drops first.
+    async fn read_event_stream(
+        &self,
+        response: reqwest::Response,
+        id: u64,
+    ) -> Result<JsonRpcResponse, McplugError> {
+        let mut parser = SseParser::new();
+        let mut stream: Result<reqwest::Response, McplugError> = Ok(response);
+        let mut attempts = 0;
+        loop {
+            let dropped = match stream {
+                Ok(mut response) => match self.read_events(&mut response, &mut parser, id).await {
+                    Ok(Some(resp)) => {
+                        self.persist_last_event_id();
+                        return Ok(resp);
+                    }
+                    Ok(None) => "the stream ended".to_string(),
+                    Err(e) => e.to_string(),
+                },
+                Err(e) => e.to_string(),
+            };
+            self.persist_last_event_id();
+            let Some(last_event_id) = parser.last_event_id().map(str::to_string) else {
+                return Err(McplugError::ConnectionFailed {
+                    server: self.server_name.clone(),
+                    source: format!(
+                        "SSE stream closed before the response to request {id} \
+                         and sent no event ID to resume from: {dropped}"
+                    )
+                    .into(),
+                });
+            };
+            if attempts == MAX_RESUME_ATTEMPTS {
+                return Err(McplugError::ConnectionFailed {
+                    server: self.server_name.clone(),
+                    source: format!(
+                        "SSE stream closed before the response to request {id} \
+                         and could not be resumed after {attempts} attempts: {dropped}"
+                    )
+                    .into(),
+                });
+            }
+            attempts += 1;
+            tracing::debug!(
+                server = %self.server_name,
+                id,
+                last_event_id = %last_event_id,
+                attempt = attempts,
+                reason = %dropped,
+                "resuming SSE stream"
+            );
+            parser.discard_partial();
+            tokio::time::sleep(parser.retry().unwrap_or(DEFAULT_RESUME_DELAY)).await;
+            stream = self.resume_stream(&last_event_id).await;
+        }
+    }
+
+    /// Read events from `response` until the response to request `id`
+    /// arrives (`Some`) or the stream ends (`None`). Notifications go to
+    /// subscribers.
+    async fn read_events(
+        &self,
+        response: &mut reqwest::Response,
+        parser: &mut SseParser,
+        id: u64,
+    ) -> Result<Option<JsonRpcResponse>, McplugError> {
+        while let Some(chunk) = response.chunk().await.map_err(|e| McplugError::ConnectionFailed {
+            server: self.server_name.clone(),
+            source: Box::new(e),
+        })? {
+            let events = parser.feed(&chunk);
+            if let Some(last_event_id) = parser.last_event_id() {
+                self.set_last_event_id(Some(last_event_id.to_string()));
+            }
+            for event in events {
+                let message: serde_json::Value = serde_json::from_str(&event.data).map_err(|e| {
+                    McplugError::ProtocolError(format!(
+                        "Failed to parse SSE message from {}: {e}",
+                        self.server_name
+                    ))
+                })?;
+                if message.get("method").is_some() {
+                    // Requests from the server (with an id) aren't supported
+                    if message.get("id").is_some() {
+                        tracing::debug!(server = %self.server_name, "skipping server request");
+                    } else if let Ok(notification) = serde_json::from_value::<Notification>(message) {
+                        tracing::debug!(server = %self.server_name, method = %notification.method, "received notification");
+                        let _ = self.notifications.send(notification);
+                    }
+                    continue;
+                }
+                let resp: JsonRpcResponse = serde_json::from_value(message).map_err(|e| {
+                    McplugError::ProtocolError(format!(
+                        "Failed to parse JSON-RPC response from {}: {e}",
+                        self.server_name
+                    ))
+                })?;
+                if resp.id == Some(id) {
+                    return Ok(Some(resp));
+                }
+                tracing::warn!(
+                    server = %self.server_name,
+                    expected_id = id,
+                    got_id = ?resp.id,
+                    "received response with unexpected id, skipping"
+                );
+            }
+        }
+        Ok(None)
+    }
 
-        // Check for JSON-RPC error
+    /// Reopen a dropped stream with a GET carrying `Last-Event-ID`, so the
+    /// server replays the events sent after it.
+    async fn resume_stream(&self, last_event_id: &str) -> Result<reqwest::Response, McplugError> {
+        let mut http_req = self
+            .client
+            .get(&self.base_url)
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .header("Last-Event-ID", last_event_id);
+        if let Some(sid) = self.current_session_id() {
+            http_req = http_req.header("Mcp-Session-Id", sid);
+        }
+
+        let response = self.authorize(http_req).await.send().await.map_err(|e| {
+            McplugError::ConnectionFailed {
+                server: self.server_name.clone(),
+                source: Box::new(e),
+            }
+        })?;
+        if let Some(err) = self.auth_challenge(&response) {
+            return Err(err);
+        }
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(McplugError::ConnectionFailed {
+                server: self.server_name.clone(),
+                source: format!("HTTP {status} resuming stream: {body}").into(),
+            });
+        }
+        if !is_event_stream(&response) {
+            return Err(McplugError::ProtocolError(format!(
+                "{} did not answer the stream resumption with text/event-stream",
+                self.server_name
+            )));
+        }
+        Ok(response)
+    }
+
+    /// A 401 or 403 carrying a `WWW-Authenticate` challenge means the server
+    /// wants credentials, which `mcplug auth` can obtain.
+    fn auth_challenge(&self, response: &reqwest::Response) -> Option<McplugError> {
+        let status = response.status();
+        let challenged = (status == reqwest::StatusCode::UNAUTHORIZED
+            || status == reqwest::StatusCode::FORBIDDEN)
+            && response
+                .headers()
+                .contains_key(reqwest::header::WWW_AUTHENTICATE);
+        challenged.then(|| McplugError::AuthRequired(self.server_name.clone()))
+    }
+
+    /// Check a JSON-RPC response for errors, returning the result value on success.
+    fn check_response(rpc_response: JsonRpcResponse) -> Result<serde_json::Value, McplugError> {
         if let Some(err) = rpc_response.error {
             return Err(McplugError::ProtocolError(format!(
                 "JSON-RPC error {}: {}{}",
@@ -184,6 +544,16 @@ impl HttpSseTransport {
         })
     }
 
+    /// Send a JSON-RPC request and return the result value.
+    async fn send_request(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, McplugError> {
+        let rpc_response = self.send_raw(method, params).await?;
+        Self::check_response(rpc_response)
+    }
+
     /// Send a JSON-RPC notification (no response expected).
     async fn send_notification(
         &self,
@@ -204,7 +574,7 @@ impl HttpSseTransport {
             }
         }
 
-        let response = http_req.json(&notif).send().await.map_err(|e| {
+        let response = self.authorize(http_req).await.json(&notif).send().await.map_err(|e| {
             McplugError::ConnectionFailed {
                 server: self.server_name.clone(),
                 source: Box::new(e),
@@ -212,6 +582,9 @@ impl HttpSseTransport {
         })?;
 
         let status = response.status();
+        if let Some(err) = self.auth_challenge(&response) {
+            return Err(err);
+        }
         if !status.is_success() {
             let body = response.text().await.unwrap_or_default();
             return Err(McplugError::ConnectionFailed {
@@ -227,19 +600,34 @@ impl HttpSseTransport {
 #[async_trait]
 impl McpTransport for HttpSseTransport {
     async fn initialize(&mut self) -> Result<ServerInfo, McplugError> {
-        let result = self
-            .send_request(
-                "initialize",
-                Some(json!({
-                    "protocolVersion": "2025-03-26",
-                    "capabilities": {},
-                    "clientInfo": {
-                        "name": "mcplug",
-                        "version": env!("CARGO_PKG_VERSION"),
-                    }
-                })),
-            )
-            .await?;
+        if let Some(info) = self.try_resume().await {
+            return Ok(info);
+        }
+
+        // Offer the newest protocol version, falling back to older ones if rejected
+        let mut offered = PROTOCOL_VERSIONS[0];
+        let result = loop {
+            let resp = self
+                .send_raw("initialize", Some(initialize_params(offered)))
+                .await?;
+            let next = resp
+                .error
+                .as_ref()
+                .filter(|err| is_protocol_version_error(err))
+                .and_then(|_| next_protocol_version(offered));
+            match next {
+                Some(older) => {
+                    tracing::info!(
+                        server = %self.server_name,
+                        rejected = offered,
+                        retry = older,
+                        "server rejected protocol version, retrying initialize"
+                    );
+                    offered = older;
+                }
+                None => break Self::check_response(resp)?,
+            }
+        };
 
         // Send initialized notification
         self.send_notification("notifications/initialized", None)
@@ -266,12 +654,25 @@ impl McpTransport for HttpSseTransport {
             .get("capabilities")
             .cloned()
             .unwrap_or(json!({}));
+        let protocol_version = result
+            .get("protocolVersion")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+        tracing::debug!(
+            server = %self.server_name,
+            offered,
+            negotiated = ?protocol_version,
+            "protocol version negotiated"
+        );
 
-        Ok(ServerInfo {
+        let info = ServerInfo {
             name,
             version,
             capabilities,
-        })
+            protocol_version,
+        };
+        self.persist_session(&info);
+        Ok(info)
     }
 
     async fn list_tools(&self) -> Result<Vec<ToolDefinition>, McplugError> {
@@ -313,6 +714,35 @@ impl McpTransport for HttpSseTransport {
         Ok(call_result)
     }
 
+    async fn request(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, McplugError> {
+        self.send_request(method, params).await
+    }
+
+    async fn notify(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<(), McplugError> {
+        self.send_notification(method, params).await
+    }
+
+    fn subscribe(&self) -> Option<tokio::sync::broadcast::Receiver<Notification>> {
+        Some(self.notifications.subscribe())
+    }
+
+    async fn cancel_pending(&self, reason: &str) -> Result<(), McplugError> {
+        for id in self.in_flight.take() {
+            tracing::debug!(server = %self.server_name, id, "cancelling request");
+            self.send_notification("notifications/cancelled", Some(cancelled_params(id, reason)))
+                .await?;
+        }
+        Ok(())
+    }
+
     async fn close(&mut self) -> Result<(), McplugError> {
         // Best-effort: send a close notification but don't fail if it errors
         let _ = self.send_notification("notifications/cancelled", None).await;
@@ -320,10 +750,28 @@ impl McpTransport for HttpSseTransport {
     }
 }
 
+/// Whether the response body is an SSE stream rather than JSON.
+fn is_event_stream(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn env_allow_http_values() {
+        assert!(env_allows_http(Some("1")));
+        assert!(env_allows_http(Some(" TRUE ")));
+        assert!(!env_allows_http(Some("0")));
+        assert!(!env_allows_http(None));
+        assert!(allow_http(true));
+    }
+
     #[test]
     fn rejects_http_without_allow_flag() {
         let result = HttpSseTransport::new(
@@ -476,4 +924,131 @@ mod tests {
         let guard = transport.session_id.lock().unwrap();
         assert!(guard.is_none());
     }
+
+    /// An SSE response of `lines`, after which the connection closes.
+    fn sse(lines: &[&str]) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n{}\n\n",
+            lines.join("\n")
+        )
+    }
+
+    /// Answer one connection with `response`, returning the request's
+    /// lowercased head and its JSON body (null if it has none).
+    async fn serve_once(
+        listener: &tokio::net::TcpListener,
+        response: &str,
+    ) -> (String, serde_json::Value) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        let head_end = loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+        let head = String::from_utf8_lossy(&request[..head_end]).to_lowercase();
+        let length: usize = head
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length: "))
+            .map_or(0, |v| v.trim().parse().unwrap());
+        while request.len() < head_end + length {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+        let body = serde_json::from_slice(&request[head_end..]).unwrap_or_default();
+        stream.write_all(response.as_bytes()).await.unwrap();
+        stream.shutdown().await.unwrap();
+        (head, body)
+    }
+
+    #[tokio::test]
+    async fn resumes_dropped_stream_from_last_event_id() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            // The stream drops after a progress notification
+            let progress = r#"data: {"jsonrpc":"2.0","method":"notifications/progress","params":{"progress":1}}"#;
+            let (_, body) = serve_once(&listener, &sse(&["id: e1", "retry: 10", progress])).await;
+            let result = format!(r#"data: {{"jsonrpc":"2.0","id":{},"result":{{"ok":true}}}}"#, body["id"]);
+            serve_once(&listener, &sse(&["id: e2", &result])).await.0
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let session_file = dir.path().join("session.json");
+        let state = SessionState {
+            session_id: "sess-1".into(),
+            base_url: url.clone(),
+            last_event_id: None,
+            server_info: ServerInfo {
+                name: "srv".into(),
+                version: "1.0".into(),
+                capabilities: json!({}),
+                protocol_version: None,
+            },
+            saved_at: chrono::Utc::now(),
+        };
+        save_session(&session_file, &state).unwrap();
+        let transport = HttpSseTransport::new(&url, &HashMap::new(), "srv", true)
+            .unwrap()
+            .with_session_file(session_file.clone());
+        transport.set_session_id(Some("sess-1".into()));
+        let mut notifications = transport.subscribe().unwrap();
+
+        let result = transport.request("tools/call", Some(json!({"name": "slow"}))).await.unwrap();
+        assert_eq!(result, json!({"ok": true}));
+        assert_eq!(notifications.try_recv().unwrap().method, "notifications/progress");
+
+        let resumed = server.await.unwrap();
+        assert!(resumed.starts_with("get /mcp "), "{resumed}");
+        assert!(resumed.contains("last-event-id: e1\r\n"), "{resumed}");
+        assert!(resumed.contains("mcp-session-id: sess-1\r\n"), "{resumed}");
+        assert_eq!(transport.current_last_event_id().as_deref(), Some("e2"));
+        let saved = load_session(&session_file).unwrap();
+        assert_eq!(saved.last_event_id.as_deref(), Some("e2"));
+    }
+
+    #[tokio::test]
+    async fn session_saved_for_another_url_is_discarded() {
+        let dir = tempfile::tempdir().unwrap();
+        let session_file = dir.path().join("session.json");
+        let state = SessionState {
+            session_id: "sess-1".into(),
+            base_url: "https://old.example.com/mcp".into(),
+            last_event_id: None,
+            server_info: ServerInfo {
+                name: "srv".into(),
+                version: "1.0".into(),
+                capabilities: json!({}),
+                protocol_version: None,
+            },
+            saved_at: chrono::Utc::now(),
+        };
+        save_session(&session_file, &state).unwrap();
+        let transport =
+            HttpSseTransport::new("https://new.example.com/mcp", &HashMap::new(), "srv", false)
+                .unwrap()
+                .with_session_file(session_file.clone());
+
+        assert!(transport.try_resume().await.is_none());
+        assert!(transport.current_session_id().is_none());
+        assert!(!session_file.exists());
+    }
+
+    #[tokio::test]
+    async fn stream_without_event_ids_is_not_resumed() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            serve_once(&listener, &sse(&[": nothing yet"])).await;
+        });
+
+        let transport = HttpSseTransport::new(&url, &HashMap::new(), "srv", true).unwrap();
+        let err = transport.request("ping", None).await.unwrap_err().to_string();
+        assert!(err.contains("no event ID to resume from"), "{err}");
+        server.await.unwrap();
+    }
 }
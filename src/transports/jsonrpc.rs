@@ -35,6 +35,76 @@ pub struct JsonRpcError {
     pub data: Option<serde_json::Value>,
 }
 
+/// MCP protocol versions this client can speak, newest first.
+///
+/// `initialize` offers the first entry and falls back down the list when a
+/// server rejects the offered version.
+pub const PROTOCOL_VERSIONS: &[&str] = &["2025-03-26", "2024-11-05"];
+
+/// Build `initialize` request params offering the given protocol version.
+pub fn initialize_params(protocol_version: &str) -> serde_json::Value {
+    serde_json::json!({
+        "protocolVersion": protocol_version,
+        "capabilities": {},
+        "clientInfo": {
+            "name": "mcplug",
+            "version": env!("CARGO_PKG_VERSION"),
+        }
+    })
+}
+
+/// Whether an `initialize` error looks like a protocol version rejection.
+///
+/// Servers report this as "Invalid params" (-32602) or with a message that
+/// mentions the protocol version.
+pub fn is_protocol_version_error(err: &JsonRpcError) -> bool {
+    let message = err.message.to_lowercase();
+    err.code == -32602 || (message.contains("protocol") && message.contains("version"))
+}
+
+/// Return the protocol version to retry with after `rejected`, if any.
+pub fn next_protocol_version(rejected: &str) -> Option<&'static str> {
+    let pos = PROTOCOL_VERSIONS.iter().position(|v| *v == rejected)?;
+    PROTOCOL_VERSIONS.get(pos + 1).copied()
+}
+
+/// Build `notifications/cancelled` params for request `id`.
+pub fn cancelled_params(id: u64, reason: &str) -> serde_json::Value {
+    serde_json::json!({"requestId": id, "reason": reason})
+}
+
+/// Ids of requests that were sent but whose caller has not yet seen an
+/// answer, so a transport can cancel them when the caller gives up.
+///
+/// A request whose future is dropped mid-flight never reaches `finish`, and
+/// stays here until `take` hands it to `notifications/cancelled`.
+/// `initialize` must never be cancelled, so it is not tracked.
+#[derive(Debug, Default)]
+pub struct InFlight(std::sync::Mutex<Vec<u64>>);
+
+impl InFlight {
+    /// Record request `id` to `method` as sent.
+    pub fn start(&self, id: u64, method: &str) {
+        if method != "initialize" {
+            self.lock().push(id);
+        }
+    }
+
+    /// Record request `id` as answered (or failed).
+    pub fn finish(&self, id: u64) {
+        self.lock().retain(|pending| *pending != id);
+    }
+
+    /// Remove and return every unanswered request id, oldest first.
+    pub fn take(&self) -> Vec<u64> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<u64>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
 /// Helper that generates JSON-RPC requests with auto-incrementing IDs.
 pub struct RequestBuilder {
     next_id: AtomicU64,
@@ -164,10 +234,67 @@ mod tests {
         assert!(resp.error.is_none());
     }
 
+    #[test]
+    fn protocol_versions_newest_first() {
+        let mut sorted = PROTOCOL_VERSIONS.to_vec();
+        sorted.sort_by(|a, b| b.cmp(a));
+        assert_eq!(sorted, PROTOCOL_VERSIONS);
+    }
+
+    #[test]
+    fn next_protocol_version_walks_list() {
+        assert_eq!(next_protocol_version("2025-03-26"), Some("2024-11-05"));
+        assert_eq!(next_protocol_version("2024-11-05"), None);
+        assert_eq!(next_protocol_version("1999-01-01"), None);
+    }
+
+    #[test]
+    fn initialize_params_offers_version() {
+        let params = initialize_params("2024-11-05");
+        assert_eq!(params["protocolVersion"], "2024-11-05");
+        assert_eq!(params["clientInfo"]["name"], "mcplug");
+    }
+
+    #[test]
+    fn protocol_version_error_detection() {
+        let invalid_params = JsonRpcError {
+            code: -32602,
+            message: "Invalid params".into(),
+            data: None,
+        };
+        assert!(is_protocol_version_error(&invalid_params));
+
+        let by_message = JsonRpcError {
+            code: -32000,
+            message: "Unsupported protocol version: 2025-03-26".into(),
+            data: None,
+        };
+        assert!(is_protocol_version_error(&by_message));
+
+        let unrelated = JsonRpcError {
+            code: -32603,
+            message: "Internal error".into(),
+            data: None,
+        };
+        assert!(!is_protocol_version_error(&unrelated));
+    }
+
     #[test]
     fn request_builder_starts_at_one() {
         let builder = RequestBuilder::new();
         let first = builder.next_request("test", None);
         assert_eq!(first.id, 1);
     }
+
+    #[test]
+    fn in_flight_tracks_unanswered_requests() {
+        let in_flight = InFlight::default();
+        in_flight.start(1, "initialize");
+        in_flight.start(2, "tools/call");
+        in_flight.start(3, "tools/list");
+        in_flight.finish(3);
+        assert_eq!(in_flight.take(), vec![2]);
+        assert!(in_flight.take().is_empty());
+        assert_eq!(cancelled_params(2, "stop")["requestId"], 2);
+    }
 }
@@ -0,0 +1,161 @@
+use async_trait::async_trait;
+use tracing::warn;
+
+use crate::error::McplugError;
+use crate::transport::McpTransport;
+use crate::types::{CallResult, LogLevel, Notification, ServerInfo, ToolDefinition};
+
+/// Send `logging/setLevel` if the server advertises the logging capability.
+///
+/// Returns whether the level was sent.
+pub async fn set_log_level(
+    transport: &dyn McpTransport,
+    info: &ServerInfo,
+    level: LogLevel,
+) -> Result<bool, McplugError> {
+    if !info.supports_logging() {
+        return Ok(false);
+    }
+    transport
+        .request(
+            "logging/setLevel",
+            Some(serde_json::json!({ "level": level })),
+        )
+        .await?;
+    Ok(true)
+}
+
+/// Wraps a transport and sets the server's log level right after
+/// initialization, for servers configured with `logLevel`.
+///
+/// Servers without the logging capability are left alone, and a failed
+/// `logging/setLevel` only logs a warning: the level is a preference, not a
+/// reason to fail the connection.
+pub struct LogLevelTransport {
+    inner: Box<dyn McpTransport>,
+    server_name: String,
+    level: LogLevel,
+}
+
+impl LogLevelTransport {
+    pub fn new(inner: Box<dyn McpTransport>, server_name: &str, level: LogLevel) -> Self {
+        Self {
+            inner,
+            server_name: server_name.to_string(),
+            level,
+        }
+    }
+}
+
+#[async_trait]
+impl McpTransport for LogLevelTransport {
+    async fn wait_ready(&mut self) -> Result<(), McplugError> {
+        self.inner.wait_ready().await
+    }
+
+    async fn initialize(&mut self) -> Result<ServerInfo, McplugError> {
+        let info = self.inner.initialize().await?;
+        if let Err(e) = set_log_level(self.inner.as_ref(), &info, self.level).await {
+            warn!(server = %self.server_name, level = %self.level, error = %e, "failed to set log level");
+        }
+        Ok(info)
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>, McplugError> {
+        self.inner.list_tools().await
+    }
+
+    async fn call_tool(
+        &self,
+        name: &str,
+        args: serde_json::Value,
+    ) -> Result<CallResult, McplugError> {
+        self.inner.call_tool(name, args).await
+    }
+
+    async fn request(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, McplugError> {
+        self.inner.request(method, params).await
+    }
+
+    async fn notify(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<(), McplugError> {
+        self.inner.notify(method, params).await
+    }
+
+    fn stderr_lines(&self) -> Vec<String> {
+        self.inner.stderr_lines()
+    }
+
+    fn subscribe(&self) -> Option<tokio::sync::broadcast::Receiver<Notification>> {
+        self.inner.subscribe()
+    }
+
+    async fn cancel_pending(&self, reason: &str) -> Result<(), McplugError> {
+        self.inner.cancel_pending(reason).await
+    }
+
+    async fn close(&mut self) -> Result<(), McplugError> {
+        self.inner.close().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transports::{Exchange, Recording, ReplayTransport};
+    use serde_json::json;
+
+    fn replay(capabilities: serde_json::Value, set_level_error: bool) -> ReplayTransport {
+        let set_level = Exchange {
+            method: "logging/setLevel".into(),
+            params: Some(json!({"level": "debug"})),
+            result: (!set_level_error).then(|| json!({})),
+            error: set_level_error.then(|| json!({"message": "nope"})),
+        };
+        ReplayTransport::new(Recording {
+            server: "srv".into(),
+            exchanges: vec![
+                Exchange {
+                    method: "initialize".into(),
+                    params: None,
+                    result: Some(json!({
+                        "name": "srv", "version": "1.0", "capabilities": capabilities
+                    })),
+                    error: None,
+                },
+                set_level,
+            ],
+        })
+    }
+
+    #[tokio::test]
+    async fn sets_level_when_supported() {
+        let mut t = replay(json!({"logging": {}}), false);
+        let info = t.initialize().await.unwrap();
+        assert!(set_log_level(&t, &info, LogLevel::Debug).await.unwrap());
+        // Params must match the recording exactly
+        let err = set_log_level(&t, &info, LogLevel::Info).await.unwrap_err();
+        assert!(err.to_string().contains("No recorded response"));
+    }
+
+    #[tokio::test]
+    async fn skips_servers_without_logging() {
+        let mut t = replay(json!({"tools": {}}), false);
+        let info = t.initialize().await.unwrap();
+        assert!(!set_log_level(&t, &info, LogLevel::Debug).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn wrapper_ignores_set_level_failure() {
+        let inner = Box::new(replay(json!({"logging": {}}), true));
+        let mut t = LogLevelTransport::new(inner, "srv", LogLevel::Debug);
+        assert_eq!(t.initialize().await.unwrap().name, "srv");
+    }
+}
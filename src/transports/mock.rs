@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::error::McplugError;
+use crate::transport::McpTransport;
+use crate::types::{CallResult, ContentBlock, ServerInfo, ToolDefinition};
+
+type Handler = Box<dyn Fn(&serde_json::Value) -> Result<CallResult, McplugError> + Send + Sync>;
+
+/// A scriptable in-memory server for testing code built on mcplug, without
+/// the fixtures binary or a network.
+///
+/// Tools are answered by the responses configured for them; calling any
+/// other tool fails with `ToolNotFound`. Pair it with
+/// `Runtime::with_transports` to test code that goes through a Runtime.
+///
+/// ```
+/// # async fn example() -> Result<(), mcplug::McplugError> {
+/// use mcplug::{McpTransport, MockTransport, Runtime};
+///
+/// let mock = MockTransport::new("github")
+///     .respond_text("search", "3 results")
+///     .fail("delete", "permission denied");
+/// let calls = mock.calls();
+///
+/// let transport: Box<dyn McpTransport> = Box::new(mock);
+/// let runtime = Runtime::with_transports([("github".to_string(), transport)]);
+/// let result = runtime.call_tool("github", "search", serde_json::json!({"q": "mcp"})).await?;
+/// assert_eq!(result.text(), "3 results");
+/// assert_eq!(calls.count("search"), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct MockTransport {
+    info: ServerInfo,
+    tools: Vec<ToolDefinition>,
+    handlers: HashMap<String, Handler>,
+    latency: Duration,
+    calls: MockCalls,
+}
+
+impl MockTransport {
+    /// A server called `name` with no tools.
+    pub fn new(name: &str) -> Self {
+        Self {
+            info: ServerInfo {
+                name: name.to_string(),
+                version: "0.0.0".to_string(),
+                capabilities: serde_json::json!({"tools": {}}),
+                protocol_version: None,
+            },
+            tools: Vec::new(),
+            handlers: HashMap::new(),
+            latency: Duration::ZERO,
+            calls: MockCalls::default(),
+        }
+    }
+
+    /// Report `version` and `capabilities` from `initialize`.
+    pub fn server_info(mut self, version: &str, capabilities: serde_json::Value) -> Self {
+        self.info.version = version.to_string();
+        self.info.capabilities = capabilities;
+        self
+    }
+
+    /// List `tool`, replacing a listed tool of the same name. Tools given a
+    /// response are listed without this, with an empty object schema.
+    pub fn tool(mut self, tool: ToolDefinition) -> Self {
+        self.tools.retain(|t| t.name != tool.name);
+        self.tools.push(tool);
+        self
+    }
+
+    /// Answer calls to `tool` with `handler`, given the call's arguments.
+    pub fn respond_with(
+        mut self,
+        tool: &str,
+        handler: impl Fn(&serde_json::Value) -> Result<CallResult, McplugError> + Send + Sync + 'static,
+    ) -> Self {
+        if !self.tools.iter().any(|t| t.name == tool) {
+            self.tools.push(ToolDefinition {
+                name: tool.to_string(),
+                description: String::new(),
+                input_schema: serde_json::json!({"type": "object"}),
+                annotations: None,
+                output_schema: None,
+            });
+        }
+        self.handlers.insert(tool.to_string(), Box::new(handler));
+        self
+    }
+
+    /// Answer calls to `tool` with a text result.
+    pub fn respond_text(self, tool: &str, text: &str) -> Self {
+        let text = text.to_string();
+        self.respond_with(tool, move |_| Ok(text_result(&text, false)))
+    }
+
+    /// Answer calls to `tool` with `value` as its `structuredContent`, and
+    /// as JSON text for clients reading the text content.
+    pub fn respond_json(self, tool: &str, value: serde_json::Value) -> Self {
+        self.respond_with(tool, move |_| {
+            let mut result = text_result(&value.to_string(), false);
+            result.raw_response = Some(serde_json::json!({
+                "content": result.content,
+                "structuredContent": value,
+            }));
+            Ok(result)
+        })
+    }
+
+    /// Answer calls to `tool` with a result that has `isError` set, as a
+    /// server reports a failed tool.
+    pub fn respond_error(self, tool: &str, message: &str) -> Self {
+        let message = message.to_string();
+        self.respond_with(tool, move |_| Ok(text_result(&message, true)))
+    }
+
+    /// Fail calls to `tool` with a protocol error, as if the request itself
+    /// had failed.
+    pub fn fail(self, tool: &str, message: &str) -> Self {
+        let message = message.to_string();
+        self.respond_with(tool, move |_| Err(McplugError::ProtocolError(message.clone())))
+    }
+
+    /// Wait `latency` before answering each request.
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// A handle on the calls this transport receives, usable after the
+    /// transport has been handed to a Runtime.
+    pub fn calls(&self) -> MockCalls {
+        self.calls.clone()
+    }
+
+    async fn delay(&self) {
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+    }
+}
+
+fn text_result(text: &str, is_error: bool) -> CallResult {
+    CallResult {
+        content: vec![ContentBlock::Text {
+            text: text.to_string(),
+        }],
+        is_error,
+        raw_response: None,
+    }
+}
+
+/// The tool calls a `MockTransport` received, in order.
+#[derive(Clone, Default)]
+pub struct MockCalls(Arc<Mutex<Vec<(String, serde_json::Value)>>>);
+
+impl MockCalls {
+    /// Every call as `(tool, arguments)`, including calls to unknown tools.
+    pub fn all(&self) -> Vec<(String, serde_json::Value)> {
+        self.lock().clone()
+    }
+
+    /// How many times `tool` was called.
+    pub fn count(&self, tool: &str) -> usize {
+        self.lock().iter().filter(|(name, _)| name == tool).count()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(String, serde_json::Value)>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl McpTransport for MockTransport {
+    async fn initialize(&mut self) -> Result<ServerInfo, McplugError> {
+        self.delay().await;
+        Ok(self.info.clone())
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>, McplugError> {
+        self.delay().await;
+        Ok(self.tools.clone())
+    }
+
+    async fn call_tool(
+        &self,
+        name: &str,
+        args: serde_json::Value,
+    ) -> Result<CallResult, McplugError> {
+        self.calls.lock().push((name.to_string(), args.clone()));
+        self.delay().await;
+        match self.handlers.get(name) {
+            Some(handler) => handler(&args),
+            None => Err(McplugError::ToolNotFound {
+                server: self.info.name.clone(),
+                tool: name.to_string(),
+                suggestion: None,
+            }),
+        }
+    }
+
+    async fn request(
+        &self,
+        method: &str,
+        _params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, McplugError> {
+        self.delay().await;
+        match method {
+            "ping" | "logging/setLevel" => Ok(serde_json::json!({})),
+            _ => Err(McplugError::ProtocolError(format!(
+                "MockTransport does not handle {method}"
+            ))),
+        }
+    }
+
+    async fn close(&mut self) -> Result<(), McplugError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn answers_configured_tools() {
+        let mut t = MockTransport::new("srv")
+            .respond_text("echo", "hi")
+            .respond_json("stats", json!({"count": 2}))
+            .respond_with("add", |args| {
+                let sum = args["a"].as_i64().unwrap_or(0) + args["b"].as_i64().unwrap_or(0);
+                Ok(text_result(&sum.to_string(), false))
+            });
+        assert_eq!(t.initialize().await.unwrap().name, "srv");
+        let names: Vec<String> = t.list_tools().await.unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["echo", "stats", "add"]);
+
+        assert_eq!(t.call_tool("echo", json!({})).await.unwrap().text(), "hi");
+        let stats = t.call_tool("stats", json!({})).await.unwrap();
+        assert_eq!(stats.structured_content(), Some(&json!({"count": 2})));
+        assert_eq!(t.call_tool("add", json!({"a": 2, "b": 3})).await.unwrap().text(), "5");
+    }
+
+    #[tokio::test]
+    async fn induces_errors_and_records_calls() {
+        let t = MockTransport::new("srv")
+            .respond_error("lookup", "no such user")
+            .fail("flaky", "connection reset");
+        let calls = t.calls();
+
+        let result = t.call_tool("lookup", json!({"id": 7})).await.unwrap();
+        assert!(result.is_error);
+        let err = t.call_tool("flaky", json!({})).await.unwrap_err();
+        assert_eq!(err.to_string(), "Protocol error: connection reset");
+        let err = t.call_tool("missing", json!({})).await.unwrap_err();
+        assert!(matches!(err, McplugError::ToolNotFound { .. }));
+
+        assert_eq!(calls.count("lookup"), 1);
+        assert_eq!(calls.all()[0], ("lookup".to_string(), json!({"id": 7})));
+        assert_eq!(calls.all().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn latency_delays_answers() {
+        let t = MockTransport::new("srv")
+            .respond_text("slow", "done")
+            .latency(Duration::from_millis(50));
+        let started = std::time::Instant::now();
+        t.call_tool("slow", json!({})).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+}
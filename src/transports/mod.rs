@@ -1,6 +1,16 @@
 pub mod http_sse;
 pub mod jsonrpc;
+pub mod log_level;
+#[cfg(feature = "test-util")]
+pub mod mock;
+pub mod recording;
+pub mod session;
+pub mod sse;
 pub mod stdio;
 
 pub use http_sse::HttpSseTransport;
+pub use log_level::LogLevelTransport;
+#[cfg(feature = "test-util")]
+pub use mock::{MockCalls, MockTransport};
+pub use recording::{Exchange, Recording, RecordingTransport, ReplayTransport};
 pub use stdio::StdioTransport;
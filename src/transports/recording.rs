@@ -0,0 +1,413 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::McplugError;
+use crate::transport::McpTransport;
+use crate::types::{CallResult, Notification, ServerInfo, ToolDefinition};
+
+/// One request and its outcome.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Exchange {
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    /// The error object (`code`, `message`, ...) if the request failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<serde_json::Value>,
+}
+
+/// A recorded session with one server, as written by `--record`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Recording {
+    pub server: String,
+    pub exchanges: Vec<Exchange>,
+}
+
+impl Recording {
+    pub fn load(path: &Path) -> Result<Self, McplugError> {
+        let data = std::fs::read_to_string(path)?;
+        serde_json::from_str(&data).map_err(|e| McplugError::ConfigError {
+            path: path.to_path_buf(),
+            detail: format!("Invalid recording: {e}"),
+        })
+    }
+
+    fn save(&self, path: &Path) -> Result<(), McplugError> {
+        let data = serde_json::to_string_pretty(self).map_err(|e| {
+            McplugError::ProtocolError(format!("Failed to serialize recording: {e}"))
+        })?;
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or(serde_json::Value::Null)
+}
+
+fn call_params(name: &str, args: &serde_json::Value) -> serde_json::Value {
+    serde_json::json!({ "name": name, "arguments": args })
+}
+
+/// Wraps a transport and writes every exchange to a recording file.
+///
+/// The file is rewritten after each exchange so a session that ends in an
+/// error is still captured.
+pub struct RecordingTransport {
+    inner: Box<dyn McpTransport>,
+    /// `None` keeps the recording in memory only.
+    path: Option<PathBuf>,
+    recording: Mutex<Recording>,
+}
+
+impl RecordingTransport {
+    /// Start recording to `path`, replacing any existing file.
+    pub fn new(
+        inner: Box<dyn McpTransport>,
+        server_name: &str,
+        path: &Path,
+    ) -> Result<Self, McplugError> {
+        let recording = Recording {
+            server: server_name.to_string(),
+            exchanges: Vec::new(),
+        };
+        // Fail early on an unwritable path rather than after the call
+        recording.save(path)?;
+        Ok(Self {
+            inner,
+            path: Some(path.to_path_buf()),
+            recording: Mutex::new(recording),
+        })
+    }
+
+    /// Record exchanges without writing them anywhere; read them back with
+    /// [`RecordingTransport::recording`].
+    pub fn in_memory(inner: Box<dyn McpTransport>, server_name: &str) -> Self {
+        Self {
+            inner,
+            path: None,
+            recording: Mutex::new(Recording {
+                server: server_name.to_string(),
+                exchanges: Vec::new(),
+            }),
+        }
+    }
+
+    /// The exchanges recorded so far.
+    pub fn recording(&self) -> Recording {
+        self.recording
+            .lock()
+            .map(|recording| recording.clone())
+            .unwrap_or_default()
+    }
+
+    fn record<T>(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+        outcome: &Result<T, McplugError>,
+        to_result: impl FnOnce(&T) -> serde_json::Value,
+    ) {
+        let (result, error) = match outcome {
+            Ok(value) => (Some(to_result(value)), None),
+            Err(e) => (None, Some(e.to_json()["error"].clone())),
+        };
+        let Ok(mut recording) = self.recording.lock() else {
+            return;
+        };
+        recording.exchanges.push(Exchange {
+            method: method.to_string(),
+            params,
+            result,
+            error,
+        });
+        let Some(ref path) = self.path else {
+            return;
+        };
+        if let Err(e) = recording.save(path) {
+            warn!(path = %path.display(), error = %e, "failed to write recording");
+        }
+    }
+}
+
+#[async_trait]
+impl McpTransport for RecordingTransport {
+    async fn wait_ready(&mut self) -> Result<(), McplugError> {
+        self.inner.wait_ready().await
+    }
+
+    async fn initialize(&mut self) -> Result<ServerInfo, McplugError> {
+        let outcome = self.inner.initialize().await;
+        self.record("initialize", None, &outcome, to_value);
+        outcome
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>, McplugError> {
+        let outcome = self.inner.list_tools().await;
+        self.record("tools/list", None, &outcome, |tools| {
+            serde_json::json!({ "tools": tools })
+        });
+        outcome
+    }
+
+    async fn call_tool(
+        &self,
+        name: &str,
+        args: serde_json::Value,
+    ) -> Result<CallResult, McplugError> {
+        let params = call_params(name, &args);
+        let outcome = self.inner.call_tool(name, args).await;
+        self.record("tools/call", Some(params), &outcome, |result| {
+            result.raw_response.clone().unwrap_or_else(|| to_value(result))
+        });
+        outcome
+    }
+
+    async fn request(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, McplugError> {
+        let outcome = self.inner.request(method, params.clone()).await;
+        self.record(method, params, &outcome, Clone::clone);
+        outcome
+    }
+
+    async fn notify(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<(), McplugError> {
+        self.inner.notify(method, params).await
+    }
+
+    fn stderr_lines(&self) -> Vec<String> {
+        self.inner.stderr_lines()
+    }
+
+    fn subscribe(&self) -> Option<tokio::sync::broadcast::Receiver<Notification>> {
+        self.inner.subscribe()
+    }
+
+    async fn cancel_pending(&self, reason: &str) -> Result<(), McplugError> {
+        self.inner.cancel_pending(reason).await
+    }
+
+    async fn close(&mut self) -> Result<(), McplugError> {
+        self.inner.close().await
+    }
+}
+
+/// Serves responses from a recording instead of talking to a server.
+///
+/// Requests are matched by method and params. Unused exchanges are served
+/// first, in recorded order; once all matches are used the last one is
+/// repeated, so a recording of one call can answer it any number of times.
+pub struct ReplayTransport {
+    recording: Recording,
+    used: Mutex<Vec<bool>>,
+}
+
+impl ReplayTransport {
+    pub fn new(recording: Recording) -> Self {
+        let used = vec![false; recording.exchanges.len()];
+        Self {
+            recording,
+            used: Mutex::new(used),
+        }
+    }
+
+    /// Load a recording written by `--record`.
+    pub fn from_file(path: &Path) -> Result<Self, McplugError> {
+        Ok(Self::new(Recording::load(path)?))
+    }
+
+    fn replay(
+        &self,
+        method: &str,
+        params: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value, McplugError> {
+        let matches: Vec<usize> = self
+            .recording
+            .exchanges
+            .iter()
+            .enumerate()
+            .filter(|(_, ex)| ex.method == method && ex.params.as_ref() == params)
+            .map(|(i, _)| i)
+            .collect();
+
+        let mut used = self
+            .used
+            .lock()
+            .map_err(|_| McplugError::ProtocolError("Replay state poisoned".into()))?;
+        let index = matches
+            .iter()
+            .copied()
+            .find(|&i| !used[i])
+            .or_else(|| matches.last().copied())
+            .ok_or_else(|| {
+                McplugError::ProtocolError(format!(
+                    "No recorded response for {method}{} in recording of '{}'",
+                    params.map(|p| format!(" {p}")).unwrap_or_default(),
+                    self.recording.server
+                ))
+            })?;
+        used[index] = true;
+
+        let exchange = &self.recording.exchanges[index];
+        if let Some(ref error) = exchange.error {
+            let message = error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("recorded error");
+            return Err(McplugError::ProtocolError(message.to_string()));
+        }
+        Ok(exchange.result.clone().unwrap_or(serde_json::Value::Null))
+    }
+}
+
+fn parse_replayed<T: serde::de::DeserializeOwned>(
+    method: &str,
+    value: serde_json::Value,
+) -> Result<T, McplugError> {
+    serde_json::from_value(value).map_err(|e| {
+        McplugError::ProtocolError(format!("Invalid recorded {method} response: {e}"))
+    })
+}
+
+#[async_trait]
+impl McpTransport for ReplayTransport {
+    async fn initialize(&mut self) -> Result<ServerInfo, McplugError> {
+        parse_replayed("initialize", self.replay("initialize", None)?)
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>, McplugError> {
+        let result = self.replay("tools/list", None)?;
+        parse_replayed(
+            "tools/list",
+            result.get("tools").cloned().unwrap_or_default(),
+        )
+    }
+
+    async fn call_tool(
+        &self,
+        name: &str,
+        args: serde_json::Value,
+    ) -> Result<CallResult, McplugError> {
+        let result = self.replay("tools/call", Some(&call_params(name, &args)))?;
+        let call_result: CallResult = parse_replayed("tools/call", result.clone())?;
+        Ok(CallResult {
+            raw_response: Some(result),
+            ..call_result
+        })
+    }
+
+    async fn request(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, McplugError> {
+        self.replay(method, params.as_ref())
+    }
+
+    async fn close(&mut self) -> Result<(), McplugError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn recording() -> Recording {
+        Recording {
+            server: "srv".into(),
+            exchanges: vec![
+                Exchange {
+                    method: "initialize".into(),
+                    params: None,
+                    result: Some(json!({
+                        "name": "srv", "version": "1.0", "capabilities": {}
+                    })),
+                    error: None,
+                },
+                Exchange {
+                    method: "tools/call".into(),
+                    params: Some(call_params("echo", &json!({"input": "a"}))),
+                    result: Some(json!({"content": [{"type": "text", "text": "a"}]})),
+                    error: None,
+                },
+                Exchange {
+                    method: "tools/call".into(),
+                    params: Some(call_params("echo", &json!({"input": "a"}))),
+                    result: Some(json!({"content": [{"type": "text", "text": "a2"}]})),
+                    error: None,
+                },
+                Exchange {
+                    method: "tools/call".into(),
+                    params: Some(call_params("fail", &json!({}))),
+                    result: None,
+                    error: Some(json!({"code": "protocol_error", "message": "boom"})),
+                },
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_in_order_then_repeats_last() {
+        let mut t = ReplayTransport::new(recording());
+        assert_eq!(t.initialize().await.unwrap().version, "1.0");
+        let args = json!({"input": "a"});
+        assert_eq!(t.call_tool("echo", args.clone()).await.unwrap().text(), "a");
+        assert_eq!(t.call_tool("echo", args.clone()).await.unwrap().text(), "a2");
+        assert_eq!(t.call_tool("echo", args).await.unwrap().text(), "a2");
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_errors() {
+        let t = ReplayTransport::new(recording());
+        let err = t.call_tool("fail", json!({})).await.unwrap_err();
+        assert!(err.to_string().contains("boom"));
+    }
+
+    #[tokio::test]
+    async fn unmatched_request_errors() {
+        let t = ReplayTransport::new(recording());
+        let err = t.call_tool("echo", json!({"input": "b"})).await.unwrap_err();
+        assert!(err.to_string().contains("No recorded response for tools/call"));
+        assert!(t.list_tools().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn in_memory_recording_captures_errors_too() {
+        let inner = Box::new(ReplayTransport::new(recording()));
+        let mut t = RecordingTransport::in_memory(inner, "srv");
+        t.initialize().await.unwrap();
+        assert!(t.call_tool("fail", json!({})).await.is_err());
+        let recorded = t.recording();
+        assert_eq!(recorded.server, "srv");
+        assert_eq!(recorded.exchanges.len(), 2);
+        assert_eq!(recorded.exchanges[0].method, "initialize");
+        assert!(recorded.exchanges[1].error.is_some());
+    }
+
+    #[test]
+    fn exchange_omits_empty_fields() {
+        let ex = Exchange {
+            method: "tools/list".into(),
+            params: None,
+            result: Some(json!({"tools": []})),
+            error: None,
+        };
+        let value = serde_json::to_value(&ex).unwrap();
+        assert!(value.get("params").is_none());
+        assert!(value.get("error").is_none());
+    }
+}
@@ -0,0 +1,135 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::McplugError;
+use crate::oauth::split_token_id;
+use crate::types::ServerInfo;
+
+/// Persisted Streamable HTTP session state for a server.
+///
+/// Saved after a successful `initialize` so a later process can attempt to
+/// resume the session instead of re-initializing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionState {
+    pub session_id: String,
+    /// The URL the session was opened with; a session saved for another
+    /// URL is not resumed.
+    #[serde(default)]
+    pub base_url: String,
+    /// Last SSE event ID seen on the session, for stream resumption.
+    #[serde(default)]
+    pub last_event_id: Option<String>,
+    pub server_info: ServerInfo,
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Path of the session file for a token ID (`server` or
+/// `server:identity`, see [`crate::oauth::token_id`]):
+/// `~/.mcplug/<server>/session.json`, or `session.<identity>.json`, so
+/// identities never resume each other's sessions.
+pub fn session_path(token_id: &str) -> PathBuf {
+    let (server_name, identity) = split_token_id(token_id);
+    let file = match identity {
+        Some(identity) => format!("session.{identity}.json"),
+        None => "session.json".to_string(),
+    };
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".mcplug")
+        .join(server_name)
+        .join(file)
+}
+
+pub fn load_session(path: &Path) -> Option<SessionState> {
+    let data = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+pub fn save_session(path: &Path, state: &SessionState) -> Result<(), McplugError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let data = serde_json::to_string_pretty(state).map_err(|e| {
+        McplugError::ProtocolError(format!("Failed to serialize session state: {e}"))
+    })?;
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+/// Remove a stale session file. Missing files are not an error.
+pub fn clear_session(path: &Path) {
+    let _ = std::fs::remove_file(path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_state() -> SessionState {
+        SessionState {
+            session_id: "sess-123".into(),
+            base_url: "https://example.com/mcp".into(),
+            last_event_id: None,
+            server_info: ServerInfo {
+                name: "remote".into(),
+                version: "1.2.3".into(),
+                capabilities: serde_json::json!({"tools": {}}),
+                protocol_version: Some("2025-03-26".into()),
+            },
+            saved_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn session_path_structure() {
+        let path = session_path("github");
+        let path_str = path.to_string_lossy();
+        assert!(path_str.contains(".mcplug"));
+        assert!(path_str.contains("github"));
+        assert!(path_str.ends_with("session.json"));
+        assert!(session_path("github:work").ends_with("github/session.work.json"));
+    }
+
+    #[test]
+    fn save_and_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("srv").join("session.json");
+        save_session(&path, &sample_state()).unwrap();
+
+        let loaded = load_session(&path).unwrap();
+        assert_eq!(loaded.session_id, "sess-123");
+        assert_eq!(loaded.base_url, "https://example.com/mcp");
+        assert_eq!(loaded.server_info.version, "1.2.3");
+        assert_eq!(
+            loaded.server_info.protocol_version.as_deref(),
+            Some("2025-03-26")
+        );
+    }
+
+    #[test]
+    fn load_missing_returns_none() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_session(&dir.path().join("session.json")).is_none());
+    }
+
+    #[test]
+    fn load_corrupt_returns_none() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        std::fs::write(&path, "not json").unwrap();
+        assert!(load_session(&path).is_none());
+    }
+
+    #[test]
+    fn clear_removes_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        save_session(&path, &sample_state()).unwrap();
+        clear_session(&path);
+        assert!(!path.exists());
+        // Clearing again is a no-op
+        clear_session(&path);
+    }
+}
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+/// An event read from a `text/event-stream` body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The `event` field, if the event set one.
+    pub event: Option<String>,
+    /// The `data` lines, joined with `\n`.
+    pub data: String,
+}
+
+/// Incremental parser for server-sent events, fed the body as it arrives.
+///
+/// Follows the WHATWG rules: lines starting with `:` are comments, an empty
+/// line ends an event, and events without data are not dispatched. The
+/// last event ID outlives the event that set it, as it is what a client
+/// sends in `Last-Event-ID` to resume the stream.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    /// Bytes of an incomplete line.
+    buf: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    last_event_id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the next chunk of the body, returning the events it completes.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buf.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buf.iter().position(|&b| b == b'\n') {
+            let mut line: Vec<u8> = self.buf.drain(..=end).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            let line = String::from_utf8_lossy(&line);
+            if line.is_empty() {
+                events.extend(self.dispatch());
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line.as_ref(), ""),
+            };
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                // An ID with a NUL is ignored, per the spec
+                "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+                "retry" => {
+                    if let Ok(ms) = value.parse() {
+                        self.retry = Some(Duration::from_millis(ms));
+                    }
+                }
+                _ => {}
+            }
+        }
+        events
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        let data = std::mem::take(&mut self.data).join("\n");
+        Some(SseEvent { event, data })
+    }
+
+    /// The ID of the latest event that set one.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// How long the server asked clients to wait before reconnecting.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// Drop the event that was being read when the connection broke,
+    /// keeping the last event ID and retry delay for the next connection.
+    pub fn discard_partial(&mut self) {
+        self.buf.clear();
+        self.event = None;
+        self.data.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_events_split_across_chunks() {
+        let mut parser = SseParser::new();
+        assert!(parser.feed(b"id: 1\r\nevent: message\r\nda").is_empty());
+        let events = parser.feed(b"ta: {\"a\":\r\ndata: 1}\r\n\r\n: keep-alive\n\ndata:x\n\n");
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: Some("message".into()),
+                    data: "{\"a\":\n1}".into(),
+                },
+                SseEvent {
+                    event: None,
+                    data: "x".into(),
+                },
+            ]
+        );
+        assert_eq!(parser.last_event_id(), Some("1"));
+    }
+
+    #[test]
+    fn id_and_retry_outlive_their_event() {
+        let mut parser = SseParser::new();
+        // A priming event carries an ID but no data
+        assert!(parser.feed(b"id: abc\nretry: 250\n\n").is_empty());
+        assert_eq!(parser.last_event_id(), Some("abc"));
+        assert_eq!(parser.retry(), Some(Duration::from_millis(250)));
+
+        parser.feed(b"id: a\0b\nretry: soon\ndata: partial");
+        assert_eq!(parser.last_event_id(), Some("abc"));
+        assert_eq!(parser.retry(), Some(Duration::from_millis(250)));
+
+        parser.discard_partial();
+        assert_eq!(
+            parser.feed(b"data: next\n\n"),
+            vec![SseEvent {
+                event: None,
+                data: "next".into(),
+            }]
+        );
+        assert_eq!(parser.last_event_id(), Some("abc"));
+    }
+}
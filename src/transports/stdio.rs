@@ -1,18 +1,46 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::Path;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
-use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command};
 use tokio::sync::Mutex;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
 use crate::error::McplugError;
 use crate::transport::McpTransport;
-use crate::types::{CallResult, ServerInfo, ToolDefinition};
+use crate::types::{CallResult, Notification, ServerInfo, ToolDefinition};
+
+use super::jsonrpc::{
+    cancelled_params, initialize_params, is_protocol_version_error, next_protocol_version,
+    InFlight, JsonRpcResponse, RequestBuilder, PROTOCOL_VERSIONS,
+};
+
+/// Default time a server may take to produce its first output (e.g. while
+/// `npx -y` installs packages) before startup is abandoned.
+pub const DEFAULT_INSTALL_TIMEOUT_SECS: u64 = 300;
+
+/// Resolve the install timeout: the server's `installTimeout` if set, then
+/// `MCPLUG_INSTALL_TIMEOUT`, then the default (all in seconds).
+pub fn install_timeout(configured: Option<u64>) -> Duration {
+    let secs = configured.unwrap_or_else(|| {
+        std::env::var("MCPLUG_INSTALL_TIMEOUT")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_INSTALL_TIMEOUT_SECS)
+    });
+    Duration::from_secs(secs)
+}
+
+/// How many of the server's latest stderr lines are kept for diagnostics.
+const STDERR_TAIL_LINES: usize = 200;
 
-use super::jsonrpc::{JsonRpcResponse, RequestBuilder};
+/// How many notifications a slow subscriber may fall behind by before it
+/// misses some.
+const NOTIFICATION_BUFFER: usize = 64;
 
 pub struct StdioTransport {
     child: Mutex<Child>,
@@ -20,6 +48,17 @@ pub struct StdioTransport {
     stdout: Mutex<BufReader<ChildStdout>>,
     next_id: AtomicU64,
     server_name: String,
+    install_timeout: Duration,
+    /// Set once the server has written to stdout; until then it is installing.
+    ready: Arc<AtomicBool>,
+    /// Id of an `initialize` request sent by `wait_ready` but not yet read.
+    pending_init: Option<u64>,
+    /// Latest stderr lines, kept alongside forwarding them.
+    stderr_tail: Arc<std::sync::Mutex<VecDeque<String>>>,
+    /// Requests sent whose responses haven't been read.
+    in_flight: InFlight,
+    /// Notifications read from stdout, for subscribers.
+    notifications: tokio::sync::broadcast::Sender<Notification>,
 }
 
 impl std::fmt::Debug for StdioTransport {
@@ -44,8 +83,9 @@ impl StdioTransport {
         cmd.args(args)
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::inherit())
-            .envs(env);
+            .stderr(std::process::Stdio::piped())
+            .envs(env)
+            .kill_on_drop(true);
 
         if let Some(dir) = cwd {
             cmd.current_dir(dir);
@@ -65,21 +105,57 @@ impl StdioTransport {
             .take()
             .ok_or_else(|| McplugError::TransportError("Failed to capture stdout".into()))?;
 
+        let ready = Arc::new(AtomicBool::new(false));
+        let stderr_tail = Arc::new(std::sync::Mutex::new(VecDeque::new()));
+        if let Some(stderr) = child.stderr.take() {
+            forward_stderr(
+                stderr,
+                server_name.to_string(),
+                Arc::clone(&ready),
+                Arc::clone(&stderr_tail),
+            );
+        }
+
         Ok(Self {
             child: Mutex::new(child),
             stdin: Mutex::new(BufWriter::new(child_stdin)),
             stdout: Mutex::new(BufReader::new(child_stdout)),
             next_id: AtomicU64::new(1),
             server_name: server_name.to_string(),
+            install_timeout: Duration::from_secs(DEFAULT_INSTALL_TIMEOUT_SECS),
+            ready,
+            pending_init: None,
+            stderr_tail,
+            in_flight: InFlight::default(),
+            notifications: tokio::sync::broadcast::channel(NOTIFICATION_BUFFER).0,
         })
     }
 
+    /// Set how long the server may take to produce its first output.
+    pub fn with_install_timeout(mut self, timeout: Duration) -> Self {
+        self.install_timeout = timeout;
+        self
+    }
+
     /// Send a JSON-RPC request and read the response.
     async fn send_request(
         &self,
         method: &str,
         params: Option<serde_json::Value>,
     ) -> Result<JsonRpcResponse, McplugError> {
+        let id = self.write_request(method, params).await?;
+        self.in_flight.start(id, method);
+        let resp = self.read_response(id).await;
+        self.in_flight.finish(id);
+        resp
+    }
+
+    /// Write a JSON-RPC request to the server, returning its id.
+    async fn write_request(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<u64, McplugError> {
         let id = self.next_id.fetch_add(1, Ordering::Relaxed);
         let builder = RequestBuilder::new();
         // We don't use the builder's auto-increment here; we manage IDs ourselves.
@@ -108,7 +184,11 @@ impl StdioTransport {
                 .await
                 .map_err(|e| McplugError::TransportError(Box::new(e)))?;
         }
+        Ok(id)
+    }
 
+    /// Read responses until the one for request `id` arrives.
+    async fn read_response(&self, id: u64) -> Result<JsonRpcResponse, McplugError> {
         // Read response lines until we get one matching our request ID.
         // Skip notifications (lines without an id or with a different id).
         loop {
@@ -119,9 +199,15 @@ impl StdioTransport {
                 ))
             })?;
 
-            // If this is a notification (no id), skip it
+            // A notification (no id) goes to subscribers, if there are any
             if resp.id.is_none() {
-                debug!(server = %self.server_name, "skipping notification");
+                match serde_json::from_str::<Notification>(&line) {
+                    Ok(notification) => {
+                        debug!(server = %self.server_name, method = %notification.method, "received notification");
+                        let _ = self.notifications.send(notification);
+                    }
+                    Err(_) => debug!(server = %self.server_name, "skipping message without id"),
+                }
                 continue;
             }
 
@@ -185,6 +271,7 @@ impl StdioTransport {
             ));
         }
 
+        self.ready.store(true, Ordering::Relaxed);
         Ok(line)
     }
 
@@ -209,20 +296,103 @@ impl StdioTransport {
     }
 }
 
+/// Forward the server's stderr to ours. Lines written before the server
+/// first answers (typically `npx` install progress) are prefixed with the
+/// server name so it is clear what is being waited on. The latest lines are
+/// also kept in `tail`.
+fn forward_stderr(
+    stderr: ChildStderr,
+    server_name: String,
+    ready: Arc<AtomicBool>,
+    tail: Arc<std::sync::Mutex<VecDeque<String>>>,
+) {
+    let Ok(handle) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    handle.spawn(async move {
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if ready.load(Ordering::Relaxed) {
+                eprintln!("{}", line);
+            } else {
+                eprintln!("[{}] {}", server_name, line);
+            }
+            if let Ok(mut tail) = tail.lock() {
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
+            }
+        }
+    });
+}
+
 #[async_trait]
 impl McpTransport for StdioTransport {
-    async fn initialize(&mut self) -> Result<ServerInfo, McplugError> {
-        let params = serde_json::json!({
-            "protocolVersion": "2024-11-05",
-            "capabilities": {},
-            "clientInfo": {
-                "name": "mcplug",
-                "version": "0.1.0"
+    /// Send the first `initialize` request and wait, up to the install
+    /// timeout, for the server to start answering.
+    async fn wait_ready(&mut self) -> Result<(), McplugError> {
+        if self.pending_init.is_some() || self.ready.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let id = self
+            .write_request("initialize", Some(initialize_params(PROTOCOL_VERSIONS[0])))
+            .await?;
+        self.pending_init = Some(id);
+
+        let mut stdout = self.stdout.lock().await;
+        match tokio::time::timeout(self.install_timeout, stdout.fill_buf()).await {
+            Err(_) => {
+                drop(stdout);
+                // Don't leave a half-installed npx process behind
+                let _ = self.child.lock().await.kill().await;
+                Err(McplugError::ConnectionFailed {
+                    server: self.server_name.clone(),
+                    source: format!(
+                        "no response within the install timeout of {}s; \
+                         raise `installTimeout` for slow first-run installs",
+                        self.install_timeout.as_secs()
+                    )
+                    .into(),
+                })
             }
-        });
+            Ok(Err(e)) => Err(McplugError::TransportError(Box::new(e))),
+            // Data (or EOF, which initialize reports as an exit) is available
+            Ok(Ok(_)) => Ok(()),
+        }
+    }
 
-        let resp = self.send_request("initialize", Some(params)).await?;
-        let result = self.check_response(resp)?;
+    async fn initialize(&mut self) -> Result<ServerInfo, McplugError> {
+        // Offer the newest protocol version, falling back to older ones if rejected
+        let mut offered = PROTOCOL_VERSIONS[0];
+        let mut pending = self.pending_init.take();
+        let result = loop {
+            // wait_ready may already have sent the first initialize request
+            let resp = match pending.take() {
+                Some(id) => self.read_response(id).await?,
+                None => {
+                    self.send_request("initialize", Some(initialize_params(offered)))
+                        .await?
+                }
+            };
+            let next = resp
+                .error
+                .as_ref()
+                .filter(|err| is_protocol_version_error(err))
+                .and_then(|_| next_protocol_version(offered));
+            match next {
+                Some(older) => {
+                    info!(
+                        server = %self.server_name,
+                        rejected = offered,
+                        retry = older,
+                        "server rejected protocol version, retrying initialize"
+                    );
+                    offered = older;
+                }
+                None => break self.check_response(resp)?,
+            }
+        };
 
         // Extract server info from the result
         let server_info_value = result
@@ -247,6 +417,17 @@ impl McpTransport for StdioTransport {
             .cloned()
             .unwrap_or_else(|| serde_json::json!({}));
 
+        let protocol_version = result
+            .get("protocolVersion")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+        debug!(
+            server = %self.server_name,
+            offered,
+            negotiated = ?protocol_version,
+            "protocol version negotiated"
+        );
+
         // Send initialized notification
         self.send_notification("notifications/initialized", None)
             .await?;
@@ -255,6 +436,7 @@ impl McpTransport for StdioTransport {
             name,
             version,
             capabilities,
+            protocol_version,
         })
     }
 
@@ -299,6 +481,43 @@ impl McpTransport for StdioTransport {
         })
     }
 
+    async fn request(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, McplugError> {
+        let resp = self.send_request(method, params).await?;
+        self.check_response(resp)
+    }
+
+    async fn notify(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<(), McplugError> {
+        self.send_notification(method, params).await
+    }
+
+    fn stderr_lines(&self) -> Vec<String> {
+        self.stderr_tail
+            .lock()
+            .map(|tail| tail.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn subscribe(&self) -> Option<tokio::sync::broadcast::Receiver<Notification>> {
+        Some(self.notifications.subscribe())
+    }
+
+    async fn cancel_pending(&self, reason: &str) -> Result<(), McplugError> {
+        for id in self.in_flight.take() {
+            debug!(server = %self.server_name, id, "cancelling request");
+            self.send_notification("notifications/cancelled", Some(cancelled_params(id, reason)))
+                .await?;
+        }
+        Ok(())
+    }
+
     async fn close(&mut self) -> Result<(), McplugError> {
         let mut child = self.child.lock().await;
         // Try to kill the child process
@@ -335,6 +554,34 @@ mod tests {
         });
     }
 
+    #[test]
+    fn install_timeout_prefers_configured_value() {
+        assert_eq!(install_timeout(Some(7)), Duration::from_secs(7));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn wait_ready_times_out_on_silent_server() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut t = StdioTransport::new(
+                "sleep",
+                &["5".to_string()],
+                &HashMap::new(),
+                None,
+                "silent-server",
+            )
+            .unwrap()
+            .with_install_timeout(Duration::from_millis(200));
+            let started = std::time::Instant::now();
+            let err = t.wait_ready().await.unwrap_err();
+            assert!(started.elapsed() < Duration::from_secs(3));
+            assert!(err.to_string().contains("silent-server"));
+            assert!(format!("{:?}", err).contains("install timeout"));
+            let _ = t.close().await;
+        });
+    }
+
     #[test]
     fn stdio_transport_creation_fails_with_bad_command() {
         let result = StdioTransport::new(
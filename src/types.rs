@@ -8,6 +8,79 @@ pub struct ServerInfo {
     pub name: String,
     pub version: String,
     pub capabilities: serde_json::Value,
+    /// Protocol version the server agreed to during initialization.
+    #[serde(default)]
+    pub protocol_version: Option<String>,
+}
+
+impl ServerInfo {
+    /// Whether the server advertises the `logging` capability.
+    pub fn supports_logging(&self) -> bool {
+        self.capabilities.get("logging").is_some()
+    }
+}
+
+/// Severity for `logging/setLevel`, from most to least verbose (RFC 5424).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 8] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Notice,
+        LogLevel::Warning,
+        LogLevel::Error,
+        LogLevel::Critical,
+        LogLevel::Alert,
+        LogLevel::Emergency,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Notice => "notice",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+            LogLevel::Critical => "critical",
+            LogLevel::Alert => "alert",
+            LogLevel::Emergency => "emergency",
+        }
+    }
+}
+
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = McplugError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|level| level.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|l| l.as_str()).collect();
+                McplugError::ProtocolError(format!(
+                    "Invalid log level '{s}': expected one of {}",
+                    names.join(", ")
+                ))
+            })
+    }
 }
 
 /// A tool definition exposed by an MCP server.
@@ -17,6 +90,311 @@ pub struct ToolDefinition {
     pub description: String,
     #[serde(alias = "inputSchema")]
     pub input_schema: serde_json::Value,
+    /// Behavioral hints advertised by the server, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+    /// JSON Schema of the tool's `structuredContent`, if it declares one.
+    #[serde(default, alias = "outputSchema", skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
+}
+
+impl ToolDefinition {
+    /// SHA-256 of the input schema, formatted as `sha256:<hex>`.
+    ///
+    /// Object keys serialize in sorted order, so the hash does not depend on
+    /// the order the server sent them in.
+    pub fn schema_hash(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let canonical = serde_json::to_vec(&self.input_schema).unwrap_or_default();
+        let digest = Sha256::digest(&canonical);
+        let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+        format!("sha256:{hex}")
+    }
+
+    /// The input schema, parsed.
+    pub fn schema(&self) -> ToolSchema {
+        ToolSchema::parse(&self.input_schema)
+    }
+
+    /// The output schema, parsed, if the tool declares one.
+    pub fn result_schema(&self) -> Option<ToolSchema> {
+        self.output_schema.as_ref().map(ToolSchema::parse)
+    }
+}
+
+/// A tool's input schema, parsed from JSON Schema into the parts mcplug
+/// reads: its properties, which of them are required, and whether
+/// undeclared arguments are allowed.
+///
+/// Parsing is lenient, as servers' schemas vary: keywords mcplug doesn't use
+/// are ignored, and anything that isn't an object schema has no properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolSchema {
+    /// Declared properties, sorted by name.
+    pub properties: Vec<SchemaProperty>,
+    /// Names of the required arguments. These need not be declared.
+    pub required: Vec<String>,
+    /// Whether arguments not in `properties` are accepted, which they are
+    /// unless `additionalProperties` is `false`.
+    pub additional_properties: bool,
+}
+
+impl ToolSchema {
+    /// Parse a JSON Schema object, such as a tool's `inputSchema`.
+    pub fn parse(schema: &serde_json::Value) -> Self {
+        let required: Vec<String> = schema
+            .get("required")
+            .and_then(|r| r.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str()).map(String::from).collect())
+            .unwrap_or_default();
+        let properties = schema
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .map(|props| {
+                let mut properties: Vec<SchemaProperty> = props
+                    .iter()
+                    .map(|(name, prop)| SchemaProperty::parse(name, prop, required.contains(name)))
+                    .collect();
+                // Maps keep insertion order with serde_json's `preserve_order`
+                properties.sort_by(|a, b| a.name.cmp(&b.name));
+                properties
+            })
+            .unwrap_or_default();
+        let additional_properties = !matches!(
+            schema.get("additionalProperties"),
+            Some(serde_json::Value::Bool(false))
+        );
+        Self {
+            properties,
+            required,
+            additional_properties,
+        }
+    }
+
+    /// The declared property called `name`.
+    pub fn property(&self, name: &str) -> Option<&SchemaProperty> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+}
+
+/// One declared argument of a tool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaProperty {
+    pub name: String,
+    /// The JSON Schema `type`, when the schema gives one. Lists of types
+    /// and `oneOf`/`anyOf` alternatives are a `SchemaType::Union`.
+    pub ty: Option<SchemaType>,
+    pub description: Option<String>,
+    /// The values allowed by `enum`; empty when any value is.
+    pub enum_values: Vec<serde_json::Value>,
+    pub default: Option<serde_json::Value>,
+    pub required: bool,
+}
+
+impl SchemaProperty {
+    fn parse(name: &str, schema: &serde_json::Value, required: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            ty: SchemaType::parse(schema),
+            description: schema
+                .get("description")
+                .and_then(|d| d.as_str())
+                .map(String::from),
+            enum_values: schema
+                .get("enum")
+                .and_then(|e| e.as_array())
+                .cloned()
+                .unwrap_or_default(),
+            default: schema.get("default").cloned(),
+            required,
+        }
+    }
+
+    /// The type's name for display, `any` when the schema doesn't give one.
+    pub fn type_name(&self) -> String {
+        self.ty.as_ref().map_or_else(|| "any".to_string(), SchemaType::to_string)
+    }
+}
+
+/// A JSON Schema `type`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    /// An array, with the type of its items when the schema gives one.
+    Array(Option<Box<SchemaType>>),
+    Object,
+    Null,
+    /// A type name JSON Schema doesn't define, kept as given.
+    Other(String),
+    /// A value of any of several types, from a list of types or from
+    /// `oneOf`/`anyOf` alternatives. Never nested, and never a single type.
+    Union(Vec<SchemaType>),
+}
+
+impl SchemaType {
+    /// The type of `schema`: its `type`, or the union of its listed types or
+    /// of its `oneOf`/`anyOf` alternatives. `None` if it has none of these,
+    /// or if an alternative has no type.
+    pub fn parse(schema: &serde_json::Value) -> Option<Self> {
+        match schema.get("type") {
+            Some(serde_json::Value::String(name)) => Some(Self::named(name, schema)),
+            Some(serde_json::Value::Array(names)) => {
+                let types = names
+                    .iter()
+                    .map(|name| name.as_str().map(|name| Self::named(name, schema)))
+                    .collect::<Option<Vec<_>>>()?;
+                Self::union(types)
+            }
+            Some(_) => None,
+            None => {
+                let alternatives = schema
+                    .get("oneOf")
+                    .or_else(|| schema.get("anyOf"))?
+                    .as_array()?;
+                let types = alternatives.iter().map(Self::parse).collect::<Option<Vec<_>>>()?;
+                Self::union(types)
+            }
+        }
+    }
+
+    fn named(name: &str, schema: &serde_json::Value) -> Self {
+        match name {
+            "string" => SchemaType::String,
+            "number" => SchemaType::Number,
+            "integer" => SchemaType::Integer,
+            "boolean" => SchemaType::Boolean,
+            "array" => SchemaType::Array(
+                schema.get("items").and_then(SchemaType::parse).map(Box::new),
+            ),
+            "object" => SchemaType::Object,
+            "null" => SchemaType::Null,
+            other => SchemaType::Other(other.to_string()),
+        }
+    }
+
+    /// A union of `types`, flattened and without duplicates; the type
+    /// itself if only one remains.
+    fn union(types: Vec<SchemaType>) -> Option<Self> {
+        let mut members: Vec<SchemaType> = Vec::new();
+        for ty in types {
+            let flattened = match ty {
+                SchemaType::Union(inner) => inner,
+                ty => vec![ty],
+            };
+            for ty in flattened {
+                if !members.contains(&ty) {
+                    members.push(ty);
+                }
+            }
+        }
+        match members.len() {
+            0 => None,
+            1 => members.pop(),
+            _ => Some(SchemaType::Union(members)),
+        }
+    }
+
+    /// The types a value may have: a union's members, or this type.
+    pub fn members(&self) -> &[SchemaType] {
+        match self {
+            SchemaType::Union(members) => members,
+            ty => std::slice::from_ref(ty),
+        }
+    }
+
+    /// Whether `null` is one of the types.
+    pub fn is_nullable(&self) -> bool {
+        self.members().contains(&SchemaType::Null)
+    }
+
+    /// The one type other than `null`, if there is exactly one: `T` for
+    /// `T` or for a union of `T` and `null`.
+    pub fn without_null(&self) -> Option<&SchemaType> {
+        let mut rest = self.members().iter().filter(|ty| **ty != SchemaType::Null);
+        match (rest.next(), rest.next()) {
+            (Some(ty), None) => Some(ty),
+            _ => None,
+        }
+    }
+
+    /// Whether `value` is of this type. Unknown types accept anything.
+    pub fn matches(&self, value: &serde_json::Value) -> bool {
+        match self {
+            SchemaType::String => value.is_string(),
+            SchemaType::Number => value.is_number(),
+            SchemaType::Integer => value.is_i64() || value.is_u64(),
+            SchemaType::Boolean => value.is_boolean(),
+            SchemaType::Array(_) => value.is_array(),
+            SchemaType::Object => value.is_object(),
+            SchemaType::Null => value.is_null(),
+            SchemaType::Other(_) => true,
+            SchemaType::Union(members) => members.iter().any(|ty| ty.matches(value)),
+        }
+    }
+}
+
+impl std::fmt::Display for SchemaType {
+    /// The JSON Schema name of the type, with a union's members joined by
+    /// ` | `.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SchemaType::String => "string",
+            SchemaType::Number => "number",
+            SchemaType::Integer => "integer",
+            SchemaType::Boolean => "boolean",
+            SchemaType::Array(_) => "array",
+            SchemaType::Object => "object",
+            SchemaType::Null => "null",
+            SchemaType::Other(name) => name,
+            SchemaType::Union(members) => {
+                let names: Vec<String> = members.iter().map(ToString::to_string).collect();
+                return f.write_str(&names.join(" | "));
+            }
+        };
+        f.write_str(name)
+    }
+}
+
+/// Optional hints describing a tool's behavior (MCP `ToolAnnotations`).
+///
+/// These are advisory: servers are not required to send them and clients
+/// should not rely on them for security decisions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotent_hint: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_world_hint: Option<bool>,
+}
+
+impl ToolAnnotations {
+    /// Short labels for the hints that are set, e.g. `["read-only", "idempotent"]`.
+    pub fn hint_labels(&self) -> Vec<&'static str> {
+        let mut labels = Vec::new();
+        if self.read_only_hint == Some(true) {
+            labels.push("read-only");
+        }
+        if self.destructive_hint == Some(true) {
+            labels.push("destructive");
+        }
+        if self.idempotent_hint == Some(true) {
+            labels.push("idempotent");
+        }
+        if self.open_world_hint == Some(true) {
+            labels.push("open-world");
+        }
+        labels
+    }
 }
 
 /// A single content block returned by a tool call.
@@ -24,10 +402,23 @@ pub struct ToolDefinition {
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum ContentBlock {
     Text { text: String },
-    Image { data: String, mime_type: String },
+    Image {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
     Resource { uri: String, text: String },
 }
 
+/// A notification sent by a server, such as a log message
+/// (`notifications/message`) or `notifications/tools/list_changed`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<serde_json::Value>,
+}
+
 /// The result of calling an MCP tool.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallResult {
@@ -39,6 +430,23 @@ pub struct CallResult {
     pub raw_response: Option<serde_json::Value>,
 }
 
+/// Escape characters that would end a markdown link's text early.
+fn escape_link_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+/// A markdown link destination; URIs with spaces or parentheses are wrapped
+/// in `<>` so they aren't cut short.
+fn link_destination(uri: &str) -> String {
+    if uri.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+        format!("<{}>", uri.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        uri.to_string()
+    }
+}
+
 impl CallResult {
     /// Extract plain text from all text content blocks, joined by newlines.
     pub fn text(&self) -> String {
@@ -61,7 +469,24 @@ impl CallResult {
         })
     }
 
+    /// The `structuredContent` of the response, if the server sent one.
+    pub fn structured_content(&self) -> Option<&serde_json::Value> {
+        self.raw_response.as_ref()?.get("structuredContent")
+    }
+
+    /// Deserialize `structuredContent` as a typed value, or the text content
+    /// parsed as JSON when the server sent none.
+    pub fn structured<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        match self.structured_content() {
+            Some(value) => T::deserialize(value),
+            None => serde_json::from_str(&self.text()),
+        }
+    }
+
     /// Format content blocks as markdown.
+    ///
+    /// Images become inline `data:` URIs and resources a link to their URI
+    /// followed by their text.
     pub fn markdown(&self) -> String {
         self.content
             .iter()
@@ -71,7 +496,12 @@ impl CallResult {
                     format!("![image](data:{mime_type};base64,{data})")
                 }
                 ContentBlock::Resource { uri, text } => {
-                    format!("[{uri}]({uri})\n\n{text}")
+                    let link = format!("[{}]({})", escape_link_text(uri), link_destination(uri));
+                    if text.is_empty() {
+                        link
+                    } else {
+                        format!("{link}\n\n{text}")
+                    }
                 }
             })
             .collect::<Vec<_>>()
@@ -167,6 +597,17 @@ mod tests {
         assert_eq!(result.text(), "before\nafter");
     }
 
+    #[test]
+    fn image_block_uses_mime_type_wire_name() {
+        let block: ContentBlock = serde_json::from_value(serde_json::json!({
+            "type": "image", "data": "AA==", "mimeType": "image/png"
+        }))
+        .unwrap();
+        assert!(matches!(block, ContentBlock::Image { ref mime_type, .. } if mime_type == "image/png"));
+        let json = serde_json::to_value(&block).unwrap();
+        assert_eq!(json["mimeType"], "image/png");
+    }
+
     #[test]
     fn json_deserialize() {
         let result = make_text_result(&[r#"{"key":"value"}"#]);
@@ -181,6 +622,28 @@ mod tests {
         assert!(err.to_string().contains("Failed to deserialize"));
     }
 
+    #[test]
+    fn structured_prefers_structured_content() {
+        let mut result = make_text_result(&["not json"]);
+        assert!(result.structured_content().is_none());
+        assert!(result.structured::<serde_json::Value>().is_err());
+
+        result.raw_response = Some(serde_json::json!({
+            "content": [],
+            "structuredContent": {"temp": 21}
+        }));
+        assert_eq!(result.structured_content(), Some(&serde_json::json!({"temp": 21})));
+        let parsed: std::collections::HashMap<String, i64> = result.structured().unwrap();
+        assert_eq!(parsed["temp"], 21);
+    }
+
+    #[test]
+    fn structured_falls_back_to_text() {
+        let result = make_text_result(&["[1, 2]"]);
+        let parsed: Vec<u8> = result.structured().unwrap();
+        assert_eq!(parsed, vec![1, 2]);
+    }
+
     #[test]
     fn markdown_formats_text() {
         let result = make_text_result(&["# Title", "body"]);
@@ -219,12 +682,207 @@ mod tests {
         );
     }
 
+    #[test]
+    fn markdown_resource_links_are_escaped() {
+        let result = CallResult {
+            content: vec![
+                ContentBlock::Resource {
+                    uri: "file:///tmp/my notes (1).md".into(),
+                    text: String::new(),
+                },
+                ContentBlock::Resource {
+                    uri: "memo://[draft]".into(),
+                    text: "x".into(),
+                },
+            ],
+            is_error: false,
+            raw_response: None,
+        };
+        assert_eq!(
+            result.markdown(),
+            "[file:///tmp/my notes (1).md](<file:///tmp/my notes (1).md>)\n\n\
+             [memo://\\[draft\\]](memo://[draft])\n\nx"
+        );
+    }
+
+    #[test]
+    fn log_level_parse_and_serialize() {
+        assert_eq!("debug".parse::<LogLevel>().unwrap(), LogLevel::Debug);
+        assert_eq!("WARNING".parse::<LogLevel>().unwrap(), LogLevel::Warning);
+        let err = "verbose".parse::<LogLevel>().unwrap_err();
+        assert!(err.to_string().contains("expected one of debug, info"));
+        assert_eq!(serde_json::to_value(LogLevel::Emergency).unwrap(), "emergency");
+    }
+
     #[test]
     fn content_returns_blocks() {
         let result = make_text_result(&["a", "b"]);
         assert_eq!(result.content().len(), 2);
     }
 
+    #[test]
+    fn tool_definition_parses_annotations() {
+        let raw = serde_json::json!({
+            "name": "delete_file",
+            "description": "Delete a file",
+            "inputSchema": {"type": "object"},
+            "annotations": {
+                "title": "Delete File",
+                "readOnlyHint": false,
+                "destructiveHint": true,
+                "idempotentHint": true
+            }
+        });
+        let tool: ToolDefinition = serde_json::from_value(raw).unwrap();
+        let ann = tool.annotations.unwrap();
+        assert_eq!(ann.title.as_deref(), Some("Delete File"));
+        assert_eq!(ann.read_only_hint, Some(false));
+        assert_eq!(ann.destructive_hint, Some(true));
+        assert_eq!(ann.hint_labels(), vec!["destructive", "idempotent"]);
+    }
+
+    #[test]
+    fn tool_definition_without_annotations() {
+        let raw = serde_json::json!({
+            "name": "echo",
+            "description": "Echo",
+            "inputSchema": {}
+        });
+        let tool: ToolDefinition = serde_json::from_value(raw).unwrap();
+        assert!(tool.annotations.is_none());
+        assert!(tool.result_schema().is_none());
+        let out = serde_json::to_value(&tool).unwrap();
+        assert!(out.get("annotations").is_none());
+        assert!(out.get("output_schema").is_none());
+    }
+
+    #[test]
+    fn tool_definition_parses_output_schema() {
+        let raw = serde_json::json!({
+            "name": "forecast",
+            "description": "",
+            "inputSchema": {},
+            "outputSchema": {"type": "object", "properties": {"tempC": {"type": "number"}}}
+        });
+        let tool: ToolDefinition = serde_json::from_value(raw).unwrap();
+        let schema = tool.result_schema().unwrap();
+        assert_eq!(schema.property("tempC").unwrap().ty, Some(SchemaType::Number));
+    }
+
+    #[test]
+    fn schema_hash_ignores_key_order() {
+        let a: ToolDefinition = serde_json::from_str(
+            r#"{"name": "t", "description": "", "inputSchema": {"type": "object", "required": ["x"]}}"#,
+        )
+        .unwrap();
+        let b: ToolDefinition = serde_json::from_str(
+            r#"{"name": "t", "description": "", "inputSchema": {"required": ["x"], "type": "object"}}"#,
+        )
+        .unwrap();
+        assert_eq!(a.schema_hash(), b.schema_hash());
+        assert!(a.schema_hash().starts_with("sha256:"));
+        assert_eq!(a.schema_hash().len(), "sha256:".len() + 64);
+    }
+
+    #[test]
+    fn schema_hash_changes_with_schema() {
+        let mut tool: ToolDefinition = serde_json::from_str(
+            r#"{"name": "t", "description": "", "inputSchema": {"type": "object"}}"#,
+        )
+        .unwrap();
+        let before = tool.schema_hash();
+        tool.input_schema["required"] = serde_json::json!(["x"]);
+        assert_ne!(before, tool.schema_hash());
+        // Description is not part of the signature
+        let pinned = tool.schema_hash();
+        tool.description = "changed".into();
+        assert_eq!(pinned, tool.schema_hash());
+    }
+
+    #[test]
+    fn tool_schema_parses_properties() {
+        let schema = ToolSchema::parse(&serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {"type": "string", "description": "Search text"},
+                "mode": {"type": "string", "enum": ["fast", "full"], "default": "fast"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "extra": {}
+            },
+            "required": ["query", "undeclared"],
+            "additionalProperties": false
+        }));
+        let names: Vec<&str> = schema.properties.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["extra", "mode", "query", "tags"]);
+        assert_eq!(schema.required, ["query", "undeclared"]);
+        assert!(!schema.additional_properties);
+
+        let query = schema.property("query").unwrap();
+        assert!(query.required);
+        assert_eq!(query.ty, Some(SchemaType::String));
+        assert_eq!(query.description.as_deref(), Some("Search text"));
+        let mode = schema.property("mode").unwrap();
+        assert!(!mode.required);
+        assert_eq!(mode.enum_values, [serde_json::json!("fast"), serde_json::json!("full")]);
+        assert_eq!(mode.default, Some(serde_json::json!("fast")));
+        assert_eq!(
+            schema.property("tags").unwrap().ty,
+            Some(SchemaType::Array(Some(Box::new(SchemaType::String))))
+        );
+        assert_eq!(schema.property("extra").unwrap().type_name(), "any");
+    }
+
+    #[test]
+    fn tool_schema_is_lenient() {
+        let empty = ToolSchema::parse(&serde_json::json!({}));
+        assert!(empty.properties.is_empty());
+        assert!(empty.additional_properties);
+        assert!(ToolSchema::parse(&serde_json::json!("not a schema")).properties.is_empty());
+
+        let odd = SchemaType::parse(&serde_json::json!({"type": "decimal"})).unwrap();
+        assert_eq!(odd.to_string(), "decimal");
+        assert!(odd.matches(&serde_json::json!("1.5")));
+        assert!(SchemaType::parse(&serde_json::json!({"type": 3})).is_none());
+        assert!(!SchemaType::Integer.matches(&serde_json::json!(3.5)));
+    }
+
+    #[test]
+    fn schema_type_parses_unions() {
+        let nullable = SchemaType::parse(&serde_json::json!({"type": ["string", "null"]})).unwrap();
+        assert_eq!(nullable, SchemaType::Union(vec![SchemaType::String, SchemaType::Null]));
+        assert!(nullable.is_nullable());
+        assert_eq!(nullable.without_null(), Some(&SchemaType::String));
+        assert_eq!(nullable.to_string(), "string | null");
+        assert!(nullable.matches(&serde_json::Value::Null));
+        assert!(!nullable.matches(&serde_json::json!(1)));
+
+        let one_of = SchemaType::parse(&serde_json::json!({
+            "oneOf": [
+                {"type": "integer"},
+                {"type": "array", "items": {"type": "string"}},
+                {"anyOf": [{"type": "integer"}, {"type": "boolean"}]}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(
+            one_of.members(),
+            [
+                SchemaType::Integer,
+                SchemaType::Array(Some(Box::new(SchemaType::String))),
+                SchemaType::Boolean
+            ]
+        );
+        assert!(!one_of.is_nullable());
+        assert_eq!(one_of.without_null(), None);
+
+        // A single alternative is just that type
+        let single = SchemaType::parse(&serde_json::json!({"anyOf": [{"type": "number"}]}));
+        assert_eq!(single, Some(SchemaType::Number));
+        assert_eq!(single.unwrap().without_null(), Some(&SchemaType::Number));
+        // An untyped alternative leaves the type unknown
+        assert!(SchemaType::parse(&serde_json::json!({"anyOf": [{"type": "string"}, {}]})).is_none());
+    }
+
     #[test]
     fn raw_returns_none_when_unset() {
         let result = make_text_result(&["x"]);
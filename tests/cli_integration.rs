@@ -3,9 +3,21 @@ use predicates::prelude::*;
 
 mod common;
 
+/// A scratch home directory shared by this test binary's commands, so calls
+/// don't record history or cache state in the developer's real `~/.mcplug`.
+fn scratch_home() -> &'static std::path::Path {
+    static HOME: std::sync::OnceLock<tempfile::TempDir> = std::sync::OnceLock::new();
+    HOME.get_or_init(|| tempfile::tempdir_in(env!("CARGO_TARGET_TMPDIR")).unwrap())
+        .path()
+}
+
+/// The mcplug binary with `HOME` set to [`scratch_home`]. Tests that inspect
+/// the home directory set their own `HOME`, which takes precedence.
 fn mcplug_cmd() -> Command {
     #[allow(deprecated)]
-    Command::cargo_bin("mcplug").unwrap()
+    let mut cmd = Command::cargo_bin("mcplug").unwrap();
+    cmd.env("HOME", scratch_home());
+    cmd
 }
 
 /// I7: mcplug list <server> output
@@ -111,3 +123,1838 @@ fn config_show_output() {
         .success()
         .stdout(predicate::str::contains("mock"));
 }
+
+/// mcplug config validate reports problems with file and line
+#[test]
+fn config_validate_reports_issues() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("mcplug.json");
+    std::fs::write(
+        &config_path,
+        "{\n  \"mcpServers\": {\n    \"ok\": {\"command\": \"x\"},\n    \"broken\": {\"descripton\": \"typo\"}\n  }\n}\n",
+    )
+    .unwrap();
+    mcplug_cmd()
+        .args(["config", "validate"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("HOME", dir.path())
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "mcplug.json:4: warning: Server 'broken': unknown key 'descripton'",
+        ))
+        .stdout(predicate::str::contains(
+            "mcplug.json:4: error: Server 'broken' has neither 'command' nor 'baseUrl'",
+        ))
+        .stdout(predicate::str::contains("Checked 1 file(s): 1 error(s), 1 warning(s)"))
+        .stderr(predicate::str::contains("1 error(s) found"));
+
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    mcplug_cmd()
+        .args(["config", "validate"])
+        .env("MCPLUG_CONFIG", config_dir.path().join("mcplug.json"))
+        .env("HOME", dir.path())
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("0 error(s), 0 warning(s)"));
+}
+
+/// mcplug config export prints and writes editor config files
+#[test]
+fn config_export_to_editor_formats() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("mcplug.json");
+    std::fs::write(
+        &config_path,
+        r#"{"mcpServers": {
+            "fs": {"command": "npx", "args": ["-y", "fs"], "env": {"TOKEN": "${FS_TOKEN}"}},
+            "api": {"baseUrl": "https://api.example.com/mcp"}
+        }}"#,
+    )
+    .unwrap();
+
+    mcplug_cmd()
+        .args(["config", "export", "--to", "vscode"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("HOME", dir.path())
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"servers\""))
+        .stdout(predicate::str::contains("\"type\": \"http\""))
+        .stdout(predicate::str::contains("${FS_TOKEN}"));
+
+    let cursor_dir = dir.path().join(".cursor");
+    std::fs::create_dir_all(&cursor_dir).unwrap();
+    std::fs::write(
+        cursor_dir.join("mcp.json"),
+        r#"{"mcpServers": {"mine": {"command": "x"}}}"#,
+    )
+    .unwrap();
+    mcplug_cmd()
+        .args(["config", "export", "--to", "cursor", "--write"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("HOME", dir.path())
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 2 server(s)"));
+    let doc: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(cursor_dir.join("mcp.json")).unwrap())
+            .unwrap();
+    assert_eq!(doc["mcpServers"]["mine"]["command"], "x");
+    assert_eq!(doc["mcpServers"]["api"]["url"], "https://api.example.com/mcp");
+    assert_eq!(doc["mcpServers"]["fs"]["args"][1], "fs");
+}
+
+/// mcplug config edit validates the saved file; config remove deletes an entry
+#[cfg(unix)]
+#[test]
+fn config_edit_and_remove() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("mcplug.json");
+    std::fs::write(&config_path, r#"{"mcpServers": {"old": {"command": "x"}}}"#).unwrap();
+    let config = |args: &[&str], editor: &str| {
+        let mut cmd = mcplug_cmd();
+        cmd.arg("config")
+            .args(args)
+            .env("MCPLUG_CONFIG", &config_path)
+            .env("HOME", dir.path())
+            .env_remove("VISUAL")
+            .env("EDITOR", editor)
+            .current_dir(dir.path());
+        cmd
+    };
+
+    // The "editor" copies a prepared file over the config
+    let bad = dir.path().join("bad.json");
+    std::fs::write(&bad, r#"{"mcpServers": {"bad": {}}}"#).unwrap();
+    let good = dir.path().join("good.json");
+    std::fs::write(
+        &good,
+        r#"{"mcpServers": {"a": {"command": "x"}, "b": {"command": "y"}}}"#,
+    )
+    .unwrap();
+
+    config(&["edit"], &format!("cp {}", bad.display()))
+        .write_stdin("n\n")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Server 'bad' has neither 'command' nor 'baseUrl'"))
+        .stdout(predicate::str::contains("Edit again?"));
+
+    config(&["edit"], &format!("cp {}", good.display()))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("is valid"));
+
+    config(&["remove", "a"], "true").assert().success();
+    let doc: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert!(doc["mcpServers"].get("a").is_none());
+    assert_eq!(doc["mcpServers"]["b"]["command"], "y");
+    config(&["remove", "a"], "true").assert().failure();
+}
+
+/// mcplug config import merges a snippet, resolving name conflicts
+#[test]
+fn config_import_merges_snippet() {
+    let home = tempfile::tempdir().unwrap();
+    let config_path = home.path().join(".mcplug").join("mcplug.json");
+    std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    std::fs::write(&config_path, r#"{"mcpServers": {"fs": {"command": "old"}}}"#).unwrap();
+    let snippet = home.path().join("snippet.json");
+    std::fs::write(
+        &snippet,
+        r#"{"mcpServers": {"fs": {"command": "npx", "args": ["fs"]}, "api": {"url": "https://example.com/mcp"}}}"#,
+    )
+    .unwrap();
+    let import = |args: &[&str]| {
+        let mut cmd = mcplug_cmd();
+        cmd.args(["config", "import"])
+            .arg(&snippet)
+            .args(args)
+            .env("HOME", home.path())
+            .current_dir(home.path());
+        cmd
+    };
+    let read_servers = || -> serde_json::Value {
+        let doc: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+        doc["mcpServers"].clone()
+    };
+
+    import(&["--rename"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Importing 'fs' as 'fs-2'"))
+        .stdout(predicate::str::contains("Imported 2 server(s)"));
+    assert_eq!(read_servers()["fs"]["command"], "old");
+    assert_eq!(read_servers()["fs-2"]["command"], "npx");
+    assert_eq!(read_servers()["api"]["baseUrl"], "https://example.com/mcp");
+
+    import(&[])
+        .write_stdin("s\no\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Skipped 'api'"));
+    assert_eq!(read_servers()["fs"]["command"], "npx");
+
+    mcplug_cmd()
+        .args(["config", "import", "-"])
+        .env("HOME", home.path())
+        .write_stdin(r#"{"fs": {"command": "x"}}"#)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force or --rename"));
+}
+
+#[test]
+fn auth_status_list_and_logout() {
+    let home = tempfile::tempdir().unwrap();
+    let mcplug_dir = home.path().join(".mcplug");
+    std::fs::create_dir_all(mcplug_dir.join("gh")).unwrap();
+    std::fs::write(
+        mcplug_dir.join("mcplug.json"),
+        r#"{"mcpServers": {"api": {"baseUrl": "https://api.example.com/mcp"}, "fs": {"command": "fs"}}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        mcplug_dir.join("gh").join("tokens.json"),
+        r#"{"access_token": "a", "refresh_token": "r", "expires_at": "2020-01-01T00:00:00Z", "token_type": "Bearer", "scope": "repo read"}"#,
+    )
+    .unwrap();
+    let auth = |args: &[&str]| {
+        let mut cmd = mcplug_cmd();
+        cmd.arg("auth")
+            .args(args)
+            .env("HOME", home.path())
+            .current_dir(home.path());
+        cmd
+    };
+
+    auth(&["status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("api  not logged in"))
+        .stdout(predicate::str::contains("gh   expired (will refresh)"))
+        .stdout(predicate::str::contains("scopes: repo read"))
+        .stdout(predicate::str::contains("fs").not());
+
+    let output = auth(&["list", "--json"]).output().unwrap();
+    assert!(output.status.success());
+    let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(rows.as_array().unwrap().len(), 1);
+    assert_eq!(rows[0]["server"], "gh");
+    assert_eq!(rows[0]["state"], "refreshable");
+    assert_eq!(rows[0]["scopes"], serde_json::json!(["repo", "read"]));
+
+    auth(&["status", "missing"]).assert().failure();
+
+    auth(&["logout", "api"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Not logged in to 'api'"));
+    auth(&["logout", "--all"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Logged out of 'gh'"));
+    assert!(!mcplug_dir.join("gh").join("tokens.json").exists());
+    auth(&["list"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("No cached tokens."));
+}
+
+#[test]
+fn auth_identities_are_separate_token_sets() {
+    let home = tempfile::tempdir().unwrap();
+    let gh_dir = home.path().join(".mcplug").join("gh");
+    std::fs::create_dir_all(&gh_dir).unwrap();
+    std::fs::write(
+        home.path().join(".mcplug").join("mcplug.json"),
+        r#"{"mcpServers": {"gh": {"baseUrl": "https://gh.example.com/mcp"}}}"#,
+    )
+    .unwrap();
+    for file in ["tokens.json", "tokens.work.json"] {
+        std::fs::write(
+            gh_dir.join(file),
+            r#"{"access_token": "a", "token_type": "Bearer"}"#,
+        )
+        .unwrap();
+    }
+    let auth = |args: &[&str]| {
+        let mut cmd = mcplug_cmd();
+        cmd.arg("auth")
+            .args(args)
+            .env("HOME", home.path())
+            .current_dir(home.path());
+        cmd
+    };
+
+    let output = auth(&["list", "--json"]).output().unwrap();
+    let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let servers: Vec<&str> = rows
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["server"].as_str().unwrap())
+        .collect();
+    assert_eq!(servers, ["gh", "gh:work"]);
+
+    auth(&["status", "gh", "--as", "personal"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("gh:personal  not logged in"));
+    auth(&["status", "--as", "bad/name", "gh"]).assert().failure();
+
+    auth(&["logout", "gh", "--as", "work"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Logged out of 'gh:work'"));
+    assert!(!gh_dir.join("tokens.work.json").exists());
+    assert!(gh_dir.join("tokens.json").exists());
+}
+
+/// mcplug search across configured servers
+#[test]
+fn search_finds_tool_by_description() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    mcplug_cmd()
+        .args(["search", "numbers"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mock.add"));
+}
+
+/// mcplug call --explain prints the plan, and a declined call fails when
+/// the answer was piped in
+#[test]
+fn call_explain_declined_does_not_execute() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    mcplug_cmd()
+        .args(["call", "mock.add", "a:3", "b:4", "--explain"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .write_stdin("n\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Execution plan for mock.add"))
+        .stderr(predicate::str::contains("Call not confirmed"))
+        .stdout(predicate::str::contains("7").not());
+}
+
+/// --explain needs stdin for its answer, so arguments can't be read from it
+#[test]
+fn call_explain_rejects_stdin_args() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    for args in [
+        &["call", "mock.echo", "--stdin", "--explain"][..],
+        &["call", "mock.echo", "--args-json", "@-", "--explain"],
+        &["call", "mock.echo", "input:@-", "--explain"],
+    ] {
+        mcplug_cmd()
+            .args(args)
+            .env("MCPLUG_CONFIG", &config_path)
+            .write_stdin("y\n")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("arguments can't come from stdin"))
+            .stderr(predicate::str::contains("Execution plan").not());
+    }
+}
+
+/// mcplug call --explain executes after confirmation
+#[test]
+fn call_explain_confirmed_executes() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    mcplug_cmd()
+        .args(["call", "mock.add", "a:3", "b:4", "--explain"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .write_stdin("y\n")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Validation: ok"))
+        .stdout(predicate::str::contains("7"));
+}
+
+/// mcplug info --json reports protocol version and counts
+#[test]
+fn info_json_output() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    mcplug_cmd()
+        .args(["info", "mock", "--json"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"protocolVersion\": \"2024-11-05\""))
+        .stdout(predicate::str::contains("\"toolCount\": 6"))
+        .stdout(predicate::str::contains("\"resourceCount\": 1"))
+        .stdout(predicate::str::contains("\"promptCount\": 2"));
+}
+
+/// mcplug __complete completes server.tool references from the live catalog
+#[test]
+fn complete_tool_refs() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    let home = tempfile::tempdir().unwrap();
+    mcplug_cmd()
+        .args(["__complete", "--", "call", "mo"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("HOME", home.path())
+        .assert()
+        .success()
+        .stdout("mock.\n");
+    mcplug_cmd()
+        .args(["__complete", "--", "call", "mock.e"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("HOME", home.path())
+        .assert()
+        .success()
+        .stdout("mock.echo\nmock.error\n");
+    assert!(home.path().join(".mcplug/mock/tool-names.json").exists());
+}
+
+/// Secret references are only resolved for the server being connected, and
+/// never while completing
+#[cfg(unix)]
+#[test]
+fn secrets_resolve_only_for_the_connected_server() {
+    let home = tempfile::tempdir().unwrap();
+    let marker = home.path().join("ran");
+    let mut config = common::mock_stdio_config("mock");
+    let mut locked = config.mcp_servers["mock"].clone();
+    locked.env.insert(
+        "TOKEN".into(),
+        format!("${{cmd:touch {} && exit 1}}", marker.display()),
+    );
+    config.mcp_servers.insert("locked".into(), locked);
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["__complete", "--", "call", "locked."])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("HOME", home.path())
+        .assert()
+        .success()
+        .stdout("");
+    assert!(!marker.exists());
+
+    mcplug_cmd()
+        .args(["call", "mock.echo", "message=hi"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("HOME", home.path())
+        .assert()
+        .success();
+    assert!(!marker.exists());
+
+    mcplug_cmd()
+        .args(["list", "locked"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("HOME", home.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot read command"));
+    assert!(marker.exists());
+}
+
+/// mcplug completions prints a script that calls back into __complete
+#[test]
+fn completions_bash_script() {
+    mcplug_cmd()
+        .args(["completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mcplug __complete"));
+}
+
+/// mcplug call warns when arguments exceed the configured size threshold
+#[test]
+fn call_warns_on_large_arguments() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    mcplug_cmd()
+        .args(["call", "mock.echo", "input:hello world"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("MCPLUG_WARN_ARGS_BYTES", "10")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hello world"))
+        .stderr(predicate::str::contains(
+            "Warning: arguments for mock.echo are 23 bytes (threshold 10 bytes)",
+        ));
+}
+
+/// mcplug call --args-json passes the whole argument object through
+#[test]
+fn call_with_args_json() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    mcplug_cmd()
+        .args(["call", "mock.add", "--args-json", r#"{"a": 2, "b": 40}"#])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("42"));
+
+    let args_path = config_dir.path().join("args.json");
+    std::fs::write(&args_path, r#"{"input": "from file"}"#).unwrap();
+    mcplug_cmd()
+        .args(["call", "mock.echo", "--args-json"])
+        .arg(format!("@{}", args_path.display()))
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("from file"));
+}
+
+/// mcplug call reads key:@- values from stdin
+#[test]
+fn call_reads_argument_value_from_stdin() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    mcplug_cmd()
+        .args(["call", "mock.echo", "input:@-"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .write_stdin("piped: text")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("piped: text"));
+}
+
+/// mcplug config add test-connects, lists tools, and saves after confirmation
+#[test]
+fn config_add_wizard_validates_connection() {
+    let home = tempfile::tempdir().unwrap();
+    let mock_bin = common::mock_server_bin_path();
+    let input = format!("mock\nstdio\n{}\n\n\ny\n\n", mock_bin.display());
+    mcplug_cmd()
+        .args(["config", "add"])
+        .env("HOME", home.path())
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Connected to mock-server v1.0.0"))
+        .stdout(predicate::str::contains("Discovered 6 tool(s):"))
+        .stdout(predicate::str::contains("add — Add two numbers"))
+        .stdout(predicate::str::contains("suggested: ephemeral"))
+        .stdout(predicate::str::contains("Server 'mock' added to"));
+    let saved = std::fs::read_to_string(home.path().join(".mcplug/mcplug.json")).unwrap();
+    assert!(saved.contains("mock_mcp_server"));
+}
+
+/// mcplug config add does not save an unreachable server unless asked to
+#[test]
+fn config_add_wizard_rejects_unreachable_server() {
+    let home = tempfile::tempdir().unwrap();
+    mcplug_cmd()
+        .args(["config", "add"])
+        .env("HOME", home.path())
+        .write_stdin("broken\nstdio\n/nonexistent/mcp-server\n\n\n\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Connection failed"))
+        .stdout(predicate::str::contains("Server 'broken' not added."));
+    assert!(!home.path().join(".mcplug/mcplug.json").exists());
+}
+
+/// mcplug call --stdin reads the argument object from stdin
+#[test]
+fn call_with_stdin_args() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    mcplug_cmd()
+        .args(["call", "mock.add", "--stdin"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .write_stdin(r#"{"a": 20, "b": 22}"#)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("42"));
+
+    mcplug_cmd()
+        .args(["call", "mock.add", "--stdin"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .write_stdin("[1, 2]")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid JSON arguments in stdin"));
+}
+
+/// mcplug batch runs each JSONL line and prints results in input order
+#[test]
+fn batch_runs_jsonl_in_order() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    let batch_path = config_dir.path().join("calls.jsonl");
+    std::fs::write(
+        &batch_path,
+        concat!(
+            r#"{"tool": "mock.slow", "args": {"delay_ms": 300}}"#, "\n",
+            r#"{"tool": "mock.add", "args": {"a": 1, "b": 2}}"#, "\n",
+            "\n",
+            r#"{"tool": "mock.echo", "args": {"input": "hi"}}"#, "\n",
+        ),
+    )
+    .unwrap();
+    let output = mcplug_cmd()
+        .args(["batch", "--parallel", "2"])
+        .arg(&batch_path)
+        .env("MCPLUG_CONFIG", &config_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let lines: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["tool"], "mock.slow");
+    assert_eq!(lines[1]["result"]["content"][0]["text"], "3");
+    assert_eq!(lines[2]["line"], 4);
+    assert!(lines.iter().all(|l| l["ok"] == true));
+}
+
+/// mcplug batch reports failing lines and exits non-zero
+#[test]
+fn batch_reports_failures() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    let batch_path = config_dir.path().join("calls.jsonl");
+    std::fs::write(
+        &batch_path,
+        "{\"tool\": \"mock.echo\", \"args\": {\"input\": \"ok\"}}\nnot json\n",
+    )
+    .unwrap();
+    mcplug_cmd()
+        .arg("batch")
+        .arg(&batch_path)
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("\"ok\":true"))
+        .stdout(predicate::str::contains("Invalid batch line"))
+        .stderr(predicate::str::contains("1 of 2 batch calls failed"));
+}
+
+/// mcplug server disable/enable/rename/remove edit the defining config file
+#[test]
+fn server_group_edits_config_file() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    let home = tempfile::tempdir().unwrap();
+    let server = |args: &[&str]| {
+        let mut cmd = mcplug_cmd();
+        cmd.arg("server")
+            .args(args)
+            .env("MCPLUG_CONFIG", &config_path)
+            .env("HOME", home.path());
+        cmd
+    };
+    let read_config = || -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap()
+    };
+
+    server(&["disable", "mock"]).assert().success();
+    assert_eq!(read_config()["mcpServers"]["mock"]["disabled"], true);
+    mcplug_cmd()
+        .args(["call", "mock.echo", "input:hi"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("HOME", home.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Server 'mock' is disabled"));
+    server(&["show", "mock"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Status: disabled"));
+
+    server(&["enable", "mock"]).assert().success();
+    assert!(read_config()["mcpServers"]["mock"].get("disabled").is_none());
+
+    server(&["test", "mock"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Discovered 6 tool(s):"));
+
+    server(&["rename", "mock", "renamed"]).assert().success();
+    assert!(read_config()["mcpServers"].get("mock").is_none());
+    assert!(read_config()["mcpServers"].get("renamed").is_some());
+
+    server(&["remove", "renamed"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Server 'renamed' removed"));
+    assert_eq!(read_config()["mcpServers"], serde_json::json!({}));
+
+    server(&["remove", "renamed"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
+}
+
+/// mcplug call --save-as stores a result for result show and @result: references
+#[test]
+fn saved_results_can_be_shown_and_referenced() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    let home = tempfile::tempdir().unwrap();
+    let cmd = |args: &[&str]| {
+        let mut cmd = mcplug_cmd();
+        cmd.args(args)
+            .env("MCPLUG_CONFIG", &config_path)
+            .env("HOME", home.path());
+        cmd
+    };
+
+    cmd(&["call", "mock.echo", "input:chained value", "--save-as", "first"])
+        .assert()
+        .success();
+    cmd(&["result", "list"]).assert().success().stdout("first\n");
+    cmd(&["result", "show", "first"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("chained value"));
+    cmd(&["result", "show", "first", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tool\": \"mock.echo\""));
+
+    cmd(&["call", "mock.echo", "input:@result:first.text"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("chained value"));
+
+    cmd(&["result", "show", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No saved result named 'missing'"));
+}
+
+/// Calls are recorded and can be queried and summarized with mcplug history
+#[test]
+fn history_records_and_filters_calls() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    let home = tempfile::tempdir().unwrap();
+    let cmd = |args: &[&str]| {
+        let mut cmd = mcplug_cmd();
+        cmd.args(args)
+            .env("MCPLUG_CONFIG", &config_path)
+            .env("HOME", home.path());
+        cmd
+    };
+
+    cmd(&["call", "mock.echo", "input:hi"]).assert().success();
+    cmd(&["call", "mock.error"]).assert().success();
+
+    cmd(&["history", "query", "--since", "1h"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mock.echo  ok"))
+        .stdout(predicate::str::contains("mock.error  FAILED"));
+    cmd(&["history", "query", "--failed"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mock.echo").not())
+        .stdout(predicate::str::contains("mock.error"));
+    cmd(&["history", "query", "--tool", "echo", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"durationMs\""))
+        .stdout(predicate::str::contains("mock.error").not());
+    cmd(&["history", "stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Calls: 2 since"))
+        .stdout(predicate::str::contains("mock.error  1 calls  1 failed"))
+        .stdout(predicate::str::contains("history  2 history entries"));
+    cmd(&["history", "query", "--since", "soon"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid age 'soon'"));
+}
+
+/// With the sqlite backend, history lives in state.db and is queried there
+#[cfg(feature = "sqlite")]
+#[test]
+fn history_in_sqlite_store() {
+    let state_dir = tempfile::tempdir().unwrap();
+    let mut config = common::mock_stdio_config("mock");
+    config.state = Some(mcplug::state::StateConfig {
+        backend: mcplug::state::StateBackend::Sqlite,
+        path: Some(state_dir.path().to_path_buf()),
+    });
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+    let cmd = |args: &[&str]| {
+        let mut cmd = mcplug_cmd();
+        cmd.args(args).env("MCPLUG_CONFIG", &config_path);
+        cmd
+    };
+
+    cmd(&["call", "mock.echo", "input:hi"]).assert().success();
+    cmd(&["call", "mock.error"]).assert().success();
+
+    assert!(state_dir.path().join("state.db").exists());
+    assert!(!state_dir.path().join("history").exists());
+    cmd(&["history", "query", "--failed"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mock.echo").not())
+        .stdout(predicate::str::contains("mock.error  FAILED"));
+    cmd(&["history", "stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Calls: 2 since"))
+        .stdout(predicate::str::contains("history  2 history entries"));
+}
+
+/// sensitiveArgs values are masked in --explain and stored as hashes in history
+#[test]
+fn sensitive_args_are_redacted() {
+    let mut config = common::mock_stdio_config("mock");
+    config.mcp_servers.get_mut("mock").unwrap().sensitive_args = vec!["input".into()];
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+    let home = tempfile::tempdir().unwrap();
+    let cmd = |args: &[&str]| {
+        let mut cmd = mcplug_cmd();
+        cmd.args(args)
+            .env("MCPLUG_CONFIG", &config_path)
+            .env("HOME", home.path());
+        cmd
+    };
+
+    cmd(&["call", "mock.echo", "input:hunter2", "--explain"])
+        .write_stdin("y\n")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("<redacted hmac:"))
+        .stderr(predicate::str::contains("hunter2").not())
+        .stdout(predicate::str::contains("hunter2"));
+    cmd(&["history", "query", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"input\": \"<redacted hmac:"))
+        .stdout(predicate::str::contains("hunter2").not());
+}
+
+/// diagnose writes a sanitized report with the exchanges and server stderr
+#[test]
+fn diagnose_writes_sanitized_report() {
+    let mut config = common::mock_stdio_config("mock");
+    config.mcp_servers.get_mut("mock").unwrap().env =
+        std::collections::HashMap::from([("MOCK_STDERR".to_string(), "token sk-secret-42".to_string())]);
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+    let report_path = config_dir.path().join("report.json");
+
+    mcplug_cmd()
+        .args(["diagnose", "mock", "-o"])
+        .arg(&report_path)
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Wrote diagnostics for mock"));
+    let text = std::fs::read_to_string(&report_path).unwrap();
+    assert!(!text.contains("sk-secret-42"));
+    let report: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(report["serverInfo"]["name"], "mock-server");
+    assert_eq!(report["tools"]["count"], 6);
+    assert_eq!(report["exchanges"][0]["method"], "initialize");
+    assert!(report["capabilities"]["logging"].is_object());
+    assert!(report["timing"]["initializeMs"].is_u64());
+    assert_eq!(report["stderr"][0], "<redacted>");
+
+    mcplug_cmd()
+        .args(["diagnose", "mock", "--stdio", "/nonexistent/server"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("\"code\": \"connection_refused\""));
+}
+
+/// Credentials in a server's command line or URL query stay out of the report
+#[test]
+fn diagnose_redacts_url_and_arg_credentials() {
+    let mut config = common::mock_stdio_config("mock");
+    config.mcp_servers.get_mut("mock").unwrap().args =
+        vec!["--token".into(), "tok-arg-77".into(), "--mode=sk-eq-88".into()];
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+
+    let output = mcplug_cmd()
+        .args(["diagnose", "mock"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let text = String::from_utf8_lossy(&output.stdout);
+    assert!(!text.contains("tok-arg-77"), "{text}");
+    assert!(!text.contains("sk-eq-88"), "{text}");
+    assert!(text.contains("--token <redacted> --mode=<redacted>"), "{text}");
+
+    let output = mcplug_cmd()
+        .args([
+            "diagnose",
+            "adhoc",
+            "--http-url",
+            "http://127.0.0.1:1/mcp?api_key=sk-url-99",
+        ])
+        .env("MCPLUG_CONFIG", &config_path)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let text = String::from_utf8_lossy(&output.stdout);
+    assert!(!text.contains("sk-url-99"), "{text}");
+    assert!(text.contains("/mcp?api_key=<redacted>"), "{text}");
+}
+
+#[test]
+fn conformance_reports_checks() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["conformance", "mock"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("PASS  unknown method is rejected"))
+        .stdout(predicate::str::contains("PASS  pagination completes - 6 tools on 1 page"))
+        .stdout(predicate::str::contains("0 failed"));
+
+    let output = mcplug_cmd()
+        .args(["conformance", "mock", "--json"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["failed"], 0);
+    assert_eq!(report["checks"][0]["section"], "initialize");
+    assert_eq!(report["checks"][0]["status"], "pass");
+}
+
+/// --env overrides the configured env of the spawned stdio server
+#[test]
+fn env_overrides_reach_stdio_server() {
+    let mut config = common::mock_stdio_config("mock");
+    config.mcp_servers.get_mut("mock").unwrap().env =
+        std::collections::HashMap::from([("MOCK_STDERR".to_string(), "configured".to_string())]);
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["call", "mock.echo", "input:x", "--env", "MOCK_STDERR=overridden"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("overridden"))
+        .stderr(predicate::str::contains("configured").not());
+    mcplug_cmd()
+        .args(["list", "mock", "--env", "MOCK_STDERR=from-list"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("from-list"));
+    mcplug_cmd()
+        .args(["call", "mock.echo", "input:x", "--env", "NOEQUALS"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected KEY=VALUE"));
+    mcplug_cmd()
+        .args(["call", "x.echo", "--stdio", "cat", "--env", "A=1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+/// -v raises the log level and MCPLUG_LOG_FORMAT=json logs JSON lines
+#[test]
+fn verbose_json_logging() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["-v", "call", "mock.echo", "input:hi"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("MCPLUG_LOG_FORMAT", "json")
+        .assert()
+        .success()
+        .stdout("hi\n")
+        .stderr(predicate::str::contains(r#""level":"INFO""#))
+        .stderr(predicate::str::contains(r#""message":"tool call payload sizes""#));
+    // Global flags work after the subcommand too; -q hides info logs
+    mcplug_cmd()
+        .args(["call", "mock.echo", "input:hi", "-q"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("MCPLUG_LOG_LEVEL", "info")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("tool call payload sizes").not());
+    mcplug_cmd()
+        .args(["-q", "-v", "list"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+/// A server's timeoutMs overrides the default call timeout
+#[test]
+fn per_server_timeout_applies_to_call() {
+    let mut config = common::mock_stdio_config("mock");
+    config.mcp_servers.get_mut("mock").unwrap().timeout_ms = Some(300);
+    let config_dir = common::temp_config_dir(&config);
+
+    mcplug_cmd()
+        .args(["call", "mock.slow", "delay_ms:5000"])
+        .env("MCPLUG_CONFIG", config_dir.path().join("mcplug.json"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Timeout after 300ms calling mock.slow"));
+}
+
+/// Ctrl-C during a call cancels it and shuts the server down instead of waiting
+#[cfg(unix)]
+#[test]
+fn interrupted_call_is_cancelled() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    #[allow(deprecated)]
+    let child = std::process::Command::new(assert_cmd::cargo::cargo_bin("mcplug"))
+        .args(["call", "mock.slow", "delay_ms:10000"])
+        .env("MCPLUG_CONFIG", config_dir.path().join("mcplug.json"))
+        .env("HOME", scratch_home())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Give it time to connect and send the call
+    std::thread::sleep(std::time::Duration::from_millis(1000));
+    let started = std::time::Instant::now();
+    std::process::Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Call to mock.slow was cancelled"), "stderr: {stderr}");
+}
+
+/// Cleartext http:// servers need allowHttp in config or the global --allow-http flag
+#[test]
+fn cleartext_http_server_requires_allow_http() {
+    let config: mcplug::McplugConfig = serde_json::from_value(serde_json::json!({
+        "mcpServers": {"lan": {"baseUrl": "http://127.0.0.1:9/mcp"}}
+    }))
+    .unwrap();
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["list", "lan"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .env_remove("MCPLUG_ALLOW_HTTP")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cleartext HTTP is not allowed"));
+    // With the flag the connection is attempted (and refused)
+    mcplug_cmd()
+        .args(["list", "lan", "--allow-http"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cleartext HTTP is not allowed").not());
+}
+
+/// Disabled servers are left out of list and search, and naming one is an error
+#[test]
+fn disabled_server_excluded_from_list_and_search() {
+    let mut config = common::mock_stdio_config("mock");
+    let mut parked = config.mcp_servers["mock"].clone();
+    parked.disabled = true;
+    config.mcp_servers.insert("parked".to_string(), parked);
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["list", "--json"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"mock\""))
+        .stdout(predicate::str::contains("parked").not());
+    mcplug_cmd()
+        .args(["search", "echo"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mock.echo"))
+        .stdout(predicate::str::contains("parked").not());
+    mcplug_cmd()
+        .args(["list", "parked"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Server 'parked' is disabled"));
+}
+
+/// list --tag and search --tag only consider servers with a matching tag
+#[test]
+fn tag_filters_list_and_search() {
+    let mut config = common::mock_stdio_config("mock");
+    let mut other = config.mcp_servers["mock"].clone();
+    config.mcp_servers.get_mut("mock").unwrap().tags = vec!["search".to_string()];
+    other.tags = vec!["internal".to_string()];
+    config.mcp_servers.insert("other".to_string(), other);
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["list", "--tag", "search", "--json"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"mock\""))
+        .stdout(predicate::str::contains("other").not());
+    mcplug_cmd()
+        .args(["search", "echo", "--tag", "internal"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("other.echo"))
+        .stdout(predicate::str::contains("mock.echo").not());
+    mcplug_cmd()
+        .args(["list", "--tag", "nope"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No enabled servers are tagged nope"));
+    mcplug_cmd()
+        .args(["list", "mock", "--tag", "search"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+/// mcplug pipe binds the text output of one stage into the next
+#[test]
+fn pipe_binds_previous_output() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args([
+            "pipe",
+            "mock.add(a: 2, b: 3) | mock.echo(input: \"sum=$out\")",
+        ])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sum=5"));
+}
+
+/// mcplug pipe stops at the first failing stage
+#[test]
+fn pipe_stops_on_error() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["pipe", "mock.error | mock.echo(input: $out)"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Pipeline stage 1 (mock.error) failed"));
+}
+
+/// pipe and batch refuse a pinned tool whose schema drifted, like call
+#[test]
+fn pipe_and_batch_refuse_drifted_schema() {
+    let mut config = common::mock_stdio_config("mock");
+    config
+        .mcp_servers
+        .get_mut("mock")
+        .unwrap()
+        .tool_pins
+        .insert("echo".into(), "sha256:0000".into());
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["pipe", "mock.add(a: 2, b: 3) | mock.echo(input: $out)"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("sha256:0000"));
+
+    let batch_path = config_dir.path().join("calls.jsonl");
+    std::fs::write(
+        &batch_path,
+        concat!(
+            r#"{"tool": "mock.add", "args": {"a": 1, "b": 2}}"#, "\n",
+            r#"{"tool": "mock.echo", "args": {"input": "hi"}}"#, "\n",
+        ),
+    )
+    .unwrap();
+    mcplug_cmd()
+        .arg("batch")
+        .arg(&batch_path)
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("\"ok\":true"))
+        .stdout(predicate::str::contains("pin_mismatch"));
+}
+
+/// vars() in a pipeline captures values from the previous result by JSONPath
+#[test]
+fn pipe_vars_capture_jsonpath() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args([
+            "pipe",
+            r#"mock.echo(input: '{"issue": {"id": 41}}') | vars(id: $.issue.id) | mock.echo(input: "filed #$id")"#,
+        ])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("filed #41"));
+}
+
+/// mcplug watch re-runs the call on one connection and marks changed lines
+#[test]
+fn watch_reruns_and_diffs() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["watch", "mock.counter", "--interval", "0.1", "--count", "3", "--diff"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--- Every 0.1s: mock.counter"))
+        .stdout(predicate::str::contains("- 1\n+ 2"))
+        .stdout(predicate::str::contains("- 2\n+ 3"));
+}
+
+/// A stdio server that never answers is abandoned after its installTimeout,
+/// with its startup stderr shown prefixed by the server name
+#[cfg(unix)]
+#[test]
+fn install_timeout_reports_slow_startup() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("mcplug.json");
+    std::fs::write(
+        &config_path,
+        r#"{"mcpServers": {"slow": {
+            "command": "sh",
+            "args": ["-c", "echo resolving packages >&2; sleep 10"],
+            "installTimeout": 1
+        }}}"#,
+    )
+    .unwrap();
+
+    mcplug_cmd()
+        .args(["call", "slow.anything"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .timeout(std::time::Duration::from_secs(8))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("[slow] resolving packages"))
+        .stderr(predicate::str::contains("install timeout of 1s"));
+}
+
+/// mcplug bench reports latency percentiles and error rate
+#[test]
+fn bench_reports_percentiles() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+
+    let output = mcplug_cmd()
+        .args([
+            "bench", "mock.echo", "input:hi", "--warmup", "2", "-n", "10", "-c", "2", "--json",
+        ])
+        .env("MCPLUG_CONFIG", &config_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["iterations"], 10);
+    assert_eq!(report["concurrency"], 2);
+    assert_eq!(report["errors"], 0);
+    let latency = &report["latencyMs"];
+    assert!(latency["p99"].as_f64().unwrap() >= latency["p50"].as_f64().unwrap());
+
+    mcplug_cmd()
+        .args(["bench", "mock.error", "-n", "3", "--warmup", "0"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("errors    3 (100.0%)"));
+}
+
+/// A session recorded with --record can be replayed without the server
+#[test]
+fn record_and_replay_call_and_list() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    let call_recording = config_dir.path().join("call.json");
+    let list_recording = config_dir.path().join("list.json");
+
+    mcplug_cmd()
+        .args(["call", "mock.echo", "input:recorded", "--record"])
+        .arg(&call_recording)
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success();
+    mcplug_cmd()
+        .args(["list", "mock", "--json", "--record"])
+        .arg(&list_recording)
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success();
+
+    let recording: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&call_recording).unwrap()).unwrap();
+    let methods: Vec<&str> = recording["exchanges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["method"].as_str().unwrap())
+        .collect();
+    assert_eq!(methods, ["initialize", "tools/list", "tools/call"]);
+
+    // Replay against a config where the server no longer exists
+    let empty = tempfile::tempdir().unwrap();
+    let empty_config = empty.path().join("mcplug.json");
+    std::fs::write(&empty_config, r#"{"mcpServers": {}}"#).unwrap();
+
+    mcplug_cmd()
+        .args(["call", "mock.echo", "input:recorded", "--replay"])
+        .arg(&call_recording)
+        .env("MCPLUG_CONFIG", &empty_config)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("recorded"));
+    mcplug_cmd()
+        .args(["call", "mock.echo", "input:other", "--replay"])
+        .arg(&call_recording)
+        .env("MCPLUG_CONFIG", &empty_config)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No recorded response for tools/call"));
+    mcplug_cmd()
+        .args(["list", "mock", "--replay"])
+        .arg(&list_recording)
+        .env("MCPLUG_CONFIG", &empty_config)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("echo"));
+}
+
+/// mcplug prefetch skips servers that are not package-runner or Docker based
+#[test]
+fn prefetch_skips_plain_commands() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["prefetch"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mock: nothing to prefetch"));
+
+    mcplug_cmd()
+        .args(["prefetch", "missing"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure();
+}
+
+/// mcplug warm connects to every server and reports failures
+#[test]
+fn warm_connects_servers() {
+    let mut config = common::mock_stdio_config("mock");
+    let mut broken = config.mcp_servers["mock"].clone();
+    broken.command = Some("/nonexistent/mcp-server".into());
+    config.mcp_servers.insert("broken".into(), broken);
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["warm", "mock"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mock: ready in"));
+
+    mcplug_cmd()
+        .args(["warm"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("mock: ready in"))
+        .stderr(predicate::str::contains("1 of 2 servers failed to connect"));
+}
+
+/// Group names expand to their servers for list, server test, and prefetch
+#[test]
+fn groups_expand_to_member_servers() {
+    let mut config = common::mock_stdio_config("mock");
+    let mock = config.mcp_servers["mock"].clone();
+    config.mcp_servers.insert("mock2".into(), mock);
+    config
+        .groups
+        .insert("fleet".into(), vec!["mock2".into(), "mock".into()]);
+    config
+        .groups
+        .insert("broken".into(), vec!["mock".into(), "ghost".into()]);
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+    let cmd = |args: &[&str]| {
+        let mut cmd = mcplug_cmd();
+        cmd.args(args).env("MCPLUG_CONFIG", &config_path);
+        cmd
+    };
+
+    cmd(&["list", "fleet", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"total\": 2"))
+        .stdout(predicate::str::contains("\"reachable\": 2"));
+    cmd(&["server", "test", "fleet"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mock2:"))
+        .stdout(predicate::str::contains("Discovered"));
+    cmd(&["prefetch", "fleet"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mock2: nothing to prefetch"));
+    cmd(&["list", "broken"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("ghost (member of group 'broken')"));
+}
+
+/// --filter prints only the selected part of a JSON text result
+#[test]
+fn call_filter_selects_from_json_text() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    let call = |filter: &str| {
+        let mut cmd = mcplug_cmd();
+        cmd.args([
+            "call",
+            "mock.echo",
+            "--args-json",
+            r#"{"input": "{\"items\": [{\"url\": \"https://a\"}, {\"url\": \"https://b\"}]}"}"#,
+            "--filter",
+            filter,
+        ])
+        .env("MCPLUG_CONFIG", &config_path);
+        cmd
+    };
+
+    call(".items[1].url").assert().success().stdout("https://b\n");
+    call("$.items[*].url")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"https://a\","));
+    call(".missing")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("matched nothing"));
+    call("items")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid JSONPath"));
+
+    mcplug_cmd()
+        .args(["call", "mock.echo", "input:plain", "--filter", ".a"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--filter needs JSON"));
+}
+
+/// --save-images writes image blocks to files and prints their paths
+#[test]
+fn call_save_images_writes_files() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    let out = config_dir.path().join("images");
+
+    mcplug_cmd()
+        .args(["call", "mock.image", "--save-images"])
+        .arg(&out)
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("a pixel"))
+        .stdout(predicate::str::contains("image-1.png"));
+    assert_eq!(std::fs::read(out.join("image-1.png")).unwrap(), b"PNGDATA");
+
+    mcplug_cmd()
+        .args(["call", "mock.image", "--json", "--save-images"])
+        .arg(&out)
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"savedImages\""));
+
+    // Without the flag, only a placeholder is shown
+    mcplug_cmd()
+        .args(["call", "mock.image"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[image: image/png]"));
+}
+
+/// log-level sends logging/setLevel, and a configured logLevel is applied on connect
+#[test]
+fn log_level_command_and_config_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let level_file = dir.path().join("level");
+    let mut config = common::mock_stdio_config("mock");
+    let server = config.mcp_servers.get_mut("mock").unwrap();
+    server.env.insert(
+        "MOCK_LOG_LEVEL_FILE".into(),
+        level_file.to_string_lossy().into_owned(),
+    );
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["log-level", "mock", "debug"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout("Set mock log level to debug\n");
+    assert_eq!(std::fs::read_to_string(&level_file).unwrap(), "debug");
+
+    mcplug_cmd()
+        .args(["log-level", "mock", "loud"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid log level 'loud'"));
+
+    config.mcp_servers.get_mut("mock").unwrap().log_level = Some(mcplug::types::LogLevel::Error);
+    let config_dir = common::temp_config_dir(&config);
+    mcplug_cmd()
+        .args(["call", "mock.echo", "input:hi"])
+        .env("MCPLUG_CONFIG", config_dir.path().join("mcplug.json"))
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(&level_file).unwrap(), "error");
+}
+
+/// Fan-out exit policies: --fail-fast skips the rest, --require-any tolerates partial failure
+#[test]
+fn batch_fanout_policies() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    let batch_path = config_dir.path().join("calls.jsonl");
+    std::fs::write(
+        &batch_path,
+        concat!(
+            r#"{"tool": "mock.echo", "args": {"input": "a"}}"#, "\n",
+            r#"{"tool": "mock.error"}"#, "\n",
+            r#"{"tool": "mock.echo", "args": {"input": "b"}}"#, "\n",
+        ),
+    )
+    .unwrap();
+    let batch = |flag: &str| {
+        let mut cmd = mcplug_cmd();
+        cmd.args(["batch", flag])
+            .arg(&batch_path)
+            .env("MCPLUG_CONFIG", &config_path);
+        cmd
+    };
+
+    let output = batch("--fail-fast").output().unwrap();
+    assert!(!output.status.success());
+    let lines: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["ok"], true);
+    assert_eq!(lines[1]["ok"], false);
+    assert_eq!(lines[2]["skipped"], true);
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("1 of 3 batch calls failed, 1 skipped after --fail-fast"));
+
+    batch("--require-any").assert().success();
+    batch("--require-all")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("1 of 3 batch calls failed"));
+}
+
+/// list reports per-server error codes and applies --require-all
+#[test]
+fn list_partial_failure_summary() {
+    let mut config = common::mock_stdio_config("mock");
+    let mut broken = config.mcp_servers["mock"].clone();
+    broken.command = Some("/nonexistent/mcplug-test-server".into());
+    config.mcp_servers.insert("broken".into(), broken);
+    let config_dir = common::temp_config_dir(&config);
+    let config_path = config_dir.path().join("mcplug.json");
+
+    mcplug_cmd()
+        .args(["list", "--json"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"code\": \"connection_refused\""))
+        .stdout(predicate::str::contains("\"latencyMs\""))
+        .stdout(predicate::str::contains("\"unreachable\": 1"));
+    mcplug_cmd()
+        .args(["list", "--require-all"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("1 of 2 servers failed"));
+    mcplug_cmd()
+        .args(["list", "--json", "--fail-fast"])
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("\"status\": \"skipped\""));
+}
+
+/// generate-cli --compile writes a Cargo project and reports a failed build
+#[cfg(unix)]
+#[test]
+fn generate_cli_compile_writes_project() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    let tmp = tempfile::tempdir().unwrap();
+
+    mcplug_cmd()
+        .args(["generate-cli", "mock", "--compile", "--out"])
+        .arg(tmp.path().join("mock-cli"))
+        .env("MCPLUG_CONFIG", &config_path)
+        .env("TMPDIR", tmp.path())
+        .env("CARGO", "false")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cargo build failed"));
+
+    let project = tmp.path().join("mcplug-gen-mock");
+    let manifest = std::fs::read_to_string(project.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("name = \"mock\""));
+    assert!(manifest.contains(&format!(
+        "mcplug = {{ version = \"={}\", path = \"{}\" }}",
+        env!("CARGO_PKG_VERSION"),
+        env!("CARGO_MANIFEST_DIR")
+    )));
+    let source = std::fs::read_to_string(project.join("src/main.rs")).unwrap();
+    assert!(source.contains("pub struct AddArgs"));
+    assert!(!tmp.path().join("mock-cli").exists());
+}
+
+/// snapshot saves the catalog, and codegen reads it back without a config
+#[test]
+fn snapshot_feeds_offline_codegen() {
+    let config_dir = common::temp_config_dir(&common::mock_stdio_config("mock"));
+    let config_path = config_dir.path().join("mcplug.json");
+    let tmp = tempfile::tempdir().unwrap();
+    let snapshot = tmp.path().join("mock.json");
+
+    mcplug_cmd()
+        .args(["snapshot", "mock", "--output"])
+        .arg(&snapshot)
+        .env("MCPLUG_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote snapshot of"));
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&snapshot).unwrap()).unwrap();
+    assert_eq!(saved["server"], "mock");
+    assert!(saved["serverInfo"]["name"].is_string());
+    assert!(saved["tools"].as_array().unwrap().iter().any(|t| t["name"] == "add"));
+
+    // No config, so only the snapshot can supply the tools
+    let missing = tmp.path().join("missing.json");
+    mcplug_cmd()
+        .args(["emit-rs", "--from-snapshot"])
+        .arg(&snapshot)
+        .env("MCPLUG_CONFIG", &missing)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pub struct AddArgs"))
+        .stdout(predicate::str::contains("pub struct MockClient"));
+    mcplug_cmd()
+        .args(["generate-cli", "renamed", "--from-snapshot"])
+        .arg(&snapshot)
+        .env("MCPLUG_CONFIG", &missing)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"renamed\""));
+    mcplug_cmd()
+        .args(["emit-rs", "--from-snapshot"])
+        .arg(&missing)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot read snapshot"));
+}
+
+/// generate-cli --embed-config carries the server's config, unexpanded
+#[test]
+fn generate_cli_embeds_server_config() {
+    let mut config = common::mock_stdio_config("mock");
+    config
+        .mcp_servers
+        .get_mut("mock")
+        .unwrap()
+        .env
+        .insert("TOKEN".into(), "${MCPLUG_TEST_EMBED_TOKEN:-none}".into());
+    let config_dir = common::temp_config_dir(&config);
+
+    let output = mcplug_cmd()
+        .args(["generate-cli", "mock", "--embed-config"])
+        .env("MCPLUG_CONFIG", config_dir.path().join("mcplug.json"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let source = String::from_utf8(output.stdout).unwrap();
+    assert!(source.contains("const SERVER_CONFIG: &str = r#\""));
+    assert!(source.contains("\"TOKEN\": \"${MCPLUG_TEST_EMBED_TOKEN:-none}\""));
+    assert!(source.contains(&format!(
+        "\"command\": {:?}",
+        common::mock_server_bin_path().to_string_lossy()
+    )));
+}
+
+/// emit-rs --all writes a formatted module per server and an McpClients struct
+#[test]
+fn emit_rs_all_writes_module_per_server() {
+    let mut config = common::mock_stdio_config("mock");
+    let server = config.mcp_servers["mock"].clone();
+    config.mcp_servers.insert("mock-two".into(), server);
+    let config_dir = common::temp_config_dir(&config);
+    let out = tempfile::tempdir().unwrap();
+    let dir = out.path().join("mcp");
+
+    mcplug_cmd()
+        .args(["emit-rs", "--all", "--output"])
+        .arg(&dir)
+        .env("MCPLUG_CONFIG", config_dir.path().join("mcplug.json"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote Rust modules for 2 servers"));
+    let root = std::fs::read_to_string(dir.join("mod.rs")).unwrap();
+    assert!(root.contains("pub mod mock;\npub mod mock_two;\n"));
+    assert!(root.contains("pub mock_two: mock_two::MockTwoClient,"));
+    let module = std::fs::read_to_string(dir.join("mock_two.rs")).unwrap();
+    assert!(module.contains("pub struct MockTwoClient"));
+    // rustfmt collapses the empty structs emit-rs writes on two lines
+    assert!(module.contains("pub struct ErrorArgs {}"));
+
+    mcplug_cmd()
+        .args(["emit-rs", "--all"])
+        .env("MCPLUG_CONFIG", config_dir.path().join("mcplug.json"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--output"));
+}
+
+/// mock-server serves the tools of a spec file, here to mcplug itself over stdio
+#[test]
+fn mock_server_answers_from_spec() {
+    let tmp = tempfile::tempdir().unwrap();
+    let spec = tmp.path().join("weather.json");
+    std::fs::write(
+        &spec,
+        serde_json::json!({
+            "name": "weather",
+            "tools": [{
+                "name": "forecast",
+                "inputSchema": {"type": "object", "properties": {"city": {"type": "string"}}},
+                "responses": [
+                    {"when": {"city": "Taipei"}, "text": "Sunny in {{city}}"},
+                    {"error": "no forecast for {{city}}"}
+                ]
+            }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let server = format!("{} mock-server {}", env!("CARGO_BIN_EXE_mcplug"), spec.display());
+
+    mcplug_cmd()
+        .args(["call", "weather.forecast", "city=Taipei", "--stdio", &server])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Sunny in Taipei"));
+    mcplug_cmd()
+        .args(["call", "weather.forecast", "city=Oslo", "--stdio", &server])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no forecast for Oslo"));
+
+    mcplug_cmd()
+        .args(["mock-server"])
+        .arg(tmp.path().join("missing.yaml"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot read mock server spec"));
+}
+
+/// scaffold-server writes a server project and never overwrites one
+#[test]
+fn scaffold_server_writes_project() {
+    let tmp = tempfile::tempdir().unwrap();
+    let out = tmp.path().join("wrapped");
+
+    mcplug_cmd()
+        .args(["scaffold-server", "--from-command", "mytool --json", "--out"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote MCP server mytool-mcp with 1 tools"));
+    let manifest = std::fs::read_to_string(out.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("name = \"mytool-mcp\""));
+    let source = std::fs::read_to_string(out.join("src/main.rs")).unwrap();
+    assert!(source.contains("run_command(&[\"mytool\", \"--json\"], args)"));
+
+    mcplug_cmd()
+        .args(["scaffold-server", "--from-command", "other", "--out"])
+        .arg(&out)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+
+    let spec = tmp.path().join("api.yaml");
+    std::fs::write(&spec, "openapi: 3.0.0").unwrap();
+    mcplug_cmd()
+        .args(["scaffold-server", "--from-openapi"])
+        .arg(&spec)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not a JSON OpenAPI document"));
+}
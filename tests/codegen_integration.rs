@@ -1,7 +1,10 @@
 mod common;
 
 use mcplug::codegen::generate_cli::generate_cli_source;
+use mcplug::codegen::emit_openapi::emit_openapi;
+use mcplug::codegen::emit_py::emit_python;
 use mcplug::codegen::emit_rs::emit_rust_types;
+use mcplug::codegen::emit_ts::emit_typescript;
 
 /// I19: Generate CLI from mock server
 #[tokio::test]
@@ -59,3 +62,51 @@ async fn generated_rust_types_are_valid() {
     }
     runtime.close().await.unwrap();
 }
+
+/// TypeScript client from the mock server
+#[tokio::test]
+async fn generate_typescript_client_from_mock_server() {
+    let config = common::mock_stdio_config("mock");
+    let runtime = mcplug::Runtime::with_config(config);
+    let tools = runtime.list_tools("mock").await.unwrap();
+    let source = emit_typescript(&tools, "mock");
+    assert!(source.contains("export interface AddArgs {\n  a: number;\n  b: number;\n}"));
+    assert!(source.contains("export class MockClient {"));
+    for tool in &tools {
+        assert!(source.contains(&format!("this.transport.callTool(\"{}\", args)", tool.name)));
+    }
+    runtime.close().await.unwrap();
+}
+
+/// Python client from the mock server
+#[tokio::test]
+async fn generate_python_client_from_mock_server() {
+    let config = common::mock_stdio_config("mock");
+    let runtime = mcplug::Runtime::with_config(config);
+    let tools = runtime.list_tools("mock").await.unwrap();
+    let source = emit_python(&tools, "mock");
+    assert!(source.contains("class AddArgs(TypedDict):\n    a: float\n    b: float\n"));
+    assert!(source.contains("class MockClient:"));
+    for tool in &tools {
+        assert!(source.contains(&format!("self._transport.call_tool(\"{}\", args)", tool.name)));
+    }
+    runtime.close().await.unwrap();
+}
+
+/// OpenAPI document from the mock server
+#[tokio::test]
+async fn generate_openapi_from_mock_server() {
+    let config = common::mock_stdio_config("mock");
+    let runtime = mcplug::Runtime::with_config(config);
+    let tools = runtime.list_tools("mock").await.unwrap();
+    let info = runtime.server_info("mock").await.unwrap();
+    let doc: serde_json::Value = serde_json::from_str(&emit_openapi(&tools, "mock", &info)).unwrap();
+    assert_eq!(doc["openapi"], "3.1.0");
+    assert_eq!(doc["info"]["version"], info.version);
+    for tool in &tools {
+        let op = &doc["paths"][format!("/tools/{}", tool.name)]["post"];
+        assert_eq!(op["operationId"], tool.name.as_str());
+        assert_eq!(op["requestBody"]["content"]["application/json"]["schema"], tool.input_schema);
+    }
+    runtime.close().await.unwrap();
+}
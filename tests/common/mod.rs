@@ -18,11 +18,27 @@ pub fn mock_stdio_config(server_name: &str) -> McplugConfig {
             env: HashMap::new(),
             headers: HashMap::new(),
             lifecycle: None,
+            tool_pins: HashMap::new(),
+            disabled: false,
+            install_timeout: None,
+            log_level: None,
+            sensitive_args: Vec::new(),
+            timeout_ms: None,
+            list_timeout_ms: None,
+            allow_http: None,
+            auth: None,
+            tags: Vec::new(),
+            transport: None,
+            retry: None,
         },
     );
     McplugConfig {
         mcp_servers: servers,
         imports: vec![],
+        state: None,
+        groups: HashMap::new(),
+        extends: Vec::new(),
+        defaults: None,
     }
 }
 
@@ -40,8 +40,7 @@ async fn daemon_persistent_state() {
 /// I17: Same instance across calls
 /// Verifying that the runtime reuses the same server connection.
 /// The first call initializes and returns the real server name ("mock-server"),
-/// while subsequent calls return a placeholder with the config key.
-/// Both calls should succeed, demonstrating connection reuse.
+/// and subsequent calls return the same info from the open connection.
 #[tokio::test]
 async fn daemon_same_instance_across_calls() {
     let config = common::mock_stdio_config("mock");
@@ -50,8 +49,9 @@ async fn daemon_same_instance_across_calls() {
     // First call initializes the transport and returns the real server name
     assert_eq!(info1.name, "mock-server");
     let info2 = runtime.server_info("mock").await.unwrap();
-    // Second call reuses the connection (placeholder returned)
-    assert_eq!(info2.name, "mock");
+    // Second call reuses the connection and its initialize result
+    assert_eq!(info2.name, "mock-server");
+    assert_eq!(info2.version, info1.version);
     runtime.close().await.unwrap();
 }
 
@@ -4,6 +4,10 @@ use std::sync::atomic::{AtomicU64, Ordering};
 static COUNTER: AtomicU64 = AtomicU64::new(0);
 
 fn main() {
+    // Lets tests see what the client captures from stderr
+    if let Ok(message) = std::env::var("MOCK_STDERR") {
+        eprintln!("{message}");
+    }
     let stdin = io::stdin();
     let stdout = io::stdout();
 
@@ -26,6 +30,12 @@ fn main() {
 
         // Notifications have no id — skip response
         if id.is_none() {
+            // Lets tests observe cancellations, which get no reply
+            if method == "notifications/cancelled" {
+                if let Ok(path) = std::env::var("MOCK_CANCEL_FILE") {
+                    std::fs::write(path, req["params"].to_string()).unwrap();
+                }
+            }
             continue;
         }
 
@@ -36,7 +46,7 @@ fn main() {
                 "result": {
                     "protocolVersion": "2024-11-05",
                     "serverInfo": { "name": "mock-server", "version": "1.0.0" },
-                    "capabilities": { "tools": {} }
+                    "capabilities": { "tools": {}, "resources": {}, "prompts": {}, "logging": {} }
                 }
             }),
             "tools/list" => serde_json::json!({
@@ -87,10 +97,43 @@ fn main() {
                             "name": "counter",
                             "description": "Stateful counter",
                             "inputSchema": { "type": "object", "properties": {} }
+                        },
+                        {
+                            "name": "image",
+                            "description": "Caption and a small PNG",
+                            "inputSchema": { "type": "object", "properties": {} }
                         }
                     ]
                 }
             }),
+            "ping" => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": {} }),
+            "logging/setLevel" => {
+                // Lets tests observe the level without a tool of its own
+                if let Ok(path) = std::env::var("MOCK_LOG_LEVEL_FILE") {
+                    let level = req["params"]["level"].as_str().unwrap_or("");
+                    std::fs::write(path, level).unwrap();
+                }
+                serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+            }
+            "resources/list" => serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": {
+                    "resources": [
+                        { "uri": "mock://readme", "name": "readme", "mimeType": "text/plain" }
+                    ]
+                }
+            }),
+            "prompts/list" => serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": {
+                    "prompts": [
+                        { "name": "greet", "description": "Greeting prompt" },
+                        { "name": "summarize", "description": "Summarize text" }
+                    ]
+                }
+            }),
             "tools/call" => {
                 let params = &req["params"];
                 let tool_name = params["name"].as_str().unwrap_or("");
@@ -151,6 +194,18 @@ fn main() {
                             }
                         })
                     }
+                    "image" => serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "content": [
+                                { "type": "text", "text": "a pixel" },
+                                // base64 of "PNGDATA"
+                                { "type": "image", "data": "UE5HREFUQQ==", "mimeType": "image/png" }
+                            ],
+                            "isError": false
+                        }
+                    }),
                     _ => serde_json::json!({
                         "jsonrpc": "2.0",
                         "id": id,
@@ -166,6 +221,16 @@ fn main() {
         };
 
         let mut out = stdout.lock();
+        // Lets tests subscribe to notifications, sent ahead of call results
+        if method == "tools/call" && std::env::var_os("MOCK_NOTIFY_ON_CALL").is_some() {
+            let notification = serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/message",
+                "params": { "level": "info", "data": format!("calling {}", req["params"]["name"]) }
+            });
+            serde_json::to_writer(&mut out, &notification).unwrap();
+            out.write_all(b"\n").unwrap();
+        }
         serde_json::to_writer(&mut out, &response).unwrap();
         out.write_all(b"\n").unwrap();
         out.flush().unwrap();
@@ -42,6 +42,69 @@ async fn call_echo_tool_over_stdio() {
     runtime.close().await.unwrap();
 }
 
+/// Typed calls deserialize the result or fail with a typed error
+#[tokio::test]
+async fn call_tool_as_deserializes_result() {
+    #[derive(Debug, serde::Deserialize)]
+    struct Greeting {
+        hello: String,
+    }
+
+    let config = common::mock_stdio_config("mock");
+    let runtime = Runtime::with_config(config);
+    let sum: f64 = runtime
+        .call_tool_as("mock", "add", serde_json::json!({"a": 3, "b": 4}))
+        .await
+        .unwrap();
+    assert_eq!(sum, 7.0);
+
+    let proxy = mcplug::ServerProxy::new(&runtime, "mock");
+    let greeting: Greeting = proxy
+        .call_as("echo", serde_json::json!({"input": r#"{"hello": "world"}"#}))
+        .await
+        .unwrap();
+    assert_eq!(greeting.hello, "world");
+
+    let err = proxy
+        .call_as::<Greeting>("add", serde_json::json!({"a": 1, "b": 2}))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, mcplug::McplugError::ResultMismatch { ref tool, .. } if tool == "add"));
+
+    let err = proxy
+        .call_as::<serde_json::Value>("error", serde_json::json!({}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("forced error"));
+    runtime.close().await.unwrap();
+}
+
+/// ServerProxy lists tools and refuses calls to tools the server lacks
+#[tokio::test]
+async fn server_proxy_checks_tools_exist() {
+    let runtime = Runtime::with_config(common::mock_stdio_config("mock"));
+    let proxy = mcplug::ServerProxy::new(&runtime, "mock");
+    let tools = proxy.list_tools().await.unwrap();
+    assert!(tools.iter().any(|t| t.name == "add"));
+    assert!(proxy.has_tool("echo").await.unwrap());
+    assert!(!proxy.has_tool("ech").await.unwrap());
+
+    let result = proxy
+        .call_checked("add", serde_json::json!({"a": 2, "b": 2}))
+        .await
+        .unwrap();
+    assert_eq!(result.text(), "4");
+    let err = proxy
+        .call_checked("ech", serde_json::json!({}))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, mcplug::McplugError::ToolNotFound { ref suggestion, .. } if suggestion.as_deref() == Some("echo"))
+    );
+    assert_eq!(err.to_string(), "Tool 'ech' not found on mock. Did you mean 'echo'?");
+    runtime.close().await.unwrap();
+}
+
 /// I4: Connection reuse
 #[tokio::test]
 async fn connection_reuse_same_server() {
@@ -75,6 +138,18 @@ async fn multi_server_same_runtime() {
             env: std::collections::HashMap::new(),
             headers: std::collections::HashMap::new(),
             lifecycle: None,
+            tool_pins: std::collections::HashMap::new(),
+            disabled: false,
+            install_timeout: None,
+            log_level: None,
+            sensitive_args: Vec::new(),
+            timeout_ms: None,
+            list_timeout_ms: None,
+            allow_http: None,
+            auth: None,
+            tags: Vec::new(),
+            transport: None,
+            retry: None,
         },
     );
     let runtime = Runtime::with_config(config);
@@ -95,6 +170,105 @@ async fn multi_server_same_runtime() {
     runtime.close().await.unwrap();
 }
 
+/// A slow call to one server doesn't hold up calls to another
+#[tokio::test]
+async fn calls_to_different_servers_run_concurrently() {
+    use std::time::{Duration, Instant};
+    let mut config = common::mock_stdio_config("server-a");
+    let server_b = config.mcp_servers["server-a"].clone();
+    config.mcp_servers.insert("server-b".to_string(), server_b);
+    let runtime = Runtime::with_config(config);
+    runtime.list_tools("server-a").await.unwrap();
+    runtime.list_tools("server-b").await.unwrap();
+
+    let slow = runtime.call_tool("server-a", "slow", serde_json::json!({"delay_ms": 2000}));
+    let fast = async {
+        let start = Instant::now();
+        let result = runtime
+            .call_tool("server-b", "add", serde_json::json!({"a": 1, "b": 2}))
+            .await
+            .unwrap();
+        (result, start.elapsed())
+    };
+    let (slow, (fast, elapsed)) = tokio::join!(slow, fast);
+    assert_eq!(slow.unwrap().text(), "done");
+    assert_eq!(fast.text().trim(), "3");
+    assert!(elapsed < Duration::from_millis(1500), "fast call waited {elapsed:?}");
+    runtime.close().await.unwrap();
+}
+
+/// connect_all initializes every server up front and reports each outcome
+#[tokio::test]
+async fn connect_all_reports_each_server() {
+    let mut config = common::mock_stdio_config("mock");
+    let mut broken = config.mcp_servers["mock"].clone();
+    broken.command = Some("/nonexistent/mcp-server".into());
+    config.mcp_servers.insert("broken".to_string(), broken);
+    let runtime = Runtime::with_config(config);
+
+    let mut results = runtime.connect_all().await;
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(results[0].0, "broken");
+    assert!(results[0].1.is_err());
+    assert_eq!(results[1].0, "mock");
+    assert!(results[1].1.is_ok());
+
+    let result = runtime
+        .call_tool("mock", "add", serde_json::json!({"a": 1, "b": 1}))
+        .await
+        .unwrap();
+    assert_eq!(result.text().trim(), "2");
+    runtime.close().await.unwrap();
+}
+
+/// close_server and reconnect recycle one server's connection
+#[tokio::test]
+async fn close_server_and_reconnect_start_fresh_sessions() {
+    let config = common::mock_stdio_config("mock");
+    let runtime = Runtime::with_config(config);
+    async fn count(runtime: &Runtime) -> String {
+        let result = runtime.call_tool("mock", "counter", serde_json::json!({})).await;
+        result.unwrap().text()
+    }
+    assert_eq!(count(&runtime).await, "1");
+    assert_eq!(count(&runtime).await, "2");
+
+    // A new server process starts counting again
+    let info = runtime.reconnect("mock").await.unwrap();
+    assert_eq!(info.name, "mock-server");
+    assert_eq!(count(&runtime).await, "1");
+
+    runtime.close_server("mock").await.unwrap();
+    assert_eq!(count(&runtime).await, "1");
+
+    runtime.close_server("never-connected").await.unwrap();
+    assert!(matches!(
+        runtime.reconnect("missing").await,
+        Err(mcplug::McplugError::ServerNotFound(_))
+    ));
+    runtime.close().await.unwrap();
+}
+
+/// list_all_tools gathers (server, tool) pairs and skips unreachable servers
+#[tokio::test]
+async fn list_all_tools_across_servers() {
+    let mut config = common::mock_stdio_config("server-a");
+    let mut server = config.mcp_servers["server-a"].clone();
+    config.mcp_servers.insert("server-b".to_string(), server.clone());
+    server.command = Some("/nonexistent/mcp-server".into());
+    config.mcp_servers.insert("broken".to_string(), server);
+    let runtime = Runtime::with_config(config);
+
+    let tools = runtime.list_all_tools().await;
+    let per_server = |name: &str| tools.iter().filter(|(s, _)| s == name).count();
+    assert!(per_server("server-a") >= 5);
+    assert_eq!(per_server("server-a"), per_server("server-b"));
+    assert_eq!(per_server("broken"), 0);
+    assert!(tools.windows(2).all(|w| w[0].0 <= w[1].0));
+    assert!(tools.iter().any(|(s, t)| s == "server-b" && t.name == "echo"));
+    runtime.close().await.unwrap();
+}
+
 /// I6: Call timeout enforcement
 #[tokio::test]
 async fn call_timeout_enforcement() {
@@ -109,3 +283,890 @@ async fn call_timeout_enforcement() {
     assert!(result.is_err(), "Should have timed out");
     runtime.close().await.unwrap();
 }
+
+/// cancel_pending sends notifications/cancelled for a call whose caller gave up
+#[tokio::test]
+async fn cancel_pending_notifies_server_of_abandoned_call() {
+    use mcplug::McpTransport;
+    use std::time::Duration;
+
+    let dir = tempfile::tempdir().unwrap();
+    let cancel_file = dir.path().join("cancelled");
+    let env = std::collections::HashMap::from([(
+        "MOCK_CANCEL_FILE".to_string(),
+        cancel_file.to_string_lossy().into_owned(),
+    )]);
+    let mut transport = mcplug::StdioTransport::new(
+        &common::mock_server_bin_path().to_string_lossy(),
+        &[],
+        &env,
+        None,
+        "mock",
+    )
+    .unwrap();
+    transport.initialize().await.unwrap();
+
+    let abandoned = tokio::time::timeout(
+        Duration::from_millis(50),
+        transport.call_tool("slow", serde_json::json!({"delay_ms": 300})),
+    )
+    .await;
+    assert!(abandoned.is_err());
+    transport.cancel_pending("caller gave up").await.unwrap();
+
+    // The mock reads the notification once the slow call has finished
+    let mut params = None;
+    for _ in 0..50 {
+        if let Ok(text) = std::fs::read_to_string(&cancel_file) {
+            params = Some(text);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let params: serde_json::Value =
+        serde_json::from_str(&params.expect("server never saw the cancellation")).unwrap();
+    // Request 1 was initialize, which is never cancelled
+    assert_eq!(params["requestId"], 2);
+    assert_eq!(params["reason"], "caller gave up");
+
+    // Nothing is left to cancel, and the transport still works
+    transport.cancel_pending("again").await.unwrap();
+    let result = transport
+        .call_tool("add", serde_json::json!({"a": 1, "b": 2}))
+        .await
+        .unwrap();
+    assert_eq!(result.text(), "3");
+    transport.close().await.unwrap();
+}
+
+/// A configured timeoutMs bounds Runtime calls
+#[tokio::test]
+async fn configured_timeout_bounds_runtime_calls() {
+    let mut config = common::mock_stdio_config("mock");
+    config.mcp_servers.get_mut("mock").unwrap().timeout_ms = Some(200);
+    let runtime = Runtime::with_config(config);
+    let err = runtime
+        .call_tool("mock", "slow", serde_json::json!({"delay_ms": 5000}))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), "timeout");
+    assert!(err.to_string().contains("slow"));
+    runtime.close().await.unwrap();
+}
+
+/// Initialize falls back to an older protocol version when the server rejects the newest one
+#[tokio::test]
+async fn initialize_falls_back_to_older_protocol_version() {
+    use mcplug::McpTransport;
+    use wiremock::matchers::{body_partial_json, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "initialize",
+            "params": { "protocolVersion": "2025-03-26" }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": -32602, "message": "Unsupported protocol version" }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "initialize",
+            "params": { "protocolVersion": "2024-11-05" }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "result": {
+                "protocolVersion": "2024-11-05",
+                "serverInfo": { "name": "legacy", "version": "0.9.0" },
+                "capabilities": { "tools": {} }
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "notifications/initialized"
+        })))
+        .respond_with(ResponseTemplate::new(202))
+        .mount(&server)
+        .await;
+
+    let mut transport = mcplug::HttpSseTransport::new(
+        &server.uri(),
+        &std::collections::HashMap::new(),
+        "legacy",
+        true,
+    )
+    .unwrap();
+    let info = transport.initialize().await.unwrap();
+    assert_eq!(info.name, "legacy");
+    assert_eq!(info.protocol_version.as_deref(), Some("2024-11-05"));
+}
+
+/// A server's bearer auth section is sent as the Authorization header
+#[tokio::test]
+async fn bearer_auth_section_sets_authorization_header() {
+    use wiremock::matchers::{body_partial_json, header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(header("Authorization", "Bearer secret-token"))
+        .and(body_partial_json(serde_json::json!({ "method": "initialize" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "protocolVersion": "2025-03-26",
+                "serverInfo": { "name": "secured", "version": "1.0.0" },
+                "capabilities": { "tools": {} }
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "notifications/initialized"
+        })))
+        .respond_with(ResponseTemplate::new(202))
+        .mount(&server)
+        .await;
+
+    let config: mcplug::McplugConfig = serde_json::from_value(serde_json::json!({
+        "mcpServers": {
+            "bearer-auth-test": {
+                "baseUrl": server.uri(),
+                "allowHttp": true,
+                "auth": {"type": "bearer", "token": "secret-token"}
+            }
+        }
+    }))
+    .unwrap();
+    let runtime = Runtime::with_config(config);
+    let info = runtime.server_info("bearer-auth-test").await.unwrap();
+    assert_eq!(info.name, "secured");
+    runtime.close().await.unwrap();
+}
+
+/// A persisted HTTP session is resumed with a ping instead of re-initializing
+#[tokio::test]
+async fn http_session_resumed_from_state_file() {
+    use mcplug::McpTransport;
+    use wiremock::matchers::{body_partial_json, header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({ "method": "initialize" })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Mcp-Session-Id", "sess-1")
+                .set_body_json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": {
+                        "protocolVersion": "2025-03-26",
+                        "serverInfo": { "name": "remote", "version": "2.0.0" },
+                        "capabilities": { "tools": {} }
+                    }
+                })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "notifications/initialized"
+        })))
+        .respond_with(ResponseTemplate::new(202))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(header("Mcp-Session-Id", "sess-1"))
+        .and(body_partial_json(serde_json::json!({ "method": "ping" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {}
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let session_file = dir.path().join("session.json");
+    let connect = || {
+        mcplug::HttpSseTransport::new(
+            &server.uri(),
+            &std::collections::HashMap::new(),
+            "remote",
+            true,
+        )
+        .unwrap()
+        .with_session_file(session_file.clone())
+    };
+
+    // First connection initializes and persists the session
+    let info = connect().initialize().await.unwrap();
+    assert_eq!(info.version, "2.0.0");
+    assert!(session_file.exists());
+
+    // Second connection resumes it without calling initialize again
+    let info = connect().initialize().await.unwrap();
+    assert_eq!(info.name, "remote");
+    assert_eq!(info.protocol_version.as_deref(), Some("2025-03-26"));
+}
+
+/// A persisted session the server no longer knows is discarded and re-initialized
+#[tokio::test]
+async fn http_stale_session_reinitializes() {
+    use mcplug::McpTransport;
+    use wiremock::matchers::{body_partial_json, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({ "method": "ping" })))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({ "method": "initialize" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "result": {
+                "protocolVersion": "2025-03-26",
+                "serverInfo": { "name": "remote", "version": "3.0.0" },
+                "capabilities": {}
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "notifications/initialized"
+        })))
+        .respond_with(ResponseTemplate::new(202))
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let session_file = dir.path().join("session.json");
+    std::fs::write(
+        &session_file,
+        serde_json::json!({
+            "sessionId": "expired",
+            "baseUrl": server.uri(),
+            "serverInfo": { "name": "remote", "version": "1.0.0", "capabilities": {} },
+            "savedAt": "2026-01-01T00:00:00Z"
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let mut transport = mcplug::HttpSseTransport::new(
+        &server.uri(),
+        &std::collections::HashMap::new(),
+        "remote",
+        true,
+    )
+    .unwrap()
+    .with_session_file(session_file.clone());
+    let info = transport.initialize().await.unwrap();
+    assert_eq!(info.version, "3.0.0");
+    // No session ID was issued, so the stale file is not rewritten
+    assert!(!session_file.exists());
+}
+
+/// Runtime refuses to call a tool whose pinned schema hash no longer matches
+#[tokio::test]
+async fn call_refused_on_schema_pin_mismatch() {
+    let mut config = common::mock_stdio_config("mock");
+    config
+        .mcp_servers
+        .get_mut("mock")
+        .unwrap()
+        .tool_pins
+        .insert("echo".into(), "sha256:stale".into());
+    let runtime = Runtime::with_config(config);
+    let err = runtime
+        .call_tool("mock", "echo", serde_json::json!({"input": "hi"}))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), "pin_mismatch");
+
+    // Unpinned tools on the same server are unaffected
+    let result = runtime
+        .call_tool("mock", "add", serde_json::json!({"a": 1, "b": 2}))
+        .await
+        .unwrap();
+    assert_eq!(result.text(), "3");
+    runtime.close().await.unwrap();
+}
+
+/// Applying a new config adds servers, drops removed ones, and keeps
+/// untouched connections working
+#[tokio::test]
+async fn apply_config_without_restart() {
+    let config = common::mock_stdio_config("mock");
+    let mut runtime = Runtime::with_config(config.clone());
+    let result = runtime
+        .call_tool("mock", "add", serde_json::json!({"a": 1, "b": 2}))
+        .await
+        .unwrap();
+    assert_eq!(result.text(), "3");
+
+    let mut updated = config.clone();
+    let mut added = updated.mcp_servers["mock"].clone();
+    added.description = Some("second".into());
+    updated.mcp_servers.insert("other".into(), added);
+    let changes = runtime.apply_config(updated.clone()).await;
+    assert_eq!(changes.added, vec!["other"]);
+    assert!(changes.changed.is_empty() && changes.removed.is_empty());
+    assert_eq!(runtime.server_names(), vec!["mock", "other"]);
+    let result = runtime
+        .call_tool("other", "echo", serde_json::json!({"input": "hi"}))
+        .await
+        .unwrap();
+    assert_eq!(result.text(), "hi");
+
+    // Editing a server reconnects it with the new settings
+    updated.mcp_servers.remove("other");
+    updated.mcp_servers.get_mut("mock").unwrap().timeout_ms = Some(5000);
+    let changes = runtime.apply_config(updated).await;
+    assert_eq!(changes.removed, vec!["other"]);
+    assert_eq!(changes.changed, vec!["mock"]);
+    assert!(runtime.list_tools("other").await.is_err());
+    let result = runtime
+        .call_tool("mock", "add", serde_json::json!({"a": 2, "b": 2}))
+        .await
+        .unwrap();
+    assert_eq!(result.text(), "4");
+    runtime.close().await.unwrap();
+}
+
+/// A 401 with a WWW-Authenticate challenge surfaces as AuthRequired
+#[tokio::test]
+async fn http_auth_challenge_is_auth_required() {
+    use mcplug::McpTransport;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(401)
+                .insert_header("WWW-Authenticate", r#"Bearer realm="mcp""#),
+        )
+        .mount(&server)
+        .await;
+
+    let mut transport = mcplug::HttpSseTransport::new(
+        &server.uri(),
+        &std::collections::HashMap::new(),
+        "locked",
+        true,
+    )
+    .unwrap();
+    let err = transport.initialize().await.unwrap_err();
+    assert!(matches!(err, mcplug::McplugError::AuthRequired(ref s) if s == "locked"));
+}
+
+/// A 401 without a challenge stays a plain connection failure
+#[tokio::test]
+async fn http_401_without_challenge_is_connection_failure() {
+    use mcplug::McpTransport;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+
+    let mut transport = mcplug::HttpSseTransport::new(
+        &server.uri(),
+        &std::collections::HashMap::new(),
+        "locked",
+        true,
+    )
+    .unwrap();
+    let err = transport.initialize().await.unwrap_err();
+    assert!(matches!(err, mcplug::McplugError::ConnectionFailed { .. }));
+}
+
+/// A token cached by `mcplug auth` is sent as the bearer token
+#[tokio::test]
+async fn http_sends_cached_oauth_token() {
+    use mcplug::McpTransport;
+    use wiremock::matchers::{body_partial_json, header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server_name = "test-http-cached-oauth-token";
+    let server = MockServer::start().await;
+    mcplug::oauth::save_token(
+        server_name,
+        &mcplug::oauth::TokenData {
+            access_token: "cached-access".into(),
+            refresh_token: None,
+            expires_at: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
+            token_type: "Bearer".into(),
+            scope: None,
+            resource: None,
+            token_endpoint: None,
+        }
+        .issued_for(&server.uri(), &format!("{}/token", server.uri())),
+    )
+    .unwrap();
+
+    Mock::given(method("POST"))
+        .and(header("Authorization", "Bearer cached-access"))
+        .and(body_partial_json(serde_json::json!({ "method": "initialize" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "protocolVersion": "2025-03-26",
+                "serverInfo": { "name": "secured", "version": "1.0.0" },
+                "capabilities": {}
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(header("Authorization", "Bearer cached-access"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "notifications/initialized"
+        })))
+        .respond_with(ResponseTemplate::new(202))
+        .mount(&server)
+        .await;
+
+    let mut transport = mcplug::HttpSseTransport::new(
+        &server.uri(),
+        &std::collections::HashMap::new(),
+        server_name,
+        true,
+    )
+    .unwrap()
+    .with_oauth(mcplug::oauth::OAuthClient::default());
+    let result = transport.initialize().await;
+
+    let path = mcplug::oauth::cache_path(server_name);
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_dir(path.parent().unwrap());
+    assert_eq!(result.unwrap().name, "secured");
+}
+
+/// list_tools reuses a connection's tool list until refresh_tools or the TTL
+#[tokio::test]
+async fn tool_list_is_cached_per_connection() {
+    use wiremock::matchers::{body_partial_json, method};
+    use wiremock::{Mock, ResponseTemplate};
+
+    let server = common::http_mock::start_mock_http_server().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "notifications/initialized"
+        })))
+        .respond_with(ResponseTemplate::new(202))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({ "method": "tools/list" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "result": {
+                "tools": [{ "name": "ping", "description": "", "inputSchema": {} }]
+            }
+        })))
+        .expect(4)
+        .mount(&server)
+        .await;
+    let config: mcplug::McplugConfig = serde_json::from_value(serde_json::json!({
+        "mcpServers": {
+            "cached": { "baseUrl": server.uri(), "allowHttp": true }
+        }
+    }))
+    .unwrap();
+
+    // Default TTL: two lists, one request; refresh_tools makes a second
+    let runtime = Runtime::with_config(config.clone());
+    assert_eq!(runtime.list_tools("cached").await.unwrap()[0].name, "ping");
+    runtime.list_tools("cached").await.unwrap();
+    runtime.refresh_tools("cached").await.unwrap();
+    runtime.close().await.unwrap();
+
+    // A zero TTL lists on every use: two more requests
+    let runtime = Runtime::builder()
+        .config(config)
+        .tool_cache_ttl(std::time::Duration::ZERO)
+        .build()
+        .await
+        .unwrap();
+    runtime.list_tools("cached").await.unwrap();
+    runtime.list_tools("cached").await.unwrap();
+    runtime.close().await.unwrap();
+}
+
+/// Interceptors can rewrite arguments, refuse calls, and observe outcomes
+#[tokio::test]
+async fn interceptors_run_around_calls() {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use mcplug::{CallInterceptor, CallResult, McplugError, ToolCall};
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl CallInterceptor for Recorder {
+        async fn before(&self, call: &mut ToolCall) -> Result<(), McplugError> {
+            if call.tool == "error" {
+                return Err(McplugError::ProtocolError("blocked by policy".into()));
+            }
+            // Double every number passed to add
+            if call.tool == "add" {
+                for value in call.args.as_object_mut().unwrap().values_mut() {
+                    *value = serde_json::json!(value.as_f64().unwrap() * 2.0);
+                }
+            }
+            Ok(())
+        }
+
+        async fn after(&self, call: &ToolCall, result: &CallResult, _elapsed: Duration) {
+            let event = format!("after {} {}", call.tool, result.text());
+            self.events.lock().unwrap().push(event);
+        }
+
+        async fn on_error(&self, call: &ToolCall, error: &McplugError, _elapsed: Duration) {
+            let event = format!("error {} {}", call.tool, error);
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    let recorder = Arc::new(Recorder::default());
+    let mut runtime = Runtime::with_config(common::mock_stdio_config("mock"));
+    runtime.add_interceptor(recorder.clone());
+
+    let result = runtime
+        .call_tool("mock", "add", serde_json::json!({"a": 1, "b": 2}))
+        .await
+        .unwrap();
+    assert_eq!(result.text().trim(), "6");
+    let err = runtime
+        .call_tool("mock", "error", serde_json::json!({}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("blocked by policy"));
+
+    assert_eq!(
+        *recorder.events.lock().unwrap(),
+        [
+            "after add 6",
+            "error error Protocol error: blocked by policy"
+        ]
+    );
+    runtime.close().await.unwrap();
+}
+
+/// A config `type` selects a transport registered on the Runtime
+#[tokio::test]
+async fn registered_transport_serves_custom_type() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use mcplug::{McpTransport, McplugError, ServerConfig, StdioTransport};
+
+    let mut config = common::mock_stdio_config("mock");
+    config.mcp_servers.get_mut("mock").unwrap().transport = Some("counted".into());
+
+    let created = Arc::new(AtomicUsize::new(0));
+    let counter = created.clone();
+    let runtime = Runtime::builder()
+        .config(config)
+        .transport(
+            "counted",
+            move |server: &str, cfg: &ServerConfig| -> Result<Box<dyn McpTransport>, McplugError> {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let command = cfg.command.as_deref().unwrap_or_default();
+                Ok(Box::new(StdioTransport::new(command, &cfg.args, &cfg.env, None, server)?))
+            },
+        )
+        .build()
+        .await
+        .unwrap();
+
+    let result = runtime
+        .call_tool("mock", "add", serde_json::json!({"a": 2, "b": 5}))
+        .await
+        .unwrap();
+    assert_eq!(result.text().trim(), "7");
+    runtime.list_tools("mock").await.unwrap();
+    assert_eq!(created.load(Ordering::SeqCst), 1);
+    runtime.close().await.unwrap();
+}
+
+/// Prebuilt transports stand in for configured servers
+#[tokio::test]
+async fn with_transports_uses_given_transports() {
+    use mcplug::transports::{Exchange, Recording};
+    use mcplug::{McpTransport, ReplayTransport};
+
+    let exchange = |method: &str, params: Option<serde_json::Value>, result: serde_json::Value| Exchange {
+        method: method.into(),
+        params,
+        result: Some(result),
+        error: None,
+    };
+    let recording = Recording {
+        server: "fake".into(),
+        exchanges: vec![
+            exchange("initialize", None, serde_json::json!({"name": "fake", "version": "2.0", "capabilities": {}})),
+            exchange(
+                "tools/call",
+                Some(serde_json::json!({"name": "greet", "arguments": {"who": "ci"}})),
+                serde_json::json!({"content": [{"type": "text", "text": "hello ci"}]}),
+            ),
+        ],
+    };
+    let transport: Box<dyn McpTransport> = Box::new(ReplayTransport::new(recording));
+    let runtime = Runtime::with_transports([("fake".to_string(), transport)]);
+
+    assert_eq!(runtime.server_names(), vec!["fake"]);
+    assert_eq!(runtime.server_info("fake").await.unwrap().version, "2.0");
+    let result = runtime
+        .call_tool("fake", "greet", serde_json::json!({"who": "ci"}))
+        .await
+        .unwrap();
+    assert_eq!(result.text(), "hello ci");
+
+    runtime.close_server("fake").await.unwrap();
+    let err = runtime.server_info("fake").await.unwrap_err();
+    assert!(err.to_string().contains("was closed"), "{err}");
+}
+
+/// health_check initializes a new connection and pings an open one
+#[tokio::test]
+async fn health_check_reports_probe_and_outcome() {
+    use mcplug::HealthProbe;
+
+    let mut config = common::mock_stdio_config("mock");
+    let mut broken = config.mcp_servers["mock"].clone();
+    broken.command = Some("/nonexistent/mcplug-test-server".into());
+    config.mcp_servers.insert("broken".into(), broken);
+    let runtime = Runtime::with_config(config);
+
+    let report = runtime.health_check("mock").await;
+    assert!(report.is_healthy(), "{:?}", report.result);
+    assert_eq!(report.probe, HealthProbe::Initialize);
+    assert_eq!(report.result.as_ref().unwrap().version, "1.0.0");
+
+    let report = runtime.health_check("mock").await;
+    assert!(report.is_healthy());
+    assert_eq!(report.probe, HealthProbe::Ping);
+    assert_eq!(report.to_json()["version"], "1.0.0");
+
+    let report = runtime.health_check("broken").await;
+    assert!(!report.is_healthy());
+    assert_eq!(report.to_json()["code"], "connection_refused");
+    assert!(!runtime.health_check("ghost").await.is_healthy());
+    runtime.close().await.unwrap();
+}
+
+/// Retryable failures are retried per the call's policy; others are not
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn call_tool_with_retry_retries_timeouts() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use mcplug::{
+        CallResult, ContentBlock, McpTransport, McplugError, MockTransport, RetryOn, RetryPolicy,
+    };
+
+    let attempts = Arc::new(AtomicU32::new(0));
+    let seen = attempts.clone();
+    let mock = MockTransport::new("flaky")
+        .respond_with("fetch", move |_| {
+            // Time out twice, then succeed
+            if seen.fetch_add(1, Ordering::SeqCst) < 2 {
+                return Err(McplugError::Timeout {
+                    server: "flaky".into(),
+                    tool: Some("fetch".into()),
+                    duration: Duration::from_millis(10),
+                });
+            }
+            Ok(CallResult {
+                content: vec![ContentBlock::Text { text: "done".into() }],
+                is_error: false,
+                raw_response: None,
+            })
+        })
+        .fail("broken", "bad request");
+    let calls = mock.calls();
+    let transport: Box<dyn McpTransport> = Box::new(mock);
+    let runtime = Runtime::with_transports([("flaky".to_string(), transport)]);
+    // Timeouts are retried only when the policy opts in
+    let result = runtime
+        .call_tool_with_retry("flaky", "fetch", serde_json::json!({}), &RetryPolicy::default())
+        .await;
+    assert!(matches!(result, Err(McplugError::Timeout { .. })));
+    assert_eq!(calls.count("fetch"), 1);
+
+    attempts.store(0, Ordering::SeqCst);
+    let policy = RetryPolicy {
+        backoff: Duration::from_millis(1),
+        retry_on: vec![RetryOn::Timeout],
+        ..RetryPolicy::default()
+    };
+
+    let result = runtime
+        .call_tool_with_retry("flaky", "fetch", serde_json::json!({}), &policy)
+        .await
+        .unwrap();
+    assert_eq!(result.text(), "done");
+    assert_eq!(calls.count("fetch"), 1 + 3);
+
+    attempts.store(0, Ordering::SeqCst);
+    let err = runtime
+        .call_tool_with_retry("flaky", "fetch", serde_json::json!({}), &RetryPolicy::none())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, McplugError::Timeout { .. }));
+
+    runtime
+        .call_tool_with_retry("flaky", "broken", serde_json::json!({}), &policy)
+        .await
+        .unwrap_err();
+    assert_eq!(calls.count("broken"), 1);
+}
+
+/// stats counts each call per server, with errors and latencies
+#[tokio::test]
+async fn stats_track_calls_per_server() {
+    let mut config = common::mock_stdio_config("mock");
+    config.mcp_servers.get_mut("mock").unwrap().timeout_ms = Some(200);
+    let runtime = Runtime::with_config(config);
+    assert!(runtime.stats().servers.is_empty());
+
+    runtime
+        .call_tool("mock", "add", serde_json::json!({"a": 1, "b": 2}))
+        .await
+        .unwrap();
+    let result = runtime
+        .call_tool("mock", "error", serde_json::json!({}))
+        .await
+        .unwrap();
+    assert!(result.is_error);
+    runtime
+        .call_tool("mock", "slow", serde_json::json!({"delay_ms": 5000}))
+        .await
+        .unwrap_err();
+    runtime
+        .call_tool("missing", "add", serde_json::json!({}))
+        .await
+        .unwrap_err();
+
+    let stats = runtime.stats();
+    let mock = stats.server("mock").unwrap();
+    assert_eq!((mock.calls, mock.errors, mock.tool_errors), (3, 1, 1));
+    assert_eq!(mock.latency.count(), 3);
+    assert!(mock.latency.max_ms >= 200);
+    assert_eq!(stats.server("missing").unwrap().errors, 1);
+    assert_eq!(stats.total().calls, 4);
+    runtime.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribe_delivers_server_notifications() {
+    use futures_util::StreamExt;
+
+    let mut config = common::mock_stdio_config("mock");
+    config
+        .mcp_servers
+        .get_mut("mock")
+        .unwrap()
+        .env
+        .insert("MOCK_NOTIFY_ON_CALL".into(), "1".into());
+    let runtime = Runtime::with_config(config);
+    let notifications = runtime.subscribe("mock").await.unwrap();
+    let mut notifications = Box::pin(notifications);
+
+    let result = runtime
+        .call_tool("mock", "echo", serde_json::json!({"input": "hi"}))
+        .await
+        .unwrap();
+    assert_eq!(result.text(), "hi");
+
+    let notification = tokio::time::timeout(std::time::Duration::from_secs(5), notifications.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(notification.method, "notifications/message");
+    assert_eq!(notification.params.unwrap()["data"], "calling \"echo\"");
+
+    // Closing the connection ends the stream
+    runtime.close().await.unwrap();
+    assert!(notifications.next().await.is_none());
+}
+
+/// call_many fans out across servers and keeps results in input order
+#[tokio::test]
+async fn call_many_runs_concurrently_in_order() {
+    use mcplug::ToolCall;
+    use std::time::{Duration, Instant};
+    let mut config = common::mock_stdio_config("server-a");
+    let server_b = config.mcp_servers["server-a"].clone();
+    config.mcp_servers.insert("server-b".to_string(), server_b);
+    let runtime = Runtime::with_config(config);
+    runtime.connect_all().await;
+
+    let call = |server: &str, tool: &str, args| ToolCall {
+        server: server.into(),
+        tool: tool.into(),
+        args,
+    };
+    let start = Instant::now();
+    let results = runtime
+        .call_many(
+            [
+                call("server-a", "slow", serde_json::json!({"delay_ms": 500})),
+                call("server-b", "slow", serde_json::json!({"delay_ms": 500})),
+                call("missing", "add", serde_json::json!({})),
+                call("server-b", "add", serde_json::json!({"a": 1, "b": 2})),
+            ],
+            4,
+        )
+        .await;
+    let elapsed = start.elapsed();
+
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0].as_ref().unwrap().text(), "done");
+    assert_eq!(results[1].as_ref().unwrap().text(), "done");
+    assert!(matches!(results[2], Err(mcplug::McplugError::ServerNotFound(_))));
+    assert_eq!(results[3].as_ref().unwrap().text().trim(), "3");
+    assert!(elapsed < Duration::from_millis(950), "calls ran serially: {elapsed:?}");
+    assert_eq!(runtime.stats().total().calls, 4);
+    runtime.close().await.unwrap();
+}